@@ -3,8 +3,9 @@
 use clap::{Parser, Subcommand};
 
 use crate::commands::{
-    CleanCmd, ConfigCmd, IndexCmd, InitCmd, ListCmd, McpCmd, PruneCmd, RemoveCmd, RetryCmd,
-    SearchCmd, SkipCmd, StatsCmd, StatusCmd, UpdateCmd, WatchCmd,
+    BenchCmd, CatCmd, CleanCmd, ConfigCmd, DiffCmd, IndexCmd, InitCmd, ListCmd, McpCmd, PruneCmd,
+    ReindexCmd, RemoveCmd, RetryCmd, SearchCmd, ServeCmd, SkipCmd, StatsCmd, StatusCmd, UpdateCmd,
+    WatchCmd,
 };
 
 #[derive(Parser)]
@@ -12,6 +13,11 @@ use crate::commands::{
 #[command(about = "Index - semantic code search for your dependencies")]
 #[command(version)]
 pub struct Cli {
+    /// Never use the network: fail instead of contacting registries or a
+    /// remote embeddings API
+    #[arg(long, global = true, env = "IDX_OFFLINE")]
+    pub offline: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -33,6 +39,15 @@ pub enum Command {
     /// Search for code in indexed packages
     Search(SearchCmd),
 
+    /// Print an indexed chunk's code, optionally with surrounding lines
+    Cat(CatCmd),
+
+    /// Compare the symbols of two indexed versions of a package
+    Diff(DiffCmd),
+
+    /// Measure search latency and, for labeled queries, recall and MRR
+    Bench(BenchCmd),
+
     /// List all indexed packages
     List(ListCmd),
 
@@ -42,6 +57,9 @@ pub enum Command {
     /// Show index status vs manifest dependencies
     Status(StatusCmd),
 
+    /// Rebuild indexed packages from scratch (e.g. after changing embedding models)
+    Reindex(ReindexCmd),
+
     /// Remove a package from the index
     Remove(RemoveCmd),
 
@@ -54,12 +72,20 @@ pub enum Command {
     /// Remove packages no longer in manifests
     Prune(PruneCmd),
 
-    /// Delete the entire .index directory
+    /// Delete or reset the index, or clear its registry cache or orphaned vectors
+    ///
+    /// Without flags, deletes the .index directory. `--all` resets it to an
+    /// empty index in place, under the index lock; `--cache` only clears
+    /// cached registry responses; `--vectors-orphaned` drops vector
+    /// namespaces with no chunks (`--dry-run` lists them instead).
     Clean(CleanCmd),
 
     /// Run as MCP server (for AI tools)
     Mcp(McpCmd),
 
+    /// Run a local HTTP/JSON search API
+    Serve(ServeCmd),
+
     /// Manage configuration (API keys, etc.)
     Config(ConfigCmd),
 }
@@ -72,15 +98,20 @@ impl Command {
             Command::Watch(cmd) => cmd.run().await,
             Command::Index(cmd) => cmd.run().await,
             Command::Search(cmd) => cmd.run().await,
+            Command::Cat(cmd) => cmd.run().await,
+            Command::Diff(cmd) => cmd.run().await,
+            Command::Bench(cmd) => cmd.run().await,
             Command::List(cmd) => cmd.run().await,
             Command::Stats(cmd) => cmd.run().await,
             Command::Status(cmd) => cmd.run().await,
+            Command::Reindex(cmd) => cmd.run().await,
             Command::Remove(cmd) => cmd.run().await,
             Command::Skip(cmd) => cmd.run().await,
             Command::Retry(cmd) => cmd.run().await,
             Command::Prune(cmd) => cmd.run().await,
             Command::Clean(cmd) => cmd.run().await,
             Command::Mcp(cmd) => cmd.run().await,
+            Command::Serve(cmd) => cmd.run().await,
             Command::Config(cmd) => cmd.run().await,
         }
     }
@@ -0,0 +1,344 @@
+//! Bench command - measure search latency and result quality.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use super::run_args::ProfileArgs;
+use super::search::FilterArgs;
+use crate::local::models::{BenchQuery, BenchReport, Latency, SearchOptions};
+use crate::local::{self, LocalSearch};
+
+/// Queries run when no file is given: common questions about dependencies,
+/// timed but not labeled, since what answers them depends on the index.
+const BUILTIN_QUERIES: &[&str] = &[
+    "parse json from a string",
+    "send an http request with a timeout",
+    "retry a failed request with backoff",
+    "read a file into a string",
+    "format a date",
+    "connect to a database",
+    "validate user input",
+    "serialize a struct",
+    "stream a response body",
+    "parse command line arguments",
+    "hash a password",
+    "spawn a background task",
+];
+
+/// Results of a missed query shown as what came back instead.
+const MISSED_PREVIEW: usize = 3;
+
+#[derive(Args)]
+pub struct BenchCmd {
+    /// Queries to run (`-` for stdin): one per line, labeled with the
+    /// symbols that answer it as `query => symbol, Parent.symbol` to measure
+    /// recall and MRR, or a JSON array of {query, expected}. Blank lines and
+    /// `#` comments are skipped (default: a built-in set, only timed)
+    #[arg(long, value_name = "PATH")]
+    pub queries: Option<PathBuf>,
+
+    /// Package to search within
+    #[arg(short, long)]
+    pub package: Option<String>,
+
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    /// Results per query; recall counts expected symbols among these
+    #[arg(short = 'k', long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    pub limit: u64,
+
+    /// Print the report as JSON, with each query's outcome
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub profile: ProfileArgs,
+}
+
+impl BenchCmd {
+    pub async fn run(&self) -> Result<()> {
+        let index_dir =
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+
+        let queries = match &self.queries {
+            Some(path) => {
+                let (queries, warnings) = parse_bench_queries(&read_queries(path)?);
+                for warning in &warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+                queries
+            }
+            None => BUILTIN_QUERIES
+                .iter()
+                .map(|query| BenchQuery {
+                    query: query.to_string(),
+                    expected: Vec::new(),
+                })
+                .collect(),
+        };
+        if queries.is_empty() {
+            println!("No queries listed.");
+            return Ok(());
+        }
+
+        let search = LocalSearch::new_with_profile(&index_dir, self.profile.profile())
+            .await?
+            .with_shared(local::open_global_search(&index_dir, self.profile.profile()).await?);
+        // What `idx search` searches with the same flags
+        let options = SearchOptions {
+            package: self.package.as_deref(),
+            filter: self.filters.filter(search.config()),
+            limit: self.limit as usize,
+            ..Default::default()
+        };
+
+        let report = search.bench(&queries, &options).await?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{}", format_report(&report));
+        }
+        Ok(())
+    }
+}
+
+/// Contents of the query file at `path`, or stdin for `-`.
+fn read_queries(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("Failed to read queries from stdin")?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Queries listed in `text`: a JSON array of `{query, expected}` objects,
+/// or one query per line, optionally followed by `=>` and the
+/// comma-separated symbols that answer it. Blank lines and `#` comments are
+/// skipped.
+///
+/// Malformed entries are left out and described in the returned warnings,
+/// naming their line (or array index).
+fn parse_bench_queries(text: &str) -> (Vec<BenchQuery>, Vec<String>) {
+    let mut queries = Vec::new();
+    let mut warnings = Vec::new();
+
+    if text.trim_start().starts_with('[') {
+        match serde_json::from_str::<Vec<serde_json::Value>>(text) {
+            Ok(entries) => {
+                for (i, entry) in entries.into_iter().enumerate() {
+                    match serde_json::from_value::<BenchQuery>(entry) {
+                        Ok(query) if query.query.trim().is_empty() => {
+                            warnings.push(format!("entry {}: empty query", i))
+                        }
+                        Ok(query) => queries.push(query),
+                        Err(e) => warnings.push(format!("entry {}: {}", i, e)),
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("invalid JSON query list: {}", e)),
+        }
+        return (queries, warnings);
+    }
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (query, expected) = match line.split_once("=>") {
+            Some((query, expected)) => (query.trim(), expected),
+            None => (line, ""),
+        };
+        if query.is_empty() {
+            warnings.push(format!("line {}: empty query", i + 1));
+            continue;
+        }
+        queries.push(BenchQuery {
+            query: query.to_string(),
+            expected: expected
+                .split(',')
+                .map(str::trim)
+                .filter(|symbol| !symbol.is_empty())
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+
+    (queries, warnings)
+}
+
+/// Latency percentiles, overall and for embedding and searching apart,
+/// recall and MRR, then the labeled queries whose expected symbols were all
+/// missed.
+fn format_report(report: &BenchReport) -> String {
+    let percentiles =
+        |latency: Latency| format!("p50 {:.1}ms, p95 {:.1}ms", latency.p50_ms, latency.p95_ms);
+    let mut output = format!(
+        "Ran {} queries, top {} results each\n\n",
+        report.queries, report.k
+    );
+    output.push_str(&format!("Latency:    {}\n", percentiles(report.latency)));
+    output.push_str(&format!("  embed:    {}\n", percentiles(report.embedding)));
+    output.push_str(&format!("  search:   {}\n", percentiles(report.search)));
+
+    let (Some(recall), Some(mrr)) = (report.recall_at_k, report.mrr) else {
+        output.push_str(
+            "Recall/MRR: no labeled queries (list them as `query => symbol` with --queries)\n",
+        );
+        return output;
+    };
+    output.push_str(&format!(
+        "{:<12}{:.3} over {} labeled queries\n",
+        format!("Recall@{}:", report.k),
+        recall,
+        report.labeled
+    ));
+    output.push_str(&format!("MRR:        {:.3}\n", mrr));
+
+    let missed: Vec<_> = report
+        .outcomes
+        .iter()
+        .filter(|o| o.is_labeled() && o.rank.is_none())
+        .collect();
+    if !missed.is_empty() {
+        output.push_str(&format!("\nMissed ({}):\n", missed.len()));
+        for outcome in missed {
+            let got: Vec<&str> = outcome
+                .results
+                .iter()
+                .take(MISSED_PREVIEW)
+                .map(String::as_str)
+                .collect();
+            output.push_str(&format!(
+                "  {:?}: expected {}, got {}\n",
+                outcome.query,
+                outcome.expected.join(", "),
+                if got.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    got.join(", ")
+                }
+            ));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::LocalConfig;
+    use crate::local::models::{BenchOutcome, KindFilter, TestFilter};
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        bench: BenchCmd,
+    }
+
+    #[test]
+    fn test_filter_flags_match_search() {
+        let config = LocalConfig::default();
+
+        let filter = Cli::parse_from(["idx"]).bench.filters.filter(&config);
+        assert!(filter.public_only);
+        assert_eq!(
+            (filter.kind, filter.tests),
+            (KindFilter::All, TestFilter::Include)
+        );
+
+        let bench = Cli::parse_from(["idx", "--code-only", "--all-visibility"]).bench;
+        let filter = bench.filters.filter(&config);
+        assert!(!filter.public_only);
+        assert_eq!(
+            (filter.kind, filter.tests),
+            (KindFilter::Code, TestFilter::Exclude)
+        );
+    }
+
+    #[test]
+    fn test_parse_bench_queries() {
+        let (queries, warnings) = parse_bench_queries(
+            "# labeled\n\
+             close the connection => close, Socket.destroy\n\
+             \n\
+             parse json\n\
+             => orphan\n",
+        );
+        assert_eq!(
+            queries,
+            [
+                BenchQuery {
+                    query: "close the connection".to_string(),
+                    expected: vec!["close".to_string(), "Socket.destroy".to_string()],
+                },
+                BenchQuery {
+                    query: "parse json".to_string(),
+                    expected: Vec::new(),
+                },
+            ]
+        );
+        assert_eq!(warnings, ["line 5: empty query"]);
+
+        let (queries, warnings) = parse_bench_queries(
+            r#"[{"query": "retry", "expected": ["retryRequest"]}, {"query": "timeout"}, {"expected": []}]"#,
+        );
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].expected, ["retryRequest"]);
+        assert!(queries[1].expected.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("entry 2: missing field `query`"));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let outcome = |query: &str, expected: &[&str], rank, results: &[&str]| BenchOutcome {
+            query: query.to_string(),
+            latency_ms: 12.0,
+            embed_ms: 9.0,
+            search_ms: 3.0,
+            results: results.iter().map(|r| r.to_string()).collect(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+            rank,
+            found: rank.map_or(0, |_| 1),
+        };
+        let report = BenchReport::new(
+            5,
+            vec![
+                outcome("close", &["close"], Some(1), &["close"]),
+                outcome(
+                    "encode",
+                    &["encode"],
+                    None,
+                    &["decode", "parse", "load", "dump"],
+                ),
+            ],
+        );
+        assert_eq!(
+            format_report(&report),
+            "Ran 2 queries, top 5 results each\n\
+             \n\
+             Latency:    p50 12.0ms, p95 12.0ms\n  \
+             embed:    p50 9.0ms, p95 9.0ms\n  \
+             search:   p50 3.0ms, p95 3.0ms\n\
+             Recall@5:   0.500 over 2 labeled queries\n\
+             MRR:        0.500\n\
+             \n\
+             Missed (1):\n  \
+             \"encode\": expected encode, got decode, parse, load\n"
+        );
+
+        let timed = BenchReport::new(5, vec![outcome("close", &[], None, &[])]);
+        assert!(format_report(&timed).contains("Recall/MRR: no labeled queries"));
+    }
+}
@@ -0,0 +1,48 @@
+//! Cat command - print the code of an indexed chunk.
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::local::{self, LocalSearch};
+
+#[derive(Args)]
+pub struct CatCmd {
+    /// Chunk id (the `id` of a result from `idx search --json`)
+    pub chunk_id: String,
+
+    /// Also print this many lines before and after the chunk from its
+    /// source file (needs `store_source_files = true` at index time)
+    #[arg(long, value_name = "LINES")]
+    pub with_context: Option<usize>,
+}
+
+impl CatCmd {
+    pub async fn run(&self) -> Result<()> {
+        let index_dir =
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+
+        let search = LocalSearch::new(&index_dir)
+            .await?
+            .with_shared(local::open_global_search(&index_dir, None).await?);
+
+        if let Some(lines) = self.with_context {
+            let context = search.get_context(&self.chunk_id, lines).await?;
+            println!("{}:{}\n", context.file_path, context.start_line);
+            print!("{}{}{}", context.before, context.code, context.after);
+            if !context.after.ends_with('\n') {
+                println!();
+            }
+            return Ok(());
+        }
+
+        let chunk = search
+            .get_chunk(&self.chunk_id)
+            .await?
+            .with_context(|| format!("Chunk not found: {}", self.chunk_id))?;
+        let code = search.get_code(&chunk.storage_key).await?;
+
+        println!("{}:{}\n", chunk.file_path, chunk.start_line);
+        println!("{}", code);
+        Ok(())
+    }
+}
@@ -1,15 +1,37 @@
-//! Clean command - remove .index directory.
+//! Clean command - remove .index directory (or reset it to an empty index,
+//! or clear just its registry cache or orphaned vectors).
 
-use anyhow::Result;
+use std::path::Path;
+
+use anyhow::{Result, bail};
 use clap::Args;
 
-use crate::local;
+use crate::local::models::NamespaceSpec;
+use crate::local::{self, IndexLock, LocalConfig, LocalIndexer, VectorBackendKind};
+use crate::registry::RegistryCache;
 
 #[derive(Args)]
 pub struct CleanCmd {
     /// Skip confirmation prompt
     #[arg(long, short = 'y')]
     pub yes: bool,
+
+    /// Only clear cached registry responses, keeping the index
+    #[arg(long)]
+    pub cache: bool,
+
+    /// Reset the index to an empty one in place: database, blobs and vectors
+    /// are replaced together, even if the old ones are corrupt (requires --yes)
+    #[arg(long, conflicts_with_all = ["cache", "vectors_orphaned"])]
+    pub all: bool,
+
+    /// Only drop vector namespaces that have no chunks in the database
+    #[arg(long, conflicts_with = "cache")]
+    pub vectors_orphaned: bool,
+
+    /// With --vectors-orphaned, only show what would be dropped
+    #[arg(long, requires = "vectors_orphaned")]
+    pub dry_run: bool,
 }
 
 impl CleanCmd {
@@ -22,18 +44,36 @@ impl CleanCmd {
             }
         };
 
-        if !self.yes {
-            println!("This will delete: {}", index_dir.display());
-            print!("Continue? [y/N] ");
-            std::io::Write::flush(&mut std::io::stdout())?;
+        if self.cache {
+            let cache_dir = index_dir.join(local::CACHE_DIR_NAME);
+            RegistryCache::new(&cache_dir).clear()?;
+            println!("Cleared {}", cache_dir.display());
+            return Ok(());
+        }
 
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
+        if self.vectors_orphaned {
+            return self.clean_orphaned_vectors(&index_dir).await;
+        }
 
-            if !input.trim().eq_ignore_ascii_case("y") {
-                println!("Aborted.");
-                return Ok(());
+        if self.all {
+            if !self.yes {
+                bail!(
+                    "`idx clean --all` deletes every indexed package in {}; pass --yes to confirm.",
+                    index_dir.display()
+                );
+            }
+            local::reset_index(&index_dir).await?;
+            println!("Reset {} to an empty index.", index_dir.display());
+            // The config may be unreadable too; the reset doesn't need it
+            if LocalConfig::load().is_ok_and(|c| c.vector_backend != VectorBackendKind::Lance) {
+                println!("Vectors stored in Turbopuffer were left in place.");
             }
+            return Ok(());
+        }
+
+        if !self.yes && !confirm(&format!("This will delete: {}", index_dir.display()))? {
+            println!("Aborted.");
+            return Ok(());
         }
 
         std::fs::remove_dir_all(&index_dir)?;
@@ -41,4 +81,81 @@ impl CleanCmd {
 
         Ok(())
     }
+
+    /// Drop vector namespaces without chunk rows and report chunk namespaces
+    /// without vectors.
+    async fn clean_orphaned_vectors(&self, index_dir: &Path) -> Result<()> {
+        // Turbopuffer namespaces may belong to other projects sharing the prefix
+        if LocalConfig::load()?.vector_backend != VectorBackendKind::Lance {
+            bail!("--vectors-orphaned only cleans the local LanceDB vector store.");
+        }
+
+        // Keep indexing out while the two stores are compared
+        let _lock = IndexLock::exclusive(index_dir)?;
+        let indexer = LocalIndexer::new(index_dir).await?;
+        let audit = indexer.audit_namespaces().await?;
+
+        if !audit.missing_vectors.is_empty() {
+            println!(
+                "{} indexed versions have no vectors:",
+                audit.missing_vectors.len()
+            );
+            for ns in &audit.missing_vectors {
+                println!("  {}", package_spec(ns));
+            }
+            println!("Run `idx retry <package>` and `idx update` to re-embed them.\n");
+        }
+
+        if audit.orphaned_vectors.is_empty() {
+            println!("No orphaned vector namespaces.");
+            return Ok(());
+        }
+
+        println!(
+            "{} vector namespaces have no chunks:",
+            audit.orphaned_vectors.len()
+        );
+        for ns in &audit.orphaned_vectors {
+            println!("  {}", ns);
+        }
+
+        if self.dry_run {
+            println!("\nDry run - nothing dropped.");
+            return Ok(());
+        }
+
+        if !self.yes && !confirm("\nDrop them?")? {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        for ns in &audit.orphaned_vectors {
+            indexer.vectors().delete_namespace(ns).await?;
+        }
+        println!(
+            "Dropped {} vector namespaces.",
+            audit.orphaned_vectors.len()
+        );
+
+        Ok(())
+    }
+}
+
+/// Ask a yes/no question on stdin, defaulting to no.
+pub(super) fn confirm(prompt: &str) -> Result<bool> {
+    println!("{}", prompt);
+    print!("Continue? [y/N] ");
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Turn a `registry/name/version` namespace into a `registry:name@version` spec.
+fn package_spec(namespace: &str) -> String {
+    namespace
+        .parse::<NamespaceSpec>()
+        .map_or_else(|_| namespace.to_string(), |ns| ns.package_spec())
 }
@@ -1,9 +1,14 @@
 //! Config command - manage local configuration.
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::{Args, Subcommand};
 
-use crate::local::LocalConfig;
+use crate::local::models::DistanceMetric;
+use crate::local::{
+    BUILTIN_TRANSFORMERS, DEFAULT_PROFILE, EnvSecret, LicenseAction, LocalConfig, VectorBackendKind,
+};
 
 #[derive(Args)]
 pub struct ConfigCmd {
@@ -22,6 +27,66 @@ pub enum ConfigSubCmd {
     /// Set the embedding model (default: text-embedding-3-small)
     SetModel(SetModelCmd),
 
+    /// Set how many texts and estimated tokens go in one embeddings request
+    SetEmbeddingBatch(SetEmbeddingBatchCmd),
+
+    /// Also embed documentation on its own for natural-language queries (more embedding cost)
+    SetDocEmbeddings(SetDocEmbeddingsCmd),
+
+    /// Experimental: repeat each symbol's name in its embedding text (0 = off)
+    SetEmbeddingNameWeight(SetEmbeddingNameWeightCmd),
+
+    /// Set the USD price per 1000 embedded tokens used for cost estimates
+    SetEmbeddingPrice(SetEmbeddingPriceCmd),
+
+    /// Store whole source files for context retrieval (increases storage)
+    SetStoreFiles(SetStoreFilesCmd),
+
+    /// Set the zstd level code blobs are compressed with (0 = uncompressed)
+    SetBlobCompression(SetBlobCompressionCmd),
+
+    /// Index package test files as usage examples
+    SetIndexTests(SetIndexTestsCmd),
+
+    /// Index third-party code vendored inside packages (e.g. pip/_vendor)
+    SetIndexVendored(SetIndexVendoredCmd),
+
+    /// Include npm peerDependencies / optionalDependencies when scanning manifests
+    SetNpmDeps(SetNpmDepsCmd),
+
+    /// Set the minimum search score to report (0 = return every hit)
+    SetMinScore(SetMinScoreCmd),
+
+    /// Whether searches return only public API by default (--all-visibility overrides)
+    SetPublicOnly(SetPublicOnlyCmd),
+
+    /// Set how much of each chunk's code search results preview (default: 500)
+    SetSnippetLength(SetSnippetLengthCmd),
+
+    /// Set SPDX licenses of packages not to index (none clears the denylist)
+    SetLicenseDenylist(SetLicenseDenylistCmd),
+
+    /// Set the chunk transformers run before embedding (none clears them)
+    SetTransformers(SetTransformersCmd),
+
+    /// Set per-package indexing caps and versions kept (0 = unlimited)
+    SetLimits(SetLimitsCmd),
+
+    /// Set the number of pooled SQLite read connections (default: 4)
+    SetDbReaders(SetDbReadersCmd),
+
+    /// Set the HTTP proxy and extra trusted CAs (none clears a setting)
+    SetProxy(SetProxyCmd),
+
+    /// Choose where embeddings are stored (lance or turbopuffer)
+    SetBackend(SetBackendCmd),
+
+    /// Add or replace a named embedding profile
+    AddProfile(AddProfileCmd),
+
+    /// Switch the active embedding profile (`default` for the top-level settings)
+    UseProfile(UseProfileCmd),
+
     /// Show current configuration
     Show,
 }
@@ -44,6 +109,200 @@ pub struct SetModelCmd {
     pub model: String,
 }
 
+#[derive(Args)]
+pub struct SetEmbeddingBatchCmd {
+    /// Texts per request (default: 100)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub size: Option<u64>,
+
+    /// Estimated tokens per request; larger texts are split (default: 200000)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct SetEmbeddingPriceCmd {
+    /// USD per 1000 tokens (e.g. 0.00013 for text-embedding-3-large)
+    pub price: f64,
+}
+
+#[derive(Args)]
+pub struct SetStoreFilesCmd {
+    /// Whether to store source files (true or false)
+    #[arg(action = clap::ArgAction::Set)]
+    pub enabled: bool,
+}
+
+#[derive(Args)]
+pub struct SetDocEmbeddingsCmd {
+    /// Whether to embed documentation separately (true or false)
+    #[arg(action = clap::ArgAction::Set)]
+    pub enabled: bool,
+}
+
+#[derive(Args)]
+pub struct SetBlobCompressionCmd {
+    /// zstd level, 1 (fastest) to 22 (smallest), or 0 to store uncompressed (default: 3)
+    #[arg(value_parser = clap::value_parser!(i32).range(0..=22))]
+    pub level: i32,
+}
+
+#[derive(Args)]
+pub struct SetEmbeddingNameWeightCmd {
+    /// Times the name leads the embedded text (default: 0, off)
+    #[arg(value_parser = clap::value_parser!(u64).range(0..=8))]
+    pub weight: u64,
+}
+
+#[derive(Args)]
+pub struct SetIndexTestsCmd {
+    /// Whether to index test files (true or false)
+    #[arg(action = clap::ArgAction::Set)]
+    pub enabled: bool,
+}
+
+#[derive(Args)]
+pub struct SetIndexVendoredCmd {
+    /// Whether to index vendored files (true or false)
+    #[arg(action = clap::ArgAction::Set)]
+    pub enabled: bool,
+}
+
+#[derive(Args)]
+pub struct SetNpmDepsCmd {
+    /// Include peerDependencies (true or false)
+    #[arg(long, action = clap::ArgAction::Set)]
+    pub peer: Option<bool>,
+
+    /// Include optionalDependencies (true or false)
+    #[arg(long, action = clap::ArgAction::Set)]
+    pub optional: Option<bool>,
+}
+
+#[derive(Args)]
+pub struct SetMinScoreCmd {
+    /// Score between 0 and 1 (e.g. 0.45)
+    pub score: f32,
+}
+
+#[derive(Args)]
+pub struct SetPublicOnlyCmd {
+    /// Whether to leave out internal and private code (true or false)
+    #[arg(action = clap::ArgAction::Set)]
+    pub enabled: bool,
+}
+
+#[derive(Args)]
+pub struct SetSnippetLengthCmd {
+    /// Bytes of code per snippet, cut at a line boundary
+    #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+    pub length: u64,
+}
+
+#[derive(Args)]
+pub struct SetTransformersCmd {
+    /// Transformer names, run in order (available: strip-license-headers)
+    pub names: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct SetLicenseDenylistCmd {
+    /// SPDX license ids; a trailing `*` matches a prefix (e.g. GPL-3.0 AGPL-*)
+    pub licenses: Vec<String>,
+
+    /// Skip denylisted packages or index them with a warning
+    #[arg(long, value_enum, default_value = "skip")]
+    pub action: LicenseAction,
+}
+
+#[derive(Args)]
+pub struct SetLimitsCmd {
+    /// Maximum source files parsed per package
+    #[arg(long)]
+    pub max_files: Option<usize>,
+
+    /// Maximum chunks embedded per package
+    #[arg(long)]
+    pub max_chunks: Option<usize>,
+
+    /// Newest versions of each package kept by `idx update`
+    #[arg(long)]
+    pub max_versions: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct SetDbReadersCmd {
+    /// Number of read connections
+    #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+    pub connections: u32,
+}
+
+#[derive(Args)]
+pub struct SetProxyCmd {
+    /// Proxy URL (e.g. http://proxy.corp:8080); none uses HTTP(S)_PROXY
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Comma-separated hosts reached without the proxy; none uses NO_PROXY
+    #[arg(long)]
+    pub no_proxy: Option<String>,
+
+    /// PEM file of extra root certificates to trust
+    #[arg(long)]
+    pub ca_bundle: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SetBackendCmd {
+    /// Vector backend
+    #[arg(value_enum)]
+    pub backend: VectorBackendKind,
+
+    /// Turbopuffer API key
+    #[arg(long)]
+    pub api_key: Option<String>,
+
+    /// Turbopuffer API base URL (e.g., https://gcp-us-central1.turbopuffer.com)
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Prefix for Turbopuffer namespace names
+    #[arg(long)]
+    pub prefix: Option<String>,
+
+    /// How embedding distances are measured (Turbopuffer supports l2 and cosine)
+    #[arg(long, value_enum)]
+    pub metric: Option<DistanceMetric>,
+}
+
+#[derive(Args)]
+pub struct AddProfileCmd {
+    /// Profile name
+    pub name: String,
+
+    /// Embedding model (default: current setting)
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// API base URL (default: current setting)
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// API key (default: current setting)
+    #[arg(long)]
+    pub key: Option<String>,
+
+    /// Dimension of the model's embeddings (default: current setting)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub dimensions: Option<u32>,
+}
+
+#[derive(Args)]
+pub struct UseProfileCmd {
+    /// Profile name
+    pub name: String,
+}
+
 impl ConfigCmd {
     pub async fn run(&self) -> Result<()> {
         match &self.command {
@@ -52,35 +311,399 @@ impl ConfigCmd {
                 config.set_openai_key(cmd.key.clone());
                 config.save()?;
                 println!("API key saved.");
+                note_active_profile(&config);
             }
             ConfigSubCmd::SetUrl(cmd) => {
                 let mut config = LocalConfig::load()?;
                 config.openai_base_url = cmd.url.clone();
                 config.save()?;
                 println!("Base URL set to: {}", cmd.url);
+                note_active_profile(&config);
             }
             ConfigSubCmd::SetModel(cmd) => {
                 let mut config = LocalConfig::load()?;
                 config.embedding_model = cmd.model.clone();
                 config.save()?;
                 println!("Embedding model set to: {}", cmd.model);
+                note_active_profile(&config);
+            }
+            ConfigSubCmd::SetEmbeddingBatch(cmd) => {
+                let mut config = LocalConfig::load()?;
+                if let Some(n) = cmd.size {
+                    config.embedding_batch_size = n as usize;
+                }
+                if let Some(n) = cmd.max_tokens {
+                    config.embedding_batch_tokens = n as usize;
+                }
+                config.save()?;
+                println!(
+                    "Embedding batches set to: {} texts, {} estimated tokens",
+                    config.embedding_batch_size, config.embedding_batch_tokens
+                );
+            }
+            ConfigSubCmd::SetEmbeddingPrice(cmd) => {
+                if cmd.price < 0.0 || !cmd.price.is_finite() {
+                    anyhow::bail!("Price must be zero or more");
+                }
+                let mut config = LocalConfig::load()?;
+                config.embedding_price_per_1k_tokens = cmd.price;
+                config.save()?;
+                println!("Embedding price set to: ${} per 1K tokens", cmd.price);
+            }
+            ConfigSubCmd::SetStoreFiles(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.store_source_files = cmd.enabled;
+                config.save()?;
+                println!("Store source files set to: {}", cmd.enabled);
+            }
+            ConfigSubCmd::SetDocEmbeddings(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.doc_embeddings = cmd.enabled;
+                config.save()?;
+                println!("Documentation embeddings set to: {}", cmd.enabled);
+                println!("Already indexed packages must be re-indexed to pick this up.");
+            }
+            ConfigSubCmd::SetBlobCompression(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.blob_compression_level = cmd.level;
+                config.save()?;
+                println!("Blob compression level set to: {}", cmd.level);
+            }
+            ConfigSubCmd::SetEmbeddingNameWeight(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.embedding_name_weight = cmd.weight as usize;
+                config.save()?;
+                println!("Embedding name weight set to: {}", cmd.weight);
+                println!("Already indexed packages must be re-indexed to pick this up.");
+            }
+            ConfigSubCmd::SetIndexTests(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.index_tests = cmd.enabled;
+                config.save()?;
+                println!("Index test files set to: {}", cmd.enabled);
+                println!("Already indexed packages must be re-indexed to pick this up.");
+            }
+            ConfigSubCmd::SetIndexVendored(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.index_vendored = cmd.enabled;
+                config.save()?;
+                println!("Index vendored files set to: {}", cmd.enabled);
+                println!("Already indexed packages must be re-indexed to pick this up.");
+            }
+            ConfigSubCmd::SetNpmDeps(cmd) => {
+                let mut config = LocalConfig::load()?;
+                if let Some(enabled) = cmd.peer {
+                    config.npm_include_peer = enabled;
+                }
+                if let Some(enabled) = cmd.optional {
+                    config.npm_include_optional = enabled;
+                }
+                config.save()?;
+                println!(
+                    "npm peerDependencies: {}, optionalDependencies: {}",
+                    config.npm_include_peer, config.npm_include_optional
+                );
+            }
+            ConfigSubCmd::SetMinScore(cmd) => {
+                if !(0.0..=1.0).contains(&cmd.score) {
+                    anyhow::bail!("Score must be between 0 and 1");
+                }
+                let mut config = LocalConfig::load()?;
+                config.min_score = cmd.score;
+                config.save()?;
+                println!("Minimum search score set to: {}", cmd.score);
+            }
+            ConfigSubCmd::SetPublicOnly(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.public_only = cmd.enabled;
+                config.save()?;
+                println!("Search public API only set to: {}", cmd.enabled);
+            }
+            ConfigSubCmd::SetSnippetLength(cmd) => {
+                let mut config = LocalConfig::load()?;
+                let previous = config.snippet_length;
+                config.snippet_length = cmd.length as usize;
+                config.save()?;
+                println!("Snippet length set to: {}", cmd.length);
+                if config.snippet_length > previous {
+                    println!("Already indexed packages must be re-indexed for longer snippets.");
+                }
+            }
+            ConfigSubCmd::SetLicenseDenylist(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.license_denylist = cmd.licenses.clone();
+                config.license_action = cmd.action;
+                config.save()?;
+                if cmd.licenses.is_empty() {
+                    println!("License denylist cleared");
+                } else {
+                    println!(
+                        "License denylist set to: {} ({})",
+                        cmd.licenses.join(", "),
+                        cmd.action
+                    );
+                }
+            }
+            ConfigSubCmd::SetTransformers(cmd) => {
+                if let Some(unknown) = cmd
+                    .names
+                    .iter()
+                    .find(|name| !BUILTIN_TRANSFORMERS.contains(&name.as_str()))
+                {
+                    anyhow::bail!(
+                        "Unknown chunk transformer '{}'. Use one of: {}",
+                        unknown,
+                        BUILTIN_TRANSFORMERS.join(", ")
+                    );
+                }
+                let mut config = LocalConfig::load()?;
+                config.chunk_transformers = cmd.names.clone();
+                config.save()?;
+                if cmd.names.is_empty() {
+                    println!("Chunk transformers cleared");
+                } else {
+                    println!("Chunk transformers set to: {}", cmd.names.join(", "));
+                }
+                println!("Already indexed packages must be re-indexed to pick this up.");
+            }
+            ConfigSubCmd::SetLimits(cmd) => {
+                let mut config = LocalConfig::load()?;
+                if let Some(n) = cmd.max_files {
+                    config.max_files_per_package = n;
+                }
+                if let Some(n) = cmd.max_chunks {
+                    config.max_chunks_per_package = n;
+                }
+                if let Some(n) = cmd.max_versions {
+                    config.max_versions_per_package = n;
+                }
+                config.save()?;
+                println!(
+                    "Limits set to: {} files, {} chunks, {} versions per package",
+                    config.max_files_per_package,
+                    config.max_chunks_per_package,
+                    config.max_versions_per_package
+                );
+            }
+            ConfigSubCmd::SetDbReaders(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.db_read_connections = cmd.connections;
+                config.save()?;
+                println!("SQLite read connections set to: {}", cmd.connections);
+            }
+            ConfigSubCmd::SetProxy(cmd) => {
+                let mut config = LocalConfig::load()?;
+                if let Some(url) = &cmd.url {
+                    config.http_proxy = unless_none(url);
+                }
+                if let Some(hosts) = &cmd.no_proxy {
+                    config.no_proxy = unless_none(hosts);
+                }
+                if let Some(path) = &cmd.ca_bundle {
+                    config.ca_bundle = unless_none(path).map(PathBuf::from);
+                }
+                // Fail before saving settings no client can be built with
+                config.http_client()?;
+                config.save()?;
+                println!("Proxy set to: {}", proxy_summary(&config));
+            }
+            ConfigSubCmd::SetBackend(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.vector_backend = cmd.backend;
+                if let Some(key) = &cmd.api_key {
+                    config.turbopuffer_api_key = Some(key.clone());
+                }
+                if let Some(url) = &cmd.url {
+                    config.turbopuffer_base_url = url.clone();
+                }
+                if let Some(prefix) = &cmd.prefix {
+                    config.turbopuffer_namespace_prefix = prefix.clone();
+                }
+                if let Some(metric) = cmd.metric {
+                    config.distance_metric = metric;
+                }
+                if cmd.backend == VectorBackendKind::Turbopuffer
+                    && config.distance_metric == DistanceMetric::Dot
+                {
+                    anyhow::bail!("Turbopuffer supports the l2 and cosine metrics, not dot");
+                }
+                config.save()?;
+                println!(
+                    "Vector backend set to: {} ({} distance)",
+                    cmd.backend, config.distance_metric
+                );
+                if cmd.backend == VectorBackendKind::Turbopuffer
+                    && config.turbopuffer_api_key.is_none()
+                {
+                    println!("Note: no Turbopuffer API key set (use --api-key).");
+                }
+                println!("Existing packages must be re-indexed to appear in the new backend.");
+            }
+            ConfigSubCmd::AddProfile(cmd) => {
+                let mut config = LocalConfig::load()?;
+                // Unset options start from the top-level settings
+                let mut profile = config.embedding_profile();
+                if let Some(model) = &cmd.model {
+                    profile.embedding_model = model.clone();
+                }
+                if let Some(url) = &cmd.url {
+                    profile.openai_base_url = url.clone();
+                }
+                if let Some(key) = &cmd.key {
+                    profile.openai_api_key = Some(key.clone());
+                }
+                if let Some(dimensions) = cmd.dimensions {
+                    profile.embedding_dimensions = dimensions as usize;
+                }
+                println!(
+                    "Profile '{}' saved: {} ({} dimensions) at {}",
+                    cmd.name,
+                    profile.embedding_model,
+                    profile.embedding_dimensions,
+                    profile.openai_base_url
+                );
+                config.add_profile(&cmd.name, profile)?;
+                config.save()?;
+                println!("Switch to it with: idx config use-profile {}", cmd.name);
+            }
+            ConfigSubCmd::UseProfile(cmd) => {
+                let mut config = LocalConfig::load()?;
+                config.use_profile(&cmd.name)?;
+                config.save()?;
+                println!("Active profile set to: {}", cmd.name);
             }
             ConfigSubCmd::Show => {
-                let config = LocalConfig::load()?;
+                let saved = LocalConfig::load()?;
+                let profiles: Vec<_> = saved.profiles.keys().cloned().collect();
+                // Show the settings in effect
+                let config = saved.with_profile(None)?;
                 println!("Config: {}", LocalConfig::config_path()?.display());
                 println!();
                 println!(
-                    "api_key:    {}",
-                    if config.has_openai_key() {
-                        "(set)"
+                    "profile:    {}{}",
+                    config.profile_name(None),
+                    if profiles.is_empty() {
+                        String::new()
                     } else {
-                        "(not set)"
+                        format!(" (available: {}, {})", DEFAULT_PROFILE, profiles.join(", "))
                     }
                 );
+                println!(
+                    "api_key:    {}",
+                    secret_status(&config, EnvSecret::OpenAiApiKey, config.has_openai_key())
+                );
                 println!("base_url:   {}", config.openai_base_url);
-                println!("model:      {}", config.embedding_model);
+                println!(
+                    "model:      {} ({} dimensions)",
+                    config.embedding_model, config.embedding_dimensions
+                );
+                println!(
+                    "emb_batch:  {} texts, {} tokens",
+                    config.embedding_batch_size, config.embedding_batch_tokens
+                );
+                println!("doc_embeddings: {}", config.doc_embeddings);
+                if config.embedding_name_weight > 0 {
+                    println!("name_weight: {}", config.embedding_name_weight);
+                }
+                println!(
+                    "emb_price:  ${} per 1K tokens",
+                    config.embedding_price_per_1k_tokens
+                );
+                println!("store_files: {}", config.store_source_files);
+                println!("blob_level: {}", config.blob_compression_level);
+                println!("index_tests: {}", config.index_tests);
+                println!("vendored:   {}", config.index_vendored);
+                println!(
+                    "npm_deps:   peer={}, optional={}",
+                    config.npm_include_peer, config.npm_include_optional
+                );
+                println!("min_score:  {}", config.min_score);
+                println!("public_only: {}", config.public_only);
+                println!("snippets:   {} bytes", config.snippet_length);
+                if !config.chunk_transformers.is_empty() {
+                    println!("transformers: {}", config.chunk_transformers.join(", "));
+                }
+                if !config.license_denylist.is_empty() {
+                    println!(
+                        "licenses:   {} denied ({})",
+                        config.license_denylist.join(", "),
+                        config.license_action
+                    );
+                }
+                println!("max_files:  {}", config.max_files_per_package);
+                println!("max_chunks: {}", config.max_chunks_per_package);
+                println!("max_versions: {}", config.max_versions_per_package);
+                println!("db_readers: {}", config.db_read_connections);
+                println!("proxy:      {}", proxy_summary(&config));
+                println!(
+                    "reg_cache:  {}h ttl, {} MB max{}",
+                    config.registry_cache_ttl_hours,
+                    config.registry_cache_max_mb,
+                    if config.registry_cache_sources {
+                        ", sources"
+                    } else {
+                        ""
+                    }
+                );
+                println!(
+                    "vectors:    {} ({} distance)",
+                    config.vector_backend, config.distance_metric
+                );
+                if config.vector_backend == VectorBackendKind::Turbopuffer {
+                    println!(
+                        "tpuf_key:   {}",
+                        secret_status(
+                            &config,
+                            EnvSecret::TurbopufferApiKey,
+                            config.turbopuffer_api_key.is_some()
+                        )
+                    );
+                    println!("tpuf_url:   {}", config.turbopuffer_base_url);
+                    println!("tpuf_prefix: {}", config.turbopuffer_namespace_prefix);
+                }
             }
         }
         Ok(())
     }
 }
+
+/// How a secret is set, without showing it.
+fn secret_status(config: &LocalConfig, secret: EnvSecret, set: bool) -> String {
+    if config.is_from_env(secret) {
+        format!("(set from {})", secret.var())
+    } else if set {
+        "(set)".to_string()
+    } else {
+        "(not set)".to_string()
+    }
+}
+
+/// Top-level embedding settings are shadowed while a named profile is active.
+fn note_active_profile(config: &LocalConfig) {
+    if let Some(profile) = &config.active_profile {
+        println!(
+            "Note: profile '{}' is active; this changed the '{}' profile.",
+            profile, DEFAULT_PROFILE
+        );
+    }
+}
+
+/// `value`, or `None` when it is `none` (clearing the setting).
+fn unless_none(value: &str) -> Option<String> {
+    (!value.eq_ignore_ascii_case("none") && !value.is_empty()).then(|| value.to_string())
+}
+
+/// One-line description of the proxy and CA settings.
+fn proxy_summary(config: &LocalConfig) -> String {
+    let mut summary = match &config.http_proxy {
+        Some(url) => url.clone(),
+        None => "(from environment)".to_string(),
+    };
+    if let Some(hosts) = &config.no_proxy {
+        summary.push_str(&format!(", bypass {}", hosts));
+    }
+    if let Some(path) = &config.ca_bundle {
+        summary.push_str(&format!(", ca {}", path.display()));
+    }
+    summary
+}
@@ -0,0 +1,185 @@
+//! Diff command - compare the symbols of two indexed package versions.
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::local::models::{ApiDiff, ApiSymbol, ChunkFilter, TestFilter};
+use crate::local::{self, LocalConfig, LocalSearch};
+
+#[derive(Args)]
+pub struct DiffCmd {
+    /// Package to compare (format: registry:name or name, which assumes crates)
+    pub package: String,
+
+    /// Older version (or "latest")
+    pub from: String,
+
+    /// Newer version (or "latest")
+    pub to: String,
+
+    /// Also report symbols whose documentation changed
+    #[arg(long)]
+    pub docs: bool,
+
+    /// Only compare files under a path prefix (src/parser/) or matching a glob (*/client/*.ts)
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Also compare internal and private code, not just public API (see
+    /// `idx config set-public-only`)
+    #[arg(long)]
+    pub all_visibility: bool,
+
+    /// Print the diff as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl DiffCmd {
+    /// Symbols to compare: public API outside tests, unless
+    /// `--all-visibility` is given or `public_only` is off.
+    fn filter(&self, config: &LocalConfig) -> ChunkFilter {
+        ChunkFilter::new(self.path.as_deref(), TestFilter::Exclude)
+            .with_public_only(config.public_only && !self.all_visibility)
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let index_dir =
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+
+        let search = LocalSearch::new(&index_dir)
+            .await?
+            .with_shared(local::open_global_search(&index_dir, None).await?);
+
+        let (registry, name) = parse_package(&self.package);
+        let diff = search
+            .diff(
+                registry,
+                name,
+                &self.from,
+                &self.to,
+                &self.filter(search.config()),
+                self.docs,
+            )
+            .await?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            print!("{}", format_diff(&diff));
+        }
+        Ok(())
+    }
+}
+
+/// Split "registry:name" (or "name", assumed to be a crate).
+fn parse_package(spec: &str) -> (&str, &str) {
+    spec.split_once(':').unwrap_or(("crates", spec))
+}
+
+/// Render a diff as text: a summary line, then one section per kind of change.
+fn format_diff(diff: &ApiDiff) -> String {
+    let mut output = format!(
+        "{}:{} {} -> {}: {} added, {} removed, {} changed\n",
+        diff.registry,
+        diff.package,
+        diff.from,
+        diff.to,
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+
+    let sections = [("Added", '+', &diff.added), ("Removed", '-', &diff.removed)];
+    for (title, marker, symbols) in sections {
+        if symbols.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n{}:\n", title));
+        for symbol in symbols {
+            output.push_str(&format!("  {} {}\n", marker, describe(symbol)));
+            if let Some(signature) = &symbol.signature {
+                output.push_str(&format!("      {}\n", signature));
+            }
+        }
+    }
+
+    if !diff.changed.is_empty() {
+        output.push_str("\nChanged:\n");
+        for change in &diff.changed {
+            output.push_str(&format!("  ~ {}\n", describe(&change.new)));
+            if change.signature_changed {
+                let old = change.old.signature.as_deref().unwrap_or_default();
+                let new = change.new.signature.as_deref().unwrap_or_default();
+                output.push_str(&format!("      - {}\n", old));
+                output.push_str(&format!("      + {}\n", new));
+            }
+            if change.docs_changed {
+                output.push_str("      docs changed\n");
+            }
+        }
+    }
+    output
+}
+
+/// "function `Client.get` (src/client.js:12)"
+fn describe(symbol: &ApiSymbol) -> String {
+    format!(
+        "{} `{}` ({}:{})",
+        symbol.chunk_type,
+        symbol.qualified_name(),
+        symbol.file_path,
+        symbol.start_line
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, signature: &str, line: i64) -> ApiSymbol {
+        ApiSymbol {
+            name: name.to_string(),
+            parent: Some("Client".to_string()),
+            chunk_type: "method".to_string(),
+            signature: Some(signature.to_string()),
+            docstring: None,
+            file_path: "src/client.js".to_string(),
+            start_line: line,
+        }
+    }
+
+    #[test]
+    fn test_parse_package() {
+        assert_eq!(parse_package("npm:axios"), ("npm", "axios"));
+        assert_eq!(
+            parse_package("maven:com.google.guava:guava"),
+            ("maven", "com.google.guava:guava")
+        );
+        assert_eq!(parse_package("serde"), ("crates", "serde"));
+    }
+
+    #[test]
+    fn test_format_diff() {
+        let old = vec![symbol("get", "get(url)", 3)];
+        let new = vec![
+            symbol("get", "get(url, options)", 3),
+            symbol("put", "put(url, body)", 9),
+        ];
+        let diff = ApiDiff::new("npm", "fetcher", "1.0.0", "1.1.0", old, new, false);
+
+        assert_eq!(
+            format_diff(&diff),
+            "npm:fetcher 1.0.0 -> 1.1.0: 1 added, 0 removed, 1 changed\n\
+             \n\
+             Added:\n  \
+             + method `Client.put` (src/client.js:9)\n      \
+             put(url, body)\n\
+             \n\
+             Changed:\n  \
+             ~ method `Client.get` (src/client.js:3)\n      \
+             - get(url)\n      \
+             + get(url, options)\n"
+        );
+    }
+}
@@ -1,70 +1,546 @@
 //! Index command - trigger package indexing.
 
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::types::Registry;
+use crate::types::{Registry, TenantId};
 use anyhow::{Context, Result};
 use clap::Args;
+use tokio::sync::mpsc;
 
-use crate::local::{self, LocalIndexer};
+use super::init::index_dependencies;
+use super::progress::Progress;
+use super::run_args::RunArgs;
+use crate::local::{self, EmbeddingUsage, IndexEvent, LocalConfig, LocalIndexer};
+use crate::manifests::Dependency;
+use crate::registry::{GitClient, GoClient, PackageFile, RefSource, RegistryError};
 
 #[derive(Args)]
 pub struct IndexCmd {
-    /// Package spec: registry:name@version (e.g., npm:axios@1.7.9)
-    pub package: String,
+    /// Package spec: registry:name[@version] (e.g., npm:axios@1.7.9).
+    /// Omit the version or use `@latest` to index the latest release.
+    #[arg(required_unless_present_any = ["from_file", "repo"])]
+    pub package: Option<String>,
+
+    /// Index a git repository directly instead of a registry package,
+    /// stored as `git:<host>/<owner>/<repo>@<ref>`. Cloned into a temporary
+    /// directory that is deleted once its files are read
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["package", "git_ref", "from_file", "global"]
+    )]
+    pub repo: Option<String>,
+
+    /// Tag, branch or commit of --repo to index (default: the default
+    /// branch, stored under its commit)
+    #[arg(long = "ref", value_name = "REF", requires = "repo")]
+    pub repo_ref: Option<String>,
+
+    /// Index every package listed in a file (`-` for stdin): one
+    /// `registry name version` per line (spaces or tabs), or
+    /// `registry:name@version`, or a JSON array of {registry, name, version}.
+    /// Malformed lines are skipped with a warning
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["package", "git_ref"])]
+    pub from_file: Option<PathBuf>,
+
+    /// Number of packages to index concurrently (with --from-file)
+    // Not `requires = "from_file"`: clap lets that go unchecked whenever a
+    // package or --repo, which --from-file conflicts with, is given
+    #[arg(long, short = 'j', default_value = "4", conflicts_with_all = ["package", "repo"])]
+    pub concurrency: usize,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Texts per embeddings request (overrides config)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub embedding_batch_size: Option<u64>,
+
+    /// Index a branch, tag or commit instead of a release (go only).
+    /// Stored under the ref's pseudo-version; cloned with git if the
+    /// module proxy can't serve it
+    #[arg(long)]
+    pub git_ref: Option<String>,
+
+    /// Index into the global index shared by all projects (`$IDX_HOME`)
+    /// instead of the project's `.index/`. Only registry packages go there;
+    /// git checkouts and private packages stay project-local
+    #[arg(long, conflicts_with_all = ["git_ref", "repo", "tenant"])]
+    pub global: bool,
+
+    /// Index as a private package of this tenant (a UUID), kept under
+    /// `private/<tenant>/...` apart from public packages of the same name.
+    /// Private packages stay project-local
+    #[arg(long, conflicts_with = "global")]
+    pub tenant: Option<TenantId>,
+
+    /// Print the estimated embedding cost of the run (price set with
+    /// `idx config set-embedding-price`)
+    #[arg(long)]
+    pub profile_cost: bool,
 }
 
 impl IndexCmd {
     pub async fn run(&self) -> Result<()> {
-        let index_dir =
-            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+        let index_dir = if self.global {
+            let dir = local::global_index_dir()
+                .context("Could not determine the global index directory. Set IDX_HOME.")?;
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            println!("Global index: {}", dir.display());
+            dir
+        } else {
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?
+        };
 
-        let (registry_str, name, version) = parse_package_spec(&self.package)?;
+        if let Some(path) = &self.from_file {
+            let config = LocalConfig::load()?.with_profile(self.run.profile())?;
+            return self.index_from_file(&index_dir, path, config).await;
+        }
+        if let Some(url) = &self.repo {
+            return self.index_repo(&index_dir, url).await;
+        }
+        let package = self.package.as_deref().unwrap_or_default();
+        let (registry_str, name, version) = parse_package_spec(package)?;
 
         let registry = Registry::from_str(&registry_str)
             .map_err(|e| anyhow::anyhow!("Unknown registry '{}': {}", registry_str, e))?;
 
-        println!("Indexing {}:{}@{}...", registry_str, name, version);
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let indexer = LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+            .await?
+            .with_limits(self.run.max_files, self.run.max_chunks)
+            .with_embedding_batch_size(self.embedding_batch_size.map(|n| n as usize))
+            .with_tenant(self.tenant)
+            .with_events(events);
+
+        let source = match &self.git_ref {
+            Some(git_ref) => {
+                if registry != Registry::Go {
+                    anyhow::bail!("--git-ref is only supported for go modules");
+                }
+                if version.is_some() {
+                    anyhow::bail!("Use either @version or --git-ref, not both");
+                }
+                if indexer.config().offline {
+                    return Err(RegistryError::OfflineMode.into());
+                }
+                let source = GoClient::with_client(indexer.config().http_client()?)
+                    .fetch_ref(&name, git_ref)
+                    .await
+                    .with_context(|| format!("Failed to fetch {} at {}", name, git_ref))?;
+                Some(source)
+            }
+            None => None,
+        };
+
+        let version = match &source {
+            Some(source) => source.version().to_string(),
+            None => match indexer
+                .registry(registry)
+                .resolve_version(&name, version.as_deref())
+                .await
+            {
+                Ok(version) => version,
+                Err(e) => {
+                    // Suggestions need a registry search, so only when someone is reading
+                    let suggestions = match &e {
+                        RegistryError::PackageNotFound(_)
+                            if !self.run.quiet && std::io::stdout().is_terminal() =>
+                        {
+                            indexer.suggest_packages(registry, &name).await
+                        }
+                        _ => Vec::new(),
+                    };
+                    return Err(anyhow::Error::new(e).context(format!(
+                        "Failed to resolve version of {}:{}{}",
+                        registry_str,
+                        name,
+                        did_you_mean(registry, &suggestions)
+                    )));
+                }
+            },
+        };
 
-        let indexer = LocalIndexer::new(&index_dir).await?;
-        let result = indexer.index_package(registry, &name, &version).await?;
+        match &source {
+            Some(RefSource::Git { .. }) => println!(
+                "Indexing {}:{}@{} (git checkout)...",
+                registry_str, name, version
+            ),
+            _ => println!("Indexing {}:{}@{}...", registry_str, name, version),
+        }
+
+        let files = match &source {
+            Some(RefSource::Git { files, .. }) => Some(files.as_slice()),
+            _ => None,
+        };
+        self.index_and_report(&indexer, event_rx, registry, &name, &version, files)
+            .await
+    }
+
+    /// Index `name@version`, from `files` when given instead of a download,
+    /// and report the outcome.
+    async fn index_and_report(
+        &self,
+        indexer: &LocalIndexer,
+        event_rx: mpsc::UnboundedReceiver<IndexEvent>,
+        registry: Registry,
+        name: &str,
+        version: &str,
+        files: Option<&[PackageFile]>,
+    ) -> Result<()> {
+        let progress = Progress::new(1, self.run.quiet, false);
+        progress.watch(event_rx);
+
+        let cancel = local::cancel_on_ctrl_c();
+        let result = match files {
+            Some(files) => {
+                indexer
+                    .index_source_with_cancel(registry, name, version, files, &cancel)
+                    .await
+            }
+            None => {
+                indexer
+                    .index_package_with_cancel(registry, name, version, &cancel)
+                    .await
+            }
+        };
+        progress.finish();
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) if e.is::<local::IndexCancelled>() => {
+                println!("Cancelled.");
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(license) = result.denied_license.as_ref().filter(|_| result.skipped) {
+            println!(
+                "Skipped: license {} is on the license denylist (see `idx config show`)",
+                license
+            );
+            return Ok(());
+        }
+        if let Some(rule) = &result.skip_rule {
+            println!(
+                "Skipped: matches skip rule {} (see `idx skip --list`)",
+                rule
+            );
+            return Ok(());
+        }
+        if let Some(license) = &result.denied_license {
+            println!("Warning: license {} is on the license denylist", license);
+        }
 
         if result.chunks_indexed > 0 {
             println!(
                 "Indexed {} chunks from {} files",
                 result.chunks_indexed, result.files_processed
             );
+            if result.embeddings_reused > 0 {
+                println!(
+                    "Reused {} embeddings of unchanged code",
+                    result.embeddings_reused
+                );
+            }
+            if result.truncated {
+                println!(
+                    "Warning: package exceeded per-package caps and was truncated \
+                     (raise with --max-files/--max-chunks, 0 = unlimited)"
+                );
+            }
+            if self.profile_cost {
+                print_embedding_cost(
+                    &result.embedding_usage,
+                    indexer.config().embedding_price_per_1k_tokens,
+                );
+            }
+        } else if result.is_empty() {
+            println!(
+                "No code extracted from {} files ({} not parsed); marked as empty. \
+                 See `idx list -s empty`.",
+                result.files_processed, result.files_skipped
+            );
         } else {
             println!("Already indexed (skipped)");
         }
 
         Ok(())
     }
+
+    /// Clone the repository at `url` and index its files as a
+    /// [`Registry::Git`] package.
+    async fn index_repo(&self, index_dir: &Path, url: &str) -> Result<()> {
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let indexer = LocalIndexer::new_with_profile(index_dir, self.run.profile())
+            .await?
+            .with_limits(self.run.max_files, self.run.max_chunks)
+            .with_embedding_batch_size(self.embedding_batch_size.map(|n| n as usize))
+            .with_tenant(self.tenant)
+            .with_events(events);
+        if indexer.config().offline {
+            return Err(RegistryError::OfflineMode.into());
+        }
+
+        println!("Cloning {}...", url);
+        let source = GitClient::new()
+            .fetch_repo(url, self.repo_ref.as_deref())
+            .await
+            .with_context(|| match &self.repo_ref {
+                Some(git_ref) => format!("Failed to fetch {} at {}", url, git_ref),
+                None => format!("Failed to fetch {}", url),
+            })?;
+
+        if !source.packages.is_empty() {
+            println!("Detected packages:");
+            for package in &source.packages {
+                println!(
+                    "  {}:{} ({})",
+                    package.registry,
+                    package.name.as_deref().unwrap_or("(unnamed)"),
+                    if package.root_path.is_empty() {
+                        "."
+                    } else {
+                        &package.root_path
+                    }
+                );
+            }
+        }
+        println!(
+            "Indexing {}:{}@{} (commit {})...",
+            Registry::Git,
+            source.name,
+            source.version,
+            &source.commit[..source.commit.len().min(12)]
+        );
+
+        self.index_and_report(
+            &indexer,
+            event_rx,
+            Registry::Git,
+            &source.name,
+            &source.version,
+            Some(&source.files),
+        )
+        .await
+    }
+
+    /// Index the packages listed at `path` concurrently with `config`,
+    /// reporting each one's outcome. Fails if any package does, so scripts
+    /// see a non-zero exit.
+    async fn index_from_file(
+        &self,
+        index_dir: &Path,
+        path: &Path,
+        config: LocalConfig,
+    ) -> Result<()> {
+        let text = if path == Path::new("-") {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .context("Failed to read package list from stdin")?;
+            text
+        } else {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+        };
+
+        let (deps, warnings) = parse_dependency_list(&text);
+        for warning in &warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        if deps.is_empty() {
+            println!("No packages listed.");
+            return Ok(());
+        }
+        println!("Read {} packages from {}", deps.len(), path.display());
+
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let indexer = Arc::new(
+            LocalIndexer::with_config(index_dir, config)
+                .await?
+                .with_limits(self.run.max_files, self.run.max_chunks)
+                .with_embedding_batch_size(self.embedding_batch_size.map(|n| n as usize))
+                .with_tenant(self.tenant)
+                .with_events(events),
+        );
+        indexer.check_embedding_dimensions().await?;
+
+        // Every line gets its outcome, as with `idx init --verbose`
+        let total = deps.len();
+        let summary = index_dependencies(
+            indexer,
+            deps,
+            event_rx,
+            self.concurrency,
+            self.run.quiet,
+            true,
+            self.profile_cost,
+        )
+        .await;
+
+        if summary.failed > 0 {
+            anyhow::bail!("{} of {} packages failed to index", summary.failed, total);
+        }
+        Ok(())
+    }
 }
 
-/// Parse package spec: registry:name@version
-fn parse_package_spec(spec: &str) -> Result<(String, String, String)> {
+/// Packages listed in `text`: a JSON array of `{registry, name, version}`
+/// objects, or one package per line as `registry name version` (separated
+/// by spaces or tabs) or `registry:name@version`. Blank lines and `#`
+/// comments are skipped.
+///
+/// Malformed entries are left out and described in the returned warnings,
+/// naming their line (or array index).
+fn parse_dependency_list(text: &str) -> (Vec<Dependency>, Vec<String>) {
+    let mut deps = Vec::new();
+    let mut warnings = Vec::new();
+
+    if text.trim_start().starts_with('[') {
+        match serde_json::from_str::<Vec<serde_json::Value>>(text) {
+            Ok(entries) => {
+                for (i, entry) in entries.into_iter().enumerate() {
+                    match serde_json::from_value::<Dependency>(entry)
+                        .map_err(|e| e.to_string())
+                        .and_then(validate_dependency)
+                    {
+                        Ok(dep) => deps.push(dep),
+                        Err(e) => warnings.push(format!("entry {}: {}", i, e)),
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("invalid JSON package list: {}", e)),
+        }
+        return (deps, warnings);
+    }
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let dep = match fields[..] {
+            [registry, name, version] => Ok(Dependency {
+                registry: registry.to_string(),
+                name: name.to_string(),
+                version: version.to_string(),
+            }),
+            [spec] => match parse_package_spec(spec) {
+                Ok((registry, name, Some(version))) => Ok(Dependency {
+                    registry,
+                    name,
+                    version,
+                }),
+                Ok(_) => Err("missing @version".to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            _ => Err("expected `registry name version`".to_string()),
+        };
+        match dep.and_then(validate_dependency) {
+            Ok(dep) => deps.push(dep),
+            Err(e) => warnings.push(format!("line {}: {} ({})", i + 1, e, line)),
+        }
+    }
+
+    (deps, warnings)
+}
+
+/// `dep` with its registry checked and normalized, and a concrete version.
+fn validate_dependency(dep: Dependency) -> Result<Dependency, String> {
+    let registry = Registry::from_str(&dep.registry)?;
+    if dep.name.is_empty() {
+        return Err("missing package name".to_string());
+    }
+    if dep.version.is_empty() || dep.version.eq_ignore_ascii_case("latest") {
+        return Err(format!("{}:{} needs an exact version", registry, dep.name));
+    }
+    Ok(Dependency {
+        registry: registry.to_string(),
+        ..dep
+    })
+}
+
+/// Print the text a run embedded and what it cost at `price_per_1k_tokens`,
+/// along with what reusing earlier embeddings saved.
+pub(super) fn print_embedding_cost(usage: &EmbeddingUsage, price_per_1k_tokens: f64) {
+    println!(
+        "Embedding cost (estimated at ${} per 1K tokens):",
+        price_per_1k_tokens
+    );
+    println!(
+        "  {} texts, {} chars, ~{} tokens: ${:.4}",
+        usage.texts,
+        usage.chars,
+        usage.tokens,
+        EmbeddingUsage::cost(usage.tokens, price_per_1k_tokens)
+    );
+    println!(
+        "  ~{} tokens reused from earlier embeddings: ${:.4} saved",
+        usage.reused_tokens,
+        EmbeddingUsage::cost(usage.reused_tokens, price_per_1k_tokens)
+    );
+}
+
+/// " (did you mean npm:lodash?)" for suggested package names, or nothing.
+fn did_you_mean(registry: Registry, suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let names: Vec<String> = suggestions
+        .iter()
+        .map(|name| format!("{}:{}", registry, name))
+        .collect();
+    format!(" (did you mean {}?)", names.join(" or "))
+}
+
+/// Parse package spec: registry:name[@version]
+///
+/// A leading `@` belongs to the name (npm scopes), not the version.
+pub(super) fn parse_package_spec(spec: &str) -> Result<(String, String, Option<String>)> {
     let (registry, rest) = spec
         .split_once(':')
-        .context("Invalid format. Use: registry:name@version (e.g., npm:axios@1.7.9)")?;
+        .context("Invalid format. Use: registry:name[@version] (e.g., npm:axios@1.7.9)")?;
+
+    let (name, version) = match rest.rfind('@') {
+        Some(i) if i > 0 => (&rest[..i], Some(rest[i + 1..].to_string())),
+        _ => (rest, None),
+    };
 
-    let (name, version) = rest
-        .rsplit_once('@')
-        .context("Invalid format. Use: registry:name@version (e.g., npm:axios@1.7.9)")?;
+    if name.is_empty() {
+        anyhow::bail!("Invalid format. Use: registry:name[@version] (e.g., npm:axios@1.7.9)");
+    }
 
-    Ok((registry.to_string(), name.to_string(), version.to_string()))
+    Ok((registry.to_string(), name.to_string(), version))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::local::models::VersionStatus;
+    use crate::registry::{CacheKind, VersionInfo};
+    use crate::test_support::stub_embeddings;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        index: IndexCmd,
+    }
 
     #[test]
     fn test_parse_package_spec() {
         let (reg, name, ver) = parse_package_spec("npm:axios@1.7.9").unwrap();
         assert_eq!(reg, "npm");
         assert_eq!(name, "axios");
-        assert_eq!(ver, "1.7.9");
+        assert_eq!(ver.as_deref(), Some("1.7.9"));
     }
 
     #[test]
@@ -72,7 +548,7 @@ mod tests {
         let (reg, name, ver) = parse_package_spec("crates:serde@1.0.228").unwrap();
         assert_eq!(reg, "crates");
         assert_eq!(name, "serde");
-        assert_eq!(ver, "1.0.228");
+        assert_eq!(ver.as_deref(), Some("1.0.228"));
     }
 
     #[test]
@@ -80,12 +556,229 @@ mod tests {
         let (reg, name, ver) = parse_package_spec("npm:@types/node@20.0.0").unwrap();
         assert_eq!(reg, "npm");
         assert_eq!(name, "@types/node");
-        assert_eq!(ver, "20.0.0");
+        assert_eq!(ver.as_deref(), Some("20.0.0"));
+    }
+
+    #[test]
+    fn test_parse_package_spec_without_version() {
+        let (_, name, ver) = parse_package_spec("npm:axios").unwrap();
+        assert_eq!(name, "axios");
+        assert_eq!(ver, None);
+
+        let (_, name, ver) = parse_package_spec("npm:@types/node").unwrap();
+        assert_eq!(name, "@types/node");
+        assert_eq!(ver, None);
+
+        let (_, _, ver) = parse_package_spec("npm:axios@latest").unwrap();
+        assert_eq!(ver.as_deref(), Some("latest"));
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean(Registry::Npm, &[]), "");
+        assert_eq!(
+            did_you_mean(Registry::Npm, &["lodash".to_string()]),
+            " (did you mean npm:lodash?)"
+        );
+        assert_eq!(
+            did_you_mean(
+                Registry::Pypi,
+                &["requests".to_string(), "request".to_string()]
+            ),
+            " (did you mean pypi:requests or pypi:request?)"
+        );
+    }
+
+    #[test]
+    fn test_parse_dependency_list_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deps.txt");
+        std::fs::write(
+            &path,
+            "# audited packages\n\
+             npm lodash 4.17.21\n\
+             pypi\trequests\t2.32.3\n\
+             \n\
+             crates:serde@1.0.228\n\
+             cpan Moose 2.2206\n\
+             npm axios\n\
+             npm:react\n\
+             go github.com/pkg/errors latest\n",
+        )
+        .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let (deps, warnings) = parse_dependency_list(&text);
+        let listed: Vec<_> = deps
+            .iter()
+            .map(|d| format!("{}:{}@{}", d.registry, d.name, d.version))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                "npm:lodash@4.17.21",
+                "pypi:requests@2.32.3",
+                "crates:serde@1.0.228"
+            ]
+        );
+
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings[0].starts_with("line 6: unknown registry: cpan"));
+        assert!(warnings[1].starts_with("line 7: expected `registry name version`"));
+        assert!(warnings[2].starts_with("line 8: missing @version"));
+        assert!(warnings[3].starts_with("line 9: go:github.com/pkg/errors needs an exact version"));
+    }
+
+    #[test]
+    fn test_parse_dependency_list_json() {
+        let (deps, warnings) = parse_dependency_list(
+            r#"[
+                {"registry": "NPM", "name": "zod", "version": "3.23.8"},
+                {"registry": "npm", "name": "yup"}
+            ]"#,
+        );
+        assert_eq!(deps.len(), 1);
+        assert_eq!(
+            (deps[0].registry.as_str(), deps[0].name.as_str()),
+            ("npm", "zod")
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("entry 1: missing field `version`"));
+
+        let (deps, warnings) = parse_dependency_list("[not json");
+        assert!(deps.is_empty());
+        assert!(warnings[0].starts_with("invalid JSON package list"));
+    }
+
+    #[tokio::test]
+    async fn test_index_from_file_reports_each_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LocalConfig {
+            openai_base_url: stub_embeddings(vec![0.5; 4]).await.url,
+            openai_api_key: Some("test".to_string()),
+            embedding_dimensions: 4,
+            license_denylist: vec!["GPL-3.0".to_string()],
+            // Offline, the registry is whatever its response cache holds
+            offline: true,
+            registry_cache_sources: true,
+            ..LocalConfig::default()
+        };
+
+        let registry = config
+            .registry_cache(dir.path().join(local::CACHE_DIR_NAME))
+            .unwrap();
+        for (name, license) in [("left-pad", "MIT"), ("copyleft", "GPL-3.0")] {
+            let info = VersionInfo {
+                name: name.to_string(),
+                version: "1.0.0".to_string(),
+                description: None,
+                repository: None,
+                license: Some(license.to_string()),
+                tarball_url: String::new(),
+                entry_point: None,
+            };
+            registry
+                .fetch(CacheKind::Version, "npm", name, "1.0.0", async { Ok(info) })
+                .await
+                .unwrap();
+        }
+        let files = vec![PackageFile {
+            path: "index.js".to_string(),
+            content: "function leftPad(s, n) {\n  return s.padStart(n);\n}\n".to_string(),
+            is_test: false,
+            is_vendored: false,
+        }];
+        registry
+            .fetch(CacheKind::Source, "npm", "left-pad", "1.0.0", async {
+                Ok(files)
+            })
+            .await
+            .unwrap();
+
+        let list = dir.path().join("deps.txt");
+        std::fs::write(
+            &list,
+            "npm left-pad 1.0.0\nnpm copyleft 1.0.0\nnpm missing 1.0.0\nnot a package\n",
+        )
+        .unwrap();
+        let cmd = Cli::parse_from(["idx", "--from-file", "deps.txt", "--quiet"]).index;
+
+        // The uncached package fails, so the run does
+        let err = cmd
+            .index_from_file(dir.path(), &list, config.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "1 of 3 packages failed to index");
+
+        let indexer = LocalIndexer::with_config(dir.path(), config).await.unwrap();
+        let status = |name: &'static str| {
+            let db = indexer.db();
+            async move {
+                db.find_version("npm", name, "1.0.0", None)
+                    .await
+                    .unwrap()
+                    .map(|v| v.status())
+            }
+        };
+        assert_eq!(status("left-pad").await, Some(VersionStatus::Indexed));
+        assert_eq!(status("copyleft").await, Some(VersionStatus::Skipped));
+        // Offline isn't the package's fault, so it is left to retry
+        assert_eq!(status("missing").await, Some(VersionStatus::Pending));
+    }
+
+    #[test]
+    fn test_repo_stays_out_of_the_global_index() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["idx"].iter().chain(args));
+        assert!(parse(&["--repo", "https://github.com/acme/lib"]).is_ok());
+        let err = parse(&["--global", "--repo", "https://github.com/acme/lib"])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_tenant_packages_stay_out_of_the_global_index() {
+        let tenant = "6f1c2b9e-3d4a-4f5b-8c7d-9e0a1b2c3d4e";
+        let parse = |args: &[&str]| Cli::try_parse_from(["idx", "npm:left-pad"].iter().chain(args));
+        assert!(parse(&["--tenant", tenant]).is_ok());
+        let err = parse(&["--global", "--tenant", tenant]).err().unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_global_index_takes_registry_packages_only() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["idx", "--global"].iter().chain(args));
+        assert!(parse(&["npm:left-pad"]).is_ok());
+        for source in [
+            &["go:example.com/mod", "--git-ref", "main"][..],
+            &["--repo", "https://github.com/acme/lib"],
+            &[
+                "npm:left-pad",
+                "--tenant",
+                "6f1c2b9e-3d4a-4f5b-8c7d-9e0a1b2c3d4e",
+            ],
+        ] {
+            let err = parse(source).err().unwrap();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+
+    #[test]
+    fn test_concurrency_needs_from_file() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["idx"].iter().chain(args));
+        assert!(parse(&["--from-file", "deps.txt", "-j", "8"]).is_ok());
+        for source in [
+            &["npm:left-pad", "-j", "8"][..],
+            &["--repo", "https://github.com/acme/lib", "-j", "8"],
+        ] {
+            let err = parse(source).err().unwrap();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
     }
 
     #[test]
     fn test_parse_package_spec_invalid() {
         assert!(parse_package_spec("invalid").is_err());
-        assert!(parse_package_spec("npm:axios").is_err());
+        assert!(parse_package_spec("npm:").is_err());
     }
 }
@@ -1,22 +1,24 @@
 //! Init command - index all dependencies from project manifests.
 
 use std::collections::HashMap;
-use std::io::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use crate::types::Registry;
 use anyhow::{Context, Result};
 use clap::Args;
 use futures::stream::{self, StreamExt};
+use tokio::sync::mpsc;
 
-use crate::local::{self, LocalIndexer};
-use crate::manifests::{
-    Dependency, discover_manifest_dirs, parse_cargo_deps, parse_go_deps, parse_maven_deps,
-    parse_npm_deps, parse_python_deps,
-};
+use super::index::print_embedding_cost;
+use super::npm_deps::NpmDepsArgs;
+use super::progress::Progress;
+use super::run_args::RunArgs;
+use crate::local::models::VersionStatus;
+use crate::local::{self, EmbeddingUsage, IndexEvent, LocalIndexer};
+use crate::manifests::{Dependency, collect_deps, discover_manifest_dirs};
 
 #[derive(Args)]
 pub struct InitCmd {
@@ -32,15 +34,40 @@ pub struct InitCmd {
     #[arg(long, short = 'v')]
     pub verbose: bool,
 
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Print the estimated embedding cost of the run (price set with
+    /// `idx config set-embedding-price`)
+    #[arg(long)]
+    pub profile_cost: bool,
+
     /// Number of packages to index concurrently
     #[arg(long, short = 'j', default_value = "4")]
     pub concurrency: usize,
+
+    /// Index symbols of all dependencies first, then embed the stored
+    /// chunks in a second pass, without downloading them again. Lookups by
+    /// name work as soon as the first pass is done.
+    #[arg(long)]
+    pub metadata_first: bool,
+
+    /// Only index dependencies from these registries (e.g. crates,pypi)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub registry: Vec<Registry>,
+
+    /// Don't index dependencies from these registries (e.g. npm)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub exclude_registry: Vec<Registry>,
+
+    #[command(flatten)]
+    pub npm: NpmDepsArgs,
 }
 
 impl InitCmd {
     pub async fn run(&self) -> Result<()> {
         // Check for API key first
-        let config = local::LocalConfig::load()?;
+        let config = local::LocalConfig::load()?.with_profile(self.run.profile())?;
         if !config.has_openai_key() {
             anyhow::bail!("OpenAI API key not configured. Run: idx config set-key <key>");
         }
@@ -75,91 +102,72 @@ impl InitCmd {
             return Ok(());
         }
 
-        let indexer = Arc::new(LocalIndexer::new(&index_dir).await?);
-
-        let indexed = Arc::new(AtomicUsize::new(0));
-        let skipped = Arc::new(AtomicUsize::new(0));
-        let failed = Arc::new(AtomicUsize::new(0));
-        let completed = Arc::new(AtomicUsize::new(0));
-        let total = deps.len();
-        let verbose = self.verbose;
-        let concurrency = self.concurrency.max(1);
-
-        println!("Indexing with {} concurrent workers...", concurrency);
-
-        stream::iter(deps.into_iter().map(|dep| {
-            let indexer = Arc::clone(&indexer);
-            let indexed = Arc::clone(&indexed);
-            let skipped = Arc::clone(&skipped);
-            let failed = Arc::clone(&failed);
-            let completed = Arc::clone(&completed);
-
-            async move {
-                let registry = match Registry::from_str(&dep.registry) {
-                    Ok(r) => r,
-                    Err(e) => {
-                        failed.fetch_add(1, Ordering::Relaxed);
-                        if verbose {
-                            eprintln!("  {} -> error: {}", dep.name, e);
-                        }
-                        return;
-                    }
-                };
-
-                match indexer
-                    .index_package(registry, &dep.name, &dep.version)
-                    .await
-                {
-                    Ok(result) => {
-                        if result.chunks_indexed > 0 {
-                            indexed.fetch_add(1, Ordering::Relaxed);
-                            if verbose {
-                                eprintln!(
-                                    "  {}@{} -> indexed ({} chunks)",
-                                    dep.name, dep.version, result.chunks_indexed
-                                );
-                            }
-                        } else {
-                            skipped.fetch_add(1, Ordering::Relaxed);
-                            if verbose {
-                                eprintln!("  {}@{} -> already indexed", dep.name, dep.version);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        failed.fetch_add(1, Ordering::Relaxed);
-                        if verbose {
-                            eprintln!("  {}@{} -> failed: {}", dep.name, dep.version, e);
-                        }
-                    }
-                }
-
-                let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-                if !verbose {
-                    print!("\r[{}/{}] completed", done, total);
-                    std::io::stdout().flush().ok();
-                }
+        if self.metadata_first {
+            let (events, event_rx) = mpsc::unbounded_channel();
+            let indexer = Arc::new(
+                LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+                    .await?
+                    .with_limits(self.run.max_files, self.run.max_chunks)
+                    .with_metadata_only(true)
+                    .with_events(events),
+            );
+
+            println!("\nIndexing symbols (no embeddings)...");
+            let summary = index_dependencies(
+                indexer,
+                deps.clone(),
+                event_rx,
+                self.concurrency,
+                self.run.quiet,
+                self.verbose,
+                false,
+            )
+            .await;
+            if !summary.finished {
+                return Ok(());
             }
-        }))
-        .buffer_unordered(concurrency)
-        .collect::<Vec<()>>()
-        .await;
-
-        // Clear the progress line
-        if !verbose {
-            print!("\r{}\r", " ".repeat(40));
+            println!("\nSymbols are searchable by name. Embedding them...");
+
+            let (events, event_rx) = mpsc::unbounded_channel();
+            let indexer = Arc::new(
+                LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+                    .await?
+                    .with_events(events),
+            );
+            indexer.check_embedding_dimensions().await?;
+            embed_stored_versions(
+                indexer,
+                event_rx,
+                self.concurrency,
+                self.run.quiet,
+                self.verbose,
+                self.profile_cost,
+            )
+            .await?;
+
+            println!("\nDone!");
+            return Ok(());
         }
 
-        let indexed = indexed.load(Ordering::Relaxed);
-        let skipped = skipped.load(Ordering::Relaxed);
-        let failed = failed.load(Ordering::Relaxed);
-
-        println!("Results:");
-        println!("  {} indexed", indexed);
-        println!("  {} already indexed", skipped);
-        if failed > 0 {
-            println!("  {} failed", failed);
-        }
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let indexer = Arc::new(
+            LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+                .await?
+                .with_limits(self.run.max_files, self.run.max_chunks)
+                .with_events(events),
+        );
+        indexer.check_embedding_dimensions().await?;
+
+        index_dependencies(
+            indexer,
+            deps,
+            event_rx,
+            self.concurrency,
+            self.run.quiet,
+            self.verbose,
+            self.profile_cost,
+        )
+        .await;
 
         println!("\nDone!");
 
@@ -167,10 +175,9 @@ impl InitCmd {
     }
 
     fn collect_dependencies(&self) -> Result<Vec<Dependency>> {
-        let mut all_deps = Vec::new();
-
         // Discover all manifest directories (handles monorepos)
         let manifest_dirs = discover_manifest_dirs(&self.path)?;
+        let npm = self.npm.options()?;
 
         if manifest_dirs.is_empty() {
             return Ok(vec![]);
@@ -190,24 +197,8 @@ impl InitCmd {
             }
         }
 
-        // Parse manifests from each discovered directory
-        for dir in &manifest_dirs {
-            if let Ok(deps) = parse_npm_deps(dir) {
-                all_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_cargo_deps(dir) {
-                all_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_python_deps(dir) {
-                all_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_maven_deps(dir) {
-                all_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_go_deps(dir) {
-                all_deps.extend(deps);
-            }
-        }
+        let mut all_deps = collect_deps(&self.path, npm)?;
+        all_deps.retain(|dep| self.wants_registry(&dep.registry));
 
         // Dedupe by (registry, name) - keep first occurrence
         let mut seen: HashMap<(String, String), usize> = HashMap::new();
@@ -223,4 +214,352 @@ impl InitCmd {
 
         Ok(deps)
     }
+
+    /// Whether `--registry` / `--exclude-registry` let dependencies from
+    /// `registry` through.
+    fn wants_registry(&self, registry: &str) -> bool {
+        match Registry::from_str(registry) {
+            Ok(registry) => {
+                (self.registry.is_empty() || self.registry.contains(&registry))
+                    && !self.exclude_registry.contains(&registry)
+            }
+            // Reported as a failure when indexing, unless filtered out
+            Err(_) => self.registry.is_empty(),
+        }
+    }
+}
+
+/// Index `deps` with `concurrency` workers, reporting each package's outcome
+/// as it finishes and a summary at the end. Shared by `idx init` and
+/// `idx index --from-file`; failures are counted, not returned. Returns
+/// false if the run was cancelled.
+pub(super) async fn index_dependencies(
+    indexer: Arc<LocalIndexer>,
+    deps: Vec<Dependency>,
+    event_rx: mpsc::UnboundedReceiver<IndexEvent>,
+    concurrency: usize,
+    quiet: bool,
+    verbose: bool,
+    profile_cost: bool,
+) -> IndexSummary {
+    let indexed = Arc::new(AtomicUsize::new(0));
+    let skipped = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicUsize::new(0));
+    let truncated = Arc::new(AtomicUsize::new(0));
+    let empty = Arc::new(AtomicUsize::new(0));
+    let denied = Arc::new(AtomicUsize::new(0));
+    let ruled_out = Arc::new(AtomicUsize::new(0));
+    let usage = Arc::new(Mutex::new(EmbeddingUsage::default()));
+    let total = deps.len();
+    let concurrency = concurrency.max(1);
+    let cancel = local::cancel_on_ctrl_c();
+
+    println!("Indexing with {} concurrent workers...", concurrency);
+
+    let progress = Progress::new(total, quiet, verbose);
+    progress.watch(event_rx);
+
+    stream::iter(deps.into_iter().map(|dep| {
+        let indexer = Arc::clone(&indexer);
+        let indexed = Arc::clone(&indexed);
+        let skipped = Arc::clone(&skipped);
+        let failed = Arc::clone(&failed);
+        let truncated = Arc::clone(&truncated);
+        let empty = Arc::clone(&empty);
+        let denied = Arc::clone(&denied);
+        let ruled_out = Arc::clone(&ruled_out);
+        let usage = Arc::clone(&usage);
+        let progress = Arc::clone(&progress);
+        let cancel = cancel.clone();
+
+        async move {
+            // Don't start new work once cancelled; let in-flight tasks drain
+            if cancel.is_cancelled() {
+                return;
+            }
+
+            let package = format!("{}:{}@{}", dep.registry, dep.name, dep.version);
+
+            let registry = match Registry::from_str(&dep.registry) {
+                Ok(r) => r,
+                Err(e) => {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    progress.finish_package(&package, &format!("error: {}", e), true);
+                    return;
+                }
+            };
+
+            match indexer
+                .index_package_with_cancel(registry, &dep.name, &dep.version, &cancel)
+                .await
+            {
+                Ok(result) => {
+                    usage.lock().unwrap().add(&result.embedding_usage);
+                    if let Some(license) = result.denied_license.as_ref().filter(|_| result.skipped)
+                    {
+                        denied.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(
+                            &package,
+                            &format!("skipped (license {} is denied)", license),
+                            false,
+                        );
+                    } else if let Some(rule) = &result.skip_rule {
+                        ruled_out.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(
+                            &package,
+                            &format!("skipped (matches rule {})", rule),
+                            false,
+                        );
+                    } else if result.chunks_indexed > 0 {
+                        indexed.fetch_add(1, Ordering::Relaxed);
+                        if result.truncated {
+                            truncated.fetch_add(1, Ordering::Relaxed);
+                        }
+                        progress.finish_package(
+                            &package,
+                            &format!(
+                                "indexed ({} chunks{}{})",
+                                result.chunks_indexed,
+                                if result.truncated { ", truncated" } else { "" },
+                                result
+                                    .denied_license
+                                    .as_ref()
+                                    .map(|l| format!(", denylisted license {}", l))
+                                    .unwrap_or_default()
+                            ),
+                            false,
+                        );
+                    } else if result.is_empty() {
+                        empty.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(
+                            &package,
+                            &format!(
+                                "no code extracted ({} files, {} not parsed)",
+                                result.files_processed, result.files_skipped
+                            ),
+                            false,
+                        );
+                    } else {
+                        skipped.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(&package, "already indexed", false);
+                    }
+                }
+                Err(e) if e.is::<local::IndexCancelled>() => {}
+                Err(e) => {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    progress.finish_package(&package, &format!("failed: {}", e), true);
+                }
+            }
+        }
+    }))
+    .buffer_unordered(concurrency)
+    .collect::<Vec<()>>()
+    .await;
+
+    progress.finish();
+
+    let indexed = indexed.load(Ordering::Relaxed);
+    let skipped = skipped.load(Ordering::Relaxed);
+    let failed = failed.load(Ordering::Relaxed);
+    let truncated = truncated.load(Ordering::Relaxed);
+    let empty = empty.load(Ordering::Relaxed);
+    let denied = denied.load(Ordering::Relaxed);
+    let ruled_out = ruled_out.load(Ordering::Relaxed);
+    let remaining = total - indexed - skipped - failed - empty - denied - ruled_out;
+
+    println!("Results:");
+    println!("  {} indexed", indexed);
+    println!("  {} already indexed", skipped);
+    if truncated > 0 {
+        println!(
+            "  {} truncated (hit per-package file/chunk caps, see --max-files/--max-chunks)",
+            truncated
+        );
+    }
+    if empty > 0 {
+        println!(
+            "  {} empty (no code extracted, see `idx list -s empty`)",
+            empty
+        );
+    }
+    if denied > 0 {
+        println!(
+            "  {} skipped for denylisted licenses (see `idx list --licenses`)",
+            denied
+        );
+    }
+    if ruled_out > 0 {
+        println!(
+            "  {} skipped by skip rules (see `idx skip --list`)",
+            ruled_out
+        );
+    }
+    if failed > 0 {
+        println!("  {} failed", failed);
+    }
+    if cancel.is_cancelled() {
+        println!("  {} cancelled", remaining);
+    }
+    if profile_cost {
+        print_embedding_cost(
+            &usage.lock().unwrap(),
+            indexer.config().embedding_price_per_1k_tokens,
+        );
+    }
+
+    IndexSummary {
+        finished: !cancel.is_cancelled(),
+        failed,
+    }
+}
+
+/// Embed every `metadata_indexed` version from its stored chunks with
+/// `concurrency` workers, in a background task, reporting each version as
+/// it finishes and a summary at the end. Nothing is downloaded again.
+async fn embed_stored_versions(
+    indexer: Arc<LocalIndexer>,
+    event_rx: mpsc::UnboundedReceiver<IndexEvent>,
+    concurrency: usize,
+    quiet: bool,
+    verbose: bool,
+    profile_cost: bool,
+) -> Result<()> {
+    let versions = indexer
+        .db()
+        .list_versions_by_status(VersionStatus::MetadataIndexed)
+        .await?;
+    let total = versions.len();
+    let cancel = local::cancel_on_ctrl_c();
+
+    let progress = Progress::new(total, quiet, verbose);
+    progress.watch(event_rx);
+
+    let task = {
+        let indexer = Arc::clone(&indexer);
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            let outcomes = stream::iter(versions.into_iter().map(|version| {
+                let indexer = Arc::clone(&indexer);
+                let progress = Arc::clone(&progress);
+                let cancel = cancel.clone();
+
+                async move {
+                    if cancel.is_cancelled() {
+                        return None;
+                    }
+
+                    let package =
+                        format!("{}:{}@{}", version.registry, version.name, version.version);
+                    match indexer.embed_stored(&version, &cancel).await {
+                        Ok(result) => {
+                            progress.finish_package(
+                                &package,
+                                &format!("embedded ({} chunks)", result.chunks_indexed),
+                                false,
+                            );
+                            Some(Ok(result.embedding_usage))
+                        }
+                        Err(e) if e.is::<local::IndexCancelled>() => None,
+                        Err(e) => {
+                            progress.finish_package(&package, &format!("failed: {}", e), true);
+                            Some(Err(e))
+                        }
+                    }
+                }
+            }))
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+            progress.finish();
+            outcomes
+        })
+    };
+    let outcomes = task.await.context("Embedding task panicked")?;
+
+    let mut usage = EmbeddingUsage::default();
+    let mut embedded = 0;
+    let mut failed = 0;
+    for outcome in outcomes.into_iter().flatten() {
+        match outcome {
+            Ok(version_usage) => {
+                embedded += 1;
+                usage.add(&version_usage);
+            }
+            Err(_) => failed += 1,
+        }
+    }
+
+    println!("Results:");
+    println!("  {} embedded", embedded);
+    if failed > 0 {
+        println!("  {} failed (still searchable by name)", failed);
+    }
+    if cancel.is_cancelled() {
+        println!("  {} cancelled", total - embedded - failed);
+    }
+    if profile_cost {
+        print_embedding_cost(&usage, indexer.config().embedding_price_per_1k_tokens);
+    }
+
+    Ok(())
+}
+
+/// How a run of [`index_dependencies`] ended.
+pub(super) struct IndexSummary {
+    /// Every package was attempted; false after Ctrl-C
+    pub finished: bool,
+    /// Packages that failed to index
+    pub failed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        init: InitCmd,
+    }
+
+    fn parse(args: &[&str]) -> InitCmd {
+        Cli::parse_from(["idx"].iter().chain(args)).init
+    }
+
+    #[test]
+    fn test_registry_filter_skips_other_ecosystems() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1.0.210\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("web")).unwrap();
+        std::fs::write(
+            dir.path().join("web/package.json"),
+            r#"{"dependencies": {"react": "18.2.0"}}"#,
+        )
+        .unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let names = |cmd: InitCmd| -> Vec<String> {
+            let deps = cmd.collect_dependencies().unwrap();
+            deps.into_iter()
+                .map(|d| format!("{}:{}", d.registry, d.name))
+                .collect()
+        };
+        assert_eq!(
+            names(parse(&[path, "--registry", "crates"])),
+            vec!["crates:serde"]
+        );
+        assert_eq!(
+            names(parse(&[path, "--exclude-registry", "crates,pypi"])),
+            vec!["npm:react"]
+        );
+        let mut all = names(parse(&[path]));
+        all.sort();
+        assert_eq!(all, vec!["crates:serde", "npm:react"]);
+    }
 }
@@ -1,10 +1,32 @@
 //! List command - list all indexed packages.
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use clap::Args;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::local::models::{VersionStatus, compare_versions, version_parts};
+use crate::local::{self, LocalConfig, LocalIndexer};
+use crate::registry::{RegistryClients, RegistryError};
+use crate::types::Registry;
+
+/// Cache of latest registry versions, stored in the index directory.
+const LATEST_CACHE_FILE: &str = "latest_versions.json";
+
+/// How long a cached latest version is trusted, in hours.
+const LATEST_CACHE_TTL_HOURS: i64 = 6;
 
-use crate::local::models::VersionStatus;
-use crate::local::{self, LocalIndexer};
+/// Per-request timeout for registry lookups.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Attempts per registry lookup (with backoff between them).
+const LOOKUP_ATTEMPTS: u32 = 3;
 
 #[derive(Args)]
 pub struct ListCmd {
@@ -12,13 +34,33 @@ pub struct ListCmd {
     #[arg(long, short = 'r')]
     pub registry: Option<String>,
 
-    /// Filter by status (indexed, failed, skipped, pending)
+    /// Filter by status (indexed, metadata_indexed, empty, failed, skipped, pending)
     #[arg(long, short = 's')]
     pub status: Option<String>,
 
     /// Show only package names (no versions)
     #[arg(long)]
     pub names_only: bool,
+
+    /// Show package descriptions, licenses, repositories and entry points
+    #[arg(long, short = 'l', conflicts_with = "names_only")]
+    pub long: bool,
+
+    /// Show indexed packages that are behind the registry's latest version
+    #[arg(long, conflicts_with_all = ["status", "names_only"])]
+    pub outdated: bool,
+
+    /// Group packages by license, flagging denylisted ones
+    #[arg(long, conflicts_with_all = ["status", "names_only", "outdated", "long"])]
+    pub licenses: bool,
+
+    /// Ignore cached latest versions (with --outdated)
+    #[arg(long, requires = "outdated")]
+    pub refresh: bool,
+
+    /// Number of registry lookups to run concurrently (with --outdated)
+    #[arg(long, short = 'j', default_value = "8", requires = "outdated")]
+    pub concurrency: usize,
 }
 
 impl ListCmd {
@@ -28,11 +70,18 @@ impl ListCmd {
 
         let indexer = LocalIndexer::new(&index_dir).await?;
 
+        if self.outdated {
+            return self.run_outdated(&index_dir, &indexer).await;
+        }
+        if self.licenses {
+            return self.run_licenses(&indexer).await;
+        }
+
         // Get versions (optionally filtered by status)
         let versions = if let Some(ref status_str) = self.status {
             let status: VersionStatus = status_str.parse().map_err(|_| {
                 anyhow::anyhow!(
-                    "Invalid status: {}. Use: indexed, failed, skipped, pending",
+                    "Invalid status: {}. Use: indexed, empty, failed, skipped, pending",
                     status_str
                 )
             })?;
@@ -42,11 +91,8 @@ impl ListCmd {
         };
 
         if versions.is_empty() {
-            if self.status.is_some() {
-                println!(
-                    "No packages with status '{}'.",
-                    self.status.as_ref().unwrap()
-                );
+            if let Some(status) = &self.status {
+                println!("No packages with status '{}'.", status);
             } else {
                 println!("No packages indexed yet. Run `idx init` to index your dependencies.");
             }
@@ -80,17 +126,48 @@ impl ListCmd {
                     VersionStatus::Failed => " [failed]",
                     VersionStatus::Skipped => " [skipped]",
                     VersionStatus::Pending => " [pending]",
+                    VersionStatus::Empty => " [empty]",
+                    VersionStatus::MetadataIndexed => " [metadata only]",
                 };
                 println!(
                     "{}:{}@{}{}",
                     ver.registry, ver.name, ver.version, status_str
                 );
 
-                // Show error message for failed packages
-                if status == VersionStatus::Failed
+                if self.long {
+                    if let Some(ref desc) = ver.description {
+                        println!("  {}", desc);
+                    }
+                    let details: Vec<_> = [&ver.license, &ver.repository]
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str)
+                        .collect();
+                    if !details.is_empty() {
+                        println!("  {}", details.join(" · "));
+                    }
+                    if let Some(ref entry_point) = ver.entry_point {
+                        println!("  entry point: {}", entry_point);
+                    }
+                }
+
+                // Show error message for failed packages, or why a
+                // package was skipped automatically
+                if matches!(status, VersionStatus::Failed | VersionStatus::Skipped)
                     && let Some(ref err) = ver.error_message
                 {
-                    println!("  └─ {}", err);
+                    match ver.failure_kind() {
+                        Some(kind) => println!("  └─ {}: {}", kind, err),
+                        None => println!("  └─ {}", err),
+                    }
+                }
+
+                // Show what was seen for packages that yielded no code
+                if status == VersionStatus::Empty {
+                    println!(
+                        "  └─ no code extracted from {} files ({} not parsed)",
+                        ver.files_seen, ver.files_skipped
+                    );
                 }
             }
         }
@@ -101,4 +178,326 @@ impl ListCmd {
 
         Ok(())
     }
+    /// Indexed packages grouped by license, denylisted licenses first.
+    async fn run_licenses(&self, indexer: &LocalIndexer) -> Result<()> {
+        let config = LocalConfig::load()?;
+        let versions = indexer.db().list_versions().await?;
+
+        // license -> "registry:name" of packages under it
+        let mut licenses: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for ver in versions
+            .into_iter()
+            .filter(|v| self.registry.as_ref().is_none_or(|r| &v.registry == r))
+        {
+            let package = format!("{}:{}", ver.registry, ver.name);
+            let packages = licenses
+                .entry(ver.license.unwrap_or_else(|| "(unknown)".to_string()))
+                .or_default();
+            if !packages.contains(&package) {
+                packages.push(package);
+            }
+        }
+
+        if licenses.is_empty() {
+            println!("No packages indexed yet.");
+            return Ok(());
+        }
+
+        let (denied, allowed): (Vec<_>, Vec<_>) = licenses
+            .into_iter()
+            .partition(|(license, _)| config.denies_license(license));
+
+        for (license, packages) in denied.iter().chain(&allowed) {
+            let marker = if config.denies_license(license) {
+                " [denied]"
+            } else {
+                ""
+            };
+            println!("{}{} ({} packages)", license, marker, packages.len());
+            for package in packages {
+                println!("  {}", package);
+            }
+        }
+
+        if !denied.is_empty() {
+            println!(
+                "\n{} licenses match the denylist ({})",
+                denied.len(),
+                config.license_denylist.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Compare the newest indexed version of each package to the registry's latest.
+    async fn run_outdated(&self, index_dir: &Path, indexer: &LocalIndexer) -> Result<()> {
+        let versions = indexer
+            .db()
+            .list_versions_by_status(VersionStatus::Indexed)
+            .await?;
+
+        // (registry, name) -> indexed versions
+        let mut packages: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+        for ver in versions
+            .into_iter()
+            .filter(|v| self.registry.as_ref().is_none_or(|r| &v.registry == r))
+        {
+            packages
+                .entry((ver.registry.clone(), ver.name.clone()))
+                .or_default()
+                .push(ver.version);
+        }
+
+        if packages.is_empty() {
+            println!("No indexed packages.");
+            return Ok(());
+        }
+
+        let cache_path = index_dir.join(LATEST_CACHE_FILE);
+        let mut cache = if self.refresh {
+            LatestCache::default()
+        } else {
+            LatestCache::load(&cache_path)
+        };
+
+        // Look up packages without a fresh cache entry
+        let now = Utc::now();
+        let stale: Vec<_> = packages
+            .keys()
+            .filter(|key| !cache.is_fresh(key, now))
+            .cloned()
+            .collect();
+
+        if !stale.is_empty() {
+            println!("Checking {} packages against registries...", stale.len());
+        }
+
+        let http = &indexer.config().http_client()?;
+        let fetched: Vec<_> = stream::iter(stale)
+            .map(|(registry, name)| async move {
+                let latest = fetch_latest(http, &registry, &name).await;
+                ((registry, name), latest)
+            })
+            .buffer_unordered(self.concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut lookup_failures = 0;
+        for (key, latest) in fetched {
+            match latest {
+                Ok(latest) => cache.insert(&key, latest, now),
+                Err(e) => {
+                    lookup_failures += 1;
+                    eprintln!("  {}:{} -> lookup failed: {:#}", key.0, key.1, e);
+                }
+            }
+        }
+
+        if let Err(e) = cache.save(&cache_path) {
+            eprintln!("Warning: failed to cache latest versions: {:#}", e);
+        }
+
+        // Collect packages whose newest indexed version is behind
+        let mut outdated = Vec::new();
+        for ((registry, name), mut indexed) in packages {
+            let Some(latest) = cache.latest(&(registry.clone(), name.clone())) else {
+                continue;
+            };
+            indexed.sort_by(|a, b| compare_versions(b, a));
+            if let Some(delta) = behind_by(&indexed[0], latest) {
+                outdated.push((
+                    format!("{}:{}", registry, name),
+                    indexed.join(", "),
+                    latest.to_string(),
+                    delta,
+                ));
+            }
+        }
+
+        if outdated.is_empty() {
+            println!("All indexed packages are up to date.");
+        } else {
+            let w_pkg = column_width("PACKAGE", outdated.iter().map(|r| &r.0));
+            let w_idx = column_width("INDEXED", outdated.iter().map(|r| &r.1));
+            let w_latest = column_width("LATEST", outdated.iter().map(|r| &r.2));
+
+            println!(
+                "{:<w_pkg$}  {:<w_idx$}  {:<w_latest$}  BEHIND",
+                "PACKAGE", "INDEXED", "LATEST"
+            );
+            for (package, indexed, latest, delta) in &outdated {
+                println!(
+                    "{:<w_pkg$}  {:<w_idx$}  {:<w_latest$}  {}",
+                    package, indexed, latest, delta
+                );
+            }
+            println!("\n{} outdated packages", outdated.len());
+            println!("Run `idx update` after bumping versions in your manifests.");
+        }
+
+        if lookup_failures > 0 {
+            println!("{} packages could not be checked", lookup_failures);
+        }
+
+        Ok(())
+    }
+}
+
+/// Latest registry versions with the time they were fetched.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LatestCache {
+    /// "registry:name" -> entry
+    entries: HashMap<String, CachedLatest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedLatest {
+    latest: Option<String>,
+    fetched_at: DateTime<Utc>,
+}
+
+impl LatestCache {
+    /// Load the cache, starting empty if it is missing or unreadable.
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content).context("Failed to write latest version cache")
+    }
+
+    fn cache_key((registry, name): &(String, String)) -> String {
+        format!("{}:{}", registry, name)
+    }
+
+    fn is_fresh(&self, key: &(String, String), now: DateTime<Utc>) -> bool {
+        self.entries
+            .get(&Self::cache_key(key))
+            .is_some_and(|e| now - e.fetched_at < chrono::Duration::hours(LATEST_CACHE_TTL_HOURS))
+    }
+
+    fn insert(&mut self, key: &(String, String), latest: Option<String>, now: DateTime<Utc>) {
+        self.entries.insert(
+            Self::cache_key(key),
+            CachedLatest {
+                latest,
+                fetched_at: now,
+            },
+        );
+    }
+
+    fn latest(&self, key: &(String, String)) -> Option<&str> {
+        self.entries
+            .get(&Self::cache_key(key))
+            .and_then(|e| e.latest.as_deref())
+    }
+}
+
+/// Fetch a package's latest version, with a timeout and retries.
+async fn fetch_latest(
+    http: &reqwest::Client,
+    registry: &str,
+    name: &str,
+) -> Result<Option<String>> {
+    let registry = Registry::from_str(registry).map_err(|e| anyhow::anyhow!(e))?;
+    let client =
+        RegistryClients::with_http_client(registry, http.clone()).with_offline(local::is_offline());
+
+    let mut last_err = None;
+    for attempt in 0..LOOKUP_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt - 1))).await;
+        }
+
+        match tokio::time::timeout(LOOKUP_TIMEOUT, client.get_package(name)).await {
+            Ok(Ok(info)) => return Ok(info.latest_version),
+            Ok(Err(RegistryError::OfflineMode)) => return Err(RegistryError::OfflineMode.into()),
+            Ok(Err(e)) => last_err = Some(anyhow::Error::from(e)),
+            Err(_) => last_err = Some(anyhow::anyhow!("timed out after {:?}", LOOKUP_TIMEOUT)),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("lookup failed")))
+}
+
+/// How far `indexed` is behind `latest` ("+2 major", "+1 minor", ...),
+/// or None if it is current.
+fn behind_by(indexed: &str, latest: &str) -> Option<String> {
+    if indexed == latest {
+        return None;
+    }
+
+    let (Some(i), Some(l)) = (version_parts(indexed), version_parts(latest)) else {
+        return Some("unknown".to_string());
+    };
+
+    if l <= i {
+        return None;
+    }
+
+    Some(if l[0] > i[0] {
+        format!("+{} major", l[0] - i[0])
+    } else if l[1] > i[1] {
+        format!("+{} minor", l[1] - i[1])
+    } else {
+        format!("+{} patch", l[2] - i[2])
+    })
+}
+
+fn column_width<'a>(header: &str, values: impl Iterator<Item = &'a String>) -> usize {
+    values.map(|v| v.len()).max().unwrap_or(0).max(header.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_behind_by() {
+        assert_eq!(behind_by("1.2.3", "1.2.3"), None);
+        assert_eq!(behind_by("1.2.3", "3.0.0"), Some("+2 major".to_string()));
+        assert_eq!(behind_by("1.2.3", "1.5.0"), Some("+3 minor".to_string()));
+        assert_eq!(behind_by("1.2.3", "1.2.7"), Some("+4 patch".to_string()));
+        assert_eq!(behind_by("v1.2.0", "v1.3.0"), Some("+1 minor".to_string()));
+        // Ahead of latest (e.g. a pre-release) is not outdated
+        assert_eq!(behind_by("2.0.0", "1.9.9"), None);
+        assert_eq!(behind_by("abc", "def"), Some("unknown".to_string()));
+    }
+
+    #[test]
+    fn test_newest_indexed_version() {
+        let mut versions = [
+            "1.10.0".to_string(),
+            "1.9.2".to_string(),
+            "1.2.0".to_string(),
+        ];
+        versions.sort_by(|a, b| compare_versions(b, a));
+        assert_eq!(versions[0], "1.10.0");
+    }
+
+    #[test]
+    fn test_latest_cache_freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LATEST_CACHE_FILE);
+        let key = ("npm".to_string(), "react".to_string());
+        let now = Utc::now();
+
+        let mut cache = LatestCache::default();
+        cache.insert(
+            &key,
+            Some("19.0.0".to_string()),
+            now - chrono::Duration::hours(1),
+        );
+        cache.save(&path).unwrap();
+
+        let cache = LatestCache::load(&path);
+        assert!(cache.is_fresh(&key, now));
+        assert!(!cache.is_fresh(&key, now + chrono::Duration::hours(LATEST_CACHE_TTL_HOURS)));
+        assert_eq!(cache.latest(&key), Some("19.0.0"));
+    }
 }
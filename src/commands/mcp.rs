@@ -7,10 +7,23 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use crate::local;
 
 #[derive(Args)]
-pub struct McpCmd;
+pub struct McpCmd {
+    /// Print the JSON Schema of every tool's input and output, then exit
+    /// without starting the server
+    #[arg(long)]
+    pub dump_schema: bool,
+}
 
 impl McpCmd {
     pub async fn run(&self) -> Result<()> {
+        if self.dump_schema {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&local::mcp::tool_schemas())?
+            );
+            return Ok(());
+        }
+
         // Logging to stderr (stdout is for MCP protocol)
         tracing_subscriber::registry()
             .with(
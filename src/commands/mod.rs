@@ -1,31 +1,44 @@
 //! CLI command implementations.
 
+mod bench;
+mod cat;
 mod clean;
 mod config;
+mod diff;
 mod index;
 mod init;
 mod list;
 mod mcp;
+mod npm_deps;
+mod progress;
 mod prune;
+mod reindex;
 mod remove;
 mod retry;
+mod run_args;
 mod search;
+mod serve;
 mod skip;
 mod stats;
 mod status;
 mod update;
 mod watch;
 
+pub use bench::BenchCmd;
+pub use cat::CatCmd;
 pub use clean::CleanCmd;
 pub use config::ConfigCmd;
+pub use diff::DiffCmd;
 pub use index::IndexCmd;
 pub use init::InitCmd;
 pub use list::ListCmd;
 pub use mcp::McpCmd;
 pub use prune::PruneCmd;
+pub use reindex::ReindexCmd;
 pub use remove::RemoveCmd;
 pub use retry::RetryCmd;
 pub use search::SearchCmd;
+pub use serve::ServeCmd;
 pub use skip::SkipCmd;
 pub use stats::StatsCmd;
 pub use status::StatusCmd;
@@ -0,0 +1,30 @@
+//! Shared `--include-peer` / `--include-optional` flags for commands that
+//! read npm manifests.
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::local::LocalConfig;
+use crate::manifests::NpmOptions;
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct NpmDepsArgs {
+    /// Also index npm peerDependencies (default: config `npm_include_peer`)
+    #[arg(long)]
+    pub include_peer: bool,
+
+    /// Also index npm optionalDependencies (default: config `npm_include_optional`)
+    #[arg(long)]
+    pub include_optional: bool,
+}
+
+impl NpmDepsArgs {
+    /// Flags merged with the config; either one can enable a section.
+    pub fn options(&self) -> Result<NpmOptions> {
+        let config = LocalConfig::load()?.npm_options();
+        Ok(NpmOptions {
+            include_peer: self.include_peer || config.include_peer,
+            include_optional: self.include_optional || config.include_optional,
+        })
+    }
+}
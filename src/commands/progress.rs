@@ -0,0 +1,116 @@
+//! Progress reporting for commands that index many packages.
+//!
+//! Draws a progress bar on a terminal, prints one line per package when
+//! stdout is redirected (CI logs), or stays silent with `--quiet`.
+
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use tokio::sync::mpsc;
+
+use crate::local::IndexEvent;
+
+enum Output {
+    Bar(ProgressBar),
+    Lines,
+    Quiet,
+}
+
+/// Reports packages completed out of a total, the package currently being
+/// worked on and a running failure count.
+pub struct Progress {
+    output: Output,
+    total: usize,
+    done: AtomicUsize,
+    failed: AtomicUsize,
+    verbose: bool,
+}
+
+impl Progress {
+    /// Create a reporter for `total` packages.
+    ///
+    /// `verbose` also prints a line per package above the bar, or to stderr
+    /// when `quiet`.
+    pub fn new(total: usize, quiet: bool, verbose: bool) -> Arc<Self> {
+        let output = if quiet {
+            Output::Quiet
+        } else if std::io::stdout().is_terminal() {
+            let bar =
+                ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout());
+            bar.set_style(
+                ProgressStyle::with_template(
+                    "{spinner} [{bar:30}] {pos}/{len} {prefix} {wide_msg}",
+                )
+                .expect("valid progress template")
+                .progress_chars("=> "),
+            );
+            bar.enable_steady_tick(Duration::from_millis(120));
+            Output::Bar(bar)
+        } else {
+            Output::Lines
+        };
+
+        Arc::new(Self {
+            output,
+            total,
+            done: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            verbose,
+        })
+    }
+
+    /// Show pipeline events as the current package until `events` closes.
+    pub fn watch(self: &Arc<Self>, mut events: mpsc::UnboundedReceiver<IndexEvent>) {
+        let progress = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Output::Bar(bar) = &progress.output {
+                    bar.set_message(format!(
+                        "{}:{}@{} ({})",
+                        event.registry, event.name, event.version, event.phase
+                    ));
+                }
+            }
+        });
+    }
+
+    /// Record a finished package with a short outcome, e.g. "indexed (12 chunks)".
+    pub fn finish_package(&self, package: &str, outcome: &str, failed: bool) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let failed_count = if failed {
+            self.failed.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            self.failed.load(Ordering::Relaxed)
+        };
+
+        match &self.output {
+            Output::Bar(bar) => {
+                if failed_count > 0 {
+                    bar.set_prefix(format!("{} failed", failed_count));
+                }
+                if self.verbose {
+                    bar.println(format!("  {} -> {}", package, outcome));
+                }
+                bar.inc(1);
+            }
+            Output::Lines => {
+                println!("[{}/{}] {} -> {}", done, self.total, package, outcome);
+            }
+            Output::Quiet => {
+                if self.verbose {
+                    eprintln!("  {} -> {}", package, outcome);
+                }
+            }
+        }
+    }
+
+    /// Remove the bar before printing a summary.
+    pub fn finish(&self) {
+        if let Output::Bar(bar) = &self.output {
+            bar.finish_and_clear();
+        }
+    }
+}
@@ -1,16 +1,17 @@
-//! Prune command - remove packages no longer in manifests.
+//! Prune command - remove packages no longer in manifests, failed versions,
+//! or versions beyond the newest few of each package.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
 use clap::Args;
 
+use super::npm_deps::NpmDepsArgs;
+use crate::local::models::{VersionStatus, VersionWithPackage, compare_versions};
 use crate::local::{self, LocalIndexer};
-use crate::manifests::{
-    discover_manifest_dirs, parse_cargo_deps, parse_go_deps, parse_maven_deps, parse_npm_deps,
-    parse_python_deps,
-};
+use crate::manifests::{Dependency, collect_deps};
 
 #[derive(Args)]
 pub struct PruneCmd {
@@ -25,6 +26,23 @@ pub struct PruneCmd {
     /// Only show what would be removed (dry run)
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Remove versions that failed to index instead of checking manifests
+    #[arg(long)]
+    pub failed: bool,
+
+    /// With --failed, only remove versions that failed longer ago than this (e.g. 7d, 12h)
+    #[arg(long, requires = "failed", value_parser = parse_duration)]
+    pub older_than: Option<Duration>,
+
+    /// Keep only the newest N indexed versions of each package, removing
+    /// older ones no manifest references
+    #[arg(long, value_name = "N", conflicts_with = "failed",
+          value_parser = clap::value_parser!(u32).range(1..))]
+    pub keep_latest: Option<u32>,
+
+    #[command(flatten)]
+    pub npm: NpmDepsArgs,
 }
 
 impl PruneCmd {
@@ -34,39 +52,27 @@ impl PruneCmd {
 
         let indexer = LocalIndexer::new(&index_dir).await?;
 
+        if self.failed {
+            return self.prune_failed(&indexer).await;
+        }
+        if let Some(keep) = self.keep_latest {
+            return self.prune_old_versions(&indexer, keep as usize).await;
+        }
+
         // Get indexed versions
         let indexed_versions = indexer.db().list_versions().await?;
-
-        // Get manifest dependencies from all discovered roots
-        let manifest_dirs = discover_manifest_dirs(&self.path)?;
-        let mut manifest_deps = Vec::new();
-
-        for dir in &manifest_dirs {
-            if let Ok(deps) = parse_npm_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_cargo_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_python_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_maven_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_go_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-        }
+        let manifest_deps = self.manifest_deps()?;
 
         let manifest_set: HashSet<(String, String)> = manifest_deps
             .iter()
             .map(|d| (d.registry.clone(), d.name.clone()))
             .collect();
 
-        // Find versions to prune (indexed but not in manifest)
+        // Find versions to prune (indexed but not in manifest); private
+        // packages are never manifest dependencies, so they are kept
         let to_prune: Vec<_> = indexed_versions
             .iter()
+            .filter(|v| v.tenant().is_none())
             .filter(|v| !manifest_set.contains(&(v.registry.clone(), v.name.clone())))
             .collect();
 
@@ -80,9 +86,100 @@ impl PruneCmd {
             println!("  {}:{}@{}", ver.registry, ver.name, ver.version);
         }
 
+        let removed = self.remove_versions(&indexer, &to_prune).await?;
+        if let Some(removed) = removed {
+            println!("\nRemoved {} packages.", removed);
+        }
+
+        Ok(())
+    }
+
+    /// Dependencies from every manifest under `path`.
+    fn manifest_deps(&self) -> Result<Vec<Dependency>> {
+        collect_deps(&self.path, self.npm.options()?)
+    }
+
+    /// Remove versions beyond the newest `keep` of each package.
+    async fn prune_old_versions(&self, indexer: &LocalIndexer, keep: usize) -> Result<()> {
+        let mut versions = indexer.db().list_versions().await?;
+        versions.retain(|v| v.tenant().is_none());
+        let referenced = referenced_versions(&self.manifest_deps()?);
+        let to_prune = beyond_latest(&versions, keep, &referenced);
+
+        if to_prune.is_empty() {
+            println!(
+                "Nothing to prune. No package has more than {} indexed versions.",
+                keep
+            );
+            return Ok(());
+        }
+
+        println!(
+            "Versions beyond the newest {} to remove ({}):",
+            keep,
+            to_prune.len()
+        );
+        for ver in &to_prune {
+            println!("  {}:{}@{}", ver.registry, ver.name, ver.version);
+        }
+
+        let removed = self.remove_versions(indexer, &to_prune).await?;
+        if let Some(removed) = removed {
+            println!("\nPruned {} old versions.", removed);
+        }
+
+        Ok(())
+    }
+
+    /// Remove versions stuck in `failed` status.
+    async fn prune_failed(&self, indexer: &LocalIndexer) -> Result<()> {
+        let failed = indexer
+            .db()
+            .list_versions_by_status(VersionStatus::Failed)
+            .await?;
+
+        let cutoff = self.older_than.map(|d| Utc::now() - d);
+        let to_prune: Vec<_> = failed
+            .iter()
+            .filter(|v| cutoff.is_none_or(|c| failed_before(v, c)))
+            .collect();
+
+        if to_prune.is_empty() {
+            println!("Nothing to prune. No failed packages match.");
+            return Ok(());
+        }
+
+        println!("Failed packages to remove ({}):", to_prune.len());
+        for ver in &to_prune {
+            println!(
+                "  {}:{}@{} ({} attempts): {}",
+                ver.registry,
+                ver.name,
+                ver.version,
+                ver.attempts,
+                ver.error_message.as_deref().unwrap_or("unknown error")
+            );
+        }
+
+        let removed = self.remove_versions(indexer, &to_prune).await?;
+        if let Some(removed) = removed {
+            println!("\nPruned {} failed packages.", removed);
+        }
+
+        Ok(())
+    }
+
+    /// Confirm, then delete versions with their namespaces and blobs.
+    ///
+    /// Returns `None` if nothing was removed (dry run or aborted).
+    async fn remove_versions(
+        &self,
+        indexer: &LocalIndexer,
+        to_prune: &[&VersionWithPackage],
+    ) -> Result<Option<usize>> {
         if self.dry_run {
             println!("\n(dry run - nothing removed)");
-            return Ok(());
+            return Ok(None);
         }
 
         if !self.yes {
@@ -94,32 +191,185 @@ impl PruneCmd {
 
             if !input.trim().eq_ignore_ascii_case("y") {
                 println!("Aborted.");
-                return Ok(());
+                return Ok(None);
             }
         }
 
         // Remove versions
         let mut removed = 0;
-        for ver in &to_prune {
-            // Delete from db
-            let namespaces = indexer.db().delete_version(&ver.version_id).await?;
+        for ver in to_prune {
+            delete_version(indexer, ver).await?;
+            removed += 1;
+        }
 
-            // Delete from vector store
-            for ns in &namespaces {
-                indexer.vectors().delete_namespace(ns).await?;
-            }
+        Ok(Some(removed))
+    }
+}
 
-            // Delete from blob storage
-            indexer
-                .storage()
-                .delete_package(&ver.registry, &ver.name, &ver.version)
-                .await?;
+/// Delete a version with its namespaces, vectors and blobs.
+pub(super) async fn delete_version(indexer: &LocalIndexer, ver: &VersionWithPackage) -> Result<()> {
+    // Delete from db
+    let namespaces = indexer.db().delete_version(&ver.version_id).await?;
 
-            removed += 1;
+    // Delete from vector store
+    for ns in &namespaces {
+        indexer.vectors().delete_namespace(ns).await?;
+    }
+
+    // Delete from blob storage
+    indexer
+        .storage()
+        .delete_package(&ver.namespace().to_string())
+        .await
+}
+
+/// `(registry, name, version)` of every manifest dependency.
+pub(super) fn referenced_versions(deps: &[Dependency]) -> HashSet<(String, String, String)> {
+    deps.iter()
+        .map(|d| (d.registry.clone(), d.name.clone(), d.version.clone()))
+        .collect()
+}
+
+/// Indexed versions beyond the newest `keep` of their package.
+///
+/// Versions are ranked by version number, as for `latest` searches. Only
+/// indexed and empty versions count; failed ones have `--failed`. Versions
+/// in `referenced` are never returned, since a manifest still depends on
+/// them.
+pub(super) fn beyond_latest<'a>(
+    versions: &'a [VersionWithPackage],
+    keep: usize,
+    referenced: &HashSet<(String, String, String)>,
+) -> Vec<&'a VersionWithPackage> {
+    let mut by_package: BTreeMap<(&str, &str), Vec<&VersionWithPackage>> = BTreeMap::new();
+    for ver in versions
+        .iter()
+        .filter(|v| matches!(v.status(), VersionStatus::Indexed | VersionStatus::Empty))
+    {
+        by_package
+            .entry((&ver.registry, &ver.name))
+            .or_default()
+            .push(ver);
+    }
+
+    let mut old = Vec::new();
+    for (_, mut package_versions) in by_package {
+        package_versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        old.extend(package_versions.into_iter().skip(keep).filter(|v| {
+            !referenced.contains(&(v.registry.clone(), v.name.clone(), v.version.clone()))
+        }));
+    }
+    old
+}
+
+/// Whether a failed version last failed before `cutoff`.
+///
+/// Versions that failed before failure times were recorded count as old.
+fn failed_before(ver: &VersionWithPackage, cutoff: DateTime<Utc>) -> bool {
+    ver.failed_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .is_none_or(|t| t < cutoff)
+}
+
+/// Parse a duration like "30m", "12h", "7d" or "2w".
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in '{}' (use m, h, d or w)", s))?;
+    let (amount, unit) = s.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration: '{}'", s))?;
+
+    let duration = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => return Err(format!("unknown unit '{}' (use m, h, d or w)", unit)),
+    };
+
+    duration.ok_or_else(|| format!("duration out of range: '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::weeks(2));
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    fn version(name: &str, version: &str, status: VersionStatus) -> VersionWithPackage {
+        VersionWithPackage {
+            version_id: format!("{}-{}", name, version),
+            version: version.to_string(),
+            status: status.to_string(),
+            error_message: None,
+            chunk_count: 1,
+            indexed_at: None,
+            attempts: 1,
+            failed_at: None,
+            failure_kind: None,
+            files_seen: 1,
+            files_skipped: 0,
+            entry_point: None,
+            package_id: name.to_string(),
+            registry: "npm".to_string(),
+            name: name.to_string(),
+            description: None,
+            license: None,
+            repository: None,
+            tenant: String::new(),
         }
+    }
 
-        println!("\nRemoved {} packages.", removed);
+    fn names(versions: &[&VersionWithPackage]) -> Vec<String> {
+        versions
+            .iter()
+            .map(|v| format!("{}@{}", v.name, v.version))
+            .collect()
+    }
 
-        Ok(())
+    #[test]
+    fn test_beyond_latest_keeps_newest() {
+        let versions: Vec<_> = ["1.0.0", "1.10.0", "1.2.0", "2.0.0", "1.1.0"]
+            .iter()
+            .map(|v| version("lodash", v, VersionStatus::Indexed))
+            .chain([version("react", "18.0.0", VersionStatus::Indexed)])
+            .collect();
+
+        let old = beyond_latest(&versions, 2, &HashSet::new());
+
+        assert_eq!(
+            names(&old),
+            vec!["lodash@1.2.0", "lodash@1.1.0", "lodash@1.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_beyond_latest_skips_referenced_and_failed() {
+        let versions = vec![
+            version("lodash", "1.0.0", VersionStatus::Indexed),
+            version("lodash", "1.1.0", VersionStatus::Indexed),
+            version("lodash", "1.2.0", VersionStatus::Empty),
+            version("lodash", "3.0.0", VersionStatus::Failed),
+            version("lodash", "2.0.0", VersionStatus::Indexed),
+        ];
+        let referenced =
+            HashSet::from([("npm".to_string(), "lodash".to_string(), "1.0.0".to_string())]);
+
+        let old = beyond_latest(&versions, 1, &referenced);
+
+        assert_eq!(names(&old), vec!["lodash@1.2.0", "lodash@1.1.0"]);
     }
 }
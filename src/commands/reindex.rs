@@ -0,0 +1,237 @@
+//! Reindex command - rebuild already indexed packages with the current
+//! pipeline and embedding profile.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::types::Registry;
+use anyhow::{Context, Result};
+use clap::Args;
+use futures::stream::{self, StreamExt};
+use tokio::sync::mpsc;
+
+use super::clean::confirm;
+use super::index::parse_package_spec;
+use super::progress::Progress;
+use super::run_args::RunArgs;
+use crate::local::models::{VersionStatus, VersionWithPackage};
+use crate::local::{self, LocalIndexer};
+
+#[derive(Args)]
+pub struct ReindexCmd {
+    /// Package to reindex: registry:name[@version] (every indexed version if omitted)
+    #[arg(conflicts_with_all = ["registry", "all"], required_unless_present_any = ["registry", "all"])]
+    pub package: Option<String>,
+
+    /// Reindex every package from this registry (npm, crates, pypi, ...)
+    #[arg(short, long, conflicts_with = "all")]
+    pub registry: Option<String>,
+
+    /// Reindex every package in the index
+    #[arg(long)]
+    pub all: bool,
+
+    /// Skip confirmation prompt
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Show detailed per-package results
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Number of packages to index concurrently
+    #[arg(long, short = 'j', default_value = "4")]
+    pub concurrency: usize,
+}
+
+impl ReindexCmd {
+    pub async fn run(&self) -> Result<()> {
+        let index_dir =
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let indexer = Arc::new(
+            LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+                .await?
+                .with_limits(self.run.max_files, self.run.max_chunks)
+                .with_events(events),
+        );
+
+        let versions: Vec<_> = indexer
+            .db()
+            .list_versions()
+            .await?
+            .into_iter()
+            // Private packages are rebuilt with `idx index --tenant`
+            .filter(|v| v.tenant().is_none())
+            .filter(|v| matches!(v.status(), VersionStatus::Indexed | VersionStatus::Empty))
+            .collect();
+        let selected = self.select(&versions)?;
+
+        if selected.is_empty() {
+            println!("No indexed packages match.");
+            return Ok(());
+        }
+
+        // A new embedding dimension is only allowed when every embedded
+        // version is rebuilt; otherwise the index would mix dimensions
+        let selected_ids: HashSet<&str> = selected.iter().map(|v| v.version_id.as_str()).collect();
+        let covers_index = versions
+            .iter()
+            .filter(|v| v.chunk_count > 0)
+            .all(|v| selected_ids.contains(v.version_id.as_str()));
+        if !covers_index {
+            indexer.check_embedding_dimensions().await?;
+        }
+
+        if !self.yes
+            && !confirm(&format!(
+                "This will delete and rebuild the chunks and vectors of {} package versions.",
+                selected.len()
+            ))?
+        {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        let indexed = Arc::new(AtomicUsize::new(0));
+        let empty = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let total = selected.len();
+        let concurrency = self.concurrency.max(1);
+        let cancel = local::cancel_on_ctrl_c();
+        // Set by the first failure
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        println!(
+            "Reindexing {} packages with {} concurrent workers...",
+            total, concurrency
+        );
+
+        let progress = Progress::new(total, self.run.quiet, self.verbose);
+        progress.watch(event_rx);
+
+        stream::iter(selected.into_iter().map(|ver| {
+            let indexer = Arc::clone(&indexer);
+            let indexed = Arc::clone(&indexed);
+            let empty = Arc::clone(&empty);
+            let failed = Arc::clone(&failed);
+            let progress = Arc::clone(&progress);
+            let cancel = cancel.clone();
+            let stopped = Arc::clone(&stopped);
+
+            async move {
+                // Don't start new work once cancelled or after a failure
+                if cancel.is_cancelled() || stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                let package = format!("{}:{}@{}", ver.registry, ver.name, ver.version);
+                let fail = |message: String| {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    stopped.store(true, Ordering::Relaxed);
+                    progress.finish_package(&package, &message, true);
+                };
+
+                let registry = match Registry::from_str(&ver.registry) {
+                    Ok(r) => r,
+                    Err(e) => return fail(format!("error: {}", e)),
+                };
+
+                // Reset just before rebuilding, so a stop leaves the
+                // versions after it untouched
+                if let Err(e) = indexer.reset_version(&ver).await {
+                    return fail(format!("reset failed: {:#}", e));
+                }
+
+                match indexer
+                    .index_package_with_cancel(registry, &ver.name, &ver.version, &cancel)
+                    .await
+                {
+                    Ok(result) if result.is_empty() => {
+                        empty.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(&package, "no code extracted", false);
+                    }
+                    Ok(result) => {
+                        indexed.fetch_add(1, Ordering::Relaxed);
+                        progress.finish_package(
+                            &package,
+                            &format!(
+                                "reindexed ({} chunks{})",
+                                result.chunks_indexed,
+                                if result.truncated { ", truncated" } else { "" }
+                            ),
+                            false,
+                        );
+                    }
+                    Err(e) if e.is::<local::IndexCancelled>() => {}
+                    Err(e) => fail(format!("failed: {}", e)),
+                }
+            }
+        }))
+        .buffer_unordered(concurrency)
+        .collect::<Vec<()>>()
+        .await;
+
+        progress.finish();
+
+        let indexed = indexed.load(Ordering::Relaxed);
+        let empty = empty.load(Ordering::Relaxed);
+        let failed = failed.load(Ordering::Relaxed);
+        let remaining = total - indexed - empty - failed;
+
+        println!("Results:");
+        println!("  {} reindexed", indexed);
+        if empty > 0 {
+            println!("  {} empty (no code extracted)", empty);
+        }
+        if failed > 0 {
+            println!("  {} failed (see `idx list -s failed`)", failed);
+        }
+        if cancel.is_cancelled() {
+            println!(
+                "  {} cancelled (any already reset are left pending, run `idx update` to finish)",
+                remaining
+            );
+        } else if remaining > 0 {
+            println!("  {} not started (stopped at the first failure)", remaining);
+        }
+
+        if failed > 0 {
+            anyhow::bail!("Reindex stopped: {} packages failed", failed);
+        }
+        Ok(())
+    }
+
+    /// Versions chosen by the package spec, `--registry` or `--all`.
+    fn select(&self, versions: &[VersionWithPackage]) -> Result<Vec<VersionWithPackage>> {
+        let selected: Vec<_> = match (&self.package, &self.registry) {
+            (Some(spec), _) => {
+                let (registry, name, version) = parse_package_spec(spec)?;
+                versions
+                    .iter()
+                    .filter(|v| v.registry == registry && v.name == name)
+                    .filter(|v| version.as_ref().is_none_or(|ver| &v.version == ver))
+                    .cloned()
+                    .collect()
+            }
+            (None, Some(registry)) => {
+                let registry = Registry::from_str(registry)
+                    .map_err(|e| anyhow::anyhow!("Unknown registry '{}': {}", registry, e))?;
+                versions
+                    .iter()
+                    .filter(|v| v.registry == registry.as_str())
+                    .cloned()
+                    .collect()
+            }
+            (None, None) => versions.to_vec(),
+        };
+
+        Ok(selected)
+    }
+}
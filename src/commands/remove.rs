@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use clap::Args;
 
 use crate::local::{self, LocalIndexer};
+use crate::types::TenantId;
 
 #[derive(Args)]
 pub struct RemoveCmd {
@@ -13,6 +14,10 @@ pub struct RemoveCmd {
     /// Skip confirmation prompt
     #[arg(long, short = 'y')]
     pub yes: bool,
+
+    /// Private package of this tenant (a UUID) rather than the public one
+    #[arg(long)]
+    pub tenant: Option<TenantId>,
 }
 
 impl RemoveCmd {
@@ -28,7 +33,7 @@ impl RemoveCmd {
         // Find the version
         let ver = indexer
             .db()
-            .find_version(&registry, &name, &version)
+            .find_version(&registry, &name, &version, self.tenant)
             .await?
             .context(format!(
                 "Package not found: {}:{}@{}",
@@ -60,7 +65,7 @@ impl RemoveCmd {
         // Delete from blob storage
         indexer
             .storage()
-            .delete_package(&registry, &name, &version)
+            .delete_package(&ver.namespace().to_string())
             .await?;
 
         println!("Removed {}:{}@{}", registry, name, version);
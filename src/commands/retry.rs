@@ -5,6 +5,7 @@ use clap::Args;
 
 use crate::local::models::VersionStatus;
 use crate::local::{self, LocalIndexer};
+use crate::types::TenantId;
 
 #[derive(Args)]
 pub struct RetryCmd {
@@ -13,8 +14,21 @@ pub struct RetryCmd {
     pub package: Option<String>,
 
     /// Retry all failed packages
-    #[arg(long)]
+    #[arg(long, visible_alias = "failed")]
     pub all: bool,
+
+    /// With --all, give up on packages that have already failed this many times
+    #[arg(long, requires = "all")]
+    pub max_attempts: Option<i32>,
+
+    /// With --all, only retry failures that may be transient (download,
+    /// embedding, storage) and leave permanent ones such as missing sources
+    #[arg(long, requires = "all")]
+    pub transient: bool,
+
+    /// Private package of this tenant (a UUID) rather than the public one
+    #[arg(long, conflicts_with = "all")]
+    pub tenant: Option<TenantId>,
 }
 
 impl RetryCmd {
@@ -26,31 +40,68 @@ impl RetryCmd {
 
         if self.all {
             // Retry all failed packages
-            let failed = indexer
+            // Private packages are retried with `idx index --tenant`
+            let mut failed = indexer
                 .db()
                 .list_versions_by_status(VersionStatus::Failed)
                 .await?;
+            failed.retain(|v| v.tenant().is_none());
 
             if failed.is_empty() {
                 println!("No failed packages to retry.");
                 return Ok(());
             }
 
-            for ver in &failed {
+            // Failures recorded before kinds were tracked count as transient
+            let (permanent, failed): (Vec<_>, Vec<_>) = failed.iter().partition(|v| {
+                self.transient && v.failure_kind().is_some_and(|k| !k.is_transient())
+            });
+            let (exhausted, retryable): (Vec<_>, Vec<_>) = failed
+                .into_iter()
+                .partition(|v| self.max_attempts.is_some_and(|max| v.attempts >= max));
+
+            for ver in &retryable {
                 indexer.db().mark_version_pending(&ver.version_id).await?;
             }
 
             println!(
                 "Marked {} packages for retry. Run `idx update` to reindex.",
-                failed.len()
+                retryable.len()
             );
+
+            if !exhausted.is_empty() {
+                println!(
+                    "Gave up on {} packages after {} attempts:",
+                    exhausted.len(),
+                    self.max_attempts.unwrap_or_default()
+                );
+                for ver in &exhausted {
+                    println!("  {}:{}@{}", ver.registry, ver.name, ver.version);
+                }
+                println!("Run `idx prune --failed` to remove them.");
+            }
+
+            if !permanent.is_empty() {
+                println!("Left {} packages with permanent failures:", permanent.len());
+                for ver in &permanent {
+                    println!(
+                        "  {}:{}@{} ({})",
+                        ver.registry,
+                        ver.name,
+                        ver.version,
+                        ver.failure_kind()
+                            .map(|k| k.to_string())
+                            .unwrap_or_default()
+                    );
+                }
+            }
         } else if let Some(ref spec) = self.package {
             // Retry specific package
             let (registry, name, version) = parse_package_spec(spec)?;
 
             let ver = indexer
                 .db()
-                .find_version(&registry, &name, &version)
+                .find_version(&registry, &name, &version, self.tenant)
                 .await?
                 .context(format!(
                     "Package not found: {}:{}@{}",
@@ -59,10 +110,16 @@ impl RetryCmd {
 
             indexer.db().mark_version_pending(&ver.version_id).await?;
 
-            println!(
-                "Marked {}:{}@{} for retry. Run `idx update` to reindex.",
-                registry, name, version
-            );
+            match self.tenant {
+                Some(tenant) => println!(
+                    "Marked {}:{}@{} for retry. Run `idx index {}:{}@{} --tenant {}` to reindex.",
+                    registry, name, version, registry, name, version, tenant
+                ),
+                None => println!(
+                    "Marked {}:{}@{} for retry. Run `idx update` to reindex.",
+                    registry, name, version
+                ),
+            }
         } else {
             anyhow::bail!("Specify a package to retry or use --all to retry all failed packages.");
         }
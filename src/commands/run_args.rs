@@ -0,0 +1,89 @@
+//! Shared per-run flags for commands that index or query with an embedding
+//! profile.
+
+use clap::Args;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct ProfileArgs {
+    /// Embedding profile to use for this run (overrides the active profile)
+    #[arg(long)]
+    pub profile: Option<String>,
+}
+
+impl ProfileArgs {
+    /// The profile named on the command line, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct RunArgs {
+    /// Maximum source files parsed per package (overrides config, 0 = unlimited)
+    #[arg(long)]
+    pub max_files: Option<usize>,
+
+    /// Maximum chunks embedded per package (overrides config, 0 = unlimited)
+    #[arg(long)]
+    pub max_chunks: Option<usize>,
+
+    #[command(flatten)]
+    pub profile: ProfileArgs,
+
+    /// Don't report progress (for CI logs)
+    #[arg(long, short = 'q')]
+    pub quiet: bool,
+}
+
+impl RunArgs {
+    /// The profile named on the command line, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.profile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{IndexCmd, InitCmd, ReindexCmd, UpdateCmd};
+    use clap::Parser;
+
+    #[derive(Parser)]
+    enum Cli {
+        Index(IndexCmd),
+        Init(InitCmd),
+        Update(UpdateCmd),
+        Reindex(ReindexCmd),
+    }
+
+    #[test]
+    fn test_indexing_commands_share_run_flags() {
+        let flags = [
+            "--max-files",
+            "10",
+            "--max-chunks",
+            "20",
+            "--profile",
+            "small",
+            "-q",
+        ];
+        for (command, first) in [
+            ("index", "serde"),
+            ("init", "."),
+            ("update", "."),
+            ("reindex", "--all"),
+        ] {
+            let args = ["idx", command, first].into_iter().chain(flags);
+            let run = match Cli::try_parse_from(args).unwrap() {
+                Cli::Index(cmd) => cmd.run,
+                Cli::Init(cmd) => cmd.run,
+                Cli::Update(cmd) => cmd.run,
+                Cli::Reindex(cmd) => cmd.run,
+            };
+            assert_eq!(
+                (run.max_files, run.max_chunks, run.profile(), run.quiet),
+                (Some(10), Some(20), Some("small"), true),
+                "{command}"
+            );
+        }
+    }
+}
@@ -1,9 +1,18 @@
 //! Search command - find code within indexed packages.
 
+use std::path::Path;
+
 use anyhow::{Context, Result};
 use clap::Args;
 
-use crate::local::{self, LocalSearch};
+use super::run_args::ProfileArgs;
+use crate::local::models::{
+    ChunkFilter, GroupedResult, KindFilter, PreferredPackages, QueryMode, ScoreExplanation,
+    SearchOptions, SearchResult, TestFilter, group_by_parent,
+};
+use crate::local::{self, LocalConfig, LocalSearch};
+use crate::manifests::{NpmOptions, collect_deps};
+use crate::types::SearchScope;
 
 #[derive(Args)]
 pub struct SearchCmd {
@@ -14,21 +23,131 @@ pub struct SearchCmd {
     #[arg(short, long)]
     pub package: Option<String>,
 
-    /// Filter to specific version
-    #[arg(short = 'V', long)]
+    /// Filter to a version: exact, partial (4, 4.17) or latest, resolved to the
+    /// newest matching indexed version
+    #[arg(short = 'V', long, alias = "package-version")]
     pub version: Option<String>,
 
     /// Filter to registry (npm, crates, pypi)
     #[arg(short, long)]
     pub registry: Option<String>,
 
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
     /// Include full code (not just snippets)
     #[arg(short = 'c', long)]
     pub code: bool,
 
+    /// Show each result's whole snippet, cut to this many bytes (default:
+    /// config `snippet_length`), instead of its first lines
+    #[arg(long, value_name = "BYTES", conflicts_with = "code")]
+    pub snippet_length: Option<usize>,
+
+    /// Include each result's full documentation, apart from its code
+    #[arg(long)]
+    pub docs: bool,
+
+    /// Rank the project's direct dependencies (from its manifests) above
+    /// other indexed packages that match about as well
+    #[arg(long)]
+    pub context_packages: bool,
+
+    /// Drop results scoring below this (overrides config `min_score`, 0 = keep all)
+    #[arg(long)]
+    pub min_score: Option<f32>,
+
+    /// Embeddings to compare the query with: code, documentation (suits
+    /// questions in plain language) or whichever is closer (default: fused
+    /// with `doc_embeddings` enabled, else code)
+    #[arg(long, value_enum)]
+    pub mode: Option<QueryMode>,
+
     /// Max results
     #[arg(short, long, default_value = "10")]
     pub limit: u32,
+
+    /// Collapse several matching methods of one class into a single entry
+    #[arg(long)]
+    pub group_by_parent: bool,
+
+    /// Show how each result was scored (distances, keyword matches, embedded text)
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Print results as JSON (with an `explain` field per result when --explain is set)
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub profile: ProfileArgs,
+}
+
+/// Which chunks a search returns, shared by `idx search` and `idx bench`.
+#[derive(Args)]
+pub struct FilterArgs {
+    /// Only search files under a path prefix (src/parser/) or matching a glob (*/client/*.ts)
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Whether to return chunks indexed from test files (see `idx config set-index-tests`)
+    #[arg(long, value_enum, default_value_t = TestFilter::Include)]
+    pub tests: TestFilter,
+
+    /// Also return internal and private code, not just public API (see
+    /// `idx config set-public-only`)
+    #[arg(long)]
+    pub all_visibility: bool,
+
+    /// Only search documentation (READMEs and docs), for "how do I" questions
+    #[arg(long, conflicts_with_all = ["tests", "examples_only", "code_only"])]
+    pub docs_only: bool,
+
+    /// Only search examples, for runnable usage; same as `--tests only`
+    #[arg(long, conflicts_with_all = ["tests", "docs_only", "code_only"])]
+    pub examples_only: bool,
+
+    /// Only search code, leaving out documentation and examples (`--tests
+    /// exclude`)
+    #[arg(long, conflicts_with_all = ["tests", "docs_only", "examples_only"])]
+    pub code_only: bool,
+
+    /// Only search public packages or tenants' private ones (indexed with
+    /// `idx index --tenant`); default all
+    #[arg(long, value_enum)]
+    pub scope: Option<SearchScope>,
+
+    /// Only return functions taking a parameter whose type contains this
+    /// (e.g. Request); needs packages indexed since parameters are recorded
+    #[arg(long, value_name = "TYPE")]
+    pub takes: Option<String>,
+
+    /// Only return functions whose return type contains this (e.g. Response)
+    #[arg(long, value_name = "TYPE")]
+    pub returns: Option<String>,
+}
+
+impl FilterArgs {
+    /// Chunks to search: public API only, unless `--all-visibility` is
+    /// given or `public_only` is off, of the kinds `--docs-only`,
+    /// `--examples-only` or `--code-only` select, in packages of `--scope`,
+    /// with the parameter and return types of `--takes` and `--returns`.
+    pub(super) fn filter(&self, config: &LocalConfig) -> ChunkFilter {
+        let (kind, tests) = if self.docs_only {
+            (KindFilter::Docs, self.tests)
+        } else if self.examples_only {
+            (KindFilter::All, TestFilter::Only)
+        } else if self.code_only {
+            (KindFilter::Code, TestFilter::Exclude)
+        } else {
+            (KindFilter::All, self.tests)
+        };
+        ChunkFilter::new(self.path.as_deref(), tests)
+            .with_public_only(config.public_only && !self.all_visibility)
+            .with_kind(kind)
+            .with_scope(self.scope)
+            .with_signature_types(self.takes.as_deref(), self.returns.as_deref())
+    }
 }
 
 impl SearchCmd {
@@ -37,54 +156,317 @@ impl SearchCmd {
             local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
 
         let start = std::time::Instant::now();
-        let search = LocalSearch::new(&index_dir).await?;
-
-        let results = search
-            .search(
-                &self.query,
-                self.package.as_deref(),
-                self.registry.as_deref(),
-                self.version.as_deref(),
-                self.limit as usize,
-            )
-            .await?;
+        let search = LocalSearch::new_with_profile(&index_dir, self.profile.profile())
+            .await?
+            .with_shared(local::open_global_search(&index_dir, self.profile.profile()).await?)
+            .with_explain(self.explain);
+
+        let preferred = if self.context_packages {
+            // The project is the directory holding `.index/`
+            let root = index_dir.parent().unwrap_or(&index_dir);
+            context_packages(root, search.config().npm_options())?
+        } else {
+            PreferredPackages::default()
+        };
+
+        let options = SearchOptions {
+            package: self.package.as_deref(),
+            registry: self.registry.as_deref(),
+            version: self.version.as_deref(),
+            filter: self.filters.filter(search.config()),
+            preferred,
+            mode: self.mode,
+            min_score: self.min_score,
+            snippet_length: self.snippet_length,
+            limit: self.limit as usize,
+        };
+
+        let response = search.search(&self.query, &options).await?;
+        if let Some(low) = response.no_confident_matches {
+            if self.json {
+                eprintln!("No confident matches: {}", low);
+                println!("[]");
+            } else {
+                println!("No confident matches: {}", low);
+            }
+            return Ok(());
+        }
+        let results = response.results;
+
+        if self.json {
+            if self.group_by_parent {
+                let grouped = group_by_parent(results);
+                println!("{}", serde_json::to_string_pretty(&grouped)?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            }
+            return Ok(());
+        }
 
         let elapsed = start.elapsed().as_millis();
 
         println!("Found {} results in {}ms\n", results.len(), elapsed);
 
-        for (i, r) in results.iter().enumerate() {
-            println!(
-                "{}. {} `{}` in {}:{}@{} (score: {:.2})",
-                i + 1,
-                r.chunk_type,
-                r.name,
-                r.registry,
-                r.package,
-                r.version,
-                r.score
-            );
-            println!("   {} L{}-{}", r.file_path, r.start_line, r.end_line);
-
-            if let Some(ref sig) = r.signature {
-                println!("   {}", sig);
+        if !self.group_by_parent {
+            for (i, r) in results.iter().enumerate() {
+                self.print_result(&search, &format!("{}.", i + 1), r, "")
+                    .await;
             }
+            return Ok(());
+        }
 
-            if self.code {
-                if let Ok(code) = search.get_code(&r.storage_key).await {
-                    println!("   ---");
-                    for line in code.lines() {
-                        println!("   {}", line);
+        for (i, grouped) in group_by_parent(results).iter().enumerate() {
+            let label = format!("{}.", i + 1);
+            match grouped {
+                GroupedResult::Single(r) => self.print_result(&search, &label, r, "").await,
+                GroupedResult::Group(group) => {
+                    println!(
+                        "{} {} ({} matches) in {}:{}@{} (score: {:.2})",
+                        label,
+                        group.parent,
+                        group.results.len(),
+                        group.registry,
+                        group.package,
+                        group.version,
+                        group.score
+                    );
+                    println!("   {}\n", group.file_path);
+                    for r in &group.results {
+                        self.print_result(&search, "-", r, "   ").await;
                     }
-                    println!("   ---");
                 }
-            } else {
-                let snippet: String = r.snippet.lines().take(3).collect::<Vec<_>>().join("\n   ");
-                println!("   {}", snippet);
             }
-            println!();
         }
 
         Ok(())
     }
+
+    /// Print one result, prefixed by `label` and indented by `indent`.
+    async fn print_result(
+        &self,
+        search: &LocalSearch,
+        label: &str,
+        r: &SearchResult,
+        indent: &str,
+    ) {
+        println!(
+            "{}{} {} `{}` in {}:{}@{} (score: {:.2})",
+            indent, label, r.chunk_type, r.name, r.registry, r.package, r.version, r.score
+        );
+        println!(
+            "{}   {} L{}-{}",
+            indent, r.file_path, r.start_line, r.end_line
+        );
+
+        if let Some(note) = r.deprecation_note() {
+            println!("{}   {}", indent, note);
+        }
+
+        if let Some(ref sig) = r.signature {
+            println!("{}   {}", indent, sig);
+        }
+
+        if self.docs
+            && let Some(ref docs) = r.docstring
+        {
+            for line in docs.lines() {
+                println!("{}   │ {}", indent, line);
+            }
+        }
+
+        if self.code {
+            if let Ok(code) = search.get_code(&r.storage_key).await {
+                println!(
+                    "{}   ```{}",
+                    indent,
+                    r.language.as_deref().unwrap_or_default()
+                );
+                for line in code.lines() {
+                    println!("{}   {}", indent, line);
+                }
+                println!("{}   ```", indent);
+            }
+        } else {
+            // A preview, unless a length was asked for
+            let max_lines = if self.snippet_length.is_some() {
+                usize::MAX
+            } else {
+                3
+            };
+            let separator = format!("\n{}   ", indent);
+            let snippet: String = r
+                .snippet
+                .lines()
+                .take(max_lines)
+                .collect::<Vec<_>>()
+                .join(&separator);
+            println!("{}   {}", indent, snippet);
+        }
+
+        if let Some(ref explain) = r.explain {
+            print_explanation(explain);
+        }
+        println!();
+    }
+}
+
+/// Direct dependencies declared by the manifests under `root`.
+fn context_packages(root: &Path, npm: NpmOptions) -> Result<PreferredPackages> {
+    let deps = collect_deps(root, npm)?;
+    Ok(PreferredPackages::new(
+        deps.into_iter().map(|d| (d.registry, d.name)),
+    ))
+}
+
+/// Print a result's score breakdown below it.
+fn print_explanation(explain: &ScoreExplanation) {
+    println!("   explain:");
+    println!(
+        "     vector:   {} distance {:.4}, score {:.4}, cosine {:.4} ({} embedding, {} mode)",
+        explain.metric,
+        explain.distance,
+        explain.vector_score,
+        explain.cosine,
+        if explain.matched_docs { "doc" } else { "code" },
+        explain.mode
+    );
+    if explain.keyword_matches.is_empty() {
+        println!("     keywords: none ({:+.4})", explain.keyword_adjustment);
+    } else {
+        println!(
+            "     keywords: {} ({:+.4})",
+            explain.keyword_matches.join(", "),
+            explain.keyword_adjustment
+        );
+    }
+    if let Some(weight) = explain.deprecation_weight {
+        println!("     deprecated: x{}", weight);
+    }
+    if let Some(adjustment) = explain.preference_adjustment {
+        println!("     preferred: {:+.4}", adjustment);
+    }
+    println!(
+        "     cache:    {}",
+        if explain.cache_hit { "hit" } else { "miss" }
+    );
+    println!("     embedded:");
+    for line in explain.embedded_text.lines() {
+        println!("       | {}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        search: SearchCmd,
+    }
+
+    fn parse(args: &[&str]) -> SearchCmd {
+        Cli::parse_from(["idx"].iter().chain(args)).search
+    }
+
+    #[test]
+    fn test_public_only_by_default() {
+        let config = LocalConfig::default();
+
+        let filter = parse(&["parse json"]).filters.filter(&config);
+        assert!(filter.public_only);
+
+        let filter = parse(&["parse json", "--all-visibility"])
+            .filters
+            .filter(&config);
+        assert!(!filter.public_only);
+
+        let config = LocalConfig {
+            public_only: false,
+            ..LocalConfig::default()
+        };
+        assert!(!parse(&["parse json"]).filters.filter(&config).public_only);
+    }
+
+    #[test]
+    fn test_kind_flags() {
+        let config = LocalConfig::default();
+        let kind = |args: &[&str]| {
+            let filter = parse(args).filters.filter(&config);
+            (filter.kind, filter.tests)
+        };
+
+        assert_eq!(kind(&["retry"]), (KindFilter::All, TestFilter::Include));
+        assert_eq!(
+            kind(&["retry", "--docs-only"]),
+            (KindFilter::Docs, TestFilter::Include)
+        );
+        // Examples and code are aliases over the tests filter
+        assert_eq!(
+            kind(&["retry", "--examples-only"]),
+            kind(&["retry", "--tests", "only"])
+        );
+        assert_eq!(
+            kind(&["retry", "--code-only"]),
+            (KindFilter::Code, TestFilter::Exclude)
+        );
+        for conflicting in [
+            ["--docs-only", "--code-only"],
+            ["--examples-only", "--tests=exclude"],
+            ["--code-only", "--tests=include"],
+            ["--docs-only", "--tests=only"],
+        ] {
+            let args = ["idx", "retry"].into_iter().chain(conflicting);
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", conflicting);
+        }
+    }
+
+    #[test]
+    fn test_scope_flag() {
+        let config = LocalConfig::default();
+        let scope = |args: &[&str]| parse(args).filters.filter(&config).scope;
+
+        assert_eq!(scope(&["retry"]), None);
+        assert_eq!(scope(&["retry", "--scope", "all"]), None);
+        assert_eq!(
+            scope(&["retry", "--scope", "private"]),
+            Some(SearchScope::Private)
+        );
+        assert!(Cli::try_parse_from(["idx", "retry", "--scope", "tenant"]).is_err());
+    }
+
+    #[test]
+    fn test_signature_type_flags() {
+        let config = LocalConfig::default();
+        let filter = parse(&["handler", "--takes", "Request", "--returns", "Response"])
+            .filters
+            .filter(&config);
+        assert_eq!(filter.param_type.as_deref(), Some("Request"));
+        assert_eq!(filter.return_type.as_deref(), Some("Response"));
+
+        let filter = parse(&["handler"]).filters.filter(&config);
+        assert_eq!((filter.param_type, filter.return_type), (None, None));
+    }
+
+    #[test]
+    fn test_context_packages_from_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/app\n\nrequire github.com/pkg/errors v0.9.1\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("web")).unwrap();
+        std::fs::write(
+            dir.path().join("web/package.json"),
+            r#"{"dependencies": {"axios": "1.7.9"}}"#,
+        )
+        .unwrap();
+
+        let preferred = context_packages(dir.path(), NpmOptions::default()).unwrap();
+        assert!(preferred.contains("go", "github.com/pkg/errors"));
+        assert!(preferred.contains("npm", "axios"));
+        assert!(!preferred.contains("npm", "react"));
+    }
 }
@@ -0,0 +1,38 @@
+//! Serve command - run a local HTTP/JSON search API.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::local;
+
+#[derive(Args)]
+pub struct ServeCmd {
+    /// Port to listen on
+    #[arg(long, short = 'p', default_value = "7878")]
+    pub port: u16,
+
+    /// Address to bind (default: localhost only)
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub host: IpAddr,
+
+    /// Enable `POST /index` to index packages (read-only otherwise)
+    #[arg(long)]
+    pub allow_index: bool,
+}
+
+impl ServeCmd {
+    pub async fn run(&self) -> Result<()> {
+        let index_dir =
+            local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
+
+        let addr = SocketAddr::new(self.host, self.port);
+        println!("Serving {} on http://{}", index_dir.display(), addr);
+        if self.allow_index {
+            println!("Indexing enabled (POST /index)");
+        }
+
+        local::http::run_local(&index_dir, addr, self.allow_index).await
+    }
+}
@@ -1,14 +1,33 @@
-//! Skip command - mark a package version as skipped.
+//! Skip command - mark a package version as skipped, or manage skip rules.
 
 use anyhow::{Context, Result};
 use clap::Args;
 
 use crate::local::{self, LocalIndexer};
+use crate::types::{Registry, TenantId};
 
 #[derive(Args)]
 pub struct SkipCmd {
     /// Package to skip (format: registry:name@version or name@version)
-    pub package: String,
+    #[arg(required_unless_present_any = ["pattern", "remove", "list"])]
+    pub package: Option<String>,
+
+    /// Skip every package matching a glob on registry:name (e.g. 'npm:@types/*',
+    /// 'maven:*'), before it is downloaded; without a registry it matches any
+    #[arg(long, conflicts_with_all = ["package", "remove", "list"])]
+    pub pattern: Option<String>,
+
+    /// Remove a skip rule added with --pattern
+    #[arg(long, conflicts_with_all = ["package", "list"])]
+    pub remove: Option<String>,
+
+    /// List skip rules
+    #[arg(long, conflicts_with = "package")]
+    pub list: bool,
+
+    /// Private package of this tenant (a UUID) rather than the public one
+    #[arg(long, requires = "package")]
+    pub tenant: Option<TenantId>,
 }
 
 impl SkipCmd {
@@ -18,13 +37,46 @@ impl SkipCmd {
 
         let indexer = LocalIndexer::new(&index_dir).await?;
 
+        if let Some(ref pattern) = self.pattern {
+            let pattern = rule_pattern(pattern);
+            if indexer.db().add_skip_rule(&pattern).await? {
+                println!("Added skip rule {}", pattern);
+                println!("Matching packages are skipped from the next index run.");
+            } else {
+                println!("Skip rule {} already exists.", pattern);
+            }
+            return Ok(());
+        }
+
+        if let Some(ref pattern) = self.remove {
+            let pattern = rule_pattern(pattern);
+            if !indexer.db().remove_skip_rule(&pattern).await? {
+                anyhow::bail!("No skip rule {}. See `idx skip --list`.", pattern);
+            }
+            println!("Removed skip rule {}", pattern);
+            println!("Run `idx retry` on skipped versions to index them.");
+            return Ok(());
+        }
+
+        if self.list {
+            let rules = indexer.db().list_skip_rules().await?;
+            if rules.is_empty() {
+                println!("No skip rules.");
+            }
+            for rule in rules {
+                println!("{}", rule);
+            }
+            return Ok(());
+        }
+
         // Parse package spec
-        let (registry, name, version) = parse_package_spec(&self.package)?;
+        let spec = self.package.as_deref().unwrap_or_default();
+        let (registry, name, version) = parse_package_spec(spec)?;
 
         // Find the version
         let ver = indexer
             .db()
-            .find_version(&registry, &name, &version)
+            .find_version(&registry, &name, &version, self.tenant)
             .await?
             .context(format!(
                 "Package not found: {}:{}@{}",
@@ -32,7 +84,10 @@ impl SkipCmd {
             ))?;
 
         // Mark as skipped
-        indexer.db().mark_version_skipped(&ver.version_id).await?;
+        indexer
+            .db()
+            .mark_version_skipped(&ver.version_id, None)
+            .await?;
 
         println!("Skipped {}:{}@{}", registry, name, version);
 
@@ -40,6 +95,20 @@ impl SkipCmd {
     }
 }
 
+/// A skip rule glob on `registry:name`; without a registry prefix
+/// ("@types/*", "org.slf4j:*") it matches any registry.
+fn rule_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim();
+    let has_registry = pattern
+        .split_once(':')
+        .is_some_and(|(registry, _)| registry == "*" || registry.parse::<Registry>().is_ok());
+    if has_registry {
+        pattern.to_string()
+    } else {
+        format!("*:{}", pattern)
+    }
+}
+
 /// Parse package spec: "crates:tokio@1.0.0" or "tokio@1.0.0" (assumes crates)
 fn parse_package_spec(spec: &str) -> Result<(String, String, String)> {
     let (registry, rest) = if spec.contains(':') {
@@ -55,3 +124,17 @@ fn parse_package_spec(spec: &str) -> Result<(String, String, String)> {
 
     Ok((registry, name.to_string(), version.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_pattern() {
+        assert_eq!(rule_pattern("npm:@types/*"), "npm:@types/*");
+        assert_eq!(rule_pattern("maven:*"), "maven:*");
+        assert_eq!(rule_pattern("@types/*"), "*:@types/*");
+        assert_eq!(rule_pattern("org.slf4j:*"), "*:org.slf4j:*");
+        assert_eq!(rule_pattern("*:types-*"), "*:types-*");
+    }
+}
@@ -31,7 +31,8 @@ impl StatsCmd {
         // Get storage sizes
         let db_size = get_file_size(&index_dir.join("db.sqlite"));
         let vectors_size = get_dir_size(&index_dir.join("vectors"));
-        let blobs_size = get_dir_size(&index_dir.join("blobs"));
+        let blobs = indexer.storage().usage().await?;
+        let blobs_size = blobs.stored;
         let total_size = db_size + vectors_size + blobs_size;
 
         // Count by registry
@@ -66,7 +67,16 @@ impl StatsCmd {
         println!("Storage:");
         println!("  Database:  {}", format_size(db_size));
         println!("  Vectors:   {}", format_size(vectors_size));
-        println!("  Blobs:     {}", format_size(blobs_size));
+        if blobs.content > blobs.stored {
+            println!(
+                "  Blobs:     {} ({} uncompressed, {:.0}% saved)",
+                format_size(blobs_size),
+                format_size(blobs.content),
+                blobs.savings_percent()
+            );
+        } else {
+            println!("  Blobs:     {}", format_size(blobs_size));
+        }
         println!("  Total:     {}", format_size(total_size));
 
         Ok(())
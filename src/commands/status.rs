@@ -1,23 +1,141 @@
 //! Status command - show indexed vs manifest dependencies.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
 use std::path::PathBuf;
 
 use anyhow::Result;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use serde::Serialize;
 
-use crate::local::models::VersionStatus;
+use super::npm_deps::NpmDepsArgs;
+use crate::local::models::{FailureKind, VersionStatus, VersionWithPackage};
 use crate::local::{self, LocalIndexer};
-use crate::manifests::{
-    discover_manifest_dirs, parse_cargo_deps, parse_go_deps, parse_maven_deps, parse_npm_deps,
-    parse_python_deps,
-};
+use crate::manifests::collect_deps;
+
+/// Number of recently indexed versions included in the report.
+const RECENT_LIMIT: usize = 10;
 
 #[derive(Args)]
 pub struct StatusCmd {
     /// Directory to scan for manifests (default: current directory)
     #[arg(default_value = ".")]
     pub path: PathBuf,
+
+    /// Output format
+    #[arg(long, short = 'f', value_enum, default_value_t = StatusFormat::Table)]
+    pub format: StatusFormat,
+
+    #[command(flatten)]
+    pub npm: NpmDepsArgs,
+}
+
+/// Output format for `idx status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFormat {
+    /// Human-readable summary
+    Table,
+    /// JSON for dashboards and scripts
+    Json,
+    /// Markdown tables for PRs and issues
+    Md,
+}
+
+/// Index status, as rendered by every output format.
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    index: String,
+    counts: StatusCounts,
+    manifest_dependencies: usize,
+    missing: Vec<PackageRef>,
+    extra: Vec<PackageRef>,
+    recently_indexed: Vec<RecentEntry>,
+    /// Failed versions per failure kind, `unknown` for older failures.
+    failed_by_kind: BTreeMap<String, u32>,
+    failed: Vec<FailedEntry>,
+    empty: Vec<EmptyEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusCounts {
+    packages: u32,
+    versions: u32,
+    indexed: u32,
+    failed: u32,
+    skipped: u32,
+    empty: u32,
+    pending: u32,
+    chunks: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct PackageRef {
+    registry: String,
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RecentEntry {
+    registry: String,
+    name: String,
+    version: String,
+    chunk_count: i32,
+    indexed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct FailedEntry {
+    registry: String,
+    name: String,
+    version: String,
+    kind: Option<FailureKind>,
+    error: Option<String>,
+    attempts: i32,
+    failed_at: Option<String>,
+}
+
+impl FailedEntry {
+    fn kind_label(&self) -> String {
+        self.kind
+            .map_or_else(|| "unknown".to_string(), |k| k.to_string())
+    }
+}
+
+/// An indexed version that yielded no code.
+#[derive(Debug, Serialize)]
+struct EmptyEntry {
+    registry: String,
+    name: String,
+    version: String,
+    files_seen: i32,
+    files_skipped: i32,
+}
+
+impl From<&VersionWithPackage> for EmptyEntry {
+    fn from(v: &VersionWithPackage) -> Self {
+        Self {
+            registry: v.registry.clone(),
+            name: v.name.clone(),
+            version: v.version.clone(),
+            files_seen: v.files_seen,
+            files_skipped: v.files_skipped,
+        }
+    }
+}
+
+impl From<&VersionWithPackage> for FailedEntry {
+    fn from(v: &VersionWithPackage) -> Self {
+        Self {
+            registry: v.registry.clone(),
+            name: v.name.clone(),
+            version: v.version.clone(),
+            kind: v.failure_kind(),
+            error: v.error_message.clone(),
+            attempts: v.attempts,
+            failed_at: v.failed_at.clone(),
+        }
+    }
 }
 
 impl StatusCmd {
@@ -32,45 +150,30 @@ impl StatusCmd {
         let index_dir = index_dir.unwrap();
         let indexer = LocalIndexer::new(&index_dir).await?;
 
-        // Get indexed versions
-        let indexed_versions = indexer.db().list_versions().await?;
+        let stats = indexer.db().get_stats().await?;
+
+        // Get indexed versions (ordered by most recently indexed); empty
+        // versions count as present so they aren't reported as missing
+        let (indexed_versions, empty_versions): (Vec<_>, Vec<_>) = indexer
+            .db()
+            .list_versions()
+            .await?
+            .into_iter()
+            .filter(|v| matches!(v.status(), VersionStatus::Indexed | VersionStatus::Empty))
+            .partition(|v| v.status() == VersionStatus::Indexed);
         let indexed_set: HashSet<(String, String, String)> = indexed_versions
             .iter()
-            .filter(|v| v.status() == VersionStatus::Indexed)
+            .chain(&empty_versions)
             .map(|v| (v.registry.clone(), v.name.clone(), v.version.clone()))
             .collect();
 
-        let failed_count = indexed_versions
-            .iter()
-            .filter(|v| v.status() == VersionStatus::Failed)
-            .count();
-
-        let skipped_count = indexed_versions
-            .iter()
-            .filter(|v| v.status() == VersionStatus::Skipped)
-            .count();
+        let failed_versions = indexer
+            .db()
+            .list_versions_by_status(VersionStatus::Failed)
+            .await?;
 
         // Get manifest dependencies from all discovered roots
-        let manifest_dirs = discover_manifest_dirs(&self.path)?;
-        let mut manifest_deps = Vec::new();
-
-        for dir in &manifest_dirs {
-            if let Ok(deps) = parse_npm_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_cargo_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_python_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_maven_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_go_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-        }
+        let manifest_deps = collect_deps(&self.path, self.npm.options()?)?;
 
         let manifest_set: HashSet<(String, String, String)> = manifest_deps
             .iter()
@@ -78,42 +181,391 @@ impl StatusCmd {
             .collect();
 
         // Find gaps
-        let missing: Vec<_> = manifest_set.difference(&indexed_set).collect();
-        let extra: Vec<_> = indexed_set.difference(&manifest_set).collect();
-
-        // Print status
-        println!("Index: {}", index_dir.display());
-        println!();
-        println!("Indexed:  {} packages", indexed_set.len());
-        if failed_count > 0 {
-            println!(
-                "Failed:   {} packages (run `idx list -s failed` to see errors)",
-                failed_count
-            );
-        }
-        if skipped_count > 0 {
-            println!("Skipped:  {} packages", skipped_count);
-        }
-        println!("Manifest: {} dependencies", manifest_set.len());
-        println!();
-
-        if missing.is_empty() {
-            println!("All dependencies are indexed.");
-        } else {
-            println!("Missing ({}):", missing.len());
-            for (registry, name, version) in &missing {
-                println!("  {}:{}@{}", registry, name, version);
-            }
+        let to_refs = |set: HashSet<&(String, String, String)>| {
+            let mut refs: Vec<_> = set
+                .into_iter()
+                .map(|(registry, name, version)| PackageRef {
+                    registry: registry.clone(),
+                    name: name.clone(),
+                    version: version.clone(),
+                })
+                .collect();
+            refs.sort();
+            refs
+        };
+        let missing = to_refs(manifest_set.difference(&indexed_set).collect());
+        let extra = to_refs(indexed_set.difference(&manifest_set).collect());
+
+        // Group failures by kind
+        let mut failed: Vec<_> = failed_versions.iter().map(FailedEntry::from).collect();
+        failed.sort_by_key(FailedEntry::kind_label);
+        let mut failed_by_kind = BTreeMap::new();
+        for f in &failed {
+            *failed_by_kind.entry(f.kind_label()).or_insert(0) += 1;
         }
 
-        if !extra.is_empty() {
-            println!();
-            println!("Extra (indexed but not in manifest) ({}):", extra.len());
-            for (registry, name, version) in &extra {
-                println!("  {}:{}@{}", registry, name, version);
-            }
+        let report = StatusReport {
+            index: index_dir.display().to_string(),
+            counts: StatusCounts {
+                packages: stats.package_count,
+                versions: stats.version_count,
+                indexed: stats.indexed_count,
+                failed: stats.failed_count,
+                skipped: stats.skipped_count,
+                empty: stats.empty_count,
+                pending: stats.version_count.saturating_sub(
+                    stats.indexed_count
+                        + stats.failed_count
+                        + stats.skipped_count
+                        + stats.empty_count,
+                ),
+                chunks: stats.chunk_count,
+            },
+            manifest_dependencies: manifest_set.len(),
+            missing,
+            extra,
+            recently_indexed: indexed_versions
+                .iter()
+                .take(RECENT_LIMIT)
+                .map(|v| RecentEntry {
+                    registry: v.registry.clone(),
+                    name: v.name.clone(),
+                    version: v.version.clone(),
+                    chunk_count: v.chunk_count,
+                    indexed_at: v.indexed_at.clone(),
+                })
+                .collect(),
+            failed_by_kind,
+            failed,
+            empty: empty_versions.iter().map(EmptyEntry::from).collect(),
+        };
+
+        match self.format {
+            StatusFormat::Table => print!("{}", render_table(&report)),
+            StatusFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            StatusFormat::Md => print!("{}", render_markdown(&report)),
         }
 
         Ok(())
     }
 }
+
+fn render_table(report: &StatusReport) -> String {
+    let mut out = String::new();
+    let counts = &report.counts;
+
+    writeln!(out, "Index: {}", report.index).ok();
+    writeln!(out).ok();
+    writeln!(out, "Indexed:  {} packages", counts.indexed).ok();
+    if counts.failed > 0 {
+        writeln!(out, "Failed:   {} packages", counts.failed).ok();
+    }
+    if counts.skipped > 0 {
+        writeln!(out, "Skipped:  {} packages", counts.skipped).ok();
+    }
+    if counts.empty > 0 {
+        writeln!(
+            out,
+            "Empty:    {} packages (no code extracted)",
+            counts.empty
+        )
+        .ok();
+    }
+    if counts.pending > 0 {
+        writeln!(out, "Pending:  {} packages", counts.pending).ok();
+    }
+    writeln!(
+        out,
+        "Manifest: {} dependencies",
+        report.manifest_dependencies
+    )
+    .ok();
+    writeln!(out).ok();
+
+    if report.missing.is_empty() {
+        writeln!(out, "All dependencies are indexed.").ok();
+    } else {
+        writeln!(out, "Missing ({}):", report.missing.len()).ok();
+        for p in &report.missing {
+            writeln!(out, "  {}:{}@{}", p.registry, p.name, p.version).ok();
+        }
+    }
+
+    if !report.extra.is_empty() {
+        writeln!(out).ok();
+        writeln!(
+            out,
+            "Extra (indexed but not in manifest) ({}):",
+            report.extra.len()
+        )
+        .ok();
+        for p in &report.extra {
+            writeln!(out, "  {}:{}@{}", p.registry, p.name, p.version).ok();
+        }
+    }
+
+    if !report.failed.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "Failed ({}):", report.failed.len()).ok();
+        for (kind, count) in &report.failed_by_kind {
+            writeln!(out, "  {} ({}):", kind, count).ok();
+            for f in report.failed.iter().filter(|f| &f.kind_label() == kind) {
+                writeln!(out, "    {}:{}@{}", f.registry, f.name, f.version).ok();
+                if let Some(ref err) = f.error {
+                    writeln!(out, "    └─ {}", err).ok();
+                }
+            }
+        }
+        if report
+            .failed
+            .iter()
+            .any(|f| f.kind.is_none_or(FailureKind::is_transient))
+        {
+            writeln!(
+                out,
+                "Run `idx retry --all --transient` to retry failures that may be transient."
+            )
+            .ok();
+        }
+    }
+
+    if !report.empty.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "Empty ({}):", report.empty.len()).ok();
+        for e in &report.empty {
+            writeln!(
+                out,
+                "  {}:{}@{} ({} files, {} not parsed)",
+                e.registry, e.name, e.version, e.files_seen, e.files_skipped
+            )
+            .ok();
+        }
+    }
+
+    out
+}
+
+fn render_markdown(report: &StatusReport) -> String {
+    let mut out = String::new();
+    let counts = &report.counts;
+
+    writeln!(out, "## Index status").ok();
+    writeln!(out).ok();
+    writeln!(out, "| Status | Count |").ok();
+    writeln!(out, "|---|---:|").ok();
+    for (label, count) in [
+        ("Indexed", counts.indexed),
+        ("Failed", counts.failed),
+        ("Skipped", counts.skipped),
+        ("Empty", counts.empty),
+        ("Pending", counts.pending),
+        ("Chunks", counts.chunks),
+    ] {
+        writeln!(out, "| {} | {} |", label, count).ok();
+    }
+    writeln!(
+        out,
+        "| Manifest dependencies | {} |",
+        report.manifest_dependencies
+    )
+    .ok();
+
+    if !report.failed.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "### Failed ({})", report.failed.len()).ok();
+        writeln!(out).ok();
+        writeln!(out, "| Package | Version | Kind | Attempts | Error |").ok();
+        writeln!(out, "|---|---|---|---:|---|").ok();
+        for f in &report.failed {
+            writeln!(
+                out,
+                "| {}:{} | {} | {} | {} | {} |",
+                f.registry,
+                md_cell(&f.name),
+                md_cell(&f.version),
+                f.kind_label(),
+                f.attempts,
+                md_cell(f.error.as_deref().unwrap_or(""))
+            )
+            .ok();
+        }
+    }
+
+    if !report.empty.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "### Empty ({})", report.empty.len()).ok();
+        writeln!(out).ok();
+        writeln!(out, "| Package | Version | Files | Not parsed |").ok();
+        writeln!(out, "|---|---|---:|---:|").ok();
+        for e in &report.empty {
+            writeln!(
+                out,
+                "| {}:{} | {} | {} | {} |",
+                e.registry,
+                md_cell(&e.name),
+                md_cell(&e.version),
+                e.files_seen,
+                e.files_skipped
+            )
+            .ok();
+        }
+    }
+
+    for (title, refs) in [
+        ("Missing", &report.missing),
+        ("Extra (not in manifest)", &report.extra),
+    ] {
+        if refs.is_empty() {
+            continue;
+        }
+        writeln!(out).ok();
+        writeln!(out, "### {} ({})", title, refs.len()).ok();
+        writeln!(out).ok();
+        writeln!(out, "| Package | Version |").ok();
+        writeln!(out, "|---|---|").ok();
+        for p in refs {
+            writeln!(
+                out,
+                "| {}:{} | {} |",
+                p.registry,
+                md_cell(&p.name),
+                md_cell(&p.version)
+            )
+            .ok();
+        }
+    }
+
+    if !report.recently_indexed.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "### Recently indexed").ok();
+        writeln!(out).ok();
+        writeln!(out, "| Package | Version | Chunks | Indexed at |").ok();
+        writeln!(out, "|---|---|---:|---|").ok();
+        for r in &report.recently_indexed {
+            writeln!(
+                out,
+                "| {}:{} | {} | {} | {} |",
+                r.registry,
+                md_cell(&r.name),
+                md_cell(&r.version),
+                r.chunk_count,
+                r.indexed_at.as_deref().unwrap_or("")
+            )
+            .ok();
+        }
+    }
+
+    out
+}
+
+/// Make text safe for a single Markdown table cell.
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> StatusReport {
+        StatusReport {
+            index: "/repo/.index".to_string(),
+            counts: StatusCounts {
+                packages: 2,
+                versions: 3,
+                indexed: 1,
+                failed: 2,
+                skipped: 0,
+                empty: 1,
+                pending: 1,
+                chunks: 42,
+            },
+            manifest_dependencies: 2,
+            missing: vec![PackageRef {
+                registry: "npm".to_string(),
+                name: "left-pad".to_string(),
+                version: "1.3.0".to_string(),
+            }],
+            extra: vec![],
+            recently_indexed: vec![RecentEntry {
+                registry: "npm".to_string(),
+                name: "lodash".to_string(),
+                version: "4.17.21".to_string(),
+                chunk_count: 42,
+                indexed_at: Some("2024-01-01T00:00:00Z".to_string()),
+            }],
+            failed_by_kind: BTreeMap::from([
+                ("embedding".to_string(), 1),
+                ("no_source".to_string(), 1),
+            ]),
+            failed: vec![
+                FailedEntry {
+                    registry: "npm".to_string(),
+                    name: "flaky".to_string(),
+                    version: "1.0.0".to_string(),
+                    kind: Some(FailureKind::Embedding),
+                    error: Some("Embeddings API error: 503".to_string()),
+                    attempts: 1,
+                    failed_at: None,
+                },
+                FailedEntry {
+                    registry: "pypi".to_string(),
+                    name: "broken".to_string(),
+                    version: "0.1.0".to_string(),
+                    kind: Some(FailureKind::NoSource),
+                    error: Some("no source | distribution\nfound".to_string()),
+                    attempts: 2,
+                    failed_at: None,
+                },
+            ],
+            empty: vec![EmptyEntry {
+                registry: "npm".to_string(),
+                name: "prebuilt".to_string(),
+                version: "2.0.0".to_string(),
+                files_seen: 12,
+                files_skipped: 11,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_json_includes_failure_reasons() {
+        let json = serde_json::to_value(report()).unwrap();
+        assert_eq!(json["counts"]["pending"], 1);
+        assert_eq!(
+            json["failed"][1]["error"],
+            "no source | distribution\nfound"
+        );
+        assert_eq!(json["failed"][1]["kind"], "no_source");
+        assert_eq!(json["failed_by_kind"]["embedding"], 1);
+        assert_eq!(json["recently_indexed"][0]["name"], "lodash");
+        assert_eq!(json["counts"]["empty"], 1);
+        assert_eq!(json["empty"][0]["files_seen"], 12);
+    }
+
+    #[test]
+    fn test_markdown_tables() {
+        let md = render_markdown(&report());
+        assert!(md.contains("| Indexed | 1 |"));
+        assert!(md.contains(
+            "| pypi:broken | 0.1.0 | no_source | 2 | no source \\| distribution found |"
+        ));
+        assert!(md.contains("| npm:left-pad | 1.3.0 |"));
+        assert!(md.contains("| npm:prebuilt | 2.0.0 | 12 | 11 |"));
+        assert!(md.contains("| npm:lodash | 4.17.21 | 42 | 2024-01-01T00:00:00Z |"));
+    }
+
+    #[test]
+    fn test_table_lists_failed_errors() {
+        let table = render_table(&report());
+        assert!(table.contains("Failed:   2 packages"));
+        assert!(table.contains("  embedding (1):\n    npm:flaky@1.0.0\n"));
+        assert!(table.contains("  no_source (1):\n    pypi:broken@0.1.0\n    └─ no source"));
+        assert!(table.contains("idx retry --all --transient"));
+        assert!(table.contains("Empty:    1 packages (no code extracted)"));
+        assert!(table.contains("  npm:prebuilt@2.0.0 (12 files, 11 not parsed)"));
+    }
+}
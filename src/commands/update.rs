@@ -13,11 +13,11 @@ use anyhow::{Context, Result};
 use clap::Args;
 use futures::stream::{self, StreamExt};
 
+use super::npm_deps::NpmDepsArgs;
+use super::prune::{beyond_latest, delete_version, referenced_versions};
+use super::run_args::RunArgs;
 use crate::local::{self, LocalIndexer};
-use crate::manifests::{
-    Dependency, discover_manifest_dirs, parse_cargo_deps, parse_go_deps, parse_maven_deps,
-    parse_npm_deps, parse_python_deps,
-};
+use crate::manifests::{Dependency, collect_deps};
 
 #[derive(Args)]
 pub struct UpdateCmd {
@@ -32,6 +32,16 @@ pub struct UpdateCmd {
     /// Show detailed output
     #[arg(long, short = 'v')]
     pub verbose: bool,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Texts per embeddings request (overrides config)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub embedding_batch_size: Option<u64>,
+
+    #[command(flatten)]
+    pub npm: NpmDepsArgs,
 }
 
 impl UpdateCmd {
@@ -39,37 +49,27 @@ impl UpdateCmd {
         let index_dir =
             local::get_index_dir().context("No .index directory found. Run `idx init` first.")?;
 
-        let indexer = Arc::new(LocalIndexer::new(&index_dir).await?);
+        let indexer = Arc::new(
+            LocalIndexer::new_with_profile(&index_dir, self.run.profile())
+                .await?
+                .with_limits(self.run.max_files, self.run.max_chunks)
+                .with_embedding_batch_size(self.embedding_batch_size.map(|n| n as usize)),
+        );
+        indexer.check_embedding_dimensions().await?;
 
-        // Get indexed versions: (registry, name) -> version (only for indexed status)
+        // Get indexed versions: (registry, name) -> version (indexed or empty)
         let indexed_versions = indexer.db().list_versions().await?;
         let indexed_map: HashMap<(String, String), String> = indexed_versions
             .iter()
-            .filter(|v| v.status() == VersionStatus::Indexed)
+            .filter(|v| v.tenant().is_none())
+            .filter(|v| matches!(v.status(), VersionStatus::Indexed | VersionStatus::Empty))
             .map(|v| ((v.registry.clone(), v.name.clone()), v.version.clone()))
             .collect();
 
         // Get manifest dependencies from all discovered roots
-        let manifest_dirs = discover_manifest_dirs(&self.path)?;
-        let mut manifest_deps = Vec::new();
+        let manifest_deps = collect_deps(&self.path, self.npm.options()?)?;
 
-        for dir in &manifest_dirs {
-            if let Ok(deps) = parse_npm_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_cargo_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_python_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_maven_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-            if let Ok(deps) = parse_go_deps(dir) {
-                manifest_deps.extend(deps);
-            }
-        }
+        let referenced = referenced_versions(&manifest_deps);
 
         // Find packages that need updating (version changed or new)
         let mut to_update: Vec<Dependency> = Vec::new();
@@ -111,7 +111,7 @@ impl UpdateCmd {
 
         if to_update.is_empty() {
             println!("All packages are up to date.");
-            return Ok(());
+            return prune_beyond_limit(&indexer, &referenced).await;
         }
 
         println!("Found {} packages to update", to_update.len());
@@ -121,15 +121,24 @@ impl UpdateCmd {
         let completed = Arc::new(AtomicUsize::new(0));
         let total = to_update.len();
         let verbose = self.verbose;
+        // The running count is the progress report --quiet silences
+        let show_count = !verbose && !self.run.quiet;
         let concurrency = self.concurrency.max(1);
+        let cancel = local::cancel_on_ctrl_c();
 
         stream::iter(to_update.into_iter().map(|dep| {
             let indexer = Arc::clone(&indexer);
             let indexed = Arc::clone(&indexed);
             let failed = Arc::clone(&failed);
             let completed = Arc::clone(&completed);
+            let cancel = cancel.clone();
 
             async move {
+                // Don't start new work once cancelled; let in-flight tasks drain
+                if cancel.is_cancelled() {
+                    return;
+                }
+
                 let registry = match Registry::from_str(&dep.registry) {
                     Ok(r) => r,
                     Err(e) => {
@@ -142,18 +151,22 @@ impl UpdateCmd {
                 };
 
                 match indexer
-                    .index_package(registry, &dep.name, &dep.version)
+                    .index_package_with_cancel(registry, &dep.name, &dep.version, &cancel)
                     .await
                 {
                     Ok(result) => {
                         indexed.fetch_add(1, Ordering::Relaxed);
                         if verbose {
                             eprintln!(
-                                "  {}@{} -> indexed ({} chunks)",
-                                dep.name, dep.version, result.chunks_indexed
+                                "  {}@{} -> indexed ({} chunks{})",
+                                dep.name,
+                                dep.version,
+                                result.chunks_indexed,
+                                if result.truncated { ", truncated" } else { "" }
                             );
                         }
                     }
+                    Err(e) if e.is::<local::IndexCancelled>() => return,
                     Err(e) => {
                         failed.fetch_add(1, Ordering::Relaxed);
                         if verbose {
@@ -163,7 +176,7 @@ impl UpdateCmd {
                 }
 
                 let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-                if !verbose {
+                if show_count {
                     print!("\r[{}/{}] completed", done, total);
                     std::io::stdout().flush().ok();
                 }
@@ -173,19 +186,50 @@ impl UpdateCmd {
         .collect::<Vec<()>>()
         .await;
 
-        if !verbose {
+        if show_count {
             print!("\r{}\r", " ".repeat(40));
         }
 
         let indexed = indexed.load(Ordering::Relaxed);
         let failed = failed.load(Ordering::Relaxed);
+        let remaining = total - indexed - failed;
 
         println!("Results:");
         println!("  {} indexed", indexed);
         if failed > 0 {
             println!("  {} failed", failed);
         }
+        if cancel.is_cancelled() {
+            println!("  {} cancelled", remaining);
+            return Ok(());
+        }
+
+        prune_beyond_limit(&indexer, &referenced).await
+    }
+}
 
-        Ok(())
+/// Apply the configured `max_versions_per_package` retention, if any.
+async fn prune_beyond_limit(
+    indexer: &LocalIndexer,
+    referenced: &HashSet<(String, String, String)>,
+) -> Result<()> {
+    let keep = indexer.config().max_versions_per_package;
+    if keep == 0 {
+        return Ok(());
     }
+
+    let mut versions = indexer.db().list_versions().await?;
+    versions.retain(|v| v.tenant().is_none());
+    let to_prune = beyond_latest(&versions, keep, referenced);
+    if to_prune.is_empty() {
+        return Ok(());
+    }
+
+    println!("Pruning versions beyond the newest {}:", keep);
+    for ver in to_prune {
+        delete_version(indexer, ver).await?;
+        println!("  {}:{}@{}", ver.registry, ver.name, ver.version);
+    }
+
+    Ok(())
 }
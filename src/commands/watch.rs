@@ -12,12 +12,11 @@ use clap::Args;
 use futures::stream::{self, StreamExt};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
+use super::npm_deps::NpmDepsArgs;
 use crate::local::{self, LocalIndexer};
-use crate::manifests::{
-    Dependency, parse_cargo_deps, parse_go_deps, parse_maven_deps, parse_npm_deps,
-    parse_python_deps,
-};
+use crate::manifests::{Dependency, collect_deps};
 
 #[derive(Args)]
 pub struct WatchCmd {
@@ -28,6 +27,9 @@ pub struct WatchCmd {
     /// Number of packages to index concurrently
     #[arg(long, short = 'j', default_value = "4")]
     pub concurrency: usize,
+
+    #[command(flatten)]
+    pub npm: NpmDepsArgs,
 }
 
 impl WatchCmd {
@@ -38,8 +40,14 @@ impl WatchCmd {
         println!("Watching {} for manifest changes...", self.path.display());
         println!("Press Ctrl+C to stop.\n");
 
+        let cancel = local::cancel_on_ctrl_c();
+
         // Initial sync
-        self.sync_index(&index_dir).await?;
+        self.sync_index(&index_dir, &cancel).await?;
+        if cancel.is_cancelled() {
+            println!("\nStopping watch.");
+            return Ok(());
+        }
 
         // Set up file watcher
         let (tx, mut rx) = mpsc::channel(100);
@@ -61,6 +69,13 @@ impl WatchCmd {
             "Cargo.lock",
             "pyproject.toml",
             "requirements.txt",
+            "setup.cfg",
+            "setup.py",
+            "uv.lock",
+            "pdm.lock",
+            "pubspec.yaml",
+            "pubspec.lock",
+            "MODULE.bazel",
         ];
 
         for manifest in manifests {
@@ -98,13 +113,13 @@ impl WatchCmd {
                     // Debounce: only sync if enough time has passed
                     if last_sync.elapsed() > debounce {
                         println!("\nManifest changed, syncing...");
-                        if let Err(e) = self.sync_index(&index_dir).await {
+                        if let Err(e) = self.sync_index(&index_dir, &cancel).await {
                             eprintln!("Sync failed: {}", e);
                         }
                         last_sync = std::time::Instant::now();
                     }
                 }
-                _ = tokio::signal::ctrl_c() => {
+                _ = cancel.cancelled() => {
                     println!("\nStopping watch.");
                     break;
                 }
@@ -114,33 +129,23 @@ impl WatchCmd {
         Ok(())
     }
 
-    async fn sync_index(&self, index_dir: &std::path::Path) -> Result<()> {
+    async fn sync_index(
+        &self,
+        index_dir: &std::path::Path,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
         let indexer = Arc::new(LocalIndexer::new(index_dir).await?);
 
         // Get indexed versions
         let indexed_versions = indexer.db().list_versions().await?;
         let indexed_set: HashSet<(String, String, String)> = indexed_versions
             .iter()
+            .filter(|v| v.tenant().is_none())
             .map(|v| (v.registry.clone(), v.name.clone(), v.version.clone()))
             .collect();
 
         // Get manifest dependencies
-        let mut manifest_deps = Vec::new();
-        if let Ok(deps) = parse_npm_deps(&self.path) {
-            manifest_deps.extend(deps);
-        }
-        if let Ok(deps) = parse_cargo_deps(&self.path) {
-            manifest_deps.extend(deps);
-        }
-        if let Ok(deps) = parse_python_deps(&self.path) {
-            manifest_deps.extend(deps);
-        }
-        if let Ok(deps) = parse_maven_deps(&self.path) {
-            manifest_deps.extend(deps);
-        }
-        if let Ok(deps) = parse_go_deps(&self.path) {
-            manifest_deps.extend(deps);
-        }
+        let manifest_deps = collect_deps(&self.path, self.npm.options()?)?;
 
         // Find new packages to index
         let to_index: Vec<Dependency> = manifest_deps
@@ -165,6 +170,9 @@ impl WatchCmd {
             let indexed = Arc::clone(&indexed);
             let failed = Arc::clone(&failed);
             async move {
+                if cancel.is_cancelled() {
+                    return;
+                }
                 let registry = match Registry::from_str(&dep.registry) {
                     Ok(r) => r,
                     Err(_) => {
@@ -173,7 +181,7 @@ impl WatchCmd {
                     }
                 };
                 match indexer
-                    .index_package(registry, &dep.name, &dep.version)
+                    .index_package_with_cancel(registry, &dep.name, &dep.version, cancel)
                     .await
                 {
                     Ok(result) => {
@@ -183,6 +191,7 @@ impl WatchCmd {
                         );
                         indexed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                     }
+                    Err(e) if e.is::<local::IndexCancelled>() => {}
                     Err(e) => {
                         eprintln!("  {}@{} -> failed: {}", dep.name, dep.version, e);
                         failed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -1,5 +1,11 @@
 use crate::types::{ChunkType, Visibility};
 
+use super::language::Language;
+
+/// A declared parameter: its name, and its type when the declaration gives
+/// one (annotations are optional in Python and TypeScript).
+pub type Param = (String, Option<String>);
+
 /// A code chunk extracted from source code.
 ///
 /// Contains the code itself plus any associated documentation
@@ -18,12 +24,21 @@ pub struct CodeChunk {
     /// Full signature if applicable (e.g., function signature with params)
     pub signature: Option<String>,
 
+    /// Parameters of a function or method, without the receiver (`self`, `this`)
+    pub params: Vec<Param>,
+
+    /// Declared return type of a function or method
+    pub returns: Option<String>,
+
     /// The actual code
     pub code: String,
 
     /// Associated documentation (docstring, JSDoc, doc comment)
     pub documentation: Option<String>,
 
+    /// Enclosing type/trait name if this is a method
+    pub parent: Option<String>,
+
     /// File path within the package
     pub file_path: String,
 
@@ -38,6 +53,130 @@ pub struct CodeChunk {
 
     /// End byte offset
     pub end_byte: usize,
+
+    /// Marked deprecated (`@deprecated`, `#[deprecated]`, `Deprecated:`, ...)
+    pub deprecated: bool,
+
+    /// Reason or replacement given with the deprecation marker
+    pub deprecation_message: Option<String>,
+}
+
+/// A deprecation marker found on a symbol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Reason or replacement, when the marker carries one
+    pub message: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(message: Option<String>) -> Self {
+        let message = message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        Self { message }
+    }
+
+    /// Find a line of documentation starting with `tag` (`@deprecated`,
+    /// `Deprecated:`), taking the rest of the paragraph as the message.
+    ///
+    /// The message ends at a blank line or the next `@` tag.
+    pub fn from_doc_tag(doc: &str, tag: &str) -> Option<Self> {
+        let mut lines = doc.lines().map(str::trim);
+        let first = lines.by_ref().find_map(|line| line.strip_prefix(tag))?;
+
+        let mut message = vec![first.trim()];
+        message.extend(lines.take_while(|line| !line.is_empty() && !line.starts_with('@')));
+        Some(Self::new(Some(message.join(" "))))
+    }
+}
+
+/// The longest prefix of `s` at most `max_bytes` long that ends on a char
+/// boundary. Slicing chunk text by byte length must go through this, since a
+/// multibyte character straddling the limit would otherwise panic.
+pub fn safe_truncate(s: &str, max_bytes: usize) -> &str {
+    &s[..s.floor_char_boundary(max_bytes)]
+}
+
+/// A stored snippet (see [`CodeChunk::snippet`]) shortened to `max_len`
+/// bytes the same way: whole lines, cutting only a first line that is longer
+/// on its own.
+pub fn trim_snippet(snippet: &str, max_len: usize) -> &str {
+    let mut end = 0;
+    for line in snippet.split_inclusive('\n') {
+        if end + line.trim_end_matches('\n').len() > max_len {
+            if end == 0 {
+                return safe_truncate(snippet, max_len);
+            }
+            break;
+        }
+        end += line.len();
+    }
+    snippet[..end].trim_end_matches('\n')
+}
+
+// Limit total size to stay under embedding model token limits (~8k tokens ≈ 32k chars)
+// Prioritize: signature (most searchable) > docs (context) > code (implementation)
+const MAX_DOC_CHARS: usize = 4000;
+const MAX_SIG_CHARS: usize = 1000;
+const MAX_CODE_CHARS: usize = 2000;
+const MAX_NAME_CHARS: usize = 500;
+
+/// Line leading a chunk's embedding text to bias the embedding toward its
+/// identifier: the enclosing type, then the name `weight` times
+/// (`Client get get`). `None` at weight 0, the unweighted text.
+///
+/// A name appears once amid the documentation and code otherwise, so
+/// short queries that are just an identifier ("DataFrame") match weakly.
+pub fn name_header(name: &str, parent: Option<&str>, weight: usize) -> Option<String> {
+    if weight == 0 || name.is_empty() {
+        return None;
+    }
+    let mut words: Vec<&str> = parent.into_iter().collect();
+    words.extend(std::iter::repeat_n(name, weight));
+    Some(words.join(" "))
+}
+
+/// Text embedded for a chunk with the given name header (see
+/// [`name_header`]), documentation, signature and code. Also used to show
+/// what a stored chunk was embedded from.
+pub fn embedding_text(
+    header: Option<&str>,
+    documentation: Option<&str>,
+    signature: Option<&str>,
+    code: &str,
+) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(header) = header {
+        parts.push(safe_truncate(header, MAX_NAME_CHARS));
+    }
+
+    if let Some(doc) = documentation {
+        parts.push(safe_truncate(doc, MAX_DOC_CHARS));
+    }
+
+    if let Some(sig) = signature {
+        parts.push(safe_truncate(sig, MAX_SIG_CHARS));
+    }
+
+    parts.push(safe_truncate(code, MAX_CODE_CHARS));
+
+    parts.join("\n\n")
+}
+
+/// Text for a chunk's documentation embedding: the documentation and
+/// signature without the code, so the embedding says what the symbol does
+/// rather than how. `None` for undocumented chunks, which have nothing to
+/// add over their code embedding.
+pub fn doc_embedding_text(documentation: Option<&str>, signature: Option<&str>) -> Option<String> {
+    let doc = documentation.map(str::trim).filter(|doc| !doc.is_empty())?;
+
+    let mut text = safe_truncate(doc, MAX_DOC_CHARS).to_string();
+    if let Some(sig) = signature {
+        text.push_str("\n\n");
+        text.push_str(safe_truncate(sig, MAX_SIG_CHARS));
+    }
+    Some(text)
 }
 
 impl CodeChunk {
@@ -48,58 +187,78 @@ impl CodeChunk {
     /// - The API surface (from signature)
     /// - The implementation (from code)
     pub fn embedding_text(&self) -> String {
-        // Limit total size to stay under embedding model token limits (~8k tokens ≈ 32k chars)
-        // Prioritize: signature (most searchable) > docs (context) > code (implementation)
-        const MAX_DOC_CHARS: usize = 4000;
-        const MAX_SIG_CHARS: usize = 1000;
-        const MAX_CODE_CHARS: usize = 2000;
-
-        let mut parts = Vec::new();
-
-        if let Some(ref doc) = self.documentation {
-            if doc.len() > MAX_DOC_CHARS {
-                parts.push(&doc[..doc.floor_char_boundary(MAX_DOC_CHARS)]);
-            } else {
-                parts.push(doc.as_str());
-            }
-        }
-
-        if let Some(ref sig) = self.signature {
-            if sig.len() > MAX_SIG_CHARS {
-                parts.push(&sig[..sig.floor_char_boundary(MAX_SIG_CHARS)]);
-            } else {
-                parts.push(sig.as_str());
-            }
-        }
+        self.weighted_embedding_text(0)
+    }
 
-        let code_preview = if self.code.len() > MAX_CODE_CHARS {
-            &self.code[..self.code.floor_char_boundary(MAX_CODE_CHARS)]
-        } else {
-            &self.code
-        };
-        parts.push(code_preview);
+    /// [`Self::embedding_text`] led by the chunk's name repeated
+    /// `name_weight` times (see [`name_header`]).
+    pub fn weighted_embedding_text(&self, name_weight: usize) -> String {
+        let header = name_header(&self.name, self.parent.as_deref(), name_weight);
+        embedding_text(
+            header.as_deref(),
+            self.documentation.as_deref(),
+            self.signature.as_deref(),
+            &self.code,
+        )
+    }
 
-        parts.join("\n\n")
+    /// Text for the chunk's documentation embedding, if it is documented.
+    pub fn doc_embedding_text(&self) -> Option<String> {
+        doc_embedding_text(self.documentation.as_deref(), self.signature.as_deref())
     }
 
     /// Create a snippet for display in search results.
     /// Prioritizes signature + first few lines of code.
+    ///
+    /// Ends on a line boundary, taking whole lines up to `max_len` bytes.
+    /// Only a signature or first line longer than that (e.g. minified code)
+    /// is cut short.
     pub fn snippet(&self, max_len: usize) -> String {
         let mut result = String::new();
 
         if let Some(ref sig) = self.signature {
-            result.push_str(sig);
+            result.push_str(safe_truncate(sig, max_len.saturating_sub(1)));
             result.push('\n');
         }
 
-        let remaining = max_len.saturating_sub(result.len());
-        if remaining > 0 {
-            let code_snippet: String = self.code.chars().take(remaining).collect();
-            result.push_str(&code_snippet);
+        for line in self.code.lines() {
+            if result.len() + line.len() > max_len {
+                if result.is_empty() {
+                    result.push_str(safe_truncate(line, max_len));
+                }
+                break;
+            }
+            result.push_str(line);
+            result.push('\n');
         }
 
+        result.truncate(result.trim_end_matches('\n').len());
         result
     }
+
+    /// Language of the chunk's file, for tagging rendered code.
+    pub fn language(&self) -> Option<Language> {
+        Language::from_path(&self.file_path)
+    }
+}
+
+/// A trait implemented by a type, declared with `impl Trait for Type`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitImpl {
+    /// Trait name, without its path or generic arguments
+    pub trait_name: String,
+
+    /// Implementing type's name, without its path or generic arguments
+    pub type_name: String,
+
+    /// The impl header as written (e.g., `impl<T> Iterator for Iter<T>`)
+    pub signature: String,
+
+    /// File path within the package
+    pub file_path: String,
+
+    /// Line of the impl block (1-indexed)
+    pub line: u32,
 }
 
 /// Builder for creating CodeChunks during parsing.
@@ -109,13 +268,17 @@ pub struct ChunkBuilder {
     visibility: Visibility,
     name: Option<String>,
     signature: Option<String>,
+    params: Vec<Param>,
+    returns: Option<String>,
     code: Option<String>,
     documentation: Option<String>,
+    parent: Option<String>,
     file_path: Option<String>,
     start_line: Option<u32>,
     end_line: Option<u32>,
     start_byte: Option<usize>,
     end_byte: Option<usize>,
+    deprecation: Option<Deprecation>,
 }
 
 impl ChunkBuilder {
@@ -143,6 +306,13 @@ impl ChunkBuilder {
         self
     }
 
+    /// Declared parameters and return type of a function or method.
+    pub fn params(mut self, params: Vec<Param>, returns: Option<String>) -> Self {
+        self.params = params;
+        self.returns = returns;
+        self
+    }
+
     pub fn code(mut self, code: impl Into<String>) -> Self {
         self.code = Some(code.into());
         self
@@ -156,6 +326,11 @@ impl ChunkBuilder {
         self
     }
 
+    pub fn parent(mut self, parent: impl Into<String>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
     pub fn file_path(mut self, path: impl Into<String>) -> Self {
         self.file_path = Some(path.into());
         self
@@ -175,19 +350,30 @@ impl ChunkBuilder {
         self
     }
 
+    /// Mark the symbol deprecated when `deprecation` is found.
+    pub fn deprecation(mut self, deprecation: Option<Deprecation>) -> Self {
+        self.deprecation = deprecation;
+        self
+    }
+
     pub fn build(self) -> Option<CodeChunk> {
         Some(CodeChunk {
             chunk_type: self.chunk_type?,
             visibility: self.visibility,
             name: self.name?,
             signature: self.signature,
+            params: self.params,
+            returns: self.returns,
             code: self.code?,
             documentation: self.documentation,
+            parent: self.parent,
             file_path: self.file_path?,
             start_line: self.start_line?,
             end_line: self.end_line?,
             start_byte: self.start_byte?,
             end_byte: self.end_byte?,
+            deprecated: self.deprecation.is_some(),
+            deprecation_message: self.deprecation.and_then(|d| d.message),
         })
     }
 }
@@ -216,6 +402,48 @@ mod tests {
         assert!(chunk.documentation.is_some());
     }
 
+    #[test]
+    fn test_deprecation_from_doc_tag() {
+        let doc =
+            "Fetches data.\n@deprecated Use fetchJson instead,\nwhich parses.\n@param url the URL";
+        assert_eq!(
+            Deprecation::from_doc_tag(doc, "@deprecated"),
+            Some(Deprecation::new(Some(
+                "Use fetchJson instead, which parses.".to_string()
+            )))
+        );
+
+        let go = "Dial connects.\n\nDeprecated: use DialContext.\n\nMore text.";
+        assert_eq!(
+            Deprecation::from_doc_tag(go, "Deprecated:")
+                .unwrap()
+                .message,
+            Some("use DialContext.".to_string())
+        );
+
+        assert_eq!(
+            Deprecation::from_doc_tag("@deprecated", "@deprecated"),
+            Some(Deprecation::default())
+        );
+        assert!(Deprecation::from_doc_tag("Not deprecated: yet", "Deprecated:").is_none());
+    }
+
+    #[test]
+    fn test_chunk_builder_deprecation() {
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("old")
+            .code("fn old() {}")
+            .file_path("lib.rs")
+            .location(1, 1, 0, 11)
+            .deprecation(Some(Deprecation::new(Some("use new".to_string()))))
+            .build()
+            .unwrap();
+
+        assert!(chunk.deprecated);
+        assert_eq!(chunk.deprecation_message.as_deref(), Some("use new"));
+    }
+
     #[test]
     fn test_chunk_builder_default_visibility() {
         let chunk = ChunkBuilder::new()
@@ -264,6 +492,84 @@ mod tests {
         assert!(text.contains("fn test()"));
     }
 
+    #[test]
+    fn test_weighted_embedding_text_repeats_name() {
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Method)
+            .name("merge")
+            .parent("DataFrame")
+            .signature("def merge(self, right)")
+            .code("def merge(self, right): ...")
+            .documentation("Merge with another frame.")
+            .file_path("frame.py")
+            .location(1, 1, 0, 10)
+            .build()
+            .unwrap();
+
+        assert_eq!(chunk.weighted_embedding_text(0), chunk.embedding_text());
+        assert!(
+            chunk
+                .embedding_text()
+                .starts_with("Merge with another frame.")
+        );
+
+        let text = chunk.weighted_embedding_text(2);
+        assert_eq!(
+            text,
+            "DataFrame merge merge\n\nMerge with another frame.\n\n\
+             def merge(self, right)\n\ndef merge(self, right): ..."
+        );
+        assert_eq!(
+            name_header("DataFrame", None, 1).as_deref(),
+            Some("DataFrame")
+        );
+        assert_eq!(name_header("DataFrame", None, 0), None);
+    }
+
+    #[test]
+    fn test_weighted_embedding_text_stays_bounded() {
+        // '─' is 3 bytes, so a byte limit can land inside one
+        let name = "─".repeat(100);
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name(name.clone())
+            .code("x".repeat(5000))
+            .file_path("test.rs")
+            .location(1, 1, 0, 5000)
+            .build()
+            .unwrap();
+
+        // Should not panic, and the header is cut like the other parts
+        let text = chunk.weighted_embedding_text(10);
+        let header = text.split("\n\n").next().unwrap();
+        assert!(header.len() <= MAX_NAME_CHARS);
+        assert!(header.starts_with(&name));
+        assert!(text.len() < MAX_NAME_CHARS + 2500);
+    }
+
+    #[test]
+    fn test_doc_embedding_text_leaves_out_code() {
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("retry")
+            .signature("fn retry(attempts: u32)")
+            .code("fn retry(attempts: u32) { loop {} }")
+            .documentation("Retry a failed request with backoff.")
+            .file_path("test.rs")
+            .location(1, 1, 0, 10)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            chunk.doc_embedding_text().unwrap(),
+            "Retry a failed request with backoff.\n\nfn retry(attempts: u32)"
+        );
+
+        // Undocumented chunks get no documentation embedding
+        assert_eq!(doc_embedding_text(None, Some("fn retry()")), None);
+        assert_eq!(doc_embedding_text(Some("  \n"), Some("fn retry()")), None);
+    }
+
     #[test]
     fn test_snippet_respects_max_len() {
         let chunk = ChunkBuilder::new()
@@ -279,6 +585,37 @@ mod tests {
         assert!(snippet.len() <= 100);
     }
 
+    #[test]
+    fn test_snippet_ends_on_line_boundary() {
+        let code = (1..=20)
+            .map(|i| format!("    let value_{} = compute({});", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("compute_all")
+            .signature("fn compute_all()")
+            .code(format!("fn compute_all() {{\n{}\n}}", code))
+            .file_path("src/compute.rs")
+            .location(1, 22, 0, 700)
+            .build()
+            .unwrap();
+
+        let snippet = chunk.snippet(120);
+        assert!(snippet.len() <= 120);
+        assert!(snippet.starts_with("fn compute_all()\nfn compute_all() {\n"));
+        // Every line is whole
+        for line in snippet.lines().skip(2) {
+            assert!(
+                line.starts_with("    let value_") && line.ends_with(");"),
+                "{}",
+                line
+            );
+        }
+        assert_eq!(chunk.language(), Some(Language::Rust));
+        assert_eq!(chunk.language().unwrap().fence_tag(), "rust");
+    }
+
     #[test]
     fn test_embedding_text_utf8_boundary() {
         // Create code with multi-byte UTF-8 characters near the 1000 byte boundary
@@ -317,4 +654,73 @@ mod tests {
         // Should be truncated to ~2000 chars (MAX_CODE_CHARS), not the full 5000
         assert!(text.len() < 2500);
     }
+
+    #[test]
+    fn test_safe_truncate() {
+        assert_eq!(safe_truncate("hello", 3), "hel");
+        assert_eq!(safe_truncate("hello", 10), "hello");
+        // 'é' is 2 bytes, '─' is 3
+        assert_eq!(safe_truncate("aé", 2), "a");
+        assert_eq!(safe_truncate("──", 5), "─");
+        assert_eq!(safe_truncate("─", 0), "");
+    }
+
+    #[test]
+    fn test_snippet_utf8_boundary() {
+        // One long line of multibyte characters, cut mid-character at every limit
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("box_drawing")
+            .code("─".repeat(100))
+            .file_path("test.rs")
+            .location(1, 1, 0, 300)
+            .build()
+            .unwrap();
+
+        for max_len in 0..12 {
+            let snippet = chunk.snippet(max_len);
+            assert!(snippet.len() <= max_len);
+            assert_eq!(snippet.len() % 3, 0);
+        }
+
+        // A signature over the limit is cut too
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("greet")
+            .signature(format!("fn greet(){}", "é".repeat(50)))
+            .code("fn greet() {}")
+            .file_path("test.rs")
+            .location(1, 1, 0, 13)
+            .build()
+            .unwrap();
+
+        let snippet = chunk.snippet(20);
+        assert!(snippet.len() <= 20);
+        assert!(snippet.starts_with("fn greet()é"));
+        assert!(!snippet.contains("fn greet() {}"));
+    }
+
+    #[test]
+    fn test_trim_snippet_matches_shorter_snippet() {
+        let code = (1..=20)
+            .map(|i| format!("    let value_{} = compute({});", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunk = ChunkBuilder::new()
+            .chunk_type(ChunkType::Function)
+            .name("compute_all")
+            .signature("fn compute_all()")
+            .code(format!("fn compute_all() {{\n{}\n}}", code))
+            .file_path("src/compute.rs")
+            .location(1, 22, 0, 700)
+            .build()
+            .unwrap();
+
+        let stored = chunk.snippet(500);
+        for max_len in [0, 17, 120, 300, 500] {
+            assert_eq!(trim_snippet(&stored, max_len), chunk.snippet(max_len));
+        }
+        assert_eq!(trim_snippet(&stored, 1000), stored);
+        assert_eq!(trim_snippet("─────", 4), "─");
+    }
 }
@@ -1,6 +1,6 @@
 use crate::types::Registry;
 
-use super::chunk::CodeChunk;
+use super::chunk::{CodeChunk, TraitImpl};
 use super::error::IndexerError;
 
 /// Supported languages for parsing.
@@ -12,6 +12,8 @@ pub enum Language {
     Rust,
     Go,
     Java,
+    Scala,
+    Dart,
     /// Markdown files (README, docs)
     Markdown,
 }
@@ -26,6 +28,8 @@ impl Language {
             "rs" => Some(Language::Rust),
             "go" => Some(Language::Go),
             "java" => Some(Language::Java),
+            "scala" | "sc" => Some(Language::Scala),
+            "dart" => Some(Language::Dart),
             "md" | "markdown" => Some(Language::Markdown),
             _ => None,
         }
@@ -38,16 +42,38 @@ impl Language {
     }
 
     /// Infer likely language from registry.
+    ///
+    /// Packages may hold other languages too; their files are parsed by
+    /// extension with [`Language::from_path`].
     pub fn from_registry(registry: Registry) -> Vec<Self> {
         match registry {
             Registry::Npm => vec![Language::TypeScript, Language::JavaScript],
             Registry::Pypi => vec![Language::Python],
             Registry::Crates => vec![Language::Rust],
             Registry::Go => vec![Language::Go],
-            Registry::Maven => vec![Language::Java],
+            Registry::Maven => vec![Language::Java, Language::Scala],
+            Registry::Pub => vec![Language::Dart],
+            Registry::Git => vec![
+                Language::TypeScript,
+                Language::JavaScript,
+                Language::Python,
+                Language::Rust,
+                Language::Go,
+                Language::Java,
+                Language::Scala,
+                Language::Dart,
+            ],
         }
     }
 
+    /// Whether this language holds documentation rather than code.
+    ///
+    /// Documentation is indexed for every registry, regardless of
+    /// [`Language::from_registry`].
+    pub fn is_documentation(&self) -> bool {
+        matches!(self, Language::Markdown)
+    }
+
     /// File extensions this language typically uses.
     pub fn extensions(&self) -> &'static [&'static str] {
         match self {
@@ -57,10 +83,28 @@ impl Language {
             Language::Rust => &["rs"],
             Language::Go => &["go"],
             Language::Java => &["java"],
+            Language::Scala => &["scala", "sc"],
+            Language::Dart => &["dart"],
             Language::Markdown => &["md", "markdown"],
         }
     }
 
+    /// Info string for fenced code blocks (```` ```rust ````), which
+    /// Markdown renderers use to pick syntax highlighting.
+    pub fn fence_tag(&self) -> &'static str {
+        match self {
+            Language::TypeScript => "typescript",
+            Language::JavaScript => "javascript",
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::Go => "go",
+            Language::Java => "java",
+            Language::Scala => "scala",
+            Language::Dart => "dart",
+            Language::Markdown => "markdown",
+        }
+    }
+
     /// Name of the language.
     pub fn name(&self) -> &'static str {
         match self {
@@ -70,6 +114,8 @@ impl Language {
             Language::Rust => "Rust",
             Language::Go => "Go",
             Language::Java => "Java",
+            Language::Scala => "Scala",
+            Language::Dart => "Dart",
             Language::Markdown => "Markdown",
         }
     }
@@ -85,6 +131,20 @@ pub trait LanguageParser: Send + Sync {
     /// Parse source code and extract code chunks.
     fn parse(&self, source: &str, file_path: &str) -> Result<Vec<CodeChunk>, IndexerError>;
 
+    /// Parse source code into chunks plus the trait implementations it
+    /// declares.
+    ///
+    /// Only languages that declare implementations explicitly (Rust's
+    /// `impl Trait for Type`) report any; Go interfaces are satisfied
+    /// implicitly, so Go methods only record their receiver as parent.
+    fn parse_with_impls(
+        &self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<(Vec<CodeChunk>, Vec<TraitImpl>), IndexerError> {
+        Ok((self.parse(source, file_path)?, Vec::new()))
+    }
+
     /// The language this parser handles.
     fn language(&self) -> Language;
 
@@ -110,6 +170,9 @@ mod tests {
         assert_eq!(Language::from_extension("rs"), Some(Language::Rust));
         assert_eq!(Language::from_extension("go"), Some(Language::Go));
         assert_eq!(Language::from_extension("java"), Some(Language::Java));
+        assert_eq!(Language::from_extension("scala"), Some(Language::Scala));
+        assert_eq!(Language::from_extension("sc"), Some(Language::Scala));
+        assert_eq!(Language::from_extension("dart"), Some(Language::Dart));
         assert_eq!(Language::from_extension("txt"), None);
     }
 
@@ -134,5 +197,9 @@ mod tests {
 
         let crates_langs = Language::from_registry(Registry::Crates);
         assert!(crates_langs.contains(&Language::Rust));
+
+        let maven_langs = Language::from_registry(Registry::Maven);
+        assert!(maven_langs.contains(&Language::Java));
+        assert!(maven_langs.contains(&Language::Scala));
     }
 }
@@ -0,0 +1,327 @@
+use crate::types::{ChunkType, Visibility};
+use tree_sitter::{Node, Parser};
+
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::error::IndexerError;
+use crate::indexer::language::{Language, LanguageParser};
+
+/// Parser for Dart using tree-sitter.
+///
+/// Dart visibility is library-level: identifiers starting with `_` are
+/// private, everything else is public.
+///
+/// The grammar keeps a function's signature and body as sibling nodes, so
+/// chunks span from the signature to the following `function_body`. A
+/// top-level function parses as a `lambda_expression` holding both.
+pub struct DartParser {
+    _marker: (),
+}
+
+impl DartParser {
+    pub fn new() -> Result<Self, IndexerError> {
+        Ok(Self { _marker: () })
+    }
+
+    fn create_parser() -> Result<Parser, IndexerError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_dart::language();
+        parser
+            .set_language(&language)
+            .map_err(|e| IndexerError::TreeSitter(e.to_string()))?;
+        Ok(parser)
+    }
+
+    fn extract_chunks(
+        &self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<Vec<CodeChunk>, IndexerError> {
+        let mut parser = Self::create_parser()?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| IndexerError::ParseError("failed to parse Dart source".into()))?;
+
+        let mut chunks = Vec::new();
+        self.visit_node(tree.root_node(), source, file_path, &mut chunks);
+        Ok(chunks)
+    }
+
+    fn visit_node(&self, node: Node, source: &str, file_path: &str, chunks: &mut Vec<CodeChunk>) {
+        match node.kind() {
+            // Top-level functions only; methods wrap theirs in method_signature
+            "lambda_expression" if node.parent().is_some_and(|p| p.kind() == "program") => {
+                if let Some(signature) = node.child_by_field_name("parameters") {
+                    let name = self.get_child_text(signature, "name", source);
+                    if let Some(chunk) = self.extract_callable(
+                        signature,
+                        name,
+                        ChunkType::Function,
+                        source,
+                        file_path,
+                    ) {
+                        chunks.push(chunk);
+                    }
+                }
+            }
+            "method_signature" => {
+                let name = self.method_name(node, source);
+                if let Some(chunk) =
+                    self.extract_callable(node, name, ChunkType::Method, source, file_path)
+                {
+                    chunks.push(chunk);
+                }
+                return;
+            }
+            "class_definition" => {
+                if let Some(chunk) = self.extract_class(node, source, file_path) {
+                    chunks.push(chunk);
+                }
+            }
+            "enum_declaration" => {
+                if let Some(chunk) = self.extract_enum(node, source, file_path) {
+                    chunks.push(chunk);
+                }
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit_node(child, source, file_path, chunks);
+        }
+    }
+
+    /// Extract a function or method, including its body if one follows.
+    fn extract_callable(
+        &self,
+        node: Node,
+        name: Option<String>,
+        chunk_type: ChunkType,
+        source: &str,
+        file_path: &str,
+    ) -> Option<CodeChunk> {
+        let name = name?;
+        let signature = node.utf8_text(source.as_bytes()).ok()?;
+
+        let end = node
+            .next_sibling()
+            .filter(|n| n.kind() == "function_body")
+            .unwrap_or(node);
+        let code = source.get(node.start_byte()..end.end_byte())?;
+
+        // Doc comments sit outside the lambda_expression wrapping a top-level function
+        let documented = node
+            .parent()
+            .filter(|p| p.kind() == "lambda_expression")
+            .unwrap_or(node);
+        let doc = self.extract_doc_comment(documented, source);
+        let visibility = self.detect_visibility(&name);
+
+        ChunkBuilder::new()
+            .chunk_type(chunk_type)
+            .visibility(visibility)
+            .name(name)
+            .signature(signature.trim().to_string())
+            .code(code)
+            .documentation(doc.unwrap_or_default())
+            .file_path(file_path)
+            .location(
+                node.start_position().row as u32 + 1,
+                end.end_position().row as u32 + 1,
+                node.start_byte(),
+                end.end_byte(),
+            )
+            .build()
+    }
+
+    fn extract_class(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
+        let name = self.get_child_text(node, "name", source)?;
+        let code = node.utf8_text(source.as_bytes()).ok()?;
+        let doc = self.extract_doc_comment(node, source);
+        let visibility = self.detect_visibility(&name);
+
+        ChunkBuilder::new()
+            .chunk_type(ChunkType::Class)
+            .visibility(visibility)
+            .name(name)
+            .signature(code.lines().next().unwrap_or("").to_string())
+            .code(code)
+            .documentation(doc.unwrap_or_default())
+            .file_path(file_path)
+            .location(
+                node.start_position().row as u32 + 1,
+                node.end_position().row as u32 + 1,
+                node.start_byte(),
+                node.end_byte(),
+            )
+            .build()
+    }
+
+    fn extract_enum(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
+        let name = self.get_child_text(node, "name", source)?;
+        let code = node.utf8_text(source.as_bytes()).ok()?;
+        let doc = self.extract_doc_comment(node, source);
+        let visibility = self.detect_visibility(&name);
+
+        ChunkBuilder::new()
+            .chunk_type(ChunkType::Type)
+            .visibility(visibility)
+            .name(name)
+            .signature(code.lines().next().unwrap_or("").to_string())
+            .code(code)
+            .documentation(doc.unwrap_or_default())
+            .file_path(file_path)
+            .location(
+                node.start_position().row as u32 + 1,
+                node.end_position().row as u32 + 1,
+                node.start_byte(),
+                node.end_byte(),
+            )
+            .build()
+    }
+
+    /// Name of a method from its inner signature (function, getter, setter, constructor).
+    fn method_name(&self, node: Node, source: &str) -> Option<String> {
+        let mut cursor = node.walk();
+        let inner = node
+            .children(&mut cursor)
+            .find(|c| c.kind().ends_with("_signature"))?;
+        self.get_child_text(inner, "name", source)
+    }
+
+    /// Collect consecutive `///` comments above a declaration.
+    fn extract_doc_comment(&self, node: Node, source: &str) -> Option<String> {
+        let mut comments = Vec::new();
+        let mut prev = node.prev_sibling();
+
+        while let Some(sibling) = prev {
+            match sibling.kind() {
+                "comment" | "documentation_comment" => {
+                    let text = sibling.utf8_text(source.as_bytes()).ok()?;
+                    if !text.starts_with("///") {
+                        break;
+                    }
+                    // A single node may hold several `///` lines
+                    for line in text.lines().rev() {
+                        comments.push(line.trim().trim_start_matches("///").trim().to_string());
+                    }
+                }
+                "annotation" | "marker_annotation" => {}
+                _ => break,
+            }
+            prev = sibling.prev_sibling();
+        }
+
+        if comments.is_empty() {
+            None
+        } else {
+            comments.reverse();
+            Some(comments.join("\n"))
+        }
+    }
+
+    fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
+        let child = node.child_by_field_name(field)?;
+        child
+            .utf8_text(source.as_bytes())
+            .ok()
+            .map(|s| s.to_string())
+    }
+
+    /// Dart visibility: `_`-prefixed = library-private, otherwise public
+    fn detect_visibility(&self, name: &str) -> Visibility {
+        if name.starts_with('_') {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }
+    }
+}
+
+impl LanguageParser for DartParser {
+    fn parse(&self, source: &str, file_path: &str) -> Result<Vec<CodeChunk>, IndexerError> {
+        self.extract_chunks(source, file_path)
+    }
+
+    fn language(&self) -> Language {
+        Language::Dart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_top_level_function() {
+        let parser = DartParser::new().unwrap();
+        let source = r#"
+/// Adds two numbers.
+/// Returns the sum.
+int add(int a, int b) {
+  return a + b;
+}
+"#;
+        let chunks = parser.parse(source, "lib/math.dart").unwrap();
+
+        let func = chunks.iter().find(|c| c.name == "add").unwrap();
+        assert_eq!(func.chunk_type, ChunkType::Function);
+        assert_eq!(func.visibility, Visibility::Public);
+        assert!(func.code.contains("return a + b;"));
+        let doc = func.documentation.as_ref().unwrap();
+        assert!(doc.contains("Adds two numbers."));
+        assert!(doc.contains("Returns the sum."));
+    }
+
+    #[test]
+    fn test_parse_class_and_methods() {
+        let parser = DartParser::new().unwrap();
+        let source = r#"
+/// A simple counter.
+class Counter {
+  int _count = 0;
+
+  /// Increments the counter.
+  void increment() {
+    _count++;
+  }
+
+  void _reset() {
+    _count = 0;
+  }
+}
+"#;
+        let chunks = parser.parse(source, "lib/counter.dart").unwrap();
+
+        let class = chunks.iter().find(|c| c.name == "Counter").unwrap();
+        assert_eq!(class.chunk_type, ChunkType::Class);
+        assert!(
+            class
+                .documentation
+                .as_ref()
+                .unwrap()
+                .contains("A simple counter")
+        );
+
+        let method = chunks.iter().find(|c| c.name == "increment").unwrap();
+        assert_eq!(method.chunk_type, ChunkType::Method);
+        assert_eq!(method.visibility, Visibility::Public);
+        assert!(method.code.contains("_count++"));
+
+        let private = chunks.iter().find(|c| c.name == "_reset").unwrap();
+        assert_eq!(private.visibility, Visibility::Private);
+    }
+
+    #[test]
+    fn test_parse_enum() {
+        let parser = DartParser::new().unwrap();
+        let source = r#"
+enum _Mode { light, dark }
+"#;
+        let chunks = parser.parse(source, "lib/mode.dart").unwrap();
+
+        let mode = chunks.iter().find(|c| c.name == "_Mode").unwrap();
+        assert_eq!(mode.chunk_type, ChunkType::Type);
+        assert_eq!(mode.visibility, Visibility::Private);
+    }
+}
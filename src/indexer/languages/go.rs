@@ -1,7 +1,7 @@
 use crate::types::{ChunkType, Visibility};
 use tree_sitter::{Node, Parser};
 
-use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation, Param};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
@@ -58,6 +58,11 @@ impl GoParser {
             "type_declaration" => {
                 self.extract_types(node, source, file_path, &mut *chunks);
             }
+            "const_declaration" | "var_declaration"
+                if node.parent().is_some_and(|p| p.kind() == "source_file") =>
+            {
+                self.extract_values(node, source, file_path, &mut *chunks);
+            }
             _ => {}
         }
 
@@ -72,13 +77,16 @@ impl GoParser {
         let code = node.utf8_text(source.as_bytes()).ok()?;
         let doc = self.extract_doc_comment(node, source);
         let visibility = self.detect_visibility(&name);
+        let (params, returns) = self.extract_params(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Function)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.extract_signature(node, source).unwrap_or_default())
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -95,13 +103,16 @@ impl GoParser {
         let code = node.utf8_text(source.as_bytes()).ok()?;
         let doc = self.extract_doc_comment(node, source);
         let visibility = self.detect_visibility(&name);
+        let (params, returns) = self.extract_params(node, source);
 
-        ChunkBuilder::new()
+        let mut chunk = ChunkBuilder::new()
             .chunk_type(ChunkType::Method)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.extract_signature(node, source).unwrap_or_default())
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -110,7 +121,30 @@ impl GoParser {
                 node.start_byte(),
                 node.end_byte(),
             )
-            .build()
+            .build()?;
+        // Grouping methods by receiver records each type's method set, which
+        // decides the interfaces it satisfies
+        chunk.parent = self.receiver_type(node, source);
+        Some(chunk)
+    }
+
+    /// Base type of a method's receiver: `Server` for `(s *Server)`, `List`
+    /// for `(l List[T])`.
+    fn receiver_type(&self, node: Node, source: &str) -> Option<String> {
+        let receiver = node.child_by_field_name("receiver")?;
+        let mut cursor = receiver.walk();
+        let param = receiver
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "parameter_declaration")?;
+        let mut ty = param.child_by_field_name("type")?;
+        loop {
+            ty = match ty.kind() {
+                "generic_type" => ty.child_by_field_name("type")?,
+                "pointer_type" | "parenthesized_type" => ty.named_child(0)?,
+                _ => break,
+            };
+        }
+        ty.utf8_text(source.as_bytes()).ok().map(|s| s.to_string())
     }
 
     fn extract_types(
@@ -120,20 +154,26 @@ impl GoParser {
         file_path: &str,
         chunks: &mut Vec<CodeChunk>,
     ) {
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "type_spec"
-                && let Some(chunk) = self.extract_type_spec(child, source, file_path)
+        let specs = self.specs(node);
+        for spec in &specs {
+            if spec.kind() == "type_spec"
+                && let Some(chunk) = self.extract_type_spec(*spec, &specs, source, file_path)
             {
                 chunks.push(chunk);
             }
         }
     }
 
-    fn extract_type_spec(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
+    fn extract_type_spec(
+        &self,
+        node: Node,
+        specs: &[Node],
+        source: &str,
+        file_path: &str,
+    ) -> Option<CodeChunk> {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
-        let doc = self.extract_doc_comment(node.parent()?, source);
+        let doc = self.extract_spec_doc(node, specs, source);
         let visibility = self.detect_visibility(&name);
 
         // Determine if struct or interface
@@ -154,8 +194,12 @@ impl GoParser {
             .chunk_type(chunk_type)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(
+                self.extract_type_signature(node, source)
+                    .unwrap_or_default(),
+            )
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -167,12 +211,108 @@ impl GoParser {
             .build()
     }
 
+    /// Extract package-level `const` and `var` declarations.
+    ///
+    /// Each spec in a grouped `const (...)` or `var (...)` gets its own chunk
+    /// spanning just that spec, one per declared name.
+    fn extract_values(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &str,
+        chunks: &mut Vec<CodeChunk>,
+    ) {
+        let keyword = if node.kind() == "const_declaration" {
+            "const"
+        } else {
+            "var"
+        };
+        let specs = self.specs(node);
+        for spec in &specs {
+            let Ok(code) = spec.utf8_text(source.as_bytes()) else {
+                continue;
+            };
+            let doc = self.extract_spec_doc(*spec, &specs, source);
+            let signature = format!("{keyword} {}", code.lines().next().unwrap_or(""));
+
+            let mut cursor = spec.walk();
+            for name in spec.children_by_field_name("name", &mut cursor) {
+                let Ok(name) = name.utf8_text(source.as_bytes()) else {
+                    continue;
+                };
+                if name == "_" {
+                    continue;
+                }
+                let chunk = ChunkBuilder::new()
+                    .chunk_type(ChunkType::Constant)
+                    .visibility(self.detect_visibility(name))
+                    .name(name)
+                    .signature(signature.clone())
+                    .code(code)
+                    .deprecation(self.detect_deprecation(doc.as_deref()))
+                    .documentation(doc.clone().unwrap_or_default())
+                    .file_path(file_path)
+                    .location(
+                        spec.start_position().row as u32 + 1,
+                        spec.end_position().row as u32 + 1,
+                        spec.start_byte(),
+                        spec.end_byte(),
+                    )
+                    .build();
+                if let Some(chunk) = chunk {
+                    chunks.push(chunk);
+                }
+            }
+        }
+    }
+
+    /// Specs of a `const`, `var` or `type` declaration, grouped or not.
+    fn specs<'a>(&self, node: Node<'a>) -> Vec<Node<'a>> {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .flat_map(|child| {
+                if child.kind() == "var_spec_list" {
+                    let mut cursor = child.walk();
+                    child.children(&mut cursor).collect()
+                } else {
+                    vec![child]
+                }
+            })
+            .filter(|c| matches!(c.kind(), "const_spec" | "var_spec" | "type_spec"))
+            .collect()
+    }
+
+    /// Doc comment for one spec of a declaration.
+    ///
+    /// Specs inside a parenthesized group carry their own comments. The
+    /// comment above the keyword documents the group as a whole, so it only
+    /// applies to a spec when that spec is the only one.
+    fn extract_spec_doc(&self, spec: Node, specs: &[Node], source: &str) -> Option<String> {
+        self.extract_doc_comment(spec, source).or_else(|| {
+            let declaration = spec.parent()?;
+            let declaration = if declaration.kind() == "var_spec_list" {
+                declaration.parent()?
+            } else {
+                declaration
+            };
+            if specs.len() == 1 {
+                self.extract_doc_comment(declaration, source)
+            } else {
+                None
+            }
+        })
+    }
+
     fn extract_doc_comment(&self, node: Node, source: &str) -> Option<String> {
         let mut comments = Vec::new();
         let mut prev = node.prev_sibling();
 
         while let Some(sibling) = prev {
-            if sibling.kind() == "comment" {
+            // A comment trailing the previous line's code documents that line.
+            let trailing = sibling
+                .prev_sibling()
+                .is_some_and(|before| before.end_position().row == sibling.start_position().row);
+            if sibling.kind() == "comment" && !trailing {
                 let text = sibling.utf8_text(source.as_bytes()).ok()?;
                 comments.push(text.trim_start_matches("//").trim().to_string());
             } else {
@@ -189,6 +329,71 @@ impl GoParser {
         }
     }
 
+    /// Everything from `func` up to the body, so multi-line parameter lists
+    /// are kept whole. Body-less declarations (implemented in assembly) are
+    /// entirely signature.
+    fn extract_signature(&self, node: Node, source: &str) -> Option<String> {
+        let mut cursor = node.walk();
+        let end = node
+            .children(&mut cursor)
+            .find(|child| child.kind() == "block")
+            .map_or(node.end_byte(), |block| block.start_byte());
+        source
+            .get(node.start_byte()..end)
+            .map(|s| s.trim().to_string())
+    }
+
+    /// `type`, the name and any type parameters with their constraints, then
+    /// the underlying type up to the body of a struct or interface. Type
+    /// parameter lists spanning several lines are kept whole.
+    fn extract_type_signature(&self, node: Node, source: &str) -> Option<String> {
+        let underlying = node.child_by_field_name("type")?;
+        let end = match underlying.kind() {
+            "struct_type" | "interface_type" => {
+                let mut cursor = underlying.walk();
+                underlying
+                    .children(&mut cursor)
+                    .find(|child| matches!(child.kind(), "{" | "field_declaration_list"))
+                    .map_or(underlying.end_byte(), |body| body.start_byte())
+            }
+            _ => underlying.end_byte(),
+        };
+        source
+            .get(node.start_byte()..end)
+            .map(|s| format!("type {}", s.trim()))
+    }
+
+    /// Parameters (without the receiver) and result of a function or
+    /// method. `a, b int` gives both names the type; unnamed parameters are
+    /// named `_`, and a variadic one's type keeps its `...`.
+    fn extract_params(&self, node: Node, source: &str) -> (Vec<Param>, Option<String>) {
+        let mut params = Vec::new();
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                let ty = self.get_child_text(param, "type", source);
+                let ty = match param.kind() {
+                    "parameter_declaration" => ty,
+                    "variadic_parameter_declaration" => ty.map(|ty| format!("...{}", ty)),
+                    _ => continue,
+                };
+
+                let mut names_cursor = param.walk();
+                let names: Vec<String> = param
+                    .children_by_field_name("name", &mut names_cursor)
+                    .filter_map(|n| n.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string)
+                    .collect();
+                if names.is_empty() {
+                    params.push(("_".to_string(), ty));
+                } else {
+                    params.extend(names.into_iter().map(|name| (name, ty.clone())));
+                }
+            }
+        }
+        (params, self.get_child_text(node, "result", source))
+    }
+
     fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
         let child = node.child_by_field_name(field)?;
         child
@@ -197,6 +402,11 @@ impl GoParser {
             .map(|s| s.to_string())
     }
 
+    /// Go marks deprecation with a doc comment paragraph starting `Deprecated:`.
+    fn detect_deprecation(&self, doc: Option<&str>) -> Option<Deprecation> {
+        Deprecation::from_doc_tag(doc?, "Deprecated:")
+    }
+
     /// Go visibility: Capitalized = exported, lowercase = unexported
     fn detect_visibility(&self, name: &str) -> Visibility {
         if name
@@ -242,6 +452,195 @@ func Add(a, b int) int {
         assert_eq!(chunks[0].visibility, Visibility::Public);
     }
 
+    #[test]
+    fn test_multiline_signature() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+func NewServer(
+    addr string,
+    handler http.Handler,
+    opts ...Option,
+) (*Server, error) {
+    return nil, nil
+}
+"#;
+        let chunks = parser.parse(source, "server.go").unwrap();
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].signature.as_deref(),
+            Some(
+                "func NewServer(\n    addr string,\n    handler http.Handler,\n    opts ...Option,\n) (*Server, error)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_method_signature_with_receiver() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+// Get fetches a key.
+func (c *Client) Get(ctx context.Context, key string) ([]byte, error) { return nil, nil }
+
+func (c *Client) close() error
+"#;
+        let chunks = parser.parse(source, "client.go").unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].chunk_type, ChunkType::Method);
+        assert_eq!(
+            chunks[0].signature.as_deref(),
+            Some("func (c *Client) Get(ctx context.Context, key string) ([]byte, error)")
+        );
+        assert_eq!(
+            chunks[1].signature.as_deref(),
+            Some("func (c *Client) close() error")
+        );
+    }
+
+    #[test]
+    fn test_methods_grouped_by_receiver_type() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+func (c *Client) Get(key string) string { return "" }
+func (l List[T]) Len() int { return 0 }
+func (Buffer) Reset() {}
+func Open() *Client { return nil }
+"#;
+        let chunks = parser.parse(source, "client.go").unwrap();
+        let parents: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.name.as_str(), c.parent.as_deref()))
+            .collect();
+        assert_eq!(
+            parents,
+            vec![
+                ("Get", Some("Client")),
+                ("Len", Some("List")),
+                ("Reset", Some("Buffer")),
+                ("Open", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_params_and_results() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+func (s *Server) Handle(w http.ResponseWriter, r *http.Request) {}
+
+func Join(sep string, a, b string, rest ...string) (string, error) { return "", nil }
+
+func Apply(func(int) int, int) int { return 0 }
+"#;
+        let chunks = parser.parse(source, "server.go").unwrap();
+
+        let handle = &chunks[0];
+        assert_eq!(
+            handle.params,
+            vec![
+                ("w".to_string(), Some("http.ResponseWriter".to_string())),
+                ("r".to_string(), Some("*http.Request".to_string())),
+            ]
+        );
+        assert_eq!(handle.returns, None);
+
+        let join = &chunks[1];
+        let types: Vec<_> = join
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{} {}", name, ty.as_deref().unwrap_or("")))
+            .collect();
+        assert_eq!(
+            types,
+            vec!["sep string", "a string", "b string", "rest ...string"]
+        );
+        assert_eq!(join.returns.as_deref(), Some("(string, error)"));
+
+        let apply = &chunks[2];
+        assert_eq!(
+            apply.params,
+            vec![
+                ("_".to_string(), Some("func(int) int".to_string())),
+                ("_".to_string(), Some("int".to_string())),
+            ]
+        );
+        assert_eq!(apply.returns.as_deref(), Some("int"));
+    }
+
+    #[test]
+    fn test_generic_function_signature() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+// Map applies f to each element.
+func Map[T, U any](s []T, f func(T) U) []U {
+    return nil
+}
+
+func Keys[
+    M ~map[K]V,
+    K comparable,
+    V any,
+](m M) []K {
+    return nil
+}
+"#;
+        let chunks = parser.parse(source, "slices.go").unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].name, "Map");
+        assert_eq!(
+            chunks[0].signature.as_deref(),
+            Some("func Map[T, U any](s []T, f func(T) U) []U")
+        );
+        assert_eq!(
+            chunks[1].signature.as_deref(),
+            Some("func Keys[\n    M ~map[K]V,\n    K comparable,\n    V any,\n](m M) []K")
+        );
+    }
+
+    #[test]
+    fn test_generic_type_signature() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+// Set is a set of comparable values.
+type Set[T comparable] struct {
+    items map[T]struct{}
+}
+
+type Number interface {
+    ~int | ~int64 | ~float64
+}
+
+type (
+    Pair[K comparable, V any] struct{ Key K; Value V }
+    List[T any] []T
+)
+"#;
+        let chunks = parser.parse(source, "set.go").unwrap();
+        let signature = |name: &str| {
+            chunks
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap()
+                .signature
+                .clone()
+                .unwrap()
+        };
+
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(signature("Set"), "type Set[T comparable] struct");
+        assert_eq!(signature("Number"), "type Number interface");
+        assert_eq!(signature("Pair"), "type Pair[K comparable, V any] struct");
+        assert_eq!(signature("List"), "type List[T any] []T");
+        assert_eq!(chunks[0].chunk_type, ChunkType::Type);
+        assert_eq!(
+            chunks[0].documentation.as_deref(),
+            Some("Set is a set of comparable values.")
+        );
+        assert_eq!(chunks[1].chunk_type, ChunkType::Interface);
+    }
+
     #[test]
     fn test_parse_unexported_function() {
         let parser = GoParser::new().unwrap();
@@ -255,6 +654,118 @@ func helper() {}
         assert_eq!(chunks[0].visibility, Visibility::Internal);
     }
 
+    #[test]
+    fn test_deprecated_paragraph() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+// Dial connects to the address.
+//
+// Deprecated: Use DialContext instead.
+func Dial(addr string) (Conn, error) {
+    return nil, nil
+}
+
+// DialContext connects to the address. Not Deprecated: at all.
+func DialContext(ctx context.Context, addr string) (Conn, error) {
+    return nil, nil
+}
+"#;
+        let chunks = parser.parse(source, "dial.go").unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].deprecated);
+        assert_eq!(
+            chunks[0].deprecation_message.as_deref(),
+            Some("Use DialContext instead.")
+        );
+        assert!(!chunks[1].deprecated);
+    }
+
+    #[test]
+    fn test_grouped_declarations_get_own_ranges() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"package http
+
+// Default limits.
+const (
+    // MaxHeaderBytes caps request headers.
+    MaxHeaderBytes = 1 << 20
+    minBuffer      = 512 // not exported
+
+    DefaultPort, DefaultTLSPort = 80, 443
+)
+
+// ErrClosed is returned after Close.
+var ErrClosed = errors.New("closed")
+
+var (
+    Timeout = 30
+    retries = 3
+)
+
+type (
+    // Handler serves requests.
+    Handler interface {
+        Serve()
+    }
+    Header map[string][]string
+)
+"#;
+        let chunks = parser.parse(source, "http.go").unwrap();
+        let chunk = |name: &str| chunks.iter().find(|c| c.name == name).unwrap();
+        let lines = |name: &str| (chunk(name).start_line, chunk(name).end_line);
+
+        assert_eq!(lines("MaxHeaderBytes"), (6, 6));
+        assert_eq!(lines("minBuffer"), (7, 7));
+        assert_eq!(lines("DefaultPort"), (9, 9));
+        assert_eq!(lines("DefaultTLSPort"), (9, 9));
+        assert_eq!(lines("ErrClosed"), (13, 13));
+        assert_eq!(lines("Timeout"), (16, 16));
+        assert_eq!(lines("retries"), (17, 17));
+        assert_eq!(lines("Handler"), (22, 24));
+        assert_eq!(lines("Header"), (25, 25));
+
+        let max = chunk("MaxHeaderBytes");
+        assert_eq!(max.chunk_type, ChunkType::Constant);
+        assert_eq!(max.code, "MaxHeaderBytes = 1 << 20");
+        assert_eq!(
+            max.signature.as_deref(),
+            Some("const MaxHeaderBytes = 1 << 20")
+        );
+        assert_eq!(
+            max.documentation.as_deref(),
+            Some("MaxHeaderBytes caps request headers.")
+        );
+        assert_eq!(&source[max.start_byte..max.end_byte], max.code);
+        assert_eq!(chunk("minBuffer").visibility, Visibility::Internal);
+        assert!(chunk("DefaultPort").documentation.is_none());
+        assert_eq!(
+            chunk("ErrClosed").documentation.as_deref(),
+            Some("ErrClosed is returned after Close.")
+        );
+        assert_eq!(chunk("Handler").chunk_type, ChunkType::Interface);
+        assert_eq!(
+            chunk("Handler").documentation.as_deref(),
+            Some("Handler serves requests.")
+        );
+        assert!(chunk("Header").documentation.is_none());
+    }
+
+    #[test]
+    fn test_local_declarations_skipped() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+func run() {
+    const limit = 10
+    var count int
+}
+"#;
+        let chunks = parser.parse(source, "run.go").unwrap();
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].name, "run");
+    }
+
     #[test]
     fn test_visibility_detection() {
         let parser = GoParser::new().unwrap();
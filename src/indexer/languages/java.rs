@@ -1,7 +1,7 @@
 use crate::types::{ChunkType, Visibility};
 use tree_sitter::{Node, Parser};
 
-use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation, Param};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
@@ -12,6 +12,11 @@ use crate::indexer::language::{Language, LanguageParser};
 /// - protected = Protected
 /// - (default/package) = Internal
 /// - private = Private
+///
+/// Fields are extracted as constants when `static final` (or declared in an
+/// interface) or annotated. Annotations on types and fields are appended to
+/// their documentation, so annotation-driven code (`@Service`, `@Value`) is
+/// searchable.
 pub struct JavaParser {
     _marker: (),
 }
@@ -67,6 +72,9 @@ impl JavaParser {
                     chunks.push(chunk);
                 }
             }
+            "field_declaration" | "constant_declaration" => {
+                chunks.extend(self.extract_fields(node, source, file_path));
+            }
             _ => {}
         }
 
@@ -81,13 +89,16 @@ impl JavaParser {
         let code = node.utf8_text(source.as_bytes()).ok()?;
         let doc = self.extract_javadoc(node, source);
         let visibility = self.detect_visibility(node, source);
+        let (params, returns) = self.extract_params(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Method)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.declaration_line(node, source))
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -102,15 +113,16 @@ impl JavaParser {
     fn extract_class(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
-        let doc = self.extract_javadoc(node, source);
+        let doc = self.documentation(node, source);
         let visibility = self.detect_visibility(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Class)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.declaration_line(node, source))
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -125,15 +137,16 @@ impl JavaParser {
     fn extract_interface(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
-        let doc = self.extract_javadoc(node, source);
+        let doc = self.documentation(node, source);
         let visibility = self.detect_visibility(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Interface)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.declaration_line(node, source))
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -148,15 +161,16 @@ impl JavaParser {
     fn extract_enum(&self, node: Node, source: &str, file_path: &str) -> Option<CodeChunk> {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
-        let doc = self.extract_javadoc(node, source);
+        let doc = self.documentation(node, source);
         let visibility = self.detect_visibility(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Type)
             .visibility(visibility)
             .name(name)
-            .signature(code.lines().next().unwrap_or("").to_string())
+            .signature(self.declaration_line(node, source))
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -168,6 +182,170 @@ impl JavaParser {
             .build()
     }
 
+    /// Extract constants and annotated fields, one chunk per declared variable.
+    ///
+    /// Interface fields (`constant_declaration`) are implicitly public static
+    /// final. Other fields are skipped unless `static final` or annotated.
+    /// When one declaration declares several variables, each chunk spans only
+    /// its own declarator.
+    fn extract_fields(&self, node: Node, source: &str, file_path: &str) -> Vec<CodeChunk> {
+        let in_interface = node.kind() == "constant_declaration";
+        let keywords = self.modifier_keywords(node, source);
+        let is_constant =
+            in_interface || (keywords.contains(&"static") && keywords.contains(&"final"));
+        let annotations = self.annotations(node, source);
+        if !is_constant && annotations.is_empty() {
+            return Vec::new();
+        }
+
+        let Some(field_type) = self.get_child_text(node, "type", source) else {
+            return Vec::new();
+        };
+        let doc = self.documentation(node, source);
+        let deprecation = self.detect_deprecation(node, source);
+        let parent = self.enclosing_type(node, source);
+        let visibility = if in_interface {
+            Visibility::Public
+        } else {
+            self.detect_visibility(node, source)
+        };
+
+        let mut cursor = node.walk();
+        let declarators: Vec<Node> = node
+            .children_by_field_name("declarator", &mut cursor)
+            .collect();
+        let grouped = declarators.len() > 1;
+
+        declarators
+            .into_iter()
+            .filter_map(|declarator| {
+                let name = self.get_child_text(declarator, "name", source)?;
+                let span = if grouped { declarator } else { node };
+                let code = span.utf8_text(source.as_bytes()).ok()?;
+                let declarator = declarator.utf8_text(source.as_bytes()).ok()?;
+                let signature = keywords
+                    .iter()
+                    .copied()
+                    .chain([field_type.as_str(), declarator])
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let mut builder = ChunkBuilder::new()
+                    .chunk_type(ChunkType::Constant)
+                    .visibility(visibility)
+                    .name(name)
+                    .signature(signature.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .code(code)
+                    .deprecation(deprecation.clone())
+                    .documentation(doc.clone().unwrap_or_default())
+                    .file_path(file_path)
+                    .location(
+                        span.start_position().row as u32 + 1,
+                        span.end_position().row as u32 + 1,
+                        span.start_byte(),
+                        span.end_byte(),
+                    );
+                if let Some(parent) = &parent {
+                    builder = builder.parent(parent);
+                }
+                builder.build()
+            })
+            .collect()
+    }
+
+    /// First line of a declaration, starting after its annotations.
+    fn declaration_line(&self, node: Node, source: &str) -> String {
+        let start = self
+            .modifiers(node)
+            .and_then(|modifiers| {
+                let mut cursor = modifiers.walk();
+                modifiers
+                    .children(&mut cursor)
+                    .filter(|c| matches!(c.kind(), "marker_annotation" | "annotation"))
+                    .last()
+                    .map(|annotation| annotation.end_byte())
+            })
+            .unwrap_or(node.start_byte());
+
+        source[start..node.end_byte()]
+            .trim_start()
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string()
+    }
+
+    /// Javadoc followed by the declaration's annotations, one per line.
+    fn documentation(&self, node: Node, source: &str) -> Option<String> {
+        let mut parts: Vec<String> = self.extract_javadoc(node, source).into_iter().collect();
+        parts.extend(self.annotations(node, source));
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+
+    /// Java marks deprecation with `@Deprecated`, usually explained by a
+    /// Javadoc `@deprecated` tag. Either one counts.
+    fn detect_deprecation(&self, node: Node, source: &str) -> Option<Deprecation> {
+        let tagged = self
+            .extract_javadoc(node, source)
+            .and_then(|doc| Deprecation::from_doc_tag(&doc, "@deprecated"));
+        let annotated = self.annotations(node, source).iter().any(|annotation| {
+            let name = annotation.trim_start_matches('@').split('(').next();
+            matches!(name, Some("Deprecated" | "java.lang.Deprecated"))
+        });
+
+        tagged.or_else(|| annotated.then(Deprecation::default))
+    }
+
+    /// Annotations in a declaration's modifiers, whitespace-collapsed.
+    fn annotations(&self, node: Node, source: &str) -> Vec<String> {
+        let Some(modifiers) = self.modifiers(node) else {
+            return Vec::new();
+        };
+        let mut cursor = modifiers.walk();
+        modifiers
+            .children(&mut cursor)
+            .filter(|c| matches!(c.kind(), "marker_annotation" | "annotation"))
+            .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    /// Modifier keywords (`public`, `static`, ...) without annotations.
+    fn modifier_keywords<'a>(&self, node: Node, source: &'a str) -> Vec<&'a str> {
+        let Some(modifiers) = self.modifiers(node) else {
+            return Vec::new();
+        };
+        let mut cursor = modifiers.walk();
+        modifiers
+            .children(&mut cursor)
+            .filter(|c| !matches!(c.kind(), "marker_annotation" | "annotation"))
+            .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+            .collect()
+    }
+
+    fn modifiers<'t>(&self, node: Node<'t>) -> Option<Node<'t>> {
+        let mut cursor = node.walk();
+        node.children(&mut cursor).find(|c| c.kind() == "modifiers")
+    }
+
+    /// Name of the class, interface, enum or record declaring `node`.
+    fn enclosing_type(&self, node: Node, source: &str) -> Option<String> {
+        let mut current = node.parent();
+        while let Some(n) = current {
+            if matches!(
+                n.kind(),
+                "class_declaration"
+                    | "interface_declaration"
+                    | "enum_declaration"
+                    | "record_declaration"
+            ) {
+                return self.get_child_text(n, "name", source);
+            }
+            current = n.parent();
+        }
+        None
+    }
+
     fn extract_javadoc(&self, node: Node, source: &str) -> Option<String> {
         let mut prev = node.prev_sibling();
         while let Some(sibling) = prev {
@@ -201,6 +379,41 @@ impl JavaParser {
             .join("\n")
     }
 
+    /// Parameters and return type of a method. A varargs parameter's type
+    /// keeps its `...`.
+    fn extract_params(&self, node: Node, source: &str) -> (Vec<Param>, Option<String>) {
+        let text = |node: Node| node.utf8_text(source.as_bytes()).ok().map(str::to_string);
+
+        let mut params = Vec::new();
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                match param.kind() {
+                    "formal_parameter" => {
+                        if let Some(name) = self.get_child_text(param, "name", source) {
+                            params.push((name, self.get_child_text(param, "type", source)));
+                        }
+                    }
+                    "spread_parameter" => {
+                        // No fields: [modifiers] type ... declarator
+                        let mut inner = param.walk();
+                        let children: Vec<Node> = param
+                            .named_children(&mut inner)
+                            .filter(|c| c.kind() != "modifiers")
+                            .collect();
+                        if let [ty, declarator] = children[..]
+                            && let Some(name) = self.get_child_text(declarator, "name", source)
+                        {
+                            params.push((name, text(ty).map(|ty| format!("{}...", ty))));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        (params, self.get_child_text(node, "type", source))
+    }
+
     fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
         let child = node.child_by_field_name(field)?;
         child
@@ -209,7 +422,8 @@ impl JavaParser {
             .map(|s| s.to_string())
     }
 
-    /// Detect visibility from Java modifiers.
+    /// Detect visibility from Java modifiers. Interface members without one
+    /// are implicitly public.
     fn detect_visibility(&self, node: Node, source: &str) -> Visibility {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
@@ -224,6 +438,9 @@ impl JavaParser {
                 }
             }
         }
+        if node.parent().is_some_and(|p| p.kind() == "interface_body") {
+            return Visibility::Public;
+        }
         // Default (package-private) in Java
         Visibility::Internal
     }
@@ -272,6 +489,44 @@ public class Calculator {
         );
     }
 
+    #[test]
+    fn test_params_and_return_type() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+public class Handler {
+    public Response handle(final Request request, Map<String, String> headers) {
+        return null;
+    }
+
+    public void log(String format, Object... args) {}
+}
+"#;
+        let chunks = parser.parse(source, "Handler.java").unwrap();
+
+        let handle = chunks.iter().find(|c| c.name == "handle").unwrap();
+        assert_eq!(
+            handle.params,
+            vec![
+                ("request".to_string(), Some("Request".to_string())),
+                (
+                    "headers".to_string(),
+                    Some("Map<String, String>".to_string())
+                ),
+            ]
+        );
+        assert_eq!(handle.returns.as_deref(), Some("Response"));
+
+        let log = chunks.iter().find(|c| c.name == "log").unwrap();
+        assert_eq!(
+            log.params,
+            vec![
+                ("format".to_string(), Some("String".to_string())),
+                ("args".to_string(), Some("Object...".to_string())),
+            ]
+        );
+        assert_eq!(log.returns.as_deref(), Some("void"));
+    }
+
     #[test]
     fn test_visibility_private() {
         let parser = JavaParser::new().unwrap();
@@ -323,6 +578,10 @@ class Internal {
  */
 public interface Service {
     void execute();
+
+    default void log(String message) {}
+
+    private void flush() {}
 }
 "#;
         let chunks = parser.parse(source, "Service.java").unwrap();
@@ -330,5 +589,177 @@ public interface Service {
         let iface = chunks.iter().find(|c| c.name == "Service").unwrap();
         assert_eq!(iface.chunk_type, ChunkType::Interface);
         assert_eq!(iface.visibility, Visibility::Public);
+
+        // Interface methods are public unless declared private
+        let visibility = |name: &str| chunks.iter().find(|c| c.name == name).unwrap().visibility;
+        assert_eq!(visibility("execute"), Visibility::Public);
+        assert_eq!(visibility("log"), Visibility::Public);
+        assert_eq!(visibility("flush"), Visibility::Private);
+    }
+
+    #[test]
+    fn test_public_static_final_field() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+public class ApiClient {
+    /**
+     * Default endpoint for all requests.
+     */
+    public static final String BASE_URL = "https://api.example.com";
+
+    private static final int MAX_RETRIES = 3, TIMEOUT_MS = 5000;
+
+    private String token;
+}
+"#;
+        let chunks = parser.parse(source, "ApiClient.java").unwrap();
+
+        let base_url = chunks.iter().find(|c| c.name == "BASE_URL").unwrap();
+        assert_eq!(base_url.chunk_type, ChunkType::Constant);
+        assert_eq!(base_url.visibility, Visibility::Public);
+        assert_eq!(base_url.parent.as_deref(), Some("ApiClient"));
+        assert_eq!(
+            base_url.signature.as_deref(),
+            Some("public static final String BASE_URL = \"https://api.example.com\"")
+        );
+        assert_eq!(
+            base_url.documentation.as_deref(),
+            Some("Default endpoint for all requests.")
+        );
+
+        // One chunk per declared variable
+        let timeout = chunks.iter().find(|c| c.name == "TIMEOUT_MS").unwrap();
+        assert_eq!(timeout.visibility, Visibility::Private);
+        assert_eq!(
+            timeout.signature.as_deref(),
+            Some("private static final int TIMEOUT_MS = 5000")
+        );
+        assert!(chunks.iter().any(|c| c.name == "MAX_RETRIES"));
+
+        // Plain instance fields are not worth a chunk
+        assert!(chunks.iter().all(|c| c.name != "token"));
+    }
+
+    #[test]
+    fn test_interface_constants_and_annotated_fields() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+interface Defaults {
+    int PORT = 8080;
+}
+
+class Settings {
+    @Value("${app.name}")
+    private String appName;
+}
+"#;
+        let chunks = parser.parse(source, "Defaults.java").unwrap();
+
+        let port = chunks.iter().find(|c| c.name == "PORT").unwrap();
+        assert_eq!(port.chunk_type, ChunkType::Constant);
+        assert_eq!(port.visibility, Visibility::Public);
+
+        let app_name = chunks.iter().find(|c| c.name == "appName").unwrap();
+        assert_eq!(app_name.chunk_type, ChunkType::Constant);
+        assert_eq!(
+            app_name.documentation.as_deref(),
+            Some("@Value(\"${app.name}\")")
+        );
+        assert_eq!(
+            app_name.signature.as_deref(),
+            Some("private String appName")
+        );
+    }
+
+    #[test]
+    fn test_multi_declarator_field_ranges() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+public class Limits {
+    public static final int MIN = 1,
+        MAX = 100;
+}
+"#;
+        let chunks = parser.parse(source, "Limits.java").unwrap();
+
+        let min = chunks.iter().find(|c| c.name == "MIN").unwrap();
+        assert_eq!((min.start_line, min.end_line), (3, 3));
+        assert_eq!(min.code, "MIN = 1");
+        assert_eq!(
+            min.signature.as_deref(),
+            Some("public static final int MIN = 1")
+        );
+
+        let max = chunks.iter().find(|c| c.name == "MAX").unwrap();
+        assert_eq!((max.start_line, max.end_line), (4, 4));
+        assert_eq!(&source[max.start_byte..max.end_byte], "MAX = 100");
+    }
+
+    #[test]
+    fn test_class_annotations_in_documentation() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+/**
+ * Persists users.
+ */
+@Service
+@Transactional(readOnly = true)
+public class UserService {
+    @Override public String toString() { return "users"; }
+}
+"#;
+        let chunks = parser.parse(source, "UserService.java").unwrap();
+
+        let class = chunks.iter().find(|c| c.name == "UserService").unwrap();
+        assert_eq!(
+            class.documentation.as_deref(),
+            Some("Persists users.\n@Service\n@Transactional(readOnly = true)")
+        );
+        assert_eq!(
+            class.signature.as_deref(),
+            Some("public class UserService {")
+        );
+
+        let method = chunks.iter().find(|c| c.name == "toString").unwrap();
+        assert_eq!(
+            method.signature.as_deref(),
+            Some("public String toString() { return \"users\"; }")
+        );
+    }
+
+    #[test]
+    fn test_deprecated_annotation_and_tag() {
+        let parser = JavaParser::new().unwrap();
+        let source = r#"
+public class Dates {
+    /**
+     * Parses a date.
+     * @deprecated Use {@link #parseIso(String)} instead.
+     */
+    @Deprecated(since = "2.0")
+    public static Date parse(String text) { return null; }
+
+    @Deprecated
+    public static final int EPOCH = 1970;
+
+    /** Parses an ISO-8601 date. */
+    public static Date parseIso(String text) { return null; }
+}
+"#;
+        let chunks = parser.parse(source, "Dates.java").unwrap();
+
+        let parse = chunks.iter().find(|c| c.name == "parse").unwrap();
+        assert!(parse.deprecated);
+        assert_eq!(
+            parse.deprecation_message.as_deref(),
+            Some("Use {@link #parseIso(String)} instead.")
+        );
+
+        let epoch = chunks.iter().find(|c| c.name == "EPOCH").unwrap();
+        assert!(epoch.deprecated);
+        assert!(epoch.deprecation_message.is_none());
+
+        let parse_iso = chunks.iter().find(|c| c.name == "parseIso").unwrap();
+        assert!(!parse_iso.deprecated);
     }
 }
@@ -7,9 +7,22 @@ use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
+/// Sections with less prose than this (in bytes) are not worth their own chunk.
+const MIN_PROSE_LEN: usize = 40;
+
+/// File names (before the extension, lowercased) treated as changelogs.
+const CHANGELOG_NAMES: &[&str] = &["changelog", "changes", "history"];
+
 /// Parser for Markdown files (README.md, docs/).
 ///
-/// Uses tree-sitter to extract fenced code blocks with surrounding context.
+/// Uses tree-sitter to extract fenced code blocks with surrounding context,
+/// plus one chunk per heading-delimited prose section. In changelogs, each
+/// release section (`## [2.0.0] - 2023-01-01`) becomes a single chunk named
+/// after its version.
+///
+/// Code blocks and prose sections get the headings of the sections they
+/// are in as their parent (`Configuration > Retries > Backoff`), locating
+/// them in the document.
 pub struct MarkdownParser {
     _marker: (),
 }
@@ -47,9 +60,202 @@ impl MarkdownParser {
             &mut chunks,
             &mut chunk_index,
         );
+        self.visit_sections(
+            tree.root_node(),
+            source,
+            file_path,
+            is_changelog(file_path),
+            &mut chunks,
+        );
         Ok(chunks)
     }
 
+    /// Emit a prose chunk for the document preamble and every section.
+    ///
+    /// With `changelog` set, release sections are emitted whole instead,
+    /// their subsections (`### Added`, `### Fixed`) included.
+    fn visit_sections(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &str,
+        changelog: bool,
+        chunks: &mut Vec<CodeChunk>,
+    ) {
+        if changelog
+            && node.kind() == "section"
+            && let Some(release) = self.section_heading(node, source).and_then(parse_release)
+        {
+            if let Some(chunk) = self.extract_release(node, release, source, file_path) {
+                chunks.push(chunk);
+            }
+            return;
+        }
+
+        if matches!(node.kind(), "document" | "section")
+            && let Some(chunk) = self.extract_prose_section(node, source, file_path)
+        {
+            chunks.push(chunk);
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit_sections(child, source, file_path, changelog, chunks);
+        }
+    }
+
+    /// Headings of the sections enclosing `node`, outermost first, joined
+    /// with ` > `. `None` outside any headed section.
+    fn breadcrumb(&self, node: Node, source: &str) -> Option<String> {
+        let mut headings = Vec::new();
+        let mut current = node.parent();
+        while let Some(n) = current {
+            if n.kind() == "section"
+                && let Some(heading) = self.section_heading(n, source).filter(|h| !h.is_empty())
+            {
+                headings.push(heading);
+            }
+            current = n.parent();
+        }
+        headings.reverse();
+        (!headings.is_empty()).then(|| headings.join(" > "))
+    }
+
+    /// Text of a section's heading, without the `#` markers.
+    fn section_heading<'a>(&self, node: Node, source: &'a str) -> Option<&'a str> {
+        let mut cursor = node.walk();
+        let heading = node
+            .children(&mut cursor)
+            .find(|c| matches!(c.kind(), "atx_heading" | "setext_heading"))?;
+        let text = heading.utf8_text(source.as_bytes()).ok()?;
+        Some(text.lines().next()?.trim_start_matches('#').trim())
+    }
+
+    /// One chunk for a changelog release section, named after its version.
+    ///
+    /// The release date, when the heading has one, goes in the signature
+    /// (`2.0.0 - 2023-01-01`). Trailing link definitions (`[2.0.0]: https://...`)
+    /// are left out.
+    fn extract_release(
+        &self,
+        node: Node,
+        release: Release,
+        source: &str,
+        file_path: &str,
+    ) -> Option<CodeChunk> {
+        let start = node.start_byte();
+        let end = content_end(node).max(start);
+        let code = source.get(start..end)?.trim_end();
+        let end = start + code.len();
+
+        let signature = match &release.date {
+            Some(date) => format!("{} - {}", release.version, date),
+            None => release.version.clone(),
+        };
+        let start_line = node.start_position().row as u32 + 1;
+        let end_line = start_line + code.matches('\n').count() as u32;
+
+        ChunkBuilder::new()
+            .chunk_type(ChunkType::Documentation)
+            .visibility(Visibility::Public)
+            .name(release.version)
+            .signature(signature)
+            .code(code)
+            .file_path(file_path)
+            .location(start_line, end_line, start, end)
+            .build()
+    }
+
+    /// Extract the prose directly under a section (or the document preamble).
+    ///
+    /// Fenced code and nested sections are left out: code blocks have their
+    /// own chunks and subsections are visited separately.
+    fn extract_prose_section(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &str,
+    ) -> Option<CodeChunk> {
+        let mut heading: Option<Node> = None;
+        let mut prose: Vec<Node> = Vec::new();
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "atx_heading" | "setext_heading" if heading.is_none() && prose.is_empty() => {
+                    heading = Some(child);
+                }
+                "paragraph" | "list" | "block_quote" | "pipe_table" => prose.push(child),
+                _ => {}
+            }
+        }
+
+        let text = prose
+            .iter()
+            .filter_map(|n| self.text_without_fences(*n, source))
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        if text.len() < MIN_PROSE_LEN {
+            return None;
+        }
+
+        let title = heading
+            .and_then(|h| h.utf8_text(source.as_bytes()).ok())
+            .and_then(|t| t.lines().next())
+            .map(|t| t.trim_start_matches('#').trim().to_string())
+            .filter(|t| !t.is_empty());
+
+        let name = title
+            .as_deref()
+            .map(slugify)
+            .unwrap_or_else(|| "introduction".to_string());
+
+        let code = match &title {
+            Some(title) => format!("{}\n\n{}", title, text),
+            None => text,
+        };
+
+        let first = heading.unwrap_or(prose[0]);
+        let last = prose[prose.len() - 1];
+
+        let mut builder = ChunkBuilder::new()
+            .chunk_type(ChunkType::Documentation)
+            .visibility(Visibility::Public)
+            .name(name)
+            .code(code)
+            .file_path(file_path)
+            .location(
+                first.start_position().row as u32 + 1,
+                last.end_position().row as u32 + 1,
+                first.start_byte(),
+                last.end_byte(),
+            );
+        if let Some(breadcrumb) = self.breadcrumb(node, source) {
+            builder = builder.parent(breadcrumb);
+        }
+        builder.build()
+    }
+
+    /// Text of a node with any fenced code blocks inside it (e.g. in list
+    /// items) cut out, so prose chunks don't repeat code-block chunks.
+    fn text_without_fences(&self, node: Node, source: &str) -> Option<String> {
+        let mut fences = Vec::new();
+        collect_fences(node, &mut fences);
+
+        let mut text = String::new();
+        let mut pos = node.start_byte();
+        for (start, end) in fences {
+            text.push_str(source.get(pos..start)?);
+            pos = end;
+        }
+        text.push_str(source.get(pos..node.end_byte())?);
+
+        Some(text)
+    }
+
     fn visit_node(
         &self,
         node: Node,
@@ -113,7 +319,7 @@ impl MarkdownParser {
         let context = self.extract_context(node, source);
         let name = self.generate_name(&context, &lang_hint, chunk_index);
 
-        ChunkBuilder::new()
+        let mut builder = ChunkBuilder::new()
             .chunk_type(ChunkType::Documentation)
             .visibility(Visibility::Public)
             .name(name)
@@ -130,8 +336,11 @@ impl MarkdownParser {
                 node.end_position().row as u32 + 1,
                 node.start_byte(),
                 node.end_byte(),
-            )
-            .build()
+            );
+        if let Some(breadcrumb) = self.breadcrumb(node, source) {
+            builder = builder.parent(breadcrumb);
+        }
+        builder.build()
     }
 
     fn extract_context(&self, node: Node, source: &str) -> String {
@@ -194,6 +403,91 @@ impl LanguageParser for MarkdownParser {
     }
 }
 
+/// A release heading in a changelog.
+#[derive(Debug, PartialEq, Eq)]
+struct Release {
+    /// Version as written (`2.0.0`, `v1.4.0-beta.1`), or `Unreleased`
+    version: String,
+    /// Release date in `YYYY-MM-DD` form
+    date: Option<String>,
+}
+
+/// Whether `file_path` names a changelog (`CHANGELOG.md`, `docs/HISTORY.md`).
+fn is_changelog(file_path: &str) -> bool {
+    let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    CHANGELOG_NAMES.contains(&stem.to_lowercase().as_str())
+}
+
+/// Parse a changelog release heading: `[2.0.0] - 2023-01-01`,
+/// `v2.0.0 (2023-01-01)`, `[1.0.0](https://...) / 2022-06-15` or
+/// `[Unreleased]`.
+///
+/// The version is the first word, which must start with a digit (after an
+/// optional `v`) and contain a dot. Headings like `Added` or `Migration
+/// guide` aren't releases.
+fn parse_release(heading: &str) -> Option<Release> {
+    let rest = heading.trim().trim_start_matches('[');
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, ']' | '(' | ')'))
+        .unwrap_or(rest.len());
+    let version = &rest[..end];
+
+    let is_version = {
+        let number = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        number.starts_with(|c: char| c.is_ascii_digit()) && number.contains('.')
+    };
+    if !is_version && !version.eq_ignore_ascii_case("unreleased") {
+        return None;
+    }
+
+    Some(Release {
+        version: version.to_string(),
+        date: find_date(&rest[end..]),
+    })
+}
+
+/// First `YYYY-MM-DD` date in `text`.
+fn find_date(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    (0..bytes.len().saturating_sub(9)).find_map(|i| {
+        let candidate = &bytes[i..i + 10];
+        let is_date = candidate.iter().enumerate().all(|(j, b)| match j {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        });
+        let standalone = bytes.get(i + 10).is_none_or(|b| !b.is_ascii_digit())
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+        (is_date && standalone).then(|| text[i..i + 10].to_string())
+    })
+}
+
+/// End of a section's content, leaving out link reference definitions.
+fn content_end(node: Node) -> usize {
+    let mut end = node.start_byte();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        end = end.max(match child.kind() {
+            "link_reference_definition" => continue,
+            "section" => content_end(child),
+            _ => child.end_byte(),
+        });
+    }
+    end
+}
+
+/// Byte ranges of fenced code blocks under `node`, in document order.
+fn collect_fences(node: Node, fences: &mut Vec<(usize, usize)>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "fenced_code_block" {
+            fences.push((child.start_byte(), child.end_byte()));
+        } else {
+            collect_fences(child, fences);
+        }
+    }
+}
+
 /// Convert a string to a slug-like identifier.
 fn slugify(s: &str) -> String {
     s.chars()
@@ -257,6 +551,221 @@ const y: number = 2;
         assert_eq!(chunks.len(), 2);
     }
 
+    #[test]
+    fn test_parse_prose_sections() {
+        let parser = MarkdownParser::new().unwrap();
+        let source = r#"# http-client
+
+A small HTTP client with sensible defaults for timeouts and retries.
+
+## Configuring retries
+
+Retries are disabled by default. Set `maxRetries` to retry failed
+requests with exponential backoff.
+
+```js
+const client = createClient({ maxRetries: 3 });
+```
+
+Only idempotent requests are retried:
+
+- `GET` and `HEAD` always
+- `PUT` when `retryPut` is set:
+  ```js
+  createClient({ retryPut: true });
+  ```
+"#;
+
+        let chunks = parser.parse(source, "README.md").unwrap();
+
+        // Code block is still extracted
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.signature.as_deref() == Some("```js"))
+        );
+
+        // The section's code block shares its name; the prose chunk has no signature
+        let retries = chunks
+            .iter()
+            .find(|c| c.name == "configuring_retries" && c.signature.is_none())
+            .unwrap();
+        assert_eq!(retries.chunk_type, ChunkType::Documentation);
+        assert!(retries.code.starts_with("Configuring retries"));
+        assert!(retries.code.contains("exponential backoff"));
+        assert!(retries.code.contains("Only idempotent requests"));
+        // Fenced code is not duplicated into the prose chunk
+        assert!(retries.code.contains("`GET` and `HEAD`"));
+        assert!(!retries.code.contains("createClient"));
+
+        let intro = chunks.iter().find(|c| c.name == "http_client").unwrap();
+        assert!(intro.code.contains("sensible defaults"));
+        assert!(!intro.code.contains("maxRetries"));
+    }
+
+    #[test]
+    fn test_chunks_carry_heading_breadcrumb() {
+        let parser = MarkdownParser::new().unwrap();
+        let source = r#"# fetchy
+
+## Usage
+
+Call `fetch` with a URL and it resolves to the response body.
+
+### Async
+
+```js
+const body = await fetchy("https://example.com");
+```
+
+## Install
+
+```sh
+npm install fetchy
+```
+"#;
+
+        let chunks = parser.parse(source, "README.md").unwrap();
+
+        let async_block = chunks
+            .iter()
+            .find(|c| c.signature.as_deref() == Some("```js"))
+            .unwrap();
+        assert_eq!(
+            async_block.parent.as_deref(),
+            Some("fetchy > Usage > Async")
+        );
+
+        let install = chunks
+            .iter()
+            .find(|c| c.signature.as_deref() == Some("```sh"))
+            .unwrap();
+        assert_eq!(install.parent.as_deref(), Some("fetchy > Install"));
+
+        // A prose section's parent is the sections around it
+        let usage = chunks
+            .iter()
+            .find(|c| c.name == "usage" && c.signature.is_none())
+            .unwrap();
+        assert_eq!(usage.parent.as_deref(), Some("fetchy"));
+
+        // Code before any heading has no breadcrumb
+        let chunks = parser
+            .parse("```sh\nnpm install fetchy\n```\n", "README.md")
+            .unwrap();
+        assert!(chunks[0].parent.is_none());
+    }
+
+    #[test]
+    fn test_short_prose_is_skipped() {
+        let parser = MarkdownParser::new().unwrap();
+        let chunks = parser
+            .parse("# Title\n\nToo short.\n", "README.md")
+            .unwrap();
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn test_changelog_release_sections() {
+        let parser = MarkdownParser::new().unwrap();
+        let source = r#"# Changelog
+
+All notable changes to this project will be documented in this file.
+
+## [Unreleased]
+
+### Added
+- Streaming responses.
+
+## [2.0.0] - 2023-01-01
+
+### Changed
+- `connect` now returns a `Result` instead of panicking.
+
+### Removed
+- The deprecated `connect_sync` function.
+
+## [1.0.0] - 2022-06-15
+
+Initial release.
+
+[2.0.0]: https://github.com/acme/lib/compare/v1.0.0...v2.0.0
+[1.0.0]: https://github.com/acme/lib/releases/tag/v1.0.0
+"#;
+
+        let chunks = parser.parse(source, "CHANGELOG.md").unwrap();
+        let names: Vec<_> = chunks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["changelog", "Unreleased", "2.0.0", "1.0.0"]);
+
+        let v2 = &chunks[2];
+        assert_eq!(v2.chunk_type, ChunkType::Documentation);
+        assert_eq!(v2.signature.as_deref(), Some("2.0.0 - 2023-01-01"));
+        assert_eq!((v2.start_line, v2.end_line), (10, 16));
+        assert!(v2.code.starts_with("## [2.0.0] - 2023-01-01"));
+        assert!(v2.code.contains("### Changed"));
+        assert!(v2.code.ends_with("The deprecated `connect_sync` function."));
+        assert_eq!(&source[v2.start_byte..v2.end_byte], v2.code);
+
+        let v1 = &chunks[3];
+        assert_eq!(v1.signature.as_deref(), Some("1.0.0 - 2022-06-15"));
+        assert_eq!(v1.code, "## [1.0.0] - 2022-06-15\n\nInitial release.");
+        assert_eq!((v1.start_line, v1.end_line), (18, 20));
+
+        assert_eq!(chunks[1].signature.as_deref(), Some("Unreleased"));
+    }
+
+    #[test]
+    fn test_readme_release_headings_stay_prose() {
+        let parser = MarkdownParser::new().unwrap();
+        let source = r#"# Upgrading
+
+## 2.0 migration
+
+Version 2.0 replaced `connect_sync` with `connect`, which returns a `Result`.
+"#;
+
+        let chunks = parser.parse(source, "README.md").unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].name, "2_0_migration");
+    }
+
+    #[test]
+    fn test_parse_release() {
+        let release = |version: &str, date: Option<&str>| Release {
+            version: version.to_string(),
+            date: date.map(str::to_string),
+        };
+
+        assert_eq!(
+            parse_release("[2.0.0] - 2023-01-01"),
+            Some(release("2.0.0", Some("2023-01-01")))
+        );
+        assert_eq!(
+            parse_release("v1.4.0-beta.1 (2021-03-09)"),
+            Some(release("v1.4.0-beta.1", Some("2021-03-09")))
+        );
+        assert_eq!(
+            parse_release("[1.0.0](https://example.com/v1.0.0) / 2022-06-15"),
+            Some(release("1.0.0", Some("2022-06-15")))
+        );
+        assert_eq!(parse_release("0.3.1"), Some(release("0.3.1", None)));
+        assert_eq!(
+            parse_release("[Unreleased]"),
+            Some(release("Unreleased", None))
+        );
+        assert_eq!(parse_release("Added"), None);
+        assert_eq!(parse_release("2023 roadmap"), None);
+    }
+
+    #[test]
+    fn test_is_changelog() {
+        assert!(is_changelog("CHANGELOG.md"));
+        assert!(is_changelog("docs/History.markdown"));
+        assert!(is_changelog("CHANGES.md"));
+        assert!(!is_changelog("README.md"));
+        assert!(!is_changelog("docs/changelog-policy/README.md"));
+    }
+
     #[test]
     fn test_slugify() {
         assert_eq!(slugify("Getting Started"), "getting_started");
@@ -1,18 +1,22 @@
+mod dart;
 mod go;
 mod java;
 mod markdown;
 mod python;
 mod rust_lang;
+mod scala;
 mod typescript;
 
 use super::error::IndexerError;
 use super::language::{Language, LanguageParser};
 
+pub use dart::DartParser;
 pub use go::GoParser;
 pub use java::JavaParser;
 pub use markdown::MarkdownParser;
 pub use python::PythonParser;
 pub use rust_lang::RustParser;
+pub use scala::ScalaParser;
 pub use typescript::TypeScriptParser;
 
 /// Get a parser for the given language.
@@ -24,6 +28,8 @@ pub fn get_parser(language: Language) -> Result<Box<dyn LanguageParser>, Indexer
         Language::Rust => Ok(Box::new(RustParser::new()?)),
         Language::Go => Ok(Box::new(GoParser::new()?)),
         Language::Java => Ok(Box::new(JavaParser::new()?)),
+        Language::Scala => Ok(Box::new(ScalaParser::new()?)),
+        Language::Dart => Ok(Box::new(DartParser::new()?)),
         Language::Markdown => Ok(Box::new(MarkdownParser::new()?)),
     }
 }
@@ -1,7 +1,7 @@
 use crate::types::{ChunkType, Visibility};
 use tree_sitter::{Node, Parser};
 
-use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation, Param};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
@@ -12,6 +12,9 @@ use crate::indexer::language::{Language, LanguageParser};
 /// - Async functions (async def)
 /// - Classes
 /// - Docstrings (first string literal in function/class body)
+/// - Decorators, prepended to the signature (`@app.route("/users")` marks an
+///   HTTP handler)
+/// - Methods: functions defined directly in a class body
 pub struct PythonParser {
     _marker: (), // Placeholder for any state
 }
@@ -71,19 +74,29 @@ impl PythonParser {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
         let docstring = self.extract_docstring(node, source);
-        let signature = self.extract_function_signature(node, source);
+        let signature = self
+            .extract_function_signature(node, source)
+            .unwrap_or_else(|| code.lines().next().unwrap_or("").to_string());
         let visibility = self.detect_visibility(&name);
+        let decorators = self.decorators(node, source);
+        let (params, returns) = self.extract_params(node, source);
 
         // Could differentiate async functions in the future
         let _is_async = node.child(0).map(|n| n.kind() == "async").unwrap_or(false);
-        let chunk_type = ChunkType::Function;
+        let chunk_type = if enclosing_class(node).is_some() {
+            ChunkType::Method
+        } else {
+            ChunkType::Function
+        };
 
         ChunkBuilder::new()
             .chunk_type(chunk_type)
             .visibility(visibility)
             .name(name)
-            .signature(signature.unwrap_or_else(|| code.lines().next().unwrap_or("").to_string()))
+            .signature(with_decorators(&decorators, signature))
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(node, source, docstring.as_deref()))
             .documentation(docstring.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -99,15 +112,17 @@ impl PythonParser {
         let name = self.get_child_text(node, "name", source)?;
         let code = node.utf8_text(source.as_bytes()).ok()?;
         let docstring = self.extract_docstring(node, source);
-        let signature = code.lines().next().map(|s| s.to_string());
+        let signature = code.lines().next().unwrap_or("").to_string();
         let visibility = self.detect_visibility(&name);
+        let decorators = self.decorators(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Class)
             .visibility(visibility)
             .name(name)
-            .signature(signature.unwrap_or_default())
+            .signature(with_decorators(&decorators, signature))
             .code(code)
+            .deprecation(self.detect_deprecation(node, source, docstring.as_deref()))
             .documentation(docstring.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -143,6 +158,105 @@ impl PythonParser {
         None
     }
 
+    /// Python marks deprecation with a `@deprecated(...)` decorator (PEP 702,
+    /// the `deprecated` package), a `warnings.warn(..., DeprecationWarning)`
+    /// call in the body, or a `.. deprecated::` docstring directive.
+    fn detect_deprecation(
+        &self,
+        node: Node,
+        source: &str,
+        docstring: Option<&str>,
+    ) -> Option<Deprecation> {
+        for decorator in self.decorator_nodes(node) {
+            let text = decorator.utf8_text(source.as_bytes()).unwrap_or("");
+            if decorator_name(text) == "deprecated" {
+                return Some(Deprecation::new(self.first_string(decorator, source)));
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body")
+            && let Some(deprecation) = self.find_deprecation_warning(body, source)
+        {
+            return Some(deprecation);
+        }
+
+        Deprecation::from_doc_tag(docstring?, ".. deprecated::")
+    }
+
+    /// `decorator` nodes above a function or class, in source order.
+    fn decorator_nodes<'t>(&self, node: Node<'t>) -> Vec<Node<'t>> {
+        let Some(parent) = node.parent().filter(|p| p.kind() == "decorated_definition") else {
+            return Vec::new();
+        };
+        let mut cursor = parent.walk();
+        parent
+            .children(&mut cursor)
+            .filter(|c| c.kind() == "decorator")
+            .collect()
+    }
+
+    /// Decorator source text with whitespace collapsed, so a decorator whose
+    /// arguments span lines fits on one.
+    fn decorators(&self, node: Node, source: &str) -> Vec<String> {
+        self.decorator_nodes(node)
+            .into_iter()
+            .filter_map(|d| d.utf8_text(source.as_bytes()).ok())
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    /// Find a `warnings.warn(..., DeprecationWarning)` call, without looking
+    /// into nested functions or classes.
+    fn find_deprecation_warning(&self, node: Node, source: &str) -> Option<Deprecation> {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "function_definition" | "class_definition" | "decorated_definition" => continue,
+                "call" => {
+                    let function = child
+                        .child_by_field_name("function")
+                        .and_then(|f| f.utf8_text(source.as_bytes()).ok())
+                        .unwrap_or("");
+                    let arguments = child.child_by_field_name("arguments");
+                    let warns_deprecation = arguments
+                        .and_then(|a| a.utf8_text(source.as_bytes()).ok())
+                        .is_some_and(|a| a.contains("DeprecationWarning"));
+
+                    if (function == "warn" || function.ends_with(".warn")) && warns_deprecation {
+                        let message = arguments.and_then(|a| self.first_string(a, source));
+                        return Some(Deprecation::new(message));
+                    }
+                }
+                _ => {}
+            }
+
+            if let Some(deprecation) = self.find_deprecation_warning(child, source) {
+                return Some(deprecation);
+            }
+        }
+        None
+    }
+
+    /// Contents of the first string literal under `node`.
+    fn first_string(&self, node: Node, source: &str) -> Option<String> {
+        if node.kind() == "string" {
+            let mut cursor = node.walk();
+            return node
+                .children(&mut cursor)
+                .find(|c| c.kind() == "string_content")
+                .and_then(|c| c.utf8_text(source.as_bytes()).ok())
+                .map(|s| s.to_string());
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if let Some(text) = self.first_string(child, source) {
+                return Some(text);
+            }
+        }
+        None
+    }
+
     fn extract_function_signature(&self, node: Node, source: &str) -> Option<String> {
         // Get def name(params) -> return_type:
         let start = node.start_byte();
@@ -154,6 +268,40 @@ impl PythonParser {
         source.get(start..end).map(|s| s.trim().to_string())
     }
 
+    /// Parameters (without `self` and `cls`) and return annotation of a
+    /// function. Unannotated parameters have no type; `*args` and
+    /// `**kwargs` keep their stars.
+    fn extract_params(&self, node: Node, source: &str) -> (Vec<Param>, Option<String>) {
+        let text = |node: Node| node.utf8_text(source.as_bytes()).ok().map(str::to_string);
+
+        let mut params = Vec::new();
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                let (name, ty) = match param.kind() {
+                    "identifier" | "list_splat_pattern" | "dictionary_splat_pattern" => {
+                        (text(param), None)
+                    }
+                    // The name is the first child, not a field
+                    "typed_parameter" => (
+                        param.named_child(0).and_then(text),
+                        self.get_child_text(param, "type", source),
+                    ),
+                    "default_parameter" => (self.get_child_text(param, "name", source), None),
+                    "typed_default_parameter" => (
+                        self.get_child_text(param, "name", source),
+                        self.get_child_text(param, "type", source),
+                    ),
+                    _ => continue,
+                };
+                if let Some(name) = name.filter(|name| name != "self" && name != "cls") {
+                    params.push((name, ty));
+                }
+            }
+        }
+        (params, self.get_child_text(node, "return_type", source))
+    }
+
     fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
         let child = node.child_by_field_name(field)?;
         child
@@ -195,10 +343,12 @@ impl PythonParser {
             .map(|(i, line)| {
                 if i == 0 || line.trim().is_empty() {
                     line.trim().to_string()
-                } else if line.len() >= min_indent {
-                    line[min_indent..].to_string()
                 } else {
-                    line.to_string()
+                    // Indentation mixing in multibyte whitespace may not end
+                    // on a char boundary at `min_indent`
+                    line.get(min_indent..)
+                        .unwrap_or_else(|| line.trim_start())
+                        .to_string()
                 }
             })
             .collect::<Vec<_>>()
@@ -234,6 +384,37 @@ impl PythonParser {
     }
 }
 
+/// Last dotted segment of a decorator's callee: `@app.route("/")` -> `route`.
+fn decorator_name(decorator: &str) -> &str {
+    let callee = decorator
+        .trim_start_matches('@')
+        .split('(')
+        .next()
+        .unwrap_or("");
+    callee.trim().rsplit('.').next().unwrap_or("")
+}
+
+/// The class whose body defines `node` (through any decorators), if any.
+/// Functions nested in a method aren't in the class body.
+fn enclosing_class(node: Node) -> Option<Node> {
+    let mut parent = node.parent()?;
+    if parent.kind() == "decorated_definition" {
+        parent = parent.parent()?;
+    }
+    if parent.kind() != "block" {
+        return None;
+    }
+    parent.parent().filter(|p| p.kind() == "class_definition")
+}
+
+/// Put decorators on the lines above a signature.
+fn with_decorators(decorators: &[String], signature: String) -> String {
+    if decorators.is_empty() {
+        return signature;
+    }
+    format!("{}\n{}", decorators.join("\n"), signature)
+}
+
 impl LanguageParser for PythonParser {
     fn parse(&self, source: &str, file_path: &str) -> Result<Vec<CodeChunk>, IndexerError> {
         self.extract_chunks(source, file_path)
@@ -295,7 +476,7 @@ class Calculator:
         let chunks = parser.parse(source, "calc.py").unwrap();
 
         // Should get class and two methods
-        assert!(chunks.len() >= 1);
+        assert!(!chunks.is_empty());
 
         let class_chunk = chunks.iter().find(|c| c.chunk_type == ChunkType::Class);
         assert!(class_chunk.is_some());
@@ -354,6 +535,152 @@ def multiply(a, b):
         );
     }
 
+    #[test]
+    fn test_deprecated_decorator_and_warning() {
+        let parser = PythonParser::new().unwrap();
+        let source = r#"
+@deprecated("Use load_json instead")
+def load(path):
+    return load_json(path)
+
+def read(path):
+    """Read a file."""
+    warnings.warn("read() is deprecated; use open()", DeprecationWarning, stacklevel=2)
+    return open(path).read()
+
+def fetch(url):
+    warnings.warn("slow network", RuntimeWarning)
+    return get(url)
+
+def parse(text):
+    """Parse text.
+
+    .. deprecated:: 2.0
+    """
+    return text
+"#;
+        let chunks = parser.parse(source, "io.py").unwrap();
+        let find = |name: &str| chunks.iter().find(|c| c.name == name).unwrap();
+
+        assert!(find("load").deprecated);
+        assert_eq!(
+            find("load").deprecation_message.as_deref(),
+            Some("Use load_json instead")
+        );
+        assert!(find("read").deprecated);
+        assert_eq!(
+            find("read").deprecation_message.as_deref(),
+            Some("read() is deprecated; use open()")
+        );
+        assert!(!find("fetch").deprecated);
+        assert!(find("parse").deprecated);
+        assert_eq!(find("parse").deprecation_message.as_deref(), Some("2.0"));
+    }
+
+    #[test]
+    fn test_decorators_in_signature() {
+        let parser = PythonParser::new().unwrap();
+        let source = r#"
+@app.route(
+    "/users",
+    methods=["GET"],
+)
+@login_required
+def list_users():
+    """Return all users."""
+    return User.query.all()
+
+@pytest.fixture
+def client():
+    return app.test_client()
+
+@dataclass(frozen=True)
+class Point:
+    x: int
+"#;
+        let chunks = parser.parse(source, "views.py").unwrap();
+
+        let handler = chunks.iter().find(|c| c.name == "list_users").unwrap();
+        assert_eq!(handler.chunk_type, ChunkType::Function);
+        assert_eq!(
+            handler.signature.as_deref(),
+            Some(
+                "@app.route( \"/users\", methods=[\"GET\"], )\n@login_required\ndef list_users():"
+            )
+        );
+        assert!(handler.embedding_text().contains("@app.route"));
+
+        let fixture = chunks.iter().find(|c| c.name == "client").unwrap();
+        assert!(
+            fixture
+                .signature
+                .as_deref()
+                .unwrap()
+                .starts_with("@pytest.fixture\ndef client")
+        );
+
+        let point = chunks.iter().find(|c| c.name == "Point").unwrap();
+        assert_eq!(
+            point.signature.as_deref(),
+            Some("@dataclass(frozen=True)\nclass Point:")
+        );
+    }
+
+    #[test]
+    fn test_defs_in_class_are_methods() {
+        let parser = PythonParser::new().unwrap();
+        let source = r#"
+class Account:
+    def deposit(self, amount: int) -> None:
+        def log():
+            pass
+        self._balance += amount
+
+    @property
+    def balance(self) -> int:
+        return self._balance
+
+    @balance.setter
+    def balance(self, value: int) -> None:
+        self._balance = value
+
+    @staticmethod
+    def currency() -> str:
+        return "EUR"
+
+    @classmethod
+    def open(cls) -> "Account":
+        return cls()
+
+    @functools.cached_property
+    def history(self):
+        return []
+
+def helper():
+    pass
+"#;
+        let chunks = parser.parse(source, "account.py").unwrap();
+
+        let methods: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.chunk_type == ChunkType::Method)
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                "deposit", "balance", "balance", "currency", "open", "history"
+            ]
+        );
+
+        let log = chunks.iter().find(|c| c.name == "log").unwrap();
+        assert_eq!(log.chunk_type, ChunkType::Function);
+
+        let helper = chunks.iter().find(|c| c.name == "helper").unwrap();
+        assert_eq!(helper.chunk_type, ChunkType::Function);
+        assert_eq!(helper.signature.as_deref(), Some("def helper():"));
+    }
+
     #[test]
     fn test_clean_docstring() {
         let parser = PythonParser::new().unwrap();
@@ -370,6 +697,17 @@ def multiply(a, b):
         assert!(!cleaned.contains("\"\"\""));
     }
 
+    #[test]
+    fn test_clean_docstring_multibyte_indent() {
+        let parser = PythonParser::new().unwrap();
+
+        // An ideographic space (3 bytes) indents one line, two ASCII spaces another
+        let input = "\"\"\"Summary.\n  Two spaces.\n\u{3000}Wide space.\n\"\"\"";
+        let cleaned = parser.clean_docstring(input);
+
+        assert_eq!(cleaned, "Summary.\nTwo spaces.\nWide space.");
+    }
+
     #[test]
     fn test_function_signature() {
         let parser = PythonParser::new().unwrap();
@@ -387,6 +725,43 @@ def complex_func(a: int, b: str = "default", *args, **kwargs) -> List[int]:
         assert!(sig.contains("**kwargs"));
     }
 
+    #[test]
+    fn test_params_and_return_annotation() {
+        let parser = PythonParser::new().unwrap();
+        let source = r#"
+class Session:
+    def send(self, request: Request, timeout: float = 5.0, *args, stream=False, **kwargs) -> Response:
+        pass
+
+def parse(data, *, strict: bool):
+    pass
+"#;
+        let chunks = parser.parse(source, "sessions.py").unwrap();
+
+        let send = chunks.iter().find(|c| c.name == "send").unwrap();
+        assert_eq!(
+            send.params,
+            vec![
+                ("request".to_string(), Some("Request".to_string())),
+                ("timeout".to_string(), Some("float".to_string())),
+                ("*args".to_string(), None),
+                ("stream".to_string(), None),
+                ("**kwargs".to_string(), None),
+            ]
+        );
+        assert_eq!(send.returns.as_deref(), Some("Response"));
+
+        let parse = chunks.iter().find(|c| c.name == "parse").unwrap();
+        assert_eq!(
+            parse.params,
+            vec![
+                ("data".to_string(), None),
+                ("strict".to_string(), Some("bool".to_string())),
+            ]
+        );
+        assert_eq!(parse.returns, None);
+    }
+
     #[test]
     fn test_visibility_public_function() {
         let parser = PythonParser::new().unwrap();
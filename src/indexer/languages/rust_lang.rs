@@ -1,7 +1,7 @@
 use crate::types::{ChunkType, Visibility};
 use tree_sitter::{Node, Parser};
 
-use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation, Param, TraitImpl};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
@@ -12,7 +12,8 @@ use crate::indexer::language::{Language, LanguageParser};
 /// - Methods (impl blocks)
 /// - Structs
 /// - Enums
-/// - Traits
+/// - Traits (plus each trait method, including signature-only ones)
+/// - Trait implementations (`impl Trait for Type`)
 /// - Doc comments (///, //!, /** */)
 pub struct RustParser {
     _marker: (),
@@ -36,18 +37,26 @@ impl RustParser {
         &self,
         source: &str,
         file_path: &str,
-    ) -> Result<Vec<CodeChunk>, IndexerError> {
+    ) -> Result<(Vec<CodeChunk>, Vec<TraitImpl>), IndexerError> {
         let mut parser = Self::create_parser()?;
         let tree = parser
             .parse(source, None)
             .ok_or_else(|| IndexerError::ParseError("failed to parse Rust source".into()))?;
 
         let mut chunks = Vec::new();
-        self.visit_node(tree.root_node(), source, file_path, &mut chunks);
-        Ok(chunks)
+        let mut impls = Vec::new();
+        self.visit_node(tree.root_node(), source, file_path, &mut chunks, &mut impls);
+        Ok((chunks, impls))
     }
 
-    fn visit_node(&self, node: Node, source: &str, file_path: &str, chunks: &mut Vec<CodeChunk>) {
+    fn visit_node(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &str,
+        chunks: &mut Vec<CodeChunk>,
+        impls: &mut Vec<TraitImpl>,
+    ) {
         match node.kind() {
             "function_item" => {
                 if let Some(chunk) = self.extract_function(node, source, file_path) {
@@ -68,10 +77,15 @@ impl RustParser {
                 if let Some(chunk) = self.extract_trait(node, source, file_path) {
                     chunks.push(chunk);
                 }
+                // Methods are emitted here; don't re-emit them as free functions
+                self.extract_trait_methods(node, source, file_path, chunks);
+                return;
             }
             "impl_item" => {
+                impls.extend(self.extract_trait_impl(node, source, file_path));
                 // Extract methods from impl block
                 self.extract_impl_methods(node, source, file_path, chunks);
+                return;
             }
             _ => {}
         }
@@ -79,7 +93,7 @@ impl RustParser {
         // Recurse into children
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            self.visit_node(child, source, file_path, chunks);
+            self.visit_node(child, source, file_path, chunks, impls);
         }
     }
 
@@ -89,13 +103,16 @@ impl RustParser {
         let doc = self.extract_doc_comment(node, source);
         let visibility = self.detect_visibility(node, source);
         let signature = self.extract_signature(node, source);
+        let (params, returns) = self.extract_params(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Function)
             .visibility(visibility)
             .name(name)
             .signature(signature.unwrap_or_else(|| code.lines().next().unwrap_or("").to_string()))
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -119,6 +136,7 @@ impl RustParser {
             .name(name)
             .signature(code.lines().next().unwrap_or("").to_string())
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -142,6 +160,7 @@ impl RustParser {
             .name(name)
             .signature(code.lines().next().unwrap_or("").to_string())
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -165,6 +184,7 @@ impl RustParser {
             .name(name)
             .signature(code.lines().next().unwrap_or("").to_string())
             .code(code)
+            .deprecation(self.detect_deprecation(node, source))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -183,6 +203,7 @@ impl RustParser {
         file_path: &str,
         chunks: &mut Vec<CodeChunk>,
     ) {
+        let self_type = self.get_child_text(impl_node, "type", source);
         let mut cursor = impl_node.walk();
         for child in impl_node.children(&mut cursor) {
             if child.kind() == "declaration_list" {
@@ -192,6 +213,7 @@ impl RustParser {
                         && let Some(mut chunk) = self.extract_function(item, source, file_path)
                     {
                         chunk.chunk_type = ChunkType::Method;
+                        chunk.parent = self_type.clone();
                         chunks.push(chunk);
                     }
                 }
@@ -199,6 +221,93 @@ impl RustParser {
         }
     }
 
+    /// The trait an `impl Trait for Type` block implements, and for which type.
+    ///
+    /// Inherent impls (no trait) and negative impls (`impl !Send for T`)
+    /// declare no implementation.
+    fn extract_trait_impl(&self, node: Node, source: &str, file_path: &str) -> Option<TraitImpl> {
+        let trait_node = node.child_by_field_name("trait")?;
+        let type_node = node.child_by_field_name("type")?;
+        let mut cursor = node.walk();
+        if node.children(&mut cursor).any(|c| c.kind() == "!") {
+            return None;
+        }
+
+        // The header runs up to the body
+        let end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |body| body.start_byte());
+        let signature = source.get(node.start_byte()..end)?.trim();
+
+        Some(TraitImpl {
+            trait_name: base_type_name(trait_node, source)?,
+            type_name: base_type_name(type_node, source)?,
+            signature: signature.split_whitespace().collect::<Vec<_>>().join(" "),
+            file_path: file_path.to_string(),
+            line: node.start_position().row as u32 + 1,
+        })
+    }
+
+    /// Emit each method declared in a trait, with or without a default body.
+    ///
+    /// Trait methods take the trait's visibility, since they can't carry
+    /// their own modifier.
+    fn extract_trait_methods(
+        &self,
+        trait_node: Node,
+        source: &str,
+        file_path: &str,
+        chunks: &mut Vec<CodeChunk>,
+    ) {
+        let Some(trait_name) = self.get_child_text(trait_node, "name", source) else {
+            return;
+        };
+        let Some(body) = trait_node.child_by_field_name("body") else {
+            return;
+        };
+        let visibility = self.detect_visibility(trait_node, source);
+
+        let mut cursor = body.walk();
+        for item in body.children(&mut cursor) {
+            if !matches!(item.kind(), "function_item" | "function_signature_item") {
+                continue;
+            }
+            let Some(name) = self.get_child_text(item, "name", source) else {
+                continue;
+            };
+            let Ok(code) = item.utf8_text(source.as_bytes()) else {
+                continue;
+            };
+            let signature = self
+                .extract_signature(item, source)
+                .unwrap_or_else(|| code.trim().trim_end_matches(';').trim_end().to_string());
+            let doc = self.extract_doc_comment(item, source);
+            let (params, returns) = self.extract_params(item, source);
+
+            if let Some(chunk) = ChunkBuilder::new()
+                .chunk_type(ChunkType::Method)
+                .visibility(visibility)
+                .name(name)
+                .parent(&trait_name)
+                .signature(signature)
+                .params(params, returns)
+                .code(code)
+                .deprecation(self.detect_deprecation(item, source))
+                .documentation(doc.unwrap_or_default())
+                .file_path(file_path)
+                .location(
+                    item.start_position().row as u32 + 1,
+                    item.end_position().row as u32 + 1,
+                    item.start_byte(),
+                    item.end_byte(),
+                )
+                .build()
+            {
+                chunks.push(chunk);
+            }
+        }
+    }
+
     fn extract_doc_comment(&self, node: Node, source: &str) -> Option<String> {
         // Look for doc comments (///, //!, /** */) before this node
         let mut comments = Vec::new();
@@ -225,17 +334,41 @@ impl RustParser {
         }
     }
 
+    /// Find a `#[deprecated]` attribute among the item's attributes.
+    ///
+    /// The message comes from `#[deprecated = "..."]` or the `note` in
+    /// `#[deprecated(since = "...", note = "...")]`.
+    fn detect_deprecation(&self, node: Node, source: &str) -> Option<Deprecation> {
+        let mut prev = node.prev_sibling();
+        while let Some(sibling) = prev {
+            match sibling.kind() {
+                "attribute_item" => {
+                    let text = sibling.utf8_text(source.as_bytes()).ok()?;
+                    let attribute = text.trim_start_matches("#[").trim_start();
+                    if let Some(args) = attribute.strip_prefix("deprecated")
+                        && !args.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                    {
+                        return Some(Deprecation::new(deprecation_note(args)));
+                    }
+                }
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            prev = sibling.prev_sibling();
+        }
+        None
+    }
+
     fn clean_doc_comment(&self, comment: &str) -> String {
-        comment
-            .trim()
-            .strip_prefix("///")
-            .or_else(|| comment.strip_prefix("//!"))
-            .or_else(|| comment.strip_prefix("/**"))
-            .unwrap_or(comment)
-            .strip_suffix("*/")
-            .unwrap_or(comment)
-            .trim()
-            .to_string()
+        let comment = comment.trim();
+        let body = match comment.strip_prefix("/**") {
+            Some(block) => block.strip_suffix("*/").unwrap_or(block),
+            None => comment
+                .strip_prefix("///")
+                .or_else(|| comment.strip_prefix("//!"))
+                .unwrap_or(comment),
+        };
+        body.trim().to_string()
     }
 
     fn extract_signature(&self, node: Node, source: &str) -> Option<String> {
@@ -252,6 +385,23 @@ impl RustParser {
         None
     }
 
+    /// Parameters (without `self`) and return type of a function.
+    fn extract_params(&self, node: Node, source: &str) -> (Vec<Param>, Option<String>) {
+        let mut params = Vec::new();
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                if param.kind() != "parameter" {
+                    continue;
+                }
+                if let Some(name) = self.get_child_text(param, "pattern", source) {
+                    params.push((name, self.get_child_text(param, "type", source)));
+                }
+            }
+        }
+        (params, self.get_child_text(node, "return_type", source))
+    }
+
     fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
         let child = node.child_by_field_name(field)?;
         child
@@ -286,8 +436,47 @@ impl RustParser {
     }
 }
 
+/// The message in a `#[deprecated]` attribute's arguments: the string after
+/// `=`, or the `note = "..."` inside parentheses.
+fn deprecation_note(args: &str) -> Option<String> {
+    let args = args.trim_start();
+    let value = if let Some(value) = args.strip_prefix('=') {
+        value
+    } else {
+        let note = args.find("note")?;
+        args[note + "note".len()..].trim_start().strip_prefix('=')?
+    };
+
+    let value = value.trim_start().strip_prefix('"')?;
+    let end = value.find('"')?;
+    Some(value[..end].to_string())
+}
+
+/// Name of the type `node` refers to, without its path, generic
+/// arguments or reference (`&'a mut std::vec::Vec<T>` is `Vec`).
+fn base_type_name(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "generic_type" | "reference_type" | "pointer_type" => {
+            base_type_name(node.child_by_field_name("type")?, source)
+        }
+        "scoped_type_identifier" => base_type_name(node.child_by_field_name("name")?, source),
+        _ => node
+            .utf8_text(source.as_bytes())
+            .ok()
+            .map(|text| text.to_string()),
+    }
+}
+
 impl LanguageParser for RustParser {
     fn parse(&self, source: &str, file_path: &str) -> Result<Vec<CodeChunk>, IndexerError> {
+        Ok(self.extract_chunks(source, file_path)?.0)
+    }
+
+    fn parse_with_impls(
+        &self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<(Vec<CodeChunk>, Vec<TraitImpl>), IndexerError> {
         self.extract_chunks(source, file_path)
     }
 
@@ -355,9 +544,96 @@ pub trait Drawable {
 "#;
         let chunks = parser.parse(source, "traits.rs").unwrap();
 
-        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks.len(), 2);
         assert_eq!(chunks[0].name, "Drawable");
         assert_eq!(chunks[0].chunk_type, ChunkType::Interface);
+        assert_eq!(chunks[1].name, "draw");
+        assert_eq!(chunks[1].parent.as_deref(), Some("Drawable"));
+    }
+
+    #[test]
+    fn test_trait_methods() {
+        let parser = RustParser::new().unwrap();
+        let source = r#"
+pub trait Iterator {
+    type Item;
+
+    /// Advances the iterator and returns the next value.
+    fn next(&mut self) -> Option<Self::Item>;
+
+    fn count(self) -> usize
+    where
+        Self: Sized,
+    {
+        0
+    }
+}
+"#;
+        let chunks = parser.parse(source, "iter.rs").unwrap();
+
+        let names: Vec<_> = chunks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Iterator", "next", "count"]);
+
+        let iterator = &chunks[0];
+        assert_eq!(iterator.chunk_type, ChunkType::Interface);
+        assert!(iterator.parent.is_none());
+
+        let next = &chunks[1];
+        assert_eq!(next.chunk_type, ChunkType::Method);
+        assert_eq!(next.parent.as_deref(), Some("Iterator"));
+        assert_eq!(next.visibility, Visibility::Public);
+        assert_eq!(
+            next.signature.as_deref(),
+            Some("fn next(&mut self) -> Option<Self::Item>")
+        );
+        assert_eq!(
+            next.documentation.as_deref(),
+            Some("Advances the iterator and returns the next value.")
+        );
+
+        let count = &chunks[2];
+        assert_eq!(count.chunk_type, ChunkType::Method);
+        assert_eq!(count.parent.as_deref(), Some("Iterator"));
+        assert!(
+            count
+                .signature
+                .as_deref()
+                .unwrap()
+                .starts_with("fn count(self)")
+        );
+    }
+
+    #[test]
+    fn test_params_and_return_type() {
+        let parser = RustParser::new().unwrap();
+        let source = r#"
+pub fn send(client: &Client, req: Request, (a, b): (u8, u8)) -> Result<Response, Error> {
+    todo!()
+}
+
+impl Router {
+    pub fn route(&mut self, path: &str) {}
+}
+"#;
+        let chunks = parser.parse(source, "http.rs").unwrap();
+
+        let send = &chunks[0];
+        assert_eq!(
+            send.params,
+            vec![
+                ("client".to_string(), Some("&Client".to_string())),
+                ("req".to_string(), Some("Request".to_string())),
+                ("(a, b)".to_string(), Some("(u8, u8)".to_string())),
+            ]
+        );
+        assert_eq!(send.returns.as_deref(), Some("Result<Response, Error>"));
+
+        let route = &chunks[1];
+        assert_eq!(
+            route.params,
+            vec![("path".to_string(), Some("&str".to_string()))]
+        );
+        assert_eq!(route.returns, None);
     }
 
     #[test]
@@ -426,9 +702,112 @@ impl Foo {
 
         let new_method = chunks.iter().find(|c| c.name == "new").unwrap();
         assert_eq!(new_method.chunk_type, ChunkType::Method);
+        assert_eq!(new_method.parent.as_deref(), Some("Foo"));
         assert_eq!(new_method.visibility, Visibility::Public);
 
         let helper = chunks.iter().find(|c| c.name == "private_helper").unwrap();
         assert_eq!(helper.visibility, Visibility::Private);
     }
+
+    #[test]
+    fn test_trait_impls() {
+        let parser = RustParser::new().unwrap();
+        let source = r#"
+pub struct Counter { n: u32 }
+
+impl Counter {
+    pub fn new() -> Self { Counter { n: 0 } }
+}
+
+impl Iterator for Counter {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> { None }
+}
+
+impl<'a, T: Clone> std::fmt::Display for &'a Wrapper<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { Ok(()) }
+}
+
+impl<T> From<Vec<T>> for Stack<T>
+where
+    T: Copy,
+{
+    fn from(items: Vec<T>) -> Self { Stack(items) }
+}
+
+unsafe impl Send for Counter {}
+impl !Sync for Counter {}
+"#;
+        let (chunks, impls) = parser.parse_with_impls(source, "lib.rs").unwrap();
+        let edges: Vec<_> = impls
+            .iter()
+            .map(|i| (i.trait_name.as_str(), i.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("Iterator", "Counter"),
+                ("Display", "Wrapper"),
+                ("From", "Stack"),
+                ("Send", "Counter"),
+            ]
+        );
+
+        assert_eq!(impls[0].signature, "impl Iterator for Counter");
+        assert_eq!(impls[0].line, 8);
+        assert_eq!(impls[0].file_path, "lib.rs");
+        assert_eq!(
+            impls[2].signature,
+            "impl<T> From<Vec<T>> for Stack<T> where T: Copy,"
+        );
+
+        // Methods are still emitted, with the implementing type as parent
+        let next = chunks.iter().find(|c| c.name == "next").unwrap();
+        assert_eq!(next.parent.as_deref(), Some("Counter"));
+        assert_eq!(parser.parse(source, "lib.rs").unwrap().len(), chunks.len());
+    }
+
+    #[test]
+    fn test_deprecated_attribute() {
+        let parser = RustParser::new().unwrap();
+        let source = r#"
+/// Old entry point.
+#[deprecated(since = "0.3.0", note = "use `run` instead")]
+pub fn start() {}
+
+#[deprecated = "renamed to Config"]
+pub struct Settings;
+
+#[deprecated]
+#[inline]
+pub fn legacy() {}
+
+#[deprecated_alias]
+pub fn run() {}
+
+impl Settings {
+    #[deprecated(note = "use `Config::load`")]
+    pub fn load() -> Self { Settings }
+}
+"#;
+        let chunks = parser.parse(source, "lib.rs").unwrap();
+        let find = |name: &str| chunks.iter().find(|c| c.name == name).unwrap();
+
+        assert!(find("start").deprecated);
+        assert_eq!(
+            find("start").deprecation_message.as_deref(),
+            Some("use `run` instead")
+        );
+        assert_eq!(
+            find("Settings").deprecation_message.as_deref(),
+            Some("renamed to Config")
+        );
+        assert!(find("legacy").deprecated);
+        assert!(find("legacy").deprecation_message.is_none());
+        assert!(!find("run").deprecated);
+        assert_eq!(
+            find("load").deprecation_message.as_deref(),
+            Some("use `Config::load`")
+        );
+    }
 }
@@ -0,0 +1,469 @@
+use crate::types::{ChunkType, Visibility};
+use tree_sitter::{Node, Parser};
+
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation};
+use crate::indexer::error::IndexerError;
+use crate::indexer::language::{Language, LanguageParser};
+
+/// Parser for Scala using tree-sitter.
+///
+/// Scala members are public unless marked otherwise:
+/// - (no modifier) = Public
+/// - protected = Protected
+/// - private = Private
+/// - private[pkg] / protected[pkg] = Internal (visible within `pkg`, as
+///   Spark's `private[spark]` helpers are)
+///
+/// Classes, objects and traits become chunks, as do `def`s, abstract ones
+/// included. Defs local to another def's body are implementation detail
+/// and left out.
+pub struct ScalaParser {
+    _marker: (),
+}
+
+impl ScalaParser {
+    pub fn new() -> Result<Self, IndexerError> {
+        Ok(Self { _marker: () })
+    }
+
+    fn create_parser() -> Result<Parser, IndexerError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_scala::LANGUAGE;
+        parser
+            .set_language(&language.into())
+            .map_err(|e| IndexerError::TreeSitter(e.to_string()))?;
+        Ok(parser)
+    }
+
+    fn extract_chunks(
+        &self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<Vec<CodeChunk>, IndexerError> {
+        let mut parser = Self::create_parser()?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| IndexerError::ParseError("failed to parse Scala source".into()))?;
+
+        let mut chunks = Vec::new();
+        self.visit_node(tree.root_node(), source, file_path, &mut chunks);
+        Ok(chunks)
+    }
+
+    fn visit_node(&self, node: Node, source: &str, file_path: &str, chunks: &mut Vec<CodeChunk>) {
+        match node.kind() {
+            "function_definition" | "function_declaration" => {
+                let parent = self.enclosing_type(node, source);
+                let chunk_type = if parent.is_some() {
+                    ChunkType::Method
+                } else {
+                    ChunkType::Function
+                };
+                if let Some(chunk) =
+                    self.extract_definition(node, chunk_type, parent, source, file_path)
+                {
+                    chunks.push(chunk);
+                }
+                // Local defs are part of the body, not API
+                return;
+            }
+            "class_definition" | "object_definition" => {
+                let parent = self.enclosing_type(node, source);
+                if let Some(chunk) =
+                    self.extract_definition(node, ChunkType::Class, parent, source, file_path)
+                {
+                    chunks.push(chunk);
+                }
+            }
+            "trait_definition" => {
+                let parent = self.enclosing_type(node, source);
+                if let Some(chunk) =
+                    self.extract_definition(node, ChunkType::Interface, parent, source, file_path)
+                {
+                    chunks.push(chunk);
+                }
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit_node(child, source, file_path, chunks);
+        }
+    }
+
+    fn extract_definition(
+        &self,
+        node: Node,
+        chunk_type: ChunkType,
+        parent: Option<String>,
+        source: &str,
+        file_path: &str,
+    ) -> Option<CodeChunk> {
+        let name = self.get_child_text(node, "name", source)?;
+        let code = node.utf8_text(source.as_bytes()).ok()?;
+        let doc = self.documentation(node, source);
+
+        let mut builder = ChunkBuilder::new()
+            .chunk_type(chunk_type)
+            .visibility(self.detect_visibility(node, source))
+            .name(name)
+            .signature(self.signature(node, source))
+            .code(code)
+            .deprecation(self.detect_deprecation(node, source))
+            .documentation(doc.unwrap_or_default())
+            .file_path(file_path)
+            .location(
+                node.start_position().row as u32 + 1,
+                node.end_position().row as u32 + 1,
+                node.start_byte(),
+                node.end_byte(),
+            );
+        if let Some(parent) = parent {
+            builder = builder.parent(parent);
+        }
+        builder.build()
+    }
+
+    /// A definition up to its body, starting after its annotations, with
+    /// whitespace collapsed: `def get(key: String): Option[V]`,
+    /// `case class Point(x: Int, y: Int) extends Shape`.
+    fn signature(&self, node: Node, source: &str) -> String {
+        let start = self
+            .annotation_nodes(node)
+            .last()
+            .map_or(node.start_byte(), |annotation| annotation.end_byte());
+        let end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |body| body.start_byte());
+
+        let signature = source[start..end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        signature.trim_end_matches('=').trim_end().to_string()
+    }
+
+    /// Scaladoc followed by the definition's annotations, one per line.
+    fn documentation(&self, node: Node, source: &str) -> Option<String> {
+        let mut parts: Vec<String> = self.extract_scaladoc(node, source).into_iter().collect();
+        parts.extend(self.annotations(node, source));
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+
+    /// Scala marks deprecation with `@deprecated("message", "since")`,
+    /// sometimes alongside a Scaladoc `@deprecated` tag. Either one counts.
+    fn detect_deprecation(&self, node: Node, source: &str) -> Option<Deprecation> {
+        let tagged = self
+            .extract_scaladoc(node, source)
+            .and_then(|doc| Deprecation::from_doc_tag(&doc, "@deprecated"));
+
+        tagged.or_else(|| {
+            self.annotations(node, source)
+                .iter()
+                .find(|annotation| {
+                    let name = annotation.trim_start_matches('@').split('(').next();
+                    matches!(name, Some("deprecated" | "scala.deprecated"))
+                })
+                .map(|annotation| {
+                    // The message is the first string argument
+                    let message = annotation.split('"').nth(1).map(str::to_string);
+                    Deprecation::new(message)
+                })
+        })
+    }
+
+    fn annotation_nodes<'t>(&self, node: Node<'t>) -> Vec<Node<'t>> {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .filter(|c| c.kind() == "annotation")
+            .collect()
+    }
+
+    /// Annotations on a definition, whitespace-collapsed.
+    fn annotations(&self, node: Node, source: &str) -> Vec<String> {
+        self.annotation_nodes(node)
+            .into_iter()
+            .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    /// Name of the class, object or trait whose body declares `node`.
+    fn enclosing_type(&self, node: Node, source: &str) -> Option<String> {
+        let mut current = node.parent();
+        while let Some(n) = current {
+            match n.kind() {
+                "class_definition" | "object_definition" | "trait_definition" => {
+                    return self.get_child_text(n, "name", source);
+                }
+                // A type declared inside a def is local to it
+                "function_definition" => return None,
+                _ => {}
+            }
+            current = n.parent();
+        }
+        None
+    }
+
+    fn extract_scaladoc(&self, node: Node, source: &str) -> Option<String> {
+        let mut prev = node.prev_sibling();
+        while let Some(sibling) = prev {
+            match sibling.kind() {
+                "block_comment" | "comment" => {
+                    let text = sibling.utf8_text(source.as_bytes()).ok()?;
+                    if text.starts_with("/**") {
+                        return Some(self.clean_scaladoc(text));
+                    }
+                    if text.starts_with("/*") {
+                        return None;
+                    }
+                    // Line comments between the Scaladoc and the definition
+                }
+                _ => return None,
+            }
+            prev = sibling.prev_sibling();
+        }
+        None
+    }
+
+    fn clean_scaladoc(&self, comment: &str) -> String {
+        comment
+            .trim()
+            .strip_prefix("/**")
+            .unwrap_or(comment)
+            .strip_suffix("*/")
+            .unwrap_or(comment)
+            .lines()
+            .map(|line| line.trim().strip_prefix("*").unwrap_or(line).trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
+        let child = node.child_by_field_name(field)?;
+        child
+            .utf8_text(source.as_bytes())
+            .ok()
+            .map(|s| s.to_string())
+    }
+
+    /// Detect visibility from the access modifier, public by default.
+    fn detect_visibility(&self, node: Node, source: &str) -> Visibility {
+        let mut cursor = node.walk();
+        let Some(modifiers) = node.children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+            return Visibility::Public;
+        };
+
+        let mut cursor = modifiers.walk();
+        for modifier in modifiers.children(&mut cursor) {
+            if modifier.kind() != "access_modifier" {
+                continue;
+            }
+            let text = modifier.utf8_text(source.as_bytes()).unwrap_or("");
+            // Qualified access (`private[spark]`) opens the member up to a package
+            let qualified = text.contains('[') && !text.contains("[this]");
+            return match (text.starts_with("private"), qualified) {
+                (_, true) => Visibility::Internal,
+                (true, false) => Visibility::Private,
+                (false, false) => Visibility::Protected,
+            };
+        }
+        Visibility::Public
+    }
+}
+
+impl LanguageParser for ScalaParser {
+    fn parse(&self, source: &str, file_path: &str) -> Result<Vec<CodeChunk>, IndexerError> {
+        self.extract_chunks(source, file_path)
+    }
+
+    fn language(&self) -> Language {
+        Language::Scala
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_public_method() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+class Calculator {
+  /**
+   * Adds two numbers.
+   * @param a first number
+   * @param b second number
+   * @return the sum
+   */
+  def add(a: Int, b: Int): Int = a + b
+}
+"#;
+        let chunks = parser.parse(source, "Calculator.scala").unwrap();
+
+        let method = chunks.iter().find(|c| c.name == "add").unwrap();
+        assert_eq!(method.chunk_type, ChunkType::Method);
+        assert_eq!(method.visibility, Visibility::Public);
+        assert_eq!(method.parent.as_deref(), Some("Calculator"));
+        assert_eq!(
+            method.signature.as_deref(),
+            Some("def add(a: Int, b: Int): Int")
+        );
+        assert!(
+            method
+                .documentation
+                .as_ref()
+                .unwrap()
+                .contains("Adds two numbers")
+        );
+    }
+
+    #[test]
+    fn test_visibility_modifiers() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+class Executor {
+  private def helper(): Unit = ()
+  protected def onStart(): Unit = ()
+  private[spark] def schedule(): Unit = ()
+  private[this] def cleanup(): Unit = ()
+}
+"#;
+        let chunks = parser.parse(source, "Executor.scala").unwrap();
+
+        let visibility = |name: &str| chunks.iter().find(|c| c.name == name).unwrap().visibility;
+        assert_eq!(visibility("Executor"), Visibility::Public);
+        assert_eq!(visibility("helper"), Visibility::Private);
+        assert_eq!(visibility("onStart"), Visibility::Protected);
+        assert_eq!(visibility("schedule"), Visibility::Internal);
+        assert_eq!(visibility("cleanup"), Visibility::Private);
+    }
+
+    #[test]
+    fn test_parse_trait_and_object() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+/**
+ * Reads rows from a source.
+ */
+trait Reader[T] {
+  def read(path: String): Seq[T]
+}
+
+/** Factory for readers. */
+object Reader {
+  def csv(): Reader[String] = new CsvReader
+}
+"#;
+        let chunks = parser.parse(source, "Reader.scala").unwrap();
+
+        let reader = chunks
+            .iter()
+            .find(|c| c.name == "Reader" && c.chunk_type == ChunkType::Interface)
+            .unwrap();
+        assert_eq!(reader.signature.as_deref(), Some("trait Reader[T]"));
+        assert_eq!(
+            reader.documentation.as_deref(),
+            Some("Reads rows from a source.")
+        );
+
+        // Abstract defs are declarations
+        let read = chunks.iter().find(|c| c.name == "read").unwrap();
+        assert_eq!(read.chunk_type, ChunkType::Method);
+        assert_eq!(
+            read.signature.as_deref(),
+            Some("def read(path: String): Seq[T]")
+        );
+
+        let object = chunks
+            .iter()
+            .find(|c| c.name == "Reader" && c.chunk_type == ChunkType::Class)
+            .unwrap();
+        assert_eq!(object.signature.as_deref(), Some("object Reader"));
+        assert_eq!(
+            object.documentation.as_deref(),
+            Some("Factory for readers.")
+        );
+        let csv = chunks.iter().find(|c| c.name == "csv").unwrap();
+        assert_eq!(csv.parent.as_deref(), Some("Reader"));
+    }
+
+    #[test]
+    fn test_case_class_and_local_defs() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+@SerialVersionUID(1L)
+case class Point(x: Int, y: Int) extends Shape {
+  def distance(other: Point): Double = {
+    def square(v: Int): Int = v * v
+    math.sqrt(square(x - other.x) + square(y - other.y))
+  }
+}
+"#;
+        let chunks = parser.parse(source, "Point.scala").unwrap();
+
+        let point = chunks.iter().find(|c| c.name == "Point").unwrap();
+        assert_eq!(point.chunk_type, ChunkType::Class);
+        assert_eq!(
+            point.signature.as_deref(),
+            Some("case class Point(x: Int, y: Int) extends Shape")
+        );
+        assert_eq!(
+            point.documentation.as_deref(),
+            Some("@SerialVersionUID(1L)")
+        );
+
+        assert!(chunks.iter().any(|c| c.name == "distance"));
+        assert!(chunks.iter().all(|c| c.name != "square"));
+    }
+
+    #[test]
+    fn test_deprecated_annotation() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+object Dates {
+  @deprecated("Use parseIso instead", "2.0")
+  def parse(text: String): Date = null
+
+  /** Parses an ISO-8601 date. */
+  def parseIso(text: String): Date = null
+}
+"#;
+        let chunks = parser.parse(source, "Dates.scala").unwrap();
+
+        let parse = chunks.iter().find(|c| c.name == "parse").unwrap();
+        assert!(parse.deprecated);
+        assert_eq!(
+            parse.deprecation_message.as_deref(),
+            Some("Use parseIso instead")
+        );
+        assert_eq!(
+            parse.signature.as_deref(),
+            Some("def parse(text: String): Date")
+        );
+
+        let parse_iso = chunks.iter().find(|c| c.name == "parseIso").unwrap();
+        assert!(!parse_iso.deprecated);
+    }
+
+    #[test]
+    fn test_top_level_function() {
+        let parser = ScalaParser::new().unwrap();
+        let source = r#"
+/** Greets someone (Scala 3 top-level definition). */
+def greet(name: String): String = s"Hello, $name"
+"#;
+        let chunks = parser.parse(source, "greet.scala").unwrap();
+
+        let greet = chunks.iter().find(|c| c.name == "greet").unwrap();
+        assert_eq!(greet.chunk_type, ChunkType::Function);
+        assert!(greet.parent.is_none());
+        assert_eq!(
+            greet.documentation.as_deref(),
+            Some("Greets someone (Scala 3 top-level definition).")
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::types::{ChunkType, Visibility};
 use tree_sitter::{Node, Parser, Tree};
 
-use crate::indexer::chunk::{ChunkBuilder, CodeChunk};
+use crate::indexer::chunk::{ChunkBuilder, CodeChunk, Deprecation, Param};
 use crate::indexer::error::IndexerError;
 use crate::indexer::language::{Language, LanguageParser};
 
@@ -11,6 +11,8 @@ use crate::indexer::language::{Language, LanguageParser};
 /// - Functions (function declarations, arrow functions, methods)
 /// - Classes
 /// - Interfaces/Types (TypeScript)
+/// - Namespaces and ambient modules (TypeScript), whose members get the
+///   namespace as their parent
 /// - JSDoc comments associated with declarations
 pub struct TypeScriptParser {
     parser: Parser,
@@ -71,10 +73,20 @@ impl TypeScriptParser {
         let kind = node.kind();
 
         // Check if this node produces a chunk
+        let mut produced = Vec::new();
         let chunk = match kind {
             "function_declaration" => {
                 self.extract_function(node, source, file_path, preceding_comment)
             }
+            // Bodiless declarations are the API of `.d.ts` files and
+            // `declare` blocks; elsewhere they are overloads of a function
+            // that is indexed itself
+            "function_signature" if self.is_typescript && self.is_ambient(node, file_path) => {
+                self.extract_function(node, source, file_path, preceding_comment)
+            }
+            "internal_module" | "module" if self.is_typescript => {
+                self.extract_namespace(node, source, file_path, preceding_comment)
+            }
             "method_definition" => self.extract_method(node, source, file_path, preceding_comment),
             "class_declaration" => self.extract_class(node, source, file_path, preceding_comment),
             "interface_declaration" if self.is_typescript => {
@@ -84,15 +96,27 @@ impl TypeScriptParser {
                 self.extract_type_alias(node, source, file_path, preceding_comment)
             }
             "lexical_declaration" => {
-                // Could be arrow function: const foo = () => {}
-                self.extract_arrow_function(node, source, file_path, preceding_comment)
+                // Could be arrow functions: const foo = () => {}, bar = () => {}
+                produced.extend(self.extract_arrow_functions(
+                    node,
+                    source,
+                    file_path,
+                    preceding_comment,
+                ));
+                None
             }
             _ => None,
         };
+        produced.extend(chunk);
 
-        if let Some(c) = chunk {
-            chunks.push(c);
+        if !produced.is_empty()
+            && let Some(namespace) = self.enclosing_namespace(node, source)
+        {
+            for c in &mut produced {
+                c.parent.get_or_insert_with(|| namespace.clone());
+            }
         }
+        chunks.extend(produced);
 
         // Find preceding comment for next sibling
         let comment = if kind == "comment" {
@@ -122,13 +146,16 @@ impl TypeScriptParser {
             .map(|c| self.clean_jsdoc(c))
             .or_else(|| self.find_leading_comment(node, source));
         let visibility = self.detect_visibility(node, &name, source);
+        let (params, returns) = self.extract_params(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Function)
             .visibility(visibility)
             .name(name)
             .signature(signature.unwrap_or_else(|| code.lines().next().unwrap_or("").to_string()))
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -154,13 +181,16 @@ impl TypeScriptParser {
             .map(|c| self.clean_jsdoc(c))
             .or_else(|| self.find_leading_comment(node, source));
         let visibility = self.detect_visibility(node, &name, source);
+        let (params, returns) = self.extract_params(node, source);
 
         ChunkBuilder::new()
             .chunk_type(ChunkType::Method)
             .visibility(visibility)
             .name(name)
             .signature(signature.unwrap_or_else(|| code.lines().next().unwrap_or("").to_string()))
+            .params(params, returns)
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -195,6 +225,7 @@ impl TypeScriptParser {
             .name(name)
             .signature(signature.unwrap_or_default())
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -226,6 +257,7 @@ impl TypeScriptParser {
             .name(name)
             .signature(code.lines().next().unwrap_or("").to_string())
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -257,6 +289,7 @@ impl TypeScriptParser {
             .name(name)
             .signature(code.to_string())
             .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
             .documentation(doc.unwrap_or_default())
             .file_path(file_path)
             .location(
@@ -268,47 +301,170 @@ impl TypeScriptParser {
             .build()
     }
 
-    fn extract_arrow_function(
+    /// A `namespace Foo {}`, `module Foo {}` or `declare module "foo" {}`
+    /// block. Its members are visited as usual and get it as their parent.
+    fn extract_namespace(
         &self,
         node: Node,
         source: &str,
         file_path: &str,
         preceding_comment: Option<&str>,
     ) -> Option<CodeChunk> {
+        let name = self.namespace_name(node, source)?;
+        let code = node.utf8_text(source.as_bytes()).ok()?;
+        let doc = preceding_comment
+            .map(|c| self.clean_jsdoc(c))
+            .or_else(|| self.find_leading_comment(node, source));
+        let visibility = self.detect_visibility(node, &name, source);
+
+        // `namespace Foo`, with `declare`/`export` when present
+        let start = node
+            .parent()
+            .filter(|p| matches!(p.kind(), "ambient_declaration" | "export_statement"))
+            .unwrap_or(node)
+            .start_byte();
+        let end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |body| body.start_byte());
+        let signature = source.get(start..end).unwrap_or("").trim().to_string();
+
+        ChunkBuilder::new()
+            .chunk_type(ChunkType::Module)
+            .visibility(visibility)
+            .name(name)
+            .signature(signature)
+            .code(code)
+            .deprecation(self.detect_deprecation(doc.as_deref()))
+            .documentation(doc.unwrap_or_default())
+            .file_path(file_path)
+            .location(
+                node.start_position().row as u32 + 1,
+                node.end_position().row as u32 + 1,
+                node.start_byte(),
+                node.end_byte(),
+            )
+            .build()
+    }
+
+    /// Name of a namespace or module, without the quotes of an ambient
+    /// module's string name.
+    fn namespace_name(&self, node: Node, source: &str) -> Option<String> {
+        let name = self.get_child_text(node, "name", source)?;
+        Some(name.trim_matches(|c| c == '"' || c == '\'').to_string())
+    }
+
+    /// Dotted path of the namespaces declaring `node`, e.g. `Outer.Inner`.
+    ///
+    /// Stops at classes and function bodies: their members belong to them,
+    /// not to the surrounding namespace.
+    fn enclosing_namespace(&self, node: Node, source: &str) -> Option<String> {
+        let mut names = Vec::new();
+        let mut current = node.parent();
+        while let Some(n) = current {
+            match n.kind() {
+                "internal_module" | "module" => names.extend(self.namespace_name(n, source)),
+                "class_body"
+                | "function_declaration"
+                | "function_expression"
+                | "arrow_function"
+                | "method_definition"
+                    if names.is_empty() =>
+                {
+                    return None;
+                }
+                _ => {}
+            }
+            current = n.parent();
+        }
+        if names.is_empty() {
+            return None;
+        }
+        names.reverse();
+        Some(names.join("."))
+    }
+
+    /// Whether `node` is in an ambient context: a `.d.ts` file or a
+    /// `declare` block, where declarations describe API without bodies.
+    fn is_ambient(&self, node: Node, file_path: &str) -> bool {
+        file_path.ends_with(".d.ts") || self.in_declare_block(node)
+    }
+
+    /// Whether `node` is inside a `declare` declaration, such as
+    /// `declare namespace Foo {}` or `declare function foo(): void`.
+    fn in_declare_block(&self, node: Node) -> bool {
+        let mut current = node.parent();
+        while let Some(n) = current {
+            if n.kind() == "ambient_declaration" {
+                return true;
+            }
+            current = n.parent();
+        }
+        false
+    }
+
+    /// Extract arrow functions, one chunk per declarator.
+    ///
+    /// A declaration with a single declarator keeps the whole statement as its
+    /// code. With several (`const a = () => 1, b = () => 2`), each chunk spans
+    /// only its own declarator so line ranges point at the right symbol.
+    fn extract_arrow_functions(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &str,
+        preceding_comment: Option<&str>,
+    ) -> Vec<CodeChunk> {
         // lexical_declaration -> variable_declarator -> arrow_function
         let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "variable_declarator" {
-                let name_node = child.child_by_field_name("name")?;
+        let declarators: Vec<Node> = node
+            .children(&mut cursor)
+            .filter(|c| c.kind() == "variable_declarator")
+            .collect();
+        let grouped = declarators.len() > 1;
+
+        declarators
+            .iter()
+            .enumerate()
+            .filter_map(|(i, declarator)| {
+                let name_node = declarator.child_by_field_name("name")?;
                 let name = name_node.utf8_text(source.as_bytes()).ok()?;
 
-                let value_node = child.child_by_field_name("value")?;
-                if value_node.kind() == "arrow_function" {
-                    let code = node.utf8_text(source.as_bytes()).ok()?;
-                    let doc = preceding_comment
-                        .map(|c| self.clean_jsdoc(c))
-                        .or_else(|| self.find_leading_comment(node, source));
-                    let visibility = self.detect_visibility(node, name, source);
-
-                    return ChunkBuilder::new()
-                        .chunk_type(ChunkType::Function)
-                        .visibility(visibility)
-                        .name(name)
-                        .signature(code.lines().next().unwrap_or("").to_string())
-                        .code(code)
-                        .documentation(doc.unwrap_or_default())
-                        .file_path(file_path)
-                        .location(
-                            node.start_position().row as u32 + 1,
-                            node.end_position().row as u32 + 1,
-                            node.start_byte(),
-                            node.end_byte(),
-                        )
-                        .build();
+                let value_node = declarator.child_by_field_name("value")?;
+                if value_node.kind() != "arrow_function" {
+                    return None;
                 }
-            }
-        }
-        None
+
+                let span = if grouped { *declarator } else { node };
+                let code = span.utf8_text(source.as_bytes()).ok()?;
+                let doc = if i == 0 {
+                    preceding_comment
+                        .map(|c| self.clean_jsdoc(c))
+                        .or_else(|| self.find_leading_comment(node, source))
+                } else {
+                    self.find_leading_comment(*declarator, source)
+                };
+                let visibility = self.detect_visibility(node, name, source);
+                let (params, returns) = self.extract_params(value_node, source);
+
+                ChunkBuilder::new()
+                    .chunk_type(ChunkType::Function)
+                    .visibility(visibility)
+                    .name(name)
+                    .signature(code.lines().next().unwrap_or("").to_string())
+                    .params(params, returns)
+                    .code(code)
+                    .deprecation(self.detect_deprecation(doc.as_deref()))
+                    .documentation(doc.unwrap_or_default())
+                    .file_path(file_path)
+                    .location(
+                        span.start_position().row as u32 + 1,
+                        span.end_position().row as u32 + 1,
+                        span.start_byte(),
+                        span.end_byte(),
+                    )
+                    .build()
+            })
+            .collect()
     }
 
     fn get_child_text(&self, node: Node, field: &str, source: &str) -> Option<String> {
@@ -319,6 +475,36 @@ impl TypeScriptParser {
             .map(|s| s.to_string())
     }
 
+    /// Parameters (without a `this` annotation) and return type of a
+    /// function, method or arrow function. Untyped JavaScript parameters
+    /// have no type.
+    fn extract_params(&self, node: Node, source: &str) -> (Vec<Param>, Option<String>) {
+        // Type annotations include their leading `:`
+        let annotation = |node: Node, field: &str| {
+            self.get_child_text(node, field, source)
+                .map(|t| t.trim_start_matches(':').trim().to_string())
+        };
+
+        let mut params = Vec::new();
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                if !matches!(param.kind(), "required_parameter" | "optional_parameter") {
+                    continue;
+                }
+                if let Some(name) = self.get_child_text(param, "pattern", source)
+                    && name != "this"
+                {
+                    params.push((name, annotation(param, "type")));
+                }
+            }
+        } else if let Some(name) = self.get_child_text(node, "parameter", source) {
+            // An arrow function's lone unparenthesized parameter
+            params.push((name, None));
+        }
+        (params, annotation(node, "return_type"))
+    }
+
     fn extract_function_signature(&self, node: Node, source: &str) -> Option<String> {
         // Get everything up to and including the parameter list
         let _name = node.child_by_field_name("name")?;
@@ -338,6 +524,16 @@ impl TypeScriptParser {
     }
 
     fn find_leading_comment(&self, node: Node, source: &str) -> Option<String> {
+        // The comment of `export function`/`declare function` precedes the
+        // `export`/`declare` wrapper
+        let mut node = node;
+        while let Some(parent) = node
+            .parent()
+            .filter(|p| matches!(p.kind(), "export_statement" | "ambient_declaration"))
+        {
+            node = parent;
+        }
+
         // Look for comment node immediately before this node
         let mut prev = node.prev_sibling();
         while let Some(sibling) = prev {
@@ -374,13 +570,19 @@ impl TypeScriptParser {
             .join("\n")
     }
 
+    /// JSDoc marks deprecation with an `@deprecated` tag.
+    fn detect_deprecation(&self, doc: Option<&str>) -> Option<Deprecation> {
+        Deprecation::from_doc_tag(doc?, "@deprecated")
+    }
+
     /// Detect visibility for a node.
     ///
     /// TypeScript/JavaScript visibility rules:
     /// - `export` keyword = Public
     /// - `#privateName` = Private (ES private fields)
     /// - `_prefixedName` = Private (convention)
-    /// - No export = Internal (module-private)
+    /// - Inside `declare` = Public (ambient API, members are implicitly exported)
+    /// - No export = Internal (module-private, or namespace-private)
     fn detect_visibility(&self, node: Node, name: &str, source: &str) -> Visibility {
         // Check for # prefix (ES private)
         if name.starts_with('#') {
@@ -405,6 +607,10 @@ impl TypeScriptParser {
             }
         }
 
+        if self.in_declare_block(node) {
+            return Visibility::Public;
+        }
+
         // Check if there's an export keyword in the node text
         let node_text = node.utf8_text(source.as_bytes()).unwrap_or("");
         if node_text.trim().starts_with("export ") {
@@ -496,12 +702,44 @@ class Calculator {
         let chunks = parser.parse(source, "calc.ts").unwrap();
 
         // Should get class and method
-        assert!(chunks.len() >= 1);
+        assert!(!chunks.is_empty());
         let class_chunk = chunks.iter().find(|c| c.chunk_type == ChunkType::Class);
         assert!(class_chunk.is_some());
         assert_eq!(class_chunk.unwrap().name, "Calculator");
     }
 
+    #[test]
+    fn test_deprecated_jsdoc_tag() {
+        let parser = TypeScriptParser::new().unwrap();
+        let source = r#"
+class Client {
+    /**
+     * Fetches a resource.
+     * @deprecated Use {@link Client.request} instead.
+     * @param url The URL
+     */
+    fetch(url: string): Promise<Response> {
+        return this.request(url);
+    }
+
+    /** Sends a request. */
+    request(url: string): Promise<Response> {
+        return fetch(url);
+    }
+}
+"#;
+        let chunks = parser.parse(source, "client.ts").unwrap();
+
+        let fetch = chunks.iter().find(|c| c.name == "fetch").unwrap();
+        assert!(fetch.deprecated);
+        assert_eq!(
+            fetch.deprecation_message.as_deref(),
+            Some("Use {@link Client.request} instead.")
+        );
+        let request = chunks.iter().find(|c| c.name == "request").unwrap();
+        assert!(!request.deprecated);
+    }
+
     #[test]
     fn test_parse_arrow_function() {
         let parser = TypeScriptParser::new().unwrap();
@@ -516,6 +754,86 @@ const double = (x: number): number => x * 2;
         assert_eq!(chunks[0].chunk_type, ChunkType::Function);
     }
 
+    #[test]
+    fn test_params_and_return_type() {
+        let parser = TypeScriptParser::new().unwrap();
+        let source = r#"
+export async function handle(this: Server, req: Request, retries?: number): Promise<Response> {
+    return fetch(req);
+}
+
+class Router {
+    route(path: string, ...handlers: Handler[]): void {}
+}
+
+const parse = (input: string) => JSON.parse(input);
+"#;
+        let chunks = parser.parse(source, "server.ts").unwrap();
+
+        let handle = &chunks[0];
+        assert_eq!(
+            handle.params,
+            vec![
+                ("req".to_string(), Some("Request".to_string())),
+                ("retries".to_string(), Some("number".to_string())),
+            ]
+        );
+        assert_eq!(handle.returns.as_deref(), Some("Promise<Response>"));
+
+        let route = chunks.iter().find(|c| c.name == "route").unwrap();
+        assert_eq!(
+            route.params,
+            vec![
+                ("path".to_string(), Some("string".to_string())),
+                ("...handlers".to_string(), Some("Handler[]".to_string())),
+            ]
+        );
+        assert_eq!(route.returns.as_deref(), Some("void"));
+
+        let parse = chunks.iter().find(|c| c.name == "parse").unwrap();
+        assert_eq!(
+            parse.params,
+            vec![("input".to_string(), Some("string".to_string()))]
+        );
+        assert_eq!(parse.returns, None);
+
+        let js = TypeScriptParser::new_javascript().unwrap();
+        let chunks = js.parse("function send(url, body) {}", "send.js").unwrap();
+        assert_eq!(
+            chunks[0].params,
+            vec![("url".to_string(), None), ("body".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_multi_declarator_arrow_functions() {
+        let parser = TypeScriptParser::new().unwrap();
+        let source = r#"
+/** Increments a number */
+const inc = (x: number) => x + 1,
+  limit = 10,
+  /** Decrements a number */
+  dec = (x: number) => {
+    return x - 1;
+  };
+"#;
+        let chunks = parser.parse(source, "math.ts").unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        let (inc, dec) = (&chunks[0], &chunks[1]);
+        assert_eq!(inc.name, "inc");
+        assert_eq!((inc.start_line, inc.end_line), (3, 3));
+        assert_eq!(inc.code, "inc = (x: number) => x + 1");
+        assert_eq!(inc.documentation.as_deref(), Some("Increments a number"));
+        assert_eq!(inc.visibility, Visibility::Internal);
+
+        assert_eq!(dec.name, "dec");
+        assert_eq!((dec.start_line, dec.end_line), (6, 8));
+        assert_eq!(&source[dec.start_byte..dec.end_byte], dec.code);
+        assert_eq!(dec.documentation.as_deref(), Some("Decrements a number"));
+        assert_eq!(dec.visibility, Visibility::Internal);
+    }
+
     #[test]
     fn test_parse_interface() {
         let parser = TypeScriptParser::new().unwrap();
@@ -581,6 +899,135 @@ function greet(name) {
         assert_eq!(parser.language(), Language::JavaScript);
     }
 
+    #[test]
+    fn test_namespaced_function() {
+        let parser = TypeScriptParser::new().unwrap();
+        let source = r#"
+/** Validation helpers. */
+export namespace Validation {
+    /** Checks an email address. */
+    export function isEmail(s: string): boolean {
+        return s.includes("@");
+    }
+
+    function helper(): void {}
+
+    export namespace Strings {
+        export const trim = (s: string) => s.trim();
+    }
+
+    export class Validator {
+        check(): void {}
+    }
+}
+
+namespace Internal {
+    export function hidden(): void {}
+}
+"#;
+        let chunks = parser.parse(source, "validation.ts").unwrap();
+        let find = |name: &str| chunks.iter().find(|c| c.name == name).unwrap();
+
+        let namespace = find("Validation");
+        assert_eq!(namespace.chunk_type, ChunkType::Module);
+        assert_eq!(namespace.visibility, Visibility::Public);
+        assert_eq!(
+            namespace.signature.as_deref(),
+            Some("export namespace Validation")
+        );
+        assert_eq!(
+            namespace.documentation.as_deref(),
+            Some("Validation helpers.")
+        );
+        assert_eq!(namespace.parent, None);
+
+        let is_email = find("isEmail");
+        assert_eq!(is_email.chunk_type, ChunkType::Function);
+        assert_eq!(is_email.parent.as_deref(), Some("Validation"));
+        assert_eq!(is_email.visibility, Visibility::Public);
+        assert_eq!(
+            is_email.documentation.as_deref(),
+            Some("Checks an email address.")
+        );
+
+        let helper = find("helper");
+        assert_eq!(helper.parent.as_deref(), Some("Validation"));
+        assert_eq!(helper.visibility, Visibility::Internal);
+
+        assert_eq!(find("Strings").parent.as_deref(), Some("Validation"));
+        assert_eq!(find("trim").parent.as_deref(), Some("Validation.Strings"));
+        assert_eq!(find("Validator").parent.as_deref(), Some("Validation"));
+        // Methods belong to their class, not the namespace
+        assert_eq!(find("check").parent, None);
+
+        assert_eq!(find("Internal").visibility, Visibility::Internal);
+        assert_eq!(find("hidden").parent.as_deref(), Some("Internal"));
+    }
+
+    #[test]
+    fn test_ambient_declarations() {
+        let parser = TypeScriptParser::new().unwrap();
+        let source = r#"
+declare function greet(name: string): void;
+
+declare namespace Lib {
+    function parse(input: string): Lib.Ast;
+    interface Ast {
+        kind: string;
+    }
+}
+
+declare module "left-pad" {
+    export default function leftPad(s: string, n: number): string;
+}
+
+function overload(a: string): void;
+function overload(a: string | number): void {}
+"#;
+        let chunks = parser.parse(source, "index.ts").unwrap();
+        let find = |name: &str| chunks.iter().find(|c| c.name == name).unwrap();
+
+        let greet = find("greet");
+        assert_eq!(greet.chunk_type, ChunkType::Function);
+        assert_eq!(greet.visibility, Visibility::Public);
+        assert_eq!(
+            greet.signature.as_deref(),
+            Some("function greet(name: string): void")
+        );
+
+        let lib = find("Lib");
+        assert_eq!(lib.chunk_type, ChunkType::Module);
+        assert_eq!(lib.visibility, Visibility::Public);
+        assert_eq!(lib.signature.as_deref(), Some("declare namespace Lib"));
+
+        // Members of a `declare namespace` are implicitly exported
+        let parse = find("parse");
+        assert_eq!(parse.parent.as_deref(), Some("Lib"));
+        assert_eq!(parse.visibility, Visibility::Public);
+        let ast = find("Ast");
+        assert_eq!(ast.parent.as_deref(), Some("Lib"));
+        assert_eq!(ast.visibility, Visibility::Public);
+
+        assert_eq!(find("left-pad").chunk_type, ChunkType::Module);
+        assert_eq!(find("leftPad").parent.as_deref(), Some("left-pad"));
+
+        // Outside `declare`, a bodiless signature is just an overload
+        let overloads: Vec<_> = chunks.iter().filter(|c| c.name == "overload").collect();
+        assert_eq!(overloads.len(), 1);
+
+        // In a `.d.ts` file every signature is API
+        let chunks = parser
+            .parse(
+                "export function version(): string;
+",
+                "index.d.ts",
+            )
+            .unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].name, "version");
+        assert_eq!(chunks[0].visibility, Visibility::Public);
+    }
+
     #[test]
     fn test_visibility_exported_function() {
         let parser = TypeScriptParser::new().unwrap();
@@ -14,6 +14,8 @@ mod language;
 mod languages;
 pub mod workspace;
 
-pub use chunk::CodeChunk;
+pub use chunk::{
+    CodeChunk, Param, TraitImpl, doc_embedding_text, embedding_text, name_header, trim_snippet,
+};
 pub use language::Language;
 pub use languages::get_parser;
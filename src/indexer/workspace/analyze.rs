@@ -1,19 +1,30 @@
 //! Repository analysis - detects packages from files.
 
 use std::collections::HashSet;
+use std::path::Path;
 
 use crate::types::Registry;
 
-use super::{DetectedPackage, cargo, go, jvm, npm, patterns, python};
+use super::{DetectedPackage, cargo, dart, go, jvm, npm, patterns, python};
 use crate::indexer::Language;
 
+/// Manifests that list dependencies without naming a package. The package
+/// is named after its directory, and a declaring manifest next to one wins.
+const UNNAMED_MANIFESTS: &[&str] = &["requirements.txt"];
+
+/// Workspace files read alongside manifests by [`detect_workspaces`].
+const WORKSPACE_FILES: &[&str] = &["pnpm-workspace.yaml", "lerna.json", "go.work"];
+
+/// Most files [`read_repo_files`] lists, so a huge checkout can't stall it.
+const MAX_REPO_FILES: usize = 50_000;
+
 /// Analyze a repository to detect packages.
 pub fn analyze_repo(files: &[(String, String)]) -> Vec<DetectedPackage> {
     let manifests = collect_manifests(files);
     let workspace_members = detect_workspaces(files, &manifests);
     let has_workspace = !workspace_members.is_empty();
 
-    let mut packages = Vec::new();
+    let mut candidates = Vec::new();
 
     for (path, content, registry) in manifests {
         let root_path = parent_dir(path);
@@ -26,12 +37,37 @@ pub fn analyze_repo(files: &[(String, String)]) -> Vec<DetectedPackage> {
             continue;
         }
 
-        // Only include if there are source files for this registry
-        if !has_source_files(&root_path, registry, files) {
+        // Only include if there are source files (or docs) for this registry.
+        // A bare dependency list with only docs is likely a docs toolchain
+        // (e.g. docs/requirements.txt for Sphinx), not a package.
+        let has_source = has_source_files(&root_path, registry, files);
+        if !has_source && (is_unnamed(path) || !has_doc_files(&root_path, files)) {
+            continue;
+        }
+
+        candidates.push((root_path, path, content, registry, has_source));
+    }
+
+    // A docs-only match is ambiguous when a sibling manifest has real source
+    // files (e.g. package.json next to pyproject.toml with only .py files).
+    let sourced_roots: HashSet<String> = candidates
+        .iter()
+        .filter(|(_, _, _, _, has_source)| *has_source)
+        .map(|(root, _, _, _, _)| root.clone())
+        .collect();
+
+    let mut packages = Vec::new();
+
+    for (root_path, path, content, registry, has_source) in candidates {
+        if !has_source && sourced_roots.contains(&root_path) {
             continue;
         }
 
-        let name = parse_name(content, registry);
+        let name = if is_unnamed(path) {
+            dir_name(&root_path)
+        } else {
+            parse_name(path, content, registry)
+        };
         packages.push(DetectedPackage {
             registry,
             name,
@@ -43,10 +79,64 @@ pub fn analyze_repo(files: &[(String, String)]) -> Vec<DetectedPackage> {
     packages
 }
 
+/// Files of a checkout at `root`, as [`analyze_repo`] takes them: paths
+/// relative to `root`, with contents read for manifests and workspace files
+/// only. Hidden and skipped directories (tests, dependencies, build output)
+/// are not descended into.
+pub fn read_repo_files(root: &Path) -> std::io::Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                if !hidden && !patterns::should_skip_dir(&relative) {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let content = if manifest_registry(&relative).is_some()
+                || WORKSPACE_FILES.contains(&relative.as_str())
+            {
+                std::fs::read_to_string(&path).unwrap_or_default()
+            } else {
+                String::new()
+            };
+            files.push((relative, content));
+            if files.len() >= MAX_REPO_FILES {
+                return Ok(files);
+            }
+        }
+    }
+    Ok(files)
+}
+
 /// Check if a directory has source files matching the registry's languages.
 fn has_source_files(root_path: &str, registry: Registry, files: &[(String, String)]) -> bool {
     let langs = Language::from_registry(registry);
+    has_files_matching(root_path, files, |lang| langs.contains(&lang))
+}
+
+/// Check if a directory has documentation (README, docs/) worth indexing.
+fn has_doc_files(root_path: &str, files: &[(String, String)]) -> bool {
+    has_files_matching(root_path, files, |lang| lang.is_documentation())
+}
 
+fn has_files_matching(
+    root_path: &str,
+    files: &[(String, String)],
+    matches: impl Fn(Language) -> bool,
+) -> bool {
     files.iter().any(|(path, _)| {
         let in_subtree = if root_path.is_empty() {
             true
@@ -54,21 +144,22 @@ fn has_source_files(root_path: &str, registry: Registry, files: &[(String, Strin
             path.starts_with(root_path)
         };
 
-        in_subtree
-            && Language::from_path(path)
-                .map(|lang| langs.contains(&lang))
-                .unwrap_or(false)
+        in_subtree && Language::from_path(path).map(&matches).unwrap_or(false)
     })
 }
 
+/// Manifests in the repo, with unnamed ones last so that [`dedupe`] keeps
+/// a declaring manifest from the same directory.
 fn collect_manifests(files: &[(String, String)]) -> Vec<(&str, &str, Registry)> {
-    files
+    let mut manifests: Vec<_> = files
         .iter()
         .filter_map(|(path, content)| {
             let reg = manifest_registry(path)?;
             Some((path.as_str(), content.as_str(), reg))
         })
-        .collect()
+        .collect();
+    manifests.sort_by_key(|(path, _, _)| is_unnamed(path));
+    manifests
 }
 
 fn manifest_registry(path: &str) -> Option<Registry> {
@@ -76,9 +167,10 @@ fn manifest_registry(path: &str) -> Option<Registry> {
     match name {
         "package.json" => Some(Registry::Npm),
         "Cargo.toml" => Some(Registry::Crates),
-        "pyproject.toml" | "setup.py" => Some(Registry::Pypi),
+        "pyproject.toml" | "setup.cfg" | "setup.py" | "requirements.txt" => Some(Registry::Pypi),
         "go.mod" => Some(Registry::Go),
         "pom.xml" => Some(Registry::Maven),
+        "pubspec.yaml" => Some(Registry::Pub),
         _ => None,
     }
 }
@@ -100,6 +192,9 @@ fn detect_workspaces(
             Registry::Pypi => python::parse_workspace(content),
             Registry::Go => go::parse_workspace(content),
             Registry::Maven => jvm::parse_workspace(content),
+            Registry::Pub => dart::parse_workspace(content),
+            // No manifest declares a git package
+            Registry::Git => Vec::new(),
         };
         members.extend(m);
     }
@@ -139,16 +234,31 @@ fn is_member(path: &str, members: &HashSet<String>) -> bool {
     false
 }
 
-fn parse_name(content: &str, registry: Registry) -> Option<String> {
+fn is_unnamed(path: &str) -> bool {
+    UNNAMED_MANIFESTS.contains(&path.rsplit('/').next().unwrap_or(path))
+}
+
+fn parse_name(path: &str, content: &str, registry: Registry) -> Option<String> {
     match registry {
         Registry::Npm => npm::parse_name(content),
         Registry::Crates => cargo::parse_name(content),
+        Registry::Pypi if path.ends_with("setup.cfg") => python::parse_setup_cfg_name(content),
         Registry::Pypi => python::parse_name(content),
         Registry::Go => go::parse_name(content),
         Registry::Maven => jvm::parse_name(content),
+        Registry::Pub => dart::parse_name(content),
+        Registry::Git => None,
     }
 }
 
+/// Last component of a directory path, or `None` for the repo root.
+fn dir_name(dir: &str) -> Option<String> {
+    dir.rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+}
+
 fn parent_dir(path: &str) -> String {
     path.rfind('/')
         .map(|i| path[..i].to_string())
@@ -163,6 +273,8 @@ fn dedupe(packages: &mut Vec<DetectedPackage>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::indexer::get_parser;
+    use crate::types::ChunkType;
 
     #[test]
     fn test_simple_npm_package() {
@@ -215,6 +327,43 @@ mod tests {
         assert!(packages.iter().any(|p| p.name == Some("utils".to_string())));
     }
 
+    #[test]
+    fn test_read_repo_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("package.json", r#"{"workspaces": ["packages/*"]}"#);
+        write("packages/core/package.json", r#"{"name": "@acme/core"}"#);
+        write("packages/core/index.ts", "export {}");
+        write(
+            "node_modules/left-pad/package.json",
+            r#"{"name": "left-pad"}"#,
+        );
+        write("node_modules/left-pad/index.js", "module.exports = {}");
+        write(".git/config", "[core]");
+
+        let mut files = read_repo_files(dir.path()).unwrap();
+        files.sort();
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "package.json",
+                "packages/core/index.ts",
+                "packages/core/package.json"
+            ]
+        );
+        assert!(files[1].1.is_empty());
+        assert!(files[2].1.contains("@acme/core"));
+
+        let packages = analyze_repo(&files);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name.as_deref(), Some("@acme/core"));
+    }
+
     #[test]
     fn test_skip_test_dirs() {
         let files = vec![
@@ -275,4 +424,89 @@ mod tests {
         let packages = analyze_repo(&files);
         assert_eq!(packages.len(), 2);
     }
+
+    #[test]
+    fn test_docs_only_package_is_detected() {
+        let files = vec![
+            (
+                "package.json".to_string(),
+                r#"{"name": "docs-only"}"#.to_string(),
+            ),
+            (
+                "README.md".to_string(),
+                "# docs-only\n\n## Usage\n\n```js\nconst docs = require('docs-only');\n```\n"
+                    .to_string(),
+            ),
+        ];
+
+        let packages = analyze_repo(&files);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].registry, Registry::Npm);
+        assert_eq!(packages[0].name, Some("docs-only".to_string()));
+
+        // The README is parsed into a documentation chunk
+        let (path, content) = &files[1];
+        let parser = get_parser(Language::from_path(path).unwrap()).unwrap();
+        let chunks = parser.parse(content, path).unwrap();
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Documentation
+                    && c.code.contains("require('docs-only')"))
+        );
+    }
+
+    #[test]
+    fn test_docs_do_not_rescue_sibling_without_source() {
+        // README next to both manifests, but only Python source
+        let files = vec![
+            (
+                "lib/package.json".to_string(),
+                r#"{"name": "my-lib"}"#.to_string(),
+            ),
+            (
+                "lib/pyproject.toml".to_string(),
+                "[project]\nname = \"my-lib\"".to_string(),
+            ),
+            ("lib/README.md".to_string(), "# my-lib".to_string()),
+            ("lib/main.py".to_string(), "print('hello')".to_string()),
+        ];
+
+        let packages = analyze_repo(&files);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].registry, Registry::Pypi);
+    }
+
+    #[test]
+    fn test_requirements_only_python_project() {
+        let files = vec![
+            (
+                "services/scraper/requirements.txt".to_string(),
+                "requests==2.31.0\nbeautifulsoup4\n".to_string(),
+            ),
+            (
+                "services/scraper/scraper/main.py".to_string(),
+                "print('hello')".to_string(),
+            ),
+            // Sphinx docs toolchain, not a package
+            ("docs/requirements.txt".to_string(), "sphinx\n".to_string()),
+            ("docs/index.md".to_string(), "# Docs".to_string()),
+            // Dependency list next to a manifest that names the package
+            ("lib/requirements.txt".to_string(), "attrs\n".to_string()),
+            (
+                "lib/setup.cfg".to_string(),
+                "[metadata]\nname = my-lib\nversion = 1.0\n".to_string(),
+            ),
+            ("lib/my_lib/__init__.py".to_string(), String::new()),
+        ];
+
+        let mut packages = analyze_repo(&files);
+        packages.sort_by(|a, b| a.root_path.cmp(&b.root_path));
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].registry, Registry::Pypi);
+        assert_eq!(packages[0].root_path, "lib");
+        assert_eq!(packages[0].name, Some("my-lib".to_string()));
+        assert_eq!(packages[1].root_path, "services/scraper");
+        assert_eq!(packages[1].name, Some("scraper".to_string()));
+    }
 }
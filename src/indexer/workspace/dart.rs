@@ -0,0 +1,48 @@
+//! Dart pub workspace parsing (pubspec.yaml).
+
+use serde_yaml::Value;
+
+/// Parse workspace members from pubspec.yaml (`workspace:` list, Dart 3.6+).
+pub fn parse_workspace(content: &str) -> Vec<String> {
+    let yaml: Value = match serde_yaml::from_str(content) {
+        Ok(v) => v,
+        Err(_) => return vec![],
+    };
+
+    yaml.get("workspace")
+        .and_then(|w| w.as_sequence())
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|m| m.as_str())
+                .map(|m| m.trim_start_matches("./").to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse package name from pubspec.yaml.
+pub fn parse_name(content: &str) -> Option<String> {
+    let yaml: Value = serde_yaml::from_str(content).ok()?;
+    yaml.get("name")?.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pub_workspace() {
+        let content = "name: _\nworkspace:\n  - packages/app\n  - ./packages/core\n";
+        assert_eq!(
+            parse_workspace(content),
+            vec!["packages/app", "packages/core"]
+        );
+    }
+
+    #[test]
+    fn test_pubspec_name() {
+        let content = "name: my_app\nversion: 1.0.0\n";
+        assert_eq!(parse_name(content), Some("my_app".to_string()));
+    }
+}
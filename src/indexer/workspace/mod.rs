@@ -2,6 +2,7 @@
 
 mod analyze;
 mod cargo;
+mod dart;
 mod go;
 mod jvm;
 mod npm;
@@ -9,4 +10,5 @@ mod patterns;
 mod python;
 mod types;
 
+pub use analyze::{analyze_repo, read_repo_files};
 pub use types::DetectedPackage;
@@ -56,6 +56,27 @@ pub fn parse_name(content: &str) -> Option<String> {
     None
 }
 
+/// Parse package name from setup.cfg's `[metadata]` section.
+pub fn parse_setup_cfg_name(content: &str) -> Option<String> {
+    let mut in_metadata = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_metadata = line == "[metadata]";
+            continue;
+        }
+        if !in_metadata {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(['=', ':'])
+            && key.trim() == "name"
+        {
+            let name = value.trim();
+            return (!name.is_empty()).then(|| name.to_string());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +100,15 @@ version = "1.0.0"
 "#;
         assert_eq!(parse_name(content), Some("my-poetry-pkg".to_string()));
     }
+
+    #[test]
+    fn test_setup_cfg_name() {
+        let content =
+            "[options]\nname = not-this\n\n[metadata]\nversion = 1.0\nname = my-cfg-pkg\n";
+        assert_eq!(
+            parse_setup_cfg_name(content),
+            Some("my-cfg-pkg".to_string())
+        );
+        assert_eq!(parse_setup_cfg_name("[options]\nzip_safe = False\n"), None);
+    }
 }
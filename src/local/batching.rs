@@ -0,0 +1,238 @@
+//! Packing embedding inputs into API requests.
+//!
+//! Providers cap both the number of inputs and the tokens per request, so a
+//! fixed batch of large chunks can be rejected whole. Texts are packed until
+//! either limit is reached, and a text too large for one input is split into
+//! pieces whose embeddings are averaged back into one.
+
+use std::ops::Range;
+
+/// Default inputs per embeddings request.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Default estimated tokens per embeddings request (OpenAI allows 300k).
+pub const DEFAULT_BATCH_TOKENS: usize = 200_000;
+
+/// Estimated tokens a single input may have (OpenAI models accept 8191).
+pub const MAX_TEXT_TOKENS: usize = 8000;
+
+/// Conservative token estimate: code averages fewer characters per token
+/// than prose, so count one token per three characters.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(3)
+}
+
+/// A piece of an input text, sent as one embeddings input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    /// Index of the text this piece belongs to.
+    pub text: usize,
+    pub content: String,
+    pub tokens: usize,
+}
+
+/// Split `texts` into pieces of at most `max_tokens` estimated tokens each,
+/// in order. Texts are cut on line boundaries where possible.
+pub fn split_texts(texts: &[String], max_tokens: usize) -> Vec<Piece> {
+    let mut pieces = Vec::with_capacity(texts.len());
+    for (index, text) in texts.iter().enumerate() {
+        for content in split_text(text, max_tokens) {
+            pieces.push(Piece {
+                text: index,
+                tokens: estimate_tokens(&content),
+                content,
+            });
+        }
+    }
+    pieces
+}
+
+fn split_text(text: &str, max_tokens: usize) -> Vec<String> {
+    let max_tokens = max_tokens.max(1);
+    if estimate_tokens(text) <= max_tokens {
+        return vec![text.to_string()];
+    }
+
+    let max_chars = max_tokens * 3;
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_chars = line.chars().count();
+        if current_chars + line_chars > max_chars && !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if line_chars <= max_chars {
+            current.push_str(line);
+            current_chars += line_chars;
+            continue;
+        }
+        // A single line over the limit is cut at character boundaries
+        let chars: Vec<char> = line.chars().collect();
+        for part in chars.chunks(max_chars) {
+            pieces.push(part.iter().collect());
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Group consecutive pieces into batches of at most `max_texts` pieces and
+/// `max_tokens` estimated tokens. A piece over the token budget on its own
+/// still gets a batch of its own.
+pub fn pack(pieces: &[Piece], max_texts: usize, max_tokens: usize) -> Vec<Range<usize>> {
+    let max_texts = max_texts.max(1);
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+
+    for (i, piece) in pieces.iter().enumerate() {
+        let full = i - start >= max_texts || tokens + piece.tokens > max_tokens;
+        if full && i > start {
+            batches.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += piece.tokens;
+    }
+    if start < pieces.len() {
+        batches.push(start..pieces.len());
+    }
+    batches
+}
+
+/// Combine piece embeddings into one embedding per text, averaging the
+/// pieces of split texts weighted by their size and renormalizing.
+pub fn combine(pieces: &[Piece], embeddings: Vec<Vec<f32>>, texts: usize) -> Vec<Vec<f32>> {
+    let mut parts: Vec<Vec<(usize, Vec<f32>)>> = vec![Vec::new(); texts];
+    for (piece, embedding) in pieces.iter().zip(embeddings) {
+        parts[piece.text].push((piece.tokens, embedding));
+    }
+
+    parts
+        .into_iter()
+        .map(|mut parts| {
+            if parts.len() == 1 {
+                return parts
+                    .pop()
+                    .map(|(_, embedding)| embedding)
+                    .unwrap_or_default();
+            }
+            let dimensions = parts.first().map_or(0, |(_, e)| e.len());
+            let mut sum = vec![0.0f32; dimensions];
+            for (tokens, embedding) in &parts {
+                for (total, value) in sum.iter_mut().zip(embedding) {
+                    *total += value * *tokens as f32;
+                }
+            }
+            let norm = sum.iter().map(|v| v * v).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                sum.iter_mut().for_each(|v| *v /= norm);
+            }
+            sum
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(chars: usize) -> String {
+        "x".repeat(chars)
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 2);
+    }
+
+    #[test]
+    fn test_pack_by_count() {
+        let texts: Vec<_> = (0..5).map(|_| text(3)).collect();
+        let pieces = split_texts(&texts, MAX_TEXT_TOKENS);
+        assert_eq!(pack(&pieces, 2, 1000), vec![0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn test_pack_mixed_sizes_by_tokens() {
+        // 10, 90, 5, 50, 60 estimated tokens
+        let texts = vec![text(30), text(270), text(15), text(150), text(180)];
+        let pieces = split_texts(&texts, MAX_TEXT_TOKENS);
+        assert_eq!(pieces.len(), 5);
+
+        let batches = pack(&pieces, 100, 100);
+        assert_eq!(batches, vec![0..2, 2..4, 4..5]);
+        for batch in &batches {
+            let tokens: usize = pieces[batch.clone()].iter().map(|p| p.tokens).sum();
+            assert!(tokens <= 100);
+        }
+    }
+
+    #[test]
+    fn test_pack_oversized_piece_alone() {
+        let pieces = vec![
+            Piece {
+                text: 0,
+                content: String::new(),
+                tokens: 10,
+            },
+            Piece {
+                text: 1,
+                content: String::new(),
+                tokens: 500,
+            },
+            Piece {
+                text: 2,
+                content: String::new(),
+                tokens: 10,
+            },
+        ];
+        assert_eq!(pack(&pieces, 100, 100), vec![0..1, 1..2, 2..3]);
+        assert!(pack(&[], 100, 100).is_empty());
+    }
+
+    #[test]
+    fn test_split_oversized_text_on_lines() {
+        let line = format!("{}\n", text(29));
+        let big = line.repeat(10);
+        let texts = vec![text(6), big.clone(), text(6)];
+
+        let pieces = split_texts(&texts, 25);
+        let owners: Vec<_> = pieces.iter().map(|p| p.text).collect();
+        assert_eq!(owners, vec![0, 1, 1, 1, 1, 1, 2]);
+        assert!(pieces.iter().all(|p| p.tokens <= 25));
+        assert!(pieces[1..6].iter().all(|p| p.content.ends_with('\n')));
+
+        let rejoined: String = pieces[1..6].iter().map(|p| p.content.as_str()).collect();
+        assert_eq!(rejoined, big);
+    }
+
+    #[test]
+    fn test_split_long_line_on_char_boundaries() {
+        let texts = vec!["é".repeat(10)];
+        let pieces = split_texts(&texts, 1);
+        assert_eq!(pieces.len(), 4);
+        assert!(pieces.iter().all(|p| p.content.chars().count() <= 3));
+    }
+
+    #[test]
+    fn test_combine_averages_split_texts() {
+        let texts = vec![text(3), format!("{}\n{}", text(5), text(5))];
+        let pieces = split_texts(&texts, 2);
+        assert_eq!(pieces.len(), 3);
+
+        let embeddings = vec![vec![0.5, 0.5], vec![1.0, 0.0], vec![0.0, 1.0]];
+        let combined = combine(&pieces, embeddings, texts.len());
+        assert_eq!(combined.len(), 2);
+        assert_eq!(combined[0], vec![0.5, 0.5]);
+        let expected = 1.0 / 2.0f32.sqrt();
+        assert!(combined[1].iter().all(|v| (v - expected).abs() < 1e-6));
+    }
+}
@@ -2,9 +2,24 @@
 //!
 //! Config is stored at `~/.config/idx/config.toml` and contains:
 //! - OpenAI API key for embeddings
+//! - Storage options for the local index
+//! - Per-package indexing caps
+//! - Optional npm dependency sections
+//! - License denylist
+//! - SQLite read pool size
+//! - Vector backend selection (LanceDB or Turbopuffer)
+//! - Registry response cache limits
+//! - HTTP proxy and custom CA bundle
+//! - Named embedding profiles
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use super::models::DistanceMetric;
+use crate::manifests::NpmOptions;
+use crate::registry::{self, HttpOptions, RegistryCache, RegistryLimits};
+use crate::types::Registry;
 use anyhow::{Context, Result};
 use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
@@ -12,6 +27,40 @@ use serde::{Deserialize, Serialize};
 const CONFIG_DIR: &str = "idx";
 const CONFIG_FILE: &str = "config.toml";
 
+/// Profile name selecting the top-level embedding settings.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Secrets that can be given in the environment instead of the config
+/// file, e.g. by CI. A set variable takes precedence over the file, which
+/// takes precedence over the default (none).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvSecret {
+    /// The embeddings API key, for whichever profile is active.
+    OpenAiApiKey,
+    TurbopufferApiKey,
+}
+
+impl EnvSecret {
+    pub const ALL: [EnvSecret; 2] = [EnvSecret::OpenAiApiKey, EnvSecret::TurbopufferApiKey];
+
+    /// Environment variable holding the secret.
+    pub fn var(self) -> &'static str {
+        match self {
+            EnvSecret::OpenAiApiKey => "IDX_OPENAI_API_KEY",
+            EnvSecret::TurbopufferApiKey => "IDX_TURBOPUFFER_API_KEY",
+        }
+    }
+}
+
+/// A secret taken from the environment, along with the config file's
+/// value it replaced.
+#[derive(Debug, Clone)]
+pub(crate) struct EnvOverride {
+    secret: EnvSecret,
+    value: String,
+    file: Option<String>,
+}
+
 /// Local configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalConfig {
@@ -26,6 +75,256 @@ pub struct LocalConfig {
     /// Model to use for embeddings (default: text-embedding-3-small).
     #[serde(default = "default_embedding_model")]
     pub embedding_model: String,
+
+    /// Dimension of the model's embeddings (default: 1536). An index only
+    /// holds embeddings of one dimension.
+    #[serde(default = "default_embedding_dimensions")]
+    pub embedding_dimensions: usize,
+
+    /// Texts sent per embeddings request (default: 100).
+    #[serde(default = "default_embedding_batch_size")]
+    pub embedding_batch_size: usize,
+
+    /// Estimated tokens sent per embeddings request (default: 200000).
+    /// Larger texts are split and their embeddings averaged.
+    #[serde(default = "default_embedding_batch_tokens")]
+    pub embedding_batch_tokens: usize,
+
+    /// Also embed each documented chunk's documentation and signature on
+    /// their own, which natural-language queries match better (default:
+    /// false, up to doubles embedding cost). Searches then compare with
+    /// both embeddings unless a query mode is given.
+    #[serde(default)]
+    pub doc_embeddings: bool,
+
+    /// Experimental: lead each chunk's embedding text with its name this
+    /// many times (and its enclosing type once), biasing embeddings toward
+    /// identifiers for short name queries (default: 0, off). Embeddings
+    /// made with different weights are never reused for one another.
+    #[serde(default)]
+    pub embedding_name_weight: usize,
+
+    /// Price in USD per 1000 embedded tokens, used to estimate what a run
+    /// cost (default: 0.00002, text-embedding-3-small's price).
+    #[serde(default = "default_embedding_price_per_1k_tokens")]
+    pub embedding_price_per_1k_tokens: f64,
+
+    /// Store whole source files alongside chunks so context around a match
+    /// can be retrieved (default: false, increases storage).
+    #[serde(default)]
+    pub store_source_files: bool,
+
+    /// zstd level code blobs are compressed with (default: 3, 0 = store
+    /// uncompressed). Already stored blobs keep the level they were
+    /// written with.
+    #[serde(default = "default_blob_compression_level")]
+    pub blob_compression_level: i32,
+
+    /// Also index package test files, tagged as `example` chunks, since tests
+    /// are often the best usage examples (default: false).
+    #[serde(default)]
+    pub index_tests: bool,
+
+    /// Also index third-party code vendored inside packages, such as
+    /// `pip/_vendor/` (default: false).
+    #[serde(default)]
+    pub index_vendored: bool,
+
+    /// Also index npm `peerDependencies` (default: false).
+    #[serde(default)]
+    pub npm_include_peer: bool,
+
+    /// Also index npm `optionalDependencies` (default: false).
+    #[serde(default)]
+    pub npm_include_optional: bool,
+
+    /// Minimum score a search hit needs to be returned (default: 0, every
+    /// hit). With a threshold, off-topic queries report "no confident
+    /// matches" instead of returning the least bad chunks.
+    #[serde(default)]
+    pub min_score: f32,
+
+    /// Only return public API from `idx search` and the MCP `search_code`
+    /// tool unless asked otherwise (default: true). See
+    /// [`crate::local::models::ChunkFilter::with_public_only`].
+    #[serde(default = "default_public_only")]
+    pub public_only: bool,
+
+    /// Bytes of each chunk's code kept as its search result snippet
+    /// (default: 500). Searches can ask for shorter snippets; longer ones
+    /// apply to packages indexed after the change.
+    #[serde(default = "default_snippet_length")]
+    pub snippet_length: usize,
+
+    /// Built-in chunk transformers run on every parsed chunk before it is
+    /// embedded, in order, e.g. `["strip-license-headers"]` (default: none).
+    /// See [`super::transform`].
+    #[serde(default)]
+    pub chunk_transformers: Vec<String>,
+
+    /// SPDX licenses of packages not to index, e.g. `["GPL-*", "AGPL-*"]`
+    /// (default: none). See [`super::license::is_denied`] for matching.
+    #[serde(default)]
+    pub license_denylist: Vec<String>,
+
+    /// What to do with packages whose license is denylisted (default: skip).
+    #[serde(default)]
+    pub license_action: LicenseAction,
+
+    /// Maximum source files parsed per package (default: 5000, 0 = unlimited).
+    #[serde(default = "default_max_files_per_package")]
+    pub max_files_per_package: usize,
+
+    /// Maximum chunks embedded per package (default: 20000, 0 = unlimited).
+    #[serde(default = "default_max_chunks_per_package")]
+    pub max_chunks_per_package: usize,
+
+    /// Newest versions of each package kept by `idx update`, which removes
+    /// older ones no manifest references (default: 0, keep all).
+    #[serde(default)]
+    pub max_versions_per_package: usize,
+
+    /// Pooled SQLite read connections, so searches run alongside indexing
+    /// (default: 4). Writes always go through a single connection.
+    #[serde(default = "default_db_read_connections")]
+    pub db_read_connections: u32,
+
+    /// Where embeddings are stored (default: lance).
+    #[serde(default)]
+    pub vector_backend: VectorBackendKind,
+
+    /// How embedding distances are measured: l2, cosine or dot (default:
+    /// l2). Turbopuffer fixes it when a namespace is written, so existing
+    /// packages must be re-indexed after changing it there.
+    #[serde(default)]
+    pub distance_metric: DistanceMetric,
+
+    /// Turbopuffer API key (required for the turbopuffer backend).
+    #[serde(default)]
+    pub turbopuffer_api_key: Option<String>,
+
+    /// Turbopuffer API base URL (default: https://api.turbopuffer.com).
+    #[serde(default = "default_turbopuffer_base_url")]
+    pub turbopuffer_base_url: String,
+
+    /// Prefix for Turbopuffer namespace names (default: idx).
+    #[serde(default = "default_turbopuffer_namespace_prefix")]
+    pub turbopuffer_namespace_prefix: String,
+
+    /// Hours before cached registry metadata is refetched (default: 24,
+    /// 0 = disable the cache).
+    #[serde(default = "default_registry_cache_ttl_hours")]
+    pub registry_cache_ttl_hours: u64,
+
+    /// Size cap for `.index/cache/` in MB; least recently used entries are
+    /// evicted past it (default: 512).
+    #[serde(default = "default_registry_cache_max_mb")]
+    pub registry_cache_max_mb: u64,
+
+    /// Also cache downloaded package sources, not just metadata
+    /// (default: false, increases disk usage).
+    #[serde(default)]
+    pub registry_cache_sources: bool,
+
+    /// Requests sent to each registry at once, e.g. `{ npm = 16, maven = 2 }`
+    /// (default: per registry, see [`registry::default_concurrency`]). A
+    /// rate-limited registry backs off without slowing the others.
+    #[serde(default)]
+    pub registry_concurrency: BTreeMap<Registry, usize>,
+
+    /// Proxy URL for registry and embedding requests (default: none, honor
+    /// `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`).
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+
+    /// Comma-separated hosts reached without `http_proxy` (default: none,
+    /// use `NO_PROXY`).
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+
+    /// PEM file of extra root certificates to trust, e.g. the CA of a
+    /// TLS-intercepting corporate proxy.
+    #[serde(default)]
+    pub ca_bundle: Option<PathBuf>,
+
+    /// Profile whose embedding settings replace the top-level ones
+    /// (default: none, use the top-level settings).
+    #[serde(default)]
+    pub active_profile: Option<String>,
+
+    /// Named embedding profiles.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, EmbeddingProfile>,
+
+    /// Offline mode, from `--offline` / `IDX_OFFLINE` rather than the file.
+    #[serde(skip)]
+    pub offline: bool,
+
+    /// Secrets overridden from the environment (see [`EnvSecret`]), kept so
+    /// [`Self::save`] writes back the file's values instead.
+    #[serde(skip)]
+    pub(crate) env_overrides: Vec<EnvOverride>,
+}
+
+/// Named set of embedding settings, selected with `idx config use-profile`
+/// or `--profile`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingProfile {
+    /// API key for embeddings.
+    #[serde(default)]
+    pub openai_api_key: Option<String>,
+
+    /// Base URL for the OpenAI-compatible API.
+    #[serde(default = "default_openai_base_url")]
+    pub openai_base_url: String,
+
+    /// Model to use for embeddings.
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+
+    /// Dimension of the model's embeddings.
+    #[serde(default = "default_embedding_dimensions")]
+    pub embedding_dimensions: usize,
+}
+
+/// Vector storage backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorBackendKind {
+    /// Embedded LanceDB under `.index/vectors/`
+    #[default]
+    Lance,
+    /// Hosted Turbopuffer namespaces
+    Turbopuffer,
+}
+
+/// Handling of packages with a denylisted license.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseAction {
+    /// Don't download or index them; mark the version skipped
+    #[default]
+    Skip,
+    /// Index them but report the license
+    Warn,
+}
+
+impl std::fmt::Display for LicenseAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Skip => write!(f, "skip"),
+            Self::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+impl std::fmt::Display for VectorBackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lance => write!(f, "lance"),
+            Self::Turbopuffer => write!(f, "turbopuffer"),
+        }
+    }
 }
 
 fn default_openai_base_url() -> String {
@@ -36,28 +335,184 @@ fn default_embedding_model() -> String {
     "text-embedding-3-small".to_string()
 }
 
+fn default_embedding_dimensions() -> usize {
+    super::models::VECTOR_DIM as usize
+}
+
+fn default_embedding_batch_size() -> usize {
+    super::batching::DEFAULT_BATCH_SIZE
+}
+
+fn default_embedding_batch_tokens() -> usize {
+    super::batching::DEFAULT_BATCH_TOKENS
+}
+
+fn default_embedding_price_per_1k_tokens() -> f64 {
+    0.00002
+}
+
+fn default_blob_compression_level() -> i32 {
+    super::storage::DEFAULT_COMPRESSION_LEVEL
+}
+
+fn default_public_only() -> bool {
+    true
+}
+
+fn default_snippet_length() -> usize {
+    500
+}
+
+fn default_max_files_per_package() -> usize {
+    5000
+}
+
+fn default_max_chunks_per_package() -> usize {
+    20000
+}
+
+fn default_db_read_connections() -> u32 {
+    super::db::DEFAULT_READ_CONNECTIONS
+}
+
+fn default_turbopuffer_base_url() -> String {
+    "https://api.turbopuffer.com".to_string()
+}
+
+fn default_turbopuffer_namespace_prefix() -> String {
+    "idx".to_string()
+}
+
+fn default_registry_cache_ttl_hours() -> u64 {
+    crate::registry::DEFAULT_CACHE_TTL_HOURS
+}
+
+fn default_registry_cache_max_mb() -> u64 {
+    crate::registry::DEFAULT_CACHE_MAX_MB
+}
+
+/// Whether `url` points at this machine (`localhost` or a loopback address).
+fn is_loopback_url(url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
 impl Default for LocalConfig {
     fn default() -> Self {
         Self {
             openai_api_key: None,
             openai_base_url: default_openai_base_url(),
             embedding_model: default_embedding_model(),
+            embedding_dimensions: default_embedding_dimensions(),
+            embedding_batch_size: default_embedding_batch_size(),
+            embedding_batch_tokens: default_embedding_batch_tokens(),
+            doc_embeddings: false,
+            embedding_name_weight: 0,
+            embedding_price_per_1k_tokens: default_embedding_price_per_1k_tokens(),
+            store_source_files: false,
+            blob_compression_level: default_blob_compression_level(),
+            index_tests: false,
+            index_vendored: false,
+            npm_include_peer: false,
+            npm_include_optional: false,
+            min_score: 0.0,
+            public_only: default_public_only(),
+            snippet_length: default_snippet_length(),
+            chunk_transformers: Vec::new(),
+            license_denylist: Vec::new(),
+            license_action: LicenseAction::default(),
+            max_files_per_package: default_max_files_per_package(),
+            max_chunks_per_package: default_max_chunks_per_package(),
+            max_versions_per_package: 0,
+            db_read_connections: default_db_read_connections(),
+            vector_backend: VectorBackendKind::default(),
+            distance_metric: DistanceMetric::default(),
+            turbopuffer_api_key: None,
+            turbopuffer_base_url: default_turbopuffer_base_url(),
+            turbopuffer_namespace_prefix: default_turbopuffer_namespace_prefix(),
+            registry_cache_ttl_hours: default_registry_cache_ttl_hours(),
+            registry_cache_max_mb: default_registry_cache_max_mb(),
+            registry_cache_sources: false,
+            registry_concurrency: BTreeMap::new(),
+            http_proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+            active_profile: None,
+            profiles: BTreeMap::new(),
+            offline: super::is_offline(),
+            env_overrides: Vec::new(),
         }
     }
 }
 
 impl LocalConfig {
-    /// Load config from the default location.
+    /// Load config from the default location, with secrets set in the
+    /// environment taking precedence (see [`EnvSecret`]).
     pub fn load() -> Result<Self> {
         let path = Self::config_path()?;
 
-        if !path.exists() {
-            return Ok(Self::default());
+        let config = if path.exists() {
+            let content = std::fs::read_to_string(&path).context("Failed to read config file")?;
+            let config: Self = toml::from_str(&content).context("Failed to parse config file")?;
+            Self {
+                offline: super::is_offline(),
+                ..config
+            }
+        } else {
+            Self::default()
+        };
+
+        Ok(config.with_env(|var| std::env::var(var).ok()))
+    }
+
+    /// Override secrets with the values `lookup` finds for their
+    /// environment variables. Empty values are ignored.
+    pub fn with_env(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        for secret in EnvSecret::ALL {
+            let Some(value) = lookup(secret.var()).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            let file = self.secret_mut(secret).replace(value.clone());
+            self.env_overrides.push(EnvOverride {
+                secret,
+                value,
+                file,
+            });
         }
+        self
+    }
 
-        let content = std::fs::read_to_string(&path).context("Failed to read config file")?;
+    /// Whether `secret` comes from its environment variable.
+    pub fn is_from_env(&self, secret: EnvSecret) -> bool {
+        self.env_overrides.iter().any(|o| o.secret == secret)
+    }
 
-        toml::from_str(&content).context("Failed to parse config file")
+    fn secret_mut(&mut self, secret: EnvSecret) -> &mut Option<String> {
+        match secret {
+            EnvSecret::OpenAiApiKey => &mut self.openai_api_key,
+            EnvSecret::TurbopufferApiKey => &mut self.turbopuffer_api_key,
+        }
+    }
+
+    /// The config as written to the file: secrets still holding their
+    /// environment value are swapped back for the file's, so they never
+    /// end up on disk.
+    fn file_contents(&self) -> Result<String> {
+        let mut config = self.clone();
+        for o in &self.env_overrides {
+            let field = config.secret_mut(o.secret);
+            if field.as_deref() == Some(o.value.as_str()) {
+                *field = o.file.clone();
+            }
+        }
+        toml::to_string_pretty(&config).context("Failed to serialize config")
     }
 
     /// Save config to the default location.
@@ -68,7 +523,7 @@ impl LocalConfig {
             std::fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
-        let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        let content = self.file_contents()?;
 
         std::fs::write(&path, content).context("Failed to write config file")
     }
@@ -78,6 +533,19 @@ impl LocalConfig {
         self.openai_api_key.clone().map(SecretString::from)
     }
 
+    /// The embeddings API key, or an error saying how to configure one.
+    pub fn require_openai_key(&self) -> Result<&str> {
+        self.openai_api_key
+            .as_deref()
+            .filter(|k| !k.is_empty())
+            .with_context(|| {
+                format!(
+                    "OpenAI API key not configured. Run: idx config set-key, or set {}",
+                    EnvSecret::OpenAiApiKey.var()
+                )
+            })
+    }
+
     /// Check if the config has a valid OpenAI API key.
     pub fn has_openai_key(&self) -> bool {
         self.openai_api_key
@@ -91,6 +559,161 @@ impl LocalConfig {
         self.openai_api_key = Some(key);
     }
 
+    /// Top-level embedding settings, as a profile.
+    pub fn embedding_profile(&self) -> EmbeddingProfile {
+        EmbeddingProfile {
+            openai_api_key: self.openai_api_key.clone(),
+            openai_base_url: self.openai_base_url.clone(),
+            embedding_model: self.embedding_model.clone(),
+            embedding_dimensions: self.embedding_dimensions,
+        }
+    }
+
+    /// Add or replace a named profile.
+    pub fn add_profile(&mut self, name: &str, profile: EmbeddingProfile) -> Result<()> {
+        if name == DEFAULT_PROFILE {
+            anyhow::bail!(
+                "'{}' is reserved for the top-level settings",
+                DEFAULT_PROFILE
+            );
+        }
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Make `name` the active profile (`default` for the top-level settings).
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        if name == DEFAULT_PROFILE {
+            self.active_profile = None;
+        } else {
+            self.profile(name)?;
+            self.active_profile = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Look up a named profile.
+    pub fn profile(&self, name: &str) -> Result<&EmbeddingProfile> {
+        self.profiles.get(name).with_context(|| {
+            let known: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            format!(
+                "Unknown profile '{}' (known: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })
+    }
+
+    /// Name of the profile in effect for `profile`, or the active one.
+    pub fn profile_name<'a>(&'a self, profile: Option<&'a str>) -> &'a str {
+        profile
+            .or(self.active_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Apply the embedding settings of `profile`, or of the active profile
+    /// when `None`, to the top-level fields.
+    ///
+    /// `active_profile` is set to the applied profile. The result is for a
+    /// single run; don't [`save`](Self::save) it.
+    pub fn with_profile(mut self, profile: Option<&str>) -> Result<Self> {
+        let name = self.profile_name(profile).to_string();
+        if name == DEFAULT_PROFILE {
+            self.active_profile = None;
+        } else {
+            let profile = self.profile(&name)?.clone();
+            self.openai_api_key = self
+                .env_overrides
+                .iter()
+                .find(|o| o.secret == EnvSecret::OpenAiApiKey)
+                .map(|o| o.value.clone())
+                .or(profile.openai_api_key);
+            self.openai_base_url = profile.openai_base_url;
+            self.embedding_model = profile.embedding_model;
+            self.embedding_dimensions = profile.embedding_dimensions;
+            self.active_profile = Some(name);
+        }
+        Ok(self)
+    }
+
+    /// Fail if an embedding returned by the API doesn't have the configured
+    /// dimension, rather than storing vectors the index can't compare.
+    pub fn check_embedding(&self, embedding: &[f32]) -> Result<()> {
+        if embedding.len() != self.embedding_dimensions {
+            anyhow::bail!(
+                "Model '{}' returned {}-dimensional embeddings, but profile '{}' expects {}. \
+                 Set the profile's dimensions to match.",
+                self.embedding_model,
+                embedding.len(),
+                self.profile_name(None),
+                self.embedding_dimensions
+            );
+        }
+        Ok(())
+    }
+
+    /// Registry cache under `cache_dir` with the configured limits.
+    ///
+    /// Returns `None` when the cache is disabled (TTL of 0).
+    pub fn registry_cache(&self, cache_dir: PathBuf) -> Option<RegistryCache> {
+        (self.registry_cache_ttl_hours > 0).then(|| {
+            RegistryCache::new(cache_dir)
+                .with_ttl(Duration::from_secs(self.registry_cache_ttl_hours * 3600))
+                .with_max_bytes(self.registry_cache_max_mb * 1024 * 1024)
+                .with_sources(self.registry_cache_sources)
+        })
+    }
+
+    /// Per-registry request limits, to be shared by every registry client.
+    pub fn registry_limits(&self) -> RegistryLimits {
+        RegistryLimits::new(&self.registry_concurrency)
+    }
+
+    /// Proxy and CA settings for outgoing HTTP requests.
+    pub fn http_options(&self) -> HttpOptions {
+        HttpOptions {
+            proxy: self.http_proxy.clone(),
+            no_proxy: self.no_proxy.clone(),
+            ca_bundle: self.ca_bundle.clone(),
+        }
+    }
+
+    /// HTTP client with the configured proxy and CA settings.
+    pub fn http_client(&self) -> Result<reqwest::Client> {
+        registry::http_client(&self.http_options())
+            .context("Invalid proxy settings. Run: idx config set-proxy")
+    }
+
+    /// Fail in offline mode unless the embeddings API runs on this machine
+    /// (e.g. a local OpenAI-compatible server).
+    pub fn check_offline_embeddings(&self) -> Result<()> {
+        if self.offline && !is_loopback_url(&self.openai_base_url) {
+            anyhow::bail!(
+                "offline mode: the embeddings API at {} is not on this machine \
+                 (point `idx config set-url` at a local server)",
+                self.openai_base_url
+            );
+        }
+        Ok(())
+    }
+
+    /// npm dependency sections enabled in config.
+    pub fn npm_options(&self) -> NpmOptions {
+        NpmOptions {
+            include_peer: self.npm_include_peer,
+            include_optional: self.npm_include_optional,
+        }
+    }
+
+    /// Whether a package `license` (an SPDX expression) is denylisted.
+    pub fn denies_license(&self, license: &str) -> bool {
+        super::license::is_denied(license, &self.license_denylist)
+    }
+
     /// Get the config file path.
     pub fn config_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir().context("Could not determine config directory")?;
@@ -116,6 +739,44 @@ mod tests {
         assert!(config.openai_api_key.is_none());
         assert_eq!(config.openai_base_url, "https://api.openai.com");
         assert_eq!(config.embedding_model, "text-embedding-3-small");
+        assert_eq!(config.embedding_dimensions, 1536);
+        assert_eq!(config.embedding_batch_size, 100);
+        assert_eq!(config.embedding_batch_tokens, 200_000);
+        assert!(!config.doc_embeddings);
+        assert_eq!(config.embedding_name_weight, 0);
+        assert_eq!(config.embedding_price_per_1k_tokens, 0.00002);
+        assert!(!config.store_source_files);
+        assert_eq!(config.blob_compression_level, 3);
+        assert!(!config.index_tests);
+        assert!(!config.index_vendored);
+        assert!(config.public_only);
+        assert_eq!(config.snippet_length, 500);
+        assert!(config.chunk_transformers.is_empty());
+        assert_eq!(config.max_files_per_package, 5000);
+        assert_eq!(config.max_chunks_per_package, 20000);
+        assert_eq!(config.db_read_connections, 4);
+        assert_eq!(config.vector_backend, VectorBackendKind::Lance);
+    }
+
+    #[test]
+    fn test_offline_embeddings_must_be_local() {
+        let mut config = LocalConfig::default();
+        assert!(config.check_offline_embeddings().is_ok());
+
+        config.offline = true;
+        let err = config.check_offline_embeddings().unwrap_err();
+        assert!(err.to_string().contains("offline mode"));
+
+        for url in [
+            "http://localhost:11434",
+            "http://127.0.0.1:8080/",
+            "http://[::1]:8080",
+        ] {
+            config.openai_base_url = url.to_string();
+            assert!(config.check_offline_embeddings().is_ok(), "{}", url);
+        }
+        config.openai_base_url = "https://localhost.example.com".to_string();
+        assert!(config.check_offline_embeddings().is_err());
     }
 
     #[test]
@@ -138,4 +799,204 @@ mod tests {
         assert_eq!(parsed.openai_api_key, config.openai_api_key);
         assert_eq!(parsed.openai_base_url, config.openai_base_url);
     }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: BTreeMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |var| vars.get(var).cloned()
+    }
+
+    #[test]
+    fn test_env_overrides_file_secrets() {
+        let parsed: LocalConfig =
+            toml::from_str("openai_api_key = \"sk-file\"\nturbopuffer_api_key = \"tp-file\"")
+                .unwrap();
+
+        let config = parsed.clone().with_env(env(&[
+            ("IDX_OPENAI_API_KEY", "sk-env"),
+            ("IDX_TURBOPUFFER_API_KEY", ""),
+        ]));
+        assert_eq!(config.require_openai_key().unwrap(), "sk-env");
+        assert!(config.is_from_env(EnvSecret::OpenAiApiKey));
+        // Empty variables don't count
+        assert_eq!(config.turbopuffer_api_key.as_deref(), Some("tp-file"));
+        assert!(!config.is_from_env(EnvSecret::TurbopufferApiKey));
+
+        // Saving writes the file's key back, not the environment's
+        let written: LocalConfig = toml::from_str(&config.file_contents().unwrap()).unwrap();
+        assert_eq!(written.openai_api_key.as_deref(), Some("sk-file"));
+
+        // Unless the key was changed since
+        let mut changed = config.clone();
+        changed.set_openai_key("sk-new".to_string());
+        let written: LocalConfig = toml::from_str(&changed.file_contents().unwrap()).unwrap();
+        assert_eq!(written.openai_api_key.as_deref(), Some("sk-new"));
+
+        // Nothing in the file: the environment still supplies the key
+        let config = LocalConfig::default().with_env(env(&[("IDX_OPENAI_API_KEY", "sk-env")]));
+        assert_eq!(config.require_openai_key().unwrap(), "sk-env");
+        let written: LocalConfig = toml::from_str(&config.file_contents().unwrap()).unwrap();
+        assert!(written.openai_api_key.is_none());
+    }
+
+    #[test]
+    fn test_env_key_applies_to_profiles() {
+        let mut config = LocalConfig::default();
+        config.add_profile("cheap", cheap_profile()).unwrap();
+
+        let resolved = config
+            .with_env(env(&[("IDX_OPENAI_API_KEY", "sk-env")]))
+            .with_profile(Some("cheap"))
+            .unwrap();
+        assert_eq!(resolved.openai_api_key.as_deref(), Some("sk-env"));
+        assert_eq!(resolved.embedding_model, "nomic-embed-text");
+    }
+
+    #[test]
+    fn test_missing_key_error_names_env_var() {
+        let config = LocalConfig::default().with_env(env(&[]));
+        let err = config.require_openai_key().unwrap_err().to_string();
+        assert!(err.contains("idx config set-key"), "{}", err);
+        assert!(err.contains("IDX_OPENAI_API_KEY"), "{}", err);
+
+        let mut config = LocalConfig::default();
+        config.set_openai_key(String::new());
+        assert!(config.require_openai_key().is_err());
+    }
+
+    fn cheap_profile() -> EmbeddingProfile {
+        EmbeddingProfile {
+            openai_api_key: Some("sk-cheap".to_string()),
+            openai_base_url: "http://localhost:11434".to_string(),
+            embedding_model: "nomic-embed-text".to_string(),
+            embedding_dimensions: 768,
+        }
+    }
+
+    #[test]
+    fn test_profiles_round_trip() {
+        let mut config = LocalConfig::default();
+        config.add_profile("cheap", cheap_profile()).unwrap();
+        config.use_profile("cheap").unwrap();
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: LocalConfig = toml::from_str(&toml_str).unwrap();
+
+        assert_eq!(parsed.active_profile.as_deref(), Some("cheap"));
+        assert_eq!(parsed.profile("cheap").unwrap(), &cheap_profile());
+        // Top-level settings are untouched by the profile
+        assert_eq!(parsed.embedding_model, "text-embedding-3-small");
+        assert_eq!(parsed.embedding_dimensions, 1536);
+    }
+
+    #[test]
+    fn test_profile_defaults_from_toml() {
+        let parsed: LocalConfig =
+            toml::from_str("[profiles.small]\nembedding_model = \"text-embedding-3-small\"")
+                .unwrap();
+        let profile = parsed.profile("small").unwrap();
+        assert_eq!(profile.openai_base_url, "https://api.openai.com");
+        assert_eq!(profile.embedding_dimensions, 1536);
+        assert!(parsed.active_profile.is_none());
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let mut config = LocalConfig::default();
+        config.add_profile("cheap", cheap_profile()).unwrap();
+
+        // No active profile: top-level settings
+        let resolved = config.clone().with_profile(None).unwrap();
+        assert_eq!(resolved.embedding_model, "text-embedding-3-small");
+
+        // Explicit override
+        let resolved = config.clone().with_profile(Some("cheap")).unwrap();
+        assert_eq!(resolved.embedding_model, "nomic-embed-text");
+        assert_eq!(resolved.embedding_dimensions, 768);
+        assert_eq!(resolved.openai_api_key.as_deref(), Some("sk-cheap"));
+
+        // Active profile, overridden back to the top-level settings for one run
+        config.use_profile("cheap").unwrap();
+        assert_eq!(
+            config.clone().with_profile(None).unwrap().embedding_model,
+            "nomic-embed-text"
+        );
+        assert_eq!(
+            config
+                .clone()
+                .with_profile(Some(DEFAULT_PROFILE))
+                .unwrap()
+                .embedding_model,
+            "text-embedding-3-small"
+        );
+
+        assert!(config.clone().with_profile(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_use_profile_validates_name() {
+        let mut config = LocalConfig::default();
+        assert!(config.use_profile("missing").is_err());
+        assert!(
+            config
+                .add_profile(DEFAULT_PROFILE, cheap_profile())
+                .is_err()
+        );
+
+        config.add_profile("cheap", cheap_profile()).unwrap();
+        config.use_profile("cheap").unwrap();
+        config.use_profile(DEFAULT_PROFILE).unwrap();
+        assert!(config.active_profile.is_none());
+    }
+
+    #[test]
+    fn test_vector_backend_from_toml() {
+        let parsed: LocalConfig = toml::from_str("vector_backend = \"turbopuffer\"").unwrap();
+        assert_eq!(parsed.vector_backend, VectorBackendKind::Turbopuffer);
+        assert_eq!(parsed.turbopuffer_base_url, "https://api.turbopuffer.com");
+        assert_eq!(parsed.turbopuffer_namespace_prefix, "idx");
+        assert_eq!(parsed.distance_metric, DistanceMetric::L2);
+
+        let parsed: LocalConfig = toml::from_str("distance_metric = \"cosine\"").unwrap();
+        assert_eq!(parsed.distance_metric, DistanceMetric::Cosine);
+    }
+
+    #[test]
+    fn test_registry_concurrency_from_toml() {
+        let parsed: LocalConfig =
+            toml::from_str("registry_concurrency = { npm = 32, maven = 2 }").unwrap();
+        assert_eq!(
+            parsed.registry_concurrency,
+            BTreeMap::from([(Registry::Npm, 32), (Registry::Maven, 2)])
+        );
+        assert!(LocalConfig::default().registry_concurrency.is_empty());
+
+        let serialized = toml::to_string(&parsed).unwrap();
+        let reparsed: LocalConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.registry_concurrency, parsed.registry_concurrency);
+    }
+
+    #[test]
+    fn test_http_options_from_toml() {
+        let parsed: LocalConfig = toml::from_str(
+            "http_proxy = \"http://proxy.corp:8080\"\n\
+             no_proxy = \"localhost,.corp\"\n\
+             ca_bundle = \"/etc/ssl/corp-ca.pem\"",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.http_options(),
+            HttpOptions {
+                proxy: Some("http://proxy.corp:8080".to_string()),
+                no_proxy: Some("localhost,.corp".to_string()),
+                ca_bundle: Some(PathBuf::from("/etc/ssl/corp-ca.pem")),
+            }
+        );
+        assert_eq!(
+            LocalConfig::default().http_options(),
+            HttpOptions::default()
+        );
+    }
 }
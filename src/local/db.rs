@@ -1,137 +1,503 @@
 //! SQLite database operations for local index.
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use futures::TryStreamExt;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 use sqlx::{Row, SqlitePool};
 
 use super::models::{
-    ChunkRow, ChunkWithPackage, CreateChunk, CreatePackage, ExistingChunk, IndexStats, PackageRow,
-    VersionRow, VersionStatus, VersionWithPackage, bytes_to_vector, vector_to_bytes,
+    ApiSymbol, ChunkEmbedding, ChunkFilter, ChunkRow, ChunkWithPackage, CreateChunk, CreatePackage,
+    DistanceMetric, ExistingChunk, FailureKind, ImplWithPackage, IndexStats, OutlineSymbol,
+    PackageMetadata, PackageRow, QueryMode, TypeMethod, VECTOR_DIM, VectorSearchHit, VersionRow,
+    VersionStatus, VersionWithPackage, bytes_to_vector, vector_to_bytes,
 };
+use super::tokens::chunk_tokens;
+use crate::indexer::TraitImpl;
+use crate::types::TenantId;
+
+/// Default number of pooled read connections.
+pub const DEFAULT_READ_CONNECTIONS: u32 = 4;
+
+/// How long a connection waits on a locked database before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `index_meta` key holding the embedding dimension of the index.
+const EMBEDDING_DIMENSIONS_KEY: &str = "embedding_dimensions";
+
+/// A schema change applied by [`LocalDb::migrate`].
+#[derive(Debug, Clone, Copy)]
+enum Migration {
+    /// Move a v1 schema (single packages table with version column) aside.
+    RetireV1Schema,
+    CreateTables,
+    CreateIndexMeta,
+    VersionRetries,
+    VersionFileCounts,
+    ChunkSourceRanges,
+    ChunkTokens,
+    ChunkDeprecation,
+    CreateIndexes,
+    ChunkEmbeddingModel,
+    PackageMetadata,
+    VersionFailureKind,
+    CreateSkipRules,
+    ChunkParent,
+    ChunkDocVector,
+    PackageTenant,
+    ChunkSignatureTypes,
+    CreateImpls,
+    VersionEntryPoint,
+    ChunkEmbeddingHash,
+}
+
+/// Migrations in the order they run, keyed by the id recorded once applied.
+///
+/// Append new steps to the end; never reorder or rename existing ids.
+const MIGRATIONS: &[(&str, Migration)] = &[
+    ("0001_retire_v1_schema", Migration::RetireV1Schema),
+    ("0002_create_tables", Migration::CreateTables),
+    ("0003_create_index_meta", Migration::CreateIndexMeta),
+    ("0004_version_retries", Migration::VersionRetries),
+    ("0005_version_file_counts", Migration::VersionFileCounts),
+    ("0006_chunk_source_ranges", Migration::ChunkSourceRanges),
+    ("0007_chunk_tokens", Migration::ChunkTokens),
+    ("0008_chunk_deprecation", Migration::ChunkDeprecation),
+    ("0009_create_indexes", Migration::CreateIndexes),
+    ("0010_chunk_embedding_model", Migration::ChunkEmbeddingModel),
+    ("0011_package_metadata", Migration::PackageMetadata),
+    ("0012_version_failure_kind", Migration::VersionFailureKind),
+    ("0013_create_skip_rules", Migration::CreateSkipRules),
+    ("0014_chunk_parent", Migration::ChunkParent),
+    ("0015_chunk_doc_vector", Migration::ChunkDocVector),
+    ("0016_package_tenant", Migration::PackageTenant),
+    ("0017_chunk_signature_types", Migration::ChunkSignatureTypes),
+    ("0018_create_impls", Migration::CreateImpls),
+    ("0019_version_entry_point", Migration::VersionEntryPoint),
+    ("0020_chunk_embedding_hash", Migration::ChunkEmbeddingHash),
+];
+
+/// Error returned when embeddings don't match the dimension the index holds.
+///
+/// Vectors of different dimensions can't be compared, so an index only ever
+/// holds one.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "index holds {index}-dimensional embeddings but {requested}-dimensional ones were requested \
+     (switch to a matching profile, or rebuild with `idx clean` and `idx init`)"
+)]
+pub struct DimensionMismatch {
+    pub index: usize,
+    pub requested: usize,
+}
 
 /// Local SQLite database.
+///
+/// Uses WAL mode with a single-connection writer pool, so writes are
+/// serialized, and a separate read-only pool so searches and stats run
+/// concurrently with indexing.
 pub struct LocalDb {
-    pool: SqlitePool,
+    writer: SqlitePool,
+    reader: SqlitePool,
 }
 
 impl LocalDb {
     /// Open or create the database at the given path.
     pub async fn open(db_path: &Path) -> Result<Self> {
+        Self::open_with_readers(db_path, DEFAULT_READ_CONNECTIONS).await
+    }
+
+    /// Open or create the database with `read_connections` pooled readers.
+    pub async fn open_with_readers(db_path: &Path, read_connections: u32) -> Result<Self> {
         let options = SqliteConnectOptions::new()
             .filename(db_path)
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Wal);
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT);
 
-        let pool = SqlitePoolOptions::new()
+        let writer = SqlitePoolOptions::new()
             .max_connections(1)
-            .connect_with(options)
+            .connect_with(options.clone().create_if_missing(true))
             .await
             .context("Failed to open SQLite database")?;
 
-        let db = Self { pool };
+        let mut db = Self {
+            reader: writer.clone(),
+            writer,
+        };
         db.migrate().await?;
 
+        // Readers open after migrations so the schema exists
+        db.reader = SqlitePoolOptions::new()
+            .max_connections(read_connections.max(1))
+            .connect_with(options.read_only(true))
+            .await
+            .context("Failed to open SQLite read pool")?;
+
         Ok(db)
     }
 
-    /// Run database migrations.
-    async fn migrate(&self) -> Result<()> {
-        // Check if we need to migrate from old schema
-        let has_old_schema = sqlx::query_scalar::<_, i32>(
-            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='packages' AND sql LIKE '%version TEXT%'",
-        )
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(0) > 0;
-
-        if has_old_schema {
-            self.migrate_from_v1().await?;
-        }
+    /// Close every pooled connection, waiting until they are closed.
+    pub async fn close(self) {
+        self.reader.close().await;
+        self.writer.close().await;
+    }
 
-        // Packages table (unique by registry + name)
+    /// Run the migrations in [`MIGRATIONS`] that haven't been applied yet.
+    ///
+    /// Applied ids are recorded in `schema_migrations`. Databases created
+    /// before that table existed have no records, so every step must be
+    /// idempotent and safe to re-run against a partially upgraded schema.
+    async fn migrate(&self) -> Result<()> {
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS packages (
+            CREATE TABLE IF NOT EXISTS schema_migrations (
                 id TEXT PRIMARY KEY,
-                registry TEXT NOT NULL,
-                name TEXT NOT NULL,
-                description TEXT,
-                created_at TEXT NOT NULL,
-                UNIQUE(registry, name)
+                applied_at TEXT NOT NULL
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(&self.writer)
         .await?;
 
-        // Versions table (unique by package_id + version)
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS versions (
-                id TEXT PRIMARY KEY,
-                package_id TEXT NOT NULL,
-                version TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'pending',
-                error_message TEXT,
-                chunk_count INTEGER NOT NULL DEFAULT 0,
-                indexed_at TEXT,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (package_id) REFERENCES packages(id),
-                UNIQUE(package_id, version)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        let applied: HashSet<String> = sqlx::query_scalar("SELECT id FROM schema_migrations")
+            .fetch_all(&self.writer)
+            .await?
+            .into_iter()
+            .collect();
 
-        // Chunks table (references version_id)
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS chunks (
-                id TEXT PRIMARY KEY,
-                version_id TEXT NOT NULL,
-                namespace TEXT NOT NULL,
-                chunk_type TEXT NOT NULL,
-                name TEXT NOT NULL,
-                file_path TEXT NOT NULL,
-                start_line INTEGER NOT NULL,
-                end_line INTEGER NOT NULL,
-                visibility TEXT NOT NULL,
-                signature TEXT,
-                docstring TEXT,
-                snippet TEXT NOT NULL,
-                storage_key TEXT NOT NULL,
-                content_hash TEXT NOT NULL,
-                vector BLOB NOT NULL,
-                FOREIGN KEY (version_id) REFERENCES versions(id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
+        for &(id, migration) in MIGRATIONS {
+            if applied.contains(id) {
+                continue;
+            }
+
+            tracing::debug!(id, "applying migration");
+            self.apply(migration)
+                .await
+                .with_context(|| format!("Migration {} failed", id))?;
+
+            sqlx::query("INSERT INTO schema_migrations (id, applied_at) VALUES (?, ?)")
+                .bind(id)
+                .bind(chrono::Utc::now().to_rfc3339())
+                .execute(&self.writer)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply a single migration step.
+    async fn apply(&self, migration: Migration) -> Result<()> {
+        match migration {
+            Migration::RetireV1Schema => {
+                let has_old_schema = sqlx::query_scalar::<_, i32>(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='packages' AND sql LIKE '%version TEXT%'",
+                )
+                .fetch_one(&self.writer)
+                .await? > 0;
+
+                if has_old_schema {
+                    self.migrate_from_v1().await?;
+                }
+            }
+            Migration::CreateTables => {
+                // Packages table (unique by registry + name)
+                sqlx::query(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS packages (
+                        id TEXT PRIMARY KEY,
+                        registry TEXT NOT NULL,
+                        name TEXT NOT NULL,
+                        description TEXT,
+                        created_at TEXT NOT NULL,
+                        UNIQUE(registry, name)
+                    )
+                    "#,
+                )
+                .execute(&self.writer)
+                .await?;
+
+                // Versions table (unique by package_id + version)
+                sqlx::query(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS versions (
+                        id TEXT PRIMARY KEY,
+                        package_id TEXT NOT NULL,
+                        version TEXT NOT NULL,
+                        status TEXT NOT NULL DEFAULT 'pending',
+                        error_message TEXT,
+                        chunk_count INTEGER NOT NULL DEFAULT 0,
+                        indexed_at TEXT,
+                        created_at TEXT NOT NULL,
+                        FOREIGN KEY (package_id) REFERENCES packages(id),
+                        UNIQUE(package_id, version)
+                    )
+                    "#,
+                )
+                .execute(&self.writer)
+                .await?;
+
+                // Chunks table (references version_id)
+                sqlx::query(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS chunks (
+                        id TEXT PRIMARY KEY,
+                        version_id TEXT NOT NULL,
+                        namespace TEXT NOT NULL,
+                        chunk_type TEXT NOT NULL,
+                        name TEXT NOT NULL,
+                        file_path TEXT NOT NULL,
+                        start_line INTEGER NOT NULL,
+                        end_line INTEGER NOT NULL,
+                        visibility TEXT NOT NULL,
+                        signature TEXT,
+                        docstring TEXT,
+                        snippet TEXT NOT NULL,
+                        storage_key TEXT NOT NULL,
+                        content_hash TEXT NOT NULL,
+                        vector BLOB NOT NULL,
+                        FOREIGN KEY (version_id) REFERENCES versions(id)
+                    )
+                    "#,
+                )
+                .execute(&self.writer)
+                .await?;
+            }
+            Migration::CreateIndexMeta => {
+                // Index-wide settings, such as the embedding dimension
+                sqlx::query(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS index_meta (
+                        key TEXT PRIMARY KEY,
+                        value TEXT NOT NULL
+                    )
+                    "#,
+                )
+                .execute(&self.writer)
+                .await?;
+            }
+            Migration::VersionRetries => {
+                self.ensure_column("versions", "attempts", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+                self.ensure_column("versions", "failed_at", "TEXT").await?;
+            }
+            Migration::VersionFileCounts => {
+                self.ensure_column("versions", "files_seen", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+                self.ensure_column("versions", "files_skipped", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+            }
+            Migration::ChunkSourceRanges => {
+                self.ensure_column("chunks", "file_storage_key", "TEXT")
+                    .await?;
+                self.ensure_column("chunks", "start_byte", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+                self.ensure_column("chunks", "end_byte", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+            }
+            Migration::ChunkTokens => {
+                if self
+                    .ensure_column("chunks", "tokens", "TEXT NOT NULL DEFAULT ''")
+                    .await?
+                {
+                    self.backfill_chunk_tokens().await?;
+                }
+            }
+            Migration::ChunkDeprecation => {
+                self.ensure_column("chunks", "deprecated", "INTEGER NOT NULL DEFAULT 0")
+                    .await?;
+                self.ensure_column("chunks", "deprecation_message", "TEXT")
+                    .await?;
+            }
+            Migration::CreateIndexes => {
+                for statement in [
+                    "CREATE INDEX IF NOT EXISTS idx_versions_package ON versions(package_id)",
+                    "CREATE INDEX IF NOT EXISTS idx_versions_status ON versions(status)",
+                    "CREATE INDEX IF NOT EXISTS idx_chunks_namespace ON chunks(namespace)",
+                    "CREATE INDEX IF NOT EXISTS idx_chunks_version ON chunks(version_id)",
+                    "CREATE INDEX IF NOT EXISTS idx_chunks_content_hash ON chunks(content_hash)",
+                ] {
+                    sqlx::query(statement).execute(&self.writer).await?;
+                }
+            }
+            Migration::ChunkEmbeddingModel => {
+                // Unknown for older chunks, so their vectors are never reused
+                self.ensure_column("chunks", "embedding_model", "TEXT")
+                    .await?;
+            }
+            Migration::PackageMetadata => {
+                self.ensure_column("packages", "license", "TEXT").await?;
+                self.ensure_column("packages", "repository", "TEXT").await?;
+            }
+            Migration::VersionFailureKind => {
+                // Left NULL for older failures, which are treated as retryable
+                self.ensure_column("versions", "failure_kind", "TEXT")
+                    .await?;
+            }
+            Migration::CreateSkipRules => {
+                // Globs on `registry:name` for packages never to download
+                sqlx::query(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS skip_rules (
+                        pattern TEXT PRIMARY KEY,
+                        created_at TEXT NOT NULL
+                    )
+                    "#,
+                )
+                .execute(&self.writer)
+                .await?;
+            }
+            Migration::ChunkParent => {
+                // Left NULL for older chunks; re-index to tell methods of different types apart
+                self.ensure_column("chunks", "parent", "TEXT").await?;
+            }
+            Migration::ChunkDocVector => {
+                // Only filled in when documentation embeddings are enabled
+                self.ensure_column("chunks", "doc_hash", "TEXT").await?;
+                self.ensure_column("chunks", "doc_vector", "BLOB").await?;
+            }
+            Migration::PackageTenant => {
+                // Packages become unique per tenant (empty for public ones),
+                // so a private package can share a public one's name. SQLite
+                // can't change a UNIQUE constraint, so the table is rebuilt,
+                // with foreign keys off while versions point at the old one.
+                let rebuilt: i32 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM pragma_table_info('packages') WHERE name = 'tenant'",
+                )
+                .fetch_one(&self.writer)
+                .await?;
+                if rebuilt > 0 {
+                    return Ok(());
+                }
+
+                let mut conn = self.writer.acquire().await?;
+                sqlx::query("PRAGMA foreign_keys = OFF")
+                    .execute(&mut *conn)
+                    .await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                for statement in [
+                    r#"
+                    CREATE TABLE packages_by_tenant (
+                        id TEXT PRIMARY KEY,
+                        registry TEXT NOT NULL,
+                        name TEXT NOT NULL,
+                        description TEXT,
+                        created_at TEXT NOT NULL,
+                        license TEXT,
+                        repository TEXT,
+                        tenant TEXT NOT NULL DEFAULT '',
+                        UNIQUE(registry, name, tenant)
+                    )
+                    "#,
+                    r#"
+                    INSERT INTO packages_by_tenant
+                        (id, registry, name, description, created_at, license, repository)
+                    SELECT id, registry, name, description, created_at, license, repository
+                    FROM packages
+                    "#,
+                    "DROP TABLE packages",
+                    "ALTER TABLE packages_by_tenant RENAME TO packages",
+                ] {
+                    sqlx::query(statement).execute(&mut *tx).await?;
+                }
+                tx.commit().await?;
+                sqlx::query("PRAGMA foreign_keys = ON")
+                    .execute(&mut *conn)
+                    .await?;
+            }
+            Migration::ChunkSignatureTypes => {
+                // Left NULL for older chunks; re-index to filter them by parameter types
+                self.ensure_column("chunks", "params", "TEXT").await?;
+                self.ensure_column("chunks", "returns", "TEXT").await?;
+            }
+            Migration::CreateImpls => {
+                // `impl Trait for Type` blocks; empty for packages indexed before
+                for statement in [
+                    r#"
+                    CREATE TABLE IF NOT EXISTS impls (
+                        version_id TEXT NOT NULL,
+                        namespace TEXT NOT NULL,
+                        trait_name TEXT NOT NULL,
+                        type_name TEXT NOT NULL,
+                        signature TEXT NOT NULL,
+                        file_path TEXT NOT NULL,
+                        line INTEGER NOT NULL,
+                        FOREIGN KEY (version_id) REFERENCES versions(id)
+                    )
+                    "#,
+                    "CREATE INDEX IF NOT EXISTS idx_impls_version ON impls(version_id)",
+                    "CREATE INDEX IF NOT EXISTS idx_impls_trait ON impls(trait_name)",
+                    "CREATE INDEX IF NOT EXISTS idx_impls_type ON impls(type_name)",
+                ] {
+                    sqlx::query(statement).execute(&self.writer).await?;
+                }
+            }
+            Migration::VersionEntryPoint => {
+                // Left NULL for older versions; re-index to detect their entry point
+                self.ensure_column("versions", "entry_point", "TEXT")
+                    .await?;
+            }
+            Migration::ChunkEmbeddingHash => {
+                // Left NULL for older chunks, so their vectors are never reused
+                self.ensure_column("chunks", "embedding_hash", "TEXT")
+                    .await?;
+                sqlx::query(
+                    "CREATE INDEX IF NOT EXISTS idx_chunks_embedding_hash ON chunks(embedding_hash)",
+                )
+                .execute(&self.writer)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a column to an existing table if it is missing.
+    ///
+    /// Returns whether the column was added.
+    async fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<bool> {
+        let exists: i32 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?",
+            table
+        ))
+        .bind(column)
+        .fetch_one(&self.writer)
         .await?;
 
-        // Indexes
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_versions_package ON versions(package_id)")
-            .execute(&self.pool)
+        if exists == 0 {
+            sqlx::query(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))
+            .execute(&self.writer)
             .await?;
+        }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_versions_status ON versions(status)")
-            .execute(&self.pool)
-            .await?;
+        Ok(exists == 0)
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_chunks_namespace ON chunks(namespace)")
-            .execute(&self.pool)
+    /// Fill `chunks.tokens` for chunks indexed before the column existed.
+    async fn backfill_chunk_tokens(&self) -> Result<()> {
+        let rows = sqlx::query("SELECT id, name, signature FROM chunks")
+            .fetch_all(&self.writer)
             .await?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_chunks_version ON chunks(version_id)")
-            .execute(&self.pool)
-            .await?;
+        let mut tx = self.writer.begin().await?;
+        for row in rows {
+            let id: String = row.get("id");
+            let name: String = row.get("name");
+            let signature: Option<String> = row.get("signature");
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_chunks_content_hash ON chunks(content_hash)")
-            .execute(&self.pool)
-            .await?;
+            sqlx::query("UPDATE chunks SET tokens = ? WHERE id = ?")
+                .bind(chunk_tokens(&name, signature.as_deref()))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
@@ -142,14 +508,14 @@ impl LocalDb {
 
         // Rename old tables
         sqlx::query("ALTER TABLE packages RENAME TO packages_v1")
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         sqlx::query("ALTER TABLE chunks RENAME TO chunks_v1")
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
-        // New tables will be created by migrate()
+        // New tables are created by the next migration
         // Data migration would happen here if needed
         Ok(())
     }
@@ -158,7 +524,10 @@ impl LocalDb {
 
     /// Get or create a package, returning its ID.
     pub async fn get_or_create_package(&self, input: &CreatePackage) -> Result<String> {
-        if let Some(pkg) = self.find_package(&input.registry, &input.name).await? {
+        if let Some(pkg) = self
+            .find_package(&input.registry, &input.name, input.tenant)
+            .await?
+        {
             return Ok(pkg.id);
         }
 
@@ -167,8 +536,8 @@ impl LocalDb {
 
         sqlx::query(
             r#"
-            INSERT INTO packages (id, registry, name, description, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO packages (id, registry, name, description, created_at, tenant)
+            VALUES (?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
@@ -176,20 +545,58 @@ impl LocalDb {
         .bind(&input.name)
         .bind(&input.description)
         .bind(&now)
-        .execute(&self.pool)
+        .bind(tenant_key(input.tenant))
+        .execute(&self.writer)
         .await?;
 
         Ok(id)
     }
 
-    /// Find a package by registry and name.
-    pub async fn find_package(&self, registry: &str, name: &str) -> Result<Option<PackageRow>> {
+    /// Record registry metadata for a package, keeping stored values the
+    /// registry didn't report.
+    pub async fn update_package_metadata(
+        &self,
+        registry: &str,
+        name: &str,
+        tenant: Option<TenantId>,
+        metadata: &PackageMetadata,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE packages SET
+                description = COALESCE(?, description),
+                license = COALESCE(?, license),
+                repository = COALESCE(?, repository)
+            WHERE registry = ? AND name = ? AND tenant = ?
+            "#,
+        )
+        .bind(&metadata.description)
+        .bind(&metadata.license)
+        .bind(&metadata.repository)
+        .bind(registry)
+        .bind(name)
+        .bind(tenant_key(tenant))
+        .execute(&self.writer)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Find a package by registry and name, among `tenant`'s private
+    /// packages or, with None, the public ones.
+    pub async fn find_package(
+        &self,
+        registry: &str,
+        name: &str,
+        tenant: Option<TenantId>,
+    ) -> Result<Option<PackageRow>> {
         let row = sqlx::query_as::<_, PackageRow>(
-            "SELECT * FROM packages WHERE registry = ? AND name = ?",
+            "SELECT * FROM packages WHERE registry = ? AND name = ? AND tenant = ?",
         )
         .bind(registry)
         .bind(name)
-        .fetch_optional(&self.pool)
+        .bind(tenant_key(tenant))
+        .fetch_optional(&self.reader)
         .await?;
 
         Ok(row)
@@ -199,7 +606,7 @@ impl LocalDb {
     pub async fn list_packages(&self) -> Result<Vec<PackageRow>> {
         let rows =
             sqlx::query_as::<_, PackageRow>("SELECT * FROM packages ORDER BY created_at DESC")
-                .fetch_all(&self.pool)
+                .fetch_all(&self.reader)
                 .await?;
 
         Ok(rows)
@@ -215,28 +622,27 @@ impl LocalDb {
             "#,
         )
         .bind(package_id)
-        .fetch_all(&self.pool)
+        .fetch_all(&self.writer)
         .await?;
 
-        sqlx::query(
-            r#"
-            DELETE FROM chunks WHERE version_id IN (
-                SELECT id FROM versions WHERE package_id = ?
-            )
-            "#,
-        )
-        .bind(package_id)
-        .execute(&self.pool)
-        .await?;
+        for table in ["chunks", "impls"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE version_id IN (SELECT id FROM versions WHERE package_id = ?)",
+                table
+            ))
+            .bind(package_id)
+            .execute(&self.writer)
+            .await?;
+        }
 
         sqlx::query("DELETE FROM versions WHERE package_id = ?")
             .bind(package_id)
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         sqlx::query("DELETE FROM packages WHERE id = ?")
             .bind(package_id)
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         Ok(namespaces)
@@ -252,7 +658,10 @@ impl LocalDb {
     ) -> Result<(String, bool)> {
         if let Some(ver) = self.find_version_by_package(package_id, version).await? {
             let status = ver.status();
-            let should_skip = matches!(status, VersionStatus::Indexed | VersionStatus::Skipped);
+            let should_skip = matches!(
+                status,
+                VersionStatus::Indexed | VersionStatus::Skipped | VersionStatus::Empty
+            );
             return Ok((ver.id, should_skip));
         }
 
@@ -269,7 +678,7 @@ impl LocalDb {
         .bind(package_id)
         .bind(version)
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&self.writer)
         .await?;
 
         Ok((id, false))
@@ -286,34 +695,39 @@ impl LocalDb {
         )
         .bind(package_id)
         .bind(version)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.reader)
         .await?;
 
         Ok(row)
     }
 
-    /// Find a version by registry, name, and version.
+    /// Find a package's version by registry, name, and version, among
+    /// `tenant`'s private packages or, with None, the public ones.
     pub async fn find_version(
         &self,
         registry: &str,
         name: &str,
         version: &str,
+        tenant: Option<TenantId>,
     ) -> Result<Option<VersionWithPackage>> {
         let row = sqlx::query_as::<_, VersionWithPackage>(
             r#"
             SELECT
                 v.id as version_id, v.version, v.status, v.error_message,
-                v.chunk_count, v.indexed_at,
-                p.id as package_id, p.registry, p.name, p.description
+                v.chunk_count, v.indexed_at, v.attempts, v.failed_at, v.failure_kind, v.files_seen,
+                v.files_skipped, v.entry_point,
+                p.id as package_id, p.registry, p.name, p.description, p.license,
+                p.repository, p.tenant
             FROM versions v
             JOIN packages p ON v.package_id = p.id
-            WHERE p.registry = ? AND p.name = ? AND v.version = ?
+            WHERE p.registry = ? AND p.name = ? AND v.version = ? AND p.tenant = ?
             "#,
         )
         .bind(registry)
         .bind(name)
         .bind(version)
-        .fetch_optional(&self.pool)
+        .bind(tenant_key(tenant))
+        .fetch_optional(&self.reader)
         .await?;
 
         Ok(row)
@@ -325,14 +739,16 @@ impl LocalDb {
             r#"
             SELECT
                 v.id as version_id, v.version, v.status, v.error_message,
-                v.chunk_count, v.indexed_at,
-                p.id as package_id, p.registry, p.name, p.description
+                v.chunk_count, v.indexed_at, v.attempts, v.failed_at, v.failure_kind, v.files_seen,
+                v.files_skipped, v.entry_point,
+                p.id as package_id, p.registry, p.name, p.description, p.license,
+                p.repository, p.tenant
             FROM versions v
             JOIN packages p ON v.package_id = p.id
             ORDER BY v.indexed_at DESC NULLS LAST, v.created_at DESC
             "#,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.reader)
         .await?;
 
         Ok(rows)
@@ -347,8 +763,10 @@ impl LocalDb {
             r#"
             SELECT
                 v.id as version_id, v.version, v.status, v.error_message,
-                v.chunk_count, v.indexed_at,
-                p.id as package_id, p.registry, p.name, p.description
+                v.chunk_count, v.indexed_at, v.attempts, v.failed_at, v.failure_kind, v.files_seen,
+                v.files_skipped, v.entry_point,
+                p.id as package_id, p.registry, p.name, p.description, p.license,
+                p.repository, p.tenant
             FROM versions v
             JOIN packages p ON v.package_id = p.id
             WHERE v.status = ?
@@ -356,7 +774,7 @@ impl LocalDb {
             "#,
         )
         .bind(status.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&self.reader)
         .await?;
 
         Ok(rows)
@@ -364,77 +782,150 @@ impl LocalDb {
 
     /// Mark a version as successfully indexed.
     pub async fn mark_version_indexed(&self, version_id: &str, chunk_count: i32) -> Result<()> {
+        self.mark_version_stored(version_id, VersionStatus::Indexed, chunk_count)
+            .await
+    }
+
+    /// Mark a version's chunks as stored without embeddings, so they are
+    /// searchable by name and signature until a full run embeds them.
+    pub async fn mark_version_metadata_indexed(
+        &self,
+        version_id: &str,
+        chunk_count: i32,
+    ) -> Result<()> {
+        self.mark_version_stored(version_id, VersionStatus::MetadataIndexed, chunk_count)
+            .await
+    }
+
+    /// Record that `chunk_count` chunks of a version were stored, as `status`.
+    async fn mark_version_stored(
+        &self,
+        version_id: &str,
+        status: VersionStatus,
+        chunk_count: i32,
+    ) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
 
         sqlx::query(
             r#"
             UPDATE versions
-            SET status = 'indexed', chunk_count = ?, indexed_at = ?, error_message = NULL
+            SET status = ?, chunk_count = ?, indexed_at = ?, error_message = NULL,
+                attempts = 0, failed_at = NULL, failure_kind = NULL
             WHERE id = ?
             "#,
         )
+        .bind(status.to_string())
         .bind(chunk_count)
         .bind(&now)
         .bind(version_id)
-        .execute(&self.pool)
+        .execute(&self.writer)
         .await?;
 
         Ok(())
     }
 
-    /// Mark a version as failed.
-    pub async fn mark_version_failed(&self, version_id: &str, error: &str) -> Result<()> {
+    /// Mark a version as indexed without any chunks, recording how many
+    /// files were downloaded and how many of those weren't parsed.
+    pub async fn mark_version_empty(
+        &self,
+        version_id: &str,
+        files_seen: i32,
+        files_skipped: i32,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
         sqlx::query(
             r#"
             UPDATE versions
-            SET status = 'failed', error_message = ?
+            SET status = 'empty', chunk_count = 0, indexed_at = ?, error_message = NULL,
+                attempts = 0, failed_at = NULL, failure_kind = NULL, files_seen = ?,
+                files_skipped = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&now)
+        .bind(files_seen)
+        .bind(files_skipped)
+        .bind(version_id)
+        .execute(&self.writer)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a version as failed, counting the attempt.
+    pub async fn mark_version_failed(
+        &self,
+        version_id: &str,
+        kind: FailureKind,
+        error: &str,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            UPDATE versions
+            SET status = 'failed', error_message = ?, failure_kind = ?, attempts = attempts + 1,
+                failed_at = ?
             WHERE id = ?
             "#,
         )
         .bind(error)
+        .bind(kind.to_string())
+        .bind(&now)
         .bind(version_id)
-        .execute(&self.pool)
+        .execute(&self.writer)
         .await?;
 
         Ok(())
     }
 
-    /// Mark a version as skipped.
-    pub async fn mark_version_skipped(&self, version_id: &str) -> Result<()> {
-        sqlx::query("UPDATE versions SET status = 'skipped' WHERE id = ?")
+    /// Record the path of a version's entry module, or clear it.
+    pub async fn set_version_entry_point(
+        &self,
+        version_id: &str,
+        entry_point: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE versions SET entry_point = ? WHERE id = ?")
+            .bind(entry_point)
             .bind(version_id)
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         Ok(())
     }
 
-    /// Mark a version for retry (set status to pending).
-    pub async fn mark_version_pending(&self, version_id: &str) -> Result<()> {
-        sqlx::query("UPDATE versions SET status = 'pending', error_message = NULL WHERE id = ?")
+    /// Mark a version as skipped, recording why if it wasn't by request.
+    pub async fn mark_version_skipped(&self, version_id: &str, reason: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE versions SET status = 'skipped', error_message = ? WHERE id = ?")
+            .bind(reason)
             .bind(version_id)
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         Ok(())
     }
 
+    /// Mark a version for retry (set status to pending).
+    pub async fn mark_version_pending(&self, version_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE versions SET status = 'pending', error_message = NULL, failure_kind = NULL \
+             WHERE id = ?",
+        )
+        .bind(version_id)
+        .execute(&self.writer)
+        .await?;
+
+        Ok(())
+    }
+
     /// Delete a version and its chunks.
     pub async fn delete_version(&self, version_id: &str) -> Result<Vec<String>> {
-        let namespaces: Vec<String> =
-            sqlx::query_scalar("SELECT DISTINCT namespace FROM chunks WHERE version_id = ?")
-                .bind(version_id)
-                .fetch_all(&self.pool)
-                .await?;
-
-        sqlx::query("DELETE FROM chunks WHERE version_id = ?")
-            .bind(version_id)
-            .execute(&self.pool)
-            .await?;
+        let namespaces = self.delete_version_chunks(version_id).await?;
 
         sqlx::query("DELETE FROM versions WHERE id = ?")
             .bind(version_id)
-            .execute(&self.pool)
+            .execute(&self.writer)
             .await?;
 
         Ok(namespaces)
@@ -442,69 +933,260 @@ impl LocalDb {
 
     // ==================== Chunk Operations ====================
 
-    /// Insert a chunk.
+    /// Insert a chunk, replacing any stored chunk with the same id.
     pub async fn insert_chunk(&self, chunk: &CreateChunk) -> Result<()> {
-        let vector_bytes = vector_to_bytes(&chunk.vector);
-
-        sqlx::query(
-            r#"
-            INSERT INTO chunks (
-                id, version_id, namespace, chunk_type, name, file_path,
-                start_line, end_line, visibility, signature, docstring,
-                snippet, storage_key, content_hash, vector
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&chunk.id)
-        .bind(&chunk.version_id)
-        .bind(&chunk.namespace)
-        .bind(&chunk.chunk_type)
-        .bind(&chunk.name)
-        .bind(&chunk.file_path)
-        .bind(chunk.start_line as i64)
-        .bind(chunk.end_line as i64)
-        .bind(&chunk.visibility)
-        .bind(&chunk.signature)
-        .bind(&chunk.docstring)
-        .bind(&chunk.snippet)
-        .bind(&chunk.storage_key)
-        .bind(&chunk.content_hash)
-        .bind(&vector_bytes)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.insert_chunks(std::slice::from_ref(chunk)).await
     }
 
-    /// Batch insert chunks.
+    /// Batch upsert chunks in a single transaction.
+    ///
+    /// A chunk whose id already exists replaces the stored row.
     pub async fn insert_chunks(&self, chunks: &[CreateChunk]) -> Result<()> {
+        let mut tx = self.writer.begin().await?;
+
         for chunk in chunks {
-            self.insert_chunk(chunk).await?;
+            let vector_bytes = vector_to_bytes(&chunk.vector);
+            let doc_vector_bytes = chunk.doc_vector.as_deref().map(vector_to_bytes);
+            let params = (!chunk.params.is_empty())
+                .then(|| serde_json::to_string(&chunk.params))
+                .transpose()?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO chunks (
+                    id, version_id, namespace, chunk_type, name, file_path,
+                    start_line, end_line, visibility, signature, docstring,
+                    snippet, storage_key, content_hash, vector,
+                    file_storage_key, start_byte, end_byte, tokens,
+                    deprecated, deprecation_message, embedding_model, parent,
+                    doc_hash, doc_vector, params, returns, embedding_hash
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    version_id = excluded.version_id,
+                    namespace = excluded.namespace,
+                    chunk_type = excluded.chunk_type,
+                    name = excluded.name,
+                    file_path = excluded.file_path,
+                    start_line = excluded.start_line,
+                    end_line = excluded.end_line,
+                    visibility = excluded.visibility,
+                    signature = excluded.signature,
+                    docstring = excluded.docstring,
+                    snippet = excluded.snippet,
+                    storage_key = excluded.storage_key,
+                    content_hash = excluded.content_hash,
+                    vector = excluded.vector,
+                    file_storage_key = excluded.file_storage_key,
+                    start_byte = excluded.start_byte,
+                    end_byte = excluded.end_byte,
+                    tokens = excluded.tokens,
+                    deprecated = excluded.deprecated,
+                    deprecation_message = excluded.deprecation_message,
+                    embedding_model = excluded.embedding_model,
+                    parent = excluded.parent,
+                    doc_hash = excluded.doc_hash,
+                    doc_vector = excluded.doc_vector,
+                    params = excluded.params,
+                    returns = excluded.returns,
+                    embedding_hash = excluded.embedding_hash
+                "#,
+            )
+            .bind(&chunk.id)
+            .bind(&chunk.version_id)
+            .bind(&chunk.namespace)
+            .bind(&chunk.chunk_type)
+            .bind(&chunk.name)
+            .bind(&chunk.file_path)
+            .bind(chunk.start_line as i64)
+            .bind(chunk.end_line as i64)
+            .bind(&chunk.visibility)
+            .bind(&chunk.signature)
+            .bind(&chunk.docstring)
+            .bind(&chunk.snippet)
+            .bind(&chunk.storage_key)
+            .bind(&chunk.content_hash)
+            .bind(&vector_bytes)
+            .bind(&chunk.file_storage_key)
+            .bind(chunk.start_byte as i64)
+            .bind(chunk.end_byte as i64)
+            .bind(chunk_tokens(&chunk.name, chunk.signature.as_deref()))
+            .bind(chunk.deprecated)
+            .bind(&chunk.deprecation_message)
+            .bind(&chunk.embedding_model)
+            .bind(&chunk.parent)
+            .bind(&chunk.doc_hash)
+            .bind(&doc_vector_bytes)
+            .bind(&params)
+            .bind(&chunk.returns)
+            .bind(&chunk.embedding_hash)
+            .execute(&mut *tx)
+            .await?;
         }
+
+        tx.commit().await?;
         Ok(())
     }
 
-    /// Delete all chunks for a version.
+    /// Delete all chunks and trait implementations for a version.
     pub async fn delete_version_chunks(&self, version_id: &str) -> Result<Vec<String>> {
         let namespaces: Vec<String> =
             sqlx::query_scalar("SELECT DISTINCT namespace FROM chunks WHERE version_id = ?")
                 .bind(version_id)
-                .fetch_all(&self.pool)
+                .fetch_all(&self.writer)
+                .await?;
+
+        for table in ["chunks", "impls"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE version_id = ?", table))
+                .bind(version_id)
+                .execute(&self.writer)
                 .await?;
+        }
+
+        Ok(namespaces)
+    }
+
+    // ==================== Trait Implementations ====================
 
-        sqlx::query("DELETE FROM chunks WHERE version_id = ?")
+    /// Replace the trait implementations stored for a version with `impls`.
+    pub async fn replace_impls(
+        &self,
+        version_id: &str,
+        namespace: &str,
+        impls: &[TraitImpl],
+    ) -> Result<()> {
+        let mut tx = self.writer.begin().await?;
+
+        sqlx::query("DELETE FROM impls WHERE version_id = ?")
             .bind(version_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
-        Ok(namespaces)
+        for imp in impls {
+            sqlx::query(
+                r#"
+                INSERT INTO impls
+                    (version_id, namespace, trait_name, type_name, signature, file_path, line)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(version_id)
+            .bind(namespace)
+            .bind(&imp.trait_name)
+            .bind(&imp.type_name)
+            .bind(&imp.signature)
+            .bind(&imp.file_path)
+            .bind(imp.line as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Trait implementations in `namespaces`, narrowed to a trait and/or an
+    /// implementing type, ordered by package, trait and type.
+    pub async fn find_impls(
+        &self,
+        namespaces: &[String],
+        trait_name: Option<&str>,
+        type_name: Option<&str>,
+    ) -> Result<Vec<ImplWithPackage>> {
+        if namespaces.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let placeholders = vec!["?"; namespaces.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT
+                i.trait_name, i.type_name, i.signature, i.file_path, i.line,
+                p.registry, p.name as package_name, v.version
+            FROM impls i
+            JOIN versions v ON i.version_id = v.id
+            JOIN packages p ON v.package_id = p.id
+            WHERE i.namespace IN ({})
+              AND (? IS NULL OR i.trait_name = ?)
+              AND (? IS NULL OR i.type_name = ?)
+            ORDER BY p.registry, p.name, i.trait_name, i.type_name, i.file_path, i.line
+            "#,
+            placeholders
+        );
+
+        let mut query = sqlx::query_as::<_, ImplWithPackage>(&sql);
+        for namespace in namespaces {
+            query = query.bind(namespace);
+        }
+        let rows = query
+            .bind(trait_name)
+            .bind(trait_name)
+            .bind(type_name)
+            .bind(type_name)
+            .fetch_all(&self.reader)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// Methods whose parent is `type_name` in `namespaces`: the type's
+    /// method set, ordered by package and name.
+    ///
+    /// Parents recorded with generic arguments (`Stack<T>`) match too.
+    pub async fn type_methods(
+        &self,
+        namespaces: &[String],
+        type_name: &str,
+    ) -> Result<Vec<TypeMethod>> {
+        if namespaces.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let placeholders = vec!["?"; namespaces.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT
+                c.name, c.signature, c.file_path, c.start_line,
+                p.registry, p.name as package_name, v.version
+            FROM chunks c
+            JOIN versions v ON c.version_id = v.id
+            JOIN packages p ON v.package_id = p.id
+            WHERE c.namespace IN ({})
+              AND c.chunk_type = 'method'
+              AND (c.parent = ? OR instr(c.parent, ?) = 1)
+            ORDER BY p.registry, p.name, c.name, c.file_path, c.start_line
+            "#,
+            placeholders
+        );
+
+        let mut query = sqlx::query_as::<_, TypeMethod>(&sql);
+        for namespace in namespaces {
+            query = query.bind(namespace);
+        }
+        let rows = query
+            .bind(type_name)
+            .bind(format!("{}<", type_name))
+            .fetch_all(&self.reader)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// Whether any chunks are stored for a version.
+    pub async fn has_chunks(&self, version_id: &str) -> Result<bool> {
+        let found: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM chunks WHERE version_id = ? LIMIT 1")
+                .bind(version_id)
+                .fetch_optional(&self.writer)
+                .await?;
+
+        Ok(found.is_some())
     }
 
     /// Get all chunks in a namespace.
     pub async fn get_chunks_by_namespace(&self, namespace: &str) -> Result<Vec<ChunkRow>> {
         let rows = sqlx::query_as::<_, ChunkRow>("SELECT * FROM chunks WHERE namespace = ?")
             .bind(namespace)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.reader)
             .await?;
 
         Ok(rows)
@@ -518,6 +1200,8 @@ impl LocalDb {
                 c.id, c.namespace, c.chunk_type, c.name, c.file_path,
                 c.start_line, c.end_line, c.visibility, c.signature,
                 c.docstring, c.snippet, c.storage_key,
+                c.file_storage_key, c.start_byte, c.end_byte,
+                c.deprecated, c.deprecation_message, c.parent,
                 p.registry, p.name as package_name, v.version
             FROM chunks c
             JOIN versions v ON c.version_id = v.id
@@ -526,17 +1210,356 @@ impl LocalDb {
             "#,
         )
         .bind(id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.reader)
         .await?;
 
         Ok(row)
     }
 
+    /// Get a chunk's namespace and stored embedding vector.
+    pub async fn get_chunk_vector(&self, id: &str) -> Result<Option<(String, Vec<f32>)>> {
+        let row = sqlx::query("SELECT namespace, vector FROM chunks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.reader)
+            .await?;
+
+        Ok(row.map(|row| {
+            let namespace: String = row.get("namespace");
+            let vector_bytes: Vec<u8> = row.get("vector");
+            (namespace, bytes_to_vector(&vector_bytes))
+        }))
+    }
+
+    /// Namespaces holding chunks stored without embeddings, which have no
+    /// vectors until a full run embeds them.
+    pub async fn unembedded_namespaces(&self) -> Result<Vec<String>> {
+        let namespaces = sqlx::query_scalar(
+            "SELECT DISTINCT namespace FROM chunks WHERE length(vector) = 0 ORDER BY namespace",
+        )
+        .fetch_all(&self.reader)
+        .await?;
+
+        Ok(namespaces)
+    }
+
+    /// A version's chunks stored without embeddings.
+    pub async fn unembedded_chunks(&self, version_id: &str) -> Result<Vec<ChunkRow>> {
+        let rows = sqlx::query_as::<_, ChunkRow>(
+            "SELECT * FROM chunks WHERE version_id = ? AND length(vector) = 0 ORDER BY id",
+        )
+        .bind(version_id)
+        .fetch_all(&self.reader)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Fill in the embeddings of stored chunks in a single transaction,
+    /// leaving the rest of each row as it is.
+    pub async fn set_chunk_embeddings(&self, embeddings: &[ChunkEmbedding]) -> Result<()> {
+        let mut tx = self.writer.begin().await?;
+
+        for embedding in embeddings {
+            sqlx::query(
+                r#"
+                UPDATE chunks
+                SET vector = ?, embedding_model = ?, embedding_hash = ?,
+                    doc_hash = ?, doc_vector = ?
+                WHERE id = ?
+                "#,
+            )
+            .bind(vector_to_bytes(&embedding.vector))
+            .bind(&embedding.embedding_model)
+            .bind(&embedding.embedding_hash)
+            .bind(&embedding.doc_hash)
+            .bind(embedding.doc_vector.as_deref().map(vector_to_bytes))
+            .bind(&embedding.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get a chunk's documentation embedding, if one was stored.
+    pub async fn get_chunk_doc_vector(&self, id: &str) -> Result<Option<Vec<f32>>> {
+        let bytes: Option<Option<Vec<u8>>> =
+            sqlx::query_scalar("SELECT doc_vector FROM chunks WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.reader)
+                .await?;
+
+        Ok(bytes.flatten().map(|bytes| bytes_to_vector(&bytes)))
+    }
+
+    /// Find chunks in the given namespaces whose name or signature contains
+    /// any of the tokens, keeping only those that pass `filter`.
+    ///
+    /// Returns `(id, vector, doc_vector, matched)` ordered by how many tokens
+    /// matched, best first. Tokens are expected to come from
+    /// [`tokenize`](super::tokens::tokenize).
+    pub async fn keyword_search(
+        &self,
+        namespaces: &[String],
+        tokens: &[String],
+        filter: &ChunkFilter,
+        limit: usize,
+    ) -> Result<Vec<(String, Vec<f32>, Option<Vec<f32>>, usize)>> {
+        if namespaces.is_empty() || tokens.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let matched = vec!["(instr(tokens, ?) > 0)"; tokens.len()].join(" + ");
+        let placeholders = vec!["?"; namespaces.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT id, vector, doc_vector, matched FROM (
+                SELECT id, vector, doc_vector, {} AS matched FROM chunks
+                WHERE namespace IN ({}){}
+            )
+            WHERE matched > 0
+            ORDER BY matched DESC, id
+            LIMIT ?
+            "#,
+            matched,
+            placeholders,
+            filter.sql()
+        );
+
+        let mut query = sqlx::query(&sql);
+        for token in tokens {
+            query = query.bind(format!(" {} ", token));
+        }
+        for namespace in namespaces {
+            query = query.bind(namespace);
+        }
+        for value in filter.binds() {
+            query = query.bind(value);
+        }
+        let rows = query.bind(limit as i64).fetch_all(&self.reader).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let vector_bytes: Vec<u8> = row.get("vector");
+                let doc_vector_bytes: Option<Vec<u8>> = row.get("doc_vector");
+                let matched: i64 = row.get("matched");
+                (
+                    row.get("id"),
+                    bytes_to_vector(&vector_bytes),
+                    doc_vector_bytes.map(|bytes| bytes_to_vector(&bytes)),
+                    matched as usize,
+                )
+            })
+            .collect())
+    }
+
+    /// Find the chunks nearest to `query` among those in `namespaces` that
+    /// pass `filter`.
+    ///
+    /// Scans the stored embeddings of matching chunks, so unlike filtering
+    /// vector store hits afterwards it returns `limit` results whenever that
+    /// many chunks match. `mode` picks which of a chunk's embeddings the
+    /// distance is measured to.
+    pub async fn nearest_chunks(
+        &self,
+        namespaces: &[String],
+        filter: &ChunkFilter,
+        query: &[f32],
+        metric: DistanceMetric,
+        mode: QueryMode,
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        if namespaces.is_empty() || limit == 0 {
+            return Ok(vec![]);
+        }
+
+        // Chunks stored without embeddings (metadata-only runs) have no distance
+        let placeholders = vec!["?"; namespaces.len()].join(", ");
+        let sql = format!(
+            "SELECT id, vector, doc_vector FROM chunks \
+             WHERE namespace IN ({}) AND length(vector) > 0{}",
+            placeholders,
+            filter.sql()
+        );
+
+        let mut statement = sqlx::query(&sql);
+        for namespace in namespaces {
+            statement = statement.bind(namespace);
+        }
+        for value in filter.binds() {
+            statement = statement.bind(value);
+        }
+        let mut rows = statement.fetch(&self.reader);
+
+        // Keep the best `limit`, trimming in batches rather than per row
+        let mut hits: Vec<VectorSearchHit> = Vec::new();
+        while let Some(row) = rows.try_next().await? {
+            let vector_bytes: Vec<u8> = row.get("vector");
+            let doc_vector = row
+                .get::<Option<Vec<u8>>, _>("doc_vector")
+                .map(|bytes| bytes_to_vector(&bytes));
+            hits.push(VectorSearchHit {
+                chunk_id: row.get("id"),
+                distance: mode.distance(
+                    metric,
+                    query,
+                    &bytes_to_vector(&vector_bytes),
+                    doc_vector.as_deref(),
+                ),
+                metric,
+            });
+            if hits.len() >= limit * 2 {
+                hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+                hits.truncate(limit);
+            }
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Those of `ids` whose chunk has a documentation embedding.
+    pub async fn chunks_with_doc_vectors(&self, ids: &[String]) -> Result<HashSet<String>> {
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id FROM chunks WHERE doc_vector IS NOT NULL AND id IN ({})",
+            placeholders
+        );
+        let mut statement = sqlx::query_scalar(&sql);
+        for id in ids {
+            statement = statement.bind(id);
+        }
+        let documented: Vec<String> = statement.fetch_all(&self.reader).await?;
+        Ok(documented.into_iter().collect())
+    }
+
+    /// Which of the chunks with these ids have `public` visibility.
+    pub async fn public_chunk_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id FROM chunks WHERE visibility = 'public' AND id IN ({})",
+            placeholders
+        );
+        let mut statement = sqlx::query_scalar(&sql);
+        for id in ids {
+            statement = statement.bind(id);
+        }
+        let public: Vec<String> = statement.fetch_all(&self.reader).await?;
+        Ok(public.into_iter().collect())
+    }
+
+    /// Find chunk IDs by symbol name, optionally narrowed to a package.
+    pub async fn find_chunk_ids_by_name(
+        &self,
+        name: &str,
+        registry: Option<&str>,
+        package: Option<&str>,
+        version: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.id FROM chunks c
+            JOIN versions v ON c.version_id = v.id
+            JOIN packages p ON v.package_id = p.id
+            WHERE c.name = ?
+              AND (? IS NULL OR p.registry = ?)
+              AND (? IS NULL OR p.name = ?)
+              AND (? IS NULL OR v.version = ?)
+            ORDER BY v.indexed_at DESC
+            "#,
+        )
+        .bind(name)
+        .bind(registry)
+        .bind(registry)
+        .bind(package)
+        .bind(package)
+        .bind(version)
+        .bind(version)
+        .fetch_all(&self.reader)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Symbols of a namespace matching `filter`, ordered by file and line.
+    pub async fn outline_symbols(
+        &self,
+        namespace: &str,
+        filter: &ChunkFilter,
+    ) -> Result<Vec<OutlineSymbol>> {
+        let sql = format!(
+            r#"
+            SELECT name, chunk_type, visibility, signature, file_path, start_line, end_line
+            FROM chunks
+            WHERE namespace = ?{}
+            ORDER BY file_path, start_line, end_line DESC, name
+            "#,
+            filter.sql()
+        );
+
+        let mut query = sqlx::query_as::<_, OutlineSymbol>(&sql).bind(namespace);
+        for value in filter.binds() {
+            query = query.bind(value);
+        }
+
+        Ok(query.fetch_all(&self.reader).await?)
+    }
+
+    /// Entry module recorded for the version a namespace's chunks belong to.
+    pub async fn namespace_entry_point(&self, namespace: &str) -> Result<Option<String>> {
+        let entry_point: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            SELECT v.entry_point FROM versions v
+            WHERE v.id = (SELECT version_id FROM chunks WHERE namespace = ? LIMIT 1)
+            "#,
+        )
+        .bind(namespace)
+        .fetch_optional(&self.reader)
+        .await?;
+
+        Ok(entry_point.flatten())
+    }
+
+    /// Symbols of a namespace matching `filter`, for comparing versions.
+    pub async fn api_symbols(
+        &self,
+        namespace: &str,
+        filter: &ChunkFilter,
+    ) -> Result<Vec<ApiSymbol>> {
+        let sql = format!(
+            r#"
+            SELECT name, parent, chunk_type, signature, docstring, file_path, start_line
+            FROM chunks
+            WHERE namespace = ?{}
+            ORDER BY file_path, start_line, name
+            "#,
+            filter.sql()
+        );
+
+        let mut query = sqlx::query_as::<_, ApiSymbol>(&sql).bind(namespace);
+        for value in filter.binds() {
+            query = query.bind(value);
+        }
+
+        Ok(query.fetch_all(&self.reader).await?)
+    }
+
     /// Get existing chunks for deduplication.
     pub async fn get_chunks_for_dedup(&self, namespace: &str) -> Result<Vec<ExistingChunk>> {
         let rows = sqlx::query("SELECT content_hash, vector FROM chunks WHERE namespace = ?")
             .bind(namespace)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.reader)
             .await?;
 
         let chunks = rows
@@ -554,44 +1577,247 @@ impl LocalDb {
         Ok(chunks)
     }
 
+    /// Stored vectors of chunks embedded by `model`, by the hash of the
+    /// text they were embedded from.
+    ///
+    /// Hashes with no such chunk are left out of the map.
+    pub async fn cached_embeddings(
+        &self,
+        model: &str,
+        embedding_hashes: &[String],
+    ) -> Result<HashMap<String, Vec<f32>>> {
+        self.cached_vectors("embedding_hash", "vector", model, embedding_hashes)
+            .await
+    }
+
+    /// Stored documentation vectors embedded by `model`, by doc hash.
+    ///
+    /// Hashes with no such chunk are left out of the map.
+    pub async fn cached_doc_embeddings(
+        &self,
+        model: &str,
+        doc_hashes: &[String],
+    ) -> Result<HashMap<String, Vec<f32>>> {
+        self.cached_vectors("doc_hash", "doc_vector", model, doc_hashes)
+            .await
+    }
+
+    async fn cached_vectors(
+        &self,
+        hash_column: &str,
+        vector_column: &str,
+        model: &str,
+        hashes: &[String],
+    ) -> Result<HashMap<String, Vec<f32>>> {
+        // Stay well under SQLite's bound parameter limit
+        const BATCH_SIZE: usize = 500;
+
+        let mut vectors = HashMap::new();
+        for batch in hashes.chunks(BATCH_SIZE) {
+            let placeholders = vec!["?"; batch.len()].join(", ");
+            let sql = format!(
+                "SELECT {hash} AS hash, {vector} AS vector FROM chunks \
+                 WHERE embedding_model = ? AND {vector} IS NOT NULL AND {hash} IN ({}) \
+                 GROUP BY {hash}",
+                placeholders,
+                hash = hash_column,
+                vector = vector_column,
+            );
+
+            let mut query = sqlx::query(&sql).bind(model);
+            for hash in batch {
+                query = query.bind(hash);
+            }
+
+            for row in query.fetch_all(&self.reader).await? {
+                let vector_bytes: Vec<u8> = row.get("vector");
+                vectors.insert(row.get("hash"), bytes_to_vector(&vector_bytes));
+            }
+        }
+
+        Ok(vectors)
+    }
+
     /// Get all distinct namespaces.
     pub async fn get_namespaces(&self) -> Result<Vec<String>> {
         let namespaces: Vec<String> = sqlx::query_scalar("SELECT DISTINCT namespace FROM chunks")
-            .fetch_all(&self.pool)
+            .fetch_all(&self.reader)
             .await?;
 
         Ok(namespaces)
     }
 
+    /// Summary of the indexed chunks and versions that changes whenever a
+    /// package is indexed or removed, so results computed earlier can be
+    /// told apart from stale ones.
+    pub async fn index_fingerprint(&self) -> Result<String> {
+        let (chunks, last_chunk): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM chunks")
+                .fetch_one(&self.reader)
+                .await?;
+        let (versions, last_indexed): (i64, String) =
+            sqlx::query_as("SELECT COUNT(*), COALESCE(MAX(indexed_at), '') FROM versions")
+                .fetch_one(&self.reader)
+                .await?;
+
+        Ok(format!(
+            "{}:{}:{}:{}",
+            chunks, last_chunk, versions, last_indexed
+        ))
+    }
+
+    // ==================== Skip Rules ====================
+
+    /// Add a skip rule, a glob on `registry:name`. Returns false if it
+    /// already existed.
+    pub async fn add_skip_rule(&self, pattern: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let result =
+            sqlx::query("INSERT OR IGNORE INTO skip_rules (pattern, created_at) VALUES (?, ?)")
+                .bind(pattern)
+                .bind(&now)
+                .execute(&self.writer)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove a skip rule. Returns false if there was no such rule.
+    pub async fn remove_skip_rule(&self, pattern: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM skip_rules WHERE pattern = ?")
+            .bind(pattern)
+            .execute(&self.writer)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List skip rule patterns.
+    pub async fn list_skip_rules(&self) -> Result<Vec<String>> {
+        let patterns = sqlx::query_scalar("SELECT pattern FROM skip_rules ORDER BY pattern")
+            .fetch_all(&self.reader)
+            .await?;
+
+        Ok(patterns)
+    }
+
+    /// The first skip rule matching a package, if any.
+    ///
+    /// Rules are matched with SQLite `GLOB` (case-sensitive, `*` also
+    /// matches `/`) against `registry:name`.
+    pub async fn matching_skip_rule(&self, registry: &str, name: &str) -> Result<Option<String>> {
+        let pattern = sqlx::query_scalar(
+            "SELECT pattern FROM skip_rules WHERE ? GLOB pattern ORDER BY pattern LIMIT 1",
+        )
+        .bind(format!("{}:{}", registry, name))
+        .fetch_optional(&self.reader)
+        .await?;
+
+        Ok(pattern)
+    }
+
+    // ==================== Index Metadata ====================
+
+    /// Embedding dimension of the vectors in this index.
+    ///
+    /// `None` for an index with no embedded chunks that hasn't recorded one
+    /// yet. Indexes built before the dimension was recorded hold
+    /// [`VECTOR_DIM`] vectors.
+    pub async fn embedding_dimensions(&self) -> Result<Option<usize>> {
+        let stored: Option<String> =
+            sqlx::query_scalar("SELECT value FROM index_meta WHERE key = ?")
+                .bind(EMBEDDING_DIMENSIONS_KEY)
+                .fetch_optional(&self.reader)
+                .await?;
+
+        if let Some(value) = stored {
+            let dimensions = value
+                .parse()
+                .context("Invalid embedding dimension stored in index")?;
+            return Ok(Some(dimensions));
+        }
+
+        // Chunks stored without embeddings say nothing about the dimension
+        let has_vectors: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM chunks WHERE length(vector) > 0)")
+                .fetch_one(&self.reader)
+                .await?;
+        Ok(has_vectors.then_some(VECTOR_DIM as usize))
+    }
+
+    /// Fail with [`DimensionMismatch`] if the index holds embeddings of
+    /// another dimension.
+    pub async fn check_embedding_dimensions(&self, dimensions: usize) -> Result<()> {
+        match self.embedding_dimensions().await? {
+            Some(index) if index != dimensions => Err(DimensionMismatch {
+                index,
+                requested: dimensions,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check `dimensions` against the index, recording it if none is yet.
+    pub async fn record_embedding_dimensions(&self, dimensions: usize) -> Result<()> {
+        self.check_embedding_dimensions(dimensions).await?;
+
+        sqlx::query("INSERT OR IGNORE INTO index_meta (key, value) VALUES (?, ?)")
+            .bind(EMBEDDING_DIMENSIONS_KEY)
+            .bind(dimensions.to_string())
+            .execute(&self.writer)
+            .await?;
+
+        // Another writer may have recorded a different dimension first
+        self.check_embedding_dimensions(dimensions).await
+    }
+
+    /// Forget the recorded embedding dimension once no chunks remain, so the
+    /// next run may embed with another one.
+    pub async fn clear_embedding_dimensions_if_empty(&self) -> Result<()> {
+        sqlx::query("DELETE FROM index_meta WHERE key = ? AND NOT EXISTS (SELECT 1 FROM chunks)")
+            .bind(EMBEDDING_DIMENSIONS_KEY)
+            .execute(&self.writer)
+            .await?;
+
+        Ok(())
+    }
+
     // ==================== Stats ====================
 
     /// Get index statistics.
     pub async fn get_stats(&self) -> Result<IndexStats> {
         let package_count: i32 = sqlx::query_scalar("SELECT COUNT(*) FROM packages")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.reader)
             .await?;
 
         let version_count: i32 = sqlx::query_scalar("SELECT COUNT(*) FROM versions")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.reader)
             .await?;
 
         let indexed_count: i32 =
             sqlx::query_scalar("SELECT COUNT(*) FROM versions WHERE status = 'indexed'")
-                .fetch_one(&self.pool)
+                .fetch_one(&self.reader)
                 .await?;
 
         let failed_count: i32 =
             sqlx::query_scalar("SELECT COUNT(*) FROM versions WHERE status = 'failed'")
-                .fetch_one(&self.pool)
+                .fetch_one(&self.reader)
                 .await?;
 
         let skipped_count: i32 =
             sqlx::query_scalar("SELECT COUNT(*) FROM versions WHERE status = 'skipped'")
-                .fetch_one(&self.pool)
+                .fetch_one(&self.reader)
+                .await?;
+
+        let empty_count: i32 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM versions WHERE status = 'empty'")
+                .fetch_one(&self.reader)
                 .await?;
 
         let chunk_count: i32 = sqlx::query_scalar("SELECT COUNT(*) FROM chunks")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.reader)
             .await?;
 
         Ok(IndexStats {
@@ -600,7 +1826,503 @@ impl LocalDb {
             indexed_count: indexed_count as u32,
             failed_count: failed_count as u32,
             skipped_count: skipped_count as u32,
+            empty_count: empty_count as u32,
             chunk_count: chunk_count as u32,
         })
     }
 }
+
+/// Value of `packages.tenant`: the tenant id, or empty for public packages.
+fn tenant_key(tenant: Option<TenantId>) -> String {
+    tenant.map(|t| t.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn chunk(version_id: &str, n: usize) -> CreateChunk {
+        CreateChunk {
+            id: format!("{}-{}", version_id, n),
+            version_id: version_id.to_string(),
+            namespace: format!("npm/stress/{}", version_id),
+            chunk_type: "function".to_string(),
+            name: format!("fn{}", n),
+            file_path: "index.js".to_string(),
+            start_line: 1,
+            end_line: 2,
+            visibility: "public".to_string(),
+            signature: None,
+            params: Vec::new(),
+            returns: None,
+            docstring: None,
+            snippet: String::new(),
+            storage_key: format!("key{}", n),
+            content_hash: format!("hash{}", n),
+            vector: vec![0.5; 16],
+            embedding_model: "test-model".to_string(),
+            file_storage_key: None,
+            start_byte: 0,
+            end_byte: 0,
+            deprecated: false,
+            deprecation_message: None,
+            parent: None,
+            embedding_hash: None,
+            doc_hash: None,
+            doc_vector: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_reads_during_indexing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.sqlite");
+
+        // Separate handles, like the indexer and search services
+        let writer = Arc::new(LocalDb::open(&path).await.unwrap());
+        let reader = Arc::new(LocalDb::open_with_readers(&path, 4).await.unwrap());
+
+        let package_id = writer
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "stress".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+
+        let indexing = {
+            let db = writer.clone();
+            tokio::spawn(async move {
+                for v in 0..20 {
+                    let (version_id, _) = db
+                        .get_or_create_version(&package_id, &format!("1.0.{}", v))
+                        .await?;
+                    let chunks: Vec<_> = (0..50).map(|n| chunk(&version_id, n)).collect();
+                    db.insert_chunks(&chunks).await?;
+                    db.mark_version_indexed(&version_id, chunks.len() as i32)
+                        .await?;
+                }
+                anyhow::Ok(())
+            })
+        };
+
+        let searches: Vec<_> = (0..8)
+            .map(|_| {
+                let db = reader.clone();
+                tokio::spawn(async move {
+                    for _ in 0..25 {
+                        db.get_stats().await?;
+                        db.list_versions().await?;
+                        db.find_chunk_ids_by_name("fn7", Some("npm"), Some("stress"), None)
+                            .await?;
+                    }
+                    anyhow::Ok(())
+                })
+            })
+            .collect();
+
+        indexing.await.unwrap().unwrap();
+        for search in searches {
+            search.await.unwrap().unwrap();
+        }
+
+        let stats = reader.get_stats().await.unwrap();
+        assert_eq!(stats.indexed_count, 20);
+        assert_eq!(stats.chunk_count, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_are_counted() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        let package_id = db
+            .get_or_create_package(&CreatePackage {
+                registry: "pypi".to_string(),
+                name: "no-sdist".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            db.mark_version_failed(&version_id, FailureKind::Download, "connection reset")
+                .await
+                .unwrap();
+            db.mark_version_pending(&version_id).await.unwrap();
+        }
+        db.mark_version_failed(&version_id, FailureKind::NoSource, "no source distribution")
+            .await
+            .unwrap();
+
+        let failed = db
+            .list_versions_by_status(VersionStatus::Failed)
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].attempts, 4);
+        assert!(failed[0].failed_at.is_some());
+        assert_eq!(failed[0].failure_kind(), Some(FailureKind::NoSource));
+
+        // A successful index clears the failure history
+        db.mark_version_indexed(&version_id, 10).await.unwrap();
+        let ver = db
+            .find_version("pypi", "no-sdist", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ver.attempts, 0);
+        assert!(ver.failed_at.is_none());
+        assert!(ver.failure_kind().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_skip_rules() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        assert!(db.add_skip_rule("npm:@types/*").await.unwrap());
+        assert!(!db.add_skip_rule("npm:@types/*").await.unwrap());
+        assert!(db.add_skip_rule("maven:*").await.unwrap());
+        assert_eq!(
+            db.list_skip_rules().await.unwrap(),
+            vec!["maven:*", "npm:@types/*"]
+        );
+
+        let rule = db.matching_skip_rule("npm", "@types/node").await.unwrap();
+        assert_eq!(rule.as_deref(), Some("npm:@types/*"));
+        let rule = db
+            .matching_skip_rule("maven", "org.slf4j:slf4j-api")
+            .await
+            .unwrap();
+        assert_eq!(rule.as_deref(), Some("maven:*"));
+        assert!(
+            db.matching_skip_rule("npm", "lodash")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        assert!(db.remove_skip_rule("maven:*").await.unwrap());
+        assert!(!db.remove_skip_rule("maven:*").await.unwrap());
+        assert!(
+            db.matching_skip_rule("maven", "org.slf4j:slf4j-api")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_package_metadata_is_persisted() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        let package_id = db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "zod".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        db.get_or_create_version(&package_id, "3.0.0")
+            .await
+            .unwrap();
+
+        db.update_package_metadata(
+            "npm",
+            "zod",
+            None,
+            &PackageMetadata {
+                description: Some("TypeScript-first schema validation".to_string()),
+                license: Some("MIT".to_string()),
+                repository: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Missing values don't erase stored ones
+        db.update_package_metadata(
+            "npm",
+            "zod",
+            None,
+            &PackageMetadata {
+                repository: Some("https://github.com/colinhacks/zod".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let ver = db
+            .find_version("npm", "zod", "3.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ver.description.as_deref(),
+            Some("TypeScript-first schema validation")
+        );
+        assert_eq!(ver.license.as_deref(), Some("MIT"));
+        assert_eq!(
+            ver.repository.as_deref(),
+            Some("https://github.com/colinhacks/zod")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_packages_are_kept_per_tenant() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+        let tenant = TenantId::new_v4();
+
+        let package = |tenant| CreatePackage {
+            registry: "npm".to_string(),
+            name: "utils".to_string(),
+            description: None,
+            tenant,
+        };
+        let public_id = db.get_or_create_package(&package(None)).await.unwrap();
+        let private_id = db
+            .get_or_create_package(&package(Some(tenant)))
+            .await
+            .unwrap();
+        assert_ne!(public_id, private_id);
+        assert_eq!(
+            db.get_or_create_package(&package(Some(tenant)))
+                .await
+                .unwrap(),
+            private_id
+        );
+        for id in [&public_id, &private_id] {
+            db.get_or_create_version(id, "1.0.0").await.unwrap();
+        }
+
+        let public = db
+            .find_version("npm", "utils", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(public.package_id, public_id);
+        assert_eq!(public.namespace().to_string(), "npm/utils/1.0.0");
+
+        let private = db
+            .find_version("npm", "utils", "1.0.0", Some(tenant))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(private.package_id, private_id);
+        assert_eq!(private.tenant(), Some(tenant));
+        assert_eq!(
+            private.namespace().to_string(),
+            format!("private/{}/npm/utils/1.0.0", tenant)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keyword_search_matches_identifier_words() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        let package_id = db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "stress".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+
+        let named = |n: usize, name: &str, signature: Option<&str>| CreateChunk {
+            name: name.to_string(),
+            signature: signature.map(str::to_string),
+            ..chunk(&version_id, n)
+        };
+        db.insert_chunks(&[
+            named(0, "searchTimeout", None),
+            named(1, "search", Some("search(query: string)")),
+            named(2, "parseHeaders", None),
+        ])
+        .await
+        .unwrap();
+
+        let namespaces = vec![format!("npm/stress/{}", version_id)];
+        let tokens = vec!["search".to_string(), "timeout".to_string()];
+        let hits = db
+            .keyword_search(&namespaces, &tokens, &ChunkFilter::default(), 10)
+            .await
+            .unwrap();
+
+        let matched: Vec<_> = hits.iter().map(|(id, _, _, m)| (id.clone(), *m)).collect();
+        assert_eq!(
+            matched,
+            vec![
+                (format!("{}-0", version_id), 2),
+                (format!("{}-1", version_id), 1)
+            ]
+        );
+        assert_eq!(hits[0].1, vec![0.5; 16]);
+
+        // Words match whole tokens only, and other namespaces are excluded
+        let partial = vec!["time".to_string()];
+        assert!(
+            db.keyword_search(&namespaces, &partial, &ChunkFilter::default(), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let other = vec!["npm/stress/2.0.0".to_string()];
+        assert!(
+            db.keyword_search(&other, &tokens, &ChunkFilter::default(), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dimensions_are_recorded_and_enforced() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        // An empty index accepts any dimension until one is recorded
+        assert_eq!(db.embedding_dimensions().await.unwrap(), None);
+        db.check_embedding_dimensions(768).await.unwrap();
+
+        db.record_embedding_dimensions(768).await.unwrap();
+        assert_eq!(db.embedding_dimensions().await.unwrap(), Some(768));
+        db.record_embedding_dimensions(768).await.unwrap();
+
+        let err = db.check_embedding_dimensions(1536).await.unwrap_err();
+        let mismatch = err.downcast_ref::<DimensionMismatch>().unwrap();
+        assert_eq!((mismatch.index, mismatch.requested), (768, 1536));
+        assert!(db.record_embedding_dimensions(1536).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unrecorded_index_with_chunks_has_default_dimension() {
+        let dir = tempdir().unwrap();
+        let db = LocalDb::open(&dir.path().join("db.sqlite")).await.unwrap();
+
+        let package_id = db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "legacy".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+        db.insert_chunk(&chunk(&version_id, 0)).await.unwrap();
+
+        assert_eq!(
+            db.embedding_dimensions().await.unwrap(),
+            Some(VECTOR_DIM as usize)
+        );
+        assert!(db.check_embedding_dimensions(768).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_old_schema_upgrades_cleanly() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.sqlite");
+
+        // A database from before retries, byte ranges, tokens, deprecation
+        // and the migrations table
+        {
+            let pool = SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect_with(
+                    SqliteConnectOptions::new()
+                        .filename(&path)
+                        .create_if_missing(true),
+                )
+                .await
+                .unwrap();
+            for statement in [
+                "CREATE TABLE packages (id TEXT PRIMARY KEY, registry TEXT NOT NULL, \
+                 name TEXT NOT NULL, description TEXT, created_at TEXT NOT NULL, \
+                 UNIQUE(registry, name))",
+                "CREATE TABLE versions (id TEXT PRIMARY KEY, package_id TEXT NOT NULL, \
+                 version TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'pending', \
+                 error_message TEXT, chunk_count INTEGER NOT NULL DEFAULT 0, indexed_at TEXT, \
+                 created_at TEXT NOT NULL, UNIQUE(package_id, version))",
+                "CREATE TABLE chunks (id TEXT PRIMARY KEY, version_id TEXT NOT NULL, \
+                 namespace TEXT NOT NULL, chunk_type TEXT NOT NULL, name TEXT NOT NULL, \
+                 file_path TEXT NOT NULL, start_line INTEGER NOT NULL, end_line INTEGER NOT NULL, \
+                 visibility TEXT NOT NULL, signature TEXT, docstring TEXT, snippet TEXT NOT NULL, \
+                 storage_key TEXT NOT NULL, content_hash TEXT NOT NULL, vector BLOB NOT NULL)",
+                "INSERT INTO packages VALUES ('p1', 'npm', 'legacy', NULL, '2024-01-01T00:00:00Z')",
+                "INSERT INTO versions VALUES ('v1', 'p1', '1.0.0', 'indexed', NULL, 1, NULL, \
+                 '2024-01-01T00:00:00Z')",
+                "INSERT INTO chunks VALUES ('c1', 'v1', 'npm/legacy/1.0.0', 'function', \
+                 'parseJson', 'index.js', 1, 2, 'public', 'function parseJson(text)', NULL, '', \
+                 'key', 'hash', x'00')",
+            ] {
+                sqlx::query(statement).execute(&pool).await.unwrap();
+            }
+            pool.close().await;
+        }
+
+        let db = LocalDb::open(&path).await.unwrap();
+
+        let version = db
+            .find_version("npm", "legacy", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((version.attempts, version.files_seen), (0, 0));
+
+        let chunks = db
+            .get_chunks_by_namespace("npm/legacy/1.0.0")
+            .await
+            .unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(!chunks[0].deprecated);
+        let tokens: String = sqlx::query_scalar("SELECT tokens FROM chunks WHERE id = 'c1'")
+            .fetch_one(&db.writer)
+            .await
+            .unwrap();
+        assert!(tokens.contains("parse"));
+
+        let applied: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, applied_at FROM schema_migrations ORDER BY id")
+                .fetch_all(&db.writer)
+                .await
+                .unwrap();
+        let ids: Vec<_> = applied.iter().map(|(id, _)| id.as_str()).collect();
+        let expected: Vec<_> = MIGRATIONS.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, expected);
+        drop(db);
+
+        // Reopening applies nothing
+        let db = LocalDb::open(&path).await.unwrap();
+        let reapplied: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, applied_at FROM schema_migrations ORDER BY id")
+                .fetch_all(&db.writer)
+                .await
+                .unwrap();
+        assert_eq!(reapplied, applied);
+    }
+}
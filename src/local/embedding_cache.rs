@@ -0,0 +1,82 @@
+//! Bounded in-memory cache of embeddings, keyed by model and content hash.
+//!
+//! Sits in front of the vectors already stored in SQLite, so content seen
+//! earlier in a run (shared headers, re-exported code) isn't looked up or
+//! embedded again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Default number of cached embeddings (~25 MB at 1536 dimensions).
+pub const DEFAULT_CAPACITY: usize = 4096;
+
+type Key = (String, String);
+
+/// Embeddings cache evicting the oldest entry once full.
+pub struct EmbeddingCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    vectors: HashMap<Key, Vec<f32>>,
+    order: VecDeque<Key>,
+}
+
+impl EmbeddingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Cached embedding of `content_hash` by `model`.
+    pub fn get(&self, model: &str, content_hash: &str) -> Option<Vec<f32>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .vectors
+            .get(&(model.to_string(), content_hash.to_string()))
+            .cloned()
+    }
+
+    /// Cache an embedding, evicting the oldest entries beyond capacity.
+    pub fn insert(&self, model: &str, content_hash: &str, vector: Vec<f32>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = (model.to_string(), content_hash.to_string());
+        let mut inner = self.inner.lock().unwrap();
+        if inner.vectors.insert(key.clone(), vector).is_none() {
+            inner.order.push_back(key);
+        }
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.vectors.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyed_by_model_and_bounded() {
+        let cache = EmbeddingCache::new(2);
+        cache.insert("small", "a", vec![1.0]);
+        cache.insert("small", "b", vec![2.0]);
+
+        assert_eq!(cache.get("small", "a"), Some(vec![1.0]));
+        assert_eq!(cache.get("large", "a"), None);
+
+        // Evicts the oldest entry
+        cache.insert("small", "c", vec![3.0]);
+        assert_eq!(cache.get("small", "a"), None);
+        assert_eq!(cache.get("small", "b"), Some(vec![2.0]));
+        assert_eq!(cache.get("small", "c"), Some(vec![3.0]));
+    }
+}
@@ -0,0 +1,426 @@
+//! Local HTTP/JSON search API.
+//!
+//! Read-only by default; `POST /index` is only routed when indexing is
+//! explicitly allowed.
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::registry::RegistryError;
+use crate::types::Registry;
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::info;
+
+use super::indexer::LocalIndexer;
+use super::models::{
+    ChunkFilter, PreferredPackages, SearchOptions, SearchResult, TestFilter, VersionWithPackage,
+};
+use super::search::LocalSearch;
+
+/// Shared state for request handlers.
+#[derive(Clone)]
+struct AppState {
+    search: Arc<LocalSearch>,
+    indexer: Arc<LocalIndexer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    package: Option<String>,
+    registry: Option<String>,
+    version: Option<String>,
+    path: Option<String>,
+    #[serde(default)]
+    tests: TestFilter,
+    /// Comma-separated `registry:name` packages to boost.
+    #[serde(default)]
+    prefer: Option<String>,
+    /// Overrides the configured `min_score`.
+    #[serde(default)]
+    min_score: Option<f32>,
+    /// Overrides the configured `snippet_length`.
+    #[serde(default)]
+    snippet_length: Option<usize>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexRequest {
+    registry: String,
+    package: String,
+    /// Defaults to the latest published version.
+    #[serde(default)]
+    version: Option<String>,
+}
+
+/// An error rendered as `{"error": "..."}` with a status code.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+/// Build the API router.
+fn router(state: AppState, allow_index: bool) -> Router {
+    let mut router = Router::new()
+        .route("/search", get(search))
+        .route("/packages", get(packages))
+        .route("/chunks/:id/code", get(chunk_code));
+
+    if allow_index {
+        router = router.route("/index", post(index));
+    }
+
+    router.with_state(state)
+}
+
+async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<SearchResult>>, ApiError> {
+    let prefer: Vec<&str> = params
+        .prefer
+        .iter()
+        .flat_map(|p| p.split(','))
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let preferred = PreferredPackages::parse(&prefer)
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("Invalid prefer: {}", e)))?;
+
+    let response = state
+        .search
+        .search(
+            &params.q,
+            &SearchOptions {
+                package: params.package.as_deref(),
+                registry: params.registry.as_deref(),
+                version: params.version.as_deref(),
+                filter: ChunkFilter::new(params.path.as_deref(), params.tests),
+                preferred,
+                mode: None,
+                min_score: params.min_score,
+                snippet_length: params.snippet_length,
+                limit: params.limit,
+            },
+        )
+        .await?;
+
+    // Nothing relevant enough is an empty answer, not a failure
+    Ok(Json(response.results))
+}
+
+async fn packages(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<VersionWithPackage>>, ApiError> {
+    Ok(Json(state.search.list_versions().await?))
+}
+
+/// A chunk's code, or with a `Range: bytes=start-end` header just those
+/// bytes of it.
+async fn chunk_code(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let chunk = state
+        .search
+        .get_chunk(&id)
+        .await?
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Chunk not found: {}", id)))?;
+
+    let content_type = (header::CONTENT_TYPE, "text/plain; charset=utf-8");
+    let Some((start, end)) = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_range)
+    else {
+        let code = state.search.get_code(&chunk.storage_key).await?;
+        return Ok(([content_type], code).into_response());
+    };
+
+    let bytes = state
+        .search
+        .get_code_range(&chunk.storage_key, start, end)
+        .await?;
+    if bytes.is_empty() {
+        return Err(ApiError(
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            format!("Range starts past the end of chunk {}", id),
+        ));
+    }
+    let content_range = format!("bytes {}-{}/*", start, start + bytes.len() - 1);
+    Ok((
+        StatusCode::PARTIAL_CONTENT,
+        [
+            content_type,
+            (header::CONTENT_RANGE, content_range.as_str()),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Parse a single `bytes=start-end` or `bytes=start-` range into a
+/// half-open `[start, end)` byte range.
+fn parse_range(value: &str) -> Option<(usize, usize)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.trim().parse().ok()?;
+    let end = match end.trim() {
+        "" => usize::MAX,
+        end => end.parse::<usize>().ok()?.checked_add(1)?,
+    };
+    (start < end).then_some((start, end))
+}
+
+async fn index(
+    State(state): State<AppState>,
+    Json(req): Json<IndexRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let registry =
+        Registry::from_str(&req.registry).map_err(|e| ApiError(StatusCode::BAD_REQUEST, e))?;
+
+    let version = state
+        .indexer
+        .registry(registry)
+        .resolve_version(&req.package, req.version.as_deref())
+        .await
+        .map_err(|e| match e {
+            RegistryError::PackageNotFound(_) | RegistryError::NoPublishedVersion(_) => {
+                ApiError(StatusCode::NOT_FOUND, e.to_string())
+            }
+            e => anyhow::Error::from(e).into(),
+        })?;
+
+    let result = state
+        .indexer
+        .index_package(registry, &req.package, &version)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "version": version,
+        "version_id": result.version_id,
+        "chunks_indexed": result.chunks_indexed,
+        "files_processed": result.files_processed,
+        "files_skipped": result.files_skipped,
+        "skipped": result.skipped,
+        "empty": result.is_empty(),
+        "truncated": result.truncated,
+        "embeddings_reused": result.embeddings_reused,
+        "embedding_tokens": result.embedding_usage.tokens,
+        "embedding_tokens_reused": result.embedding_usage.reused_tokens,
+        "denied_license": result.denied_license,
+        "skip_rule": result.skip_rule,
+    })))
+}
+
+/// Run the local HTTP API until Ctrl-C.
+pub async fn run_local(
+    index_dir: &std::path::Path,
+    addr: SocketAddr,
+    allow_index: bool,
+) -> Result<()> {
+    let (search, indexer) = super::open_services(index_dir).await?;
+    let state = AppState {
+        search: Arc::new(search),
+        indexer,
+    };
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(addr = %listener.local_addr()?, allow_index, "serving HTTP API");
+
+    axum::serve(listener, router(state, allow_index))
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::models::CreateChunk;
+    use crate::local::models::CreatePackage;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    const CODE: &str = "function debounce(fn, wait) {\n  return fn;\n}\n";
+
+    /// A router over an index holding one chunk, `debounce`.
+    async fn seeded_router(dir: &std::path::Path, allow_index: bool) -> Router {
+        let indexer = LocalIndexer::new(dir).await.unwrap();
+        let db = indexer.db();
+        let package_id = db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "lodash".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = db
+            .get_or_create_version(&package_id, "4.17.21")
+            .await
+            .unwrap();
+        let namespace = "npm/lodash/4.17.21";
+        let storage_key = indexer
+            .storage()
+            .put(namespace, CODE.as_bytes())
+            .await
+            .unwrap();
+        db.insert_chunk(&CreateChunk {
+            id: "debounce".to_string(),
+            version_id,
+            namespace: namespace.to_string(),
+            chunk_type: "function".to_string(),
+            name: "debounce".to_string(),
+            file_path: "debounce.js".to_string(),
+            start_line: 1,
+            end_line: 3,
+            visibility: "public".to_string(),
+            signature: None,
+            params: Vec::new(),
+            returns: None,
+            docstring: None,
+            snippet: String::new(),
+            storage_key,
+            content_hash: "debounce".to_string(),
+            vector: Vec::new(),
+            embedding_model: "test-model".to_string(),
+            file_storage_key: None,
+            start_byte: 0,
+            end_byte: CODE.len(),
+            deprecated: false,
+            deprecation_message: None,
+            parent: None,
+            embedding_hash: None,
+            doc_hash: None,
+            doc_vector: None,
+        })
+        .await
+        .unwrap();
+
+        let state = AppState {
+            search: Arc::new(LocalSearch::new(dir).await.unwrap()),
+            indexer: Arc::new(indexer),
+        };
+        router(state, allow_index)
+    }
+
+    async fn get(
+        router: Router,
+        uri: &str,
+        range: Option<&str>,
+    ) -> (StatusCode, HeaderMap, String) {
+        let mut request = Request::get(uri);
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        let response = router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_packages() {
+        let dir = tempdir().unwrap();
+        let router = seeded_router(dir.path(), false).await;
+
+        let (status, _, body) = get(router, "/packages", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let versions: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(versions[0]["name"], "lodash");
+        assert_eq!(versions[0]["version"], "4.17.21");
+    }
+
+    #[tokio::test]
+    async fn test_chunk_code() {
+        let dir = tempdir().unwrap();
+        let router = seeded_router(dir.path(), false).await;
+
+        let (status, headers, body) = get(router.clone(), "/chunks/debounce/code", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(body, CODE);
+
+        let (status, _, body) = get(router, "/chunks/missing/code", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains("Chunk not found: missing"));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_code_range() {
+        let dir = tempdir().unwrap();
+        let router = seeded_router(dir.path(), false).await;
+
+        let (status, headers, body) =
+            get(router.clone(), "/chunks/debounce/code", Some("bytes=9-16")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes 9-16/*");
+        assert_eq!(body, "debounce");
+
+        // Open-ended ranges run to the end of the code
+        let (status, _, body) =
+            get(router.clone(), "/chunks/debounce/code", Some("bytes=32-")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body, CODE[32..]);
+
+        let (status, _, _) = get(router, "/chunks/debounce/code", Some("bytes=500-")).await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    #[tokio::test]
+    async fn test_index_only_routed_when_allowed() {
+        let dir = tempdir().unwrap();
+        let router = seeded_router(dir.path(), false).await;
+
+        let request = Request::post("/index")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"registry": "npm", "package": "lodash"}"#))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9"), Some((0, 10)));
+        assert_eq!(parse_range("bytes=5-"), Some((5, usize::MAX)));
+        assert_eq!(parse_range("bytes=9-3"), None);
+        assert_eq!(parse_range("bytes=-5"), None);
+        assert_eq!(parse_range("items=0-9"), None);
+    }
+}
@@ -6,29 +6,89 @@
 //! 3. Generating embeddings
 //! 4. Storing vectors and blobs locally
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use crate::indexer::{CodeChunk, Language, get_parser};
-use crate::registry::{PackageFile, RegistryClients};
-use crate::types::Registry;
+use crate::indexer::{
+    CodeChunk, Language, TraitImpl, doc_embedding_text, embedding_text, get_parser, name_header,
+};
+use crate::registry::{
+    PackageFile, RegistryCache, RegistryClients, RegistryError, RegistryLimits, entry_point,
+    rank_suggestions,
+};
+use crate::types::{ChunkType, Registry, TenantId};
 use anyhow::{Context, Result};
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
-use tracing::info;
-use uuid::Uuid;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
-use super::LocalConfig;
+use super::batching;
 use super::db::LocalDb;
-use super::models::{CreateChunk, CreatePackage, VectorRecord};
+use super::embedding_cache::{self, EmbeddingCache};
+use super::lock::IndexLock;
+use super::models::{
+    ChunkEmbedding, CreateChunk, CreatePackage, FailureKind, NamespaceSpec, PackageMetadata,
+    VectorAttributes, VectorRecord, VersionStatus, VersionWithPackage, chunk_id,
+};
 use super::storage::LocalStorage;
+use super::transform::{self, ChunkTransformer};
 use super::vector::VectorStore;
+use super::{LicenseAction, LocalConfig};
 
 /// Local indexer service.
 pub struct LocalIndexer {
+    index_dir: PathBuf,
     db: LocalDb,
     storage: LocalStorage,
     vectors: VectorStore,
     config: LocalConfig,
+    registry_cache: Option<RegistryCache>,
+    /// Per-registry request lanes, shared by concurrent index runs.
+    registry_limits: RegistryLimits,
+    /// HTTP client with the configured proxy and CA settings, shared by
+    /// registry and embedding requests.
+    http: reqwest::Client,
+    embedding_cache: EmbeddingCache,
+    events: Option<mpsc::UnboundedSender<IndexEvent>>,
+    /// Run in order on every parsed chunk (see [`transform`]).
+    transformers: Vec<Box<dyn ChunkTransformer>>,
+    /// Tenant packages are indexed for, as private packages
+    tenant: Option<TenantId>,
+    /// Store chunks without embedding them (see [`Self::with_metadata_only`])
+    metadata_only: bool,
+}
+
+/// Stage of the indexing pipeline a package has reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexPhase {
+    Downloading,
+    Parsing,
+    Embedding,
+    Storing,
+}
+
+impl std::fmt::Display for IndexPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Downloading => write!(f, "downloading"),
+            Self::Parsing => write!(f, "parsing"),
+            Self::Embedding => write!(f, "embedding"),
+            Self::Storing => write!(f, "storing"),
+        }
+    }
+}
+
+/// A package entering a pipeline phase, for progress reporting.
+#[derive(Debug, Clone)]
+pub struct IndexEvent {
+    pub registry: Registry,
+    pub name: String,
+    pub version: String,
+    pub phase: IndexPhase,
 }
 
 /// Result of indexing a package version.
@@ -37,24 +97,381 @@ pub struct IndexResult {
     pub version_id: String,
     pub chunks_indexed: usize,
     pub files_processed: usize,
+    /// Downloaded files not parsed (unsupported, excluded or over the file cap)
+    pub files_skipped: usize,
     /// True if this version was already indexed/skipped
     pub skipped: bool,
+    /// True if a per-package file or chunk cap cut indexing short
+    pub truncated: bool,
+    /// Indexed chunks whose content was already embedded (e.g. unchanged
+    /// since an earlier version), so no embeddings request was made for it
+    pub embeddings_reused: usize,
+    /// Text sent to the embeddings API, and what reuse saved
+    pub embedding_usage: EmbeddingUsage,
+    /// The package's license, if it is on the license denylist
+    pub denied_license: Option<String>,
+    /// The skip rule the package matched, if it was skipped by one
+    pub skip_rule: Option<String>,
+}
+
+/// Text embedded while indexing, in estimated tokens (see
+/// [`batching::estimate_tokens`]), for estimating what a run cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmbeddingUsage {
+    /// Texts sent to the embeddings API
+    pub texts: usize,
+    pub chars: usize,
+    pub tokens: usize,
+    /// Tokens not sent because the content was already embedded, earlier in
+    /// the package or run or by an indexed version
+    pub reused_tokens: usize,
+}
+
+impl EmbeddingUsage {
+    pub fn add(&mut self, other: &EmbeddingUsage) {
+        self.texts += other.texts;
+        self.chars += other.chars;
+        self.tokens += other.tokens;
+        self.reused_tokens += other.reused_tokens;
+    }
+
+    /// Estimated cost in USD of `tokens` at `price_per_1k_tokens`.
+    pub fn cost(tokens: usize, price_per_1k_tokens: f64) -> f64 {
+        tokens as f64 / 1000.0 * price_per_1k_tokens
+    }
+}
+
+impl IndexResult {
+    /// Result for a version that was already indexed or skipped.
+    fn skipped(version_id: String) -> Self {
+        Self {
+            version_id,
+            chunks_indexed: 0,
+            files_processed: 0,
+            files_skipped: 0,
+            skipped: true,
+            truncated: false,
+            embeddings_reused: 0,
+            embedding_usage: EmbeddingUsage::default(),
+            denied_license: None,
+            skip_rule: None,
+        }
+    }
+
+    /// True if the version was processed but yielded no code.
+    pub fn is_empty(&self) -> bool {
+        !self.skipped && self.chunks_indexed == 0
+    }
+}
+
+/// Namespaces present in only one of the vector store and SQLite.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NamespaceAudit {
+    /// Vector namespaces with no chunk rows (safe to drop)
+    pub orphaned_vectors: Vec<String>,
+    /// Chunk namespaces with no vectors (need re-embedding)
+    pub missing_vectors: Vec<String>,
+}
+
+/// Chunks parsed from a package's files.
+struct ParsedFiles {
+    chunks: Vec<CodeChunk>,
+    /// Trait implementations declared outside test files
+    impls: Vec<TraitImpl>,
+    /// True if a file or chunk cap was hit
+    truncated: bool,
+    /// Files not parsed (unsupported, excluded or over the file cap)
+    files_skipped: usize,
+}
+
+/// Outcome of [`LocalIndexer::start_version`].
+enum Start {
+    /// Index the version with this id.
+    Index(String),
+    /// Nothing to index; return this result.
+    Done(IndexResult),
+}
+
+/// Error returned when indexing is cancelled before completion.
+///
+/// The in-flight version is reset to `pending` so it is picked up again on
+/// the next run.
+#[derive(Debug, thiserror::Error)]
+#[error("indexing cancelled")]
+pub struct IndexCancelled;
+
+/// Error returned when a pipeline step fails a version.
+///
+/// The version is marked `failed` with the step's [`FailureKind`], so
+/// failures can be grouped and only transient ones retried.
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("download failed: {0:#}")]
+    Download(anyhow::Error),
+    #[error("no source available: {0:#}")]
+    NoSource(anyhow::Error),
+    #[error("parse failed: {0:#}")]
+    Parse(anyhow::Error),
+    #[error("embedding request failed: {0:#}")]
+    Embedding(anyhow::Error),
+    #[error("storage failed: {0:#}")]
+    Storage(anyhow::Error),
+}
+
+impl IndexError {
+    /// Classify a failure fetching a package from its registry.
+    ///
+    /// Missing packages, versions and sources won't appear on a retry;
+    /// anything else (HTTP, rate limits, corrupt archives) might.
+    pub fn download(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<RegistryError>() {
+            Some(
+                RegistryError::PackageNotFound(_)
+                | RegistryError::VersionNotFound { .. }
+                | RegistryError::NoPublishedVersion(_)
+                | RegistryError::NoSourcesAvailable { .. }
+                | RegistryError::InvalidPackage(_)
+                | RegistryError::UnsupportedRegistry(_),
+            ) => Self::NoSource(error),
+            _ => Self::Download(error),
+        }
+    }
+
+    pub fn kind(&self) -> FailureKind {
+        match self {
+            Self::Download(_) => FailureKind::Download,
+            Self::NoSource(_) => FailureKind::NoSource,
+            Self::Parse(_) => FailureKind::Parse,
+            Self::Embedding(_) => FailureKind::Embedding,
+            Self::Storage(_) => FailureKind::Storage,
+        }
+    }
+
+    /// The underlying error chain, without the step prefix.
+    pub fn detail(&self) -> String {
+        match self {
+            Self::Download(e)
+            | Self::NoSource(e)
+            | Self::Parse(e)
+            | Self::Embedding(e)
+            | Self::Storage(e) => format!("{:#}", e),
+        }
+    }
+}
+
+/// Create a token that is cancelled when the process receives Ctrl-C.
+///
+/// A second Ctrl-C exits immediately.
+pub fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\nCancelling... (press Ctrl-C again to force quit)");
+            trigger.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
+    token
 }
 
 impl LocalIndexer {
-    /// Create a new local indexer.
+    /// Create a new local indexer using the active embedding profile.
     pub async fn new(index_dir: &Path) -> Result<Self> {
-        let db = LocalDb::open(&index_dir.join("db.sqlite")).await?;
-        let storage = LocalStorage::new(index_dir.join("blobs")).await?;
-        let vectors = VectorStore::open(&index_dir.join("vectors")).await?;
-        let config = LocalConfig::load()?;
+        Self::new_with_profile(index_dir, None).await
+    }
+
+    /// Create a new local indexer using the named embedding profile
+    /// (`None` for the active one).
+    pub async fn new_with_profile(index_dir: &Path, profile: Option<&str>) -> Result<Self> {
+        let config = LocalConfig::load()?.with_profile(profile)?;
+        Self::with_config(index_dir, config).await
+    }
 
-        Ok(Self {
+    /// Create a new local indexer with `config` rather than the saved one.
+    ///
+    /// Cleans up versions an interrupted run left half-written (see
+    /// [`Self::recover_interrupted`]).
+    pub async fn with_config(index_dir: &Path, config: LocalConfig) -> Result<Self> {
+        let storage = LocalStorage::new(index_dir.join("blobs"))
+            .await?
+            .with_compression_level(config.blob_compression_level);
+        let db =
+            LocalDb::open_with_readers(&index_dir.join("db.sqlite"), config.db_read_connections)
+                .await?;
+        let vectors = VectorStore::open(&index_dir.join("vectors"), &config).await?;
+        let registry_cache = config.registry_cache(index_dir.join(super::CACHE_DIR_NAME));
+        let registry_limits = config.registry_limits();
+        let http = config.http_client()?;
+        let transformers = transform::builtin_transformers(&config.chunk_transformers)?;
+
+        let indexer = Self {
+            index_dir: index_dir.to_path_buf(),
             db,
             storage,
             vectors,
             config,
-        })
+            registry_cache,
+            registry_limits,
+            http,
+            embedding_cache: EmbeddingCache::new(embedding_cache::DEFAULT_CAPACITY),
+            events: None,
+            transformers,
+            tenant: None,
+            metadata_only: false,
+        };
+        if let Err(e) = indexer.recover_interrupted().await {
+            warn!(error = %format!("{:#}", e), "failed to clean up interrupted versions");
+        }
+        Ok(indexer)
+    }
+
+    /// Reset `pending` versions holding chunks or blobs, so they are rebuilt
+    /// from scratch when next indexed. Returns how many were reset.
+    ///
+    /// A version is only left `pending` with stored data when the run that
+    /// was indexing it crashed or was killed mid-write. Every write happens
+    /// under the shared index lock, so the sweep runs only when it can take
+    /// the lock exclusively, and does nothing while another process writes.
+    pub async fn recover_interrupted(&self) -> Result<usize> {
+        let Some(_lock) = IndexLock::try_exclusive(&self.index_dir)? else {
+            info!("index is being written, not checking for interrupted versions");
+            return Ok(0);
+        };
+
+        let mut recovered = 0;
+        for version in self
+            .db
+            .list_versions_by_status(VersionStatus::Pending)
+            .await?
+        {
+            let partial = self.db.has_chunks(&version.version_id).await?
+                || self
+                    .storage
+                    .has_package(&version.namespace().to_string())
+                    .await;
+            if partial {
+                warn!(
+                    registry = %version.registry,
+                    name = %version.name,
+                    version = %version.version,
+                    "cleaning up interrupted indexing run"
+                );
+                self.reset_version(&version).await?;
+                recovered += 1;
+            }
+        }
+        Ok(recovered)
+    }
+
+    /// Registry client backed by this index's response cache.
+    pub fn registry(&self, registry: Registry) -> RegistryClients {
+        let client = RegistryClients::with_http_client(registry, self.http.clone())
+            .with_limits(self.registry_limits.clone())
+            .with_offline(self.config.offline);
+        match &self.registry_cache {
+            Some(cache) => client.with_cache(cache.clone()),
+            None => client,
+        }
+    }
+
+    /// Packages `name` may have been a typo of, closest first: names from
+    /// the registry's search, then packages already in this index.
+    ///
+    /// Best effort, for "did you mean" hints after a package wasn't found.
+    pub async fn suggest_packages(&self, registry: Registry, name: &str) -> Vec<String> {
+        let mut candidates = self.registry(registry).search_names(name).await;
+        if let Ok(packages) = self.db.list_packages().await {
+            candidates.extend(
+                packages
+                    .into_iter()
+                    .filter(|p| p.registry == registry.as_str())
+                    .map(|p| p.name),
+            );
+        }
+        rank_suggestions(name, &candidates)
+    }
+
+    /// Override the configured per-package caps for this run (0 = unlimited).
+    pub fn with_limits(mut self, max_files: Option<usize>, max_chunks: Option<usize>) -> Self {
+        if let Some(n) = max_files {
+            self.config.max_files_per_package = n;
+        }
+        if let Some(n) = max_chunks {
+            self.config.max_chunks_per_package = n;
+        }
+        self
+    }
+
+    /// Override the configured texts per embeddings request for this run.
+    pub fn with_embedding_batch_size(mut self, size: Option<usize>) -> Self {
+        if let Some(n) = size {
+            self.config.embedding_batch_size = n;
+        }
+        self
+    }
+
+    /// Send an [`IndexEvent`] to `events` as each package enters a phase.
+    pub fn with_events(mut self, events: mpsc::UnboundedSender<IndexEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Also run `transformer` on every parsed chunk, after the ones enabled
+    /// in config.
+    pub fn with_transformer(mut self, transformer: impl ChunkTransformer + 'static) -> Self {
+        self.transformers.push(Box::new(transformer));
+        self
+    }
+
+    /// Index packages as `tenant`'s private packages, stored under
+    /// `private/<tenant>/...` namespaces apart from public packages of the
+    /// same name. None indexes public packages.
+    pub fn with_tenant(mut self, tenant: Option<TenantId>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Store parsed chunks without embedding them, marking versions
+    /// `metadata_indexed`: searchable by name, signature and outline right
+    /// away. A later run without this embeds them, skipping the versions
+    /// already fully indexed.
+    pub fn with_metadata_only(mut self, metadata_only: bool) -> Self {
+        self.metadata_only = metadata_only;
+        self
+    }
+
+    fn emit(&self, registry: Registry, name: &str, version: &str, phase: IndexPhase) {
+        if let Some(events) = &self.events {
+            // A closed receiver just means nobody is watching
+            let _ = events.send(IndexEvent {
+                registry,
+                name: name.to_string(),
+                version: version.to_string(),
+                phase,
+            });
+        }
+    }
+
+    /// Name of the embedding profile in use.
+    pub fn profile_name(&self) -> &str {
+        self.config.profile_name(None)
+    }
+
+    /// Fail if the profile's embedding dimension differs from the one this
+    /// index already holds.
+    pub async fn check_embedding_dimensions(&self) -> Result<()> {
+        self.db
+            .check_embedding_dimensions(self.config.embedding_dimensions)
+            .await
+            .with_context(|| {
+                format!(
+                    "Embedding profile '{}' can't be used with this index",
+                    self.profile_name()
+                )
+            })
     }
 
     /// Index a package from a registry.
@@ -63,189 +480,835 @@ impl LocalIndexer {
         registry: Registry,
         name: &str,
         version: &str,
+    ) -> Result<IndexResult> {
+        self.index_package_with_cancel(registry, name, version, &CancellationToken::new())
+            .await
+    }
+
+    /// Index a package from a registry, stopping early if `cancel` fires.
+    ///
+    /// Cancellation aborts in-flight downloads and embedding requests and
+    /// returns [`IndexCancelled`], leaving the version `pending`.
+    pub async fn index_package_with_cancel(
+        &self,
+        registry: Registry,
+        name: &str,
+        version: &str,
+        cancel: &CancellationToken,
     ) -> Result<IndexResult> {
         info!(registry = %registry, name, version, "indexing package");
 
+        let version_id = match self.start_version(registry, name, version).await? {
+            Start::Index(version_id) => version_id,
+            Start::Done(result) => return Ok(result),
+        };
+
+        // Download package
+        info!("downloading package");
+        self.emit(registry, name, version, IndexPhase::Downloading);
+        let client = self.registry(registry);
+
+        let skip_denied = self.config.license_action == LicenseAction::Skip;
+        let download = async {
+            let pkg_info = client.get_version(name, version).await?;
+            let denied = pkg_info
+                .license
+                .as_deref()
+                .is_some_and(|l| self.config.denies_license(l));
+            // Don't download what won't be indexed
+            let files = if denied && skip_denied {
+                None
+            } else {
+                Some(client.download_source(name, version).await?)
+            };
+            Ok::<_, anyhow::Error>((pkg_info, files))
+        };
+
+        let (pkg_info, files) = match self.cancellable(cancel, &version_id, download).await {
+            Ok(result) => result,
+            Err(e) if e.is::<IndexCancelled>() => return Err(e),
+            // Not the package's fault, so the version is left as it was
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<RegistryError>(),
+                    Some(RegistryError::OfflineMode)
+                ) =>
+            {
+                return Err(e);
+            }
+            Err(e) => return Err(self.fail(&version_id, IndexError::download(e)).await),
+        };
+
+        let denied_license = pkg_info
+            .license
+            .clone()
+            .filter(|l| self.config.denies_license(l));
+
+        self.db
+            .update_package_metadata(
+                registry.as_str(),
+                name,
+                self.tenant,
+                &PackageMetadata {
+                    description: pkg_info.description,
+                    license: pkg_info.license,
+                    repository: pkg_info.repository,
+                },
+            )
+            .await?;
+
+        let Some(files) = files else {
+            let license = denied_license.unwrap_or_default();
+            info!(license = %license, "license is denylisted, skipping package");
+            let reason = format!("license {} is denied", license);
+            self.db
+                .mark_version_skipped(&version_id, Some(&reason))
+                .await?;
+            return Ok(IndexResult {
+                denied_license: Some(license),
+                ..IndexResult::skipped(version_id)
+            });
+        };
+
+        if let Some(license) = &denied_license {
+            warn!(
+                registry = %registry,
+                name,
+                version,
+                license = %license,
+                "indexing package with denylisted license"
+            );
+        }
+
+        let mut result = self
+            .index_files(registry, name, version, version_id, &files, cancel)
+            .await?;
+        self.record_entry_point(
+            &result,
+            registry,
+            name,
+            pkg_info.entry_point.as_deref(),
+            &files,
+        )
+        .await?;
+        result.denied_license = denied_license;
+        Ok(result)
+    }
+
+    /// Index files fetched outside the registry client, such as a git
+    /// checkout, under `version`.
+    ///
+    /// Behaves like [`Self::index_package_with_cancel`] minus the download.
+    pub async fn index_source_with_cancel(
+        &self,
+        registry: Registry,
+        name: &str,
+        version: &str,
+        files: &[PackageFile],
+        cancel: &CancellationToken,
+    ) -> Result<IndexResult> {
+        info!(registry = %registry, name, version, "indexing fetched source");
+
+        let version_id = match self.start_version(registry, name, version).await? {
+            Start::Index(version_id) => version_id,
+            Start::Done(result) => return Ok(result),
+        };
+
+        let result = self
+            .index_files(registry, name, version, version_id, files, cancel)
+            .await?;
+        self.record_entry_point(&result, registry, name, None, files)
+            .await?;
+        Ok(result)
+    }
+
+    /// Store the entry module detected among an indexed version's files
+    /// (see [`entry_point`]).
+    async fn record_entry_point(
+        &self,
+        result: &IndexResult,
+        registry: Registry,
+        name: &str,
+        declared: Option<&str>,
+        files: &[PackageFile],
+    ) -> Result<()> {
+        let detected = entry_point(registry, name, declared, files);
+        self.db
+            .set_version_entry_point(&result.version_id, detected.as_deref())
+            .await
+    }
+
+    /// Drop a version's chunks, vectors and blobs and mark it `pending`, so
+    /// the next index run rebuilds it from scratch.
+    ///
+    /// The version row, and so its id, is kept. Once the index holds no
+    /// chunks its recorded embedding dimension is forgotten as well.
+    pub async fn reset_version(&self, version: &VersionWithPackage) -> Result<()> {
+        let mut namespaces = self.db.delete_version_chunks(&version.version_id).await?;
+        let namespace = version.namespace().to_string();
+        if !namespaces.contains(&namespace) {
+            // Vectors may outlive their chunks after a failed run
+            namespaces.push(namespace.clone());
+        }
+
+        for ns in &namespaces {
+            self.vectors.delete_namespace(ns).await?;
+        }
+        self.storage.delete_package(&namespace).await?;
+
+        self.db.mark_version_pending(&version.version_id).await?;
+        self.db.clear_embedding_dimensions_if_empty().await
+    }
+
+    /// Get or create the package and version rows, returning the id of a
+    /// version to index.
+    ///
+    /// Versions already indexed or skipped, and packages matching a skip
+    /// rule (which are marked `skipped` here, before any download), instead
+    /// give the result to return.
+    async fn start_version(&self, registry: Registry, name: &str, version: &str) -> Result<Start> {
+        // Refuse before touching the version, so it isn't marked failed
+        if !self.metadata_only {
+            self.check_embedding_dimensions().await?;
+            self.config.check_offline_embeddings()?;
+        }
+
         // Get or create package
         let package_id = self
             .db
             .get_or_create_package(&CreatePackage {
                 registry: registry.as_str().to_string(),
                 name: name.to_string(),
-                description: None, // Will be updated after download
+                description: None, // Updated from the registry after download
+                tenant: self.tenant,
             })
             .await?;
 
         // Get or create version
         let (version_id, should_skip) = self.db.get_or_create_version(&package_id, version).await?;
-
         if should_skip {
             info!("version already indexed or skipped");
-            return Ok(IndexResult {
-                version_id,
-                chunks_indexed: 0,
-                files_processed: 0,
-                skipped: true,
-            });
+            return Ok(Start::Done(IndexResult::skipped(version_id)));
+        }
+        // Versions with stored chunks only wait for a full run
+        if self.metadata_only
+            && self
+                .db
+                .find_version_by_package(&package_id, version)
+                .await?
+                .is_some_and(|v| v.status() == VersionStatus::MetadataIndexed)
+        {
+            info!("version already metadata-indexed");
+            return Ok(Start::Done(IndexResult::skipped(version_id)));
         }
 
-        // Download package
-        info!("downloading package");
-        let client = RegistryClients::new(registry);
-
-        let (_pkg_info, files) = match async {
-            let pkg_info = client.get_version(name, version).await?;
-            let files = client.download_source(name, version).await?;
-            Ok::<_, anyhow::Error>((pkg_info, files))
+        if let Some(rule) = self.db.matching_skip_rule(registry.as_str(), name).await? {
+            info!(rule = %rule, "package matches a skip rule, skipping");
+            let reason = format!("matches skip rule {}", rule);
+            self.db
+                .mark_version_skipped(&version_id, Some(&reason))
+                .await?;
+            return Ok(Start::Done(IndexResult {
+                skip_rule: Some(rule),
+                ..IndexResult::skipped(version_id)
+            }));
         }
-        .await
-        {
-            Ok(result) => result,
-            Err(e) => {
-                // Mark as failed
-                self.db
-                    .mark_version_failed(&version_id, &e.to_string())
-                    .await?;
-                return Err(e);
-            }
-        };
 
+        Ok(Start::Index(version_id))
+    }
+
+    /// Parse, embed and store downloaded files for a pending version.
+    ///
+    /// A version whose files yield no chunks is marked `empty` rather than
+    /// `indexed`, along with how many files were seen and skipped. With
+    /// [`Self::with_metadata_only`], chunks are stored with empty vectors
+    /// and the version is marked `metadata_indexed`.
+    async fn index_files(
+        &self,
+        registry: Registry,
+        name: &str,
+        version: &str,
+        version_id: String,
+        files: &[PackageFile],
+        cancel: &CancellationToken,
+    ) -> Result<IndexResult> {
         // Parse files
         info!(files = files.len(), "parsing files");
-        let chunks = self.parse_files(&files)?;
+        self.emit(registry, name, version, IndexPhase::Parsing);
+        let ParsedFiles {
+            chunks,
+            impls,
+            truncated,
+            files_skipped,
+        } = match self.parse_files(files) {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(self.fail(&version_id, IndexError::Parse(e)).await),
+        };
+        if truncated {
+            warn!(
+                registry = %registry,
+                name,
+                version,
+                max_files = self.config.max_files_per_package,
+                max_chunks = self.config.max_chunks_per_package,
+                "package exceeds per-package caps, indexing truncated"
+            );
+        }
+
+        if cancel.is_cancelled() {
+            self.db.mark_version_pending(&version_id).await?;
+            return Err(IndexCancelled.into());
+        }
 
         if chunks.is_empty() {
-            info!("no chunks extracted");
-            // Mark as indexed with 0 chunks (valid state - package has no indexable code)
-            self.db.mark_version_indexed(&version_id, 0).await?;
+            // Not a failure (e.g. binary-only or docs-free packages), but worth surfacing
+            warn!(
+                registry = %registry,
+                name,
+                version,
+                files = files.len(),
+                files_skipped,
+                "no code extracted"
+            );
+            self.db
+                .mark_version_empty(&version_id, files.len() as i32, files_skipped as i32)
+                .await?;
             return Ok(IndexResult {
                 version_id,
                 chunks_indexed: 0,
                 files_processed: files.len(),
+                files_skipped,
                 skipped: false,
+                truncated,
+                embeddings_reused: 0,
+                embedding_usage: EmbeddingUsage::default(),
+                denied_license: None,
+                skip_rule: None,
             });
         }
 
-        // Generate embeddings
-        info!(chunks = chunks.len(), "generating embeddings");
-        let embeddings = match self.generate_embeddings(&chunks).await {
-            Ok(e) => e,
-            Err(e) => {
-                self.db
-                    .mark_version_failed(&version_id, &e.to_string())
-                    .await?;
-                return Err(e);
+        // Generate embeddings, unless only metadata is stored
+        let embed = async {
+            if self.metadata_only {
+                let usage = EmbeddingUsage::default();
+                let none = vec![None; chunks.len()];
+                return Ok((none.clone(), HashSet::new(), none, usage));
             }
+            info!(chunks = chunks.len(), "generating embeddings");
+            self.emit(registry, name, version, IndexPhase::Embedding);
+            let name_weight = self.config.embedding_name_weight;
+            let texts = chunks
+                .iter()
+                .map(|chunk| chunk.weighted_embedding_text(name_weight))
+                .collect();
+            let (embeddings, reused, mut usage) = self.generate_embeddings(texts).await?;
+            let embeddings = embeddings.into_iter().map(Some).collect();
+            let doc_embeddings = if self.config.doc_embeddings {
+                let doc_texts = chunks.iter().map(CodeChunk::doc_embedding_text).collect();
+                let (doc_embeddings, doc_usage) = self.generate_doc_embeddings(doc_texts).await?;
+                usage.add(&doc_usage);
+                doc_embeddings
+            } else {
+                vec![None; chunks.len()]
+            };
+            Ok::<_, anyhow::Error>((embeddings, reused, doc_embeddings, usage))
         };
+        let (embeddings, reused, doc_embeddings, embedding_usage) =
+            match self.cancellable(cancel, &version_id, embed).await {
+                Ok(e) => e,
+                Err(e) if e.is::<IndexCancelled>() => return Err(e),
+                Err(e) => return Err(self.fail(&version_id, IndexError::Embedding(e)).await),
+            };
 
         // Build namespace
-        let namespace = format!("{}/{}/{}", registry.as_str(), name, version);
+        let namespace = NamespaceSpec::new(registry.as_str(), name, version)
+            .with_tenant(self.tenant)
+            .to_string();
+
+        // Every write below happens under the lock, so a pending version
+        // with stored data is one whose run never finished
+        let _lock = IndexLock::shared(&self.index_dir).await?;
+
+        // Store whole source files for context retrieval (opt-in)
+        let file_keys = if self.config.store_source_files {
+            match self.store_source_files(&namespace, files, &chunks).await {
+                Ok(keys) => keys,
+                Err(e) => return Err(self.fail(&version_id, IndexError::Storage(e)).await),
+            }
+        } else {
+            HashMap::new()
+        };
 
         // Store everything
         info!("storing chunks");
+        self.emit(registry, name, version, IndexPhase::Storing);
         let mut vector_records = Vec::new();
+        let mut doc_records = Vec::new();
         let mut db_chunks = Vec::new();
+        let mut seen_ids = HashSet::new();
+        let mut duplicates = 0;
+        let mut embeddings_reused = 0;
+        // Left empty without embeddings, so no run ever reuses the empty vectors
+        let embedding_model = if self.metadata_only {
+            String::new()
+        } else {
+            self.config.embedding_model.clone()
+        };
 
-        for (chunk, embedding) in chunks.iter().zip(embeddings.iter()) {
-            let chunk_id = Uuid::new_v4().to_string();
-            let content_hash = hex::encode(Sha256::digest(chunk.code.as_bytes()));
-
+        for ((chunk, embedding), doc_embedding) in chunks.iter().zip(embeddings).zip(doc_embeddings)
+        {
             // Store blob
-            let storage_key = self
-                .storage
-                .put(registry.as_str(), name, version, chunk.code.as_bytes())
-                .await?;
+            let storage_key = match self.storage.put(&namespace, chunk.code.as_bytes()).await {
+                Ok(key) => key,
+                Err(e) => return Err(self.fail(&version_id, IndexError::Storage(e)).await),
+            };
+
+            // Prepare DB record
+            let (embedding_hash, embedding) = embedding.unzip();
+            let (doc_hash, doc_vector) = doc_embedding.unzip();
+            let db_chunk = CreateChunk {
+                vector: embedding.unwrap_or_default(),
+                embedding_model: embedding_model.clone(),
+                embedding_hash,
+                doc_hash,
+                doc_vector,
+                ..Self::create_chunk(
+                    &version_id,
+                    &namespace,
+                    chunk,
+                    storage_key,
+                    file_keys.get(&chunk.file_path).cloned(),
+                    self.config.snippet_length,
+                )
+            };
+
+            // Identical chunks in one file share an id; keep the first
+            if !seen_ids.insert(db_chunk.id.clone()) {
+                duplicates += 1;
+                continue;
+            }
+            if let Some(hash) = &db_chunk.embedding_hash
+                && reused.contains(hash)
+            {
+                embeddings_reused += 1;
+            }
+            if self.metadata_only {
+                db_chunks.push(db_chunk);
+                continue;
+            }
 
-            // Prepare vector record
+            // Prepare vector records
+            let attributes =
+                VectorAttributes::for_chunk(registry.as_str(), name, version, &db_chunk);
+            if let (Some(doc_hash), Some(doc_vector)) = (&db_chunk.doc_hash, &db_chunk.doc_vector) {
+                doc_records.push(VectorRecord {
+                    chunk_id: db_chunk.id.clone(),
+                    content_hash: doc_hash.clone(),
+                    vector: doc_vector.clone(),
+                    attributes: Some(attributes.clone()),
+                });
+            }
             vector_records.push(VectorRecord {
-                chunk_id: chunk_id.clone(),
-                content_hash: content_hash.clone(),
-                vector: embedding.clone(),
+                chunk_id: db_chunk.id.clone(),
+                content_hash: db_chunk.content_hash.clone(),
+                vector: db_chunk.vector.clone(),
+                attributes: Some(attributes),
             });
 
-            // Prepare DB record
-            db_chunks.push(CreateChunk {
-                id: chunk_id,
-                version_id: version_id.clone(),
-                namespace: namespace.clone(),
-                chunk_type: format!("{:?}", chunk.chunk_type).to_lowercase(),
-                name: chunk.name.clone(),
-                file_path: chunk.file_path.clone(),
-                start_line: chunk.start_line,
-                end_line: chunk.end_line,
-                visibility: format!("{:?}", chunk.visibility).to_lowercase(),
-                signature: chunk.signature.clone(),
-                docstring: chunk.documentation.clone(),
-                snippet: chunk.snippet(500),
-                storage_key,
-                content_hash,
-                vector: embedding.clone(),
-            });
+            db_chunks.push(db_chunk);
         }
 
-        // Insert into vector store
-        if let Err(e) = self.vectors.insert(&namespace, vector_records).await {
-            // Include full error chain
-            let error_msg = format!("{:#}", e);
-            self.db.mark_version_failed(&version_id, &error_msg).await?;
-            return Err(e);
+        if !self.metadata_only {
+            // Record the dimension before the first vectors land
+            if let Err(e) = self
+                .db
+                .record_embedding_dimensions(self.config.embedding_dimensions)
+                .await
+            {
+                self.db.mark_version_pending(&version_id).await?;
+                return Err(e);
+            }
+
+            // Insert into vector store
+            if let Err(e) = self.vectors.insert(&namespace, vector_records).await {
+                return Err(self.fail(&version_id, IndexError::Storage(e)).await);
+            }
+            if !doc_records.is_empty()
+                && let Err(e) = self.vectors.insert_docs(&namespace, doc_records).await
+            {
+                return Err(self.fail(&version_id, IndexError::Storage(e)).await);
+            }
         }
 
         // Insert into SQLite
         if let Err(e) = self.db.insert_chunks(&db_chunks).await {
-            self.db
-                .mark_version_failed(&version_id, &e.to_string())
-                .await?;
-            return Err(e);
+            return Err(self.fail(&version_id, IndexError::Storage(e)).await);
+        }
+        if let Err(e) = self.db.replace_impls(&version_id, &namespace, &impls).await {
+            return Err(self.fail(&version_id, IndexError::Storage(e)).await);
         }
 
         let chunks_indexed = db_chunks.len();
 
         // Mark as successfully indexed
-        self.db
-            .mark_version_indexed(&version_id, chunks_indexed as i32)
-            .await?;
+        if self.metadata_only {
+            self.db
+                .mark_version_metadata_indexed(&version_id, chunks_indexed as i32)
+                .await?;
+        } else {
+            self.db
+                .mark_version_indexed(&version_id, chunks_indexed as i32)
+                .await?;
+        }
 
-        info!(chunks_indexed, "indexing complete");
+        if duplicates > 0 {
+            info!(
+                duplicates,
+                "dropped chunks identical to an earlier one in the same file"
+            );
+        }
+        info!(
+            chunks_indexed,
+            embeddings_reused,
+            embedding_tokens = embedding_usage.tokens,
+            "indexing complete"
+        );
 
         Ok(IndexResult {
             version_id,
             chunks_indexed,
             files_processed: files.len(),
+            files_skipped,
             skipped: false,
+            truncated,
+            embeddings_reused,
+            embedding_usage,
+            denied_license: None,
+            skip_rule: None,
         })
     }
 
-    /// Parse files into code chunks.
-    fn parse_files(&self, files: &[PackageFile]) -> Result<Vec<CodeChunk>> {
-        let chunks: Vec<_> = files
-            .par_iter()
-            .filter(|f| !self.should_skip(&f.path))
-            .filter_map(|f| {
-                let language = Language::from_path(&f.path)?;
-                let parser = get_parser(language).ok()?;
-                parser.parse(&f.content, &f.path).ok()
-            })
-            .flatten()
-            .collect();
-
-        Ok(chunks)
-    }
+    /// Embed the chunks a metadata-only run stored for `version` (see
+    /// [`Self::with_metadata_only`]) and mark it `indexed`.
+    ///
+    /// Works from the stored rows and code, so nothing is downloaded or
+    /// parsed again: each row gets its vector and embedding hash in place,
+    /// and the vectors are added to the vector store. The texts are built
+    /// as a full run builds them, so stored embeddings are reused the same
+    /// way. A failed or cancelled run leaves the version `metadata_indexed`
+    /// for the next one.
+    pub async fn embed_stored(
+        &self,
+        version: &VersionWithPackage,
+        cancel: &CancellationToken,
+    ) -> Result<IndexResult> {
+        self.check_embedding_dimensions().await?;
+        self.config.check_offline_embeddings()?;
 
-    /// Check if a file should be skipped.
-    fn should_skip(&self, path: &str) -> bool {
-        let path_lower = path.to_lowercase();
+        let registry = Registry::from_str(&version.registry)
+            .map_err(|e| anyhow::anyhow!("Unknown registry '{}': {}", version.registry, e))?;
+        let (name, number) = (version.name.as_str(), version.version.as_str());
+        let rows = self.db.unembedded_chunks(&version.version_id).await?;
 
-        const SKIP_DIRS: &[&str] = &[
-            "node_modules/",
-            "__pycache__/",
-            ".git/",
-            "target/",
-            "dist/",
-            "build/",
-            ".next/",
-            "coverage/",
-        ];
+        let name_weight = self.config.embedding_name_weight;
+        let mut texts = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let code = self.storage.get(&row.storage_key).await?;
+            let header = name_header(&row.name, row.parent.as_deref(), name_weight);
+            texts.push(embedding_text(
+                header.as_deref(),
+                row.docstring.as_deref(),
+                row.signature.as_deref(),
+                &String::from_utf8_lossy(&code),
+            ));
+        }
+
+        info!(chunks = rows.len(), "embedding stored chunks");
+        self.emit(registry, name, number, IndexPhase::Embedding);
+        let embed = async {
+            let (embeddings, reused, mut usage) = self.generate_embeddings(texts).await?;
+            let doc_embeddings = if self.config.doc_embeddings {
+                let doc_texts = rows
+                    .iter()
+                    .map(|row| {
+                        doc_embedding_text(row.docstring.as_deref(), row.signature.as_deref())
+                    })
+                    .collect();
+                let (doc_embeddings, doc_usage) = self.generate_doc_embeddings(doc_texts).await?;
+                usage.add(&doc_usage);
+                doc_embeddings
+            } else {
+                vec![None; rows.len()]
+            };
+            Ok::<_, anyhow::Error>((embeddings, reused, doc_embeddings, usage))
+        };
+        let (embeddings, reused, doc_embeddings, embedding_usage) = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(IndexCancelled.into()),
+            result = embed => result?,
+        };
+
+        let _lock = IndexLock::shared(&self.index_dir).await?;
+        self.emit(registry, name, number, IndexPhase::Storing);
+        let mut vector_records = Vec::new();
+        let mut doc_records = Vec::new();
+        let mut updates = Vec::new();
+        let mut embeddings_reused = 0;
+        for ((row, (embedding_hash, vector)), doc_embedding) in
+            rows.iter().zip(embeddings).zip(doc_embeddings)
+        {
+            if reused.contains(&embedding_hash) {
+                embeddings_reused += 1;
+            }
+            let attributes = VectorAttributes::for_row(registry.as_str(), name, number, row);
+            let (doc_hash, doc_vector) = doc_embedding.unzip();
+            if let (Some(doc_hash), Some(doc_vector)) = (&doc_hash, &doc_vector) {
+                doc_records.push(VectorRecord {
+                    chunk_id: row.id.clone(),
+                    content_hash: doc_hash.clone(),
+                    vector: doc_vector.clone(),
+                    attributes: Some(attributes.clone()),
+                });
+            }
+            vector_records.push(VectorRecord {
+                chunk_id: row.id.clone(),
+                content_hash: row.content_hash.clone(),
+                vector: vector.clone(),
+                attributes: Some(attributes),
+            });
+            updates.push(ChunkEmbedding {
+                id: row.id.clone(),
+                vector,
+                embedding_model: self.config.embedding_model.clone(),
+                embedding_hash,
+                doc_hash,
+                doc_vector,
+            });
+        }
+
+        // Vectors land before the rows are filled in, so a run cut short
+        // in between is simply embedded again
+        self.db
+            .record_embedding_dimensions(self.config.embedding_dimensions)
+            .await?;
+        let namespace = version.namespace().to_string();
+        if !vector_records.is_empty() {
+            self.vectors.insert(&namespace, vector_records).await?;
+        }
+        if !doc_records.is_empty() {
+            self.vectors.insert_docs(&namespace, doc_records).await?;
+        }
+        self.db.set_chunk_embeddings(&updates).await?;
+        self.db
+            .mark_version_indexed(&version.version_id, version.chunk_count)
+            .await?;
+
+        info!(
+            chunks_embedded = updates.len(),
+            embeddings_reused,
+            embedding_tokens = embedding_usage.tokens,
+            "embedding complete"
+        );
+
+        Ok(IndexResult {
+            version_id: version.version_id.clone(),
+            chunks_indexed: version.chunk_count as usize,
+            files_processed: version.files_seen as usize,
+            files_skipped: version.files_skipped as usize,
+            skipped: false,
+            truncated: false,
+            embeddings_reused,
+            embedding_usage,
+            denied_license: None,
+            skip_rule: None,
+        })
+    }
+
+    /// Build the SQLite row for a parsed chunk.
+    ///
+    /// The id comes from [`chunk_id`], so re-indexing a version upserts the
+    /// same rows and vectors instead of adding new ones. Its snippet keeps
+    /// `snippet_length` bytes of the code; its embedding is left empty for
+    /// the caller to fill in.
+    fn create_chunk(
+        version_id: &str,
+        namespace: &str,
+        chunk: &CodeChunk,
+        storage_key: String,
+        file_storage_key: Option<String>,
+        snippet_length: usize,
+    ) -> CreateChunk {
+        let content_hash = content_hash(chunk);
+
+        CreateChunk {
+            id: chunk_id(namespace, &chunk.file_path, &chunk.name, &content_hash),
+            version_id: version_id.to_string(),
+            namespace: namespace.to_string(),
+            chunk_type: format!("{:?}", chunk.chunk_type).to_lowercase(),
+            name: chunk.name.clone(),
+            file_path: chunk.file_path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            visibility: format!("{:?}", chunk.visibility).to_lowercase(),
+            signature: chunk.signature.clone(),
+            params: chunk.params.clone(),
+            returns: chunk.returns.clone(),
+            docstring: chunk.documentation.clone(),
+            snippet: chunk.snippet(snippet_length),
+            storage_key,
+            content_hash,
+            vector: Vec::new(),
+            embedding_model: String::new(),
+            file_storage_key,
+            start_byte: chunk.start_byte,
+            end_byte: chunk.end_byte,
+            deprecated: chunk.deprecated,
+            deprecation_message: chunk.deprecation_message.clone(),
+            parent: chunk.parent.clone(),
+            embedding_hash: None,
+            doc_hash: None,
+            doc_vector: None,
+        }
+    }
+
+    /// Run a pipeline step, resetting the version to `pending` if cancelled.
+    ///
+    /// Dropping the step future aborts any in-flight HTTP requests.
+    async fn cancellable<T>(
+        &self,
+        cancel: &CancellationToken,
+        version_id: &str,
+        step: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                info!(version_id, "indexing cancelled");
+                self.db.mark_version_pending(version_id).await?;
+                Err(IndexCancelled.into())
+            }
+            result = step => result,
+        }
+    }
+
+    /// Mark the version failed with `error`'s kind and full error chain.
+    ///
+    /// Returns the error to propagate, or the database error if recording
+    /// the failure failed.
+    async fn fail(&self, version_id: &str, error: IndexError) -> anyhow::Error {
+        match self
+            .db
+            .mark_version_failed(version_id, error.kind(), &error.detail())
+            .await
+        {
+            Ok(()) => error.into(),
+            Err(e) => e,
+        }
+    }
+
+    /// Parse files into code chunks, honoring the per-package caps.
+    ///
+    /// Chunks from test files are tagged [`ChunkType::Example`]. Every chunk
+    /// then goes through the transformers, which may drop it before the
+    /// chunk cap is applied.
+    fn parse_files(&self, files: &[PackageFile]) -> Result<ParsedFiles> {
+        let mut parseable: Vec<&PackageFile> = files
+            .iter()
+            .filter(|f| !self.should_skip(f) && Language::from_path(&f.path).is_some())
+            .collect();
+
+        let max_files = self.config.max_files_per_package;
+        let files_truncated = max_files > 0 && parseable.len() > max_files;
+        if files_truncated {
+            warn!(files = parseable.len(), max_files, "file cap hit");
+            parseable.truncate(max_files);
+        }
+
+        let (chunks, impls): (Vec<_>, Vec<_>) = parseable
+            .par_iter()
+            .filter_map(|f| {
+                let language = Language::from_path(&f.path)?;
+                let parser = get_parser(language).ok()?;
+                let (mut chunks, mut impls) = parser.parse_with_impls(&f.content, &f.path).ok()?;
+                if f.is_test {
+                    for chunk in &mut chunks {
+                        chunk.chunk_type = ChunkType::Example;
+                    }
+                    // Test fixtures aren't implementations the package provides
+                    impls.clear();
+                }
+                Some((chunks, impls))
+            })
+            .unzip();
+        let mut chunks: Vec<_> = chunks.into_iter().flatten().collect();
+        let impls = impls.into_iter().flatten().collect();
+        if !self.transformers.is_empty() {
+            chunks.retain_mut(|chunk| self.transformers.iter().all(|t| t.transform(chunk)));
+        }
+
+        let max_chunks = self.config.max_chunks_per_package;
+        let chunks_truncated = max_chunks > 0 && chunks.len() > max_chunks;
+        if chunks_truncated {
+            warn!(chunks = chunks.len(), max_chunks, "chunk cap hit");
+            chunks.truncate(max_chunks);
+        }
+
+        Ok(ParsedFiles {
+            chunks,
+            impls,
+            truncated: files_truncated || chunks_truncated,
+            files_skipped: files.len() - parseable.len(),
+        })
+    }
+
+    /// Store every source file that produced chunks, keyed by file path.
+    async fn store_source_files(
+        &self,
+        namespace: &str,
+        files: &[PackageFile],
+        chunks: &[CodeChunk],
+    ) -> Result<HashMap<String, String>> {
+        let chunked_paths: HashSet<&str> = chunks.iter().map(|c| c.file_path.as_str()).collect();
+        let mut keys = HashMap::new();
+
+        for file in files {
+            if !chunked_paths.contains(file.path.as_str()) || keys.contains_key(&file.path) {
+                continue;
+            }
+            let key = self
+                .storage
+                .put_file(namespace, file.content.as_bytes())
+                .await?;
+            keys.insert(file.path.clone(), key);
+        }
+
+        Ok(keys)
+    }
+
+    /// Check if a file should be skipped.
+    ///
+    /// Test files are skipped unless `index_tests` is set, vendored files
+    /// unless `index_vendored` is.
+    fn should_skip(&self, file: &PackageFile) -> bool {
+        if file.is_test && !self.config.index_tests {
+            return true;
+        }
+        if file.is_vendored && !self.config.index_vendored {
+            return true;
+        }
+
+        let path_lower = file.path.to_lowercase();
+
+        const SKIP_DIRS: &[&str] = &[
+            "node_modules/",
+            "__pycache__/",
+            ".git/",
+            "target/",
+            "dist/",
+            "build/",
+            ".next/",
+            "coverage/",
+        ];
 
         const SKIP_PATTERNS: &[&str] = &[".min.js", ".bundle.js", ".map", ".d.ts", ".lock", ".env"];
 
@@ -264,31 +1327,171 @@ impl LocalIndexer {
         false
     }
 
-    /// Generate embeddings for chunks.
-    async fn generate_embeddings(&self, chunks: &[CodeChunk]) -> Result<Vec<Vec<f32>>> {
-        let api_key = self
-            .config
-            .openai_api_key
-            .as_ref()
-            .context("OpenAI API key not configured. Run: idx config set-key")?;
+    /// Generate embeddings for chunks' embedding texts, each with the hash of
+    /// the text it was embedded from, along with the hashes whose embeddings
+    /// were reused and the text sent for the rest.
+    ///
+    /// Text already embedded with the configured model (earlier in this
+    /// run, or stored with an indexed chunk of any package version) is
+    /// reused; only the rest is sent to the embeddings API, each distinct
+    /// text once.
+    async fn generate_embeddings(
+        &self,
+        texts: Vec<String>,
+    ) -> Result<(Vec<(String, Vec<f32>)>, HashSet<String>, EmbeddingUsage)> {
+        let hashes: Vec<String> = texts.iter().map(|text| embedding_hash(text)).collect();
+        let (vectors, reused, usage) = self
+            .embed_reusing(EmbeddingKind::Code, &hashes, |i| texts[i].clone())
+            .await?;
+        Ok((hashes.into_iter().zip(vectors).collect(), reused, usage))
+    }
 
-        let client = reqwest::Client::new();
-        let mut all_embeddings = Vec::with_capacity(chunks.len());
+    /// Generate documentation embeddings for the documented chunks, given
+    /// each chunk's documentation text, each with the doc hash it was
+    /// embedded under. Undocumented chunks get `None`. Embeddings are reused
+    /// the same way as by [`Self::generate_embeddings`].
+    async fn generate_doc_embeddings(
+        &self,
+        doc_texts: Vec<Option<String>>,
+    ) -> Result<(Vec<Option<(String, Vec<f32>)>>, EmbeddingUsage)> {
+        let count = doc_texts.len();
+        let texts: Vec<(usize, String)> = doc_texts
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, text)| text.map(|text| (i, text)))
+            .collect();
+        let hashes: Vec<String> = texts.iter().map(|(_, text)| doc_hash(text)).collect();
+        let (vectors, _, usage) = self
+            .embed_reusing(EmbeddingKind::Docs, &hashes, |i| texts[i].1.clone())
+            .await?;
 
-        // Batch embeddings (max 100 per request)
-        const BATCH_SIZE: usize = 100;
+        let mut doc_embeddings = vec![None; count];
+        for (((index, _), hash), vector) in texts.iter().zip(hashes).zip(vectors) {
+            doc_embeddings[*index] = Some((hash, vector));
+        }
+        Ok((doc_embeddings, usage))
+    }
 
-        info!(total_chunks = chunks.len(), "generating embeddings");
+    /// Embed the texts identified by `hashes`, in order, along with the
+    /// hashes whose embeddings were reused and the text sent for the rest.
+    /// `text` gives the text for the hash at an index.
+    async fn embed_reusing(
+        &self,
+        kind: EmbeddingKind,
+        hashes: &[String],
+        text: impl Fn(usize) -> String,
+    ) -> Result<(Vec<Vec<f32>>, HashSet<String>, EmbeddingUsage)> {
+        // The hashes cover the embedded text, name weighting included
+        let model = &self.config.embedding_model;
 
-        for (batch_idx, batch) in chunks.chunks(BATCH_SIZE).enumerate() {
-            let texts: Vec<String> = batch.iter().map(|c| c.embedding_text()).collect();
+        let mut found: HashMap<String, Vec<f32>> = HashMap::new();
+        for hash in hashes {
+            if let Some(vector) = self.embedding_cache.get(model, hash) {
+                found.insert(hash.clone(), vector);
+            }
+        }
+
+        let unseen: Vec<String> = hashes
+            .iter()
+            .filter(|h| !found.contains_key(*h))
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let stored = match kind {
+            EmbeddingKind::Code => self.db.cached_embeddings(model, &unseen).await?,
+            EmbeddingKind::Docs => self.db.cached_doc_embeddings(model, &unseen).await?,
+        };
+        for (hash, vector) in stored {
+            // Stored vectors of another dimension can't be mixed in
+            if self.config.check_embedding(&vector).is_ok() {
+                self.embedding_cache.insert(model, &hash, vector.clone());
+                found.insert(hash, vector);
+            }
+        }
+
+        let reused: HashSet<String> = found.keys().cloned().collect();
+
+        // One text per hash still missing; the rest is saved
+        let mut missing = Vec::new();
+        let mut missing_hashes = HashSet::new();
+        let mut usage = EmbeddingUsage::default();
+        for (i, hash) in hashes.iter().enumerate() {
+            let content = text(i);
+            let tokens = batching::estimate_tokens(&content);
+            if !found.contains_key(hash) && missing_hashes.insert(hash) {
+                usage.texts += 1;
+                usage.chars += content.len();
+                usage.tokens += tokens;
+                missing.push((hash, content));
+            } else {
+                usage.reused_tokens += tokens;
+            }
+        }
+
+        info!(
+            ?kind,
+            total = hashes.len(),
+            cached = hashes.len() - missing.len(),
+            "generating embeddings"
+        );
+
+        let texts = missing.iter().map(|(_, text)| text.clone()).collect();
+        let vectors = self.request_embeddings(texts).await?;
+        for ((hash, _), vector) in missing.into_iter().zip(vectors) {
+            self.embedding_cache.insert(model, hash, vector.clone());
+            found.insert(hash.clone(), vector);
+        }
+
+        let embeddings = hashes
+            .iter()
+            .map(|hash| {
+                found
+                    .get(hash)
+                    .cloned()
+                    .context("Embeddings API returned too few embeddings")
+            })
+            .collect::<Result<_>>()?;
+        Ok((embeddings, reused, usage))
+    }
+
+    /// Embed `texts` with the embeddings API, in order.
+    ///
+    /// Requests are packed up to the configured batch size and token budget;
+    /// see [`batching`] for how oversized texts are split.
+    async fn request_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.config.check_offline_embeddings()?;
+        let api_key = self.config.require_openai_key()?;
+
+        let client = &self.http;
+        let budget = self.config.embedding_batch_tokens;
+        let pieces = batching::split_texts(&texts, budget.min(batching::MAX_TEXT_TOKENS));
+        if pieces.len() > texts.len() {
+            info!(
+                texts = texts.len(),
+                pieces = pieces.len(),
+                "split oversized embedding texts"
+            );
+        }
+        let mut all_embeddings = Vec::with_capacity(pieces.len());
+
+        let batches = batching::pack(&pieces, self.config.embedding_batch_size, budget);
+        for (batch_idx, range) in batches.into_iter().enumerate() {
+            let batch = &pieces[range];
+            let texts: Vec<&str> = batch.iter().map(|p| p.content.as_str()).collect();
             let total_chars: usize = texts.iter().map(|t| t.len()).sum();
+            let est_tokens: usize = batch.iter().map(|p| p.tokens).sum();
 
             let max_text_len = texts.iter().map(|t| t.len()).max().unwrap_or(0);
             info!(
                 batch = batch_idx + 1,
                 texts = texts.len(),
                 total_chars,
+                est_tokens,
                 max_text_len,
                 "sending embedding batch"
             );
@@ -329,11 +1532,45 @@ impl LocalIndexer {
             })?;
 
             for data in response.data {
+                self.config.check_embedding(&data.embedding)?;
                 all_embeddings.push(data.embedding);
             }
         }
 
-        Ok(all_embeddings)
+        if all_embeddings.len() < pieces.len() {
+            anyhow::bail!("Embeddings API returned too few embeddings");
+        }
+        Ok(batching::combine(&pieces, all_embeddings, texts.len()))
+    }
+
+    /// Compare the vector store's namespaces with the namespaces of stored
+    /// chunks.
+    ///
+    /// Hold an exclusive [`IndexLock`] while acting on the result, so
+    /// packages being written aren't mistaken for orphans.
+    pub async fn audit_namespaces(&self) -> Result<NamespaceAudit> {
+        let vector_namespaces: HashSet<String> =
+            self.vectors.list_namespaces().await?.into_iter().collect();
+        let chunk_namespaces: HashSet<String> =
+            self.db.get_namespaces().await?.into_iter().collect();
+        // Metadata-only versions have no vectors yet by design
+        let unembedded: HashSet<String> =
+            self.db.unembedded_namespaces().await?.into_iter().collect();
+
+        let mut audit = NamespaceAudit {
+            orphaned_vectors: vector_namespaces
+                .difference(&chunk_namespaces)
+                .cloned()
+                .collect(),
+            missing_vectors: chunk_namespaces
+                .difference(&vector_namespaces)
+                .filter(|ns| !unembedded.contains(*ns))
+                .cloned()
+                .collect(),
+        };
+        audit.orphaned_vectors.sort();
+        audit.missing_vectors.sort();
+        Ok(audit)
     }
 
     /// Get the underlying database.
@@ -341,6 +1578,11 @@ impl LocalIndexer {
         &self.db
     }
 
+    /// Get the configuration this indexer runs with.
+    pub fn config(&self) -> &LocalConfig {
+        &self.config
+    }
+
     /// Get the underlying vector store.
     pub fn vectors(&self) -> &VectorStore {
         &self.vectors
@@ -352,6 +1594,29 @@ impl LocalIndexer {
     }
 }
 
+/// Hash of a chunk's code, used to tell chunks apart.
+fn content_hash(chunk: &CodeChunk) -> String {
+    hex::encode(Sha256::digest(chunk.code.as_bytes()))
+}
+
+/// Hash of a chunk's embedding text, used to reuse its embedding.
+fn embedding_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// Hash of a documentation embedding text, used to reuse its embedding.
+/// Prefixed so it never equals an embedding hash in the embedding cache.
+fn doc_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(format!("doc\0{}", text).as_bytes()))
+}
+
+/// Which stored embeddings [`LocalIndexer::embed_reusing`] can reuse.
+#[derive(Debug, Clone, Copy)]
+enum EmbeddingKind {
+    Code,
+    Docs,
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct EmbeddingResponse {
     data: Vec<EmbeddingData>,
@@ -361,3 +1626,1240 @@ struct EmbeddingResponse {
 struct EmbeddingData {
     embedding: Vec<f32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::db::DimensionMismatch;
+    use crate::test_support::{serve, stub_embeddings};
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_cancel_slow_download_resets_version_to_pending() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+
+        let package_id = indexer
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "slow-pkg".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            trigger.cancel();
+        });
+
+        // Stub a download that would take far longer than the test
+        let slow_download = async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, anyhow::Error>(Vec::<PackageFile>::new())
+        };
+
+        let err = indexer
+            .cancellable(&cancel, &version_id, slow_download)
+            .await
+            .unwrap_err();
+        assert!(err.is::<IndexCancelled>());
+
+        let version = indexer
+            .db
+            .find_version("npm", "slow-pkg", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Pending);
+        assert!(version.error_message.is_none());
+        assert_eq!(version.chunk_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_dimensions_are_refused() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+
+        // Index built with a smaller model than the profile in use
+        let dimensions = indexer.config.embedding_dimensions;
+        indexer
+            .db
+            .record_embedding_dimensions(dimensions / 2)
+            .await
+            .unwrap();
+
+        let err = indexer
+            .index_package(Registry::Npm, "lodash", "4.17.21")
+            .await
+            .unwrap_err();
+        assert!(err.is::<DimensionMismatch>());
+
+        // Nothing was created for the refused package
+        assert!(
+            indexer
+                .db
+                .find_version("npm", "lodash", "4.17.21", None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    fn js_file(i: usize) -> PackageFile {
+        PackageFile {
+            path: format!("src/mod{}.js", i),
+            content: format!("function fn{}a() {{}}\nfunction fn{}b() {{}}\n", i, i),
+            is_test: false,
+            is_vendored: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_files_respects_caps() {
+        let dir = tempdir().unwrap();
+        let files: Vec<_> = (0..10).map(js_file).collect();
+
+        let indexer = LocalIndexer::new(dir.path())
+            .await
+            .unwrap()
+            .with_limits(Some(0), Some(0));
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.chunks.len(), 20);
+        assert!(!parsed.truncated);
+        assert_eq!(parsed.files_skipped, 0);
+
+        // File cap: only the first 3 files are parsed
+        let indexer = indexer.with_limits(Some(3), None);
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.chunks.len(), 6);
+        assert!(parsed.truncated);
+        assert_eq!(parsed.files_skipped, 7);
+        assert!(parsed.chunks.iter().all(|c| c.file_path != "src/mod3.js"));
+
+        // Chunk cap applies after parsing
+        let indexer = indexer.with_limits(Some(0), Some(5));
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.chunks.len(), 5);
+        assert!(parsed.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_test_files_indexed_as_examples_when_enabled() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            js_file(0),
+            PackageFile {
+                path: "test/parse.test.js".to_string(),
+                content: "function parsesNested() {}\n".to_string(),
+                is_test: true,
+                is_vendored: false,
+            },
+        ];
+
+        let mut indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        indexer.config.index_tests = false;
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.chunks.len(), 2);
+        assert_eq!(parsed.files_skipped, 1);
+        assert!(
+            parsed
+                .chunks
+                .iter()
+                .all(|c| c.chunk_type != ChunkType::Example)
+        );
+
+        indexer.config.index_tests = true;
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.chunks.len(), 3);
+        assert_eq!(parsed.files_skipped, 0);
+        let example = parsed
+            .chunks
+            .iter()
+            .find(|c| c.name == "parsesNested")
+            .unwrap();
+        assert_eq!(example.chunk_type, ChunkType::Example);
+    }
+
+    #[tokio::test]
+    async fn test_vendored_files_skipped_unless_enabled() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            PackageFile {
+                path: "pip/_internal/cli.py".to_string(),
+                content: "def main():\n    pass\n".to_string(),
+                is_test: false,
+                is_vendored: false,
+            },
+            PackageFile {
+                path: "pip/_vendor/requests/api.py".to_string(),
+                content: "def get(url):\n    pass\n".to_string(),
+                is_test: false,
+                is_vendored: true,
+            },
+        ];
+
+        let mut indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.files_skipped, 1);
+        assert!(parsed.chunks.iter().all(|c| c.name != "get"));
+
+        indexer.config.index_vendored = true;
+        let parsed = indexer.parse_files(&files).unwrap();
+        assert_eq!(parsed.files_skipped, 0);
+        assert!(parsed.chunks.iter().any(|c| c.name == "get"));
+    }
+
+    /// Store parsed chunks the way `index_files` does (minus embeddings) and
+    /// return the namespace's chunk ids.
+    async fn index_chunk_ids(
+        indexer: &LocalIndexer,
+        files: &[PackageFile],
+        version_id: &str,
+        namespace: &str,
+    ) -> Vec<String> {
+        let parsed = indexer.parse_files(files).unwrap();
+        let chunks: Vec<_> = parsed
+            .chunks
+            .iter()
+            .map(|c| CreateChunk {
+                vector: vec![0.5; 4],
+                embedding_model: "test-model".to_string(),
+                ..LocalIndexer::create_chunk(version_id, namespace, c, "key".into(), None, 500)
+            })
+            .collect();
+        indexer.db.insert_chunks(&chunks).await.unwrap();
+
+        let mut ids: Vec<_> = indexer
+            .db
+            .get_chunks_by_namespace(namespace)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_reindexing_keeps_chunk_ids_stable() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        let files: Vec<_> = (0..3).map(js_file).collect();
+
+        let package_id = indexer
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "stable".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+        let namespace = "npm/stable/1.0.0";
+
+        let first = index_chunk_ids(&indexer, &files, &version_id, namespace).await;
+        let second = index_chunk_ids(&indexer, &files, &version_id, namespace).await;
+        assert_eq!(first.len(), 6);
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_audit_finds_orphaned_and_missing_vectors() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+
+        // Chunk rows without vectors
+        let package_id = indexer
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "stable".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+        index_chunk_ids(&indexer, &[js_file(0)], &version_id, "npm/stable/1.0.0").await;
+
+        // Vectors without chunk rows, as left by a crash mid-store
+        indexer
+            .vectors
+            .insert(
+                "npm/ghost/1.0.0",
+                vec![VectorRecord {
+                    chunk_id: "c1".to_string(),
+                    content_hash: "h1".to_string(),
+                    vector: vec![0.5; 4],
+                    attributes: None,
+                }],
+            )
+            .await
+            .unwrap();
+
+        let audit = indexer.audit_namespaces().await.unwrap();
+        assert_eq!(audit.orphaned_vectors, vec!["npm/ghost/1.0.0"]);
+        assert_eq!(audit.missing_vectors, vec!["npm/stable/1.0.0"]);
+
+        for ns in &audit.orphaned_vectors {
+            indexer.vectors.delete_namespace(ns).await.unwrap();
+        }
+        let namespaces = indexer.vectors.list_namespaces().await.unwrap();
+        assert!(!namespaces.contains(&"npm/ghost/1.0.0".to_string()));
+        assert!(
+            indexer
+                .audit_namespaces()
+                .await
+                .unwrap()
+                .orphaned_vectors
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reset_version_regenerates_chunks_and_vectors() {
+        let dir = tempdir().unwrap();
+        let mut indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        indexer.config.openai_base_url = stub_embeddings(vec![0.5; 4]).await.url;
+        indexer.config.openai_api_key = Some("test".to_string());
+        indexer.config.embedding_dimensions = 4;
+
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let namespace = "npm/fresh/1.0.0";
+        let cancel = CancellationToken::new();
+        let index =
+            || indexer.index_source_with_cancel(Registry::Npm, "fresh", "1.0.0", &files, &cancel);
+
+        let first = index().await.unwrap();
+        assert_eq!(first.chunks_indexed, 4);
+        assert!(index().await.unwrap().skipped);
+
+        let version = indexer
+            .db
+            .find_version("npm", "fresh", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        indexer.reset_version(&version).await.unwrap();
+
+        let reset = indexer
+            .db
+            .find_version("npm", "fresh", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reset.version_id, version.version_id);
+        assert_eq!(reset.status(), VersionStatus::Pending);
+        assert!(
+            indexer
+                .db
+                .get_chunks_by_namespace(namespace)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            !indexer
+                .vectors
+                .list_namespaces()
+                .await
+                .unwrap()
+                .contains(&namespace.to_string())
+        );
+        assert_eq!(indexer.db.embedding_dimensions().await.unwrap(), None);
+
+        let second = index().await.unwrap();
+        assert!(!second.skipped);
+        assert_eq!(second.version_id, version.version_id);
+        assert_eq!(second.chunks_indexed, 4);
+        assert_eq!(
+            indexer
+                .db
+                .get_chunks_by_namespace(namespace)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+        let hits = indexer
+            .vectors
+            .search(namespace, &[0.5; 4], 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_version_is_cleaned_up_and_resumes() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let namespace = "npm/crashed/1.0.0";
+        let cancel = CancellationToken::new();
+
+        // Chunks, vectors and blobs are written, but the run dies before
+        // marking the version indexed
+        let crashed = stub_indexer(dir.path(), &embeddings.url).await;
+        crashed
+            .index_source_with_cancel(Registry::Npm, "crashed", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        let version = crashed
+            .db
+            .find_version("npm", "crashed", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        crashed
+            .db
+            .mark_version_pending(&version.version_id)
+            .await
+            .unwrap();
+        // A pending version with nothing stored yet is left alone
+        let idle_package = crashed
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "idle".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        crashed
+            .db
+            .get_or_create_version(&idle_package, "1.0.0")
+            .await
+            .unwrap();
+        drop(crashed);
+
+        let indexer = stub_indexer(dir.path(), &embeddings.url).await;
+        let recovered = indexer
+            .db
+            .find_version("npm", "crashed", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(recovered.version_id, version.version_id);
+        assert_eq!(recovered.status(), VersionStatus::Pending);
+        assert!(!indexer.db.has_chunks(&version.version_id).await.unwrap());
+        assert!(
+            !indexer
+                .vectors
+                .list_namespaces()
+                .await
+                .unwrap()
+                .contains(&namespace.to_string())
+        );
+        assert!(!indexer.storage.has_package("npm/crashed/1.0.0").await);
+        assert_eq!(indexer.recover_interrupted().await.unwrap(), 0);
+
+        // The next run rebuilds it in place
+        let resumed = indexer
+            .index_source_with_cancel(Registry::Npm, "crashed", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert!(!resumed.skipped);
+        assert_eq!(resumed.version_id, version.version_id);
+        assert_eq!(resumed.chunks_indexed, 4);
+        assert_eq!(
+            indexer
+                .db
+                .get_chunks_by_namespace(namespace)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+        let hits = indexer
+            .vectors
+            .search(namespace, &[0.5; 4], 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_phases_are_sent_as_events() {
+        let dir = tempdir().unwrap();
+        let (events, mut rx) = mpsc::unbounded_channel();
+        let indexer = LocalIndexer::new(dir.path())
+            .await
+            .unwrap()
+            .with_events(events);
+
+        let package_id = indexer
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "docs-only".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db
+            .get_or_create_version(&package_id, "2.0.0")
+            .await
+            .unwrap();
+
+        // Nothing to embed, so parsing is the last phase reached
+        indexer
+            .index_files(
+                Registry::Npm,
+                "docs-only",
+                "2.0.0",
+                version_id,
+                &[],
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.registry, Registry::Npm);
+        assert_eq!(event.name, "docs-only");
+        assert_eq!(event.version, "2.0.0");
+        assert_eq!(event.phase, IndexPhase::Parsing);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zero_chunk_package_is_marked_empty() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+
+        let package_id = indexer
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "binary-only".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+
+        // One unsupported file and one parseable file with nothing to extract
+        let files = vec![
+            PackageFile {
+                path: "bin/tool.node".to_string(),
+                content: String::new(),
+                is_test: false,
+                is_vendored: false,
+            },
+            PackageFile {
+                path: "index.js".to_string(),
+                content: "// nothing here\n".to_string(),
+                is_test: false,
+                is_vendored: false,
+            },
+        ];
+
+        let result = indexer
+            .index_files(
+                Registry::Npm,
+                "binary-only",
+                "1.0.0",
+                version_id,
+                &files,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 0);
+        assert_eq!(result.files_processed, 2);
+        assert_eq!(result.files_skipped, 1);
+        assert!(!result.skipped);
+
+        let version = indexer
+            .db
+            .find_version("npm", "binary-only", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Empty);
+        assert_eq!(version.files_seen, 2);
+        assert_eq!(version.files_skipped, 1);
+        assert!(version.indexed_at.is_some());
+
+        // Empty versions aren't re-downloaded on the next run
+        let (_, should_skip) = indexer
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+        assert!(should_skip);
+
+        let stats = indexer.db.get_stats().await.unwrap();
+        assert_eq!(stats.empty_count, 1);
+        assert_eq!(stats.indexed_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_index_source_skips_known_version() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        let version = "v0.0.0-20240102150405-abcdef123456";
+        let files = vec![PackageFile {
+            path: "README.md".to_string(),
+            content: String::new(),
+            is_test: false,
+            is_vendored: false,
+        }];
+
+        let first = indexer
+            .index_source_with_cancel(
+                Registry::Go,
+                "github.com/acme/lib",
+                version,
+                &files,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!first.skipped);
+
+        let second = indexer
+            .index_source_with_cancel(
+                Registry::Go,
+                "github.com/acme/lib",
+                version,
+                &files,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(second.skipped);
+        assert_eq!(second.version_id, first.version_id);
+    }
+
+    /// Indexer embedding 4-dimensional vectors with a stub server.
+    async fn stub_indexer(dir: &Path, base_url: &str) -> LocalIndexer {
+        let mut indexer = LocalIndexer::new(dir).await.unwrap();
+        indexer.config.openai_base_url = base_url.to_string();
+        indexer.config.openai_api_key = Some("test".to_string());
+        indexer.config.embedding_dimensions = 4;
+        indexer
+    }
+
+    #[tokio::test]
+    async fn test_offline_blocks_downloads_but_not_local_indexing() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let mut indexer = stub_indexer(dir.path(), &embeddings.url).await;
+        indexer.config.offline = true;
+
+        let err = indexer
+            .index_package(Registry::Npm, "left-pad", "1.3.0")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::OfflineMode)
+        ));
+        // Not recorded as a failure of the package
+        let version = indexer
+            .db
+            .find_version("npm", "left-pad", "1.3.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Pending);
+
+        // The stub embeddings server is on this machine, so fetched source still indexes
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let cancel = CancellationToken::new();
+        let result = indexer
+            .index_source_with_cancel(Registry::Npm, "local", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 4);
+
+        // A remote embeddings API is refused before the version is touched
+        indexer.config.openai_base_url = "https://api.openai.com".to_string();
+        let err = indexer
+            .index_source_with_cancel(Registry::Npm, "remote", "1.0.0", &files, &cancel)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("offline mode"));
+        assert!(
+            indexer
+                .db
+                .find_version("npm", "remote", "1.0.0", None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_embeddings_skip_api() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let cancel = CancellationToken::new();
+
+        let mut first = stub_indexer(dir.path(), &embeddings.url).await;
+        first
+            .index_source_with_cancel(Registry::Npm, "a", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 4);
+
+        // Same content again: served from memory
+        let result = first
+            .index_source_with_cancel(Registry::Npm, "b", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 4);
+        assert_eq!(embeddings.embedded(), 4);
+
+        // A fresh indexer reuses the vectors stored in SQLite
+        let second = stub_indexer(dir.path(), &embeddings.url).await;
+        let result = second
+            .index_source_with_cancel(Registry::Npm, "c", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 4);
+        assert_eq!(embeddings.embedded(), 4);
+
+        // Another model can't reuse them
+        first.config.embedding_model = "other-model".to_string();
+        first
+            .index_source_with_cancel(Registry::Npm, "d", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_embed_stored_fills_metadata_only_chunks_in_place() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let cancel = CancellationToken::new();
+
+        stub_indexer(dir.path(), &embeddings.url)
+            .await
+            .with_metadata_only(true)
+            .index_source_with_cancel(Registry::Npm, "a", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        // The same code, fully indexed under another name
+        let full = stub_indexer(dir.path(), &embeddings.url).await;
+        full.index_source_with_cancel(Registry::Npm, "b", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 4);
+
+        // Built from the stored rows, the texts match a full run's, so the
+        // other package's embeddings are reused
+        let version = full
+            .db
+            .find_version("npm", "a", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::MetadataIndexed);
+        let embedder = stub_indexer(dir.path(), &embeddings.url).await;
+        let result = embedder.embed_stored(&version, &cancel).await.unwrap();
+        assert_eq!(result.chunks_indexed, 4);
+        assert_eq!(result.embeddings_reused, 4);
+        assert_eq!(embeddings.embedded(), 4);
+
+        let version = embedder
+            .db
+            .find_version("npm", "a", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Indexed);
+        assert!(
+            embedder
+                .db
+                .unembedded_chunks(&version.version_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            embedder
+                .audit_namespaces()
+                .await
+                .unwrap()
+                .missing_vectors
+                .is_empty()
+        );
+
+        // With the other package gone, the filled-in rows' hashes are what
+        // a fresh run reuses
+        let other = embedder
+            .db
+            .find_version("npm", "b", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        embedder.reset_version(&other).await.unwrap();
+        stub_indexer(dir.path(), &embeddings.url)
+            .await
+            .index_source_with_cancel(Registry::Npm, "c", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_doc_embeddings_are_stored_and_reused() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let cancel = CancellationToken::new();
+
+        let files = vec![
+            PackageFile {
+                path: "src/retry.js".to_string(),
+                content: "/** Retry a failed request. */\nfunction retry() {}\n".to_string(),
+                is_test: false,
+                is_vendored: false,
+            },
+            js_file(0),
+        ];
+
+        let mut indexer = stub_indexer(dir.path(), &embeddings.url).await;
+        indexer.config.doc_embeddings = true;
+        indexer
+            .index_source_with_cancel(Registry::Npm, "a", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        // Three code embeddings, and one for the only documented chunk
+        assert_eq!(embeddings.embedded(), 4);
+
+        for chunk in indexer
+            .db
+            .get_chunks_by_namespace("npm/a/1.0.0")
+            .await
+            .unwrap()
+        {
+            let doc_vector = indexer.db.get_chunk_doc_vector(&chunk.id).await.unwrap();
+            assert_eq!(
+                doc_vector.is_some(),
+                chunk.name == "retry",
+                "{}",
+                chunk.name
+            );
+        }
+        // The vector store holds them beside the code embeddings
+        let docs = indexer
+            .vectors
+            .search_docs_multi(&["npm/a/1.0.0".to_string()], &[0.1; 4], 10)
+            .await
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(
+            indexer.vectors.list_namespaces().await.unwrap(),
+            vec!["npm/a/1.0.0"]
+        );
+
+        // A fresh indexer reuses the stored documentation embedding too
+        let mut second = stub_indexer(dir.path(), &embeddings.url).await;
+        second.config.doc_embeddings = true;
+        second
+            .index_source_with_cancel(Registry::Npm, "b", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 4);
+
+        // Name weighting changes the code text only, so only code is re-embedded
+        second.config.embedding_name_weight = 2;
+        second
+            .index_source_with_cancel(Registry::Npm, "c", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_close_versions_reuse_embeddings() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let cancel = CancellationToken::new();
+        let indexer = stub_indexer(dir.path(), &embeddings.url).await;
+
+        let old: Vec<_> = (0..10).map(js_file).collect();
+        let result = indexer
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.0", &old, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 20);
+        assert_eq!(result.embeddings_reused, 0);
+        assert_eq!(embeddings.embedded(), 20);
+
+        // The patch release changes one file and adds another
+        let mut new = old.clone();
+        new[3].content = "function fn3a() { return 1; }\nfunction fn3b() {}\n".to_string();
+        new.push(js_file(10));
+        let result = indexer
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.1", &new, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 22);
+        assert_eq!(result.embeddings_reused, 19);
+        assert_eq!(embeddings.embedded(), 23);
+    }
+
+    #[tokio::test]
+    async fn test_reuse_needs_the_same_embedding_text() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let cancel = CancellationToken::new();
+        let indexer = stub_indexer(dir.path(), &embeddings.url).await;
+
+        let file = |doc: &str| PackageFile {
+            path: "src/retry.js".to_string(),
+            content: format!("/** {} */\nfunction retry() {{}}\n", doc),
+            is_test: false,
+            is_vendored: false,
+        };
+        indexer
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.0", &[file("Retry.")], &cancel)
+            .await
+            .unwrap();
+        assert_eq!(embeddings.embedded(), 1);
+
+        // Same code, new documentation: the old vector doesn't describe it
+        let result = indexer
+            .index_source_with_cancel(
+                Registry::Npm,
+                "lib",
+                "1.0.1",
+                &[file("Retry a failed request.")],
+                &cancel,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.embeddings_reused, 0);
+        assert_eq!(embeddings.embedded(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_usage_counts_texts_sent() {
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let dir = tempdir().unwrap();
+        let indexer = stub_indexer(dir.path(), &embeddings.url).await;
+        let cancel = CancellationToken::new();
+
+        // A copied file's chunks are embedded once
+        let mut files: Vec<_> = (0..3).map(js_file).collect();
+        files.push(PackageFile {
+            path: "src/copy.js".to_string(),
+            ..js_file(0)
+        });
+        let first = indexer
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.0", &files, &cancel)
+            .await
+            .unwrap()
+            .embedding_usage;
+
+        let sent = embeddings.sent();
+        assert_eq!(first.texts, sent.len());
+        assert_eq!(first.texts, 6);
+        assert_eq!(first.chars, sent.iter().map(String::len).sum::<usize>());
+        assert_eq!(
+            first.tokens,
+            sent.iter()
+                .map(|text| batching::estimate_tokens(text))
+                .sum::<usize>()
+        );
+        assert!(first.reused_tokens > 0);
+
+        // Nothing is sent for the same code in another package
+        let second = indexer
+            .index_source_with_cancel(Registry::Npm, "fork", "1.0.0", &files, &cancel)
+            .await
+            .unwrap()
+            .embedding_usage;
+        assert_eq!(second.texts, 0);
+        assert_eq!(second.tokens, 0);
+        assert_eq!(second.reused_tokens, first.tokens + first.reused_tokens);
+        assert!((EmbeddingUsage::cost(1500, 0.00002) - 0.00003).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_transformers_reach_stored_chunks_and_embeddings() {
+        /// Documents every chunk and drops the `b` functions.
+        struct Annotate;
+
+        impl ChunkTransformer for Annotate {
+            fn name(&self) -> &str {
+                "annotate"
+            }
+
+            fn transform(&self, chunk: &mut CodeChunk) -> bool {
+                chunk.documentation = Some(format!("Synthetic docs for {}.", chunk.name));
+                !chunk.name.ends_with('b')
+            }
+        }
+
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let dir = tempdir().unwrap();
+        let indexer = stub_indexer(dir.path(), &embeddings.url)
+            .await
+            .with_transformer(Annotate);
+        let cancel = CancellationToken::new();
+
+        let files: Vec<_> = (0..2).map(js_file).collect();
+        let result = indexer
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 2);
+
+        let chunks = indexer
+            .db
+            .get_chunks_by_namespace("npm/lib/1.0.0")
+            .await
+            .unwrap();
+        let names: HashSet<_> = chunks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, HashSet::from(["fn0a", "fn1a"]));
+        for chunk in &chunks {
+            let doc = format!("Synthetic docs for {}.", chunk.name);
+            assert_eq!(chunk.docstring.as_deref(), Some(doc.as_str()));
+        }
+        {
+            let sent = embeddings.sent();
+            assert_eq!(sent.len(), 2);
+            assert!(
+                sent.iter()
+                    .all(|text| text.starts_with("Synthetic docs for fn"))
+            );
+        }
+
+        // Re-indexing the version without it keeps the code but not the
+        // docs, so no embedding is reused
+        let plain = stub_indexer(dir.path(), &embeddings.url).await;
+        let version = plain
+            .db
+            .find_version("npm", "lib", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        plain.reset_version(&version).await.unwrap();
+        let result = plain
+            .index_source_with_cancel(Registry::Npm, "lib", "1.0.0", &files, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(result.chunks_indexed, 4);
+        assert_eq!(result.embeddings_reused, 0);
+        let sent = embeddings.sent();
+        assert_eq!(sent.len(), 6);
+        assert!(
+            sent[2..]
+                .iter()
+                .all(|text| !text.starts_with("Synthetic docs"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_packages_are_indexed_apart_from_public_ones() {
+        let dir = tempdir().unwrap();
+        let embeddings = stub_embeddings(vec![0.5; 4]).await;
+        let tenant = TenantId::new_v4();
+        let public = stub_indexer(dir.path(), &embeddings.url).await;
+        let private = stub_indexer(dir.path(), &embeddings.url)
+            .await
+            .with_tenant(Some(tenant));
+        let cancel = CancellationToken::new();
+
+        let files = vec![js_file(0)];
+        for indexer in [&public, &private] {
+            let result = indexer
+                .index_source_with_cancel(Registry::Npm, "utils", "1.0.0", &files, &cancel)
+                .await
+                .unwrap();
+            assert!(!result.skipped);
+            assert_eq!(result.chunks_indexed, 2);
+        }
+
+        let private_ns = format!("private/{}/npm/utils/1.0.0", tenant);
+        let mut namespaces = public.db.get_namespaces().await.unwrap();
+        namespaces.sort();
+        assert_eq!(
+            namespaces,
+            vec!["npm/utils/1.0.0".to_string(), private_ns.clone()]
+        );
+        assert!(
+            public
+                .vectors
+                .list_namespaces()
+                .await
+                .unwrap()
+                .contains(&private_ns)
+        );
+        assert!(public.storage.has_package(&private_ns).await);
+    }
+
+    #[test]
+    fn test_download_errors_are_classified() {
+        let kind = |e: anyhow::Error| IndexError::download(e).kind();
+
+        let missing = RegistryError::VersionNotFound {
+            package: "lodash".to_string(),
+            version: "0.0.0".to_string(),
+        };
+        assert_eq!(kind(missing.into()), FailureKind::NoSource);
+        let no_sdist = RegistryError::NoSourcesAvailable {
+            package: "wheel-only".to_string(),
+            version: "1.0.0".to_string(),
+            reason: "no sdist".to_string(),
+        };
+        assert_eq!(
+            kind(anyhow::Error::from(no_sdist).context("fetching wheel-only")),
+            FailureKind::NoSource
+        );
+
+        assert_eq!(
+            kind(RegistryError::RateLimited.into()),
+            FailureKind::Download
+        );
+        assert_eq!(
+            kind(RegistryError::Archive("truncated".to_string()).into()),
+            FailureKind::Download
+        );
+        assert_eq!(
+            kind(anyhow::anyhow!("connection reset")),
+            FailureKind::Download
+        );
+    }
+
+    /// Stub embeddings server that always fails.
+    async fn failing_embeddings() -> String {
+        let app = axum::Router::new().route(
+            "/v1/embeddings",
+            axum::routing::post(|| async {
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "overloaded")
+            }),
+        );
+        serve(app).await
+    }
+
+    #[tokio::test]
+    async fn test_embedding_failure_is_recorded() {
+        let dir = tempdir().unwrap();
+        let indexer = stub_indexer(dir.path(), &failing_embeddings().await).await;
+        let files: Vec<_> = (0..2).map(js_file).collect();
+
+        let err = indexer
+            .index_source_with_cancel(
+                Registry::Npm,
+                "flaky",
+                "1.0.0",
+                &files,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<IndexError>().unwrap();
+        assert_eq!(err.kind(), FailureKind::Embedding);
+
+        let version = indexer
+            .db
+            .find_version("npm", "flaky", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Failed);
+        assert_eq!(version.failure_kind(), Some(FailureKind::Embedding));
+        assert!(version.error_message.unwrap().contains("overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_failure_is_recorded() {
+        let dir = tempdir().unwrap();
+        let indexer = stub_indexer(dir.path(), &stub_embeddings(vec![0.5; 4]).await.url).await;
+        let files: Vec<_> = (0..2).map(js_file).collect();
+
+        // A file where the registry's blob directory belongs
+        std::fs::write(dir.path().join("blobs").join("npm"), "").unwrap();
+
+        let err = indexer
+            .index_source_with_cancel(
+                Registry::Npm,
+                "blocked",
+                "1.0.0",
+                &files,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<IndexError>().unwrap().kind(),
+            FailureKind::Storage
+        );
+
+        let version = indexer
+            .db
+            .find_version("npm", "blocked", "1.0.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.failure_kind(), Some(FailureKind::Storage));
+        assert!(version.failure_kind().unwrap().is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_skip_rule_prevents_download() {
+        let dir = tempdir().unwrap();
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        indexer.db.add_skip_rule("npm:@types/*").await.unwrap();
+
+        let result = indexer
+            .index_package(Registry::Npm, "@types/node", "20.11.0")
+            .await
+            .unwrap();
+        assert!(result.skipped);
+        assert_eq!(result.skip_rule.as_deref(), Some("npm:@types/*"));
+
+        let version = indexer
+            .db
+            .find_version("npm", "@types/node", "20.11.0", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.status(), VersionStatus::Skipped);
+        assert_eq!(
+            version.error_message.as_deref(),
+            Some("matches skip rule npm:@types/*")
+        );
+        // Registry metadata is only stored once a package is fetched
+        assert!(version.description.is_none());
+    }
+}
@@ -0,0 +1,193 @@
+//! License denylist matching for SPDX license expressions.
+//!
+//! A package is denied when every way of satisfying its license expression
+//! needs a denylisted license: `MIT OR GPL-3.0` can be used under MIT, while
+//! `MIT AND GPL-3.0` can't avoid GPL. Denylist entries match regardless of
+//! case and `-only`/`-or-later`/`+` suffixes, and a trailing `*` matches a
+//! prefix (`AGPL-*`).
+
+/// Whether `expression` can only be satisfied with licenses on `denylist`.
+///
+/// Expressions that aren't valid SPDX (free-form license text from some
+/// registries) are matched as a single license name.
+pub fn is_denied(expression: &str, denylist: &[String]) -> bool {
+    if denylist.is_empty() || expression.trim().is_empty() {
+        return false;
+    }
+
+    let tokens = tokenize(expression);
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        denylist,
+    };
+    match parser.or_expr() {
+        Some(denied) if parser.pos == tokens.len() => denied,
+        _ => matches_denylist(expression.trim(), denylist),
+    }
+}
+
+fn tokenize(expression: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in expression.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(s) = start.take() {
+                tokens.push(&expression[s..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&expression[i..i + 1]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&expression[s..]);
+    }
+    tokens
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := term (AND term)*`,
+/// `term := "(" or ")" | id [WITH exception]`, evaluating whether each
+/// sub-expression is denied.
+struct Parser<'a> {
+    tokens: &'a [&'a str],
+    pos: usize,
+    denylist: &'a [String],
+}
+
+impl Parser<'_> {
+    fn or_expr(&mut self) -> Option<bool> {
+        let mut denied = self.and_expr()?;
+        while self.eat("OR") {
+            // Denied only if no alternative is allowed
+            denied &= self.and_expr()?;
+        }
+        Some(denied)
+    }
+
+    fn and_expr(&mut self) -> Option<bool> {
+        let mut denied = self.term()?;
+        while self.eat("AND") {
+            denied |= self.term()?;
+        }
+        Some(denied)
+    }
+
+    fn term(&mut self) -> Option<bool> {
+        if self.eat("(") {
+            let denied = self.or_expr()?;
+            return self.eat(")").then_some(denied);
+        }
+
+        let id = *self.tokens.get(self.pos)?;
+        if is_operator(id) || id == ")" {
+            return None;
+        }
+        self.pos += 1;
+
+        // Exceptions don't change which license applies
+        if self.eat("WITH") {
+            let exception = *self.tokens.get(self.pos)?;
+            if is_operator(exception) || exception == "(" || exception == ")" {
+                return None;
+            }
+            self.pos += 1;
+        }
+
+        Some(matches_denylist(id, self.denylist))
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let matched = self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case(token));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+}
+
+fn is_operator(token: &str) -> bool {
+    ["AND", "OR", "WITH"]
+        .iter()
+        .any(|op| token.eq_ignore_ascii_case(op))
+}
+
+fn matches_denylist(license: &str, denylist: &[String]) -> bool {
+    let license = normalize(license);
+    denylist
+        .iter()
+        .any(|entry| match entry.trim().strip_suffix('*') {
+            Some(prefix) => license.starts_with(&prefix.to_ascii_lowercase()),
+            None => license == normalize(entry),
+        })
+}
+
+/// Lowercase and drop version range suffixes (`GPL-3.0-or-later` -> `gpl-3.0`).
+fn normalize(license: &str) -> String {
+    let license = license.trim().to_ascii_lowercase();
+    ["-only", "-or-later", "+"]
+        .iter()
+        .find_map(|suffix| license.strip_suffix(suffix))
+        .unwrap_or(&license)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn denylist(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_single_licenses() {
+        let deny = denylist(&["GPL-3.0", "AGPL-*"]);
+
+        assert!(is_denied("GPL-3.0", &deny));
+        assert!(is_denied("gpl-3.0-only", &deny));
+        assert!(is_denied("GPL-3.0-or-later", &deny));
+        assert!(is_denied("GPL-3.0+", &deny));
+        assert!(is_denied("AGPL-3.0-only", &deny));
+        assert!(!is_denied("LGPL-3.0", &deny));
+        assert!(!is_denied("MIT", &deny));
+        assert!(!is_denied("", &deny));
+        assert!(!is_denied("GPL-3.0", &[]));
+    }
+
+    #[test]
+    fn test_expressions() {
+        let deny = denylist(&["GPL-2.0", "GPL-3.0"]);
+
+        // An allowed alternative is enough
+        assert!(!is_denied("MIT OR GPL-3.0", &deny));
+        assert!(is_denied("GPL-2.0 OR GPL-3.0", &deny));
+
+        // Every conjunct must be allowed
+        assert!(is_denied("MIT AND GPL-3.0", &deny));
+        assert!(!is_denied("MIT AND Apache-2.0", &deny));
+
+        assert!(is_denied("(MIT OR Apache-2.0) AND GPL-2.0-only", &deny));
+        assert!(!is_denied("(MIT AND GPL-3.0) OR Apache-2.0", &deny));
+        assert!(is_denied(
+            "GPL-2.0-or-later WITH Classpath-exception-2.0",
+            &deny
+        ));
+        assert!(!is_denied("Apache-2.0 WITH LLVM-exception", &deny));
+        // AND binds tighter than OR
+        assert!(!is_denied("MIT OR GPL-3.0 AND GPL-2.0", &deny));
+    }
+
+    #[test]
+    fn test_free_form_licenses() {
+        let deny = denylist(&["GNU General Public License v3 (GPLv3)"]);
+
+        assert!(is_denied("GNU General Public License v3 (GPLv3)", &deny));
+        assert!(!is_denied("MIT License", &deny));
+    }
+}
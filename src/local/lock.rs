@@ -0,0 +1,81 @@
+//! Advisory lock on the index directory.
+//!
+//! Indexing holds a shared lock while it writes vectors and chunk rows;
+//! maintenance that compares the two stores (e.g. `idx clean --vectors-orphaned`)
+//! takes it exclusively so it never sees a half-written package.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+/// Lock file name, inside the index directory.
+const LOCK_FILE_NAME: &str = "index.lock";
+
+/// A held index lock, released on drop (or when the process exits).
+pub struct IndexLock {
+    _file: File,
+}
+
+impl IndexLock {
+    /// Take a shared lock, waiting for any exclusive holder to finish.
+    pub async fn shared(index_dir: &Path) -> Result<Self> {
+        let file = Self::open(index_dir)?;
+        tokio::task::spawn_blocking(move || {
+            file.lock_shared().context("Failed to lock index")?;
+            Ok(Self { _file: file })
+        })
+        .await?
+    }
+
+    /// Take an exclusive lock, failing if another process is writing the index.
+    pub fn exclusive(index_dir: &Path) -> Result<Self> {
+        match Self::try_exclusive(index_dir)? {
+            Some(lock) => Ok(lock),
+            None => {
+                bail!(
+                    "The index is being written by another idx process; try again when it's done."
+                )
+            }
+        }
+    }
+
+    /// Take an exclusive lock, or `None` if another process is writing the index.
+    pub fn try_exclusive(index_dir: &Path) -> Result<Option<Self>> {
+        let file = Self::open(index_dir)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e).context("Failed to lock index"),
+        }
+    }
+
+    fn open(index_dir: &Path) -> Result<File> {
+        let path = index_dir.join(LOCK_FILE_NAME);
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_exclusive_lock_waits_for_shared_holders() {
+        let dir = tempdir().unwrap();
+
+        let first = IndexLock::shared(dir.path()).await.unwrap();
+        let second = IndexLock::shared(dir.path()).await.unwrap();
+        assert!(IndexLock::exclusive(dir.path()).is_err());
+
+        drop(first);
+        drop(second);
+        let _exclusive = IndexLock::exclusive(dir.path()).unwrap();
+    }
+}
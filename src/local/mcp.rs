@@ -1,21 +1,40 @@
 //! Local MCP server implementation.
 
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::types::Registry;
+use crate::indexer::workspace::{analyze_repo, read_repo_files};
+use crate::manifests::{Dependency, NpmOptions, collect_deps};
+use crate::registry::{GitClient, LATEST_VERSION, RegistryError, rank_suggestions};
+use crate::types::{Registry, SearchScope};
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use rmcp::{
-    ErrorData as McpError, ServerHandler, ServiceExt,
+    ErrorData as McpError, Peer, RoleServer, ServerHandler, ServiceExt,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::*,
     schemars, tool, tool_handler, tool_router,
     transport::io::stdio,
 };
 use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
 
-use super::indexer::LocalIndexer;
-use super::search::LocalSearch;
+use super::indexer::{EmbeddingUsage, IndexCancelled, IndexResult, LocalIndexer};
+use super::models::{
+    ChunkFilter, ImplWithPackage, KindFilter, PackageOutline, PreferredPackages, SearchOptions,
+    SearchResult, SimilarScope, TestFilter, TypeMethod,
+};
+use super::search::{LocalSearch, SearchResponse};
+
+/// Lines of a result's snippet shown when no snippet length is asked for.
+const SNIPPET_PREVIEW_LINES: usize = 5;
+
+/// Most packages `index_project` indexes at once, whatever a client asks
+/// for, so one call can't flood the registries or the embeddings API.
+const MAX_INDEX_CONCURRENCY: usize = 16;
 
 /// Local MCP Server for Code Intelligence.
 pub struct LocalMcpServer {
@@ -28,18 +47,66 @@ pub struct LocalMcpServer {
 pub struct SearchCodeInput {
     /// The search query - describe what code you're looking for
     pub query: String,
-    /// Package name (optional)
+    /// Package name (optional; omit to search all indexed packages)
     #[serde(default)]
     pub package: Option<String>,
     /// Filter to specific registry (npm, pypi, crates)
     #[serde(default)]
     pub registry: Option<String>,
-    /// Filter to specific version (or "latest")
+    /// Filter to a version: exact, partial ("4", "4.17") or "latest", resolved
+    /// to the newest matching indexed version
     #[serde(default)]
     pub version: Option<String>,
+    /// Only search files under this path prefix (e.g. "src/parser/") or
+    /// matching this glob (e.g. "*/client/*.ts")
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Chunks indexed from test files: "include" (default), "exclude" or
+    /// "only" (tests show how an API is used)
+    #[serde(default)]
+    pub tests: Option<String>,
+    /// Only return public API (default: true unless turned off in config);
+    /// set false to include internal and private helpers
+    #[serde(default)]
+    pub public_only: Option<bool>,
+    /// Only return one kind of chunk: "docs" (READMEs and documentation,
+    /// for "how do I" questions), "examples" (runnable usage from test
+    /// files, as tests "only") or "code" (the API itself, as tests
+    /// "exclude" without documentation); default "all". Not combined with
+    /// `tests`
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Only search "public" packages or tenants' "private" ones; default
+    /// "all"
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Only return functions taking a parameter whose declared type
+    /// contains this (e.g. "Request")
+    #[serde(default)]
+    pub takes: Option<String>,
+    /// Only return functions whose declared return type contains this
+    /// (e.g. "Response")
+    #[serde(default)]
+    pub returns: Option<String>,
+    /// Packages to rank above others at equal relevance, as "registry:name"
+    /// (e.g. ["npm:axios"]); pass the project's direct dependencies
+    #[serde(default)]
+    pub prefer_packages: Vec<String>,
     /// Include full code in results (not just snippets)
     #[serde(default)]
     pub include_code: bool,
+    /// Show each result's whole snippet, cut to this many bytes of code,
+    /// instead of its first 5 lines (default: configured snippet_length)
+    #[serde(default)]
+    pub snippet_length: Option<u32>,
+    /// Include each result's full documentation comment, which often
+    /// answers the question without reading the code
+    #[serde(default)]
+    pub include_docs: bool,
+    /// Minimum relevance score (0-1) a result needs; below it you get "no
+    /// confident matches" instead of weak hits (default: configured min_score)
+    #[serde(default)]
+    pub min_score: Option<f32>,
     /// Maximum results to return (default: 10)
     #[serde(default = "default_limit")]
     pub limit: u32,
@@ -49,6 +116,116 @@ fn default_limit() -> u32 {
     10
 }
 
+/// Parse a tool's `registry` argument. Unknown names are an error naming
+/// the closest registry, since a typo would otherwise just filter every
+/// result out.
+fn parse_registry(registry: &str) -> Result<Registry, String> {
+    Registry::from_str(registry).map_err(|_| {
+        let known: Vec<String> = <Registry as clap::ValueEnum>::value_variants()
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        let hint = match rank_suggestions(registry, &known).first() {
+            Some(suggestion) => format!(", did you mean '{}'?", suggestion),
+            None => ".".to_string(),
+        };
+        format!(
+            "Unknown registry '{}'{} Use one of: {}",
+            registry,
+            hint,
+            known.join(", ")
+        )
+    })
+}
+
+/// Distinct `registry:package@version`s of the results, in rank order.
+fn resolved_versions(results: &[SearchResult]) -> Vec<String> {
+    let mut versions: Vec<String> = Vec::new();
+    for r in results {
+        let version = format!("{}:{}@{}", r.registry, r.package, r.version);
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+    versions
+}
+
+/// Up to `max_lines` of `code` as an indented fenced block tagged with
+/// `language`, so clients can highlight it.
+fn fenced(code: &str, language: Option<&str>, max_lines: usize) -> String {
+    let mut output = format!("   ```{}\n", language.unwrap_or_default());
+    for line in code.lines().take(max_lines) {
+        output.push_str(&format!("   {}\n", line));
+    }
+    output.push_str("   ```\n");
+    output
+}
+
+/// Results as JSON for clients that read structured tool output.
+///
+/// `query` is the search query, or the symbol for similarity searches.
+/// With `include_docs`, each result carries its `docstring`.
+fn structured_results(
+    query: &str,
+    results: &[SearchResult],
+    include_docs: bool,
+) -> serde_json::Value {
+    let results: Vec<_> = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let mut value = serde_json::json!({
+                "rank": i + 1,
+                "score": r.score,
+                "distance": r.distance,
+                "id": r.id,
+                "registry": r.registry,
+                "package": r.package,
+                "version": r.version,
+                "chunk_type": r.chunk_type,
+                "name": r.name,
+                "file_path": r.file_path,
+                "start_line": r.start_line,
+                "end_line": r.end_line,
+                "signature": r.signature,
+                "language": r.language,
+                "deprecated": r.deprecated,
+                "deprecation_message": r.deprecation_message,
+            });
+            if include_docs {
+                value["docstring"] = serde_json::json!(r.docstring);
+            }
+            value
+        })
+        .collect();
+
+    serde_json::json!({ "query": query, "results": results })
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindSimilarInput {
+    /// Name of an indexed symbol (function, class, type, ...)
+    pub symbol: String,
+    /// Package containing the symbol (optional, narrows ambiguous names)
+    #[serde(default)]
+    pub package: Option<String>,
+    /// Registry of the package (npm, pypi, crates)
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// Version of the package
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Search all indexed packages instead of only the symbol's own package
+    #[serde(default)]
+    pub all_packages: bool,
+    /// Include full code in results (not just snippets)
+    #[serde(default)]
+    pub include_code: bool,
+    /// Maximum results to return (default: 10)
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListPackagesInput {
     /// Filter to specific registry (npm, pypi, crates)
@@ -56,22 +233,234 @@ pub struct ListPackagesInput {
     pub registry: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PackageOutlineInput {
+    /// Registry of the package (npm, pypi, crates)
+    pub registry: String,
+    /// Package name
+    pub package: String,
+    /// Indexed version (default: newest indexed version)
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Only list files under this path prefix (e.g. "src/parser/") or
+    /// matching this glob
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Only list public API (default: true unless turned off in config)
+    #[serde(default)]
+    pub public_only: Option<bool>,
+}
+
+/// Render an outline as text: one section per file, one line per symbol.
+fn format_outline(outline: &PackageOutline) -> String {
+    let mut output = format!(
+        "{}:{}@{} ({} symbols in {} files)\n",
+        outline.registry,
+        outline.package,
+        outline.version,
+        outline.symbol_count(),
+        outline.files.len()
+    );
+    if let Some(entry_point) = &outline.entry_point {
+        output.push_str(&format!("Entry point: {} (start here)\n", entry_point));
+    }
+    for file in &outline.files {
+        let marker = if file.entry { " [entry]" } else { "" };
+        output.push_str(&format!("\n{}{}\n", file.path, marker));
+        for symbol in &file.symbols {
+            output.push_str(&format!(
+                "  L{}-{} {} {} `{}`",
+                symbol.start_line,
+                symbol.end_line,
+                symbol.visibility,
+                symbol.chunk_type,
+                symbol.name
+            ));
+            if let Some(signature) = &symbol.signature {
+                output.push_str(&format!(": {}", signature));
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindImplementationsInput {
+    /// Trait to list the implementing types of (e.g. "Iterator")
+    #[serde(default)]
+    pub trait_name: Option<String>,
+    /// Type to list the implemented traits and methods of (e.g. "Counter").
+    /// For Go types the methods are the method set, which decides the
+    /// interfaces they satisfy
+    #[serde(default)]
+    pub type_name: Option<String>,
+    /// Only look in this registry (crates or go, the two whose
+    /// implementations are recorded)
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// Only look in this package
+    #[serde(default)]
+    pub package: Option<String>,
+}
+
+/// Render trait implementations as text, one line per `impl` block.
+fn format_impls(impls: &[ImplWithPackage]) -> String {
+    impls
+        .iter()
+        .map(|imp| {
+            format!(
+                "{}:{}@{} {}:{} `{}`\n",
+                imp.registry, imp.package_name, imp.version, imp.file_path, imp.line, imp.signature
+            )
+        })
+        .collect()
+}
+
+/// Render a type's methods as text, one line per method.
+fn format_methods(methods: &[TypeMethod]) -> String {
+    methods
+        .iter()
+        .map(|method| {
+            format!(
+                "{}:{}@{} {}:{} `{}`\n",
+                method.registry,
+                method.package_name,
+                method.version,
+                method.file_path,
+                method.start_line,
+                method.signature.as_deref().unwrap_or(&method.name)
+            )
+        })
+        .collect()
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct IndexPackageInput {
     /// Registry name (npm, pypi, crates)
     pub registry: String,
     /// Package name
     pub package: String,
-    /// Package version
-    pub version: String,
+    /// Package version (default: latest published version)
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct IndexRepoInput {
+    /// Git URL of the repository (https, ssh or file)
+    pub url: String,
+    /// Tag, branch or commit to index (default: the default branch)
+    #[serde(default)]
+    pub git_ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct IndexProjectInput {
+    /// Project directory to scan for manifests (default: the server's
+    /// working directory)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Number of packages to index concurrently (default: 4, at most 16)
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+/// How indexing one of a project's dependencies went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Outcome {
+    Failed,
+    Indexed,
+    Skipped,
+}
+
+/// Dependencies declared by the manifests under `root`, first occurrence of
+/// each registry and name kept. The project's own packages (workspace
+/// members depending on each other) are left out, as they aren't published.
+fn project_dependencies(root: &Path, npm: NpmOptions) -> Result<Vec<Dependency>> {
+    let mut deps = collect_deps(root, npm)?;
+
+    let own: HashSet<(String, String)> = analyze_repo(&read_repo_files(root)?)
+        .into_iter()
+        .filter_map(|p| Some((p.registry.as_str().to_string(), p.name?)))
+        .collect();
+    let mut seen = HashSet::new();
+    deps.retain(|dep| {
+        let key = (dep.registry.clone(), dep.name.clone());
+        !own.contains(&key) && seen.insert(key)
+    });
+    Ok(deps)
+}
+
+/// Classify an indexing result and describe it in a few words.
+fn describe_result(result: &IndexResult) -> (Outcome, String) {
+    if let Some(license) = result.denied_license.as_ref().filter(|_| result.skipped) {
+        (
+            Outcome::Skipped,
+            format!("skipped (license {} is denied)", license),
+        )
+    } else if let Some(rule) = &result.skip_rule {
+        (Outcome::Skipped, format!("skipped (matches rule {})", rule))
+    } else if result.chunks_indexed > 0 {
+        (
+            Outcome::Indexed,
+            format!(
+                "indexed ({} chunks{}{})",
+                result.chunks_indexed,
+                if result.truncated { ", truncated" } else { "" },
+                describe_usage(&result.embedding_usage)
+            ),
+        )
+    } else if result.is_empty() {
+        (
+            Outcome::Skipped,
+            format!(
+                "no code extracted ({} files, {} not parsed)",
+                result.files_processed, result.files_skipped
+            ),
+        )
+    } else {
+        (Outcome::Skipped, "already indexed".to_string())
+    }
+}
+
+/// ", ~N tokens embedded" with the tokens reuse saved, or nothing when
+/// nothing was embedded or reused.
+fn describe_usage(usage: &EmbeddingUsage) -> String {
+    match (usage.tokens, usage.reused_tokens) {
+        (0, 0) => String::new(),
+        (tokens, 0) => format!(", ~{} tokens embedded", tokens),
+        (tokens, reused) => format!(", ~{} tokens embedded, ~{} reused", tokens, reused),
+    }
+}
+
+/// Render an `index_project` run: counts first, then each package's
+/// outcome, failures first.
+fn format_project_summary(root: &Path, mut outcomes: Vec<(String, Outcome, String)>) -> String {
+    let count = |outcome| outcomes.iter().filter(|(_, o, _)| *o == outcome).count();
+    let mut output = format!(
+        "Indexed dependencies of {}: {} indexed, {} skipped, {} failed\n\n",
+        root.display(),
+        count(Outcome::Indexed),
+        count(Outcome::Skipped),
+        count(Outcome::Failed)
+    );
+    outcomes.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    for (package, _, message) in &outcomes {
+        output.push_str(&format!("- {}: {}\n", package, message));
+    }
+    output
 }
 
 #[tool_router]
 impl LocalMcpServer {
     /// Create a new local MCP server.
     pub async fn new(index_dir: &std::path::Path) -> Result<Self> {
-        let search = LocalSearch::new(index_dir).await?;
-        let indexer = Arc::new(LocalIndexer::new(index_dir).await?);
+        let (search, indexer) = super::open_services(index_dir).await?;
         Ok(Self {
             search,
             indexer,
@@ -79,6 +468,73 @@ impl LocalMcpServer {
         })
     }
 
+    /// Render search results as text for tool output.
+    ///
+    /// Scores run 0-1, higher being a closer match, so readers can tell
+    /// where relevance drops off.
+    ///
+    /// With `include_code`, the results' code is read up front, all blobs
+    /// at once. A result whose code can't be read shows its snippet, up to
+    /// `snippet_lines` lines. With `include_docs`, documentation comments are
+    /// shown in full.
+    async fn format_results(
+        &self,
+        results: &[SearchResult],
+        include_code: bool,
+        include_docs: bool,
+        snippet_lines: usize,
+    ) -> String {
+        let codes = if include_code {
+            let keys: Vec<&str> = results.iter().map(|r| r.storage_key.as_str()).collect();
+            self.search.get_codes(&keys).await
+        } else {
+            vec![None; results.len()]
+        };
+
+        let mut output = String::new();
+        for ((i, r), code) in results.iter().enumerate().zip(codes) {
+            output.push_str(&format!(
+                "{}. {} `{}` in {}:{}@{} (score: {:.2})\n",
+                i + 1,
+                r.chunk_type,
+                r.name,
+                r.registry,
+                r.package,
+                r.version,
+                r.score
+            ));
+            output.push_str(&format!(
+                "   File: {} L{}-{}\n",
+                r.file_path, r.start_line, r.end_line
+            ));
+
+            if let Some(note) = r.deprecation_note() {
+                output.push_str(&format!("   {}\n", note));
+            }
+
+            if let Some(ref sig) = r.signature {
+                output.push_str(&format!("   Signature: {}\n", sig));
+            }
+
+            if include_docs && let Some(ref docs) = r.docstring {
+                output.push_str("   Docs:\n");
+                for line in docs.lines() {
+                    output.push_str(format!("     {}", line).trim_end());
+                    output.push('\n');
+                }
+            }
+
+            match code {
+                Some(code) => output.push_str(&fenced(&code, r.language.as_deref(), usize::MAX)),
+                // Show snippet
+                None => output.push_str(&fenced(&r.snippet, r.language.as_deref(), snippet_lines)),
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
     #[tool(
         description = "Search for code in your project's indexed dependencies using semantic search. Returns relevant functions, classes, types, and documentation."
     )]
@@ -86,19 +542,107 @@ impl LocalMcpServer {
         &self,
         Parameters(input): Parameters<SearchCodeInput>,
     ) -> Result<CallToolResult, McpError> {
+        let tests = match input.tests.as_deref().map(TestFilter::from_str) {
+            None => TestFilter::Include,
+            Some(Ok(tests)) => tests,
+            Some(Err(e)) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{}. Use: include, exclude, or only",
+                    e
+                ))]));
+            }
+        };
+        // Examples and code narrow the tests filter, so they can't be combined with it
+        let kind = match input.kind.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("all") => None,
+            Some("docs" | "documentation") => Some((KindFilter::Docs, tests)),
+            Some("examples" | "example") => Some((KindFilter::All, TestFilter::Only)),
+            Some("code") => Some((KindFilter::Code, TestFilter::Exclude)),
+            Some(_) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "unknown chunk kind: {}. Use: all, docs, examples, or code",
+                    input.kind.as_deref().unwrap_or_default()
+                ))]));
+            }
+        };
+        let (kind, tests) = match kind {
+            Some(_) if input.tests.is_some() => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "kind and tests can't be combined; use one or the other",
+                )]));
+            }
+            Some(kind) => kind,
+            None => (KindFilter::All, tests),
+        };
+        let scope = match input
+            .scope
+            .as_deref()
+            .map(SearchScope::from_str)
+            .transpose()
+        {
+            Ok(scope) => scope,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{}. Use: public, private, or all",
+                    e
+                ))]));
+            }
+        };
+        let registry = match input.registry.as_deref().map(parse_registry).transpose() {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        let preferred = match PreferredPackages::parse(&input.prefer_packages) {
+            Ok(preferred) => preferred,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid prefer_packages: {}",
+                    e
+                ))]));
+            }
+        };
+
         let results = self
             .search
             .search(
                 &input.query,
-                input.package.as_deref(),
-                input.registry.as_deref(),
-                input.version.as_deref(),
-                input.limit as usize,
+                &SearchOptions {
+                    package: input.package.as_deref(),
+                    registry: registry.as_ref().map(Registry::as_str),
+                    version: input.version.as_deref(),
+                    filter: ChunkFilter::new(input.path.as_deref(), tests)
+                        .with_public_only(
+                            input
+                                .public_only
+                                .unwrap_or(self.search.config().public_only),
+                        )
+                        .with_kind(kind)
+                        .with_scope(scope)
+                        .with_signature_types(input.takes.as_deref(), input.returns.as_deref()),
+                    preferred,
+                    mode: None,
+                    min_score: input.min_score,
+                    snippet_length: input.snippet_length.map(|length| length as usize),
+                    limit: input.limit as usize,
+                },
             )
             .await;
 
         match results {
-            Ok(results) => {
+            Ok(SearchResponse {
+                no_confident_matches: Some(low),
+                ..
+            }) => {
+                let mut result = CallToolResult::success(vec![Content::text(format!(
+                    "No confident matches: {}. The indexed packages probably don't cover this; \
+                     don't treat weaker hits as an answer. Try rephrasing or narrowing by package.",
+                    low
+                ))]);
+                result.structured_content =
+                    Some(structured_results(&input.query, &[], input.include_docs));
+                Ok(result)
+            }
+            Ok(SearchResponse { results, .. }) => {
                 if results.is_empty() {
                     return Ok(CallToolResult::success(vec![Content::text(
                         "No results found. Try a different query or make sure dependencies are indexed.",
@@ -106,52 +650,117 @@ impl LocalMcpServer {
                 }
 
                 let mut output = String::new();
-                for (i, r) in results.iter().enumerate() {
+                if input.version.as_deref() == Some(LATEST_VERSION) {
                     output.push_str(&format!(
-                        "{}. {} `{}` in {}:{}@{}\n",
-                        i + 1,
-                        r.chunk_type,
-                        r.name,
-                        r.registry,
-                        r.package,
-                        r.version
+                        "Latest indexed version: {}\n\n",
+                        resolved_versions(&results).join(", ")
                     ));
+                } else if let Some(version) = input.version.as_deref()
+                    && results.iter().any(|r| r.version != version)
+                {
                     output.push_str(&format!(
-                        "   File: {} L{}-{}\n",
-                        r.file_path, r.start_line, r.end_line
+                        "Resolved version: {}\n\n",
+                        resolved_versions(&results).join(", ")
                     ));
+                }
+                // A requested length shows the whole snippet
+                let snippet_lines = if input.snippet_length.is_some() {
+                    usize::MAX
+                } else {
+                    SNIPPET_PREVIEW_LINES
+                };
+                output.push_str(
+                    &self
+                        .format_results(
+                            &results,
+                            input.include_code,
+                            input.include_docs,
+                            snippet_lines,
+                        )
+                        .await,
+                );
 
-                    if let Some(ref sig) = r.signature {
-                        output.push_str(&format!("   Signature: {}\n", sig));
-                    }
+                let mut result = CallToolResult::success(vec![Content::text(output)]);
+                result.structured_content = Some(structured_results(
+                    &input.query,
+                    &results,
+                    input.include_docs,
+                ));
+                Ok(result)
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Search failed: {}",
+                e
+            ))])),
+        }
+    }
 
-                    if input.include_code {
-                        if let Ok(code) = self.search.get_code(&r.storage_key).await {
-                            output.push_str("   ```\n");
-                            for line in code.lines() {
-                                output.push_str(&format!("   {}\n", line));
-                            }
-                            output.push_str("   ```\n");
-                        }
-                    } else {
-                        // Show snippet
-                        let snippet: String = r
-                            .snippet
-                            .lines()
-                            .take(5)
-                            .map(|l| format!("   {}", l))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-                        output.push_str(&snippet);
-                        output.push('\n');
-                    }
-                    output.push('\n');
+    #[tool(
+        description = "Find code similar to an indexed symbol, e.g. alternative implementations or related helpers. Searches the symbol's own package unless all_packages is set."
+    )]
+    async fn find_similar(
+        &self,
+        Parameters(input): Parameters<FindSimilarInput>,
+    ) -> Result<CallToolResult, McpError> {
+        let registry = match input.registry.as_deref().map(parse_registry).transpose() {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        let chunk_ids = match self
+            .search
+            .db()
+            .find_chunk_ids_by_name(
+                &input.symbol,
+                registry.as_ref().map(Registry::as_str),
+                input.package.as_deref(),
+                input.version.as_deref(),
+            )
+            .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Lookup failed: {}",
+                    e
+                ))]));
+            }
+        };
+
+        let Some(chunk_id) = chunk_ids.first() else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Symbol `{}` not found in the index.",
+                input.symbol
+            ))]));
+        };
+
+        let scope = if input.all_packages {
+            SimilarScope::All
+        } else {
+            SimilarScope::Namespace
+        };
+
+        match self
+            .search
+            .similar_to(chunk_id, input.limit as usize, scope)
+            .await
+        {
+            Ok(results) => {
+                if results.is_empty() {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        "No similar code found.",
+                    )]));
                 }
 
-                Ok(CallToolResult::success(vec![Content::text(output)]))
+                let output = self
+                    .format_results(&results, input.include_code, false, SNIPPET_PREVIEW_LINES)
+                    .await;
+                let mut result = CallToolResult::success(vec![Content::text(output)]);
+                result.structured_content =
+                    Some(structured_results(&input.symbol, &results, false));
+                Ok(result)
             }
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Search failed: {}",
+                "Similarity search failed: {}",
                 e
             ))])),
         }
@@ -160,10 +769,24 @@ impl LocalMcpServer {
     #[tool(description = "List all indexed packages in the local index.")]
     async fn list_packages(
         &self,
-        Parameters(_input): Parameters<ListPackagesInput>,
+        Parameters(input): Parameters<ListPackagesInput>,
     ) -> Result<CallToolResult, McpError> {
+        let registry = match input.registry.as_deref().map(parse_registry).transpose() {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
         match self.search.list_versions().await {
-            Ok(versions) => {
+            Ok(mut versions) => {
+                if let Some(registry) = registry {
+                    versions.retain(|v| v.registry == registry.as_str());
+                    if versions.is_empty() {
+                        return Ok(CallToolResult::success(vec![Content::text(format!(
+                            "No {} packages indexed.",
+                            registry
+                        ))]));
+                    }
+                }
                 if versions.is_empty() {
                     return Ok(CallToolResult::success(vec![Content::text(
                         "No packages indexed yet. Run `idx init` to index your project's dependencies.",
@@ -179,6 +802,9 @@ impl LocalMcpServer {
                     if let Some(desc) = ver.description {
                         output.push_str(&format!("  {}\n", desc));
                     }
+                    if let Some(entry_point) = ver.entry_point {
+                        output.push_str(&format!("  Entry point: {}\n", entry_point));
+                    }
                 }
 
                 Ok(CallToolResult::success(vec![Content::text(output)]))
@@ -191,50 +817,380 @@ impl LocalMcpServer {
     }
 
     #[tool(
-        description = "Index a package from a registry. Use this to add a package to the local index so it can be searched."
+        description = "Outline an indexed package: its symbols grouped by file, with type, visibility, signature and line range. The entry module, where to start reading, is listed first. A cheap map of a package's API to explore before searching."
+    )]
+    async fn package_outline(
+        &self,
+        Parameters(input): Parameters<PackageOutlineInput>,
+    ) -> Result<CallToolResult, McpError> {
+        let registry = match parse_registry(&input.registry) {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        let filter = ChunkFilter::new(input.path.as_deref(), TestFilter::Include).with_public_only(
+            input
+                .public_only
+                .unwrap_or(self.search.config().public_only),
+        );
+
+        match self
+            .search
+            .outline(
+                registry.as_str(),
+                &input.package,
+                input.version.as_deref(),
+                &filter,
+            )
+            .await
+        {
+            Ok(Some(outline)) => {
+                let mut result =
+                    CallToolResult::success(vec![Content::text(format_outline(&outline))]);
+                result.structured_content = serde_json::to_value(&outline).ok();
+                Ok(result)
+            }
+            Ok(None) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "{}:{}{} is not indexed. Use index_package to add it.",
+                input.registry,
+                input.package,
+                input
+                    .version
+                    .as_deref()
+                    .map(|v| format!("@{}", v))
+                    .unwrap_or_default()
+            ))])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Outline failed: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Find trait implementations in indexed packages. Give trait_name to list the types implementing it (Rust `impl Trait for Type`), or type_name to list the traits a type implements and its methods (for Go, its method set)."
+    )]
+    async fn find_implementations(
+        &self,
+        Parameters(input): Parameters<FindImplementationsInput>,
+    ) -> Result<CallToolResult, McpError> {
+        let registry = match input.registry.as_deref().map(parse_registry).transpose() {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        if input.trait_name.is_some() == input.type_name.is_some() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Give either trait_name or type_name.",
+            )]));
+        }
+
+        match self.implementations(&input, registry).await {
+            Ok((text, structured)) => {
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(structured);
+                Ok(result)
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Finding implementations failed: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Index a package from a registry. Use this to add a package to the local index so it can be searched. Omit version to index the latest release."
     )]
     async fn index_package(
         &self,
         Parameters(input): Parameters<IndexPackageInput>,
+        cancel: CancellationToken,
     ) -> Result<CallToolResult, McpError> {
-        let registry = match Registry::from_str(&input.registry) {
-            Ok(r) => r,
+        let registry = match parse_registry(&input.registry) {
+            Ok(registry) => registry,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let version = match self
+            .indexer
+            .registry(registry)
+            .resolve_version(&input.package, input.version.as_deref())
+            .await
+        {
+            Ok(v) => v,
             Err(e) => {
                 return Ok(CallToolResult::error(vec![Content::text(format!(
-                    "Invalid registry '{}': {}. Use: npm, pypi, or crates",
-                    input.registry, e
+                    "Failed to resolve version of {}:{}: {}",
+                    input.registry, input.package, e
                 ))]));
             }
         };
 
         match self
             .indexer
-            .index_package(registry, &input.package, &input.version)
+            .index_package_with_cancel(registry, &input.package, &version, &cancel)
             .await
         {
             Ok(result) => {
                 if result.chunks_indexed > 0 {
                     Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Indexed {}:{}@{} ({} chunks from {} files)",
+                        "Indexed {}:{}@{} ({} chunks from {} files{}{})",
                         input.registry,
                         input.package,
-                        input.version,
+                        version,
                         result.chunks_indexed,
-                        result.files_processed
+                        result.files_processed,
+                        if result.truncated {
+                            ", truncated by per-package caps"
+                        } else {
+                            ""
+                        },
+                        describe_usage(&result.embedding_usage)
+                    ))]))
+                } else if result.is_empty() {
+                    Ok(CallToolResult::success(vec![Content::text(format!(
+                        "{}:{}@{} has no indexable code ({} files, {} not parsed)",
+                        input.registry,
+                        input.package,
+                        version,
+                        result.files_processed,
+                        result.files_skipped
                     ))]))
                 } else {
                     Ok(CallToolResult::success(vec![Content::text(format!(
                         "{}:{}@{} was already indexed",
-                        input.registry, input.package, input.version
+                        input.registry, input.package, version
                     ))]))
                 }
             }
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Failed to index {}:{}@{}: {}",
-                input.registry, input.package, input.version, e
+                input.registry, input.package, version, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Index a git repository directly, for code that isn't a published package (examples, monorepos, unpublished tools). Clones the repository at git_ref (default: the default branch) into a temporary directory, removed afterwards, and indexes it as git:<host>/<owner>/<repo>@<ref>."
+    )]
+    async fn index_repo(
+        &self,
+        Parameters(input): Parameters<IndexRepoInput>,
+        cancel: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        if self.indexer.config().offline {
+            return Ok(CallToolResult::error(vec![Content::text(
+                RegistryError::OfflineMode.to_string(),
+            )]));
+        }
+
+        let source = match GitClient::new()
+            .fetch_repo(&input.url, input.git_ref.as_deref())
+            .await
+        {
+            Ok(source) => source,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to fetch {}: {}",
+                    input.url, e
+                ))]));
+            }
+        };
+        let package = format!("{}:{}@{}", Registry::Git, source.name, source.version);
+
+        match self
+            .indexer
+            .index_source_with_cancel(
+                Registry::Git,
+                &source.name,
+                &source.version,
+                &source.files,
+                &cancel,
+            )
+            .await
+        {
+            Ok(result) => {
+                let outcome = if result.chunks_indexed > 0 {
+                    format!(
+                        "Indexed {} ({} chunks from {} files{})",
+                        package,
+                        result.chunks_indexed,
+                        result.files_processed,
+                        describe_usage(&result.embedding_usage)
+                    )
+                } else if result.is_empty() {
+                    format!("{} has no indexable code", package)
+                } else {
+                    format!("{} was already indexed", package)
+                };
+                let packages: Vec<String> = source
+                    .packages
+                    .iter()
+                    .filter_map(|p| Some(format!("{}:{}", p.registry, p.name.as_ref()?)))
+                    .collect();
+                let text = if packages.is_empty() {
+                    outcome
+                } else {
+                    format!("{}\nDetected packages: {}", outcome, packages.join(", "))
+                };
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to index {}: {}",
+                package, e
             ))])),
         }
     }
+
+    #[tool(
+        description = "Index every dependency of a project in one call. Finds the project's manifests (package.json, Cargo.toml, pyproject.toml, go.mod, pom.xml, ...) and indexes the declared versions concurrently, reporting progress per package. Returns indexed/skipped/failed counts and each package's outcome."
+    )]
+    async fn index_project(
+        &self,
+        Parameters(input): Parameters<IndexProjectInput>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+        cancel: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let root = match input.path {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::current_dir() {
+                Ok(dir) => dir,
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "No project path given and the working directory is unavailable: {}",
+                        e
+                    ))]));
+                }
+            },
+        };
+
+        let deps = match project_dependencies(&root, self.indexer.config().npm_options()) {
+            Ok(deps) => deps,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to read manifests under {}: {}",
+                    root.display(),
+                    e
+                ))]));
+            }
+        };
+        if deps.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No dependencies found under {}",
+                root.display()
+            ))]));
+        }
+        if let Err(e) = self.indexer.check_embedding_dimensions().await {
+            return Ok(CallToolResult::error(vec![Content::text(e.to_string())]));
+        }
+
+        let progress_token = meta.get_progress_token();
+        let total = deps.len();
+        let finished = AtomicUsize::new(0);
+        let outcomes: Vec<(String, Outcome, String)> = stream::iter(deps.into_iter().map(|dep| {
+            let (progress_token, finished, peer, cancel) =
+                (&progress_token, &finished, &peer, &cancel);
+            async move {
+                let package = format!("{}:{}@{}", dep.registry, dep.name, dep.version);
+                let (outcome, message) = self.index_dependency(&dep, cancel).await;
+
+                let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                if let Some(token) = progress_token {
+                    let _ = peer
+                        .notify_progress(ProgressNotificationParam {
+                            progress_token: token.clone(),
+                            progress: done as f64,
+                            total: Some(total as f64),
+                            message: Some(format!("{}: {}", package, message)),
+                        })
+                        .await;
+                }
+                (package, outcome, message)
+            }
+        }))
+        .buffer_unordered(input.concurrency.clamp(1, MAX_INDEX_CONCURRENCY))
+        .collect()
+        .await;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            format_project_summary(&root, outcomes),
+        )]))
+    }
+}
+
+impl LocalMcpServer {
+    /// Text and structured output of `find_implementations`: the types
+    /// implementing `input.trait_name`, or the traits and methods of
+    /// `input.type_name`.
+    async fn implementations(
+        &self,
+        input: &FindImplementationsInput,
+        registry: Option<Registry>,
+    ) -> Result<(String, serde_json::Value)> {
+        let registry = registry.as_ref().map(Registry::as_str);
+        let package = input.package.as_deref();
+
+        if let Some(trait_name) = &input.trait_name {
+            let impls = self
+                .search
+                .implementations_of(trait_name, registry, package)
+                .await?;
+            let text = if impls.is_empty() {
+                format!(
+                    "No implementations of `{}` found. Packages indexed before \
+                     implementations were recorded need re-indexing.",
+                    trait_name
+                )
+            } else {
+                format!(
+                    "{} implementations of `{}`:\n{}",
+                    impls.len(),
+                    trait_name,
+                    format_impls(&impls)
+                )
+            };
+            return Ok((text, serde_json::json!({ "implementations": impls })));
+        }
+
+        let type_name = input.type_name.as_deref().unwrap_or_default();
+        let traits = self
+            .search
+            .traits_implemented_by(type_name, registry, package)
+            .await?;
+        let methods = self.search.method_set(type_name, registry, package).await?;
+        let text = format!(
+            "`{}` implements {} traits:\n{}\n{} methods:\n{}",
+            type_name,
+            traits.len(),
+            format_impls(&traits),
+            methods.len(),
+            format_methods(&methods)
+        );
+        Ok((
+            text,
+            serde_json::json!({ "traits": traits, "methods": methods }),
+        ))
+    }
+
+    /// Index one dependency found by `index_project`.
+    async fn index_dependency(
+        &self,
+        dep: &Dependency,
+        cancel: &CancellationToken,
+    ) -> (Outcome, String) {
+        let registry = match Registry::from_str(&dep.registry) {
+            Ok(registry) => registry,
+            Err(e) => return (Outcome::Failed, format!("error: {}", e)),
+        };
+        match self
+            .indexer
+            .index_package_with_cancel(registry, &dep.name, &dep.version, cancel)
+            .await
+        {
+            Ok(result) => describe_result(&result),
+            Err(e) if e.is::<IndexCancelled>() => (Outcome::Failed, "cancelled".to_string()),
+            Err(e) => (Outcome::Failed, format!("failed: {}", e)),
+        }
+    }
 }
 
 #[tool_handler]
@@ -252,13 +1208,25 @@ impl ServerHandler for LocalMcpServer {
             },
             instructions: Some(
                 "Local Code Intelligence - semantic search for your project's dependencies. \
-                 Tools: search_code, list_packages, index_package."
+                 Tools: search_code, find_similar, package_outline, find_implementations, \
+                 list_packages, index_package, index_repo, index_project."
                     .to_string(),
             ),
         }
     }
 }
 
+/// JSON Schemas of every tool's input (and output, for tools with
+/// structured output), in the shape of an MCP `tools/list` result.
+///
+/// Generated from the tool router, so it always matches what the server
+/// registers.
+pub fn tool_schemas() -> serde_json::Value {
+    let mut tools = LocalMcpServer::tool_router().list_all();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    serde_json::json!({ "tools": tools })
+}
+
 /// Run the local MCP server over stdio.
 pub async fn run_local(index_dir: &std::path::Path) -> Result<()> {
     let server = LocalMcpServer::new(index_dir).await?;
@@ -266,3 +1234,388 @@ pub async fn run_local(index_dir: &std::path::Path) -> Result<()> {
     service.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::models::OutlineSymbol;
+
+    fn result(package: &str, version: &str, name: &str, score: f32) -> SearchResult {
+        SearchResult {
+            id: name.to_string(),
+            registry: "npm".to_string(),
+            package: package.to_string(),
+            version: version.to_string(),
+            chunk_type: "function".to_string(),
+            name: name.to_string(),
+            parent: None,
+            file_path: "index.js".to_string(),
+            start_line: 1,
+            end_line: 2,
+            visibility: "public".to_string(),
+            signature: None,
+            docstring: None,
+            snippet: String::new(),
+            language: Some("javascript".to_string()),
+            storage_key: String::new(),
+            deprecated: false,
+            deprecation_message: None,
+            distance: 0.0,
+            score,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn test_structured_results_carry_rank_score_and_version() {
+        let results = vec![
+            result("zod", "3.22.4", "parse", 0.9),
+            result("zod", "3.22.4", "safeParse", 0.5),
+            result("yup", "1.3.0", "validate", 0.25),
+        ];
+
+        let value = structured_results("parse input", &results, false);
+        assert_eq!(value["query"], "parse input");
+        assert_eq!(value["results"][0]["language"], "javascript");
+        assert_eq!(value["results"][1]["rank"], 2);
+        assert_eq!(value["results"][1]["score"], 0.5);
+        assert_eq!(value["results"][2]["version"], "1.3.0");
+
+        assert_eq!(
+            resolved_versions(&results),
+            vec!["npm:zod@3.22.4", "npm:yup@1.3.0"]
+        );
+    }
+
+    #[test]
+    fn test_tool_schemas_describe_inputs() {
+        let schemas = tool_schemas();
+        let tools = schemas["tools"].as_array().unwrap();
+        let tool = |name: &str| {
+            tools
+                .iter()
+                .find(|t| t["name"] == name)
+                .unwrap_or_else(|| panic!("{} missing from schema dump", name))
+        };
+        let properties = |name: &str| {
+            let mut fields: Vec<String> = tool(name)["inputSchema"]["properties"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect();
+            fields.sort();
+            fields
+        };
+
+        let search = properties("search_code");
+        for field in ["query", "package", "registry", "version", "path", "tests"] {
+            assert!(search.contains(&field.to_string()), "search_code.{}", field);
+        }
+        assert_eq!(
+            tool("search_code")["inputSchema"]["required"],
+            serde_json::json!(["query"])
+        );
+        assert_eq!(properties("list_packages"), vec!["registry"]);
+        assert_eq!(
+            properties("index_package"),
+            vec!["package", "registry", "version"]
+        );
+        assert!(tool("index_package")["description"].is_string());
+        assert_eq!(properties("index_repo"), vec!["git_ref", "url"]);
+        assert_eq!(
+            properties("find_implementations"),
+            vec!["package", "registry", "trait_name", "type_name"]
+        );
+
+        let names: Vec<_> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_fenced_code_carries_language() {
+        let code = "fn parse() {\n    todo!()\n}";
+
+        assert_eq!(
+            fenced(code, Some("rust"), 2),
+            "   ```rust\n   fn parse() {\n       todo!()\n   ```\n"
+        );
+        assert_eq!(fenced("x = 1", None, 5), "   ```\n   x = 1\n   ```\n");
+    }
+
+    #[test]
+    fn test_format_outline() {
+        let symbol = |file: &str, name: &str, line: i64, signature: Option<&str>| OutlineSymbol {
+            name: name.to_string(),
+            chunk_type: "function".to_string(),
+            visibility: "public".to_string(),
+            signature: signature.map(str::to_string),
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 4,
+        };
+        let outline = PackageOutline::new(
+            "npm",
+            "zod",
+            "3.22.4",
+            vec![
+                symbol("lib/index.js", "parse", 1, Some("function parse(input)")),
+                symbol("lib/index.js", "safeParse", 10, None),
+                symbol("lib/types.js", "string", 3, None),
+            ],
+        );
+
+        assert_eq!(
+            format_outline(&outline),
+            "npm:zod@3.22.4 (3 symbols in 2 files)\n\
+             \nlib/index.js\n\
+             \x20 L1-5 public function `parse`: function parse(input)\n\
+             \x20 L10-14 public function `safeParse`\n\
+             \nlib/types.js\n\
+             \x20 L3-7 public function `string`\n"
+        );
+
+        let outline = outline.with_entry_point(Some("lib/types.js".to_string()));
+        assert_eq!(
+            format_outline(&outline),
+            "npm:zod@3.22.4 (3 symbols in 2 files)\n\
+             Entry point: lib/types.js (start here)\n\
+             \nlib/types.js [entry]\n\
+             \x20 L3-7 public function `string`\n\
+             \nlib/index.js\n\
+             \x20 L1-5 public function `parse`: function parse(input)\n\
+             \x20 L10-14 public function `safeParse`\n"
+        );
+    }
+
+    #[test]
+    fn test_format_impls() {
+        let imp = |type_name: &str, line| ImplWithPackage {
+            trait_name: "Iterator".to_string(),
+            type_name: type_name.to_string(),
+            signature: format!("impl Iterator for {}", type_name),
+            file_path: "src/iter.rs".to_string(),
+            line,
+            registry: "crates".to_string(),
+            package_name: "itertools".to_string(),
+            version: "0.13.0".to_string(),
+        };
+
+        assert_eq!(
+            format_impls(&[imp("Step", 12), imp("Batching", 40)]),
+            "crates:itertools@0.13.0 src/iter.rs:12 `impl Iterator for Step`\n\
+             crates:itertools@0.13.0 src/iter.rs:40 `impl Iterator for Batching`\n"
+        );
+    }
+
+    #[test]
+    fn test_project_dependencies_skip_own_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("package.json", r#"{"workspaces": ["packages/*"]}"#);
+        write(
+            "packages/web/package.json",
+            r#"{"name": "@acme/web", "dependencies": {"@acme/ui": "^1.0.0", "axios": "^1.7.9"}}"#,
+        );
+        write("packages/web/index.ts", "export {}");
+        write(
+            "packages/ui/package.json",
+            r#"{"name": "@acme/ui", "dependencies": {"axios": "^1.6.0"}}"#,
+        );
+        write("packages/ui/index.ts", "export {}");
+
+        let deps = project_dependencies(dir.path(), NpmOptions::default()).unwrap();
+        let names: Vec<_> = deps.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["axios"]);
+    }
+
+    #[test]
+    fn test_project_summary_reports_each_package() {
+        let result = |chunks_indexed, skipped| IndexResult {
+            version_id: String::new(),
+            chunks_indexed,
+            files_processed: 3,
+            files_skipped: 0,
+            skipped,
+            truncated: false,
+            embeddings_reused: 0,
+            embedding_usage: EmbeddingUsage::default(),
+            denied_license: None,
+            skip_rule: None,
+        };
+        let (outcome, message) = describe_result(&result(42, false));
+        assert_eq!(outcome, Outcome::Indexed);
+        assert_eq!(message, "indexed (42 chunks)");
+        let embedded = IndexResult {
+            embedding_usage: EmbeddingUsage {
+                texts: 40,
+                chars: 36_000,
+                tokens: 12_000,
+                reused_tokens: 600,
+            },
+            ..result(42, false)
+        };
+        assert_eq!(
+            describe_result(&embedded).1,
+            "indexed (42 chunks, ~12000 tokens embedded, ~600 reused)"
+        );
+        let (outcome, message) = describe_result(&result(0, true));
+        assert_eq!(outcome, Outcome::Skipped);
+        assert_eq!(message, "already indexed");
+
+        let outcomes = vec![
+            ("npm:zod@3.22.4".to_string(), Outcome::Skipped, message),
+            (
+                "npm:left-pad@9.9.9".to_string(),
+                Outcome::Failed,
+                "failed: version not found".to_string(),
+            ),
+            (
+                "npm:axios@1.7.9".to_string(),
+                Outcome::Indexed,
+                "indexed (10 chunks)".to_string(),
+            ),
+        ];
+        assert_eq!(
+            format_project_summary(Path::new("/work/app"), outcomes),
+            "Indexed dependencies of /work/app: 1 indexed, 1 skipped, 1 failed\n\n\
+             - npm:left-pad@9.9.9: failed: version not found\n\
+             - npm:axios@1.7.9: indexed (10 chunks)\n\
+             - npm:zod@3.22.4: already indexed\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_registry_suggests_closest() {
+        assert_eq!(parse_registry("crates"), Ok(Registry::Crates));
+        assert_eq!(parse_registry("NPM"), Ok(Registry::Npm));
+
+        let err = parse_registry("crate").unwrap_err();
+        assert!(err.starts_with("Unknown registry 'crate', did you mean 'crates'?"));
+        assert!(err.ends_with("npm, pypi, crates, go, maven, pub, git"));
+
+        let err = parse_registry("rubygems").unwrap_err();
+        assert!(err.starts_with("Unknown registry 'rubygems'. Use one of:"));
+    }
+
+    #[tokio::test]
+    async fn test_format_results_reads_code_of_every_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = super::super::storage::LocalStorage::new(dir.path().join("blobs"))
+            .await
+            .unwrap();
+        let server = LocalMcpServer::new(dir.path()).await.unwrap();
+
+        let mut results = Vec::new();
+        for name in ["get", "post"] {
+            let code = format!("function {}(url) {{\n  return fetch(url);\n}}", name);
+            let mut r = result("axios", "1.7.9", name, 0.9);
+            r.storage_key = storage
+                .put("npm/axios/1.7.9", code.as_bytes())
+                .await
+                .unwrap();
+            results.push(r);
+        }
+        // A pruned blob: shown with its snippet instead
+        let mut pruned = result("axios", "1.7.9", "put", 0.5);
+        pruned.storage_key = "npm/axios/1.7.9/missing".to_string();
+        pruned.snippet = "function put(url)".to_string();
+        results.push(pruned);
+
+        let output = server
+            .format_results(&results, true, false, SNIPPET_PREVIEW_LINES)
+            .await;
+        assert!(output.contains("function get(url) {\n     return fetch(url);"));
+        assert!(output.contains("function post(url) {\n     return fetch(url);"));
+        assert!(output.contains("3. function `put`"));
+        assert!(output.contains("function put(url)\n   ```"));
+    }
+
+    #[tokio::test]
+    async fn test_format_results_include_docs_and_snippet_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = LocalMcpServer::new(dir.path()).await.unwrap();
+
+        let mut r = result("axios", "1.7.9", "get", 0.9);
+        r.docstring = Some("Send a GET request.\n\nRejects on non-2xx responses.".to_string());
+        r.snippet = (1..=8)
+            .map(|i| format!("line{}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let results = vec![r];
+
+        let preview = server
+            .format_results(&results, false, false, SNIPPET_PREVIEW_LINES)
+            .await;
+        assert!(!preview.contains("Docs:"));
+        assert!(preview.contains("line5\n   ```"));
+        assert!(!preview.contains("line6"));
+
+        let output = server
+            .format_results(&results, false, true, usize::MAX)
+            .await;
+        assert!(output.contains(
+            "   Docs:\n     Send a GET request.\n\n     Rejects on non-2xx responses.\n   ```"
+        ));
+        assert!(output.contains("line8\n   ```"));
+
+        let value = structured_results("get", &results, true);
+        assert_eq!(
+            value["results"][0]["docstring"],
+            "Send a GET request.\n\nRejects on non-2xx responses."
+        );
+        let value = structured_results("get", &results, false);
+        assert!(value["results"][0].get("docstring").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_registry_is_an_error_not_empty_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = LocalMcpServer::new(dir.path()).await.unwrap();
+        let text = |result: &CallToolResult| {
+            assert_eq!(result.is_error, Some(true));
+            result.content[0].as_text().unwrap().text.clone()
+        };
+
+        let input = serde_json::json!({"query": "parse json", "registry": "crate"});
+        let result = server
+            .search_code(Parameters(serde_json::from_value(input).unwrap()))
+            .await
+            .unwrap();
+        assert!(text(&result).contains("did you mean 'crates'?"));
+
+        let input = serde_json::json!({"query": "parse json", "kind": "tests"});
+        let result = server
+            .search_code(Parameters(serde_json::from_value(input).unwrap()))
+            .await
+            .unwrap();
+        assert!(text(&result).contains("Use: all, docs, examples, or code"));
+
+        let input = serde_json::json!({"query": "parse json", "kind": "code", "tests": "only"});
+        let result = server
+            .search_code(Parameters(serde_json::from_value(input).unwrap()))
+            .await
+            .unwrap();
+        assert!(text(&result).contains("kind and tests can't be combined"));
+
+        let input = serde_json::json!({"query": "parse json", "scope": "tenant"});
+        let result = server
+            .search_code(Parameters(serde_json::from_value(input).unwrap()))
+            .await
+            .unwrap();
+        assert!(text(&result).contains("Use: public, private, or all"));
+
+        let input = serde_json::json!({"registry": "pypy"});
+        let result = server
+            .list_packages(Parameters(serde_json::from_value(input).unwrap()))
+            .await
+            .unwrap();
+        assert!(text(&result).contains("did you mean 'pypi'?"));
+    }
+}
@@ -3,27 +3,72 @@
 //! Stores indices in `.index/` directory within the project:
 //! - `db.sqlite` - package metadata and vector embeddings
 //! - `blobs/` - code chunks (content-addressed)
+//! - `vectors/` - LanceDB embeddings (unless the Turbopuffer backend is configured)
+//! - `cache/` - cached registry responses (safe to delete)
+//! - `index.lock` - advisory lock taken while writing vectors and chunks
+//!
+//! A global index with the same layout (see [`global_index_dir`]) holds
+//! public packages shared by every project and is searched alongside it.
 
 #![allow(dead_code)]
 
+mod batching;
 mod config;
 mod db;
+mod embedding_cache;
+pub mod http;
 mod indexer;
+mod license;
+mod lock;
 pub mod mcp;
 pub mod models;
+mod query_cache;
+mod reset;
 mod search;
 mod storage;
+mod tokens;
+mod transform;
 mod vector;
 
-pub use config::LocalConfig;
-pub use indexer::LocalIndexer;
+pub use config::{DEFAULT_PROFILE, EnvSecret, LicenseAction, LocalConfig, VectorBackendKind};
+pub use indexer::{EmbeddingUsage, IndexCancelled, IndexEvent, LocalIndexer, cancel_on_ctrl_c};
+pub use lock::IndexLock;
+pub use reset::reset_index;
 pub use search::LocalSearch;
+pub use transform::BUILTIN_TRANSFORMERS;
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Context;
 
 /// The name of the index directory.
 pub const INDEX_DIR_NAME: &str = ".index";
 
+/// Registry response cache directory, inside the index directory.
+pub const CACHE_DIR_NAME: &str = "cache";
+
+/// Environment variable overriding the global index directory.
+pub const IDX_HOME_ENV: &str = "IDX_HOME";
+
+/// Set by `--offline` / `IDX_OFFLINE` (see [`set_offline`]).
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn offline mode on or off for this process.
+///
+/// Offline, registry clients only serve cached responses and embeddings are
+/// only requested from an API on this machine, so nothing leaves the host.
+/// Searching, listing and stats work on the existing index as usual.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether offline mode is on (see [`set_offline`]).
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
 /// Find the `.index/` directory by walking up from the given path.
 pub fn find_index_root(start: &Path) -> Option<PathBuf> {
     let mut current = start.to_path_buf();
@@ -52,3 +97,52 @@ pub fn get_index_dir() -> Option<PathBuf> {
         .ok()
         .and_then(|cwd| find_index_root(&cwd))
 }
+
+/// The global index shared by all projects: `$IDX_HOME`, or `idx/` in the
+/// user data directory (`$XDG_DATA_HOME/idx`, usually `~/.local/share/idx`).
+///
+/// Only public registry packages are written there (`idx index --global`).
+/// Namespaces are `registry/name/version` in both stores, so the same
+/// package version never means different code.
+pub fn global_index_dir() -> Option<PathBuf> {
+    std::env::var_os(IDX_HOME_ENV)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_dir().map(|dir| dir.join("idx")))
+}
+
+/// Open the global index to search alongside `index_dir`.
+///
+/// `None` until something has been indexed globally, or when `index_dir` is
+/// the global index itself.
+pub async fn open_global_search(
+    index_dir: &Path,
+    profile: Option<&str>,
+) -> anyhow::Result<Option<LocalSearch>> {
+    let Some(global_dir) = global_index_dir().filter(|dir| dir.join("db.sqlite").is_file()) else {
+        return Ok(None);
+    };
+    let same = match (global_dir.canonicalize(), index_dir.canonicalize()) {
+        (Ok(global), Ok(project)) => global == project,
+        _ => global_dir == index_dir,
+    };
+    if same {
+        return Ok(None);
+    }
+
+    let search = LocalSearch::new_with_profile(&global_dir, profile)
+        .await
+        .with_context(|| format!("Failed to open global index at {}", global_dir.display()))?;
+    Ok(Some(search))
+}
+
+/// Open the search and indexing services for an index directory.
+///
+/// Shared by the MCP and HTTP servers.
+pub async fn open_services(index_dir: &Path) -> anyhow::Result<(LocalSearch, Arc<LocalIndexer>)> {
+    let search = LocalSearch::new(index_dir)
+        .await?
+        .with_shared(open_global_search(index_dir, None).await?);
+    let indexer = Arc::new(LocalIndexer::new(index_dir).await?);
+    Ok((search, indexer))
+}
@@ -1,8 +1,13 @@
 //! Data models for local storage.
 
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::indexer::Param;
+use crate::types::{Registry, SearchScope, TenantId, Visibility};
+
 // ============================================================================
 // Version Status
 // ============================================================================
@@ -20,6 +25,10 @@ pub enum VersionStatus {
     Failed,
     /// User explicitly skipped
     Skipped,
+    /// Indexed, but no code could be extracted
+    Empty,
+    /// Chunks stored and searchable by name and signature, embeddings pending
+    MetadataIndexed,
 }
 
 impl std::fmt::Display for VersionStatus {
@@ -29,6 +38,8 @@ impl std::fmt::Display for VersionStatus {
             Self::Indexed => write!(f, "indexed"),
             Self::Failed => write!(f, "failed"),
             Self::Skipped => write!(f, "skipped"),
+            Self::Empty => write!(f, "empty"),
+            Self::MetadataIndexed => write!(f, "metadata_indexed"),
         }
     }
 }
@@ -42,11 +53,63 @@ impl std::str::FromStr for VersionStatus {
             "indexed" => Ok(Self::Indexed),
             "failed" => Ok(Self::Failed),
             "skipped" => Ok(Self::Skipped),
+            "empty" => Ok(Self::Empty),
+            "metadata_indexed" => Ok(Self::MetadataIndexed),
             _ => Err(format!("invalid version status: {}", s)),
         }
     }
 }
 
+/// Why a version failed to index, stored alongside its error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Fetching the package from its registry failed
+    Download,
+    /// The registry has no source to index (missing package, version or sources)
+    NoSource,
+    /// Source files couldn't be parsed into chunks
+    Parse,
+    /// The embeddings API request failed
+    Embedding,
+    /// Writing blobs, vectors or chunk rows failed
+    Storage,
+}
+
+impl FailureKind {
+    /// Whether retrying might succeed without anything else changing.
+    pub fn is_transient(self) -> bool {
+        matches!(self, Self::Download | Self::Embedding | Self::Storage)
+    }
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Download => write!(f, "download"),
+            Self::NoSource => write!(f, "no_source"),
+            Self::Parse => write!(f, "parse"),
+            Self::Embedding => write!(f, "embedding"),
+            Self::Storage => write!(f, "storage"),
+        }
+    }
+}
+
+impl std::str::FromStr for FailureKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "download" => Ok(Self::Download),
+            "no_source" => Ok(Self::NoSource),
+            "parse" => Ok(Self::Parse),
+            "embedding" => Ok(Self::Embedding),
+            "storage" => Ok(Self::Storage),
+            _ => Err(format!("invalid failure kind: {}", s)),
+        }
+    }
+}
+
 // ============================================================================
 // Package Models
 // ============================================================================
@@ -59,6 +122,9 @@ pub struct PackageRow {
     pub name: String,
     pub description: Option<String>,
     pub created_at: String,
+    pub license: Option<String>,
+    /// Source repository URL.
+    pub repository: Option<String>,
 }
 
 /// Input for creating a package.
@@ -67,6 +133,16 @@ pub struct CreatePackage {
     pub registry: String,
     pub name: String,
     pub description: Option<String>,
+    /// Tenant owning a private package, None for public ones
+    pub tenant: Option<TenantId>,
+}
+
+/// Package metadata reported by the registry.
+#[derive(Debug, Clone, Default)]
+pub struct PackageMetadata {
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
 }
 
 // ============================================================================
@@ -84,6 +160,18 @@ pub struct VersionRow {
     pub chunk_count: i32,
     pub indexed_at: Option<String>,
     pub created_at: String,
+    /// Number of failed indexing attempts.
+    pub attempts: i32,
+    /// When the version last failed.
+    pub failed_at: Option<String>,
+    /// Category of the last failure, convert with FailureKind.
+    pub failure_kind: Option<String>,
+    /// Files downloaded for an `empty` version.
+    pub files_seen: i32,
+    /// Files of an `empty` version that weren't parsed.
+    pub files_skipped: i32,
+    /// Path of the package's entry module, if one was detected.
+    pub entry_point: Option<String>,
 }
 
 impl VersionRow {
@@ -102,11 +190,22 @@ pub struct VersionWithPackage {
     pub error_message: Option<String>,
     pub chunk_count: i32,
     pub indexed_at: Option<String>,
+    pub attempts: i32,
+    pub failed_at: Option<String>,
+    pub failure_kind: Option<String>,
+    pub files_seen: i32,
+    pub files_skipped: i32,
+    pub entry_point: Option<String>,
     // Package fields
     pub package_id: String,
     pub registry: String,
     pub name: String,
     pub description: Option<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    /// Tenant owning a private package, empty for public ones
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tenant: String,
 }
 
 impl VersionWithPackage {
@@ -114,9 +213,101 @@ impl VersionWithPackage {
         self.status.parse().unwrap_or_default()
     }
 
-    /// Build the namespace string for this version.
-    pub fn namespace(&self) -> String {
-        format!("{}/{}/{}", self.registry, self.name, self.version)
+    /// Tenant owning the package, if it is private.
+    pub fn tenant(&self) -> Option<TenantId> {
+        self.tenant.parse().ok()
+    }
+
+    /// Category of the last failure, if it was recorded.
+    pub fn failure_kind(&self) -> Option<FailureKind> {
+        self.failure_kind.as_deref().and_then(|k| k.parse().ok())
+    }
+
+    /// Namespace of this version.
+    pub fn namespace(&self) -> NamespaceSpec {
+        NamespaceSpec::new(&self.registry, &self.name, &self.version).with_tenant(self.tenant())
+    }
+}
+
+/// The `registry/name/version` namespace an indexed package version's chunks
+/// and vectors are stored under.
+///
+/// Package names may contain `/` (scoped npm packages like `@scope/name`, Go
+/// module paths) and `:` (Maven `group:artifact`), but registries and
+/// versions never contain `/`, so the first and last `/` delimit the name.
+///
+/// Private packages are prefixed with their tenant, as
+/// `private/<tenant>/registry/name/version`, the layout of
+/// [`Registry::private_package_namespace`](crate::types::Registry::private_package_namespace).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamespaceSpec {
+    pub registry: String,
+    pub name: String,
+    pub version: String,
+    /// Tenant owning a private package
+    pub tenant: Option<TenantId>,
+}
+
+impl NamespaceSpec {
+    pub fn new(
+        registry: impl Into<String>,
+        name: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        Self {
+            registry: registry.into(),
+            name: name.into(),
+            version: version.into(),
+            tenant: None,
+        }
+    }
+
+    /// Place the namespace under `tenant`'s private namespaces.
+    pub fn with_tenant(mut self, tenant: Option<TenantId>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// The `registry:name@version` spec users type for this version.
+    pub fn package_spec(&self) -> String {
+        format!("{}:{}@{}", self.registry, self.name, self.version)
+    }
+}
+
+impl std::fmt::Display for NamespaceSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tenant) = &self.tenant {
+            write!(f, "private/{}/", tenant)?;
+        }
+        write!(f, "{}/{}/{}", self.registry, self.name, self.version)
+    }
+}
+
+impl std::str::FromStr for NamespaceSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rest) = s.strip_prefix("private/") {
+            let (tenant, rest) = rest
+                .split_once('/')
+                .ok_or_else(|| format!("expected private/tenant/..., got '{}'", s))?;
+            let tenant = tenant
+                .parse::<TenantId>()
+                .map_err(|e| format!("invalid tenant in '{}': {}", s, e))?;
+            return Ok(rest.parse::<Self>()?.with_tenant(Some(tenant)));
+        }
+
+        let (registry, name, version) = s
+            .split_once('/')
+            .and_then(|(registry, rest)| {
+                let (name, version) = rest.rsplit_once('/')?;
+                Some((registry, name, version))
+            })
+            .ok_or_else(|| format!("expected registry/name/version, got '{}'", s))?;
+        if registry.is_empty() || name.is_empty() || version.is_empty() {
+            return Err(format!("expected registry/name/version, got '{}'", s));
+        }
+        Ok(Self::new(registry, name, version))
     }
 }
 
@@ -149,6 +340,12 @@ pub struct ChunkRow {
     pub storage_key: String,
     pub content_hash: String,
     pub vector: Vec<u8>,
+    pub file_storage_key: Option<String>,
+    pub start_byte: i64,
+    pub end_byte: i64,
+    pub deprecated: bool,
+    pub deprecation_message: Option<String>,
+    pub parent: Option<String>,
 }
 
 /// Chunk with joined package/version info.
@@ -166,6 +363,12 @@ pub struct ChunkWithPackage {
     pub docstring: Option<String>,
     pub snippet: String,
     pub storage_key: String,
+    pub file_storage_key: Option<String>,
+    pub start_byte: i64,
+    pub end_byte: i64,
+    pub deprecated: bool,
+    pub deprecation_message: Option<String>,
+    pub parent: Option<String>,
     pub registry: String,
     pub package_name: String,
     pub version: String,
@@ -184,11 +387,53 @@ pub struct CreateChunk {
     pub end_line: u32,
     pub visibility: String,
     pub signature: Option<String>,
+    /// Declared parameters of a function or method, stored as JSON.
+    pub params: Vec<Param>,
+    /// Declared return type of a function or method.
+    pub returns: Option<String>,
     pub docstring: Option<String>,
     pub snippet: String,
     pub storage_key: String,
     pub content_hash: String,
     pub vector: Vec<f32>,
+    /// Model that produced `vector`, so it's only reused for that model.
+    pub embedding_model: String,
+    /// Hash of the text `vector` was embedded from, so it's only reused for
+    /// that exact text (none without an embedding).
+    pub embedding_hash: Option<String>,
+    /// Storage key of the whole source file (only when source files are stored).
+    pub file_storage_key: Option<String>,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub deprecated: bool,
+    pub deprecation_message: Option<String>,
+    /// Enclosing class, trait or module, such as the type of a method.
+    pub parent: Option<String>,
+    /// Hash of the documentation text `doc_vector` was embedded from.
+    pub doc_hash: Option<String>,
+    /// Embedding of the documentation and signature alone, when
+    /// documentation embeddings are enabled and the chunk has documentation.
+    pub doc_vector: Option<Vec<f32>>,
+}
+
+/// Embeddings filled in for a chunk stored without them.
+#[derive(Debug, Clone)]
+pub struct ChunkEmbedding {
+    pub id: String,
+    pub vector: Vec<f32>,
+    pub embedding_model: String,
+    pub embedding_hash: String,
+    pub doc_hash: Option<String>,
+    pub doc_vector: Option<Vec<f32>>,
+}
+
+/// Deterministic chunk id for a chunk's location and content.
+///
+/// Re-indexing a version yields the same ids, so stored references stay valid
+/// and inserts replace rows instead of duplicating them.
+pub fn chunk_id(namespace: &str, file_path: &str, name: &str, content_hash: &str) -> String {
+    let key = [namespace, file_path, name, content_hash].join("\0");
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, key.as_bytes()).to_string()
 }
 
 /// Existing chunk info for deduplication.
@@ -198,11 +443,40 @@ pub struct ExistingChunk {
     pub vector: Vec<f32>,
 }
 
+/// A stored trait implementation (`impl Trait for Type`) with package info.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ImplWithPackage {
+    pub trait_name: String,
+    pub type_name: String,
+    /// The impl header as written
+    pub signature: String,
+    pub file_path: String,
+    pub line: i64,
+    pub registry: String,
+    pub package_name: String,
+    pub version: String,
+}
+
+/// A method of a type, as part of the type's method set.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TypeMethod {
+    pub name: String,
+    pub signature: Option<String>,
+    pub file_path: String,
+    pub start_line: i64,
+    pub registry: String,
+    pub package_name: String,
+    pub version: String,
+}
+
 // ============================================================================
 // Vector Models
 // ============================================================================
 
 /// Vector dimension for embeddings (text-embedding-3-small).
+///
+/// The default for `embedding_dimensions`, and the dimension of indexes built
+/// before it was recorded.
 pub const VECTOR_DIM: i32 = 1536;
 
 /// A record to insert into the vector store.
@@ -211,23 +485,248 @@ pub struct VectorRecord {
     pub chunk_id: String,
     pub content_hash: String,
     pub vector: Vec<f32>,
+    /// Filterable chunk attributes, for backends that store them server-side.
+    pub attributes: Option<VectorAttributes>,
+}
+
+/// Filterable chunk attributes stored alongside a vector.
+///
+/// Mirrors the filterable fields of `ChunkMetadata`. The LanceDB backend
+/// ignores these (SQLite holds them); Turbopuffer stores them per row.
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorAttributes {
+    pub registry: String,
+    pub package: String,
+    pub version: String,
+    pub major_version: Option<u32>,
+    pub minor_version: Option<u32>,
+    pub patch_version: Option<u32>,
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub chunk_type: String,
+    pub visibility: String,
+    pub name: String,
+    pub signature: Option<String>,
+    pub docstring: Option<String>,
+    pub snippet: String,
+    pub storage_key: String,
+}
+
+impl VectorAttributes {
+    /// Build attributes for a chunk about to be inserted.
+    pub fn for_chunk(registry: &str, package: &str, version: &str, chunk: &CreateChunk) -> Self {
+        Self {
+            file_path: chunk.file_path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            chunk_type: chunk.chunk_type.clone(),
+            visibility: chunk.visibility.clone(),
+            name: chunk.name.clone(),
+            signature: chunk.signature.clone(),
+            docstring: chunk.docstring.clone(),
+            snippet: chunk.snippet.clone(),
+            storage_key: chunk.storage_key.clone(),
+            ..Self::for_version(registry, package, version)
+        }
+    }
+
+    /// Build attributes for a stored chunk, as when it was inserted.
+    pub fn for_row(registry: &str, package: &str, version: &str, row: &ChunkRow) -> Self {
+        Self {
+            file_path: row.file_path.clone(),
+            start_line: row.start_line as u32,
+            end_line: row.end_line as u32,
+            chunk_type: row.chunk_type.clone(),
+            visibility: row.visibility.clone(),
+            name: row.name.clone(),
+            signature: row.signature.clone(),
+            docstring: row.docstring.clone(),
+            snippet: row.snippet.clone(),
+            storage_key: row.storage_key.clone(),
+            ..Self::for_version(registry, package, version)
+        }
+    }
+
+    /// Attributes of the package version, with the chunk fields left empty.
+    fn for_version(registry: &str, package: &str, version: &str) -> Self {
+        let mut parts = version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map(|p| p.parse::<u32>().ok());
+
+        Self {
+            registry: registry.to_string(),
+            package: package.to_string(),
+            version: version.to_string(),
+            major_version: parts.next().flatten(),
+            minor_version: parts.next().flatten(),
+            patch_version: parts.next().flatten(),
+            file_path: String::new(),
+            start_line: 0,
+            end_line: 0,
+            chunk_type: String::new(),
+            visibility: String::new(),
+            name: String::new(),
+            signature: None,
+            docstring: None,
+            snippet: String::new(),
+            storage_key: String::new(),
+        }
+    }
+}
+
+/// How vector stores measure the distance between embeddings.
+///
+/// Distances follow LanceDB's definitions, which Turbopuffer shares for the
+/// metrics it supports; lower is always more similar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceMetric {
+    /// Squared Euclidean distance
+    #[default]
+    L2,
+    /// One minus the cosine similarity
+    Cosine,
+    /// One minus the dot product (cosine for normalized embeddings)
+    Dot,
+}
+
+impl DistanceMetric {
+    /// Distance between two vectors under this metric.
+    pub fn distance(self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::L2 => squared_l2(a, b),
+            Self::Cosine => 1.0 - cosine_similarity(a, b),
+            Self::Dot => 1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>(),
+        }
+    }
+
+    /// Similarity score (0-1, higher = more similar) for a distance.
+    ///
+    /// L2 distances are unbounded, so they map to `1 / (1 + d)`; cosine and
+    /// dot distances are one minus a similarity, which is the score itself
+    /// with opposite directions clamped to 0.
+    pub fn score(self, distance: f32) -> f32 {
+        match self {
+            Self::L2 => 1.0 / (1.0 + distance),
+            Self::Cosine | Self::Dot => (1.0 - distance).clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl std::fmt::Display for DistanceMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::L2 => write!(f, "l2"),
+            Self::Cosine => write!(f, "cosine"),
+            Self::Dot => write!(f, "dot"),
+        }
+    }
+}
+
+/// Which stored embedding of a chunk a query is compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryMode {
+    /// The embedding of the whole chunk: documentation, signature and code
+    #[default]
+    Code,
+    /// The documentation embedding, which suits questions in plain language;
+    /// chunks without one fall back to their code embedding
+    Docs,
+    /// Whichever of the two is closer to the query
+    Fused,
+}
+
+impl QueryMode {
+    /// Distance from `query` to a chunk with embedding `code` and, if it has
+    /// one, documentation embedding `doc`.
+    pub fn distance(
+        self,
+        metric: DistanceMetric,
+        query: &[f32],
+        code: &[f32],
+        doc: Option<&[f32]>,
+    ) -> f32 {
+        match doc.filter(|_| self.matches_doc(metric, query, code, doc)) {
+            Some(doc) => metric.distance(query, doc),
+            None => metric.distance(query, code),
+        }
+    }
+
+    /// Whether the distance is measured to the documentation embedding
+    /// rather than the code embedding.
+    pub fn matches_doc(
+        self,
+        metric: DistanceMetric,
+        query: &[f32],
+        code: &[f32],
+        doc: Option<&[f32]>,
+    ) -> bool {
+        match (self, doc) {
+            (QueryMode::Code, _) | (_, None) => false,
+            (QueryMode::Docs, Some(_)) => true,
+            (QueryMode::Fused, Some(doc)) => {
+                metric.distance(query, doc) < metric.distance(query, code)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for QueryMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Code => write!(f, "code"),
+            Self::Docs => write!(f, "docs"),
+            Self::Fused => write!(f, "fused"),
+        }
+    }
+}
+
+impl std::str::FromStr for QueryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "code" => Ok(QueryMode::Code),
+            "docs" => Ok(QueryMode::Docs),
+            "fused" => Ok(QueryMode::Fused),
+            _ => Err(format!("unknown query mode: {}", s)),
+        }
+    }
 }
 
 /// A search hit from vector similarity search.
 #[derive(Debug, Clone)]
 pub struct VectorSearchHit {
     pub chunk_id: String,
-    /// L2 distance (lower = more similar)
+    /// Distance under `metric` (lower = more similar)
     pub distance: f32,
+    pub metric: DistanceMetric,
 }
 
 impl VectorSearchHit {
     /// Convert distance to similarity score (0-1, higher = more similar).
     pub fn score(&self) -> f32 {
-        1.0 / (1.0 + self.distance)
+        self.metric.score(self.distance)
     }
 }
 
+/// Squared L2 distance, matching what the vector backends report for
+/// [`DistanceMetric::L2`].
+pub fn squared_l2(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Cosine similarity (-1 to 1), or 0 if either vector is all zeros.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
 // ============================================================================
 // Stats Models
 // ============================================================================
@@ -240,6 +739,7 @@ pub struct IndexStats {
     pub indexed_count: u32,
     pub failed_count: u32,
     pub skipped_count: u32,
+    pub empty_count: u32,
     pub chunk_count: u32,
 }
 
@@ -256,6 +756,9 @@ pub struct SearchResult {
     pub version: String,
     pub chunk_type: String,
     pub name: String,
+    /// Enclosing class, trait or impl type, for methods
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
     pub file_path: String,
     pub start_line: u32,
     pub end_line: u32,
@@ -263,8 +766,912 @@ pub struct SearchResult {
     pub signature: Option<String>,
     pub docstring: Option<String>,
     pub snippet: String,
+    /// Fence tag of the file's language (e.g. `rust`), for rendering
+    /// `snippet` or the full code as a highlighted block
+    pub language: Option<String>,
     pub storage_key: String,
+    pub deprecated: bool,
+    pub deprecation_message: Option<String>,
+    /// Distance between the query and chunk embeddings under the configured
+    /// metric (lower = more similar), before any adjustments
+    pub distance: f32,
+    /// Relevance (0-1, higher = better): the distance as a score, adjusted
+    /// for keyword matches, deprecation and preferred packages
     pub score: f32,
+    /// How the score came about, for searches run with explanations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ScoreExplanation>,
+}
+
+/// Breakdown of a search result's score.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreExplanation {
+    /// Metric the distance was measured with
+    pub metric: DistanceMetric,
+    /// Which embeddings the query was compared with
+    pub mode: QueryMode,
+    /// Whether the ranking was reused from an identical recent search
+    pub cache_hit: bool,
+    /// Whether the distance is to the documentation embedding
+    pub matched_docs: bool,
+    /// Distance between the query and chunk embeddings
+    pub distance: f32,
+    /// Score from the distance alone, before adjustments
+    pub vector_score: f32,
+    /// Cosine similarity of the query and chunk embeddings
+    pub cosine: f32,
+    /// Query words found in the chunk's name or signature
+    pub keyword_matches: Vec<String>,
+    /// Change to the score from blending in keyword matches
+    pub keyword_adjustment: f32,
+    /// Multiplier applied because the symbol is deprecated
+    pub deprecation_weight: Option<f32>,
+    /// Change to the score because the package is preferred
+    pub preference_adjustment: Option<f32>,
+    /// First lines of the text that was embedded for the chunk
+    pub embedded_text: String,
+}
+
+impl SearchResult {
+    /// Name qualified by its parent, e.g. `Client.get`.
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// "Deprecated", with the reason when one was given, for deprecated symbols.
+    pub fn deprecation_note(&self) -> Option<String> {
+        if !self.deprecated {
+            return None;
+        }
+        Some(match &self.deprecation_message {
+            Some(message) => format!("Deprecated: {}", message),
+            None => "Deprecated".to_string(),
+        })
+    }
+}
+
+/// A search result, or several hits sharing a parent collapsed together.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GroupedResult {
+    Single(Box<SearchResult>),
+    Group(ParentGroup),
+}
+
+impl GroupedResult {
+    /// Score of the result, or of a group's best hit.
+    pub fn score(&self) -> f32 {
+        match self {
+            GroupedResult::Single(result) => result.score,
+            GroupedResult::Group(group) => group.score,
+        }
+    }
+}
+
+/// Hits from one class, trait or impl type in the same file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParentGroup {
+    pub parent: String,
+    pub registry: String,
+    pub package: String,
+    pub version: String,
+    pub file_path: String,
+    /// Score of the best hit in the group
+    pub score: f32,
+    /// The hits, best first
+    pub results: Vec<SearchResult>,
+}
+
+/// Collapse hits sharing a parent in the same file into one group, placed
+/// where its best hit ranked. Hits without a parent, or alone with theirs,
+/// stay as they are.
+pub fn group_by_parent(results: Vec<SearchResult>) -> Vec<GroupedResult> {
+    let key = |r: &SearchResult| {
+        r.parent.clone().map(|parent| {
+            (
+                parent,
+                r.registry.clone(),
+                r.package.clone(),
+                r.version.clone(),
+                r.file_path.clone(),
+            )
+        })
+    };
+    let mut counts: HashMap<_, usize> = HashMap::new();
+    for key in results.iter().filter_map(key) {
+        *counts.entry(key).or_default() += 1;
+    }
+
+    let mut grouped: Vec<GroupedResult> = Vec::with_capacity(results.len());
+    let mut positions = HashMap::new();
+    for result in results {
+        let Some(key) = key(&result).filter(|key| counts[key] > 1) else {
+            grouped.push(GroupedResult::Single(Box::new(result)));
+            continue;
+        };
+        match positions.get(&key) {
+            Some(&i) => {
+                if let GroupedResult::Group(group) = &mut grouped[i] {
+                    group.score = group.score.max(result.score);
+                    group.results.push(result);
+                }
+            }
+            None => {
+                positions.insert(key.clone(), grouped.len());
+                let (parent, registry, package, version, file_path) = key;
+                grouped.push(GroupedResult::Group(ParentGroup {
+                    parent,
+                    registry,
+                    package,
+                    version,
+                    file_path,
+                    score: result.score,
+                    results: vec![result],
+                }));
+            }
+        }
+    }
+    grouped
+}
+
+/// Restricts search to chunks whose file path matches.
+///
+/// Patterns containing `*`, `?` or `[` are globs, where `*` also matches
+/// `/` (`src/*.rs` covers nested files); anything else is a path prefix
+/// such as `src/parser/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathFilter {
+    Prefix(String),
+    Glob(String),
+}
+
+impl PathFilter {
+    /// Pick prefix or glob matching based on the pattern.
+    pub fn parse(pattern: &str) -> Self {
+        if pattern.contains(['*', '?', '[']) {
+            PathFilter::Glob(pattern.to_string())
+        } else {
+            PathFilter::Prefix(pattern.to_string())
+        }
+    }
+
+    /// SQL condition on `file_path`, taking [`Self::pattern`] as its only bind.
+    pub fn sql(&self) -> &'static str {
+        match self {
+            PathFilter::Prefix(_) => "instr(file_path, ?) = 1",
+            PathFilter::Glob(_) => "file_path GLOB ?",
+        }
+    }
+
+    /// The prefix or glob pattern.
+    pub fn pattern(&self) -> &str {
+        match self {
+            PathFilter::Prefix(p) | PathFilter::Glob(p) => p,
+        }
+    }
+}
+
+/// Whether search results include chunks indexed from test files.
+///
+/// Test files are only indexed with `index_tests = true`, and their chunks
+/// are stored with the `example` chunk type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFilter {
+    /// Rank test chunks alongside everything else
+    #[default]
+    Include,
+    /// Leave test chunks out
+    Exclude,
+    /// Only return test chunks
+    Only,
+}
+
+impl TestFilter {
+    /// SQL condition on `chunk_type`, if this filter narrows anything.
+    pub fn sql(self) -> Option<&'static str> {
+        match self {
+            TestFilter::Include => None,
+            TestFilter::Exclude => Some("chunk_type != 'example'"),
+            TestFilter::Only => Some("chunk_type = 'example'"),
+        }
+    }
+}
+
+impl std::str::FromStr for TestFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "include" => Ok(TestFilter::Include),
+            "exclude" => Ok(TestFilter::Exclude),
+            "only" => Ok(TestFilter::Only),
+            _ => Err(format!("unknown test filter: {}", s)),
+        }
+    }
+}
+
+/// Whether search results include documentation chunks (READMEs, docs/).
+///
+/// Examples are the test chunks [`TestFilter`] selects, so together the two
+/// pick documentation, examples or the API itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KindFilter {
+    /// Documentation alongside everything else
+    #[default]
+    All,
+    /// Only documentation prose and code blocks
+    Docs,
+    /// Leave documentation out
+    Code,
+}
+
+impl KindFilter {
+    /// SQL condition on `chunk_type`, if this filter narrows anything.
+    pub fn sql(self) -> Option<&'static str> {
+        match self {
+            KindFilter::All => None,
+            KindFilter::Docs => Some("chunk_type = 'documentation'"),
+            KindFilter::Code => Some("chunk_type != 'documentation'"),
+        }
+    }
+}
+
+/// Conditions a chunk must meet to be considered by a search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkFilter {
+    pub path: Option<PathFilter>,
+    pub tests: TestFilter,
+    /// Only keep chunks with `public` visibility.
+    pub public_only: bool,
+    pub kind: KindFilter,
+    /// Only keep public or private (tenant) packages; None keeps both.
+    pub scope: Option<SearchScope>,
+    /// Only keep functions with a parameter whose type contains this.
+    pub param_type: Option<String>,
+    /// Only keep functions whose return type contains this.
+    pub return_type: Option<String>,
+}
+
+impl ChunkFilter {
+    /// Build a filter from a path pattern (see [`PathFilter::parse`]).
+    pub fn new(path: Option<&str>, tests: TestFilter) -> Self {
+        Self {
+            path: path.map(PathFilter::parse),
+            tests,
+            public_only: false,
+            kind: KindFilter::All,
+            scope: None,
+            param_type: None,
+            return_type: None,
+        }
+    }
+
+    /// Only keep public API: exported or `pub` symbols, leaving out
+    /// internal, protected and private ones.
+    pub fn with_public_only(mut self, public_only: bool) -> Self {
+        self.public_only = public_only;
+        self
+    }
+
+    /// Only keep chunks of the kinds `kind` selects.
+    pub fn with_kind(mut self, kind: KindFilter) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Only keep packages in `scope`: public ones, tenants' private ones,
+    /// or all.
+    pub fn with_scope(mut self, scope: Option<SearchScope>) -> Self {
+        self.scope = scope.filter(|s| *s != SearchScope::All);
+        self
+    }
+
+    /// Only keep functions taking a parameter whose declared type contains
+    /// `param_type` and returning a type containing `return_type`, e.g.
+    /// `Request` and `Response`. Matching is case-sensitive.
+    pub fn with_signature_types(
+        mut self,
+        param_type: Option<&str>,
+        return_type: Option<&str>,
+    ) -> Self {
+        self.param_type = param_type.map(str::to_string);
+        self.return_type = return_type.map(str::to_string);
+        self
+    }
+
+    /// True if every chunk passes.
+    pub fn is_empty(&self) -> bool {
+        self.path.is_none()
+            && self.tests.sql().is_none()
+            && !self.public_only
+            && self.kind.sql().is_none()
+            && self.scope.is_none()
+            && self.param_type.is_none()
+            && self.return_type.is_none()
+    }
+
+    /// True if the filter only keeps public chunks, which search applies to
+    /// the vector store's hits rather than scanning the index.
+    pub fn is_public_only(&self) -> bool {
+        self.public_only
+            && Self {
+                public_only: false,
+                ..self.clone()
+            }
+            .is_empty()
+    }
+
+    /// SQL conditions, each prefixed with `AND`, taking [`Self::binds`].
+    pub fn sql(&self) -> String {
+        let mut sql = String::new();
+        if let Some(path) = &self.path {
+            sql.push_str(" AND ");
+            sql.push_str(path.sql());
+        }
+        if let Some(tests) = self.tests.sql() {
+            sql.push_str(" AND ");
+            sql.push_str(tests);
+        }
+        if self.public_only {
+            sql.push_str(" AND visibility = 'public'");
+        }
+        if let Some(kind) = self.kind.sql() {
+            sql.push_str(" AND ");
+            sql.push_str(kind);
+        }
+        match self.scope {
+            Some(SearchScope::Public) => sql.push_str(" AND namespace NOT LIKE 'private/%'"),
+            Some(SearchScope::Private) => sql.push_str(" AND namespace LIKE 'private/%'"),
+            Some(SearchScope::All) | None => {}
+        }
+        if self.param_type.is_some() {
+            // `params` holds `[name, type]` pairs
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM json_each(params) \
+                 WHERE instr(json_extract(value, '$[1]'), ?) > 0)",
+            );
+        }
+        if self.return_type.is_some() {
+            sql.push_str(" AND instr(returns, ?) > 0");
+        }
+        sql
+    }
+
+    /// Values bound to [`Self::sql`], in order.
+    pub fn binds(&self) -> Vec<&str> {
+        self.path
+            .iter()
+            .map(|p| p.pattern())
+            .chain(self.param_type.as_deref())
+            .chain(self.return_type.as_deref())
+            .collect()
+    }
+}
+
+/// Results a search returns unless asked for another number.
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// What a search looks through and how it ranks and trims its results
+/// (see [`super::LocalSearch::search`]). `None` fields fall back to the
+/// configured default.
+#[derive(Debug, Clone)]
+pub struct SearchOptions<'a> {
+    /// Package name to search, all packages if `None`.
+    pub package: Option<&'a str>,
+    pub registry: Option<&'a str>,
+    /// Exact, `latest` or partial version of `package`.
+    pub version: Option<&'a str>,
+    pub filter: ChunkFilter,
+    pub preferred: PreferredPackages,
+    /// Embeddings queries are compared with.
+    pub mode: Option<QueryMode>,
+    /// Hits scoring below this are dropped.
+    pub min_score: Option<f32>,
+    /// Bytes snippets are cut to.
+    pub snippet_length: Option<usize>,
+    pub limit: usize,
+}
+
+impl Default for SearchOptions<'_> {
+    fn default() -> Self {
+        Self {
+            package: None,
+            registry: None,
+            version: None,
+            filter: ChunkFilter::default(),
+            preferred: PreferredPackages::default(),
+            mode: None,
+            min_score: None,
+            snippet_length: None,
+            limit: DEFAULT_SEARCH_LIMIT,
+        }
+    }
+}
+
+/// Packages whose search hits get a score boost, such as the current
+/// project's direct dependencies. Matched by registry and name, so every
+/// indexed version of a package is preferred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreferredPackages(HashSet<(String, String)>);
+
+impl PreferredPackages {
+    /// Prefer the given (registry, name) pairs.
+    pub fn new(packages: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self(packages.into_iter().collect())
+    }
+
+    /// Parse `registry:name` specs, e.g. `npm:axios` or
+    /// `maven:com.google.guava:guava`.
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<Self, String> {
+        specs
+            .iter()
+            .map(|spec| {
+                let spec = spec.as_ref();
+                match spec.split_once(':') {
+                    Some((registry, name)) if !registry.is_empty() && !name.is_empty() => {
+                        Ok((registry.to_string(), name.to_string()))
+                    }
+                    _ => Err(format!("expected registry:name, got '{}'", spec)),
+                }
+            })
+            .collect::<Result<HashSet<_>, _>>()
+            .map(Self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, registry: &str, name: &str) -> bool {
+        self.0.contains(&(registry.to_string(), name.to_string()))
+    }
+
+    /// Whether a `registry/name/version` namespace is a preferred package's.
+    pub fn contains_namespace(&self, namespace: &str) -> bool {
+        namespace
+            .parse::<NamespaceSpec>()
+            .is_ok_and(|ns| self.contains(&ns.registry, &ns.name))
+    }
+}
+
+/// Which namespaces a "more like this" search covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarScope {
+    /// Only the chunk's own package version.
+    #[default]
+    Namespace,
+    /// Every indexed package version.
+    All,
+}
+
+/// A symbol listed in a package outline.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutlineSymbol {
+    pub name: String,
+    pub chunk_type: String,
+    pub visibility: String,
+    pub signature: Option<String>,
+    #[serde(skip)]
+    pub file_path: String,
+    pub start_line: i64,
+    pub end_line: i64,
+}
+
+/// Symbols of one file in a package outline, in line order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineFile {
+    pub path: String,
+    /// Part of the package's entry module
+    pub entry: bool,
+    pub symbols: Vec<OutlineSymbol>,
+}
+
+/// Structure of an indexed package version: its symbols grouped by file.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageOutline {
+    pub registry: String,
+    pub package: String,
+    pub version: String,
+    /// Path of the package's entry module, if one was detected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<String>,
+    pub files: Vec<OutlineFile>,
+}
+
+impl PackageOutline {
+    /// Group `symbols`, sorted by file, into per-file entries.
+    pub fn new(registry: &str, package: &str, version: &str, symbols: Vec<OutlineSymbol>) -> Self {
+        let mut files: Vec<OutlineFile> = Vec::new();
+        for symbol in symbols {
+            match files.last_mut() {
+                Some(file) if file.path == symbol.file_path => file.symbols.push(symbol),
+                _ => files.push(OutlineFile {
+                    path: symbol.file_path.clone(),
+                    entry: false,
+                    symbols: vec![symbol],
+                }),
+            }
+        }
+
+        Self {
+            registry: registry.to_string(),
+            package: package.to_string(),
+            version: version.to_string(),
+            entry_point: None,
+            files,
+        }
+    }
+
+    /// Mark the files of the entry module at `entry_point` and list them
+    /// first, their public symbols ahead of the rest, as where to start.
+    ///
+    /// A Go package spans its directory, so for Go every file beside the
+    /// entry point belongs to the entry module.
+    pub fn with_entry_point(mut self, entry_point: Option<String>) -> Self {
+        if let Some(entry) = &entry_point {
+            let dir = |path: &str| path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+            let whole_dir = self.registry == Registry::Go.as_str();
+            for file in &mut self.files {
+                file.entry = file.path == *entry
+                    || (whole_dir
+                        && dir(&file.path) == dir(entry)
+                        && file.path.ends_with(".go")
+                        && !file.path.ends_with("_test.go"));
+                if file.entry {
+                    file.symbols
+                        .sort_by_key(|symbol| symbol.visibility != Visibility::Public.as_str());
+                }
+            }
+            self.files.sort_by_key(|file| !file.entry);
+        }
+        self.entry_point = entry_point;
+        self
+    }
+
+    /// Number of symbols across all files.
+    pub fn symbol_count(&self) -> usize {
+        self.files.iter().map(|f| f.symbols.len()).sum()
+    }
+}
+
+/// A symbol compared between two versions by [`ApiDiff`].
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApiSymbol {
+    pub name: String,
+    pub parent: Option<String>,
+    pub chunk_type: String,
+    pub signature: Option<String>,
+    #[serde(skip)]
+    pub docstring: Option<String>,
+    pub file_path: String,
+    pub start_line: i64,
+}
+
+impl ApiSymbol {
+    /// Name qualified by its parent, e.g. `Client.get`.
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Symbols are matched across versions by name, parent and type.
+    fn key(&self) -> (&str, Option<&str>, &str) {
+        (&self.name, self.parent.as_deref(), &self.chunk_type)
+    }
+}
+
+/// A symbol present in both versions whose signature or docs changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedSymbol {
+    pub old: ApiSymbol,
+    pub new: ApiSymbol,
+    pub signature_changed: bool,
+    pub docs_changed: bool,
+}
+
+/// Symbols added, removed or changed between two versions of a package.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiDiff {
+    pub registry: String,
+    pub package: String,
+    pub from: String,
+    pub to: String,
+    pub added: Vec<ApiSymbol>,
+    pub removed: Vec<ApiSymbol>,
+    pub changed: Vec<ChangedSymbol>,
+}
+
+impl ApiDiff {
+    /// Compare the symbols of version `from` (`old`) with those of `to` (`new`).
+    ///
+    /// Overloads share a name, so symbols with equal signatures are paired
+    /// first and the rest in order; a symbol left without a partner was added
+    /// or removed. Doc changes are only reported with `docs`.
+    pub fn new(
+        registry: &str,
+        package: &str,
+        from: &str,
+        to: &str,
+        old: Vec<ApiSymbol>,
+        new: Vec<ApiSymbol>,
+        docs: bool,
+    ) -> Self {
+        let mut unmatched: HashMap<_, Vec<usize>> = HashMap::new();
+        for (i, symbol) in old.iter().enumerate() {
+            unmatched.entry(symbol.key()).or_default().push(i);
+        }
+
+        // Pair identical signatures first so reordered overloads don't show as changes
+        let mut pairs: Vec<Option<usize>> = new
+            .iter()
+            .map(|symbol| {
+                let candidates = unmatched.get_mut(&symbol.key())?;
+                let pos = candidates.iter().position(|&i| {
+                    normalize(old[i].signature.as_deref()) == normalize(symbol.signature.as_deref())
+                })?;
+                Some(candidates.remove(pos))
+            })
+            .collect();
+        for (pair, symbol) in pairs.iter_mut().zip(&new) {
+            if pair.is_none() {
+                *pair = unmatched
+                    .get_mut(&symbol.key())
+                    .filter(|candidates| !candidates.is_empty())
+                    .map(|candidates| candidates.remove(0));
+            }
+        }
+
+        let mut removed: Vec<usize> = unmatched.into_values().flatten().collect();
+        removed.sort_unstable();
+        let removed = removed.into_iter().map(|i| old[i].clone()).collect();
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for (symbol, pair) in new.into_iter().zip(pairs) {
+            let Some(i) = pair else {
+                added.push(symbol);
+                continue;
+            };
+            let old = &old[i];
+            let signature_changed =
+                normalize(old.signature.as_deref()) != normalize(symbol.signature.as_deref());
+            let docs_changed = docs
+                && normalize(old.docstring.as_deref()) != normalize(symbol.docstring.as_deref());
+            if signature_changed || docs_changed {
+                changed.push(ChangedSymbol {
+                    old: old.clone(),
+                    new: symbol,
+                    signature_changed,
+                    docs_changed,
+                });
+            }
+        }
+
+        Self {
+            registry: registry.to_string(),
+            package: package.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    /// True if the versions have the same symbols.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Collapse whitespace, so reformatting alone doesn't count as a change.
+fn normalize(text: Option<&str>) -> String {
+    text.map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
+
+/// A chunk's code with surrounding lines from its source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkContext {
+    pub file_path: String,
+    /// Line number of the first line in `before` (or of the chunk if empty).
+    pub start_line: u32,
+    pub before: String,
+    pub code: String,
+    pub after: String,
+}
+
+/// A query run by `idx bench`, labeled with the symbols that answer it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BenchQuery {
+    pub query: String,
+    /// Symbol names (`get`) or names qualified by their parent
+    /// (`Client.get`); empty for a query only timed
+    #[serde(default)]
+    pub expected: Vec<String>,
+}
+
+/// How one benchmark query went.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchOutcome {
+    pub query: String,
+    /// Time to embed the query plus time to search with it
+    pub latency_ms: f64,
+    pub embed_ms: f64,
+    pub search_ms: f64,
+    /// Qualified names of the results, best first
+    pub results: Vec<String>,
+    pub expected: Vec<String>,
+    /// Position (from 1) of the first expected symbol among the results
+    pub rank: Option<usize>,
+    /// Expected symbols found among the results
+    pub found: usize,
+}
+
+impl BenchOutcome {
+    /// Outcome of `query`, which took `embed_ms` to embed and `search_ms`
+    /// to return `results`.
+    pub fn new(
+        query: &BenchQuery,
+        embed_ms: f64,
+        search_ms: f64,
+        results: &[SearchResult],
+    ) -> Self {
+        let names: Vec<String> = results.iter().map(SearchResult::qualified_name).collect();
+        let matches =
+            |expected: &String, i: usize| *expected == names[i] || *expected == results[i].name;
+        let rank = (0..names.len())
+            .find(|&i| query.expected.iter().any(|expected| matches(expected, i)))
+            .map(|i| i + 1);
+        let found = query
+            .expected
+            .iter()
+            .filter(|expected| (0..names.len()).any(|i| matches(expected, i)))
+            .count();
+
+        Self {
+            query: query.query.clone(),
+            latency_ms: embed_ms + search_ms,
+            embed_ms,
+            search_ms,
+            results: names,
+            expected: query.expected.clone(),
+            rank,
+            found,
+        }
+    }
+
+    pub fn is_labeled(&self) -> bool {
+        !self.expected.is_empty()
+    }
+}
+
+/// Search latency and, over labeled queries, result quality measured by
+/// `idx bench`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Results asked for per query
+    pub k: usize,
+    pub queries: usize,
+    pub latency: Latency,
+    /// Time spent embedding queries
+    pub embedding: Latency,
+    /// Time spent searching the index with the embedded queries
+    pub search: Latency,
+    /// Queries labeled with expected symbols
+    pub labeled: usize,
+    /// Mean share of each labeled query's expected symbols found in its
+    /// top `k` results
+    pub recall_at_k: Option<f64>,
+    /// Mean reciprocal rank of the first expected symbol (0 when missed)
+    pub mrr: Option<f64>,
+    pub outcomes: Vec<BenchOutcome>,
+}
+
+impl BenchReport {
+    pub fn new(k: usize, outcomes: Vec<BenchOutcome>) -> Self {
+        let labeled: Vec<&BenchOutcome> = outcomes.iter().filter(|o| o.is_labeled()).collect();
+        let mean = |values: Vec<f64>| {
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        };
+        let recall_at_k = mean(
+            labeled
+                .iter()
+                .map(|o| o.found as f64 / o.expected.len() as f64)
+                .collect(),
+        );
+        let mrr = mean(
+            labeled
+                .iter()
+                .map(|o| o.rank.map_or(0.0, |rank| 1.0 / rank as f64))
+                .collect(),
+        );
+
+        Self {
+            k,
+            queries: outcomes.len(),
+            latency: Latency::of(outcomes.iter().map(|o| o.latency_ms)),
+            embedding: Latency::of(outcomes.iter().map(|o| o.embed_ms)),
+            search: Latency::of(outcomes.iter().map(|o| o.search_ms)),
+            labeled: labeled.len(),
+            recall_at_k,
+            mrr,
+            outcomes,
+        }
+    }
+}
+
+/// Median and 95th percentile of timings, in milliseconds.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Latency {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl Latency {
+    fn of(timings: impl Iterator<Item = f64>) -> Self {
+        let mut sorted: Vec<f64> = timings.collect();
+        sorted.sort_by(f64::total_cmp);
+        Self {
+            p50_ms: percentile(&sorted, 50.0),
+            p95_ms: percentile(&sorted, 95.0),
+        }
+    }
+}
+
+/// The `p`th percentile of ascending `sorted` values by nearest rank (0
+/// when empty).
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Numeric `[major, minor, patch]` of a version; pre-release and build tags
+/// are ignored and missing parts count as 0.
+pub fn version_parts(version: &str) -> Option<[u64; 3]> {
+    let core = version.trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| {
+        let digits: String = p.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse::<u64>().ok()
+    });
+
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some([major, minor, patch])
+}
+
+/// Order versions numerically, falling back to string order.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    match (version_parts(a), version_parts(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Whether `version` is a release under the partial version `spec` (`4`,
+/// `4.17`): its leading parts equal the spec's. A full `major.minor.patch`
+/// spec is no partial one and only matches itself, checked by the caller.
+pub fn matches_partial_version(spec: &str, version: &str) -> bool {
+    let spec_parts: Option<Vec<u64>> = spec
+        .trim_start_matches('v')
+        .split('.')
+        .map(|p| p.parse().ok())
+        .collect();
+    let Some(spec_parts) = spec_parts.filter(|parts| parts.len() < 3) else {
+        return false;
+    };
+    !version.contains(['-', '+'])
+        && version_parts(version).is_some_and(|parts| parts.starts_with(&spec_parts))
 }
 
 /// Convert f32 vector to bytes for SQLite storage.
@@ -284,6 +1691,151 @@ pub fn bytes_to_vector(bytes: &[u8]) -> Vec<f32> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_path_filter_parse() {
+        assert_eq!(
+            PathFilter::parse("src/parser/"),
+            PathFilter::Prefix("src/parser/".to_string())
+        );
+        assert_eq!(
+            PathFilter::parse("src/*.rs"),
+            PathFilter::Glob("src/*.rs".to_string())
+        );
+        assert_eq!(PathFilter::parse("lib/?.go").sql(), "file_path GLOB ?");
+    }
+
+    #[test]
+    fn test_chunk_filter_sql() {
+        let filter = ChunkFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(filter.sql(), "");
+        assert!(filter.binds().is_empty());
+
+        let filter = ChunkFilter::new(Some("src/"), TestFilter::Exclude);
+        assert!(!filter.is_empty());
+        assert_eq!(
+            filter.sql(),
+            " AND instr(file_path, ?) = 1 AND chunk_type != 'example'"
+        );
+        assert_eq!(filter.binds(), vec!["src/"]);
+
+        assert!(!ChunkFilter::new(None, TestFilter::Only).is_empty());
+        let public = ChunkFilter::default().with_public_only(true);
+        assert!(!public.is_empty());
+        assert_eq!(public.sql(), " AND visibility = 'public'");
+        assert!(public.binds().is_empty());
+        assert_eq!("ONLY".parse::<TestFilter>(), Ok(TestFilter::Only));
+        assert!("maybe".parse::<TestFilter>().is_err());
+
+        let docs = ChunkFilter::default().with_kind(KindFilter::Docs);
+        assert!(!docs.is_empty());
+        assert_eq!(docs.sql(), " AND chunk_type = 'documentation'");
+        let code = ChunkFilter::new(None, TestFilter::Exclude).with_kind(KindFilter::Code);
+        assert_eq!(
+            code.sql(),
+            " AND chunk_type != 'example' AND chunk_type != 'documentation'"
+        );
+
+        let private = ChunkFilter::default().with_scope(Some(SearchScope::Private));
+        assert!(!private.is_empty());
+        assert_eq!(private.sql(), " AND namespace LIKE 'private/%'");
+        assert!(
+            ChunkFilter::default()
+                .with_scope(Some(SearchScope::All))
+                .is_empty()
+        );
+
+        let typed = ChunkFilter::new(Some("src/"), TestFilter::Include)
+            .with_signature_types(Some("Request"), Some("Response"));
+        assert!(!typed.is_empty());
+        assert!(typed.sql().contains("json_each(params)"));
+        assert!(typed.sql().ends_with(" AND instr(returns, ?) > 0"));
+        assert_eq!(typed.binds(), vec!["src/", "Request", "Response"]);
+    }
+
+    #[test]
+    fn test_failure_kind_round_trip() {
+        for kind in [
+            FailureKind::Download,
+            FailureKind::NoSource,
+            FailureKind::Parse,
+            FailureKind::Embedding,
+            FailureKind::Storage,
+        ] {
+            assert_eq!(kind.to_string().parse::<FailureKind>(), Ok(kind));
+        }
+        assert!(FailureKind::Embedding.is_transient());
+        assert!(!FailureKind::NoSource.is_transient());
+        assert!(!FailureKind::Parse.is_transient());
+    }
+
+    #[test]
+    fn test_namespace_spec_round_trip() {
+        for (registry, name, version) in [
+            ("npm", "react", "18.2.0"),
+            ("npm", "@types/node", "20.1.0"),
+            ("maven", "com.google.guava:guava", "33.0.0-jre"),
+            ("go", "github.com/gorilla/mux", "v1.8.1"),
+        ] {
+            let spec = NamespaceSpec::new(registry, name, version);
+            let namespace = spec.to_string();
+            assert_eq!(namespace, format!("{}/{}/{}", registry, name, version));
+            assert_eq!(namespace.parse::<NamespaceSpec>(), Ok(spec));
+        }
+
+        assert!("npm/react".parse::<NamespaceSpec>().is_err());
+        assert!("npm//1.0.0".parse::<NamespaceSpec>().is_err());
+        assert!("react".parse::<NamespaceSpec>().is_err());
+        assert_eq!(
+            NamespaceSpec::new("npm", "@scope/pkg", "1.0.0").package_spec(),
+            "npm:@scope/pkg@1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_private_namespace_spec() {
+        let tenant = TenantId::new_v4();
+        let spec = NamespaceSpec::new("npm", "@acme/utils", "1.0.0").with_tenant(Some(tenant));
+        let namespace = spec.to_string();
+        assert_eq!(
+            namespace,
+            format!(
+                "{}/1.0.0",
+                crate::types::Registry::Npm
+                    .private_package_namespace(&tenant.to_string(), "@acme/utils")
+            )
+        );
+        assert_eq!(namespace.parse::<NamespaceSpec>(), Ok(spec));
+        assert_eq!(
+            "npm/@acme/utils/1.0.0"
+                .parse::<NamespaceSpec>()
+                .unwrap()
+                .tenant,
+            None
+        );
+
+        assert!(
+            "private/acme/npm/utils/1.0.0"
+                .parse::<NamespaceSpec>()
+                .is_err()
+        );
+        assert!(
+            format!("private/{}", tenant)
+                .parse::<NamespaceSpec>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_preferred_packages_match_namespaces() {
+        let preferred =
+            PreferredPackages::parse(&["npm:@scope/pkg", "maven:com.google.guava:guava"]).unwrap();
+        assert!(preferred.contains_namespace("npm/@scope/pkg/1.0.0"));
+        assert!(preferred.contains_namespace("maven/com.google.guava:guava/33.0.0-jre"));
+        assert!(!preferred.contains_namespace("npm/pkg/1.0.0"));
+        assert!(!preferred.contains_namespace("npm/@scope/pkg"));
+    }
+
     #[test]
     fn test_vector_round_trip() {
         let original = vec![0.1_f32, 0.2, 0.3, -0.5, 1.0];
@@ -296,13 +1848,339 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vector_attributes_version_parts() {
+        let chunk = CreateChunk {
+            id: "c1".to_string(),
+            version_id: "v1".to_string(),
+            namespace: "npm/react/18.2.0-rc.1".to_string(),
+            chunk_type: "function".to_string(),
+            name: "useState".to_string(),
+            file_path: "index.js".to_string(),
+            start_line: 1,
+            end_line: 3,
+            visibility: "public".to_string(),
+            signature: None,
+            params: Vec::new(),
+            returns: None,
+            docstring: None,
+            snippet: String::new(),
+            storage_key: "key".to_string(),
+            content_hash: "hash".to_string(),
+            vector: vec![],
+            embedding_model: "test-model".to_string(),
+            file_storage_key: None,
+            start_byte: 0,
+            end_byte: 0,
+            deprecated: false,
+            deprecation_message: None,
+            parent: None,
+            embedding_hash: None,
+            doc_hash: None,
+            doc_vector: None,
+        };
+
+        let attrs = VectorAttributes::for_chunk("npm", "react", "18.2.0-rc.1", &chunk);
+        assert_eq!(attrs.major_version, Some(18));
+        assert_eq!(attrs.minor_version, Some(2));
+        assert_eq!(attrs.patch_version, Some(0));
+
+        let attrs = VectorAttributes::for_chunk("go", "x", "v1.2", &chunk);
+        assert_eq!(attrs.major_version, Some(1));
+        assert_eq!(attrs.minor_version, Some(2));
+        assert_eq!(attrs.patch_version, None);
+    }
+
+    #[test]
+    fn test_matches_partial_version() {
+        assert!(matches_partial_version("4", "4.17.21"));
+        assert!(matches_partial_version("4.17", "4.17.21"));
+        assert!(matches_partial_version("v1", "v1.2.0"));
+        assert!(matches_partial_version("0", "v0.9.1"));
+
+        assert!(!matches_partial_version("4.1", "4.17.21"));
+        assert!(!matches_partial_version("4", "5.0.0"));
+        assert!(!matches_partial_version("4", "4.0.0-rc.1"));
+        assert!(!matches_partial_version("4.17.21", "4.17.21"));
+        assert!(!matches_partial_version("latest", "4.17.21"));
+    }
+
     #[test]
     fn test_version_status() {
         assert_eq!(VersionStatus::Pending.to_string(), "pending");
+        assert_eq!("empty".parse::<VersionStatus>(), Ok(VersionStatus::Empty));
         assert_eq!(
             "indexed".parse::<VersionStatus>(),
             Ok(VersionStatus::Indexed)
         );
+        assert_eq!(
+            "metadata_indexed".parse::<VersionStatus>(),
+            Ok(VersionStatus::MetadataIndexed)
+        );
         assert!("invalid".parse::<VersionStatus>().is_err());
     }
+
+    fn symbol(name: &str, parent: Option<&str>, signature: &str) -> ApiSymbol {
+        ApiSymbol {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            chunk_type: "method".to_string(),
+            signature: Some(signature.to_string()),
+            docstring: None,
+            file_path: "Client.java".to_string(),
+            start_line: 1,
+        }
+    }
+
+    #[test]
+    fn test_api_diff_pairs_overloads() {
+        let old = vec![
+            symbol("get", Some("Client"), "get(String url)"),
+            symbol("get", Some("Client"), "get(URI uri)"),
+            symbol("close", Some("Client"), "close()"),
+            symbol("close", Some("Pool"), "close()"),
+        ];
+        let new = vec![
+            // Reordered overloads with one reformatted
+            symbol("get", Some("Client"), "get(URI  uri)"),
+            symbol("get", Some("Client"), "get(String url)"),
+            symbol("get", Some("Client"), "get(Request request)"),
+            symbol("close", Some("Client"), "close(Duration timeout)"),
+        ];
+
+        let diff = ApiDiff::new("maven", "http", "1.0", "2.0", old, new, false);
+        let added: Vec<_> = diff.added.iter().map(|s| s.signature.clone()).collect();
+        assert_eq!(added, vec![Some("get(Request request)".to_string())]);
+        let removed: Vec<_> = diff.removed.iter().map(|s| s.qualified_name()).collect();
+        assert_eq!(removed, vec!["Pool.close"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].new.qualified_name(), "Client.close");
+        assert!(diff.changed[0].signature_changed);
+        assert!(!diff.changed[0].docs_changed);
+    }
+
+    #[test]
+    fn test_api_diff_docs_only_when_asked() {
+        let documented = |doc: &str| ApiSymbol {
+            docstring: Some(doc.to_string()),
+            ..symbol("parse", None, "fn parse(s: &str)")
+        };
+        let old = vec![documented("Parse a value.")];
+        let new = vec![documented("Parse a value, rejecting trailing input.")];
+
+        let diff = ApiDiff::new("crates", "x", "1", "2", old.clone(), new.clone(), false);
+        assert!(diff.is_empty());
+
+        let diff = ApiDiff::new("crates", "x", "1", "2", old, new, true);
+        assert_eq!(diff.changed.len(), 1);
+        assert!(diff.changed[0].docs_changed);
+        assert!(!diff.changed[0].signature_changed);
+    }
+
+    fn hit(name: &str, parent: Option<&str>, file_path: &str, score: f32) -> SearchResult {
+        SearchResult {
+            id: format!("{}-{:?}-{}", name, parent, file_path),
+            registry: "pypi".to_string(),
+            package: "shop".to_string(),
+            version: "1.0.0".to_string(),
+            chunk_type: "method".to_string(),
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            file_path: file_path.to_string(),
+            start_line: 1,
+            end_line: 2,
+            visibility: "public".to_string(),
+            signature: None,
+            docstring: None,
+            snippet: String::new(),
+            language: None,
+            storage_key: String::new(),
+            deprecated: false,
+            deprecation_message: None,
+            distance: 0.0,
+            score,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn test_distance_metric_scores_follow_relevance() {
+        let query = [1.0, 0.0];
+        // Unit vectors turning away from the query
+        let targets: Vec<[f32; 2]> = [0.0f32, 0.3, 0.6, 1.0, 1.4]
+            .iter()
+            .map(|angle| [angle.cos(), angle.sin()])
+            .collect();
+
+        for metric in [
+            DistanceMetric::L2,
+            DistanceMetric::Cosine,
+            DistanceMetric::Dot,
+        ] {
+            let scores: Vec<f32> = targets
+                .iter()
+                .map(|t| metric.score(metric.distance(&query, t)))
+                .collect();
+            assert!((scores[0] - 1.0).abs() < 1e-6, "{}: {:?}", metric, scores);
+            assert!(
+                scores.windows(2).all(|w| w[0] > w[1]),
+                "{}: {:?}",
+                metric,
+                scores
+            );
+            assert!(scores.iter().all(|s| (0.0..=1.0).contains(s)));
+        }
+
+        // Opposite directions bottom out rather than going negative
+        assert_eq!(DistanceMetric::Cosine.score(2.0), 0.0);
+        assert_eq!(DistanceMetric::Dot.score(1.5), 0.0);
+    }
+
+    #[test]
+    fn test_group_by_parent() {
+        let results = vec![
+            hit("get", Some("Cart"), "cart.py", 0.9),
+            hit("get", Some("Order"), "order.py", 0.85),
+            hit("load", None, "util.py", 0.8),
+            hit("set", Some("Cart"), "cart.py", 0.75),
+            hit("get", Some("Cart"), "legacy/cart.py", 0.7),
+            hit("build", Some("Cart"), "cart.py", 0.6),
+        ];
+
+        let grouped = group_by_parent(results);
+        assert_eq!(grouped.len(), 4);
+        let GroupedResult::Group(cart) = &grouped[0] else {
+            panic!("expected a group, got {:?}", grouped[0]);
+        };
+        assert_eq!(cart.parent, "Cart");
+        assert_eq!(cart.file_path, "cart.py");
+        assert_eq!(cart.score, 0.9);
+        let names: Vec<_> = cart.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["get", "set", "build"]);
+
+        // A lone method, a free function and a same-named class elsewhere stay flat
+        for (grouped, file_path) in
+            grouped[1..]
+                .iter()
+                .zip(["order.py", "util.py", "legacy/cart.py"])
+        {
+            match grouped {
+                GroupedResult::Single(result) => assert_eq!(result.file_path, file_path),
+                GroupedResult::Group(group) => panic!("unexpected group {:?}", group),
+            }
+        }
+        let scores: Vec<_> = grouped.iter().map(GroupedResult::score).collect();
+        assert_eq!(scores, vec![0.9, 0.85, 0.8, 0.7]);
+    }
+
+    #[test]
+    fn test_outline_lists_entry_module_first() {
+        let symbol = |file: &str, name: &str, visibility: &str, line: i64| OutlineSymbol {
+            name: name.to_string(),
+            chunk_type: "function".to_string(),
+            visibility: visibility.to_string(),
+            signature: None,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 2,
+        };
+        let symbols = |files: [&str; 3]| {
+            vec![
+                symbol(files[0], "helper", "public", 1),
+                symbol(files[1], "normalize", "private", 1),
+                symbol(files[1], "parse", "public", 9),
+                symbol(files[2], "run", "public", 1),
+            ]
+        };
+        let files = |outline: &PackageOutline| -> Vec<(String, bool)> {
+            outline
+                .files
+                .iter()
+                .map(|file| (file.path.clone(), file.entry))
+                .collect()
+        };
+
+        let outline = PackageOutline::new(
+            "pypi",
+            "flask",
+            "3.0.0",
+            symbols(["flask/app.py", "flask/__init__.py", "flask/cli.py"]),
+        )
+        .with_entry_point(Some("flask/__init__.py".to_string()));
+        assert_eq!(outline.entry_point.as_deref(), Some("flask/__init__.py"));
+        assert_eq!(
+            files(&outline),
+            [
+                ("flask/__init__.py".to_string(), true),
+                ("flask/app.py".to_string(), false),
+                ("flask/cli.py".to_string(), false),
+            ]
+        );
+        let names: Vec<_> = outline.files[0].symbols.iter().map(|s| &s.name).collect();
+        assert_eq!(names, ["parse", "normalize"]);
+
+        // A Go package is its whole directory
+        let outline = PackageOutline::new(
+            "go",
+            "github.com/spf13/cobra",
+            "v1.8.0",
+            symbols(["args.go", "cobra.go", "doc/man_docs.go"]),
+        )
+        .with_entry_point(Some("cobra.go".to_string()));
+        assert_eq!(
+            files(&outline),
+            [
+                ("args.go".to_string(), true),
+                ("cobra.go".to_string(), true),
+                ("doc/man_docs.go".to_string(), false),
+            ]
+        );
+
+        let outline = PackageOutline::new("maven", "g:a", "1.0", symbols(["A", "B", "C"]))
+            .with_entry_point(None);
+        assert!(outline.files.iter().all(|file| !file.entry));
+    }
+
+    #[test]
+    fn test_bench_report_metrics() {
+        let query = |text: &str, expected: &[&str]| BenchQuery {
+            query: text.to_string(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+        };
+        let results = vec![
+            hit("add", Some("Cart"), "cart.py", 0.9),
+            hit("get", Some("Cart"), "cart.py", 0.8),
+            hit("checkout", None, "shop.py", 0.7),
+        ];
+
+        // Plain and qualified names both match
+        let second = BenchOutcome::new(&query("cart item", &["get"]), 25.0, 5.0, &results);
+        assert_eq!(second.rank, Some(2));
+        assert_eq!(second.results[0], "Cart.add");
+        let both = BenchOutcome::new(
+            &query("pay", &["checkout", "Cart.add", "refund"]),
+            4.0,
+            6.0,
+            &results,
+        );
+        assert_eq!((both.rank, both.found), (Some(1), 2));
+        let missed = BenchOutcome::new(&query("ship", &["Order.ship"]), 15.0, 5.0, &results);
+        assert_eq!((missed.rank, missed.found), (None, 0));
+        let timed = BenchOutcome::new(&query("anything", &[]), 60.0, 40.0, &results);
+        assert!(!timed.is_labeled());
+
+        let report = BenchReport::new(3, vec![second, both, missed, timed]);
+        assert_eq!((report.queries, report.labeled), (4, 3));
+        let latency = |l: Latency| (l.p50_ms, l.p95_ms);
+        assert_eq!(latency(report.latency), (20.0, 100.0));
+        assert_eq!(latency(report.embedding), (15.0, 60.0));
+        assert_eq!(latency(report.search), (5.0, 40.0));
+        let recall = report.recall_at_k.unwrap();
+        assert!((recall - (1.0 + 2.0 / 3.0) / 3.0).abs() < 1e-9);
+        assert!((report.mrr.unwrap() - 0.5).abs() < 1e-9);
+
+        let unlabeled = BenchReport::new(3, Vec::new());
+        assert_eq!((unlabeled.recall_at_k, unlabeled.mrr), (None, None));
+        assert_eq!(unlabeled.latency.p95_ms, 0.0);
+    }
 }
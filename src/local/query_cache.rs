@@ -0,0 +1,150 @@
+//! Short-lived in-memory cache of search results, keyed by the query and
+//! everything that narrows or ranks it.
+//!
+//! Long-running servers (MCP, HTTP) see the same query repeated within
+//! seconds, e.g. an agent retrying a tool call. Entries hold the query
+//! embedding and the ranked chunk ids only; chunk details are re-read from
+//! the index on every hit, so a cached ranking never serves stale code.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::models::{ChunkFilter, PreferredPackages, QueryMode, ScoreExplanation};
+
+/// How long a cached search is reused.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Default number of cached searches.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Everything a search's ranking depends on besides the index contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryKey {
+    pub query: String,
+    /// Endpoint and model the query is embedded with
+    pub embedder: String,
+    pub package: Option<String>,
+    pub registry: Option<String>,
+    pub version: Option<String>,
+    pub filter: ChunkFilter,
+    pub preferred: PreferredPackages,
+    pub mode: QueryMode,
+    pub limit: usize,
+}
+
+/// A ranked hit, with the score it was given.
+#[derive(Debug, Clone)]
+pub struct CachedHit {
+    pub chunk_id: String,
+    pub distance: f32,
+    pub score: f32,
+    pub explain: Option<ScoreExplanation>,
+}
+
+/// A cached search.
+#[derive(Debug, Clone)]
+pub struct CachedQuery {
+    pub embedding: Vec<f32>,
+    /// Index fingerprint the hits were ranked against
+    pub fingerprint: String,
+    pub hits: Vec<CachedHit>,
+}
+
+/// Search cache evicting expired entries, then the oldest once full.
+pub struct QueryCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<VecDeque<(QueryKey, Instant, CachedQuery)>>,
+}
+
+impl QueryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Cached search for `key`, unless it has expired.
+    ///
+    /// The caller compares [`CachedQuery::fingerprint`] with the index's
+    /// current one: after an index write the hits are stale but the
+    /// embedding can still be reused.
+    pub fn get(&self, key: &QueryKey) -> Option<CachedQuery> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(_, cached_at, _)| cached_at.elapsed() < self.ttl);
+        entries
+            .iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, _, query)| query.clone())
+    }
+
+    /// Cache a search, replacing any earlier entry for the same key.
+    pub fn insert(&self, key: QueryKey, query: CachedQuery) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(k, _, _)| *k != key);
+        entries.push_back((key, Instant::now(), query));
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(query: &str, limit: usize) -> QueryKey {
+        QueryKey {
+            query: query.to_string(),
+            embedder: "text-embedding-3-small".to_string(),
+            package: None,
+            registry: None,
+            version: None,
+            filter: ChunkFilter::default(),
+            preferred: PreferredPackages::default(),
+            mode: QueryMode::Code,
+            limit,
+        }
+    }
+
+    fn cached(fingerprint: &str) -> CachedQuery {
+        CachedQuery {
+            embedding: vec![1.0],
+            fingerprint: fingerprint.to_string(),
+            hits: vec![],
+        }
+    }
+
+    #[test]
+    fn test_keyed_by_query_and_limit_and_bounded() {
+        let cache = QueryCache::new(2, DEFAULT_TTL);
+        cache.insert(key("retry", 5), cached("a"));
+        cache.insert(key("timeout", 5), cached("a"));
+
+        assert!(cache.get(&key("retry", 5)).is_some());
+        assert!(cache.get(&key("retry", 10)).is_none());
+
+        // Replacing an entry keeps one copy
+        cache.insert(key("retry", 5), cached("b"));
+        assert_eq!(cache.get(&key("retry", 5)).unwrap().fingerprint, "b");
+
+        // Evicts the oldest entry
+        cache.insert(key("backoff", 5), cached("a"));
+        assert!(cache.get(&key("timeout", 5)).is_none());
+        assert!(cache.get(&key("retry", 5)).is_some());
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = QueryCache::new(4, Duration::ZERO);
+        cache.insert(key("retry", 5), cached("a"));
+        assert!(cache.get(&key("retry", 5)).is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Hard reset of an index directory (`idx clean --all`).
+//!
+//! The database, blobs and vectors are replaced together: an empty index is
+//! created in a staging directory and checked, the old stores are moved
+//! aside, and the new ones renamed into place. Renames within the index
+//! directory are atomic, and a failed swap moves the old stores back, so an
+//! interrupted reset leaves either the old index or the new one.
+//!
+//! Nothing of the old index is opened, so a database or vector store too
+//! corrupt to open can still be reset. The registry cache and the lock file
+//! are kept.
+
+use std::io;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use tracing::warn;
+
+use super::db::LocalDb;
+use super::lock::IndexLock;
+
+/// Where the empty index is prepared, inside the index directory.
+const STAGING_DIR: &str = ".reset-staging";
+
+/// Where the old stores wait until the swap succeeded.
+const TRASH_DIR: &str = ".reset-trash";
+
+/// Whether `name` is one of the stores a reset replaces: the database with
+/// its WAL and shared-memory files, blobs and LanceDB vectors.
+fn is_store(name: &str) -> bool {
+    name.starts_with("db.sqlite") || name == "blobs" || name == "vectors"
+}
+
+/// Replace the index in `index_dir` with an empty one.
+///
+/// Takes the index lock exclusively, so it fails rather than pulling the
+/// stores from under a running `idx` process. Vectors kept by a remote
+/// backend such as Turbopuffer are not touched.
+pub async fn reset_index(index_dir: &Path) -> Result<()> {
+    let _lock = IndexLock::exclusive(index_dir)?;
+
+    let staging = index_dir.join(STAGING_DIR);
+    let trash = index_dir.join(TRASH_DIR);
+    // Leftovers of an interrupted reset
+    for dir in [&staging, &trash] {
+        remove_path(dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+
+    create_empty(&staging)
+        .await
+        .context("Failed to create an empty index")?;
+    std::fs::create_dir(&trash)?;
+
+    let mut old = Vec::new();
+    for entry in std::fs::read_dir(index_dir)? {
+        let name = entry?.file_name();
+        if name.to_str().is_some_and(is_store) {
+            old.push(name);
+        }
+    }
+    let mut new = Vec::new();
+    for entry in std::fs::read_dir(&staging)? {
+        new.push(entry?.file_name());
+    }
+
+    // Old stores out, new ones in; undo what was done on failure
+    let mut moved_out = Vec::new();
+    let mut moved_in = Vec::new();
+    let swapped = (|| -> io::Result<()> {
+        for name in &old {
+            std::fs::rename(index_dir.join(name), trash.join(name))?;
+            moved_out.push(name);
+        }
+        for name in &new {
+            std::fs::rename(staging.join(name), index_dir.join(name))?;
+            moved_in.push(name);
+        }
+        Ok(())
+    })();
+    if let Err(e) = swapped {
+        let restored = (|| -> io::Result<()> {
+            for name in moved_in {
+                remove_path(&index_dir.join(name))?;
+            }
+            for name in moved_out {
+                std::fs::rename(trash.join(name), index_dir.join(name))?;
+            }
+            Ok(())
+        })();
+        return match restored {
+            Ok(()) => Err(e).context("Failed to swap in the empty index; the old index was kept"),
+            Err(restore) => bail!(
+                "Failed to swap in the empty index ({}) and to restore the old one ({}); \
+                 its files are in {}",
+                e,
+                restore,
+                trash.display()
+            ),
+        };
+    }
+
+    // The old index is gone either way now, so cleanup failures only warn
+    for dir in [&trash, &staging] {
+        if let Err(e) = remove_path(dir) {
+            warn!(path = %dir.display(), error = %e, "failed to remove after reset");
+        }
+    }
+
+    verify_empty(index_dir)
+        .await
+        .context("The index was reset but the new database can't be read")
+}
+
+/// Create the stores of an empty index in `dir`.
+async fn create_empty(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir.join("blobs"))?;
+    std::fs::create_dir_all(dir.join("vectors"))?;
+    LocalDb::open(&dir.join("db.sqlite")).await?.close().await;
+    verify_empty(dir).await
+}
+
+/// Check that the database in `dir` opens and holds no packages.
+async fn verify_empty(dir: &Path) -> Result<()> {
+    let db = LocalDb::open(&dir.join("db.sqlite")).await?;
+    let versions = db.list_versions().await?;
+    db.close().await;
+    if !versions.is_empty() {
+        bail!("{} versions are still indexed", versions.len());
+    }
+    Ok(())
+}
+
+/// Remove a file or directory tree, if it exists.
+fn remove_path(path: &Path) -> io::Result<()> {
+    let result = match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(path),
+        Ok(_) => std::fs::remove_file(path),
+        Err(e) => Err(e),
+    };
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::LocalIndexer;
+    use crate::local::models::{CreatePackage, VectorRecord};
+    use tempfile::tempdir;
+
+    /// Index one package version by hand: a version row, a blob and vectors.
+    async fn populate(index_dir: &Path) -> String {
+        let indexer = LocalIndexer::new(index_dir).await.unwrap();
+        let package_id = indexer
+            .db()
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "left-pad".to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = indexer
+            .db()
+            .get_or_create_version(&package_id, "1.3.0")
+            .await
+            .unwrap();
+        indexer
+            .db()
+            .mark_version_indexed(&version_id, 1)
+            .await
+            .unwrap();
+        let key = indexer
+            .storage()
+            .put("npm/left-pad/1.3.0", b"function leftPad() {}")
+            .await
+            .unwrap();
+        indexer
+            .vectors()
+            .insert(
+                "npm/left-pad/1.3.0",
+                vec![VectorRecord {
+                    chunk_id: "chunk".to_string(),
+                    content_hash: "hash".to_string(),
+                    vector: vec![0.5; 4],
+                    attributes: None,
+                }],
+            )
+            .await
+            .unwrap();
+        key
+    }
+
+    #[tokio::test]
+    async fn test_reset_populated_index_starts_fresh() {
+        let dir = tempdir().unwrap();
+        let key = populate(dir.path()).await;
+        std::fs::create_dir_all(dir.path().join("cache")).unwrap();
+
+        reset_index(dir.path()).await.unwrap();
+
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        assert!(indexer.db().list_versions().await.unwrap().is_empty());
+        assert!(
+            indexer
+                .vectors()
+                .list_namespaces()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!indexer.storage().exists(&key).await);
+        // The registry cache stays, and nothing is left over from the swap
+        assert!(dir.path().join("cache").is_dir());
+        assert!(!dir.path().join(STAGING_DIR).exists());
+        assert!(!dir.path().join(TRASH_DIR).exists());
+
+        // The reset index takes new packages as usual
+        populate(dir.path()).await;
+    }
+
+    #[tokio::test]
+    async fn test_reset_corrupt_index() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("db.sqlite"), "not a database").unwrap();
+        std::fs::write(dir.path().join("db.sqlite-wal"), "garbage").unwrap();
+        std::fs::create_dir_all(dir.path().join("vectors/npm%2Fx.lance")).unwrap();
+        // An earlier reset that died half-way
+        std::fs::create_dir_all(dir.path().join(TRASH_DIR).join("blobs")).unwrap();
+        assert!(LocalIndexer::new(dir.path()).await.is_err());
+
+        reset_index(dir.path()).await.unwrap();
+
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        assert!(indexer.db().list_versions().await.unwrap().is_empty());
+        assert!(!dir.path().join("vectors/npm%2Fx.lance").exists());
+    }
+
+    #[tokio::test]
+    async fn test_reset_fails_while_index_is_written() {
+        let dir = tempdir().unwrap();
+        let key = populate(dir.path()).await;
+
+        let _writer = IndexLock::shared(dir.path()).await.unwrap();
+        assert!(reset_index(dir.path()).await.is_err());
+
+        let indexer = LocalIndexer::new(dir.path()).await.unwrap();
+        assert_eq!(indexer.db().list_versions().await.unwrap().len(), 1);
+        assert!(indexer.storage().exists(&key).await);
+    }
+}
@@ -1,12 +1,71 @@
 //! Local search service.
 
+use std::collections::{HashMap, HashSet};
+
 use anyhow::{Context, Result};
 
 use super::LocalConfig;
 use super::db::LocalDb;
-use super::models::SearchResult;
+use super::models::{
+    ApiDiff, ApiSymbol, BenchOutcome, BenchQuery, BenchReport, ChunkContext, ChunkFilter,
+    ChunkWithPackage, ImplWithPackage, NamespaceSpec, PackageOutline, QueryMode, ScoreExplanation,
+    SearchOptions, SearchResult, SimilarScope, TypeMethod, VectorSearchHit, compare_versions,
+    cosine_similarity, matches_partial_version,
+};
+use super::query_cache::{self, CachedHit, CachedQuery, QueryCache, QueryKey};
 use super::storage::LocalStorage;
+use super::tokens::{chunk_tokens, tokenize};
 use super::vector::VectorStore;
+use crate::indexer::{Language, doc_embedding_text, embedding_text, name_header, trim_snippet};
+use crate::registry::LATEST_VERSION;
+use crate::types::TenantId;
+
+/// Weight of the keyword match relative to vector similarity in the final
+/// score. A chunk whose name contains every query word gets this much extra.
+const KEYWORD_WEIGHT: f32 = 0.5;
+
+/// Score multiplier for deprecated symbols, so a current API that matches
+/// about as well ranks above them.
+const DEPRECATED_WEIGHT: f32 = 0.8;
+
+/// Share of the gap to a perfect score closed for hits from preferred
+/// packages, so they outrank equally close hits from other packages.
+const PREFERRED_BOOST: f32 = 0.2;
+
+/// Lines of embedded text shown in a score explanation.
+const EXPLAIN_PREVIEW_LINES: usize = 8;
+
+/// Hits fetched from the vector store per result asked for when only public
+/// chunks are kept, so the private ones dropped afterwards rarely leave too
+/// few.
+const PUBLIC_OVERFETCH: usize = 4;
+
+/// Why a search that had hits returned none: the best scored below the
+/// relevance threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoConfidentMatches {
+    pub best: f32,
+    pub threshold: f32,
+}
+
+impl std::fmt::Display for NoConfidentMatches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no confident matches (best score {:.2} below threshold {:.2})",
+            self.best, self.threshold
+        )
+    }
+}
+
+/// Results of [`LocalSearch::search`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Set when every hit scored below the relevance threshold, leaving
+    /// `results` empty.
+    pub no_confident_matches: Option<NoConfidentMatches>,
+}
 
 /// Local search service.
 pub struct LocalSearch {
@@ -14,81 +73,630 @@ pub struct LocalSearch {
     vectors: VectorStore,
     storage: LocalStorage,
     config: LocalConfig,
+    /// HTTP client with the configured proxy and CA settings.
+    http: reqwest::Client,
+    explain: bool,
+    /// Global index searched alongside this one (see [`super::global_index_dir`]).
+    shared: Option<Box<LocalSearch>>,
+    /// Recent searches, so repeating one skips embedding and ranking.
+    query_cache: QueryCache,
 }
 
 impl LocalSearch {
     /// Create a new search service from index directory.
     pub async fn new(index_dir: &std::path::Path) -> Result<Self> {
-        let db = LocalDb::open(&index_dir.join("db.sqlite")).await?;
-        let vectors = VectorStore::open(&index_dir.join("vectors")).await?;
+        Self::new_with_profile(index_dir, None).await
+    }
+
+    /// Create a search service embedding queries with the named profile
+    /// (`None` for the active one).
+    ///
+    /// Fails if the profile's embeddings can't be compared with the index's.
+    pub async fn new_with_profile(
+        index_dir: &std::path::Path,
+        profile: Option<&str>,
+    ) -> Result<Self> {
+        let config = LocalConfig::load()?.with_profile(profile)?;
+        let db =
+            LocalDb::open_with_readers(&index_dir.join("db.sqlite"), config.db_read_connections)
+                .await?;
+        db.check_embedding_dimensions(config.embedding_dimensions)
+            .await
+            .with_context(|| {
+                format!(
+                    "Embedding profile '{}' can't be used with this index",
+                    config.profile_name(None)
+                )
+            })?;
+        let vectors = VectorStore::open(&index_dir.join("vectors"), &config).await?;
         let storage = LocalStorage::new(index_dir.join("blobs")).await?;
-        let config = LocalConfig::load()?;
 
         Ok(Self {
             db,
             vectors,
             storage,
+            http: config.http_client()?,
             config,
+            explain: false,
+            shared: None,
+            query_cache: QueryCache::new(query_cache::DEFAULT_CAPACITY, query_cache::DEFAULT_TTL),
+        })
+    }
+
+    /// Attach a [`ScoreExplanation`] to each search result.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        if let Some(shared) = &mut self.shared {
+            shared.explain = explain;
+        }
+        self
+    }
+
+    /// Also search a global index, merging its hits with this one's.
+    ///
+    /// A package version indexed in both has the same `registry/name/version`
+    /// namespace and the same code, so only this index's copy is searched.
+    pub fn with_shared(mut self, shared: Option<LocalSearch>) -> Self {
+        self.shared = shared.map(|shared| Box::new(shared.with_explain(self.explain)));
+        self
+    }
+
+    /// Embeddings queries are compared with: `mode` if given, else both
+    /// code and documentation embeddings when the index has documentation
+    /// embeddings, else code embeddings.
+    pub fn query_mode(&self, mode: Option<QueryMode>) -> QueryMode {
+        mode.unwrap_or(if self.config.doc_embeddings {
+            QueryMode::Fused
+        } else {
+            QueryMode::Code
         })
     }
 
     /// Search for code chunks.
-    pub async fn search(
+    ///
+    /// Each of `options`' package, registry and version narrows the
+    /// namespaces searched; with none given, every indexed package is
+    /// searched and hits are ranked globally. The version may be exact,
+    /// `"latest"` or partial (`4`, `4.17`), resolving to the newest matching
+    /// indexed version; when it matches no indexed version of the package,
+    /// fails listing the ones that are. The filter keeps chunks from
+    /// matching files and can leave test chunks in or out (see
+    /// [`ChunkFilter`]). The query is also split into words and matched
+    /// against symbol names, so "search timeout" finds `searchTimeout`.
+    ///
+    /// Hits from preferred packages are boosted before ranking (see
+    /// [`PreferredPackages`]).
+    ///
+    /// Hits scoring below `min_score` (default: the configured `min_score`)
+    /// are dropped; if that leaves none, the response says so with
+    /// [`NoConfidentMatches`].
+    ///
+    /// Snippets are cut to `snippet_length` bytes (default: the configured
+    /// `snippet_length`); none is longer than what was stored when its
+    /// package was indexed. The full `docstring` is returned regardless.
+    ///
+    /// Repeating a search within a few minutes reuses its query embedding
+    /// and ranking, unless a package was indexed or removed since; chunk
+    /// details are always read afresh.
+    pub async fn search(&self, query: &str, options: &SearchOptions<'_>) -> Result<SearchResponse> {
+        let key = QueryKey {
+            query: query.to_string(),
+            embedder: format!(
+                "{} {}",
+                self.config.openai_base_url, self.config.embedding_model
+            ),
+            package: options.package.map(str::to_string),
+            registry: options.registry.map(str::to_string),
+            version: options.version.map(str::to_string),
+            filter: options.filter.clone(),
+            preferred: options.preferred.clone(),
+            mode: self.query_mode(options.mode),
+            limit: options.limit,
+        };
+        let fingerprint = self.index_fingerprint().await?;
+
+        let mut results = match self.query_cache.get(&key) {
+            Some(cached) if cached.fingerprint == fingerprint => {
+                self.rehydrate(&cached.hits).await?
+            }
+            cached => {
+                // The index changed since, but the query embeds the same
+                let query_embedding = match cached {
+                    Some(cached) => cached.embedding,
+                    None => self.embed(query).await?,
+                };
+
+                let results = self
+                    .search_vector(&query_embedding, &tokenize(query), options)
+                    .await?;
+
+                let hits = results
+                    .iter()
+                    .map(|result| CachedHit {
+                        chunk_id: result.id.clone(),
+                        distance: result.distance,
+                        score: result.score,
+                        explain: result.explain.clone(),
+                    })
+                    .collect();
+                self.query_cache.insert(
+                    key,
+                    CachedQuery {
+                        embedding: query_embedding,
+                        fingerprint,
+                        hits,
+                    },
+                );
+                results
+            }
+        };
+
+        let snippet_length = options.snippet_length.unwrap_or(self.config.snippet_length);
+        for result in &mut results {
+            result.snippet = trim_snippet(&result.snippet, snippet_length).to_string();
+        }
+
+        Ok(apply_min_score(
+            results,
+            options.min_score.unwrap_or(self.config.min_score),
+        ))
+    }
+
+    /// Run each of `queries` once as [`Self::search`] would with `options`,
+    /// timing the query embedding and the index search apart, and ranking
+    /// its expected symbols among the top `options.limit` results.
+    ///
+    /// Queries run in order and one at a time, each embedded afresh rather
+    /// than taken from the query cache; a query without confident matches
+    /// counts as returning nothing.
+    pub async fn bench(
         &self,
-        query: &str,
-        package: Option<&str>,
-        registry: Option<&str>,
-        version: Option<&str>,
-        limit: usize,
+        queries: &[BenchQuery],
+        options: &SearchOptions<'_>,
+    ) -> Result<BenchReport> {
+        let min_score = options.min_score.unwrap_or(self.config.min_score);
+        let elapsed_ms = |start: std::time::Instant| start.elapsed().as_secs_f64() * 1000.0;
+
+        let mut outcomes = Vec::with_capacity(queries.len());
+        for query in queries {
+            let failed = || format!("Query '{}' failed", query.query);
+
+            let start = std::time::Instant::now();
+            let embedding = self.embed(&query.query).await.with_context(failed)?;
+            let embed_ms = elapsed_ms(start);
+
+            let start = std::time::Instant::now();
+            let results = self
+                .search_vector(&embedding, &tokenize(&query.query), options)
+                .await
+                .with_context(failed)?;
+            let results = apply_min_score(results, min_score).results;
+            let search_ms = elapsed_ms(start);
+
+            outcomes.push(BenchOutcome::new(query, embed_ms, search_ms, &results));
+        }
+
+        Ok(BenchReport::new(options.limit, outcomes))
+    }
+
+    /// Fingerprint of this index and the global one, changing on writes to
+    /// either (see [`LocalDb::index_fingerprint`]).
+    async fn index_fingerprint(&self) -> Result<String> {
+        let mut fingerprint = self.db.index_fingerprint().await?;
+        if let Some(shared) = &self.shared {
+            fingerprint.push('|');
+            fingerprint.push_str(&shared.db.index_fingerprint().await?);
+        }
+        Ok(fingerprint)
+    }
+
+    /// Results for cached hits, with chunk details read afresh from this
+    /// index or the global one. Hits whose chunk is gone are dropped.
+    async fn rehydrate(&self, hits: &[CachedHit]) -> Result<Vec<SearchResult>> {
+        let mut results = Vec::with_capacity(hits.len());
+        for hit in hits {
+            let vector_hit = VectorSearchHit {
+                chunk_id: hit.chunk_id.clone(),
+                distance: hit.distance,
+                metric: self.config.distance_metric,
+            };
+            let mut found = self.hydrate(vec![vector_hit.clone()]).await?;
+            if found.is_empty()
+                && let Some(shared) = &self.shared
+            {
+                found = shared.hydrate(vec![vector_hit]).await?;
+            }
+            for mut result in found {
+                result.score = hit.score;
+                result.explain = hit.explain.clone().map(|explain| ScoreExplanation {
+                    cache_hit: true,
+                    ..explain
+                });
+                results.push(result);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Search with an already computed query embedding.
+    ///
+    /// With a global index attached, its namespaces that aren't indexed
+    /// here are searched too and the hits of both ranked together.
+    async fn search_vector(
+        &self,
+        query_embedding: &[f32],
+        query_tokens: &[String],
+        options: &SearchOptions<'_>,
     ) -> Result<Vec<SearchResult>> {
-        // Generate query embedding
-        let query_embedding = self.embed(query).await?;
-
-        // Determine which namespaces to search
-        let namespaces = if let Some(pkg) = package {
-            // Search specific package
-            if let Some(reg) = registry {
-                if let Some(ver) = version {
-                    vec![format!("{}/{}/{}", reg, pkg, ver)]
+        let SearchOptions {
+            package,
+            registry,
+            version,
+            limit,
+            ..
+        } = *options;
+        // Pre-select namespaces from the index, so filters never hit the vector store
+        let local = self.db.get_namespaces().await?;
+        let namespaces = select_namespaces(local.clone(), package, registry, version);
+        let shared_all = match &self.shared {
+            Some(shared) => shared.db.get_namespaces().await?,
+            None => Vec::new(),
+        };
+        let local_set: HashSet<&String> = local.iter().collect();
+        let mut shared_namespaces =
+            select_namespaces(shared_all.clone(), package, registry, version);
+        shared_namespaces.retain(|ns| !local_set.contains(ns));
+
+        if let (Some(package), Some(version)) = (package, version)
+            && namespaces.is_empty()
+            && shared_namespaces.is_empty()
+        {
+            let indexed = indexed_versions(local.iter().chain(&shared_all), package, registry);
+            anyhow::bail!(
+                "No indexed version of {} matches '{}'. Indexed versions: {}",
+                package,
+                version,
+                if indexed.is_empty() {
+                    "none".to_string()
                 } else {
-                    // Search all versions of this package in this registry
-                    self.db
-                        .get_namespaces()
-                        .await?
-                        .into_iter()
-                        .filter(|ns| ns.starts_with(&format!("{}/{}/", reg, pkg)))
-                        .collect()
+                    indexed.join(", ")
                 }
-            } else {
-                // Search all registries for this package
-                self.db
-                    .get_namespaces()
-                    .await?
-                    .into_iter()
-                    .filter(|ns| {
-                        ns.contains(&format!("/{}/", pkg)) || ns.ends_with(&format!("/{}", pkg))
-                    })
-                    .collect()
-            }
-        } else {
-            // Search all namespaces
-            self.db.get_namespaces().await?
+            );
+        }
+
+        let mut results = self
+            .search_namespaces(&namespaces, query_embedding, query_tokens, options)
+            .await?;
+
+        let Some(shared) = &self.shared else {
+            return Ok(results);
         };
+        results.extend(
+            shared
+                .search_namespaces(&shared_namespaces, query_embedding, query_tokens, options)
+                .await?,
+        );
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+
+        Ok(results)
+    }
 
+    /// Search `namespaces` of this index only; `options`' package, registry
+    /// and version were already applied in choosing them.
+    ///
+    /// Chunks whose name or signature share words with `query_tokens` are
+    /// considered alongside the nearest vectors and their scores boosted.
+    ///
+    /// With a non-empty filter, candidates are pre-filtered from the index
+    /// rather than taken from the vector store, so the filter can't eat
+    /// into the limit.
+    async fn search_namespaces(
+        &self,
+        namespaces: &[String],
+        query_embedding: &[f32],
+        query_tokens: &[String],
+        options: &SearchOptions<'_>,
+    ) -> Result<Vec<SearchResult>> {
         if namespaces.is_empty() {
             return Ok(vec![]);
         }
+        let SearchOptions {
+            filter,
+            preferred,
+            limit,
+            ..
+        } = options;
+        let limit = *limit;
+        let mode = self.query_mode(options.mode);
+
+        let mut hits = self
+            .nearest(namespaces, query_embedding, filter, mode, limit)
+            .await?;
+
+        // Preferred packages' nearest hits too, which the boost may rank in
+        let preferred_namespaces: Vec<String> = namespaces
+            .iter()
+            .filter(|ns| preferred.contains_namespace(ns))
+            .cloned()
+            .collect();
+        if !preferred_namespaces.is_empty() && preferred_namespaces.len() < namespaces.len() {
+            for hit in self
+                .nearest(&preferred_namespaces, query_embedding, filter, mode, limit)
+                .await?
+            {
+                if !hits.iter().any(|h| h.chunk_id == hit.chunk_id) {
+                    hits.push(hit);
+                }
+            }
+        }
+
+        // Keyword matches the vector search missed, scored by their stored embedding
+        for (chunk_id, vector, doc_vector, _) in self
+            .db
+            .keyword_search(namespaces, query_tokens, filter, limit)
+            .await?
+        {
+            if !hits.iter().any(|hit| hit.chunk_id == chunk_id) {
+                let metric = self.config.distance_metric;
+                // Chunks still awaiting embeddings rank on the keyword match alone
+                let distance = if vector.is_empty() {
+                    f32::MAX
+                } else {
+                    mode.distance(metric, query_embedding, &vector, doc_vector.as_deref())
+                };
+                hits.push(VectorSearchHit {
+                    chunk_id,
+                    distance,
+                    metric,
+                });
+            }
+        }
+
+        // Each result with its vector score and the adjustments made to it
+        let mut scored = Vec::new();
+        for mut result in self.hydrate(hits).await? {
+            let vector_score = result.score;
+            if !query_tokens.is_empty() {
+                result.score = keyword_score(
+                    result.score,
+                    query_tokens,
+                    &result.name,
+                    result.signature.as_deref(),
+                );
+            }
+            let keyword_adjustment = result.score - vector_score;
+            if result.deprecated {
+                result.score *= DEPRECATED_WEIGHT;
+            }
+            let mut preference_adjustment = None;
+            if preferred.contains(&result.registry, &result.package) {
+                let boost = PREFERRED_BOOST * (1.0 - result.score);
+                result.score += boost;
+                preference_adjustment = Some(boost);
+            }
+            scored.push((
+                result,
+                vector_score,
+                keyword_adjustment,
+                preference_adjustment,
+            ));
+        }
+        scored.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
+        scored.truncate(limit);
+
+        // Only the results returned are explained
+        let mut results = Vec::with_capacity(scored.len());
+        for (mut result, vector_score, keyword_adjustment, preference_adjustment) in scored {
+            if self.explain {
+                let explanation = self
+                    .explain_result(&result, mode, query_embedding, query_tokens)
+                    .await?;
+                result.explain = Some(ScoreExplanation {
+                    vector_score,
+                    keyword_adjustment,
+                    preference_adjustment,
+                    ..explanation
+                });
+            }
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    /// Nearest chunks to the query in `namespaces` passing `filter`.
+    ///
+    /// The vector store's code embeddings are searched and, unless `mode`
+    /// is [`QueryMode::Code`], its documentation embeddings too, with the
+    /// two lists fused. Keeping only public chunks (the default) over-fetches
+    /// and drops the rest; other filters, and a public-only search whose
+    /// over-fetched hits were mostly private, are answered from the index.
+    async fn nearest(
+        &self,
+        namespaces: &[String],
+        query_embedding: &[f32],
+        filter: &ChunkFilter,
+        mode: QueryMode,
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        let from_index = || {
+            self.db.nearest_chunks(
+                namespaces,
+                filter,
+                query_embedding,
+                self.config.distance_metric,
+                mode,
+                limit,
+            )
+        };
+        let public_only = filter.is_public_only();
+        if !filter.is_empty() && !public_only {
+            return from_index().await;
+        }
+
+        let fetch = if public_only {
+            limit * PUBLIC_OVERFETCH
+        } else {
+            limit
+        };
+        let mut code = self
+            .vectors
+            .search_multi(namespaces, query_embedding, fetch)
+            .await?;
+        let mut docs = if mode == QueryMode::Code {
+            Vec::new()
+        } else {
+            self.vectors
+                .search_docs_multi(namespaces, query_embedding, fetch)
+                .await?
+        };
+        if public_only {
+            let fetched = code.len();
+            let ids: Vec<String> = code
+                .iter()
+                .chain(&docs)
+                .map(|hit| hit.chunk_id.clone())
+                .collect();
+            let public = self.db.public_chunk_ids(&ids).await?;
+            code.retain(|hit| public.contains(&hit.chunk_id));
+            docs.retain(|hit| public.contains(&hit.chunk_id));
+            // Public chunks may lie past the private ones fetched
+            if fetched == fetch && code.len() < limit {
+                return from_index().await;
+            }
+        }
+        if mode == QueryMode::Code {
+            code.truncate(limit);
+            return Ok(code);
+        }
+
+        // Docs mode measures documented chunks to their doc embedding only
+        let documented = if mode == QueryMode::Docs {
+            let ids: Vec<String> = code.iter().map(|hit| hit.chunk_id.clone()).collect();
+            self.db.chunks_with_doc_vectors(&ids).await?
+        } else {
+            HashSet::new()
+        };
+        Ok(fuse_hits(code, docs, &documented, limit))
+    }
+
+    /// Find chunks semantically similar to an already-indexed chunk.
+    ///
+    /// Reuses the chunk's stored embedding, so no embeddings API call is made.
+    /// The chunk itself is excluded from the results.
+    pub async fn similar_to(
+        &self,
+        chunk_id: &str,
+        limit: usize,
+        scope: SimilarScope,
+    ) -> Result<Vec<SearchResult>> {
+        let (namespace, vector) = self
+            .db
+            .get_chunk_vector(chunk_id)
+            .await?
+            .with_context(|| format!("Chunk not found: {}", chunk_id))?;
+        if vector.is_empty() {
+            anyhow::bail!(
+                "Chunk {} has no embedding yet; finish indexing its package first",
+                chunk_id
+            );
+        }
+
+        let namespaces = match scope {
+            SimilarScope::Namespace => vec![namespace],
+            SimilarScope::All => self.db.get_namespaces().await?,
+        };
 
-        // Search vectors
-        let hits = self
+        // Ask for one extra hit since the chunk will match itself
+        let mut hits = self
             .vectors
-            .search_multi(&namespaces, &query_embedding, limit)
+            .search_multi(&namespaces, &vector, limit + 1)
             .await?;
+        hits.retain(|hit| hit.chunk_id != chunk_id);
+        hits.truncate(limit);
 
-        // Fetch chunk details
+        self.hydrate(hits).await
+    }
+
+    /// Break down how `result` was matched against the query; the score
+    /// adjustments are left for the caller, which applied them.
+    async fn explain_result(
+        &self,
+        result: &SearchResult,
+        mode: QueryMode,
+        query_embedding: &[f32],
+        query_tokens: &[String],
+    ) -> Result<ScoreExplanation> {
+        let (_, vector) = self
+            .db
+            .get_chunk_vector(&result.id)
+            .await?
+            .with_context(|| format!("Chunk not found: {}", result.id))?;
+        let doc_vector = self.db.get_chunk_doc_vector(&result.id).await?;
+
+        let metric = self.config.distance_metric;
+        let matched_docs =
+            mode.matches_doc(metric, query_embedding, &vector, doc_vector.as_deref());
+        let (vector, text) = match doc_vector.filter(|_| matched_docs) {
+            Some(doc_vector) => {
+                let text =
+                    doc_embedding_text(result.docstring.as_deref(), result.signature.as_deref());
+                (doc_vector, text.unwrap_or_default())
+            }
+            None => {
+                // Without the stored code (e.g. a pruned blob) the preview is partial
+                let code = self.get_code(&result.storage_key).await.unwrap_or_default();
+                let header = name_header(
+                    &result.name,
+                    result.parent.as_deref(),
+                    self.config.embedding_name_weight,
+                );
+                let text = embedding_text(
+                    header.as_deref(),
+                    result.docstring.as_deref(),
+                    result.signature.as_deref(),
+                    &code,
+                );
+                (vector, text)
+            }
+        };
+
+        Ok(ScoreExplanation {
+            metric,
+            mode,
+            cache_hit: false,
+            matched_docs,
+            distance: if vector.is_empty() {
+                f32::MAX
+            } else {
+                metric.distance(query_embedding, &vector)
+            },
+            vector_score: result.score,
+            cosine: cosine_similarity(query_embedding, &vector),
+            keyword_matches: matched_tokens(
+                query_tokens,
+                &result.name,
+                result.signature.as_deref(),
+            ),
+            keyword_adjustment: 0.0,
+            deprecation_weight: result.deprecated.then_some(DEPRECATED_WEIGHT),
+            preference_adjustment: None,
+            embedded_text: text
+                .lines()
+                .take(EXPLAIN_PREVIEW_LINES)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+    }
+
+    /// Fetch chunk details for vector hits, preserving hit order.
+    async fn hydrate(&self, hits: Vec<VectorSearchHit>) -> Result<Vec<SearchResult>> {
         let mut results = Vec::with_capacity(hits.len());
         for hit in hits {
             if let Some(chunk) = self.db.get_chunk_with_package(&hit.chunk_id).await? {
+                let language = fence_tag(&chunk.file_path, chunk.signature.as_deref());
                 results.push(SearchResult {
                     id: chunk.id,
                     registry: chunk.registry,
@@ -96,6 +704,7 @@ impl LocalSearch {
                     version: chunk.version,
                     chunk_type: chunk.chunk_type,
                     name: chunk.name,
+                    parent: chunk.parent,
                     file_path: chunk.file_path,
                     start_line: chunk.start_line as u32,
                     end_line: chunk.end_line as u32,
@@ -103,8 +712,13 @@ impl LocalSearch {
                     signature: chunk.signature,
                     docstring: chunk.docstring,
                     snippet: chunk.snippet,
+                    language,
                     storage_key: chunk.storage_key,
+                    deprecated: chunk.deprecated,
+                    deprecation_message: chunk.deprecation_message,
+                    distance: hit.distance,
                     score: hit.score(),
+                    explain: None,
                 });
             }
         }
@@ -114,20 +728,137 @@ impl LocalSearch {
 
     /// Get full code for a chunk.
     pub async fn get_code(&self, storage_key: &str) -> Result<String> {
-        let bytes = self.storage.get(storage_key).await?;
+        let bytes = self.get_blob(storage_key).await?;
         String::from_utf8(bytes).context("Invalid UTF-8 in stored code")
     }
 
+    /// Get bytes `[start, end)` of a chunk's code, clamped to its length.
+    pub async fn get_code_range(
+        &self,
+        storage_key: &str,
+        start: usize,
+        end: usize,
+    ) -> Result<Vec<u8>> {
+        match &self.shared {
+            Some(shared) if !self.storage.exists(storage_key).await => {
+                shared.storage.get_range(storage_key, start, end).await
+            }
+            _ => self.storage.get_range(storage_key, start, end).await,
+        }
+    }
+
+    /// Look up a chunk in this index, then in the global one.
+    pub async fn get_chunk(&self, chunk_id: &str) -> Result<Option<ChunkWithPackage>> {
+        if let Some(chunk) = self.db.get_chunk_with_package(chunk_id).await? {
+            return Ok(Some(chunk));
+        }
+        match &self.shared {
+            Some(shared) => shared.db.get_chunk_with_package(chunk_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Get the code of several chunks at once, in the order of
+    /// `storage_keys`. `None` for code that can't be read (e.g. a pruned
+    /// blob), so callers can still show the rest.
+    pub async fn get_codes(&self, storage_keys: &[&str]) -> Vec<Option<String>> {
+        let mut blobs = self.storage.get_many(storage_keys).await;
+
+        // Blobs missing here may be global hits
+        if let Some(shared) = &self.shared {
+            let missing: Vec<usize> = (0..blobs.len()).filter(|&i| blobs[i].is_err()).collect();
+            let keys: Vec<&str> = missing.iter().map(|&i| storage_keys[i]).collect();
+            for (i, blob) in missing
+                .into_iter()
+                .zip(shared.storage.get_many(&keys).await)
+            {
+                blobs[i] = blob;
+            }
+        }
+
+        blobs
+            .into_iter()
+            .map(|blob| blob.ok().and_then(|bytes| String::from_utf8(bytes).ok()))
+            .collect()
+    }
+
+    /// Read a blob from this index, falling back to the global one.
+    async fn get_blob(&self, key: &str) -> Result<Vec<u8>> {
+        match &self.shared {
+            Some(shared) if !self.storage.exists(key).await => shared.storage.get(key).await,
+            _ => self.storage.get(key).await,
+        }
+    }
+
+    /// Get a chunk's code with `context_lines` lines before and after it.
+    ///
+    /// Requires the source file to have been stored at index time
+    /// (`store_source_files = true`).
+    pub async fn get_context(&self, chunk_id: &str, context_lines: usize) -> Result<ChunkContext> {
+        let chunk = self
+            .get_chunk(chunk_id)
+            .await?
+            .with_context(|| format!("Chunk not found: {}", chunk_id))?;
+
+        let file_key = chunk.file_storage_key.as_deref().context(
+            "Source file not stored for this chunk. Set store_source_files = true and re-index.",
+        )?;
+
+        let bytes = self.get_blob(file_key).await?;
+        let source = String::from_utf8(bytes).context("Invalid UTF-8 in stored source file")?;
+
+        let end = (chunk.end_byte.max(0) as usize).min(source.len());
+        let start = (chunk.start_byte.max(0) as usize).min(end);
+        if !source.is_char_boundary(start) || !source.is_char_boundary(end) {
+            anyhow::bail!("Stored byte offsets do not match the source file");
+        }
+
+        // Expand to the start of the line `context_lines` lines above the chunk
+        let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let mut before_start = line_start;
+        for _ in 0..context_lines {
+            if before_start == 0 {
+                break;
+            }
+            before_start = source[..before_start - 1]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0);
+        }
+
+        // Expand to the end of the line `context_lines` lines below the chunk
+        let mut after_end = source[end..]
+            .find('\n')
+            .map(|i| end + i + 1)
+            .unwrap_or(source.len());
+        for _ in 0..context_lines {
+            if after_end >= source.len() {
+                break;
+            }
+            after_end = source[after_end..]
+                .find('\n')
+                .map(|i| after_end + i + 1)
+                .unwrap_or(source.len());
+        }
+
+        let before_lines = source[before_start..line_start].lines().count() as u32;
+
+        Ok(ChunkContext {
+            file_path: chunk.file_path,
+            start_line: (chunk.start_line as u32).saturating_sub(before_lines),
+            before: source[before_start..start].to_string(),
+            code: source[start..end].to_string(),
+            after: source[end..after_end].to_string(),
+        })
+    }
+
     /// Generate embedding for a query.
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        let api_key = self
-            .config
-            .openai_api_key
-            .as_ref()
-            .context("OpenAI API key not configured. Run: idx config set-key")?;
-
-        let client = reqwest::Client::new();
-        let response = client
+        self.config.check_offline_embeddings()?;
+        let api_key = self.config.require_openai_key()?;
+
+        let response = self
+            .http
             .post(format!("{}/v1/embeddings", self.config.openai_base_url))
             .header("Authorization", format!("Bearer {}", api_key))
             .json(&serde_json::json!({
@@ -143,12 +874,206 @@ impl LocalSearch {
             .await
             .context("Failed to parse embeddings response")?;
 
-        response
+        let embedding = response
             .data
             .into_iter()
             .next()
             .map(|d| d.embedding)
-            .context("No embedding returned")
+            .context("No embedding returned")?;
+        self.config.check_embedding(&embedding)?;
+
+        Ok(embedding)
+    }
+
+    /// Symbols of an indexed package version grouped by file, without
+    /// embedding anything.
+    ///
+    /// `version` defaults to the newest indexed version. Looks in this index,
+    /// then the global one; `None` if neither has the package.
+    pub async fn outline(
+        &self,
+        registry: &str,
+        package: &str,
+        version: Option<&str>,
+        filter: &ChunkFilter,
+    ) -> Result<Option<PackageOutline>> {
+        let version = version.unwrap_or(LATEST_VERSION);
+        for search in std::iter::once(self).chain(self.shared.as_deref()) {
+            let namespaces = search.db.get_namespaces().await?;
+            let selected =
+                select_namespaces(namespaces, Some(package), Some(registry), Some(version));
+            let Some(namespace) = selected.first() else {
+                continue;
+            };
+
+            let version = namespace
+                .parse::<NamespaceSpec>()
+                .map(|ns| ns.version)
+                .unwrap_or_default();
+            let symbols = search.db.outline_symbols(namespace, filter).await?;
+            let entry_point = search.db.namespace_entry_point(namespace).await?;
+            return Ok(Some(
+                PackageOutline::new(registry, package, &version, symbols)
+                    .with_entry_point(entry_point),
+            ));
+        }
+        Ok(None)
+    }
+
+    /// Path of the entry module of an indexed package version: the file to
+    /// start exploring it from.
+    ///
+    /// `version` defaults to the newest indexed version. Looks in this index,
+    /// then the global one; `None` if neither has the package or no entry
+    /// point was detected for it.
+    pub async fn entry_point(
+        &self,
+        registry: &str,
+        package: &str,
+        version: Option<&str>,
+    ) -> Result<Option<String>> {
+        let version = version.unwrap_or(LATEST_VERSION);
+        for search in std::iter::once(self).chain(self.shared.as_deref()) {
+            let namespaces = search.db.get_namespaces().await?;
+            let selected =
+                select_namespaces(namespaces, Some(package), Some(registry), Some(version));
+            if let Some(namespace) = selected.first() {
+                return search.db.namespace_entry_point(namespace).await;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Compare the symbols of two indexed versions of a package, matched by
+    /// name, parent and type.
+    ///
+    /// Either version may be `"latest"`. Each is looked up in this index,
+    /// then the global one. With `docs`, symbols whose documentation changed
+    /// are reported too.
+    pub async fn diff(
+        &self,
+        registry: &str,
+        package: &str,
+        from: &str,
+        to: &str,
+        filter: &ChunkFilter,
+        docs: bool,
+    ) -> Result<ApiDiff> {
+        let (from, old) = self
+            .version_symbols(registry, package, from, filter)
+            .await?;
+        let (to, new) = self.version_symbols(registry, package, to, filter).await?;
+        Ok(ApiDiff::new(registry, package, &from, &to, old, new, docs))
+    }
+
+    /// Resolved version and symbols of an indexed package version.
+    async fn version_symbols(
+        &self,
+        registry: &str,
+        package: &str,
+        version: &str,
+        filter: &ChunkFilter,
+    ) -> Result<(String, Vec<ApiSymbol>)> {
+        for search in std::iter::once(self).chain(self.shared.as_deref()) {
+            let namespaces = search.db.get_namespaces().await?;
+            let selected =
+                select_namespaces(namespaces, Some(package), Some(registry), Some(version));
+            let Some(namespace) = selected.first() else {
+                continue;
+            };
+
+            let resolved = namespace
+                .parse::<NamespaceSpec>()
+                .map(|ns| ns.version)
+                .unwrap_or_default();
+            let symbols = search.db.api_symbols(namespace, filter).await?;
+            return Ok((resolved, symbols));
+        }
+        anyhow::bail!("{}:{}@{} is not indexed", registry, package, version)
+    }
+
+    /// Types implementing the trait named `trait_name` (`impl Trait for
+    /// Type`), optionally narrowed to a registry and package.
+    ///
+    /// Only the newest indexed version of each package is consulted (see
+    /// [`Self::latest_namespaces`]).
+    pub async fn implementations_of(
+        &self,
+        trait_name: &str,
+        registry: Option<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<ImplWithPackage>> {
+        let mut impls = Vec::new();
+        for (search, namespaces) in self.latest_namespaces(registry, package).await? {
+            impls.extend(
+                search
+                    .db
+                    .find_impls(&namespaces, Some(trait_name), None)
+                    .await?,
+            );
+        }
+        Ok(impls)
+    }
+
+    /// Traits the type named `type_name` implements, optionally narrowed to
+    /// a registry and package.
+    ///
+    /// Only explicit implementations are known, so Go types report none;
+    /// see [`LocalSearch::method_set`] for what they satisfy.
+    pub async fn traits_implemented_by(
+        &self,
+        type_name: &str,
+        registry: Option<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<ImplWithPackage>> {
+        let mut impls = Vec::new();
+        for (search, namespaces) in self.latest_namespaces(registry, package).await? {
+            impls.extend(
+                search
+                    .db
+                    .find_impls(&namespaces, None, Some(type_name))
+                    .await?,
+            );
+        }
+        Ok(impls)
+    }
+
+    /// Methods declared on the type named `type_name`, optionally narrowed to
+    /// a registry and package.
+    ///
+    /// For Go, whose interfaces are satisfied implicitly, a type's method set
+    /// is what decides the interfaces it implements.
+    pub async fn method_set(
+        &self,
+        type_name: &str,
+        registry: Option<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<TypeMethod>> {
+        let mut methods = Vec::new();
+        for (search, namespaces) in self.latest_namespaces(registry, package).await? {
+            methods.extend(search.db.type_methods(&namespaces, type_name).await?);
+        }
+        Ok(methods)
+    }
+
+    /// Namespaces of the newest indexed version of each matching package,
+    /// in this index and then the global one, each with the index holding
+    /// it. Namespaces this index also holds are left out of the global one,
+    /// so their symbols aren't reported twice.
+    async fn latest_namespaces(
+        &self,
+        registry: Option<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<(&LocalSearch, Vec<String>)>> {
+        let mut seen = HashSet::new();
+        let mut by_index = Vec::new();
+        for search in std::iter::once(self).chain(self.shared.as_deref()) {
+            let namespaces = search.db.get_namespaces().await?;
+            let mut latest = select_namespaces(namespaces, package, registry, Some(LATEST_VERSION));
+            latest.retain(|ns| seen.insert(ns.clone()));
+            by_index.push((search, latest));
+        }
+        Ok(by_index)
     }
 
     /// List indexed packages.
@@ -165,14 +1090,2182 @@ impl LocalSearch {
     pub fn db(&self) -> &LocalDb {
         &self.db
     }
+
+    /// Get the configuration searches run with.
+    pub fn config(&self) -> &LocalConfig {
+        &self.config
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct EmbeddingResponse {
-    data: Vec<EmbeddingData>,
+/// Keep the `registry/name/version` namespaces matching every given filter.
+///
+/// Package names may themselves contain `/` (npm scopes, Go modules), so the
+/// registry is the first segment and the version the last.
+///
+/// A `"latest"` version keeps the newest indexed version of each package,
+/// and a partial one (`4`, `4.17`) the newest version of each package it
+/// matches.
+fn select_namespaces(
+    namespaces: Vec<String>,
+    package: Option<&str>,
+    registry: Option<&str>,
+    version: Option<&str>,
+) -> Vec<String> {
+    let latest = version == Some(LATEST_VERSION);
+    let version = version.filter(|_| !latest);
+
+    let selected: Vec<String> = namespaces
+        .into_iter()
+        .filter(|ns| {
+            let Ok(ns) = ns.parse::<NamespaceSpec>() else {
+                return false;
+            };
+            registry.is_none_or(|r| r == ns.registry)
+                && package.is_none_or(|p| p == ns.name)
+                && version
+                    .is_none_or(|v| v == ns.version || matches_partial_version(v, &ns.version))
+        })
+        .collect();
+
+    // An exact version leaves one namespace per package anyway
+    if latest || version.is_some() {
+        newest_per_package(selected)
+    } else {
+        selected
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct EmbeddingData {
-    embedding: Vec<f32>,
+/// Distinct versions of `package` among `namespaces`, newest first.
+fn indexed_versions<'a>(
+    namespaces: impl Iterator<Item = &'a String>,
+    package: &str,
+    registry: Option<&str>,
+) -> Vec<String> {
+    let mut versions: Vec<String> = namespaces
+        .filter_map(|ns| ns.parse::<NamespaceSpec>().ok())
+        .filter(|ns| ns.name == package && registry.is_none_or(|r| r == ns.registry))
+        .map(|ns| ns.version)
+        .collect();
+    versions.sort_by(|a, b| compare_versions(b, a));
+    versions.dedup();
+    versions
+}
+
+/// Keep only the newest version of each package among `namespaces`. A
+/// tenant's private package is apart from a public one of the same name.
+fn newest_per_package(namespaces: Vec<String>) -> Vec<String> {
+    let mut newest: HashMap<(Option<TenantId>, String, String), NamespaceSpec> = HashMap::new();
+    for namespace in namespaces {
+        let Ok(spec) = namespace.parse::<NamespaceSpec>() else {
+            continue;
+        };
+        let package = (spec.tenant, spec.registry.clone(), spec.name.clone());
+        let is_newer = newest
+            .get(&package)
+            .is_none_or(|current| compare_versions(&spec.version, &current.version).is_gt());
+        if is_newer {
+            newest.insert(package, spec);
+        }
+    }
+
+    let mut selected: Vec<String> = newest.values().map(ToString::to_string).collect();
+    selected.sort();
+    selected
+}
+
+/// Drop results scoring below `min_score`, noting when that drops them all.
+/// `results` must be sorted best first.
+fn apply_min_score(mut results: Vec<SearchResult>, min_score: f32) -> SearchResponse {
+    let Some(best) = results.first().map(|r| r.score) else {
+        return SearchResponse::default();
+    };
+    if best < min_score {
+        return SearchResponse {
+            results: Vec::new(),
+            no_confident_matches: Some(NoConfidentMatches {
+                best,
+                threshold: min_score,
+            }),
+        };
+    }
+
+    results.retain(|r| r.score >= min_score);
+    SearchResponse {
+        results,
+        no_confident_matches: None,
+    }
+}
+
+/// Language to tag a result's code with: a Markdown code block's own info
+/// string (kept as its signature, e.g. ```` ```js ````), otherwise the
+/// language of its file. Untagged code blocks get none.
+fn fence_tag(file_path: &str, signature: Option<&str>) -> Option<String> {
+    let language = Language::from_path(file_path)?;
+    if language != Language::Markdown {
+        return Some(language.fence_tag().to_string());
+    }
+    match signature.and_then(|s| s.strip_prefix("```")) {
+        Some(info) => info
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .next()
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string),
+        None => Some(language.fence_tag().to_string()),
+    }
+}
+
+/// The `limit` nearest chunks among code embedding hits `code` and
+/// documentation embedding hits `docs`, each chunk at its nearer distance.
+///
+/// Code hits for chunks in `documented` are dropped, so those rank by their
+/// doc embedding alone.
+fn fuse_hits(
+    code: Vec<VectorSearchHit>,
+    docs: Vec<VectorSearchHit>,
+    documented: &HashSet<String>,
+    limit: usize,
+) -> Vec<VectorSearchHit> {
+    let mut nearest: HashMap<String, VectorSearchHit> = HashMap::new();
+    let code = code
+        .into_iter()
+        .filter(|hit| !documented.contains(&hit.chunk_id));
+    for hit in docs.into_iter().chain(code) {
+        match nearest.get(&hit.chunk_id) {
+            Some(existing) if existing.distance <= hit.distance => {}
+            _ => {
+                nearest.insert(hit.chunk_id.clone(), hit);
+            }
+        }
+    }
+
+    let mut hits: Vec<VectorSearchHit> = nearest.into_values().collect();
+    hits.sort_by(|a, b| {
+        a.distance
+            .total_cmp(&b.distance)
+            .then_with(|| a.chunk_id.cmp(&b.chunk_id))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+/// Blend a vector similarity score with the share of query words found in
+/// the chunk's name and signature. Stays within 0-1.
+fn keyword_score(score: f32, query_tokens: &[String], name: &str, signature: Option<&str>) -> f32 {
+    let matched = matched_tokens(query_tokens, name, signature).len();
+    let keyword = matched as f32 / query_tokens.len() as f32;
+
+    (score + KEYWORD_WEIGHT * keyword) / (1.0 + KEYWORD_WEIGHT)
+}
+
+/// Query words found in a chunk's name and signature.
+fn matched_tokens(query_tokens: &[String], name: &str, signature: Option<&str>) -> Vec<String> {
+    let tokens = chunk_tokens(name, signature);
+    query_tokens
+        .iter()
+        .filter(|token| tokens.contains(&format!(" {} ", token)))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::models::{
+        CreateChunk, CreatePackage, DistanceMetric, GroupedResult, KindFilter, PreferredPackages,
+        QueryMode, TestFilter, VECTOR_DIM, VectorRecord, group_by_parent, squared_l2,
+    };
+    use crate::test_support::stub_embeddings;
+    use crate::types::SearchScope;
+    use tempfile::tempdir;
+
+    /// A unit-ish vector pointing mostly along `axis`, nudged by `jitter`.
+    fn vector_near(axis: usize, jitter: f32) -> Vec<f32> {
+        let mut v = vec![0.0; VECTOR_DIM as usize];
+        v[axis] = 1.0;
+        v[axis + 1] = jitter;
+        v
+    }
+
+    /// Create `registry/name/version`, returning its version id and namespace.
+    async fn create_version(
+        search: &LocalSearch,
+        registry: &str,
+        name: &str,
+        version: &str,
+    ) -> (String, String) {
+        let package_id = search
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: registry.to_string(),
+                name: name.to_string(),
+                description: None,
+                tenant: None,
+            })
+            .await
+            .unwrap();
+        let (version_id, _) = search
+            .db
+            .get_or_create_version(&package_id, version)
+            .await
+            .unwrap();
+        (
+            version_id,
+            NamespaceSpec::new(registry, name, version).to_string(),
+        )
+    }
+
+    async fn insert_chunk(
+        search: &LocalSearch,
+        version_id: &str,
+        namespace: &str,
+        name: &str,
+        vector: Vec<f32>,
+    ) {
+        insert_chunk_in(
+            search, version_id, namespace, "index.js", name, vector, false,
+        )
+        .await;
+    }
+
+    async fn insert_chunk_in(
+        search: &LocalSearch,
+        version_id: &str,
+        namespace: &str,
+        file_path: &str,
+        name: &str,
+        vector: Vec<f32>,
+        deprecated: bool,
+    ) {
+        let chunk = CreateChunk {
+            deprecated,
+            ..new_chunk(version_id, namespace, file_path, name, vector)
+        };
+        insert(search, chunk).await;
+    }
+
+    fn new_chunk(
+        version_id: &str,
+        namespace: &str,
+        file_path: &str,
+        name: &str,
+        vector: Vec<f32>,
+    ) -> CreateChunk {
+        CreateChunk {
+            id: name.to_string(),
+            version_id: version_id.to_string(),
+            namespace: namespace.to_string(),
+            chunk_type: "function".to_string(),
+            name: name.to_string(),
+            file_path: file_path.to_string(),
+            start_line: 1,
+            end_line: 2,
+            visibility: "public".to_string(),
+            signature: None,
+            params: Vec::new(),
+            returns: None,
+            docstring: None,
+            snippet: String::new(),
+            storage_key: format!("{}/{}", namespace, name),
+            content_hash: name.to_string(),
+            vector,
+            embedding_model: "test-model".to_string(),
+            file_storage_key: None,
+            start_byte: 0,
+            end_byte: 0,
+            deprecated: false,
+            deprecation_message: None,
+            parent: None,
+            embedding_hash: None,
+            doc_hash: None,
+            doc_vector: None,
+        }
+    }
+
+    async fn insert(search: &LocalSearch, chunk: CreateChunk) {
+        search.db.insert_chunk(&chunk).await.unwrap();
+        if let Some(doc_vector) = chunk.doc_vector.clone() {
+            search
+                .vectors
+                .insert_docs(
+                    &chunk.namespace,
+                    vec![VectorRecord {
+                        chunk_id: chunk.id.clone(),
+                        content_hash: chunk.doc_hash.clone().unwrap_or_default(),
+                        vector: doc_vector,
+                        attributes: None,
+                    }],
+                )
+                .await
+                .unwrap();
+        }
+        search
+            .vectors
+            .insert(
+                &chunk.namespace,
+                vec![VectorRecord {
+                    chunk_id: chunk.id.clone(),
+                    content_hash: chunk.content_hash.clone(),
+                    vector: chunk.vector,
+                    attributes: None,
+                }],
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unembedded_chunks_match_by_keyword() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        // Stored by a metadata-only run: a chunk row without any vectors
+        let (pending_id, pending_ns) = create_version(&search, "npm", "client", "1.0.0").await;
+        search
+            .db
+            .insert_chunk(&new_chunk(
+                &pending_id,
+                &pending_ns,
+                "index.js",
+                "retryRequest",
+                Vec::new(),
+            ))
+            .await
+            .unwrap();
+        let (embedded_id, embedded_ns) = create_version(&search, "npm", "server", "1.0.0").await;
+        insert_chunk(
+            &search,
+            &embedded_id,
+            &embedded_ns,
+            "listen",
+            vector_near(0, 0.1),
+        )
+        .await;
+
+        let query = vector_near(0, 0.0);
+        let results = search
+            .search_vector(&query, &tokenize("retry"), &SearchOptions::default())
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["listen", "retryRequest"]);
+        // No vector similarity to add to the keyword match
+        let pending = &results[1];
+        assert_eq!(pending.distance, f32::MAX);
+        assert!((pending.score - KEYWORD_WEIGHT / (1.0 + KEYWORD_WEIGHT)).abs() < 1e-4);
+
+        let err = search
+            .similar_to("retryRequest", 5, SimilarScope::All)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no embedding yet"));
+    }
+
+    #[tokio::test]
+    async fn test_similar_to_returns_nearest_excluding_self() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "cluster", "1.0.0").await;
+
+        // Cluster A around axis 0, cluster B around axis 10
+        insert_chunk(&search, &version_id, &ns, "target", vector_near(0, 0.0)).await;
+        insert_chunk(&search, &version_id, &ns, "near_a", vector_near(0, 0.1)).await;
+        insert_chunk(&search, &version_id, &ns, "near_b", vector_near(0, 0.2)).await;
+        insert_chunk(&search, &version_id, &ns, "far_a", vector_near(10, 0.0)).await;
+        insert_chunk(&search, &version_id, &ns, "far_b", vector_near(10, 0.1)).await;
+
+        let results = search
+            .similar_to("target", 2, SimilarScope::Namespace)
+            .await
+            .unwrap();
+
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["near_a", "near_b"]);
+
+        let all = search
+            .similar_to("target", 10, SimilarScope::All)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all.iter().all(|r| r.id != "target"));
+    }
+
+    #[tokio::test]
+    async fn test_similar_to_unknown_chunk() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        assert!(
+            search
+                .similar_to("missing", 5, SimilarScope::All)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_global_search_ranks_across_packages() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (alpha_id, alpha_ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        let (beta_id, beta_ns) = create_version(&search, "pypi", "beta", "2.0.0").await;
+
+        insert_chunk(
+            &search,
+            &alpha_id,
+            &alpha_ns,
+            "alpha_close",
+            vector_near(0, 0.1),
+        )
+        .await;
+        insert_chunk(
+            &search,
+            &alpha_id,
+            &alpha_ns,
+            "alpha_far",
+            vector_near(10, 0.0),
+        )
+        .await;
+        insert_chunk(
+            &search,
+            &beta_id,
+            &beta_ns,
+            "beta_closest",
+            vector_near(0, 0.05),
+        )
+        .await;
+        insert_chunk(&search, &beta_id, &beta_ns, "beta_mid", vector_near(0, 0.3)).await;
+
+        let query = vector_near(0, 0.0);
+
+        let results = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["beta_closest", "alpha_close", "beta_mid"]);
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let npm_only = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    registry: Some("npm"),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = npm_only.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha_close", "alpha_far"]);
+
+        let missing_version = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    package: Some("beta"),
+                    version: Some("1.0.0"),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            missing_version.to_string(),
+            "No indexed version of beta matches '1.0.0'. Indexed versions: 2.0.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_search_uses_the_vector_store() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+        let (id, ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&search, &id, &ns, "alpha_close", vector_near(0, 0.2)).await;
+        let private = CreateChunk {
+            visibility: "private".to_string(),
+            ..new_chunk(&id, &ns, "index.js", "alpha_private", vector_near(0, 0.0))
+        };
+        insert(&search, private).await;
+        // Closest of all, but only in SQLite: a scan of the index would find it
+        let unindexed = new_chunk(&id, &ns, "index.js", "alpha_unindexed", vector_near(0, 0.0));
+        search.db.insert_chunk(&unindexed).await.unwrap();
+
+        let filter = ChunkFilter::default().with_public_only(search.config.public_only);
+        assert!(filter.is_public_only());
+        let results = search
+            .search_vector(
+                &vector_near(0, 0.0),
+                &[],
+                &SearchOptions {
+                    filter,
+                    limit: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha_close"]);
+    }
+
+    #[tokio::test]
+    async fn test_version_spec_resolves_to_indexed_version() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+        for version in ["3.10.1", "4.16.6", "4.17.21"] {
+            let (id, ns) = create_version(&search, "npm", "lodash", version).await;
+            insert_chunk(&search, &id, &ns, version, vector_near(0, 0.0)).await;
+        }
+
+        async fn found(search: &LocalSearch, version: &str) -> Result<Vec<String>> {
+            let results = search
+                .search_vector(
+                    &vector_near(0, 0.0),
+                    &[],
+                    &SearchOptions {
+                        package: Some("lodash"),
+                        version: Some(version),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            Ok(results.into_iter().map(|r| r.version).collect())
+        }
+
+        assert_eq!(found(&search, "latest").await.unwrap(), vec!["4.17.21"]);
+        assert_eq!(found(&search, "4").await.unwrap(), vec!["4.17.21"]);
+        assert_eq!(found(&search, "4.16").await.unwrap(), vec!["4.16.6"]);
+        assert_eq!(found(&search, "3").await.unwrap(), vec!["3.10.1"]);
+        assert_eq!(found(&search, "4.16.6").await.unwrap(), vec!["4.16.6"]);
+
+        let err = found(&search, "5").await.unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "No indexed version of lodash matches '5'. Indexed versions: 4.17.21, 4.16.6, 3.10.1"
+        );
+        assert!(found(&search, "4.16.0").await.is_err());
+    }
+
+    async fn search_alpha(search: &LocalSearch) -> Result<Vec<SearchResult>> {
+        search
+            .search(
+                "alpha",
+                &SearchOptions {
+                    limit: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map(|response| response.results)
+    }
+
+    #[tokio::test]
+    async fn test_offline_search_embeds_locally() {
+        let dir = tempdir().unwrap();
+        let mut search = LocalSearch::new(dir.path()).await.unwrap();
+        let (id, ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&search, &id, &ns, "alpha_close", vector_near(0, 0.1)).await;
+
+        // Embeddings server on this machine
+        let embeddings = stub_embeddings(vector_near(0, 0.0)).await;
+
+        search.config.offline = true;
+        search.config.openai_api_key = Some("test".to_string());
+        search.config.openai_base_url = embeddings.url;
+        let results = search_alpha(&search).await.unwrap();
+        assert_eq!(results[0].name, "alpha_close");
+
+        search.config.openai_base_url = "https://api.openai.com".to_string();
+        let err = search_alpha(&search).await.unwrap_err();
+        assert!(err.to_string().contains("offline mode"));
+    }
+
+    #[tokio::test]
+    async fn test_snippet_length_and_full_docstrings() {
+        let dir = tempdir().unwrap();
+        let mut search = LocalSearch::new(dir.path()).await.unwrap();
+        let (id, ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        let docstring = "Close the connection.\n\nPending requests are rejected first.";
+        let chunk = CreateChunk {
+            signature: Some("function alphaClose(force)".to_string()),
+            docstring: Some(docstring.to_string()),
+            snippet: "function alphaClose(force)\nif (force) abort();\nreturn drain();".to_string(),
+            ..new_chunk(&id, &ns, "index.js", "alpha_close", vector_near(0, 0.1))
+        };
+        insert(&search, chunk).await;
+
+        let embeddings = stub_embeddings(vector_near(0, 0.0)).await;
+        search.config.openai_api_key = Some("test".to_string());
+        search.config.openai_base_url = embeddings.url;
+
+        async fn top(search: &LocalSearch, snippet_length: Option<usize>) -> SearchResult {
+            let results = search
+                .search(
+                    "alpha",
+                    &SearchOptions {
+                        snippet_length,
+                        limit: 5,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap()
+                .results;
+            results.into_iter().next().unwrap()
+        }
+
+        let full = "function alphaClose(force)\nif (force) abort();\nreturn drain();";
+        let result = top(&search, None).await;
+        assert_eq!(result.snippet, full);
+        assert_eq!(result.docstring.as_deref(), Some(docstring));
+
+        // Whole lines only, and the docstring stays whole
+        let result = top(&search, Some(50)).await;
+        assert_eq!(
+            result.snippet,
+            "function alphaClose(force)\nif (force) abort();"
+        );
+        assert_eq!(result.docstring.as_deref(), Some(docstring));
+        assert_eq!(top(&search, Some(10)).await.snippet, "function a");
+
+        // The configured length applies when a search doesn't ask
+        search.config.snippet_length = 30;
+        assert_eq!(
+            top(&search, None).await.snippet,
+            "function alphaClose(force)"
+        );
+        assert_eq!(top(&search, Some(500)).await.snippet, full);
+    }
+
+    #[tokio::test]
+    async fn test_bench_measures_latency_and_recall() {
+        let dir = tempdir().unwrap();
+        let mut search = LocalSearch::new(dir.path()).await.unwrap();
+        let (id, ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&search, &id, &ns, "alpha_close", vector_near(0, 0.1)).await;
+        let method = CreateChunk {
+            parent: Some("Socket".to_string()),
+            ..new_chunk(&id, &ns, "socket.js", "open", vector_near(0, 0.3))
+        };
+        insert(&search, method).await;
+        insert_chunk(&search, &id, &ns, "encode", vector_near(2, 0.0)).await;
+
+        // Every query embeds next to alpha_close
+        let embeddings = stub_embeddings(vector_near(0, 0.0)).await;
+        search.config.openai_api_key = Some("test".to_string());
+        search.config.openai_base_url = embeddings.url.clone();
+
+        let query = |text: &str, expected: &[&str]| BenchQuery {
+            query: text.to_string(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+        };
+        let queries = [
+            query("shut down", &["alpha_close"]),
+            query("start listening", &["Socket.open"]),
+            query("serialize", &["decode"]),
+            query("anything", &[]),
+        ];
+        let report = search
+            .bench(
+                &queries,
+                &SearchOptions {
+                    limit: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!((report.k, report.queries, report.labeled), (3, 4, 3));
+        let ranks: Vec<_> = report.outcomes.iter().map(|o| o.rank).collect();
+        assert_eq!(ranks, [Some(1), Some(2), None, None]);
+        assert_eq!(
+            report.outcomes[0].results,
+            ["alpha_close", "Socket.open", "encode"]
+        );
+        assert!((report.recall_at_k.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((report.mrr.unwrap() - 0.5).abs() < 1e-9);
+        assert!(report.latency.p50_ms > 0.0 && report.latency.p50_ms <= report.latency.p95_ms);
+        assert!(report.embedding.p50_ms > 0.0 && report.search.p50_ms > 0.0);
+        let outcome = &report.outcomes[0];
+        assert_eq!(outcome.latency_ms, outcome.embed_ms + outcome.search_ms);
+        // Every query is embedded, none served from the query cache
+        assert_eq!(embeddings.embedded(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_search_skips_embedding() {
+        let dir = tempdir().unwrap();
+        let mut search = LocalSearch::new(dir.path())
+            .await
+            .unwrap()
+            .with_explain(true);
+        let (id, ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&search, &id, &ns, "alpha_close", vector_near(0, 0.2)).await;
+
+        let embeddings = stub_embeddings(vector_near(0, 0.0)).await;
+        search.config.openai_api_key = Some("test".to_string());
+        search.config.openai_base_url = embeddings.url.clone();
+
+        let first = search_alpha(&search).await.unwrap();
+        assert!(!first[0].explain.as_ref().unwrap().cache_hit);
+        let second = search_alpha(&search).await.unwrap();
+        assert_eq!(embeddings.embedded(), 1);
+        assert_eq!(second[0].name, "alpha_close");
+        assert_eq!(second[0].score, first[0].score);
+        assert!(second[0].explain.as_ref().unwrap().cache_hit);
+
+        // A write re-ranks, still without embedding the query again
+        insert_chunk(&search, &id, &ns, "alpha_closer", vector_near(0, 0.1)).await;
+        let third = search_alpha(&search).await.unwrap();
+        assert_eq!(embeddings.embedded(), 1);
+        assert_eq!(third[0].name, "alpha_closer");
+        assert!(!third[0].explain.as_ref().unwrap().cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_shared_index_results_are_merged() {
+        let project_dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        let project = LocalSearch::new(project_dir.path()).await.unwrap();
+        let global = LocalSearch::new(global_dir.path()).await.unwrap();
+
+        // Indexed in both: only the project's copy is searched
+        let (id, ns) = create_version(&project, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&project, &id, &ns, "alpha_project", vector_near(0, 0.2)).await;
+        let (id, ns) = create_version(&global, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&global, &id, &ns, "alpha_global", vector_near(0, 0.0)).await;
+
+        // Only in the global index
+        let (id, ns) = create_version(&global, "pypi", "beta", "2.0.0").await;
+        insert_chunk(&global, &id, &ns, "beta_close", vector_near(0, 0.1)).await;
+        insert_chunk(&global, &id, &ns, "beta_far", vector_near(10, 0.0)).await;
+        let key = global
+            .storage
+            .put("pypi/beta/2.0.0", b"def beta(): ...")
+            .await
+            .unwrap();
+
+        let search = project.with_shared(Some(global));
+        let query = vector_near(0, 0.0);
+
+        let results = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["beta_close", "alpha_project"]);
+
+        let pypi_only = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    registry: Some("pypi"),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = pypi_only.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["beta_close", "beta_far"]);
+
+        // Chunks and code of global hits are read from the global index
+        let chunk = search.get_chunk("beta_close").await.unwrap().unwrap();
+        assert_eq!(chunk.package_name, "beta");
+        assert_eq!(search.get_code(&key).await.unwrap(), "def beta(): ...");
+        assert_eq!(
+            search.get_codes(&[&key, "pypi/beta/1.0.0/missing"]).await,
+            vec![Some("def beta(): ...".to_string()), None]
+        );
+        assert!(search.get_chunk("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_preferred_package_outranks_equal_hit() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path())
+            .await
+            .unwrap()
+            .with_explain(true);
+
+        let (alpha_id, alpha_ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        let (beta_id, beta_ns) = create_version(&search, "npm", "beta", "1.0.0").await;
+        insert_chunk(
+            &search,
+            &alpha_id,
+            &alpha_ns,
+            "alpha_fn",
+            vector_near(0, 0.5),
+        )
+        .await;
+        insert_chunk(&search, &beta_id, &beta_ns, "beta_fn", vector_near(0, 0.5)).await;
+
+        let query = vector_near(0, 0.0);
+        for preferred in ["npm:alpha", "npm:beta"] {
+            let results = search
+                .search_vector(
+                    &query,
+                    &[],
+                    &SearchOptions {
+                        preferred: PreferredPackages::parse(&[preferred]).unwrap(),
+                        limit: 2,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(format!("npm:{}", results[0].package), preferred);
+            assert!(results[0].score > results[1].score);
+
+            let boost = results[0].explain.as_ref().unwrap().preference_adjustment;
+            assert!(boost.unwrap() > 0.0);
+            assert_eq!(
+                results[1].explain.as_ref().unwrap().preference_adjustment,
+                None
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preferred_package_hit_beyond_limit() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (alpha_id, alpha_ns) = create_version(&search, "npm", "alpha", "1.0.0").await;
+        let (beta_id, beta_ns) = create_version(&search, "pypi", "beta", "1.0.0").await;
+        insert_chunk(
+            &search,
+            &alpha_id,
+            &alpha_ns,
+            "alpha_fn",
+            vector_near(0, 0.55),
+        )
+        .await;
+        insert_chunk(&search, &beta_id, &beta_ns, "beta_fn", vector_near(0, 0.5)).await;
+
+        let query = vector_near(0, 0.0);
+        let top = |preferred: PreferredPackages| {
+            let search = &search;
+            let query = &query;
+            async move {
+                search
+                    .search_vector(
+                        query,
+                        &[],
+                        &SearchOptions {
+                            preferred: preferred.clone(),
+                            limit: 1,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .unwrap()
+                    .remove(0)
+                    .name
+            }
+        };
+
+        assert_eq!(top(PreferredPackages::default()).await, "beta_fn");
+        // Slightly farther, but boosted past beta
+        let alpha = PreferredPackages::new([("npm".to_string(), "alpha".to_string())]);
+        assert_eq!(top(alpha).await, "alpha_fn");
+    }
+
+    #[test]
+    fn test_preferred_packages_parse() {
+        let preferred =
+            PreferredPackages::parse(&["npm:@scope/pkg", "maven:com.google.guava:guava"]).unwrap();
+        assert!(preferred.contains("npm", "@scope/pkg"));
+        assert!(preferred.contains_namespace("npm/@scope/pkg/1.0.0"));
+        assert!(preferred.contains_namespace("maven/com.google.guava:guava/33.0.0"));
+        assert!(!preferred.contains_namespace("npm/other/1.0.0"));
+        assert!(PreferredPackages::parse(&["axios"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_index_respects_filter() {
+        let project_dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        let project = LocalSearch::new(project_dir.path()).await.unwrap();
+        let global = LocalSearch::new(global_dir.path()).await.unwrap();
+
+        let (id, ns) = create_version(&project, "npm", "alpha", "1.0.0").await;
+        insert_chunk(&project, &id, &ns, "alpha_api", vector_near(0, 0.3)).await;
+        let (id, ns) = create_version(&global, "npm", "beta", "1.0.0").await;
+        insert(
+            &global,
+            CreateChunk {
+                visibility: "private".to_string(),
+                ..new_chunk(&id, &ns, "index.js", "beta_helper", vector_near(0, 0.0))
+            },
+        )
+        .await;
+        insert_chunk(&global, &id, &ns, "beta_api", vector_near(0, 0.1)).await;
+
+        let search = project.with_shared(Some(global));
+        let results = search
+            .search_vector(
+                &vector_near(0, 0.0),
+                &[],
+                &SearchOptions {
+                    filter: ChunkFilter::default().with_public_only(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["beta_api", "alpha_api"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_words_match_identifier() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "client", "1.0.0").await;
+
+        // The embedding alone prefers `fetchAll`
+        insert_chunk(&search, &version_id, &ns, "fetchAll", vector_near(0, 0.1)).await;
+        insert_chunk(
+            &search,
+            &version_id,
+            &ns,
+            "searchTimeout",
+            vector_near(0, 0.3),
+        )
+        .await;
+
+        let query = vector_near(0, 0.0);
+
+        let vector_only = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(vector_only[0].name, "fetchAll");
+
+        let results = search
+            .search_vector(
+                &query,
+                &tokenize("search timeout"),
+                &SearchOptions {
+                    limit: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "searchTimeout");
+        assert!(results[0].score <= 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_explain_breaks_down_score() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path())
+            .await
+            .unwrap()
+            .with_explain(true);
+
+        let (version_id, ns) = create_version(&search, "npm", "client", "1.0.0").await;
+        let code = "function searchTimeout(ms) {\n  return ms;\n}";
+        let storage_key = search
+            .storage
+            .put("npm/client/1.0.0", code.as_bytes())
+            .await
+            .unwrap();
+        insert(
+            &search,
+            CreateChunk {
+                signature: Some("searchTimeout(ms)".to_string()),
+                docstring: Some("Abort searches after `ms`.".to_string()),
+                storage_key,
+                deprecated: true,
+                ..new_chunk(
+                    &version_id,
+                    &ns,
+                    "index.js",
+                    "searchTimeout",
+                    vector_near(0, 0.3),
+                )
+            },
+        )
+        .await;
+
+        let query = vector_near(0, 0.0);
+        let results = search
+            .search_vector(
+                &query,
+                &tokenize("search retries"),
+                &SearchOptions {
+                    limit: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let explain = results[0].explain.as_ref().unwrap();
+        assert_eq!(explain.metric, DistanceMetric::L2);
+        assert_eq!(explain.distance, squared_l2(&query, &vector_near(0, 0.3)));
+        assert!((results[0].distance - explain.distance).abs() < 1e-4);
+        assert!((explain.vector_score - 1.0 / (1.0 + explain.distance)).abs() < 1e-4);
+        assert!(explain.cosine > 0.9);
+        assert_eq!(explain.keyword_matches, vec!["search"]);
+        assert_eq!(explain.deprecation_weight, Some(DEPRECATED_WEIGHT));
+        let rebuilt = (explain.vector_score + explain.keyword_adjustment) * DEPRECATED_WEIGHT;
+        assert!((results[0].score - rebuilt).abs() < 1e-4);
+        assert_eq!(
+            explain.embedded_text,
+            "Abort searches after `ms`.\n\nsearchTimeout(ms)\n\n".to_string() + code
+        );
+
+        // Explanations stay out of JSON unless requested
+        let mut plain = results[0].clone();
+        plain.explain = None;
+        assert!(
+            serde_json::to_value(&plain)
+                .unwrap()
+                .get("explain")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doc_embeddings_favor_natural_language_queries() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path())
+            .await
+            .unwrap()
+            .with_explain(true);
+        assert_eq!(search.query_mode(None), QueryMode::Code);
+
+        let (version_id, ns) = create_version(&search, "npm", "http", "1.0.0").await;
+        // Its code reads nothing like the question, but its documentation
+        // answers it
+        insert(
+            &search,
+            CreateChunk {
+                signature: Some("retry(fn, attempts)".to_string()),
+                docstring: Some("Retry a failed request with backoff.".to_string()),
+                doc_hash: Some("retry-doc".to_string()),
+                doc_vector: Some(vector_near(0, 0.05)),
+                ..new_chunk(&version_id, &ns, "index.js", "retry", vector_near(5, 0.0))
+            },
+        )
+        .await;
+        // Undocumented, with code somewhat like the question
+        insert_chunk(&search, &version_id, &ns, "loop", vector_near(0, 0.4)).await;
+
+        // The query embedding of "how do I retry a failed request?"
+        let query = vector_near(0, 0.0);
+        async fn ranked(
+            search: &LocalSearch,
+            query: &[f32],
+            mode: Option<QueryMode>,
+        ) -> Vec<SearchResult> {
+            search
+                .search_vector(
+                    query,
+                    &[],
+                    &SearchOptions {
+                        mode,
+                        limit: 2,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap()
+        }
+        let names =
+            |results: &[SearchResult]| results.iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+
+        assert_eq!(
+            names(&ranked(&search, &query, None).await),
+            vec!["loop", "retry"]
+        );
+
+        let results = ranked(&search, &query, Some(QueryMode::Docs)).await;
+        assert_eq!(names(&results), vec!["retry", "loop"]);
+        let explain = results[0].explain.as_ref().unwrap();
+        assert_eq!(explain.mode, QueryMode::Docs);
+        assert!(explain.matched_docs);
+        assert_eq!(explain.distance, squared_l2(&query, &vector_near(0, 0.05)));
+        assert_eq!(
+            explain.embedded_text,
+            "Retry a failed request with backoff.\n\nretry(fn, attempts)"
+        );
+        // Chunks without a documentation embedding fall back to their code
+        assert!(!results[1].explain.as_ref().unwrap().matched_docs);
+        assert_eq!(
+            results[1].distance,
+            squared_l2(&query, &vector_near(0, 0.4))
+        );
+
+        assert_eq!(
+            names(&ranked(&search, &query, Some(QueryMode::Fused)).await),
+            vec!["retry", "loop"]
+        );
+    }
+
+    #[test]
+    fn test_fence_tag() {
+        assert_eq!(fence_tag("src/lib.rs", None).as_deref(), Some("rust"));
+        // Code blocks in docs are tagged with their own language
+        assert_eq!(fence_tag("README.md", Some("```js")).as_deref(), Some("js"));
+        assert_eq!(
+            fence_tag("docs/guide.md", Some("```rust,ignore")).as_deref(),
+            Some("rust")
+        );
+        assert_eq!(fence_tag("README.md", Some("```")), None);
+        // Prose sections stay Markdown
+        assert_eq!(fence_tag("README.md", None).as_deref(), Some("markdown"));
+        assert_eq!(fence_tag("data.bin", None), None);
+    }
+
+    #[tokio::test]
+    async fn test_min_score_rejects_irrelevant_query() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "http", "1.0.0").await;
+        insert_chunk(&search, &version_id, &ns, "request", vector_near(0, 0.0)).await;
+        insert_chunk(&search, &version_id, &ns, "retry", vector_near(0, 0.5)).await;
+
+        // Orthogonal to every chunk, like a query about something else entirely
+        let off_topic = search
+            .search_vector(
+                &vector_near(10, 0.0),
+                &[],
+                &SearchOptions {
+                    limit: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(off_topic.len(), 2);
+
+        let response = apply_min_score(off_topic.clone(), 0.6);
+        assert!(response.results.is_empty());
+        let low = response.no_confident_matches.unwrap();
+        assert_eq!(low.best, off_topic[0].score);
+        assert_eq!(low.threshold, 0.6);
+
+        // 0 keeps the old behaviour
+        let response = apply_min_score(off_topic, 0.0);
+        assert_eq!(response.results.len(), 2);
+        assert!(response.no_confident_matches.is_none());
+
+        // On-topic: weak hits are dropped, strong ones kept
+        let on_topic = search
+            .search_vector(
+                &vector_near(0, 0.0),
+                &[],
+                &SearchOptions {
+                    limit: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let kept = apply_min_score(on_topic, 0.9).results;
+        let names: Vec<_> = kept.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["request"]);
+    }
+
+    #[tokio::test]
+    async fn test_path_filter_keeps_limit() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "crates", "syn", "2.0.0").await;
+
+        // Chunks in src/lib.rs are all closer to the query than src/parser/expr.rs
+        for i in 0..5 {
+            let name = format!("lib_{}", i);
+            let vector = vector_near(0, 0.01 * i as f32);
+            insert_chunk_in(
+                &search,
+                &version_id,
+                &ns,
+                "src/lib.rs",
+                &name,
+                vector,
+                false,
+            )
+            .await;
+        }
+        for i in 0..3 {
+            let name = format!("parse_{}", i);
+            let vector = vector_near(0, 0.5 + 0.1 * i as f32);
+            insert_chunk_in(
+                &search,
+                &version_id,
+                &ns,
+                "src/parser/expr.rs",
+                &name,
+                vector,
+                false,
+            )
+            .await;
+        }
+
+        let query = vector_near(0, 0.0);
+        let prefix = ChunkFilter::new(Some("src/parser/"), TestFilter::Include);
+        let results = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    filter: prefix.clone(),
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["parse_0", "parse_1"]);
+
+        let glob = ChunkFilter::new(Some("*/lib.rs"), TestFilter::Include);
+        let results = search
+            .search_vector(
+                &query,
+                &tokenize("parse"),
+                &SearchOptions {
+                    filter: glob.clone(),
+                    limit: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.file_path == "src/lib.rs"));
+
+        let none = ChunkFilter::new(Some("tests/"), TestFilter::Include);
+        assert!(
+            search
+                .search_vector(
+                    &query,
+                    &[],
+                    &SearchOptions {
+                        filter: none.clone(),
+                        limit: 5,
+                        ..Default::default()
+                    }
+                )
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_test_filter() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "zod", "3.0.0").await;
+
+        // The test chunk is the closest match
+        insert(
+            &search,
+            CreateChunk {
+                chunk_type: "example".to_string(),
+                ..new_chunk(
+                    &version_id,
+                    &ns,
+                    "tests/parse.test.ts",
+                    "parsesNested",
+                    vector_near(0, 0.0),
+                )
+            },
+        )
+        .await;
+        for i in 0..3 {
+            let name = format!("parse_{}", i);
+            let vector = vector_near(0, 0.1 + 0.1 * i as f32);
+            insert_chunk(&search, &version_id, &ns, &name, vector).await;
+        }
+
+        let query = vector_near(0, 0.0);
+        let names = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.name).collect()
+        };
+
+        let included = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(included), vec!["parsesNested", "parse_0"]);
+
+        let excluded = ChunkFilter::new(None, TestFilter::Exclude);
+        let results = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    filter: excluded.clone(),
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(results), vec!["parse_0", "parse_1"]);
+
+        let only = ChunkFilter::new(None, TestFilter::Only);
+        let results = search
+            .search_vector(
+                &query,
+                &tokenize("parse"),
+                &SearchOptions {
+                    filter: only.clone(),
+                    limit: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(results), vec!["parsesNested"]);
+    }
+
+    #[tokio::test]
+    async fn test_kind_filter() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "got", "14.0.0").await;
+        for (i, (chunk_type, file_path)) in [
+            ("documentation", "README.md"),
+            ("example", "test/retry.ts"),
+            ("function", "source/retry.ts"),
+            ("class", "source/client.ts"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let name = format!("retry_{}", chunk_type);
+            insert(
+                &search,
+                CreateChunk {
+                    chunk_type: chunk_type.to_string(),
+                    ..new_chunk(
+                        &version_id,
+                        &ns,
+                        file_path,
+                        &name,
+                        vector_near(0, 0.1 * i as f32),
+                    )
+                },
+            )
+            .await;
+        }
+
+        let query = vector_near(0, 0.0);
+        for (kind, expected) in [
+            (
+                KindFilter::All,
+                vec!["documentation", "example", "function", "class"],
+            ),
+            (KindFilter::Docs, vec!["documentation"]),
+            (KindFilter::Code, vec!["example", "function", "class"]),
+        ] {
+            let results = search
+                .search_vector(
+                    &query,
+                    &tokenize("retry"),
+                    &SearchOptions {
+                        filter: ChunkFilter::default().with_kind(kind),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            let types: Vec<_> = results.iter().map(|r| r.chunk_type.as_str()).collect();
+            assert_eq!(types, expected, "{:?}", kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_type_filter() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "crates", "hyper", "1.0.0").await;
+        let typed = |name: &str| Some(name.to_string());
+        for (i, (name, params, returns)) in [
+            (
+                "serve",
+                vec![("req".to_string(), typed("Request<Body>"))],
+                typed("Response<Body>"),
+            ),
+            (
+                "parse",
+                vec![("buf".to_string(), typed("&[u8]"))],
+                typed("Request<Body>"),
+            ),
+            ("reset", vec![], None),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            insert(
+                &search,
+                CreateChunk {
+                    params,
+                    returns,
+                    ..new_chunk(
+                        &version_id,
+                        &ns,
+                        "src/server.rs",
+                        name,
+                        vector_near(0, 0.1 * i as f32),
+                    )
+                },
+            )
+            .await;
+        }
+
+        let query = vector_near(0, 0.0);
+        for (param_type, return_type, expected) in [
+            (Some("Request"), None, vec!["serve"]),
+            (None, Some("Request"), vec!["parse"]),
+            (Some("Request"), Some("Response"), vec!["serve"]),
+            (Some("u8"), Some("Response"), vec![]),
+        ] {
+            let results = search
+                .search_vector(
+                    &query,
+                    &[],
+                    &SearchOptions {
+                        filter: ChunkFilter::default()
+                            .with_signature_types(param_type, return_type),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, expected, "{:?} {:?}", param_type, return_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scope_filter() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (public_id, public_ns) = create_version(&search, "npm", "utils", "1.0.0").await;
+        insert_chunk(
+            &search,
+            &public_id,
+            &public_ns,
+            "public_retry",
+            vector_near(0, 0.0),
+        )
+        .await;
+
+        // A tenant's internal package of the same name
+        let tenant = crate::types::TenantId::new_v4();
+        let package_id = search
+            .db
+            .get_or_create_package(&CreatePackage {
+                registry: "npm".to_string(),
+                name: "utils".to_string(),
+                description: None,
+                tenant: Some(tenant),
+            })
+            .await
+            .unwrap();
+        let (private_id, _) = search
+            .db
+            .get_or_create_version(&package_id, "1.0.0")
+            .await
+            .unwrap();
+        let private_ns = NamespaceSpec::new("npm", "utils", "1.0.0")
+            .with_tenant(Some(tenant))
+            .to_string();
+        insert_chunk(
+            &search,
+            &private_id,
+            &private_ns,
+            "private_retry",
+            vector_near(0, 0.1),
+        )
+        .await;
+
+        let query = vector_near(0, 0.0);
+        for (scope, expected) in [
+            (None, vec!["public_retry", "private_retry"]),
+            (
+                Some(SearchScope::All),
+                vec!["public_retry", "private_retry"],
+            ),
+            (Some(SearchScope::Public), vec!["public_retry"]),
+            (Some(SearchScope::Private), vec!["private_retry"]),
+        ] {
+            let results = search
+                .search_vector(
+                    &query,
+                    &tokenize("retry"),
+                    &SearchOptions {
+                        package: Some("utils"),
+                        registry: Some("npm"),
+                        version: Some("1.0.0"),
+                        filter: ChunkFilter::default().with_scope(scope),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, expected, "{:?}", scope);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_only_filter() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "crates", "serde", "1.0.0").await;
+
+        // Private and crate-internal helpers are the closest matches
+        for (i, (name, visibility)) in [
+            ("parse_inner", "private"),
+            ("parse_crate", "internal"),
+            ("parse", "public"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let chunk = CreateChunk {
+                visibility: visibility.to_string(),
+                ..new_chunk(
+                    &version_id,
+                    &ns,
+                    "src/de.rs",
+                    name,
+                    vector_near(0, 0.1 * i as f32),
+                )
+            };
+            insert(&search, chunk).await;
+        }
+
+        let query = vector_near(0, 0.0);
+        let names = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.name).collect()
+        };
+
+        let public = ChunkFilter::default().with_public_only(true);
+        let results = search
+            .search_vector(
+                &query,
+                &tokenize("parse"),
+                &SearchOptions {
+                    filter: public.clone(),
+                    limit: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(results), vec!["parse"]);
+
+        let all = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(all), vec!["parse_inner", "parse_crate", "parse"]);
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_symbols_rank_lower() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "http", "1.0.0").await;
+
+        // The deprecated chunk is slightly closer to the query
+        let old = vector_near(0, 0.1);
+        let new = vector_near(0, 0.2);
+        insert_chunk_in(&search, &version_id, &ns, "index.js", "get", old, true).await;
+        insert_chunk_in(&search, &version_id, &ns, "index.js", "request", new, false).await;
+
+        let query = vector_near(0, 0.0);
+        let results = search
+            .search_vector(
+                &query,
+                &[],
+                &SearchOptions {
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["request", "get"]);
+        assert!(results[1].deprecated);
+        assert!(!results[0].deprecated);
+    }
+
+    #[tokio::test]
+    async fn test_cosine_metric_scores_results() {
+        let dir = tempdir().unwrap();
+        let mut search = LocalSearch::new(dir.path()).await.unwrap();
+        search.config.distance_metric = DistanceMetric::Cosine;
+        search.vectors = VectorStore::open(&dir.path().join("vectors"), &search.config)
+            .await
+            .unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "angles", "1.0.0").await;
+        insert_chunk(&search, &version_id, &ns, "near", vector_near(0, 0.1)).await;
+        insert_chunk(&search, &version_id, &ns, "far", vector_near(0, 0.8)).await;
+        insert_chunk(&search, &version_id, &ns, "unrelated", vector_near(10, 0.0)).await;
+
+        let query = vector_near(0, 0.0);
+        // Both the vector store and the filtered scan of stored embeddings
+        for filter in [
+            ChunkFilter::default(),
+            ChunkFilter::new(Some("index.js"), TestFilter::Include),
+        ] {
+            let results = search
+                .search_vector(
+                    &query,
+                    &[],
+                    &SearchOptions {
+                        filter: filter.clone(),
+                        limit: 3,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+
+            let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, vec!["near", "far", "unrelated"]);
+            for (result, jitter) in results.iter().zip([0.1, 0.8]) {
+                let cosine = cosine_similarity(&query, &vector_near(0, jitter));
+                assert!((result.distance - (1.0 - cosine)).abs() < 1e-4);
+                assert!((result.score - cosine).abs() < 1e-4);
+            }
+            assert!(results[2].score < 1e-4);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_group_same_named_methods_by_parent() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (version_id, ns) = create_version(&search, "npm", "shop", "1.0.0").await;
+        let method = |parent: &str, file_path: &str, name: &str, offset: f32| CreateChunk {
+            id: format!("{}.{}", parent, name),
+            chunk_type: "method".to_string(),
+            parent: Some(parent.to_string()),
+            ..new_chunk(&version_id, &ns, file_path, name, vector_near(0, offset))
+        };
+        for chunk in [
+            method("Cart", "cart.js", "get", 0.1),
+            method("Order", "order.js", "get", 0.2),
+            method("Cart", "cart.js", "set", 0.3),
+            method("User", "user.js", "get", 0.4),
+            method("Cart", "cart.js", "build", 0.5),
+        ] {
+            insert(&search, chunk).await;
+        }
+
+        let query = vector_near(0, 0.0);
+        let results = search
+            .search_vector(&query, &[], &SearchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].parent.as_deref(), Some("Cart"));
+
+        let grouped = group_by_parent(results);
+        let entries: Vec<_> = grouped
+            .iter()
+            .map(|entry| match entry {
+                GroupedResult::Group(group) => {
+                    let names: Vec<_> = group.results.iter().map(|r| r.name.as_str()).collect();
+                    format!("{}: {}", group.parent, names.join(", "))
+                }
+                GroupedResult::Single(r) => format!("{}.{}", r.parent.as_deref().unwrap(), r.name),
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec!["Cart: get, set, build", "Order.get", "User.get"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outline_covers_package_symbols() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (old_id, old_ns) = create_version(&search, "crates", "serde", "1.0.0").await;
+        let (new_id, new_ns) = create_version(&search, "crates", "serde", "1.10.0").await;
+        let (other_id, other_ns) = create_version(&search, "crates", "toml", "0.8.0").await;
+        insert_chunk(&search, &old_id, &old_ns, "old_api", vector_near(0, 0.0)).await;
+        insert_chunk(
+            &search,
+            &other_id,
+            &other_ns,
+            "from_str",
+            vector_near(0, 0.0),
+        )
+        .await;
+
+        // Inserted out of order; the outline sorts by file and line
+        for (file, name, line, visibility) in [
+            ("src/ser.rs", "Serializer", 20, "public"),
+            ("src/de.rs", "deserialize_inner", 40, "private"),
+            ("src/lib.rs", "Error", 5, "public"),
+            ("src/de.rs", "Deserializer", 10, "public"),
+            ("src/ser.rs", "serialize_seq", 2, "public"),
+        ] {
+            let chunk = CreateChunk {
+                start_line: line,
+                end_line: line + 5,
+                visibility: visibility.to_string(),
+                signature: Some(format!("fn {}()", name)),
+                ..new_chunk(&new_id, &new_ns, file, name, vector_near(0, 0.0))
+            };
+            insert(&search, chunk).await;
+        }
+
+        let symbols = |outline: &PackageOutline| -> Vec<(String, String)> {
+            outline
+                .files
+                .iter()
+                .flat_map(|f| f.symbols.iter().map(|s| (f.path.clone(), s.name.clone())))
+                .collect()
+        };
+
+        let outline = search
+            .outline("crates", "serde", None, &ChunkFilter::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(outline.version, "1.10.0");
+        assert_eq!(outline.symbol_count(), 5);
+        assert_eq!(
+            symbols(&outline),
+            [
+                ("src/de.rs", "Deserializer"),
+                ("src/de.rs", "deserialize_inner"),
+                ("src/lib.rs", "Error"),
+                ("src/ser.rs", "serialize_seq"),
+                ("src/ser.rs", "Serializer"),
+            ]
+            .map(|(f, n)| (f.to_string(), n.to_string()))
+        );
+        let first = &outline.files[0].symbols[0];
+        assert_eq!((first.start_line, first.end_line), (10, 15));
+        assert_eq!(first.signature.as_deref(), Some("fn Deserializer()"));
+
+        let public_de =
+            ChunkFilter::new(Some("src/de"), TestFilter::Include).with_public_only(true);
+        let outline = search
+            .outline("crates", "serde", Some("1.10.0"), &public_de)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            symbols(&outline),
+            vec![("src/de.rs".to_string(), "Deserializer".to_string())]
+        );
+
+        let old = search
+            .outline("crates", "serde", Some("1.0.0"), &ChunkFilter::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.symbol_count(), 1);
+
+        assert!(
+            search
+                .outline("npm", "serde", None, &ChunkFilter::default())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_point_leads_outline() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (old_id, old_ns) = create_version(&search, "npm", "zod", "3.21.0").await;
+        let (new_id, new_ns) = create_version(&search, "npm", "zod", "3.22.4").await;
+        insert_chunk(&search, &old_id, &old_ns, "old_parse", vector_near(0, 0.0)).await;
+        for (file, name) in [
+            ("src/errors.ts", "ZodError"),
+            ("src/index.ts", "z"),
+            ("src/types.ts", "ZodString"),
+        ] {
+            insert(
+                &search,
+                new_chunk(&new_id, &new_ns, file, name, vector_near(0, 0.0)),
+            )
+            .await;
+        }
+        search
+            .db
+            .set_version_entry_point(&new_id, Some("src/index.ts"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            search.entry_point("npm", "zod", None).await.unwrap(),
+            Some("src/index.ts".to_string())
+        );
+        // Indexed before entry points were recorded
+        assert_eq!(
+            search
+                .entry_point("npm", "zod", Some("3.21.0"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(search.entry_point("pypi", "zod", None).await.unwrap(), None);
+
+        let outline = search
+            .outline("npm", "zod", None, &ChunkFilter::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(outline.entry_point.as_deref(), Some("src/index.ts"));
+        let files: Vec<_> = outline
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.entry))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("src/index.ts", true),
+                ("src/errors.ts", false),
+                ("src/types.ts", false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trait_implementations() {
+        use crate::indexer::TraitImpl;
+
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (old_id, old_ns) = create_version(&search, "crates", "counters", "0.1.0").await;
+        let (new_id, new_ns) = create_version(&search, "crates", "counters", "0.2.0").await;
+        let (other_id, other_ns) = create_version(&search, "crates", "ranges", "1.0.0").await;
+
+        let edge = |trait_name: &str, type_name: &str| TraitImpl {
+            trait_name: trait_name.to_string(),
+            type_name: type_name.to_string(),
+            signature: format!("impl {} for {}", trait_name, type_name),
+            file_path: "src/lib.rs".to_string(),
+            line: 10,
+        };
+        for (version_id, namespace, impls) in [
+            (&old_id, &old_ns, vec![edge("Iterator", "OldCounter")]),
+            (
+                &new_id,
+                &new_ns,
+                vec![edge("Iterator", "Counter"), edge("Clone", "Counter")],
+            ),
+            (&other_id, &other_ns, vec![edge("Iterator", "Range")]),
+        ] {
+            // Chunk ids are their names, so each version gets its own
+            let name = format!("lib_{}", version_id);
+            insert_chunk(&search, version_id, namespace, &name, vector_near(0, 0.0)).await;
+            search
+                .db
+                .replace_impls(version_id, namespace, &impls)
+                .await
+                .unwrap();
+        }
+        let next = CreateChunk {
+            id: "next".to_string(),
+            chunk_type: "method".to_string(),
+            parent: Some("Counter<T>".to_string()),
+            signature: Some("fn next(&mut self) -> Option<T>".to_string()),
+            ..new_chunk(&new_id, &new_ns, "src/lib.rs", "next", vector_near(0, 0.0))
+        };
+        insert(&search, next).await;
+
+        let implementors = |impls: Vec<ImplWithPackage>| -> Vec<(String, String)> {
+            impls
+                .into_iter()
+                .map(|i| (i.package_name, i.type_name))
+                .collect()
+        };
+
+        // Only the newest version of each package is consulted
+        let iterators = search
+            .implementations_of("Iterator", None, None)
+            .await
+            .unwrap();
+        assert_eq!(iterators[0].signature, "impl Iterator for Counter");
+        assert_eq!(iterators[0].version, "0.2.0");
+        assert_eq!(
+            implementors(iterators),
+            [("counters", "Counter"), ("ranges", "Range")]
+                .map(|(p, t)| (p.to_string(), t.to_string()))
+        );
+        let ranges = search
+            .implementations_of("Iterator", Some("crates"), Some("ranges"))
+            .await
+            .unwrap();
+        assert_eq!(implementors(ranges).len(), 1);
+
+        let traits: Vec<_> = search
+            .traits_implemented_by("Counter", None, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|i| i.trait_name)
+            .collect();
+        assert_eq!(traits, vec!["Clone", "Iterator"]);
+
+        let methods = search.method_set("Counter", None, None).await.unwrap();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].name, "next");
+        assert!(
+            search
+                .method_set("Count", None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Re-indexing replaces a version's implementations
+        search
+            .db
+            .replace_impls(&new_id, &new_ns, &[])
+            .await
+            .unwrap();
+        assert!(
+            search
+                .traits_implemented_by("Counter", None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // A package indexed in the global index too is only reported once
+        let global_dir = tempdir().unwrap();
+        let global = LocalSearch::new(global_dir.path()).await.unwrap();
+        let (id, ns) = create_version(&global, "crates", "ranges", "1.0.0").await;
+        insert_chunk(&global, &id, &ns, "lib_global", vector_near(0, 0.0)).await;
+        global
+            .db
+            .replace_impls(&id, &ns, &[edge("Iterator", "Range")])
+            .await
+            .unwrap();
+        let search = search.with_shared(Some(global));
+        let ranges = search
+            .implementations_of("Iterator", Some("crates"), Some("ranges"))
+            .await
+            .unwrap();
+        assert_eq!(implementors(ranges).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_diff_reports_added_and_changed_symbols() {
+        let dir = tempdir().unwrap();
+        let search = LocalSearch::new(dir.path()).await.unwrap();
+
+        let (old_id, old_ns) = create_version(&search, "npm", "fetcher", "1.0.0").await;
+        let (new_id, new_ns) = create_version(&search, "npm", "fetcher", "1.1.0").await;
+        let symbol = |version_id: &str, namespace: &str, name: &str, signature: &str| CreateChunk {
+            id: format!("{}/{}", namespace, name),
+            signature: Some(signature.to_string()),
+            parent: Some("Client".to_string()),
+            ..new_chunk(
+                version_id,
+                namespace,
+                "client.js",
+                name,
+                vector_near(0, 0.0),
+            )
+        };
+
+        for chunk in [
+            symbol(&old_id, &old_ns, "get", "get(url)"),
+            symbol(&old_id, &old_ns, "post", "post(url, body)"),
+        ] {
+            insert(&search, chunk).await;
+        }
+        for chunk in [
+            symbol(&new_id, &new_ns, "get", "get(url, options)"),
+            symbol(&new_id, &new_ns, "post", "post(url, body)"),
+            symbol(&new_id, &new_ns, "put", "put(url, body)"),
+        ] {
+            insert(&search, chunk).await;
+        }
+
+        let diff = search
+            .diff(
+                "npm",
+                "fetcher",
+                "1.0.0",
+                "latest",
+                &ChunkFilter::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!((diff.from.as_str(), diff.to.as_str()), ("1.0.0", "1.1.0"));
+        let added: Vec<_> = diff.added.iter().map(|s| s.qualified_name()).collect();
+        assert_eq!(added, vec!["Client.put"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        let changed = &diff.changed[0];
+        assert_eq!(changed.old.signature.as_deref(), Some("get(url)"));
+        assert_eq!(changed.new.signature.as_deref(), Some("get(url, options)"));
+
+        // Reversed, the added symbol is removed
+        let diff = search
+            .diff(
+                "npm",
+                "fetcher",
+                "1.1.0",
+                "1.0.0",
+                &ChunkFilter::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed[0].name, "put");
+
+        let missing = search
+            .diff(
+                "npm",
+                "fetcher",
+                "1.0.0",
+                "2.0.0",
+                &ChunkFilter::default(),
+                false,
+            )
+            .await;
+        assert!(missing.unwrap_err().to_string().contains("not indexed"));
+    }
+
+    #[test]
+    fn test_select_namespaces() {
+        let all = || {
+            vec![
+                "npm/@types/node/20.1.0".to_string(),
+                "npm/react/18.2.0".to_string(),
+                "pypi/react/1.0.0".to_string(),
+                "go/github.com/pkg/errors/v0.9.1".to_string(),
+            ]
+        };
+
+        assert_eq!(select_namespaces(all(), None, None, None).len(), 4);
+        assert_eq!(
+            select_namespaces(all(), Some("react"), None, None),
+            vec!["npm/react/18.2.0", "pypi/react/1.0.0"]
+        );
+        assert_eq!(
+            select_namespaces(all(), Some("@types/node"), Some("npm"), None),
+            vec!["npm/@types/node/20.1.0"]
+        );
+        assert_eq!(
+            select_namespaces(all(), None, Some("go"), Some("v0.9.1")),
+            vec!["go/github.com/pkg/errors/v0.9.1"]
+        );
+        assert!(select_namespaces(all(), Some("types"), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_select_latest_namespaces() {
+        let all = vec![
+            "npm/react/18.2.0".to_string(),
+            "npm/react/18.10.0".to_string(),
+            "npm/react/17.0.2".to_string(),
+            "pypi/react/1.0.0".to_string(),
+        ];
+
+        assert_eq!(
+            select_namespaces(all.clone(), Some("react"), Some("npm"), Some("latest")),
+            vec!["npm/react/18.10.0"]
+        );
+        assert_eq!(
+            select_namespaces(all.clone(), Some("react"), None, Some("latest")),
+            vec!["npm/react/18.10.0", "pypi/react/1.0.0"]
+        );
+        assert_eq!(
+            select_namespaces(all.clone(), Some("react"), Some("npm"), Some("18")),
+            vec!["npm/react/18.10.0"]
+        );
+        assert_eq!(
+            select_namespaces(all.clone(), Some("react"), None, Some("18.2")),
+            vec!["npm/react/18.2.0"]
+        );
+        assert!(select_namespaces(all, Some("react"), None, Some("19")).is_empty());
+    }
 }
@@ -3,16 +3,58 @@
 //! Blobs are stored per-package for easy cleanup:
 //! ```text
 //! .index/blobs/{registry}/{name}/{version}/{content_hash}
+//! .index/blobs/{registry}/{name}/{version}/files/{content_hash}
 //! ```
+//!
+//! Whole source files are only stored when `store_source_files` is enabled.
+//!
+//! Blobs are written as zstd frames and decompressed on read. A blob is
+//! recognized as compressed by the zstd frame magic, so blobs written
+//! uncompressed by earlier versions still read back as they are. Keys hash
+//! the uncompressed content, so identical content dedups at any level.
 
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use sha2::{Digest, Sha256};
 
+/// Default zstd level blobs are compressed with.
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// First bytes of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Largest zstd frame header, which holds the uncompressed size.
+const ZSTD_MAX_HEADER: usize = 18;
+
+/// Blobs [`LocalStorage::get_many`] reads at once.
+const READ_CONCURRENCY: usize = 16;
+
+/// Space taken by stored blobs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlobUsage {
+    /// Bytes on disk.
+    pub stored: u64,
+    /// Bytes once decompressed.
+    pub content: u64,
+}
+
+impl BlobUsage {
+    /// Percentage of the content size saved by compression.
+    pub fn savings_percent(&self) -> f64 {
+        if self.content == 0 {
+            return 0.0;
+        }
+        100.0 * (1.0 - self.stored as f64 / self.content as f64)
+    }
+}
+
 /// Content-addressed blob storage organized by package.
 pub struct LocalStorage {
     blobs_dir: PathBuf,
+    compression_level: i32,
 }
 
 impl LocalStorage {
@@ -22,35 +64,96 @@ impl LocalStorage {
             .await
             .context("Failed to create blobs directory")?;
 
-        Ok(Self { blobs_dir })
+        Ok(Self {
+            blobs_dir,
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+        })
+    }
+
+    /// Compress new blobs at zstd `level` (1-22), or store them
+    /// uncompressed with 0.
+    pub fn with_compression_level(mut self, level: i32) -> Self {
+        self.compression_level = level;
+        self
     }
 
-    /// Store a blob for a package, returns the storage key.
-    pub async fn put(
-        &self,
-        registry: &str,
-        name: &str,
-        version: &str,
-        content: &[u8],
-    ) -> Result<String> {
+    /// Store a blob for the package version stored under `namespace`,
+    /// returns the storage key.
+    pub async fn put(&self, namespace: &str, content: &[u8]) -> Result<String> {
         let hash = hex::encode(Sha256::digest(content));
-        let key = format!("{}/{}/{}/{}", registry, name, version, hash);
-        let path = self.blobs_dir.join(&key);
+        let key = format!("{}/{}", namespace, hash);
+        self.write_if_missing(&key, content).await?;
+
+        Ok(key)
+    }
+
+    /// Store a whole source file for a package, returns the storage key.
+    ///
+    /// Files are content-addressed under a `files/` prefix, so identical
+    /// files within a version are stored once.
+    pub async fn put_file(&self, namespace: &str, content: &[u8]) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(content));
+        let key = format!("{}/files/{}", namespace, hash);
+        self.write_if_missing(&key, content).await?;
+
+        Ok(key)
+    }
+
+    async fn write_if_missing(&self, key: &str, content: &[u8]) -> Result<()> {
+        let path = self.blobs_dir.join(key);
 
         if !path.exists() {
             if let Some(parent) = path.parent() {
                 tokio::fs::create_dir_all(parent).await?;
             }
-            tokio::fs::write(&path, content).await?;
+            tokio::fs::write(&path, self.encode(content)?).await?;
         }
 
-        Ok(key)
+        Ok(())
+    }
+
+    /// The bytes stored for `content`. Content that happens to start with
+    /// the zstd magic is compressed even at level 0, so it can't be
+    /// mistaken for a frame on read.
+    fn encode(&self, content: &[u8]) -> Result<Vec<u8>> {
+        if self.compression_level <= 0 && !content.starts_with(&ZSTD_MAGIC) {
+            return Ok(content.to_vec());
+        }
+        let level = self.compression_level.max(1);
+        zstd::bulk::compress(content, level).context("Failed to compress blob")
     }
 
     /// Get a blob by storage key.
     pub async fn get(&self, key: &str) -> Result<Vec<u8>> {
         let path = self.blobs_dir.join(key);
-        tokio::fs::read(&path).await.context("Blob not found")
+        let bytes = tokio::fs::read(&path).await.context("Blob not found")?;
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            return zstd::decode_all(bytes.as_slice())
+                .with_context(|| format!("Failed to decompress blob {}", key));
+        }
+        // Written uncompressed (by an older version or at level 0)
+        Ok(bytes)
+    }
+
+    /// Get several blobs at once, each result in the order of `keys`. A
+    /// missing blob fails only its own entry.
+    pub async fn get_many(&self, keys: &[&str]) -> Vec<Result<Vec<u8>>> {
+        let reads: Vec<_> = keys.iter().map(|key| self.get(key)).collect();
+        stream::iter(reads)
+            .buffered(READ_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Get a byte range `[start, end)` of a blob, clamped to its length.
+    ///
+    /// Reads no further than `end`: uncompressed blobs are read from
+    /// `start`, compressed ones are decoded only up to `end`.
+    pub async fn get_range(&self, key: &str, start: usize, end: usize) -> Result<Vec<u8>> {
+        let path = self.blobs_dir.join(key);
+        tokio::task::spawn_blocking(move || read_range(&path, start, end))
+            .await?
+            .with_context(|| format!("Failed to read blob {}", key))
     }
 
     /// Check if a blob exists.
@@ -67,21 +170,93 @@ impl LocalStorage {
         Ok(())
     }
 
+    /// Check if any blobs are stored for a package version.
+    pub async fn has_package(&self, namespace: &str) -> bool {
+        self.blobs_dir.join(namespace).exists()
+    }
+
     /// Delete all blobs for a package version.
-    pub async fn delete_package(&self, registry: &str, name: &str, version: &str) -> Result<()> {
-        let path = self.blobs_dir.join(registry).join(name).join(version);
+    pub async fn delete_package(&self, namespace: &str) -> Result<()> {
+        let path = self.blobs_dir.join(namespace);
         if path.exists() {
             tokio::fs::remove_dir_all(&path).await?;
         }
         Ok(())
     }
 
+    /// Measure the space blobs take on disk and decompressed. Reads only
+    /// the frame headers, which record each blob's uncompressed size.
+    pub async fn usage(&self) -> Result<BlobUsage> {
+        let blobs_dir = self.blobs_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut usage = BlobUsage::default();
+            add_usage(&blobs_dir, &mut usage)?;
+            Ok(usage)
+        })
+        .await?
+    }
+
     /// Get the content hash from a storage key.
     pub fn hash_from_key(key: &str) -> Option<&str> {
         key.rsplit('/').next()
     }
 }
 
+fn add_usage(dir: &Path, usage: &mut BlobUsage) -> Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            add_usage(&entry.path(), usage)?;
+            continue;
+        }
+
+        let mut header = Vec::with_capacity(ZSTD_MAX_HEADER);
+        std::fs::File::open(entry.path())?
+            .take(ZSTD_MAX_HEADER as u64)
+            .read_to_end(&mut header)?;
+        let content = if header.starts_with(&ZSTD_MAGIC) {
+            zstd::zstd_safe::get_frame_content_size(&header)
+                .ok()
+                .flatten()
+                .unwrap_or(metadata.len())
+        } else {
+            metadata.len()
+        };
+        usage.stored += metadata.len();
+        usage.content += content;
+    }
+    Ok(())
+}
+
+/// Read bytes `[start, end)` of the blob at `path`, decoding a zstd frame
+/// as far as needed.
+fn read_range(path: &Path, start: usize, end: usize) -> Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path).context("Blob not found")?;
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut file)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+
+    let len = end.saturating_sub(start) as u64;
+    let mut range = Vec::new();
+    if magic == ZSTD_MAGIC {
+        file.seek(SeekFrom::Start(0))?;
+        let mut decoder = zstd::Decoder::new(file)?;
+        std::io::copy(&mut (&mut decoder).take(start as u64), &mut std::io::sink())?;
+        decoder.take(len).read_to_end(&mut range)?;
+    } else {
+        file.seek(SeekFrom::Start(start as u64))?;
+        file.take(len).read_to_end(&mut range)?;
+    }
+    Ok(range)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,10 +268,7 @@ mod tests {
         let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
 
         let content = b"hello world";
-        let key = storage
-            .put("npm", "lodash", "4.17.21", content)
-            .await
-            .unwrap();
+        let key = storage.put("npm/lodash/4.17.21", content).await.unwrap();
 
         assert!(key.starts_with("npm/lodash/4.17.21/"));
         assert!(storage.exists(&key).await);
@@ -111,8 +283,8 @@ mod tests {
         let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
 
         let content = b"same content";
-        let key1 = storage.put("npm", "axios", "1.0.0", content).await.unwrap();
-        let key2 = storage.put("npm", "axios", "1.0.0", content).await.unwrap();
+        let key1 = storage.put("npm/axios/1.0.0", content).await.unwrap();
+        let key2 = storage.put("npm/axios/1.0.0", content).await.unwrap();
 
         assert_eq!(key1, key2);
     }
@@ -122,25 +294,129 @@ mod tests {
         let dir = tempdir().unwrap();
         let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
 
+        let key1 = storage.put("npm/react/18.0.0", b"chunk1").await.unwrap();
+        let key2 = storage.put("npm/react/18.0.0", b"chunk2").await.unwrap();
+
+        assert!(storage.exists(&key1).await);
+        assert!(storage.exists(&key2).await);
+
+        storage.delete_package("npm/react/18.0.0").await.unwrap();
+
+        assert!(!storage.exists(&key1).await);
+        assert!(!storage.exists(&key2).await);
+    }
+
+    #[tokio::test]
+    async fn test_put_file_deduplicates_and_slices() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
+
+        let content = b"use std::io;\n\nfn main() {}\n";
         let key1 = storage
-            .put("npm", "react", "18.0.0", b"chunk1")
+            .put_file("crates/demo/0.1.0", content)
             .await
             .unwrap();
         let key2 = storage
-            .put("npm", "react", "18.0.0", b"chunk2")
+            .put_file("crates/demo/0.1.0", content)
             .await
             .unwrap();
 
-        assert!(storage.exists(&key1).await);
-        assert!(storage.exists(&key2).await);
+        assert_eq!(key1, key2);
+        assert!(key1.starts_with("crates/demo/0.1.0/files/"));
+
+        let slice = storage.get_range(&key1, 14, 26).await.unwrap();
+        assert_eq!(slice, b"fn main() {}");
 
-        storage
-            .delete_package("npm", "react", "18.0.0")
+        let clamped = storage.get_range(&key1, 14, 1000).await.unwrap();
+        assert_eq!(clamped, b"fn main() {}\n");
+    }
+
+    #[tokio::test]
+    async fn test_blobs_compressed_on_disk() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
+
+        let content = "export function debounce(fn, wait) {\n  return fn;\n}\n".repeat(50);
+        let key = storage
+            .put("npm/lodash/4.17.21", content.as_bytes())
             .await
             .unwrap();
 
-        assert!(!storage.exists(&key1).await);
-        assert!(!storage.exists(&key2).await);
+        // Keyed by the uncompressed content
+        let hash = hex::encode(Sha256::digest(content.as_bytes()));
+        assert_eq!(LocalStorage::hash_from_key(&key), Some(hash.as_str()));
+
+        let on_disk = std::fs::read(dir.path().join("blobs").join(&key)).unwrap();
+        assert!(on_disk.starts_with(&ZSTD_MAGIC));
+        assert!(on_disk.len() < content.len() / 10);
+
+        assert_eq!(storage.get(&key).await.unwrap(), content.as_bytes());
+        assert_eq!(storage.get_range(&key, 7, 15).await.unwrap(), b"function");
+
+        let usage = storage.usage().await.unwrap();
+        assert_eq!(usage.stored, on_disk.len() as u64);
+        assert_eq!(usage.content, content.len() as u64);
+        assert!(usage.savings_percent() > 90.0);
+    }
+
+    #[tokio::test]
+    async fn test_reads_uncompressed_blobs() {
+        let dir = tempdir().unwrap();
+        let blobs_dir = dir.path().join("blobs");
+
+        // A blob written before compression was added
+        let content = b"def main():\n    pass\n";
+        let key = format!("pypi/demo/1.0.0/{}", hex::encode(Sha256::digest(content)));
+        std::fs::create_dir_all(blobs_dir.join("pypi/demo/1.0.0")).unwrap();
+        std::fs::write(blobs_dir.join(&key), content).unwrap();
+
+        let storage = LocalStorage::new(blobs_dir.clone()).await.unwrap();
+        assert_eq!(storage.get(&key).await.unwrap(), content);
+
+        // Storing it again leaves the existing blob in place
+        let again = storage.put("pypi/demo/1.0.0", content).await.unwrap();
+        assert_eq!(again, key);
+        assert_eq!(std::fs::read(blobs_dir.join(&key)).unwrap(), content);
+
+        let usage = storage.usage().await.unwrap();
+        assert_eq!(usage.stored, usage.content);
+    }
+
+    #[tokio::test]
+    async fn test_uncompressed_level() {
+        let dir = tempdir().unwrap();
+        let blobs_dir = dir.path().join("blobs");
+        let storage = LocalStorage::new(blobs_dir.clone())
+            .await
+            .unwrap()
+            .with_compression_level(0);
+
+        let plain = storage.put("npm/a/1.0.0", b"plain").await.unwrap();
+        assert_eq!(std::fs::read(blobs_dir.join(&plain)).unwrap(), b"plain");
+
+        // Content that looks like a frame is still compressed, so it reads
+        // back unchanged
+        let mut framelike = ZSTD_MAGIC.to_vec();
+        framelike.extend_from_slice(b"not really zstd");
+        let key = storage.put("npm/a/1.0.0", &framelike).await.unwrap();
+        assert_eq!(storage.get(&key).await.unwrap(), framelike);
+    }
+
+    #[tokio::test]
+    async fn test_get_many_keeps_order_and_missing_blobs() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().join("blobs")).await.unwrap();
+
+        let first = storage.put("npm/a/1.0.0", b"first").await.unwrap();
+        let second = storage.put("npm/a/1.0.0", b"second").await.unwrap();
+
+        let blobs = storage
+            .get_many(&[&second, "npm/a/1.0.0/missing", &first])
+            .await;
+        assert_eq!(blobs.len(), 3);
+        assert_eq!(blobs[0].as_ref().unwrap(), b"second");
+        assert!(blobs[1].is_err());
+        assert_eq!(blobs[2].as_ref().unwrap(), b"first");
     }
 
     #[tokio::test]
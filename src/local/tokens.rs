@@ -0,0 +1,161 @@
+//! Identifier tokenization for keyword matching.
+//!
+//! Splits camelCase, PascalCase, snake_case and kebab-case identifiers into
+//! lowercase words, so a query like "http client timeout" can match
+//! `HttpClient.setTimeout` even when the embedding doesn't connect them.
+
+/// Words too common in signatures or queries to be worth matching.
+const STOPWORDS: &[&str] = &[
+    // Language keywords
+    "fn",
+    "pub",
+    "mut",
+    "self",
+    "let",
+    "const",
+    "def",
+    "function",
+    "async",
+    "await",
+    "return",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "void",
+    "func",
+    "var",
+    "new",
+    "impl",
+    "dyn",
+    "crate",
+    "super",
+    "this",
+    "export",
+    "default",
+    "class",
+    "interface",
+    "type",
+    "where",
+    // Query filler
+    "the",
+    "an",
+    "and",
+    "or",
+    "of",
+    "to",
+    "in",
+    "for",
+    "with",
+    "how",
+    "what",
+    "is",
+    "on",
+];
+
+/// Split text into lowercase identifier words, deduplicated in order.
+///
+/// `HTTPClient.setTimeout(ms)` becomes `["http", "client", "set", "timeout", "ms"]`.
+/// Single characters and [`STOPWORDS`] are dropped.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+
+    for run in text.split(|c: char| !c.is_alphanumeric()) {
+        for word in split_case(run) {
+            let word = word.to_lowercase();
+            if word.chars().count() > 1
+                && !STOPWORDS.contains(&word.as_str())
+                && !tokens.contains(&word)
+            {
+                tokens.push(word);
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Tokens for a chunk's name and signature, as stored in `chunks.tokens`.
+///
+/// Space-padded so a word can be matched with `' ' || word || ' '`.
+pub fn chunk_tokens(name: &str, signature: Option<&str>) -> String {
+    let mut tokens = tokenize(name);
+    for word in tokenize(signature.unwrap_or_default()) {
+        if !tokens.contains(&word) {
+            tokens.push(word);
+        }
+    }
+
+    if tokens.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", tokens.join(" "))
+    }
+}
+
+/// Split an alphanumeric run at case boundaries.
+///
+/// A new word starts at an uppercase letter that follows a lowercase letter
+/// or digit (`setTimeout`), or that ends an acronym (`HTTPClient`).
+fn split_case(run: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = run.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for i in 1..chars.len() {
+        let (pos, c) = chars[i];
+        let prev = chars[i - 1].1;
+        let next_is_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower));
+        if boundary {
+            words.push(&run[start..pos]);
+            start = pos;
+        }
+    }
+
+    if start < run.len() {
+        words.push(&run[start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_identifiers() {
+        assert_eq!(tokenize("searchTimeout"), vec!["search", "timeout"]);
+        assert_eq!(tokenize("HTTPClient"), vec!["http", "client"]);
+        assert_eq!(tokenize("set_timeout_ms"), vec!["set", "timeout", "ms"]);
+        assert_eq!(tokenize("parseJSON2Xml"), vec!["parse", "json2", "xml"]);
+        assert_eq!(tokenize("utf8-decoder"), vec!["utf8", "decoder"]);
+    }
+
+    #[test]
+    fn test_tokenize_query_matches_identifier() {
+        let query = tokenize("search timeout");
+        let name = tokenize("searchTimeout");
+        assert_eq!(query, name);
+    }
+
+    #[test]
+    fn test_tokenize_drops_keywords_and_duplicates() {
+        assert_eq!(
+            tokenize("pub fn set_timeout(&mut self, timeout: Duration)"),
+            vec!["set", "timeout", "duration"]
+        );
+        assert_eq!(tokenize("how to set a timeout"), vec!["set", "timeout"]);
+    }
+
+    #[test]
+    fn test_chunk_tokens() {
+        assert_eq!(
+            chunk_tokens("setTimeout", Some("setTimeout(ms: number): void")),
+            " set timeout ms number "
+        );
+        assert_eq!(chunk_tokens("x", None), "");
+    }
+}
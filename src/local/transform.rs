@@ -0,0 +1,180 @@
+//! Transformers rewriting parsed chunks before they are embedded and stored.
+//!
+//! Enabled by name with `chunk_transformers` in config, and run in order on
+//! every chunk of a package right after parsing, so their changes reach
+//! both the stored chunk and its embedding text.
+
+use anyhow::Result;
+
+use crate::indexer::CodeChunk;
+use crate::types::ChunkType;
+
+/// Rewrites or drops parsed chunks.
+pub trait ChunkTransformer: Send + Sync {
+    /// Name the transformer is enabled by.
+    fn name(&self) -> &str;
+
+    /// Rewrite `chunk` in place. Returning false drops it from the package.
+    fn transform(&self, chunk: &mut CodeChunk) -> bool;
+}
+
+/// Names of the transformers shipped with idx.
+pub const BUILTIN_TRANSFORMERS: &[&str] = &[StripLicenseHeaders::NAME];
+
+/// The built-in transformers `names` enable, in order.
+pub fn builtin_transformers(names: &[String]) -> Result<Vec<Box<dyn ChunkTransformer>>> {
+    names
+        .iter()
+        .map(|name| match name.as_str() {
+            StripLicenseHeaders::NAME => {
+                Ok(Box::new(StripLicenseHeaders) as Box<dyn ChunkTransformer>)
+            }
+            _ => anyhow::bail!(
+                "Unknown chunk transformer '{}'. Use one of: {}",
+                name,
+                BUILTIN_TRANSFORMERS.join(", ")
+            ),
+        })
+        .collect()
+}
+
+/// Drops license and copyright headers from chunks.
+///
+/// A file's header comment often sits right above its first declaration,
+/// where parsers take it for that declaration's documentation or include it
+/// in the code. Either way it is boilerplate that drowns out what the chunk
+/// is about. Documentation chunks keep their code, since a README's license
+/// section is content.
+pub struct StripLicenseHeaders;
+
+impl StripLicenseHeaders {
+    pub const NAME: &str = "strip-license-headers";
+}
+
+impl ChunkTransformer for StripLicenseHeaders {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn transform(&self, chunk: &mut CodeChunk) -> bool {
+        if let Some(doc) = &chunk.documentation {
+            let kept: Vec<&str> = doc
+                .split("\n\n")
+                .filter(|paragraph| !is_license_text(paragraph))
+                .collect();
+            chunk.documentation = Some(kept.join("\n\n")).filter(|doc| !doc.trim().is_empty());
+        }
+
+        if chunk.chunk_type == ChunkType::Documentation {
+            return true;
+        }
+
+        // A leading comment block that is a license header
+        let mut header_lines = 0;
+        let mut header_bytes = 0;
+        for line in chunk.code.split_inclusive('\n') {
+            let trimmed = line.trim();
+            let is_comment = ["//", "#", "/*", "*", "--"]
+                .iter()
+                .any(|marker| trimmed.starts_with(marker));
+            let blank_in_header = trimmed.is_empty() && header_lines > 0;
+            if !is_comment && !blank_in_header {
+                break;
+            }
+            header_lines += 1;
+            header_bytes += line.len();
+        }
+        if header_lines > 0
+            && header_bytes < chunk.code.len()
+            && is_license_text(&chunk.code[..header_bytes])
+        {
+            chunk.code.drain(..header_bytes);
+            chunk.start_line += header_lines as u32;
+            chunk.start_byte += header_bytes;
+        }
+
+        true
+    }
+}
+
+/// Whether `text` reads like a license or copyright notice.
+fn is_license_text(text: &str) -> bool {
+    let text = text.to_lowercase();
+    text.contains("spdx-license-identifier")
+        || text.contains("@license")
+        || (text.contains("copyright")
+            && (text.contains("license") || text.contains("all rights reserved")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Visibility;
+
+    fn chunk(code: &str, documentation: Option<&str>) -> CodeChunk {
+        CodeChunk {
+            chunk_type: ChunkType::Function,
+            visibility: Visibility::Public,
+            name: "parse".to_string(),
+            signature: None,
+            params: Vec::new(),
+            returns: None,
+            code: code.to_string(),
+            documentation: documentation.map(str::to_string),
+            parent: None,
+            file_path: "src/parse.js".to_string(),
+            start_line: 1,
+            end_line: 8,
+            start_byte: 0,
+            end_byte: code.len(),
+            deprecated: false,
+            deprecation_message: None,
+        }
+    }
+
+    #[test]
+    fn test_strips_license_documentation_and_code_header() {
+        let mut doc_header = chunk(
+            "function parse(s) {}",
+            Some("Copyright (c) 2024 Acme. Licensed under the MIT license.\n\nParse a string."),
+        );
+        assert!(StripLicenseHeaders.transform(&mut doc_header));
+        assert_eq!(doc_header.documentation.as_deref(), Some("Parse a string."));
+
+        let code =
+            "// Copyright 2024 Acme\n// SPDX-License-Identifier: MIT\n\nfunction parse(s) {}";
+        let mut code_header = chunk(code, None);
+        StripLicenseHeaders.transform(&mut code_header);
+        assert_eq!(code_header.code, "function parse(s) {}");
+        assert_eq!(code_header.start_line, 4);
+        assert_eq!(&code[code_header.start_byte..], code_header.code);
+
+        let mut license_only = chunk("fn a() {}", Some("@license MIT"));
+        StripLicenseHeaders.transform(&mut license_only);
+        assert!(license_only.documentation.is_none());
+    }
+
+    #[test]
+    fn test_keeps_ordinary_comments() {
+        let code = "// Parse a string, see the license field of package.json\nfunction parse(s) {}";
+        let mut kept = chunk(code, Some("Reads the copyright field."));
+        StripLicenseHeaders.transform(&mut kept);
+        assert_eq!(kept.code, code);
+        assert_eq!(
+            kept.documentation.as_deref(),
+            Some("Reads the copyright field.")
+        );
+    }
+
+    #[test]
+    fn test_builtin_transformers_by_name() {
+        let transformers = builtin_transformers(&["strip-license-headers".to_string()]).unwrap();
+        assert_eq!(transformers[0].name(), "strip-license-headers");
+
+        let err = builtin_transformers(&["redact".to_string()])
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("Unknown chunk transformer 'redact'"));
+    }
+}
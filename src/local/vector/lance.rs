@@ -1,4 +1,4 @@
-//! LanceDB vector storage for similarity search.
+//! LanceDB vector backend (embedded, stored under `.index/vectors/`).
 
 use std::path::Path;
 use std::sync::Arc;
@@ -10,16 +10,21 @@ use arrow_array::{
 use arrow_schema::{DataType, Field, Schema};
 use futures::TryStreamExt;
 use lancedb::query::{ExecutableQuery, QueryBase};
-use lancedb::{Connection, Table};
+use lancedb::{Connection, DistanceType, Table};
 
-use super::models::{VECTOR_DIM, VectorRecord, VectorSearchHit};
+use super::{VectorBackend, sanitize_namespace, unsanitize_namespace};
+use crate::local::models::{DistanceMetric, VectorRecord, VectorSearchHit};
 
 /// LanceDB-based vector store.
-pub struct VectorStore {
+///
+/// Only embeddings are stored here; chunk metadata lives in SQLite. The
+/// metric is applied at query time, so it can change without re-indexing.
+pub struct LanceStore {
     db: Connection,
+    metric: DistanceMetric,
 }
 
-impl VectorStore {
+impl LanceStore {
     /// Open or create a vector store at the given path.
     pub async fn open(path: &Path) -> Result<Self> {
         let db = lancedb::connect(path.to_str().unwrap())
@@ -27,19 +32,36 @@ impl VectorStore {
             .await
             .context("Failed to connect to LanceDB")?;
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            metric: DistanceMetric::default(),
+        })
     }
 
-    /// Get or create a table for a namespace.
-    pub async fn get_or_create_table(&self, namespace: &str) -> Result<Table> {
-        let table_name = sanitize_table_name(namespace);
+    /// Measure distances with `metric` (default: L2).
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    fn distance_type(&self) -> DistanceType {
+        match self.metric {
+            DistanceMetric::L2 => DistanceType::L2,
+            DistanceMetric::Cosine => DistanceType::Cosine,
+            DistanceMetric::Dot => DistanceType::Dot,
+        }
+    }
+
+    /// Get or create a table for a namespace holding `dim`-dimensional vectors.
+    pub async fn get_or_create_table(&self, namespace: &str, dim: i32) -> Result<Table> {
+        let table_name = sanitize_namespace(namespace);
 
         if let Ok(table) = self.db.open_table(&table_name).execute().await {
             return Ok(table);
         }
 
-        let schema = Self::schema();
-        let empty_batch = Self::empty_batch(&schema)?;
+        let schema = Self::schema(dim);
+        let empty_batch = Self::empty_batch(&schema, dim)?;
         let batches = RecordBatchIterator::new(vec![Ok(empty_batch)], schema);
 
         let table = self
@@ -57,125 +79,24 @@ impl VectorStore {
         Ok(table)
     }
 
-    /// Insert vectors into a namespace.
-    pub async fn insert(&self, namespace: &str, records: Vec<VectorRecord>) -> Result<()> {
-        if records.is_empty() {
-            return Ok(());
-        }
-
-        let table = self.get_or_create_table(namespace).await?;
-        let batch = Self::records_to_batch(&records)?;
-        let batches = RecordBatchIterator::new(vec![Ok(batch)], Self::schema());
-
-        table
-            .add(Box::new(batches))
-            .execute()
-            .await
-            .context("Failed to insert vectors")?;
-
-        Ok(())
-    }
-
-    /// Search for similar vectors in a namespace.
-    pub async fn search(
-        &self,
-        namespace: &str,
-        query_vector: &[f32],
-        limit: usize,
-    ) -> Result<Vec<VectorSearchHit>> {
-        let table_name = sanitize_table_name(namespace);
-
-        let table = match self.db.open_table(&table_name).execute().await {
-            Ok(t) => t,
-            Err(_) => return Ok(vec![]),
-        };
-
-        let results = table
-            .query()
-            .nearest_to(query_vector)
-            .context("Invalid query vector")?
-            .limit(limit)
-            .execute()
-            .await
-            .context("Failed to execute search")?
-            .try_collect::<Vec<_>>()
-            .await
-            .context("Failed to collect results")?;
-
-        let mut hits = Vec::new();
-        for batch in results {
-            hits.extend(Self::batch_to_hits(&batch)?);
-        }
-
-        hits.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
-        hits.truncate(limit);
-
-        Ok(hits)
-    }
-
-    /// Search across multiple namespaces.
-    pub async fn search_multi(
-        &self,
-        namespaces: &[String],
-        query_vector: &[f32],
-        limit: usize,
-    ) -> Result<Vec<VectorSearchHit>> {
-        let mut all_hits = Vec::new();
-
-        for ns in namespaces {
-            let hits = self.search(ns, query_vector, limit).await?;
-            all_hits.extend(hits);
-        }
-
-        all_hits.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
-        all_hits.truncate(limit);
-
-        Ok(all_hits)
-    }
-
-    /// Delete all vectors for a namespace.
-    pub async fn delete_namespace(&self, namespace: &str) -> Result<()> {
-        let table_name = sanitize_table_name(namespace);
-        self.db.drop_table(&table_name).await.ok();
-        Ok(())
-    }
-
-    /// List all namespaces (tables).
-    pub async fn list_namespaces(&self) -> Result<Vec<String>> {
-        let tables = self
-            .db
-            .table_names()
-            .execute()
-            .await
-            .context("Failed to list tables")?;
-
-        Ok(tables
-            .into_iter()
-            .map(|t| unsanitize_table_name(&t))
-            .collect())
-    }
-
-    fn schema() -> Arc<Schema> {
+    fn schema(dim: i32) -> Arc<Schema> {
         Arc::new(Schema::new(vec![
             Field::new("chunk_id", DataType::Utf8, false),
             Field::new("content_hash", DataType::Utf8, false),
             Field::new(
                 "vector",
-                DataType::FixedSizeList(
-                    Arc::new(Field::new("item", DataType::Float32, true)),
-                    VECTOR_DIM,
-                ),
+                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim),
                 false,
             ),
         ]))
     }
 
-    fn empty_batch(schema: &Arc<Schema>) -> Result<RecordBatch> {
+    fn empty_batch(schema: &Arc<Schema>, dim: i32) -> Result<RecordBatch> {
         let chunk_ids = StringArray::from(Vec::<String>::new());
         let content_hashes = StringArray::from(Vec::<String>::new());
         let vectors = FixedSizeListArray::new(
             Arc::new(Field::new("item", DataType::Float32, true)),
-            VECTOR_DIM,
+            dim,
             Arc::new(Float32Array::from(Vec::<f32>::new())),
             None,
         );
@@ -191,7 +112,13 @@ impl VectorStore {
         .context("Failed to create empty batch")
     }
 
+    /// Vector dimension of a batch of records, taken from the first one.
+    fn records_dim(records: &[VectorRecord]) -> i32 {
+        records.first().map_or(0, |r| r.vector.len() as i32)
+    }
+
     fn records_to_batch(records: &[VectorRecord]) -> Result<RecordBatch> {
+        let dim = Self::records_dim(records);
         let chunk_ids: Vec<&str> = records.iter().map(|r| r.chunk_id.as_str()).collect();
         let content_hashes: Vec<&str> = records.iter().map(|r| r.content_hash.as_str()).collect();
         let flat_vectors: Vec<f32> = records
@@ -203,13 +130,13 @@ impl VectorStore {
         let content_hash_array = StringArray::from(content_hashes);
         let vector_array = FixedSizeListArray::new(
             Arc::new(Field::new("item", DataType::Float32, true)),
-            VECTOR_DIM,
+            dim,
             Arc::new(Float32Array::from(flat_vectors)),
             None,
         );
 
         RecordBatch::try_new(
-            Self::schema(),
+            Self::schema(dim),
             vec![
                 Arc::new(chunk_id_array),
                 Arc::new(content_hash_array),
@@ -219,7 +146,7 @@ impl VectorStore {
         .context("Failed to create record batch")
     }
 
-    fn batch_to_hits(batch: &RecordBatch) -> Result<Vec<VectorSearchHit>> {
+    fn batch_to_hits(&self, batch: &RecordBatch) -> Result<Vec<VectorSearchHit>> {
         let chunk_ids = batch
             .column_by_name("chunk_id")
             .context("Missing chunk_id column")?
@@ -239,6 +166,7 @@ impl VectorStore {
             hits.push(VectorSearchHit {
                 chunk_id: chunk_ids.value(i).to_string(),
                 distance: distances.value(i),
+                metric: self.metric,
             });
         }
 
@@ -246,38 +174,114 @@ impl VectorStore {
     }
 }
 
-fn sanitize_table_name(namespace: &str) -> String {
-    // LanceDB: "Table names can only contain alphanumeric characters, underscores, hyphens, and periods"
-    // We need to escape: / and @
-    namespace
-        .replace('/', "--S--") // slash
-        .replace('@', "--A--") // at
-}
+impl VectorBackend for LanceStore {
+    /// Upsert vectors into a namespace, keyed by chunk id.
+    async fn insert(&self, namespace: &str, records: Vec<VectorRecord>) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let dim = Self::records_dim(&records);
+        let table = self.get_or_create_table(namespace, dim).await?;
+        let batch = Self::records_to_batch(&records)?;
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], Self::schema(dim));
+
+        let mut merge = table.merge_insert(&["chunk_id"]);
+        merge
+            .when_matched_update_all(None)
+            .when_not_matched_insert_all();
+        merge
+            .execute(Box::new(batches))
+            .await
+            .context("Failed to insert vectors")?;
+
+        Ok(())
+    }
+
+    /// Search for similar vectors in a namespace.
+    async fn search(
+        &self,
+        namespace: &str,
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        let table_name = sanitize_namespace(namespace);
+
+        let table = match self.db.open_table(&table_name).execute().await {
+            Ok(t) => t,
+            Err(_) => return Ok(vec![]),
+        };
+
+        let results = table
+            .query()
+            .nearest_to(query_vector)
+            .context("Invalid query vector")?
+            .distance_type(self.distance_type())
+            .limit(limit)
+            .execute()
+            .await
+            .context("Failed to execute search")?
+            .try_collect::<Vec<_>>()
+            .await
+            .context("Failed to collect results")?;
+
+        let mut hits = Vec::new();
+        for batch in results {
+            hits.extend(self.batch_to_hits(&batch)?);
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits.truncate(limit);
+
+        Ok(hits)
+    }
 
-fn unsanitize_table_name(table_name: &str) -> String {
-    table_name.replace("--S--", "/").replace("--A--", "@")
+    /// Delete all vectors for a namespace.
+    async fn delete_namespace(&self, namespace: &str) -> Result<()> {
+        let table_name = sanitize_namespace(namespace);
+        self.db.drop_table(&table_name).await.ok();
+        Ok(())
+    }
+
+    /// List all namespaces (tables).
+    async fn list_namespaces(&self) -> Result<Vec<String>> {
+        let tables = self
+            .db
+            .table_names()
+            .execute()
+            .await
+            .context("Failed to list tables")?;
+
+        Ok(tables
+            .into_iter()
+            .map(|t| unsanitize_namespace(&t))
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::local::models::VECTOR_DIM;
     use tempfile::tempdir;
 
     #[tokio::test]
     async fn test_insert_and_search() {
         let dir = tempdir().unwrap();
-        let store = VectorStore::open(dir.path()).await.unwrap();
+        let store = LanceStore::open(dir.path()).await.unwrap();
 
         let records = vec![
             VectorRecord {
                 chunk_id: "chunk1".to_string(),
                 content_hash: "hash1".to_string(),
                 vector: vec![1.0; VECTOR_DIM as usize],
+                attributes: None,
             },
             VectorRecord {
                 chunk_id: "chunk2".to_string(),
                 content_hash: "hash2".to_string(),
                 vector: vec![0.0; VECTOR_DIM as usize],
+                attributes: None,
             },
         ];
 
@@ -290,15 +294,99 @@ mod tests {
         assert_eq!(results[0].chunk_id, "chunk1");
     }
 
+    #[tokio::test]
+    async fn test_insert_replaces_same_chunk_id() {
+        let dir = tempdir().unwrap();
+        let store = LanceStore::open(dir.path()).await.unwrap();
+
+        let record = |value: f32| VectorRecord {
+            chunk_id: "chunk1".to_string(),
+            content_hash: "hash1".to_string(),
+            vector: vec![value; VECTOR_DIM as usize],
+            attributes: None,
+        };
+
+        store
+            .insert("test/namespace", vec![record(1.0)])
+            .await
+            .unwrap();
+        store
+            .insert("test/namespace", vec![record(0.5)])
+            .await
+            .unwrap();
+
+        let query = vec![1.0; VECTOR_DIM as usize];
+        let results = store.search("test/namespace", &query, 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, "chunk1");
+    }
+
+    #[tokio::test]
+    async fn test_search_with_each_metric() {
+        let dir = tempdir().unwrap();
+        // Unit vectors turning away from the query, most relevant first
+        let records: Vec<VectorRecord> = [0.0f32, 0.4, 0.8, 1.2]
+            .iter()
+            .enumerate()
+            .map(|(i, angle)| VectorRecord {
+                chunk_id: format!("chunk{}", i),
+                content_hash: format!("hash{}", i),
+                vector: vec![angle.cos(), angle.sin()],
+                attributes: None,
+            })
+            .collect();
+        let query = [1.0, 0.0];
+
+        for metric in [
+            DistanceMetric::L2,
+            DistanceMetric::Cosine,
+            DistanceMetric::Dot,
+        ] {
+            let store = LanceStore::open(dir.path())
+                .await
+                .unwrap()
+                .with_metric(metric);
+            store.insert("test/metric", records.clone()).await.unwrap();
+
+            let hits = store.search("test/metric", &query, 10).await.unwrap();
+            let ids: Vec<_> = hits.iter().map(|h| h.chunk_id.as_str()).collect();
+            assert_eq!(
+                ids,
+                vec!["chunk0", "chunk1", "chunk2", "chunk3"],
+                "{}",
+                metric
+            );
+            for (hit, record) in hits.iter().zip(&records) {
+                assert_eq!(hit.metric, metric);
+                let expected = metric.distance(&query, &record.vector);
+                assert!(
+                    (hit.distance - expected).abs() < 1e-4,
+                    "{}: {} vs {}",
+                    metric,
+                    hit.distance,
+                    expected
+                );
+            }
+            let scores: Vec<_> = hits.iter().map(VectorSearchHit::score).collect();
+            assert!(
+                scores.windows(2).all(|w| w[0] > w[1]),
+                "{}: {:?}",
+                metric,
+                scores
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_delete_namespace() {
         let dir = tempdir().unwrap();
-        let store = VectorStore::open(dir.path()).await.unwrap();
+        let store = LanceStore::open(dir.path()).await.unwrap();
 
         let records = vec![VectorRecord {
             chunk_id: "chunk1".to_string(),
             content_hash: "hash1".to_string(),
             vector: vec![1.0; VECTOR_DIM as usize],
+            attributes: None,
         }];
 
         store.insert("to_delete", records).await.unwrap();
@@ -0,0 +1,247 @@
+//! Vector storage for similarity search.
+//!
+//! Two backends implement [`VectorBackend`]:
+//! - [`LanceStore`] - embedded LanceDB under `.index/vectors/` (default)
+//! - [`TurbopufferStore`] - hosted Turbopuffer namespaces, shareable across machines
+//!
+//! The backend is selected with `vector_backend` in [`LocalConfig`].
+//!
+//! Documentation embeddings live beside each namespace's code embeddings in
+//! a companion namespace (see [`docs_namespace`]), which [`VectorStore`]
+//! creates, searches and deletes along with it.
+
+mod lance;
+mod turbopuffer;
+
+pub use lance::LanceStore;
+pub use turbopuffer::TurbopufferStore;
+
+use std::future::Future;
+use std::path::Path;
+
+use anyhow::Result;
+use futures::{StreamExt, TryStreamExt, stream};
+
+use super::LocalConfig;
+use super::config::VectorBackendKind;
+use super::models::{VectorRecord, VectorSearchHit};
+
+/// Maximum namespaces searched concurrently by [`VectorBackend::search_multi`].
+const SEARCH_CONCURRENCY: usize = 8;
+
+/// Suffix of the companion namespace holding a namespace's doc embeddings.
+const DOCS_SUFFIX: &str = "#docs";
+
+/// Namespace holding the documentation embeddings of `namespace`'s chunks.
+pub fn docs_namespace(namespace: &str) -> String {
+    format!("{}{}", namespace, DOCS_SUFFIX)
+}
+
+/// Trait for vector storage backends.
+///
+/// Namespaces are `registry/package/version` strings; each backend maps them
+/// to whatever its own naming rules allow.
+pub trait VectorBackend: Send + Sync {
+    /// Insert vectors into a namespace, creating it if needed.
+    ///
+    /// Records replace any existing record with the same chunk id.
+    fn insert(
+        &self,
+        namespace: &str,
+        records: Vec<VectorRecord>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Search for similar vectors in a namespace.
+    ///
+    /// A missing namespace yields no hits rather than an error.
+    fn search(
+        &self,
+        namespace: &str,
+        query_vector: &[f32],
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<VectorSearchHit>>> + Send;
+
+    /// Search across multiple namespaces, ranking hits globally.
+    ///
+    /// Namespaces are searched concurrently; each contributes up to `limit`
+    /// hits before the merged list is sorted by distance and truncated.
+    fn search_multi(
+        &self,
+        namespaces: &[String],
+        query_vector: &[f32],
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<VectorSearchHit>>> + Send {
+        async move {
+            let searches: Vec<_> = namespaces
+                .iter()
+                .map(|ns| self.search(ns, query_vector, limit))
+                .collect();
+            let per_namespace: Vec<Vec<VectorSearchHit>> = stream::iter(searches)
+                .buffer_unordered(SEARCH_CONCURRENCY)
+                .try_collect()
+                .await?;
+
+            // Completion order varies, so break distance ties by id
+            let mut all_hits: Vec<_> = per_namespace.into_iter().flatten().collect();
+            all_hits.sort_by(|a, b| {
+                a.distance
+                    .total_cmp(&b.distance)
+                    .then_with(|| a.chunk_id.cmp(&b.chunk_id))
+            });
+            all_hits.truncate(limit);
+
+            Ok(all_hits)
+        }
+    }
+
+    /// Delete all vectors for a namespace.
+    fn delete_namespace(&self, namespace: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// List all namespaces.
+    fn list_namespaces(&self) -> impl Future<Output = Result<Vec<String>>> + Send;
+}
+
+/// Vector store dispatching to the configured backend.
+pub enum VectorStore {
+    Lance(LanceStore),
+    Turbopuffer(TurbopufferStore),
+}
+
+impl VectorStore {
+    /// Open the backend selected in `config`.
+    ///
+    /// `path` is only used by the LanceDB backend.
+    pub async fn open(path: &Path, config: &LocalConfig) -> Result<Self> {
+        match config.vector_backend {
+            VectorBackendKind::Lance => Ok(Self::Lance(
+                LanceStore::open(path)
+                    .await?
+                    .with_metric(config.distance_metric),
+            )),
+            VectorBackendKind::Turbopuffer => {
+                if config.offline {
+                    anyhow::bail!(
+                        "offline mode: the Turbopuffer vector backend needs network access"
+                    );
+                }
+                Ok(Self::Turbopuffer(TurbopufferStore::from_config(config)?))
+            }
+        }
+    }
+
+    /// Insert vectors into a namespace.
+    pub async fn insert(&self, namespace: &str, records: Vec<VectorRecord>) -> Result<()> {
+        match self {
+            Self::Lance(s) => s.insert(namespace, records).await,
+            Self::Turbopuffer(s) => s.insert(namespace, records).await,
+        }
+    }
+
+    /// Search for similar vectors in a namespace.
+    pub async fn search(
+        &self,
+        namespace: &str,
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        match self {
+            Self::Lance(s) => s.search(namespace, query_vector, limit).await,
+            Self::Turbopuffer(s) => s.search(namespace, query_vector, limit).await,
+        }
+    }
+
+    /// Insert documentation vectors for a namespace's chunks.
+    pub async fn insert_docs(&self, namespace: &str, records: Vec<VectorRecord>) -> Result<()> {
+        self.insert(&docs_namespace(namespace), records).await
+    }
+
+    /// Search the documentation vectors of multiple namespaces.
+    ///
+    /// Hit distances are measured to the chunks' doc embeddings.
+    pub async fn search_docs_multi(
+        &self,
+        namespaces: &[String],
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        let namespaces: Vec<String> = namespaces.iter().map(|ns| docs_namespace(ns)).collect();
+        self.search_multi(&namespaces, query_vector, limit).await
+    }
+
+    /// Search across multiple namespaces.
+    pub async fn search_multi(
+        &self,
+        namespaces: &[String],
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        match self {
+            Self::Lance(s) => s.search_multi(namespaces, query_vector, limit).await,
+            Self::Turbopuffer(s) => s.search_multi(namespaces, query_vector, limit).await,
+        }
+    }
+
+    /// Delete all vectors for a namespace, documentation vectors included.
+    pub async fn delete_namespace(&self, namespace: &str) -> Result<()> {
+        let docs = docs_namespace(namespace);
+        match self {
+            Self::Lance(s) => {
+                s.delete_namespace(namespace).await?;
+                s.delete_namespace(&docs).await
+            }
+            Self::Turbopuffer(s) => {
+                s.delete_namespace(namespace).await?;
+                s.delete_namespace(&docs).await
+            }
+        }
+    }
+
+    /// List all namespaces, leaving out documentation companions.
+    pub async fn list_namespaces(&self) -> Result<Vec<String>> {
+        let namespaces = match self {
+            Self::Lance(s) => s.list_namespaces().await?,
+            Self::Turbopuffer(s) => s.list_namespaces().await?,
+        };
+        Ok(namespaces
+            .into_iter()
+            .filter(|ns| !ns.ends_with(DOCS_SUFFIX))
+            .collect())
+    }
+}
+
+/// Escape characters that table/namespace names can't contain.
+///
+/// Both LanceDB and Turbopuffer only allow alphanumerics, `_`, `-` and `.`.
+fn sanitize_namespace(namespace: &str) -> String {
+    namespace
+        .replace('/', "--S--") // slash
+        .replace('@', "--A--") // at
+        .replace('#', "--H--") // hash
+}
+
+fn unsanitize_namespace(name: &str) -> String {
+    name.replace("--S--", "/")
+        .replace("--A--", "@")
+        .replace("--H--", "#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_round_trip() {
+        for ns in [
+            "npm/@types/node/20.1.0",
+            &docs_namespace("crates/serde/1.0.0"),
+        ] {
+            let sanitized = sanitize_namespace(ns);
+            assert!(
+                sanitized
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+            );
+            assert_eq!(unsanitize_namespace(&sanitized), ns);
+        }
+    }
+}
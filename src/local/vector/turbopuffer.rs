@@ -0,0 +1,345 @@
+//! Turbopuffer vector backend (hosted).
+//!
+//! Each `registry/package/version` namespace maps to one Turbopuffer
+//! namespace named `<prefix>.<sanitized namespace>`, so several projects
+//! (or teammates) can point at the same account without colliding.
+//!
+//! Rows carry the embedding plus the filterable attributes from
+//! [`VectorAttributes`]. Search still hydrates results from the local
+//! SQLite db, which keeps queries cheap (only ids and distances come back)
+//! and lets `idx` work unchanged on top of either backend. The tradeoff is
+//! that a machine can only return hits for chunks it has indexed itself;
+//! the server-side attributes are what a shared index would need to serve
+//! results without SQLite, at the cost of storing (and paying for) the
+//! metadata twice.
+
+use anyhow::{Context, Result};
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{VectorBackend, sanitize_namespace, unsanitize_namespace};
+use crate::local::models::{DistanceMetric, VectorAttributes, VectorRecord, VectorSearchHit};
+use crate::local::{EnvSecret, LocalConfig};
+
+/// Maximum rows per upsert request.
+const UPSERT_BATCH_SIZE: usize = 500;
+
+/// Turbopuffer-based vector store.
+pub struct TurbopufferStore {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    prefix: String,
+    metric: DistanceMetric,
+}
+
+#[derive(Serialize)]
+struct Row<'a> {
+    id: &'a str,
+    vector: &'a [f32],
+    content_hash: &'a str,
+    #[serde(flatten)]
+    attributes: Option<&'a VectorAttributes>,
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    rows: Vec<QueryRow>,
+}
+
+#[derive(Deserialize)]
+struct QueryRow {
+    id: String,
+    #[serde(rename = "$dist")]
+    dist: f32,
+}
+
+#[derive(Deserialize)]
+struct NamespacesResponse {
+    #[serde(default)]
+    namespaces: Vec<NamespaceEntry>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NamespaceEntry {
+    id: String,
+}
+
+impl TurbopufferStore {
+    /// Create a store from the Turbopuffer settings in `config`.
+    pub fn from_config(config: &LocalConfig) -> Result<Self> {
+        let api_key = config
+            .turbopuffer_api_key
+            .clone()
+            .filter(|k| !k.is_empty())
+            .with_context(|| {
+                format!(
+                    "Turbopuffer API key not configured. Run: idx config set-backend turbopuffer --api-key <KEY>, or set {}",
+                    EnvSecret::TurbopufferApiKey.var()
+                )
+            })?;
+        if config.distance_metric == DistanceMetric::Dot {
+            anyhow::bail!(
+                "Turbopuffer supports the l2 and cosine metrics, not dot. Run: idx config set-backend turbopuffer --metric cosine"
+            );
+        }
+
+        Ok(Self {
+            client: config.http_client()?,
+            base_url: config
+                .turbopuffer_base_url
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            prefix: config.turbopuffer_namespace_prefix.clone(),
+            metric: config.distance_metric,
+        })
+    }
+
+    /// Turbopuffer's name for the store's metric, set when a namespace is
+    /// first written.
+    fn distance_metric(&self) -> &'static str {
+        match self.metric {
+            DistanceMetric::Cosine => "cosine_distance",
+            DistanceMetric::L2 | DistanceMetric::Dot => "euclidean_squared",
+        }
+    }
+
+    /// Turbopuffer namespace name for a local namespace.
+    fn remote_namespace(&self, namespace: &str) -> String {
+        format!("{}.{}", self.prefix, sanitize_namespace(namespace))
+    }
+
+    fn namespace_url(&self, namespace: &str) -> String {
+        format!(
+            "{}/v2/namespaces/{}",
+            self.base_url,
+            self.remote_namespace(namespace)
+        )
+    }
+
+    /// Turn a non-success response into an error carrying the body.
+    async fn check(resp: Response, action: &str) -> Result<Response> {
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("Turbopuffer {} failed: {} - {}", action, status, body)
+    }
+}
+
+impl VectorBackend for TurbopufferStore {
+    async fn insert(&self, namespace: &str, records: Vec<VectorRecord>) -> Result<()> {
+        let url = self.namespace_url(namespace);
+
+        for batch in records.chunks(UPSERT_BATCH_SIZE) {
+            let rows: Vec<Row> = batch
+                .iter()
+                .map(|r| Row {
+                    id: &r.chunk_id,
+                    vector: &r.vector,
+                    content_hash: &r.content_hash,
+                    attributes: r.attributes.as_ref(),
+                })
+                .collect();
+
+            debug!(namespace, rows = rows.len(), "upserting to turbopuffer");
+
+            // Distances match LanceDB's for the same metric, so scores are comparable.
+            // Free-text attributes are stored but not indexed for filtering.
+            let resp = self
+                .client
+                .post(&url)
+                .bearer_auth(&self.api_key)
+                .json(&serde_json::json!({
+                    "upsert_rows": rows,
+                    "distance_metric": self.distance_metric(),
+                    "schema": {
+                        "signature": { "type": "string", "filterable": false },
+                        "docstring": { "type": "string", "filterable": false },
+                        "snippet": { "type": "string", "filterable": false },
+                        "storage_key": { "type": "string", "filterable": false },
+                    },
+                }))
+                .send()
+                .await
+                .context("Failed to call Turbopuffer write API")?;
+
+            Self::check(resp, "write").await?;
+        }
+
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        namespace: &str,
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchHit>> {
+        let resp = self
+            .client
+            .post(format!("{}/query", self.namespace_url(namespace)))
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "rank_by": ["vector", "ANN", query_vector],
+                "top_k": limit,
+            }))
+            .send()
+            .await
+            .context("Failed to call Turbopuffer query API")?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+
+        let response: QueryResponse = Self::check(resp, "query")
+            .await?
+            .json()
+            .await
+            .context("Failed to parse Turbopuffer query response")?;
+
+        Ok(response
+            .rows
+            .into_iter()
+            .map(|r| VectorSearchHit {
+                chunk_id: r.id,
+                distance: r.dist,
+                metric: self.metric,
+            })
+            .collect())
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<()> {
+        let resp = self
+            .client
+            .delete(self.namespace_url(namespace))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .context("Failed to call Turbopuffer delete API")?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        Self::check(resp, "delete").await?;
+        Ok(())
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<String>> {
+        let prefix = format!("{}.", self.prefix);
+        let mut namespaces = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut req = self
+                .client
+                .get(format!("{}/v1/namespaces", self.base_url))
+                .bearer_auth(&self.api_key)
+                .query(&[("prefix", prefix.as_str())]);
+            if let Some(c) = &cursor {
+                req = req.query(&[("cursor", c.as_str())]);
+            }
+
+            let resp = req
+                .send()
+                .await
+                .context("Failed to call Turbopuffer namespaces API")?;
+            let page: NamespacesResponse = Self::check(resp, "list namespaces")
+                .await?
+                .json()
+                .await
+                .context("Failed to parse Turbopuffer namespaces response")?;
+
+            namespaces.extend(
+                page.namespaces
+                    .iter()
+                    .filter_map(|ns| ns.id.strip_prefix(&prefix))
+                    .map(unsanitize_namespace),
+            );
+
+            match page.next_cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        Ok(namespaces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::VectorBackendKind;
+
+    fn config() -> LocalConfig {
+        LocalConfig {
+            vector_backend: VectorBackendKind::Turbopuffer,
+            turbopuffer_api_key: Some("tpuf_test".to_string()),
+            turbopuffer_namespace_prefix: "acme".to_string(),
+            ..LocalConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_requires_api_key() {
+        let config = LocalConfig {
+            turbopuffer_api_key: None,
+            ..config()
+        };
+        assert!(TurbopufferStore::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_distance_metric() {
+        let store = TurbopufferStore::from_config(&config()).unwrap();
+        assert_eq!(store.distance_metric(), "euclidean_squared");
+
+        let cosine = LocalConfig {
+            distance_metric: DistanceMetric::Cosine,
+            ..config()
+        };
+        let store = TurbopufferStore::from_config(&cosine).unwrap();
+        assert_eq!(store.distance_metric(), "cosine_distance");
+
+        let dot = LocalConfig {
+            distance_metric: DistanceMetric::Dot,
+            ..config()
+        };
+        assert!(TurbopufferStore::from_config(&dot).is_err());
+    }
+
+    #[test]
+    fn test_remote_namespace() {
+        let store = TurbopufferStore::from_config(&config()).unwrap();
+        assert_eq!(
+            store.remote_namespace("npm/@types/node/20.1.0"),
+            "acme.npm--S----A--types--S--node--S--20.1.0"
+        );
+        assert_eq!(
+            store.namespace_url("pypi/requests/2.31.0"),
+            "https://api.turbopuffer.com/v2/namespaces/acme.pypi--S--requests--S--2.31.0"
+        );
+    }
+
+    #[test]
+    fn test_row_flattens_attributes() {
+        let row = Row {
+            id: "c1",
+            vector: &[0.5],
+            content_hash: "h1",
+            attributes: None,
+        };
+        let json = serde_json::to_value(&row).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "id": "c1", "vector": [0.5], "content_hash": "h1" })
+        );
+    }
+}
@@ -6,6 +6,8 @@ mod indexer;
 mod local;
 mod manifests;
 mod registry;
+#[cfg(test)]
+mod test_support;
 mod types;
 
 use anyhow::Result;
@@ -27,5 +29,6 @@ async fn main() -> Result<()> {
     }
 
     let cli = Cli::parse();
+    local::set_offline(cli.offline);
     cli.command.execute().await
 }
@@ -0,0 +1,392 @@
+//! Bazel module parsing (MODULE.bazel).
+//!
+//! `bazel_dep` modules come from the Bazel Central Registry (BCR), not from
+//! the registries idx indexes, and most of them (C++ libraries, rule sets,
+//! toolchains) have no package counterpart. Only modules listed in
+//! [`KNOWN_MODULES`], whose BCR versions follow a registry package's
+//! releases, are returned; the rest are skipped.
+//!
+//! Module extensions that name registry packages directly are read too:
+//! - `maven.install(artifacts = [...])` and `maven.artifact(...)` from
+//!   rules_jvm_external
+//! - the `requirements_lock` of `pip.parse(...)` from rules_python, read as a
+//!   requirements file
+//!
+//! Go and Rust dependencies managed by `go_deps`/`crate` extensions come
+//! from a `go.mod` or `Cargo.toml`, which discovery finds on its own. Only
+//! literal arguments are read, and legacy `WORKSPACE` files are ignored:
+//! their `http_archive` rules name URLs, not packages.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::Dependency;
+use super::python::{delimited, parse_requirements, string_literals};
+
+/// BCR modules published from the same source as a registry package, with
+/// matching version numbers: (module, registry, package).
+const KNOWN_MODULES: &[(&str, &str, &str)] = &[
+    ("rules_go", "go", "github.com/bazelbuild/rules_go"),
+    ("gazelle", "go", "github.com/bazelbuild/bazel-gazelle"),
+];
+
+/// Parse Bazel dependencies from a directory.
+pub fn parse_bazel_deps(dir: &Path) -> Result<Vec<Dependency>> {
+    let module_path = dir.join("MODULE.bazel");
+    if !module_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = std::fs::read_to_string(&module_path).context("Failed to read MODULE.bazel")?;
+
+    let mut deps = parse_bazel_deps_content(&content);
+    deps.extend(parse_maven_artifacts(&content));
+
+    for lock in pip_requirement_locks(&content) {
+        let Some(path) = label_path(dir, &lock) else {
+            continue;
+        };
+        if !path.exists() {
+            tracing::warn!("Skipping missing pip requirements lock: {}", path.display());
+            continue;
+        }
+        deps.extend(parse_requirements(&path)?);
+    }
+
+    let mut seen = HashSet::new();
+    deps.retain(|d| seen.insert((d.registry.clone(), d.name.clone())));
+
+    Ok(deps)
+}
+
+/// `bazel_dep(name = ..., version = ...)` entries that map to a registry.
+fn parse_bazel_deps_content(content: &str) -> Vec<Dependency> {
+    calls(content, "bazel_dep")
+        .into_iter()
+        .filter_map(|(_, args)| {
+            let module = string_kwarg(args, "name")?;
+            let version = string_kwarg(args, "version")?;
+            let Some((_, registry, name)) = KNOWN_MODULES.iter().find(|(m, _, _)| *m == module)
+            else {
+                tracing::debug!(module, "skipping bazel_dep with no registry package");
+                return None;
+            };
+
+            // Patched BCR releases append `.bcr.N` to the upstream version
+            let version = match version.find(".bcr.") {
+                Some(pos) => &version[..pos],
+                None => &version,
+            };
+            let version = match *registry {
+                "go" => format!("v{}", version.trim_start_matches('v')),
+                _ => version.to_string(),
+            };
+
+            Some(Dependency {
+                registry: registry.to_string(),
+                name: name.to_string(),
+                version,
+            })
+        })
+        .collect()
+}
+
+/// Maven artifacts from rules_jvm_external's `maven` extension.
+fn parse_maven_artifacts(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+
+    for var in extension_vars(content, "@rules_jvm_external", "maven") {
+        for (_, args) in calls(content, &format!("{}.install", var)) {
+            let Some(list) = list_kwarg(args, "artifacts") else {
+                continue;
+            };
+            deps.extend(
+                string_literals(list)
+                    .iter()
+                    .filter_map(|coords| parse_coordinates(coords)),
+            );
+        }
+
+        for (_, args) in calls(content, &format!("{}.artifact", var)) {
+            let (Some(group), Some(artifact), Some(version)) = (
+                string_kwarg(args, "group"),
+                string_kwarg(args, "artifact"),
+                string_kwarg(args, "version"),
+            ) else {
+                continue;
+            };
+            deps.push(Dependency {
+                registry: "maven".to_string(),
+                name: format!("{}:{}", group, artifact),
+                version,
+            });
+        }
+    }
+
+    deps
+}
+
+/// Parse `group:artifact[:packaging[:classifier]]:version` coordinates.
+fn parse_coordinates(coords: &str) -> Option<Dependency> {
+    let parts: Vec<&str> = coords.split(':').collect();
+    if parts.len() < 3 || parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+
+    Some(Dependency {
+        registry: "maven".to_string(),
+        name: format!("{}:{}", parts[0], parts[1]),
+        version: parts[parts.len() - 1].to_string(),
+    })
+}
+
+/// Labels of the requirements locks passed to rules_python's `pip.parse`.
+fn pip_requirement_locks(content: &str) -> Vec<String> {
+    extension_vars(content, "@rules_python", "pip")
+        .iter()
+        .flat_map(|var| calls(content, &format!("{}.parse", var)))
+        .filter_map(|(_, args)| string_kwarg(args, "requirements_lock"))
+        .collect()
+}
+
+/// Resolve a label in the root module (`//pkg:file` or `:file`) to a path.
+/// Labels in other repositories (`@repo//...`) can't be resolved.
+fn label_path(dir: &Path, label: &str) -> Option<PathBuf> {
+    let label = label
+        .strip_prefix("//")
+        .or_else(|| label.starts_with(':').then_some(label))?;
+    let (package, file) = label.split_once(':')?;
+    Some(dir.join(package).join(file))
+}
+
+/// Variables bound to `use_extension("<repo>//...", "<name>")`.
+fn extension_vars(content: &str, repo: &str, name: &str) -> Vec<String> {
+    calls(content, "use_extension")
+        .into_iter()
+        .filter_map(|(start, args)| {
+            let literals = string_literals(args);
+            let matches = literals.len() >= 2
+                && literals[0].starts_with(&format!("{}//", repo))
+                && literals[1] == name;
+            if !matches {
+                return None;
+            }
+
+            // `var = use_extension(...)`
+            let before = content[..start].trim_end().strip_suffix('=')?.trim_end();
+            let var = before
+                .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .next()?;
+            (!var.is_empty()).then(|| var.to_string())
+        })
+        .collect()
+}
+
+/// Find calls to `callee`, returning each call's start offset and its
+/// parenthesized arguments. Calls in comments and strings are ignored.
+fn calls<'a>(content: &'a str, callee: &str) -> Vec<(usize, &'a str)> {
+    let mut found = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut in_comment = false;
+    let mut prev: Option<char> = None;
+
+    for (i, c) in content.char_indices() {
+        if in_comment {
+            in_comment = c != '\n';
+        } else if let Some(q) = quote {
+            if c == q && prev != Some('\\') {
+                quote = None;
+            }
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if c == '#' {
+            in_comment = true;
+        } else if content[i..].starts_with(callee)
+            && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '.')
+        {
+            let rest = content[i + callee.len()..].trim_start();
+            if rest.starts_with('(')
+                && let Some(args) = delimited(rest, '(', ')')
+            {
+                found.push((i, args));
+            }
+        }
+        prev = Some(c);
+    }
+
+    found
+}
+
+/// The string value of `key = "..."` in call arguments.
+fn string_kwarg(args: &str, key: &str) -> Option<String> {
+    let value = kwarg_value(args, key)?;
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let end = value[1..].find(quote)?;
+    Some(value[1..=end].to_string())
+}
+
+/// The `[...]` list value of `key = [...]` in call arguments.
+fn list_kwarg<'a>(args: &'a str, key: &str) -> Option<&'a str> {
+    let value = kwarg_value(args, key)?;
+    value
+        .starts_with('[')
+        .then(|| delimited(value, '[', ']'))
+        .flatten()
+}
+
+/// Text following `key =` in call arguments, for a whole-word `key`.
+fn kwarg_value<'a>(args: &'a str, key: &str) -> Option<&'a str> {
+    let mut offset = 0;
+    while let Some(pos) = args[offset..].find(key) {
+        let start = offset + pos;
+        let end = start + key.len();
+        offset = end;
+
+        let word_start = args[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_alphanumeric() || c == '_'));
+        if !word_start {
+            continue;
+        }
+        let after = args[end..].trim_start();
+        if let Some(value) = after.strip_prefix('=')
+            && !value.starts_with('=')
+        {
+            return Some(value.trim_start());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const MODULE: &str = r#"
+module(name = "acme", version = "1.0")
+
+bazel_dep(name = "rules_go", version = "0.46.0")
+bazel_dep(name = "gazelle", version = "0.35.0.bcr.1", dev_dependency = True)
+bazel_dep(name = "abseil-cpp", version = "20240116.2")
+# bazel_dep(name = "rules_go", version = "0.1.0")
+
+maven = use_extension("@rules_jvm_external//:extensions.bzl", "maven")
+maven.install(
+    artifacts = [
+        "com.google.guava:guava:33.0.0-jre",
+        "junit:junit:jar:4.13.2",  # packaging
+        # "org.example:commented:1.0",
+    ],
+    repositories = ["https://repo1.maven.org/maven2"],
+)
+maven.artifact(
+    group = "org.slf4j",
+    artifact = "slf4j-api",
+    version = "2.0.12",
+)
+use_repo(maven, "maven")
+
+pip = use_extension("@rules_python//python/extensions:pip.bzl", "pip")
+pip.parse(
+    hub_name = "pypi",
+    python_version = "3.11",
+    requirements_lock = "//third_party:requirements_lock.txt",
+)
+"#;
+
+    #[test]
+    fn test_parse_bazel_deps() {
+        let deps = parse_bazel_deps_content(MODULE);
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].registry, "go");
+        assert_eq!(deps[0].name, "github.com/bazelbuild/rules_go");
+        assert_eq!(deps[0].version, "v0.46.0");
+        // BCR patch suffix dropped
+        assert_eq!(deps[1].name, "github.com/bazelbuild/bazel-gazelle");
+        assert_eq!(deps[1].version, "v0.35.0");
+    }
+
+    #[test]
+    fn test_parse_maven_artifacts() {
+        let deps = parse_maven_artifacts(MODULE);
+        let names: Vec<_> = deps
+            .iter()
+            .map(|d| format!("{}@{}", d.name, d.version))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "com.google.guava:guava@33.0.0-jre",
+                "junit:junit@4.13.2",
+                "org.slf4j:slf4j-api@2.0.12",
+            ]
+        );
+        assert!(deps.iter().all(|d| d.registry == "maven"));
+    }
+
+    #[test]
+    fn test_extension_under_another_name() {
+        let content = r#"
+jvm = use_extension("@rules_jvm_external//:extensions.bzl", "maven")
+jvm.install(artifacts = ["io.netty:netty-all:4.1.100.Final"])
+maven.install(artifacts = ["not:bound:1.0"])
+"#;
+        let deps = parse_maven_artifacts(content);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].name, "io.netty:netty-all");
+    }
+
+    #[test]
+    fn test_label_path() {
+        let dir = Path::new("/repo");
+        assert_eq!(
+            label_path(dir, "//:requirements.txt"),
+            Some(dir.join("requirements.txt"))
+        );
+        assert_eq!(
+            label_path(dir, "//third_party:reqs.txt"),
+            Some(dir.join("third_party").join("reqs.txt"))
+        );
+        assert_eq!(label_path(dir, ":reqs.txt"), Some(dir.join("reqs.txt")));
+        assert_eq!(label_path(dir, "@other//:reqs.txt"), None);
+    }
+
+    #[test]
+    fn test_parse_bazel_deps_from_dir() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("MODULE.bazel"), MODULE).unwrap();
+        fs::create_dir_all(tmp.path().join("third_party")).unwrap();
+        fs::write(
+            tmp.path().join("third_party/requirements_lock.txt"),
+            "requests==2.31.0 \\\n    --hash=sha256:abc\nurllib3==2.2.1\n",
+        )
+        .unwrap();
+
+        let deps = parse_bazel_deps(tmp.path()).unwrap();
+        let find = |registry: &str, name: &str| {
+            deps.iter()
+                .find(|d| d.registry == registry && d.name == name)
+                .map(|d| d.version.as_str())
+        };
+
+        assert_eq!(deps.len(), 7);
+        assert_eq!(
+            find("go", "github.com/bazelbuild/rules_go"),
+            Some("v0.46.0")
+        );
+        assert_eq!(find("maven", "com.google.guava:guava"), Some("33.0.0-jre"));
+        assert_eq!(find("pypi", "requests"), Some("2.31.0"));
+        assert_eq!(find("pypi", "urllib3"), Some("2.2.1"));
+    }
+
+    #[test]
+    fn test_no_module_file() {
+        let tmp = TempDir::new().unwrap();
+        assert!(parse_bazel_deps(tmp.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+//! Dart/Flutter manifest parsing (pubspec.yaml, pubspec.lock).
+//!
+//! Only indexes DIRECT dependencies, not transitive.
+//! Uses pinned versions from pubspec.lock if available, otherwise cleans version ranges.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+
+use super::Dependency;
+
+/// Parse Dart dependencies from a directory.
+/// Only returns DIRECT hosted dependencies with resolved versions.
+pub fn parse_dart_deps(dir: &Path) -> Result<Vec<Dependency>> {
+    let pubspec_path = dir.join("pubspec.yaml");
+    if !pubspec_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = std::fs::read_to_string(&pubspec_path).context("Failed to read pubspec.yaml")?;
+    let direct_deps = parse_pubspec(&content)?;
+
+    if direct_deps.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Build version map from pubspec.lock (if exists)
+    let lock_path = dir.join("pubspec.lock");
+    let lock_versions = if lock_path.exists() {
+        std::fs::read_to_string(&lock_path)
+            .ok()
+            .and_then(|c| parse_lock(&c).ok())
+            .unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+
+    // Resolve versions: prefer lockfile, fall back to cleaned manifest version
+    let deps = direct_deps
+        .into_iter()
+        .filter_map(|(name, manifest_version)| {
+            let version = lock_versions
+                .get(&name)
+                .cloned()
+                .or_else(|| manifest_version.as_deref().and_then(clean_version))?;
+
+            Some(Dependency {
+                registry: "pub".to_string(),
+                name,
+                version,
+            })
+        })
+        .collect();
+
+    Ok(deps)
+}
+
+/// Collect direct hosted dependencies (name -> version spec) from pubspec.yaml.
+fn parse_pubspec(content: &str) -> Result<Vec<(String, Option<String>)>> {
+    let yaml: Value = serde_yaml::from_str(content).context("Failed to parse pubspec.yaml")?;
+
+    let mut deps = Vec::new();
+
+    for section in ["dependencies", "dev_dependencies"] {
+        let Some(map) = yaml.get(section).and_then(|d| d.as_mapping()) else {
+            continue;
+        };
+
+        for (name, value) in map {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+
+            let version = match value {
+                Value::String(v) => Some(v.clone()),
+                Value::Null => None,
+                Value::Mapping(m) => {
+                    // Skip sdk/path/git deps (flutter, local packages, forks)
+                    if ["sdk", "path", "git"].iter().any(|k| m.contains_key(*k)) {
+                        continue;
+                    }
+                    m.get("version").and_then(|v| v.as_str()).map(String::from)
+                }
+                _ => continue,
+            };
+
+            deps.push((name.to_string(), version));
+        }
+    }
+
+    Ok(deps)
+}
+
+/// Build a name -> version map of hosted packages from pubspec.lock.
+fn parse_lock(content: &str) -> Result<HashMap<String, String>> {
+    let yaml: Value = serde_yaml::from_str(content).context("Failed to parse pubspec.lock")?;
+
+    let map = yaml
+        .get("packages")
+        .and_then(|p| p.as_mapping())
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|(name, info)| {
+                    if info.get("source").and_then(|s| s.as_str()) != Some("hosted") {
+                        return None;
+                    }
+                    let version = info.get("version").and_then(|v| v.as_str())?;
+                    Some((name.as_str()?.to_string(), version.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(map)
+}
+
+/// Clean a version constraint to a usable version.
+/// "^1.2.3" -> "1.2.3", ">=1.0.0 <2.0.0" -> None, "any" -> None.
+fn clean_version(version: &str) -> Option<String> {
+    let v = version
+        .trim()
+        .trim_start_matches('^')
+        .trim_start_matches('=')
+        .trim_start_matches('>');
+
+    if v.is_empty() || v == "any" || v.contains(' ') || v.contains('<') {
+        return None;
+    }
+
+    Some(v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const PUBSPEC: &str = r#"
+name: my_app
+environment:
+  sdk: ">=3.0.0 <4.0.0"
+
+dependencies:
+  flutter:
+    sdk: flutter
+  provider: ^6.0.0
+  http:
+    hosted: https://pub.dev
+    version: ^1.1.0
+  local_utils:
+    path: ../local_utils
+  collection: any
+
+dev_dependencies:
+  mocktail: ^1.0.0
+"#;
+
+    #[test]
+    fn test_clean_version() {
+        assert_eq!(clean_version("^1.2.3"), Some("1.2.3".to_string()));
+        assert_eq!(clean_version("1.2.3"), Some("1.2.3".to_string()));
+        assert_eq!(clean_version(">=1.0.0 <2.0.0"), None);
+        assert_eq!(clean_version("any"), None);
+    }
+
+    #[test]
+    fn test_pubspec_without_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("pubspec.yaml"), PUBSPEC).unwrap();
+
+        let deps = parse_dart_deps(tmp.path()).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("provider", "6.0.0"),
+                ("http", "1.1.0"),
+                ("mocktail", "1.0.0")
+            ]
+        );
+        assert!(deps.iter().all(|d| d.registry == "pub"));
+    }
+
+    #[test]
+    fn test_pubspec_with_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("pubspec.yaml"), PUBSPEC).unwrap();
+        fs::write(
+            tmp.path().join("pubspec.lock"),
+            r#"
+packages:
+  collection:
+    dependency: "direct main"
+    description:
+      name: collection
+      url: "https://pub.dev"
+    source: hosted
+    version: "1.18.0"
+  provider:
+    dependency: "direct main"
+    description:
+      name: provider
+      url: "https://pub.dev"
+    source: hosted
+    version: "6.1.2"
+  nested:
+    dependency: transitive
+    description:
+      name: nested
+      url: "https://pub.dev"
+    source: hosted
+    version: "1.0.0"
+  local_utils:
+    dependency: "direct main"
+    description:
+      path: "../local_utils"
+      relative: true
+    source: path
+    version: "0.0.1"
+"#,
+        )
+        .unwrap();
+
+        let deps = parse_dart_deps(tmp.path()).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        // Lockfile pins win; transitive and path packages are not indexed
+        assert_eq!(
+            pairs,
+            vec![
+                ("provider", "6.1.2"),
+                ("http", "1.1.0"),
+                ("collection", "1.18.0"),
+                ("mocktail", "1.0.0"),
+            ]
+        );
+    }
+}
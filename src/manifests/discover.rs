@@ -31,6 +31,7 @@ const SKIP_DIRS: &[&str] = &[
     ".cache",
     ".parcel-cache",
     ".turbo",
+    ".dart_tool",
     "coverage",
     ".nyc_output",
     // Virtual envs
@@ -54,7 +55,11 @@ const MANIFEST_FILES: &[&str] = &[
     "go.mod",
     "pyproject.toml",
     "requirements.txt",
+    "setup.cfg",
+    "setup.py",
     "pom.xml",
+    "pubspec.yaml",
+    "MODULE.bazel",
 ];
 
 /// Configuration from `.idx.toml`.
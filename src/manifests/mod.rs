@@ -1,21 +1,61 @@
 //! Manifest file parsing for dependency extraction.
 
+mod bazel;
 mod cargo;
+mod dart;
 mod discover;
 mod go;
 mod maven;
 mod npm;
 mod python;
 
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+pub use bazel::parse_bazel_deps;
 pub use cargo::parse_cargo_deps;
+pub use dart::parse_dart_deps;
 pub use discover::discover_manifest_dirs;
 pub use go::parse_go_deps;
 pub use maven::parse_maven_deps;
-pub use npm::parse_npm_deps;
+pub use npm::{NpmOptions, parse_npm_deps};
 pub use python::parse_python_deps;
 
+/// Dependencies declared by the manifests of every project root under
+/// `root` (see [`discover_manifest_dirs`]), in discovery order and not
+/// deduplicated. Manifests that fail to parse are left out.
+pub fn collect_deps(root: &Path, npm: NpmOptions) -> Result<Vec<Dependency>> {
+    let mut deps = Vec::new();
+    for dir in discover_manifest_dirs(root)? {
+        if let Ok(found) = parse_npm_deps(&dir, npm) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_cargo_deps(&dir) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_python_deps(&dir) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_maven_deps(&dir) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_go_deps(&dir) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_dart_deps(&dir) {
+            deps.extend(found);
+        }
+        if let Ok(found) = parse_bazel_deps(&dir) {
+            deps.extend(found);
+        }
+    }
+    Ok(deps)
+}
+
 /// A dependency extracted from a manifest file.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Dependency {
     pub registry: String,
     pub name: String,
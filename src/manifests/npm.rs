@@ -2,6 +2,7 @@
 //!
 //! Only indexes DIRECT dependencies, not transitive.
 //! Uses pinned versions from package-lock.json if available, otherwise cleans version ranges.
+//! `peerDependencies` and `optionalDependencies` are opt-in via [`NpmOptions`].
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -11,16 +12,26 @@ use serde::Deserialize;
 
 use super::Dependency;
 
+/// Extra `package.json` dependency sections to read alongside
+/// `dependencies` and `devDependencies`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NpmOptions {
+    /// Include `peerDependencies` (e.g. a plugin's `react`).
+    pub include_peer: bool,
+    /// Include `optionalDependencies`.
+    pub include_optional: bool,
+}
+
 /// Parse npm dependencies from a directory.
 /// Only returns DIRECT dependencies with resolved versions.
-pub fn parse_npm_deps(dir: &Path) -> Result<Vec<Dependency>> {
+pub fn parse_npm_deps(dir: &Path, options: NpmOptions) -> Result<Vec<Dependency>> {
     let pkg_path = dir.join("package.json");
     if !pkg_path.exists() {
         return Ok(vec![]);
     }
 
     // Get direct deps from package.json
-    let direct_deps = parse_package_json(&pkg_path)?;
+    let direct_deps = parse_package_json(&pkg_path, options)?;
 
     if direct_deps.is_empty() {
         return Ok(vec![]);
@@ -59,17 +70,29 @@ struct PackageJson {
     dependencies: Option<HashMap<String, String>>,
     #[serde(rename = "devDependencies")]
     dev_dependencies: Option<HashMap<String, String>>,
+    #[serde(rename = "peerDependencies")]
+    peer_dependencies: Option<HashMap<String, String>>,
+    #[serde(rename = "optionalDependencies")]
+    optional_dependencies: Option<HashMap<String, String>>,
 }
 
 /// Parse direct dependencies (name -> version spec) from package.json.
-fn parse_package_json(path: &Path) -> Result<HashMap<String, String>> {
+///
+/// Peer and optional sections are read first, so a package that is also a
+/// regular or dev dependency keeps that (usually narrower) spec.
+fn parse_package_json(path: &Path, options: NpmOptions) -> Result<HashMap<String, String>> {
     let content = std::fs::read_to_string(path).context("Failed to read package.json")?;
     let pkg: PackageJson =
         serde_json::from_str(&content).context("Failed to parse package.json")?;
 
     let mut deps = HashMap::new();
 
-    for map in [pkg.dependencies, pkg.dev_dependencies]
+    let peer = pkg.peer_dependencies.filter(|_| options.include_peer);
+    let optional = pkg
+        .optional_dependencies
+        .filter(|_| options.include_optional);
+
+    for map in [peer, optional, pkg.dependencies, pkg.dev_dependencies]
         .into_iter()
         .flatten()
     {
@@ -166,4 +189,82 @@ mod tests {
         assert_eq!(clean_version(">=1.0.0 <2.0.0"), None);
         assert_eq!(clean_version("*"), None);
     }
+
+    fn write_project(dir: &Path) {
+        std::fs::write(
+            dir.join("package.json"),
+            r#"{
+                "dependencies": { "axios": "^1.6.0" },
+                "devDependencies": { "vitest": "^1.2.0", "react": "18.2.0" },
+                "peerDependencies": { "react": "^17 || ^18", "react-dom": "^17 || ^18" },
+                "optionalDependencies": { "fsevents": "~2.3.2" }
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("package-lock.json"),
+            r#"{
+                "packages": {
+                    "": { "name": "plugin" },
+                    "node_modules/axios": { "version": "1.6.7" },
+                    "node_modules/react-dom": { "version": "18.2.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+    }
+
+    fn versions(deps: Vec<Dependency>) -> Vec<(String, String)> {
+        let mut versions: Vec<_> = deps.into_iter().map(|d| (d.name, d.version)).collect();
+        versions.sort();
+        versions
+    }
+
+    #[test]
+    fn test_peer_and_optional_excluded_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+
+        let deps = parse_npm_deps(dir.path(), NpmOptions::default()).unwrap();
+        assert_eq!(
+            versions(deps),
+            vec![
+                ("axios".to_string(), "1.6.7".to_string()),
+                ("react".to_string(), "18.2.0".to_string()),
+                ("vitest".to_string(), "1.2.0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_peer_and_optional_included_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+
+        let options = NpmOptions {
+            include_peer: true,
+            include_optional: true,
+        };
+        let deps = parse_npm_deps(dir.path(), options).unwrap();
+        assert_eq!(
+            versions(deps),
+            vec![
+                ("axios".to_string(), "1.6.7".to_string()),
+                ("fsevents".to_string(), "2.3.2".to_string()),
+                // The dev dependency's pin wins over the peer range
+                ("react".to_string(), "18.2.0".to_string()),
+                // Peer range resolved against the lockfile
+                ("react-dom".to_string(), "18.2.0".to_string()),
+                ("vitest".to_string(), "1.2.0".to_string()),
+            ]
+        );
+
+        let peer_only = NpmOptions {
+            include_peer: true,
+            include_optional: false,
+        };
+        let deps = parse_npm_deps(dir.path(), peer_only).unwrap();
+        assert!(deps.iter().any(|d| d.name == "react-dom"));
+        assert!(!deps.iter().any(|d| d.name == "fsevents"));
+    }
 }
@@ -1,6 +1,16 @@
-//! Python manifest parsing (pyproject.toml, requirements.txt).
+//! Python manifest parsing (pyproject.toml, requirements.txt, setup.cfg, setup.py).
+//!
+//! Versions pinned in a `uv.lock` or `pdm.lock` are preferred over manifest
+//! ranges, and resolve `pyproject.toml` dependencies that have no version.
+//! Only direct dependencies are returned; locked transitive packages are not.
+//!
+//! `setup.py` is not executed: only literal `install_requires=[...]` and
+//! `extras_require={...: [...]}` lists are read. Requirements computed at
+//! runtime (read from files, built in variables, conditional on the platform)
+//! can't be resolved and are skipped.
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 
@@ -10,10 +20,24 @@ use super::Dependency;
 pub fn parse_python_deps(dir: &Path) -> Result<Vec<Dependency>> {
     let mut deps = Vec::new();
 
+    // Build version map from uv.lock or pdm.lock (if exists)
+    let locked = ["uv.lock", "pdm.lock"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+        .map(|path| {
+            // A broken lockfile falls back to the manifest ranges
+            build_version_map(&path).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable lockfile {}: {:#}", path.display(), e);
+                HashMap::new()
+            })
+        })
+        .unwrap_or_default();
+
     // Try pyproject.toml
     let pyproject_path = dir.join("pyproject.toml");
     if pyproject_path.exists() {
-        deps.extend(parse_pyproject(&pyproject_path)?);
+        deps.extend(parse_pyproject(&pyproject_path, &locked)?);
     }
 
     // Try requirements.txt (may have additional deps)
@@ -22,14 +46,89 @@ pub fn parse_python_deps(dir: &Path) -> Result<Vec<Dependency>> {
         deps.extend(parse_requirements(&requirements_path)?);
     }
 
+    // Legacy setuptools metadata
+    let setup_cfg_path = dir.join("setup.cfg");
+    if setup_cfg_path.exists() {
+        deps.extend(parse_setup_cfg(&setup_cfg_path)?);
+    }
+
+    let setup_py_path = dir.join("setup.py");
+    if setup_py_path.exists() {
+        deps.extend(parse_setup_py(&setup_py_path)?);
+    }
+
+    // Prefer locked versions over manifest ranges
+    for dep in &mut deps {
+        if let Some(version) = locked.get(&normalize_name(&dep.name)) {
+            dep.version = version.clone();
+        }
+    }
+
     // Dedupe by name (prefer pyproject versions)
-    let mut seen = std::collections::HashSet::new();
+    let mut seen = HashSet::new();
     deps.retain(|d| seen.insert(d.name.clone()));
 
     Ok(deps)
 }
 
-fn parse_pyproject(path: &Path) -> Result<Vec<Dependency>> {
+/// Build a normalized name -> version map from a `uv.lock` or `pdm.lock`.
+///
+/// Both list `[[package]]` tables with a name and version. Packages that
+/// aren't from a registry (the project itself, paths, git and URL sources)
+/// are left out. When markers fork the resolution into several versions of
+/// a package, the newest is used.
+fn build_version_map(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let lock: toml::Value = content
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut map: HashMap<String, String> = HashMap::new();
+    let packages = lock.get("package").and_then(|p| p.as_array());
+    for package in packages.into_iter().flatten() {
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(|n| n.as_str()),
+            package.get("version").and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+
+        // uv nests the source in a table, pdm puts it on the package
+        let source = package.get("source").unwrap_or(package);
+        let is_local = ["editable", "virtual", "directory", "path", "git", "url"]
+            .iter()
+            .any(|key| source.get(key).is_some());
+        if is_local {
+            continue;
+        }
+
+        let name = normalize_name(name);
+        let newer = map
+            .get(&name)
+            .is_none_or(|current| release_parts(version) > release_parts(current));
+        if newer {
+            map.insert(name, version.to_string());
+        }
+    }
+
+    Ok(map)
+}
+
+/// Numeric release segments of a version, "2.32.0rc1" -> [2, 32, 0].
+fn release_parts(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Parse PEP 621 and Poetry dependencies, taking versions from `locked`
+/// first.
+fn parse_pyproject(path: &Path, locked: &HashMap<String, String>) -> Result<Vec<Dependency>> {
     let content = std::fs::read_to_string(path).context("Failed to read pyproject.toml")?;
     let toml: toml::Value = content.parse().context("Failed to parse pyproject.toml")?;
 
@@ -42,10 +141,15 @@ fn parse_pyproject(path: &Path) -> Result<Vec<Dependency>> {
         .and_then(|d| d.as_array())
     {
         for dep in project_deps {
-            if let Some(s) = dep.as_str()
-                && let Some(d) = parse_pep508(s)
-            {
-                deps.push(d);
+            let Some((name, version)) = dep.as_str().and_then(parse_requirement_line) else {
+                continue;
+            };
+            if let Some(version) = locked.get(&normalize_name(&name)).cloned().or(version) {
+                deps.push(Dependency {
+                    registry: "pypi".to_string(),
+                    name,
+                    version,
+                });
             }
         }
     }
@@ -68,7 +172,11 @@ fn parse_pyproject(path: &Path) -> Result<Vec<Dependency>> {
                 }
                 _ => None,
             };
-            if let Some(v) = version.and_then(|v| clean_version(&v)) {
+            let version = locked
+                .get(&normalize_name(name))
+                .cloned()
+                .or_else(|| version.and_then(|v| clean_version(&v)));
+            if let Some(v) = version {
                 deps.push(Dependency {
                     registry: "pypi".to_string(),
                     name: name.clone(),
@@ -81,20 +189,345 @@ fn parse_pyproject(path: &Path) -> Result<Vec<Dependency>> {
     Ok(deps)
 }
 
-fn parse_requirements(path: &Path) -> Result<Vec<Dependency>> {
-    let content = std::fs::read_to_string(path).context("Failed to read requirements.txt")?;
+pub(super) fn parse_requirements(path: &Path) -> Result<Vec<Dependency>> {
+    let mut requirements = Vec::new();
+    let mut constraints = HashMap::new();
+    let mut visited = HashSet::new();
+    collect_requirements(
+        path,
+        false,
+        &mut requirements,
+        &mut constraints,
+        &mut visited,
+    )?;
 
-    let deps = content
-        .lines()
-        .filter(|l| {
-            !l.trim().is_empty() && !l.trim().starts_with('#') && !l.trim().starts_with('-')
+    // Unpinned requirements take their version from a constraints file, if any
+    let deps = requirements
+        .into_iter()
+        .filter_map(|(name, version)| {
+            let version = version.or_else(|| constraints.get(&normalize_name(&name)).cloned())?;
+            Some(Dependency {
+                registry: "pypi".to_string(),
+                name,
+                version,
+            })
         })
-        .filter_map(parse_pep508)
         .collect();
 
     Ok(deps)
 }
 
+/// Walk a requirements file, following `-r` includes and `-c` constraint files.
+///
+/// Lines from constraint files only pin versions; they never add requirements.
+fn collect_requirements(
+    path: &Path,
+    is_constraints: bool,
+    requirements: &mut Vec<(String, Option<String>)>,
+    constraints: &mut HashMap<String, String>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<()> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    for line in logical_lines(&content) {
+        let line = strip_comment(&line).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+
+        let include = option_value(&line, "-r", "--requirement")
+            .map(|f| (f, is_constraints))
+            .or_else(|| option_value(&line, "-c", "--constraint").map(|f| (f, true)));
+        if let Some((file, as_constraints)) = include {
+            let include_path = base.join(file);
+            if !include_path.exists() {
+                tracing::warn!(
+                    "Skipping missing requirements include: {}",
+                    include_path.display()
+                );
+                continue;
+            }
+            collect_requirements(
+                &include_path,
+                as_constraints,
+                requirements,
+                constraints,
+                visited,
+            )?;
+            continue;
+        }
+
+        // Editable installs, index options, etc.
+        if line.starts_with('-') {
+            continue;
+        }
+
+        let Some((name, version)) = parse_requirement_line(&line) else {
+            continue;
+        };
+
+        if is_constraints {
+            if let Some(v) = version {
+                constraints.insert(normalize_name(&name), v);
+            }
+        } else {
+            requirements.push((name, version));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse `[options] install_requires` and `[options.extras_require]` from setup.cfg.
+fn parse_setup_cfg(path: &Path) -> Result<Vec<Dependency>> {
+    let content = std::fs::read_to_string(path).context("Failed to read setup.cfg")?;
+
+    let mut specs = Vec::new();
+    let mut section = String::new();
+    let mut in_requires = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        // Indented lines continue the previous key's value
+        if line.starts_with([' ', '\t']) {
+            if in_requires {
+                specs.push(strip_comment(trimmed).trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            section = name.trim().to_string();
+            in_requires = false;
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(['=', ':']) else {
+            in_requires = false;
+            continue;
+        };
+
+        in_requires = match section.as_str() {
+            "options" => key.trim() == "install_requires",
+            "options.extras_require" => true,
+            _ => false,
+        };
+        if in_requires {
+            specs.push(strip_comment(value).trim().to_string());
+        }
+    }
+
+    Ok(pinned_deps(specs.iter().map(String::as_str)))
+}
+
+/// Best-effort extraction of literal requirement lists from setup.py.
+fn parse_setup_py(path: &Path) -> Result<Vec<Dependency>> {
+    let content = std::fs::read_to_string(path).context("Failed to read setup.py")?;
+
+    let mut specs = Vec::new();
+
+    if let Some(list) = keyword_value(&content, "install_requires", '[', ']') {
+        specs.extend(string_literals(list));
+    }
+
+    // Only strings inside the per-extra lists, not the dict keys
+    if let Some(extras) = keyword_value(&content, "extras_require", '{', '}') {
+        let mut rest = extras;
+        while let Some(start) = rest.find('[') {
+            let Some(list) = delimited(&rest[start..], '[', ']') else {
+                break;
+            };
+            specs.extend(string_literals(list));
+            rest = &rest[start + list.len()..];
+        }
+    }
+
+    Ok(pinned_deps(specs.iter().map(String::as_str)))
+}
+
+/// Turn requirement specs into dependencies, dropping unpinned ones.
+fn pinned_deps<'a>(specs: impl Iterator<Item = &'a str>) -> Vec<Dependency> {
+    specs
+        .filter(|s| !s.is_empty())
+        .filter_map(parse_requirement_line)
+        .filter_map(|(name, version)| {
+            Some(Dependency {
+                registry: "pypi".to_string(),
+                name,
+                version: version?,
+            })
+        })
+        .collect()
+}
+
+/// Find `keyword = <open>...<close>` and return the delimited text.
+///
+/// Returns `None` when the value isn't a literal (e.g. a variable).
+fn keyword_value<'a>(src: &'a str, keyword: &str, open: char, close: char) -> Option<&'a str> {
+    let mut search = src;
+    while let Some(pos) = search.find(keyword) {
+        let after = search[pos + keyword.len()..].trim_start();
+        if let Some(value) = after.strip_prefix('=')
+            && value.trim_start().starts_with(open)
+        {
+            return delimited(value.trim_start(), open, close);
+        }
+        search = &search[pos + keyword.len()..];
+    }
+    None
+}
+
+/// Given text starting with `open`, return it up to the matching `close`,
+/// skipping brackets inside string literals and comments.
+pub(super) fn delimited(src: &str, open: char, close: char) -> Option<&str> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut in_comment = false;
+
+    for (i, c) in src.char_indices() {
+        if in_comment {
+            in_comment = c != '\n';
+            continue;
+        }
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => in_comment = true,
+            None if c == open => depth += 1,
+            None if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&src[..=i]);
+                }
+            }
+            None => {}
+        }
+    }
+    None
+}
+
+/// Collect simple quoted string literals, ignoring comments.
+pub(super) fn string_literals(src: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut current: Option<(char, String)> = None;
+    let mut in_comment = false;
+
+    for c in src.chars() {
+        if in_comment {
+            in_comment = c != '\n';
+            continue;
+        }
+        match &mut current {
+            Some((q, s)) if c == *q => {
+                literals.push(std::mem::take(s));
+                current = None;
+            }
+            Some((_, s)) => s.push(c),
+            None if c == '"' || c == '\'' => current = Some((c, String::new())),
+            None if c == '#' => in_comment = true,
+            None => {}
+        }
+    }
+
+    literals
+}
+
+/// Join backslash-continued lines into single logical lines.
+fn logical_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        if let Some(head) = line.trim_end().strip_suffix('\\') {
+            current.push_str(head);
+            current.push(' ');
+        } else {
+            current.push_str(line);
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Strip a `#` comment that starts the line or follows whitespace.
+fn strip_comment(line: &str) -> &str {
+    let mut prev_is_space = true;
+    for (i, c) in line.char_indices() {
+        if c == '#' && prev_is_space {
+            return &line[..i];
+        }
+        prev_is_space = c.is_whitespace();
+    }
+    line
+}
+
+/// Get the argument of an option line like `-r file`, `-rfile` or `--requirement=file`.
+fn option_value<'a>(line: &'a str, short: &str, long: &str) -> Option<&'a str> {
+    let rest = if let Some(rest) = line.strip_prefix(long) {
+        rest.strip_prefix('=').unwrap_or(rest)
+    } else {
+        line.strip_prefix(short)?
+    };
+
+    let value = rest.trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Parse a requirement line into (name, pinned version).
+///
+/// Hashes and other per-requirement options are dropped. URL and VCS
+/// requirements return `None` since they have no registry version.
+fn parse_requirement_line(line: &str) -> Option<(String, Option<String>)> {
+    // Per-requirement options (--hash, --config-settings, ...) follow the spec
+    let spec = match line.find(" --") {
+        Some(pos) => &line[..pos],
+        None => line,
+    };
+    let spec = spec.split(';').next()?.trim(); // Remove env markers
+
+    if spec.contains("://")
+        || spec.contains(" @ ")
+        || spec.starts_with('.')
+        || spec.starts_with('/')
+    {
+        return None;
+    }
+
+    if let Some(dep) = parse_pep508(spec) {
+        return Some((dep.name, Some(dep.version)));
+    }
+
+    // Bare or loosely constrained name, e.g. "requests" or "requests<3"
+    let name = spec.split(|c: char| "<>=!~[( ".contains(c)).next()?.trim();
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+
+    is_name.then(|| (name.to_string(), None))
+}
+
+/// Normalize a package name per PEP 503 for comparisons.
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
 /// Parse PEP 508 dependency spec: "package==1.2.3" or "package>=1.2.3"
 fn parse_pep508(spec: &str) -> Option<Dependency> {
     let spec = spec.split(';').next()?.trim(); // Remove env markers
@@ -136,6 +569,8 @@ fn clean_version(version: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
+    use tempfile::TempDir;
 
     #[test]
     fn test_parse_pep508() {
@@ -151,4 +586,342 @@ mod tests {
         assert_eq!(dep.name, "torch");
         assert_eq!(dep.version, "2.0.0");
     }
+
+    #[test]
+    fn test_requirements_includes_and_constraints() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        fs::create_dir_all(root.join("requirements")).unwrap();
+        fs::write(
+            root.join("requirements/base.txt"),
+            "requests==2.31.0\n-c constraints.txt\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("requirements/constraints.txt"),
+            "flask==3.0.0\nunused==1.0.0\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("requirements.txt"),
+            "-r requirements/base.txt\n--requirement=requirements/base.txt\nFlask\n",
+        )
+        .unwrap();
+
+        let deps = parse_python_deps(root).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        assert_eq!(pairs, vec![("requests", "2.31.0"), ("Flask", "3.0.0")]);
+    }
+
+    #[test]
+    fn test_requirements_hashes_markers_and_skips() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        fs::write(
+            root.join("requirements.txt"),
+            "# pinned deps\n\
+             --index-url https://pypi.org/simple\n\
+             certifi==2024.2.2 \\\n    --hash=sha256:abc \\\n    --hash=sha256:def\n\
+             pywin32==306 ; sys_platform == 'win32'  # windows only\n\
+             -e .\n\
+             git+https://github.com/psf/black.git#egg=black\n\
+             pkg @ https://example.com/pkg-1.0.tar.gz\n\
+             ./local/wheel.whl\n",
+        )
+        .unwrap();
+
+        let deps = parse_python_deps(root).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        assert_eq!(pairs, vec![("certifi", "2024.2.2"), ("pywin32", "306")]);
+    }
+
+    fn pairs(deps: &[Dependency]) -> Vec<(&str, &str)> {
+        deps.iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_uv_lock_versions() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        fs::write(
+            root.join("pyproject.toml"),
+            r#"
+[project]
+name = "app"
+dependencies = [
+    "requests>=2.31",
+    "httpx[http2]",
+    "numpy",
+    "pywin32; sys_platform == 'win32'",
+    "unlocked",
+]
+
+[project.optional-dependencies]
+cli = ["rich"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("uv.lock"),
+            r#"
+version = 1
+requires-python = ">=3.9"
+resolution-markers = [
+    "python_full_version >= '3.10'",
+    "python_full_version < '3.10'",
+]
+
+[[package]]
+name = "app"
+version = "0.1.0"
+source = { editable = "." }
+dependencies = [
+    { name = "httpx", extra = ["http2"] },
+    { name = "numpy", version = "2.0.2", source = { registry = "https://pypi.org/simple" }, marker = "python_full_version < '3.10'" },
+    { name = "numpy", version = "2.1.3", source = { registry = "https://pypi.org/simple" }, marker = "python_full_version >= '3.10'" },
+    { name = "pywin32", marker = "sys_platform == 'win32'" },
+    { name = "requests" },
+]
+
+[package.optional-dependencies]
+cli = [
+    { name = "rich" },
+]
+
+[[package]]
+name = "httpx"
+version = "0.27.2"
+source = { registry = "https://pypi.org/simple" }
+
+[package.optional-dependencies]
+http2 = [
+    { name = "h2" },
+]
+
+[[package]]
+name = "numpy"
+version = "2.0.2"
+source = { registry = "https://pypi.org/simple" }
+resolution-markers = [
+    "python_full_version < '3.10'",
+]
+
+[[package]]
+name = "numpy"
+version = "2.1.3"
+source = { registry = "https://pypi.org/simple" }
+resolution-markers = [
+    "python_full_version >= '3.10'",
+]
+
+[[package]]
+name = "pywin32"
+version = "308"
+source = { registry = "https://pypi.org/simple" }
+
+[[package]]
+name = "Requests"
+version = "2.32.3"
+source = { registry = "https://pypi.org/simple" }
+
+[[package]]
+name = "rich"
+version = "13.9.4"
+source = { git = "https://github.com/Textualize/rich?rev=abc#abc" }
+
+[[package]]
+name = "h2"
+version = "4.1.0"
+source = { registry = "https://pypi.org/simple" }
+"#,
+        )
+        .unwrap();
+
+        let deps = parse_python_deps(root).unwrap();
+        assert_eq!(
+            pairs(&deps),
+            vec![
+                ("requests", "2.32.3"),
+                ("httpx", "0.27.2"),
+                ("numpy", "2.1.3"),
+                ("pywin32", "308"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pdm_lock_versions() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        fs::write(
+            root.join("pyproject.toml"),
+            r#"
+[project]
+name = "app"
+dependencies = ["requests[socks]~=2.31", "Django"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("pdm.lock"),
+            r#"
+[metadata]
+groups = ["default"]
+strategy = ["inherit_metadata"]
+lock_version = "4.5.0"
+
+[[package]]
+name = "django"
+version = "5.1.2"
+requires_python = ">=3.10"
+groups = ["default"]
+
+[[package]]
+name = "requests"
+version = "2.32.3"
+groups = ["default"]
+
+[[package]]
+name = "requests"
+version = "2.32.3"
+extras = ["socks"]
+groups = ["default"]
+marker = "python_version >= \"3.8\""
+dependencies = [
+    "PySocks!=1.5.7,>=1.5.6",
+    "requests==2.32.3",
+]
+
+[[package]]
+name = "local-lib"
+version = "0.0.1"
+path = "./libs/local"
+"#,
+        )
+        .unwrap();
+        fs::write(root.join("requirements.txt"), "local-lib==0.0.2\n").unwrap();
+
+        let deps = parse_python_deps(root).unwrap();
+        assert_eq!(
+            pairs(&deps),
+            vec![
+                ("requests", "2.32.3"),
+                ("Django", "5.1.2"),
+                ("local-lib", "0.0.2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setup_cfg() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("setup.cfg"),
+            "[metadata]\n\
+             name = legacy\n\
+             \n\
+             [options]\n\
+             packages = find:\n\
+             install_requires =\n\
+             \x20   requests>=2.25.0\n\
+             \x20   click==8.1.7  # cli\n\
+             \x20   six\n\
+             \x20   pywin32==306; sys_platform == \"win32\"\n\
+             python_requires = >=3.8\n\
+             \n\
+             [options.extras_require]\n\
+             test = pytest==7.4.0\n\
+             docs =\n\
+             \x20   sphinx[docs]==7.2.6\n",
+        )
+        .unwrap();
+
+        let deps = parse_python_deps(tmp.path()).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("requests", "2.25.0"),
+                ("click", "8.1.7"),
+                ("pywin32", "306"),
+                ("pytest", "7.4.0"),
+                ("sphinx", "7.2.6"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setup_py() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("setup.py"),
+            r#"
+from setuptools import setup
+
+requirements = open("requirements.in").read().splitlines()
+
+setup(
+    name="legacy",
+    install_requires=[
+        "numpy>=1.21.0",
+        'attrs==23.1.0 ; python_version >= "3.8"',
+        # "commented==1.0.0",
+        "unpinned",
+    ],
+    extras_require={
+        "cli": ["click==8.1.7"],
+        'yaml': ['pyyaml>=6.0'],
+    },
+    tests_require=requirements,
+)
+"#,
+        )
+        .unwrap();
+
+        let deps = parse_python_deps(tmp.path()).unwrap();
+        let pairs: Vec<_> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("numpy", "1.21.0"),
+                ("attrs", "23.1.0"),
+                ("click", "8.1.7"),
+                ("pyyaml", "6.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setup_py_dynamic_requirements_are_skipped() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("setup.py"),
+            "setup(name='dyn', install_requires=read_requirements('requirements.in'))\n",
+        )
+        .unwrap();
+
+        assert!(parse_python_deps(tmp.path()).unwrap().is_empty());
+    }
 }
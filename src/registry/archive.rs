@@ -0,0 +1,53 @@
+//! Checks on archive entries before they are read.
+//!
+//! Entries are only read into memory, but their paths become the file
+//! paths of chunks and stored source files, so an entry must not point
+//! outside the package.
+
+use tracing::warn;
+
+/// Whether the archive entry at `path` may be read.
+///
+/// Absolute paths, `..` components and links (symbolic or hard) are
+/// skipped with a warning; a package archive has no reason to contain them.
+/// Both `/` and `\` count as separators, since zips built on Windows may
+/// use either.
+pub fn is_safe_entry(path: &str, is_link: bool) -> bool {
+    if is_link {
+        warn!(path, "skipping link entry in archive");
+        return false;
+    }
+
+    let mut components = path.split(['/', '\\']);
+    // Drive letters (`C:`) make a path absolute too
+    let is_absolute = path.starts_with(['/', '\\'])
+        || components
+            .clone()
+            .next()
+            .is_some_and(|first| first.ends_with(':'));
+    if is_absolute || components.any(|c| c == "..") {
+        warn!(path, "skipping archive entry outside the package");
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_safe_entry() {
+        assert!(is_safe_entry("package/lib/index.js", false));
+        assert!(is_safe_entry("./lib/provider.dart", false));
+        assert!(is_safe_entry("src/a..b/mod.rs", false));
+
+        assert!(!is_safe_entry("../../etc/passwd", false));
+        assert!(!is_safe_entry("package/../../etc/passwd", false));
+        assert!(!is_safe_entry("pkg\\..\\..\\evil.py", false));
+        assert!(!is_safe_entry("/etc/passwd", false));
+        assert!(!is_safe_entry("C:\\Windows\\evil.java", false));
+        assert!(!is_safe_entry("package/lib/link.js", true));
+    }
+}
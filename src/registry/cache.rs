@@ -0,0 +1,382 @@
+//! On-disk cache for registry responses.
+//!
+//! Entries live under `<dir>/<kind>/<hash>.json`, keyed by
+//! `(registry, name, version)`. Each entry records when it was fetched and
+//! is ignored once older than the TTL. When the cache grows past its size
+//! cap, the least recently used entries are evicted (file mtime, which is
+//! bumped on every hit).
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use super::error::RegistryError;
+
+/// Default time before a cached response is refetched.
+pub const DEFAULT_CACHE_TTL_HOURS: u64 = 24;
+
+/// Default cap on the total size of the cache directory.
+pub const DEFAULT_CACHE_MAX_MB: u64 = 512;
+
+/// What a cache entry holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// `get_package` metadata
+    Package,
+    /// `get_version` metadata
+    Version,
+    /// Extracted source files from `download_source`
+    Source,
+}
+
+impl CacheKind {
+    const ALL: [CacheKind; 3] = [Self::Package, Self::Version, Self::Source];
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::Package => "packages",
+            Self::Version => "versions",
+            Self::Source => "sources",
+        }
+    }
+}
+
+/// Disk-backed cache of registry responses with a TTL and LRU size cap.
+#[derive(Debug, Clone)]
+pub struct RegistryCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+    cache_sources: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    fetched_at: DateTime<Utc>,
+    value: T,
+}
+
+impl RegistryCache {
+    /// Create a cache rooted at `dir` with default TTL and size cap.
+    ///
+    /// Downloaded sources are not cached unless enabled with
+    /// [`with_sources`](Self::with_sources).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: Duration::from_secs(DEFAULT_CACHE_TTL_HOURS * 3600),
+            max_bytes: DEFAULT_CACHE_MAX_MB * 1024 * 1024,
+            cache_sources: false,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_sources(mut self, enabled: bool) -> Self {
+        self.cache_sources = enabled;
+        self
+    }
+
+    /// Whether downloaded sources are cached, not just metadata.
+    pub fn caches_sources(&self) -> bool {
+        self.cache_sources
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the cached value for a key, or run `fetch` and cache its result.
+    ///
+    /// `fetch` is only awaited on a miss, so a hit makes no network call.
+    /// Errors are never cached.
+    pub async fn fetch<T, F>(
+        &self,
+        kind: CacheKind,
+        registry: &str,
+        name: &str,
+        version: &str,
+        fetch: F,
+    ) -> Result<T, RegistryError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T, RegistryError>>,
+    {
+        let path = self.entry_path(kind, registry, name, version);
+
+        if let Some(value) = self.read(&path).await {
+            debug!(registry, name, version, ?kind, "registry cache hit");
+            return Ok(value);
+        }
+
+        let value = fetch.await?;
+        if let Err(e) = self.write(&path, &value).await {
+            warn!(error = %e, "failed to write registry cache entry");
+        }
+        Ok(value)
+    }
+
+    /// Remove every cached entry.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn entry_path(&self, kind: CacheKind, registry: &str, name: &str, version: &str) -> PathBuf {
+        let hash = hex::encode(Sha256::digest(format!(
+            "{}\0{}\0{}",
+            registry, name, version
+        )));
+        self.dir
+            .join(kind.dir_name())
+            .join(format!("{}.json", hash))
+    }
+
+    async fn read<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let content = tokio::fs::read(path).await.ok()?;
+        let entry: Entry<T> = serde_json::from_slice(&content).ok()?;
+
+        let age = (Utc::now() - entry.fetched_at).to_std().unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+
+        // Mark as recently used for eviction
+        if let Ok(file) = std::fs::File::options().write(true).open(path) {
+            file.set_modified(SystemTime::now()).ok();
+        }
+
+        Some(entry.value)
+    }
+
+    async fn write<T: Serialize>(&self, path: &Path, value: &T) -> std::io::Result<()> {
+        let content = serde_json::to_vec(&Entry {
+            fetched_at: Utc::now(),
+            value,
+        })?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write then rename, so concurrent readers never see a partial entry
+        let tmp = path.with_extension(format!("tmp.{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp, content).await?;
+        tokio::fs::rename(&tmp, path).await?;
+
+        let cache = self.clone();
+        tokio::task::spawn_blocking(move || cache.evict())
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    /// Delete least recently used entries until the cache fits its cap.
+    fn evict(&self) -> std::io::Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+
+        for kind in CacheKind::ALL {
+            let Ok(dir) = std::fs::read_dir(self.dir.join(kind.dir_name())) else {
+                continue;
+            };
+            for entry in dir.flatten() {
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if !meta.is_file() {
+                    continue;
+                }
+                total += meta.len();
+                let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push((used, meta.len(), entry.path()));
+            }
+        }
+
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|(used, _, _)| *used);
+        for (_, len, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            debug!(path = %path.display(), "evicting registry cache entry");
+            match std::fs::remove_file(&path) {
+                Ok(()) => total -= len,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => total -= len,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Fetch that counts how often the "network" is hit.
+    async fn counted(calls: &AtomicUsize, value: &str) -> Result<String, RegistryError> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(value.to_string())
+    }
+
+    fn set_used(cache: &RegistryCache, name: &str, ago_secs: u64) {
+        let path = cache.entry_path(CacheKind::Version, "npm", name, "1.0.0");
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(ago_secs))
+            .unwrap();
+    }
+
+    fn is_cached(cache: &RegistryCache, name: &str) -> bool {
+        cache
+            .entry_path(CacheKind::Version, "npm", name, "1.0.0")
+            .exists()
+    }
+
+    #[tokio::test]
+    async fn test_second_fetch_hits_cache() {
+        let dir = TempDir::new().unwrap();
+        let cache = RegistryCache::new(dir.path());
+        let calls = AtomicUsize::new(0);
+
+        let first = cache
+            .fetch(CacheKind::Version, "npm", "lodash", "4.17.21", async {
+                counted(&calls, "fresh").await
+            })
+            .await
+            .unwrap();
+        let second = cache
+            .fetch(CacheKind::Version, "npm", "lodash", "4.17.21", async {
+                counted(&calls, "refetched").await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(first, "fresh");
+        assert_eq!(second, "fresh");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A different key is a miss
+        cache
+            .fetch(CacheKind::Version, "npm", "lodash", "4.17.20", async {
+                counted(&calls, "other").await
+            })
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_refetched() {
+        let dir = TempDir::new().unwrap();
+        let cache = RegistryCache::new(dir.path()).with_ttl(Duration::ZERO);
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            cache
+                .fetch(CacheKind::Package, "pypi", "requests", "", async {
+                    counted(&calls, "info").await
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let dir = TempDir::new().unwrap();
+        let cache = RegistryCache::new(dir.path());
+
+        let result: Result<String, _> = cache
+            .fetch(CacheKind::Package, "npm", "ghost", "", async {
+                Err(RegistryError::PackageNotFound("ghost".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(
+            !cache
+                .entry_path(CacheKind::Package, "npm", "ghost", "")
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let dir = TempDir::new().unwrap();
+        let calls = AtomicUsize::new(0);
+        let value = "x".repeat(100);
+
+        // Size one entry to set a cap that fits two but not three
+        let probe = RegistryCache::new(dir.path().join("probe"));
+        probe
+            .fetch(CacheKind::Version, "npm", "a", "1.0.0", async {
+                counted(&calls, &value).await
+            })
+            .await
+            .unwrap();
+        let entry_len =
+            std::fs::metadata(probe.entry_path(CacheKind::Version, "npm", "a", "1.0.0"))
+                .unwrap()
+                .len();
+
+        let cache = RegistryCache::new(dir.path().join("cache")).with_max_bytes(entry_len * 5 / 2);
+        for name in ["a", "b"] {
+            cache
+                .fetch(CacheKind::Version, "npm", name, "1.0.0", async {
+                    counted(&calls, &value).await
+                })
+                .await
+                .unwrap();
+        }
+        set_used(&cache, "a", 100);
+        set_used(&cache, "b", 50);
+
+        // Reading `a` makes `b` the least recently used
+        cache
+            .fetch(CacheKind::Version, "npm", "a", "1.0.0", async {
+                counted(&calls, &value).await
+            })
+            .await
+            .unwrap();
+        cache
+            .fetch(CacheKind::Version, "npm", "c", "1.0.0", async {
+                counted(&calls, &value).await
+            })
+            .await
+            .unwrap();
+
+        assert!(is_cached(&cache, "a"));
+        assert!(!is_cached(&cache, "b"));
+        assert!(is_cached(&cache, "c"));
+    }
+
+    #[test]
+    fn test_clear_missing_dir() {
+        let dir = TempDir::new().unwrap();
+        let cache = RegistryCache::new(dir.path().join("cache"));
+        assert!(cache.clear().is_ok());
+    }
+}
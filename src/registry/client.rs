@@ -1,5 +1,8 @@
-//! Registry client trait and common types.
+//! Registry client trait, common types, and the shared HTTP client builder.
 
+use std::path::PathBuf;
+
+use reqwest::{Certificate, Client, NoProxy, Proxy};
 use serde::{Deserialize, Serialize};
 
 use super::error::RegistryError;
@@ -24,15 +27,30 @@ pub struct VersionInfo {
     pub repository: Option<String>,
     pub license: Option<String>,
     pub tarball_url: String,
+    /// Module the manifest declares as the package's entry point (npm
+    /// `exports`, `module` or `main`), relative to the package root
+    #[serde(default)]
+    pub entry_point: Option<String>,
 }
 
 /// A file extracted from a package.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageFile {
     pub path: String,
     pub content: String,
+    /// Part of the package's test suite. Registries return test files so
+    /// they can be indexed as usage examples when `index_tests` is on.
+    #[serde(default)]
+    pub is_test: bool,
+    /// Third-party code bundled into the package (e.g. `pip/_vendor/`).
+    /// Skipped at index time unless `index_vendored` is on.
+    #[serde(default)]
+    pub is_vendored: bool,
 }
 
+/// Version spec that resolves to the registry's latest published version.
+pub const LATEST_VERSION: &str = "latest";
+
 /// Trait for registry clients.
 ///
 /// Each registry (npm, pypi, crates, etc.) implements this trait
@@ -63,3 +81,202 @@ pub trait RegistryClient: Send + Sync {
 }
 
 use std::future::Future;
+
+/// Resolve a requested version, looking up the latest release when the
+/// version is omitted or `"latest"`.
+pub async fn resolve_version(
+    client: &impl RegistryClient,
+    name: &str,
+    version: Option<&str>,
+) -> Result<String, RegistryError> {
+    match version.map(str::trim) {
+        Some(v) if !v.is_empty() && !v.eq_ignore_ascii_case(LATEST_VERSION) => Ok(v.to_string()),
+        _ => client
+            .get_package(name)
+            .await?
+            .latest_version
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| RegistryError::NoPublishedVersion(name.to_string())),
+    }
+}
+
+/// User agent sent to every registry (crates.io rejects requests without one).
+pub const USER_AGENT: &str = "index-registry/0.1.0 (https://github.com/yourusername/index)";
+
+/// Proxy and TLS settings for outgoing HTTP requests.
+///
+/// With no explicit `proxy`, the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY`
+/// and `NO_PROXY` environment variables (or their lowercase forms) are
+/// honored. An explicit `proxy` replaces them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// Proxy URL for all requests, e.g. `http://proxy.corp:8080`.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts reached directly when `proxy` is set. Falls back
+    /// to `NO_PROXY` when unset.
+    pub no_proxy: Option<String>,
+    /// PEM file of extra root certificates to trust, e.g. a corporate CA.
+    pub ca_bundle: Option<PathBuf>,
+}
+
+/// Build an HTTP client with `options` applied.
+pub fn http_client(options: &HttpOptions) -> Result<Client, RegistryError> {
+    let mut builder = Client::builder().user_agent(USER_AGENT);
+
+    if let Some(url) = &options.proxy {
+        let no_proxy = match &options.no_proxy {
+            Some(hosts) => NoProxy::from_string(hosts),
+            None => NoProxy::from_env(),
+        };
+        let proxy = Proxy::all(url)
+            .map_err(|e| RegistryError::HttpConfig(format!("invalid proxy {}: {}", url, e)))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = &options.ca_bundle {
+        let pem = std::fs::read(path).map_err(|e| {
+            RegistryError::HttpConfig(format!("can't read CA bundle {}: {}", path.display(), e))
+        })?;
+        let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            RegistryError::HttpConfig(format!("invalid CA bundle {}: {}", path.display(), e))
+        })?;
+        if certs.is_empty() {
+            return Err(RegistryError::HttpConfig(format!(
+                "no certificates in CA bundle {}",
+                path.display()
+            )));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| RegistryError::HttpConfig(e.to_string()))
+}
+
+/// HTTP client with default options, for clients built without config.
+pub(crate) fn default_http_client() -> Client {
+    http_client(&HttpOptions::default()).expect("failed to build http client")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Registry stub that only answers `get_package`.
+    struct StubClient {
+        latest: Option<&'static str>,
+    }
+
+    impl RegistryClient for StubClient {
+        async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
+            Ok(PackageInfo {
+                name: name.to_string(),
+                description: None,
+                repository: None,
+                license: None,
+                versions: self.latest.iter().map(|v| v.to_string()).collect(),
+                latest_version: self.latest.map(str::to_string),
+            })
+        }
+
+        async fn get_version(&self, _: &str, _: &str) -> Result<VersionInfo, RegistryError> {
+            unimplemented!()
+        }
+
+        async fn download_source(
+            &self,
+            _: &str,
+            _: &str,
+        ) -> Result<Vec<PackageFile>, RegistryError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_version_latest() {
+        let client = StubClient {
+            latest: Some("4.17.21"),
+        };
+
+        for requested in [None, Some("latest"), Some("LATEST"), Some("")] {
+            let version = resolve_version(&client, "lodash", requested).await.unwrap();
+            assert_eq!(version, "4.17.21");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_version_explicit() {
+        let client = StubClient {
+            latest: Some("4.17.21"),
+        };
+        let version = resolve_version(&client, "lodash", Some("3.10.1"))
+            .await
+            .unwrap();
+        assert_eq!(version, "3.10.1");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_version_unpublished() {
+        let client = StubClient { latest: None };
+        let err = resolve_version(&client, "ghost", None).await.unwrap_err();
+        assert!(matches!(err, RegistryError::NoPublishedVersion(ref n) if n == "ghost"));
+    }
+    /// Self-signed CA certificate, standing in for a corporate root.
+    const TEST_CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBhDCCASmgAwIBAgIUVjAGs/J8MBrD1drSEvcOw0AI7oEwCgYIKoZIzj0EAwIw
+FjEUMBIGA1UEAwwLaWR4IHRlc3QgQ0EwIBcNMjYxMDE2MTgyMDU3WhgPMjEyNjA5
+MjIxODIwNTdaMBYxFDASBgNVBAMMC2lkeCB0ZXN0IENBMFkwEwYHKoZIzj0CAQYI
+KoZIzj0DAQcDQgAE7Efh0nIfBfY3GPkVo5xiPA8hKUG5yxwE43xkp3des0JIDNU8
+BMMnTKOIpK/wwBzpfErR2h68dekwqsqS9nVoIqNTMFEwHQYDVR0OBBYEFGrB/V1O
+jagU0v+celuUVd/xmX5fMB8GA1UdIwQYMBaAFGrB/V1OjagU0v+celuUVd/xmX5f
+MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhALKIJvx2ZDTyYKcN
+5nw4zHPIlG17oJ8LzMW9HLcgclzGAiEAvyxqSLut71rH/hnI9Bel40S66v8vpzr7
+qsG5ZDMMKcQ=
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_http_client_with_proxy_and_ca() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ca_bundle = dir.path().join("corp-ca.pem");
+        std::fs::write(&ca_bundle, TEST_CA).unwrap();
+
+        let options = HttpOptions {
+            proxy: Some("http://proxy.corp.example:8080".to_string()),
+            no_proxy: Some("localhost,.corp.example".to_string()),
+            ca_bundle: Some(ca_bundle),
+        };
+        assert!(http_client(&options).is_ok());
+        assert!(http_client(&HttpOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_http_client_rejects_bad_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let not_pem = dir.path().join("not-a-cert.pem");
+        std::fs::write(&not_pem, "not a certificate").unwrap();
+
+        for options in [
+            HttpOptions {
+                proxy: Some("::not a url::".to_string()),
+                ..Default::default()
+            },
+            HttpOptions {
+                ca_bundle: Some(dir.path().join("missing.pem")),
+                ..Default::default()
+            },
+            HttpOptions {
+                ca_bundle: Some(not_pem),
+                ..Default::default()
+            },
+        ] {
+            let err = http_client(&options).unwrap_err();
+            assert!(matches!(err, RegistryError::HttpConfig(_)), "{:?}", options);
+        }
+    }
+}
@@ -1,14 +1,14 @@
 //! crates.io registry client.
 
-use std::io::Read;
-
 use flate2::read::GzDecoder;
 use reqwest::Client;
 use serde::Deserialize;
 use tar::Archive;
 use tracing::debug;
 
-use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo};
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo, default_http_client};
+use super::encoding::read_source;
 use super::error::RegistryError;
 
 const CRATES_API: &str = "https://crates.io/api/v1";
@@ -22,12 +22,12 @@ pub struct CratesIoClient {
 
 impl CratesIoClient {
     pub fn new() -> Self {
-        // crates.io requires a user agent
-        let client = Client::builder()
-            .user_agent("index-registry/0.1.0 (https://github.com/yourusername/index)")
-            .build()
-            .expect("failed to build http client");
+        Self::with_client(default_http_client())
+    }
 
+    /// Client sending requests through `client` (see [`super::http_client`]),
+    /// which must send a user agent as crates.io requires.
+    pub fn with_client(client: Client) -> Self {
         Self {
             client,
             api_url: CRATES_API.to_string(),
@@ -119,6 +119,7 @@ impl RegistryClient for CratesIoClient {
             repository: None,
             license: ver_resp.version.license,
             tarball_url,
+            entry_point: None,
         })
     }
 
@@ -162,7 +163,14 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         let path = entry.path()?;
         let path_str = path.to_string_lossy().to_string();
 
-        if entry.header().entry_type().is_dir() {
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            continue;
+        }
+        if !is_safe_entry(
+            &path_str,
+            entry_type.is_symlink() || entry_type.is_hard_link(),
+        ) {
             continue;
         }
 
@@ -173,11 +181,12 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
             continue;
         }
 
-        let mut content = String::new();
-        if entry.read_to_string(&mut content).is_ok() {
+        if let Some(content) = read_source(&clean_path, &mut entry) {
             files.push(PackageFile {
+                is_test: is_test_file(&clean_path),
                 path: clean_path,
                 content,
+                is_vendored: false,
             });
         }
     }
@@ -204,21 +213,27 @@ fn is_indexable_file(path: &str) -> bool {
         return true;
     }
 
-    // Must be a Rust source file
-    if !path_lower.ends_with(".rs") {
+    // Must be source in a language we parse (wasm bindings bring JS, ...)
+    if !super::is_source_file(&path_lower) {
         return false;
     }
 
-    // Skip test/bench files (but NOT examples - we want those!)
-    let skip_patterns = ["tests/", "benches/", "test_"];
-
-    if skip_patterns.iter().any(|p| path_lower.contains(p)) {
+    // Skip benches (but NOT examples or tests - we want those!)
+    if path_lower.contains("benches/") {
         return false;
     }
 
     true
 }
 
+/// Check if a file belongs to the crate's tests (`tests/`, `test_*.rs`).
+fn is_test_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    let file_name = path_lower.rsplit('/').next().unwrap_or(&path_lower);
+
+    path_lower.contains("tests/") || file_name.starts_with("test_")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,6 +244,10 @@ mod tests {
         assert!(is_indexable_file("src/lib.rs"));
         assert!(is_indexable_file("src/parser/mod.rs"));
 
+        // Bundled sources in other languages too
+        assert!(is_indexable_file("src/js/snippets.js"));
+        assert!(!is_indexable_file("src/js/snippets.min.js"));
+
         // Examples should be included!
         assert!(is_indexable_file("examples/basic.rs"));
         assert!(is_indexable_file("examples/advanced/multi.rs"));
@@ -238,14 +257,24 @@ mod tests {
         assert!(is_indexable_file("docs/guide.md"));
         assert!(is_indexable_file("CHANGELOG.markdown"));
 
-        // Tests and benches still skipped
-        assert!(!is_indexable_file("tests/integration.rs"));
+        // Tests are returned, marked by is_test_file; benches still skipped
+        assert!(is_indexable_file("tests/integration.rs"));
         assert!(!is_indexable_file("benches/bench.rs"));
 
         // Non-source files still skipped
         assert!(!is_indexable_file("Cargo.toml"));
     }
 
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("tests/integration.rs"));
+        assert!(is_test_file("src/test_util.rs"));
+
+        assert!(!is_test_file("src/lib.rs"));
+        assert!(!is_test_file("src/latest_version.rs"));
+        assert!(!is_test_file("examples/basic.rs"));
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_get_package_serde() {
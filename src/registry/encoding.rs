@@ -0,0 +1,124 @@
+//! Decoding of extracted source files.
+//!
+//! Archives occasionally contain source files with a UTF-8 BOM or in a
+//! legacy encoding such as latin-1. Rather than silently dropping them,
+//! they are normalized to UTF-8 here so the package still indexes.
+
+use std::io::Read;
+
+use tracing::warn;
+
+/// UTF-8 byte order mark.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Number of leading bytes inspected when sniffing for binary content.
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Why an extracted file was not kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The file contains NUL bytes and is not text.
+    Binary,
+    /// The archive entry could not be read.
+    Unreadable(String),
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Binary => write!(f, "binary content"),
+            Self::Unreadable(e) => write!(f, "unreadable: {}", e),
+        }
+    }
+}
+
+/// Decode raw file bytes into source text.
+///
+/// Strips a leading UTF-8 BOM, rejects binary content, and falls back to a
+/// lossy decode (with a warning) for text that isn't valid UTF-8.
+pub fn decode_source(path: &str, bytes: Vec<u8>) -> Result<String, SkipReason> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if sniff.contains(&0) {
+        return Err(SkipReason::Binary);
+    }
+
+    let bytes = match bytes.strip_prefix(UTF8_BOM) {
+        Some(rest) => rest.to_vec(),
+        None => bytes,
+    };
+
+    match String::from_utf8(bytes) {
+        Ok(content) => Ok(content),
+        Err(e) => {
+            warn!(path, error = %e.utf8_error(), "file is not valid UTF-8, decoding lossily");
+            Ok(String::from_utf8_lossy(e.as_bytes()).into_owned())
+        }
+    }
+}
+
+/// Read an archive entry and decode it as source text.
+///
+/// Returns `None` (and logs the reason) if the file should be skipped.
+pub fn read_source(path: &str, mut reader: impl Read) -> Option<String> {
+    let mut bytes = Vec::new();
+    let result = match reader.read_to_end(&mut bytes) {
+        Ok(_) => decode_source(path, bytes),
+        Err(e) => Err(SkipReason::Unreadable(e.to_string())),
+    };
+
+    match result {
+        Ok(content) => Some(content),
+        Err(reason) => {
+            warn!(path, %reason, "skipping file");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_plain_utf8() {
+        let content = decode_source("a.py", b"def f():\n    pass\n".to_vec()).unwrap();
+        assert_eq!(content, "def f():\n    pass\n");
+    }
+
+    #[test]
+    fn test_decode_strips_bom() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice("package main\n// café\n".as_bytes());
+
+        let content = decode_source("main.go", bytes).unwrap();
+        assert_eq!(content, "package main\n// café\n");
+        assert!(!content.starts_with('\u{feff}'));
+    }
+
+    #[test]
+    fn test_decode_latin1_is_lossy_not_dropped() {
+        // "# caf\xe9" is latin-1 for "# café"
+        let bytes = b"# caf\xe9\ndef greet():\n    return 1\n".to_vec();
+
+        let content = decode_source("greet.py", bytes).unwrap();
+        assert!(content.starts_with("# caf\u{FFFD}"));
+        assert!(content.contains("def greet():"));
+    }
+
+    #[test]
+    fn test_decode_rejects_binary() {
+        let bytes = b"\x7fELF\x02\x01\x01\x00\x00\x00".to_vec();
+        assert_eq!(decode_source("lib.py", bytes), Err(SkipReason::Binary));
+    }
+
+    #[test]
+    fn test_read_source_from_reader() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice(b"fn main() {}\n");
+
+        let content = read_source("main.rs", bytes.as_slice());
+        assert_eq!(content.as_deref(), Some("fn main() {}\n"));
+
+        assert!(read_source("blob.rs", &b"\x00\x01\x02"[..]).is_none());
+    }
+}
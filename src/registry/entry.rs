@@ -0,0 +1,302 @@
+//! Detection of a package's entry point: the module its users import.
+//!
+//! npm manifests declare it (`exports`, `module`, `main`, see
+//! [`VersionInfo::entry_point`](super::VersionInfo::entry_point)); elsewhere
+//! it follows from convention (`src/lib.rs`, `lib/<name>.dart`) or from the
+//! layout of the files (a Python distribution's top-level package).
+
+use crate::types::Registry;
+
+use super::client::PackageFile;
+
+/// Directories holding a distribution's own tooling rather than its code.
+const NON_PACKAGE_DIRS: &[&str] = &["tests", "test", "docs", "doc", "examples", "benchmarks"];
+
+/// Path of the entry module of package `name`, given the entry point its
+/// manifest declares (if any) and its downloaded files.
+///
+/// A declared entry is mapped to the file that was indexed: npm packages
+/// often point at a build output that is skipped (`dist/`), so its source
+/// next to it (`src/index.ts`) is used when present. When no indexed file
+/// matches, the declared path is kept as is.
+pub fn entry_point(
+    registry: Registry,
+    name: &str,
+    declared: Option<&str>,
+    files: &[PackageFile],
+) -> Option<String> {
+    let paths: Vec<&str> = files
+        .iter()
+        .filter(|file| !file.is_test && !file.is_vendored)
+        .map(|file| file.path.as_str())
+        .collect();
+
+    match registry {
+        Registry::Npm => npm_entry(declared, &paths),
+        Registry::Pypi => python_entry(name, &paths),
+        Registry::Crates => ["src/lib.rs", "src/main.rs"]
+            .into_iter()
+            .find(|path| paths.contains(path))
+            .map(str::to_string),
+        Registry::Go => go_entry(name, &paths),
+        Registry::Pub => {
+            let library = format!("lib/{}.dart", name);
+            paths.contains(&library.as_str()).then_some(library)
+        }
+        Registry::Maven | Registry::Git => None,
+    }
+}
+
+/// The indexed file behind npm's declared entry, else a conventional
+/// `index` module.
+fn npm_entry(declared: Option<&str>, paths: &[&str]) -> Option<String> {
+    let declared = declared
+        .map(|path| path.trim_start_matches("./"))
+        .filter(|path| !path.is_empty());
+
+    let mut candidates = Vec::new();
+    if let Some(path) = declared {
+        candidates.push(path.to_string());
+        match path.rsplit_once('.').filter(|(_, ext)| !ext.contains('/')) {
+            Some((stem, _)) => {
+                // dist/index.js → dist/index.ts, src/index.ts
+                let stem = stem.strip_suffix(".d").unwrap_or(stem);
+                for ext in ["ts", "mts", "tsx", "js"] {
+                    candidates.push(format!("{}.{}", stem, ext));
+                }
+                if let Some((_, rest)) = stem.split_once('/') {
+                    for ext in ["ts", "tsx", "js"] {
+                        candidates.push(format!("src/{}.{}", rest, ext));
+                    }
+                }
+            }
+            // An extensionless file or a directory
+            None => {
+                for ext in ["js", "ts"] {
+                    candidates.push(format!("{}.{}", path, ext));
+                    candidates.push(format!("{}/index.{}", path, ext));
+                }
+            }
+        }
+    }
+    candidates.extend(
+        [
+            "index.js",
+            "index.ts",
+            "index.mjs",
+            "src/index.ts",
+            "src/index.js",
+        ]
+        .map(String::from),
+    );
+
+    candidates
+        .into_iter()
+        .find(|candidate| paths.contains(&candidate.as_str()))
+        .or_else(|| declared.map(str::to_string))
+}
+
+/// `__init__.py` of the distribution's top-level package: the one named
+/// after the distribution, else the largest. Single-module distributions
+/// fall back to `<name>.py`.
+fn python_entry(name: &str, paths: &[&str]) -> Option<String> {
+    let wanted = name.to_lowercase().replace(['-', '.'], "_");
+
+    let packages: Vec<&str> = paths
+        .iter()
+        .filter_map(|path| path.strip_suffix("/__init__.py"))
+        .filter(|dir| {
+            let dir = dir.strip_prefix("src/").unwrap_or(dir);
+            !dir.contains('/') && !NON_PACKAGE_DIRS.contains(&dir)
+        })
+        .collect();
+    let package_name = |dir: &str| dir.rsplit('/').next().unwrap_or(dir).to_lowercase();
+    let package = packages
+        .iter()
+        .find(|dir| package_name(dir) == wanted)
+        .or_else(|| {
+            packages.iter().max_by_key(|dir| {
+                let prefix = format!("{}/", dir);
+                paths
+                    .iter()
+                    .filter(|path| path.starts_with(&prefix))
+                    .count()
+            })
+        });
+    if let Some(dir) = package {
+        return Some(format!("{}/__init__.py", dir));
+    }
+
+    [format!("{}.py", wanted), format!("src/{}.py", wanted)]
+        .into_iter()
+        .find(|module| paths.contains(&module.as_str()))
+}
+
+/// The root package's main file: the one named after the module
+/// (`cobra.go` in `github.com/spf13/cobra`), else `doc.go`, else the first.
+fn go_entry(module: &str, paths: &[&str]) -> Option<String> {
+    let mut root_files: Vec<&str> = paths
+        .iter()
+        .copied()
+        .filter(|path| !path.contains('/') && path.ends_with(".go") && !path.ends_with("_test.go"))
+        .collect();
+    root_files.sort_unstable();
+
+    // github.com/go-yaml/yaml/v3 and gopkg.in/yaml.v3 → yaml
+    let is_major = |suffix: &str| !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit());
+    let mut segments = module.rsplit('/');
+    let mut last = segments.next().unwrap_or(module);
+    if let Some(major) = last.strip_prefix('v')
+        && is_major(major)
+    {
+        last = segments.next().unwrap_or(last);
+    }
+    if let Some((base, major)) = last.rsplit_once(".v")
+        && is_major(major)
+    {
+        last = base;
+    }
+    let package = last
+        .trim_start_matches("go-")
+        .trim_end_matches(".go")
+        .trim_end_matches("-go");
+
+    [format!("{}.go", package), "doc.go".to_string()]
+        .into_iter()
+        .find(|file| root_files.contains(&file.as_str()))
+        .or_else(|| root_files.first().map(|file| file.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<PackageFile> {
+        paths
+            .iter()
+            .map(|path| PackageFile {
+                path: path.to_string(),
+                content: String::new(),
+                is_test: path.starts_with("tests/"),
+                is_vendored: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_npm_entry_maps_build_output_to_source() {
+        let ts_files = files(&["src/index.ts", "src/client.ts", "README.md"]);
+        let entry = |declared| entry_point(Registry::Npm, "pkg", declared, &ts_files);
+
+        assert_eq!(
+            entry(Some("./dist/index.js")).as_deref(),
+            Some("src/index.ts")
+        );
+        assert_eq!(
+            entry(Some("dist/index.d.ts")).as_deref(),
+            Some("src/index.ts")
+        );
+        assert_eq!(
+            entry(Some("src/client.js")).as_deref(),
+            Some("src/client.ts")
+        );
+        // No source by the build output's name: the conventional index
+        assert_eq!(entry(Some("lib/main.cjs")).as_deref(), Some("src/index.ts"));
+        // Nothing indexed matches: the manifest is still the best answer
+        assert_eq!(
+            entry_point(Registry::Npm, "pkg", Some("lib/main.cjs"), &[]).as_deref(),
+            Some("lib/main.cjs")
+        );
+
+        let js_files = files(&["lib/index.js", "index.js"]);
+        assert_eq!(
+            entry_point(Registry::Npm, "pkg", Some("lib"), &js_files).as_deref(),
+            Some("lib/index.js")
+        );
+        assert_eq!(
+            entry_point(Registry::Npm, "pkg", None, &js_files).as_deref(),
+            Some("index.js")
+        );
+    }
+
+    #[test]
+    fn test_python_top_level_package() {
+        let entry = |name, paths: &[&str]| entry_point(Registry::Pypi, name, None, &files(paths));
+
+        // Named after the distribution, with `-` and `.` as `_`
+        assert_eq!(
+            entry(
+                "typing-extensions-plus",
+                &[
+                    "docs/__init__.py",
+                    "helpers/__init__.py",
+                    "typing_extensions_plus/__init__.py"
+                ]
+            )
+            .as_deref(),
+            Some("typing_extensions_plus/__init__.py")
+        );
+        assert_eq!(
+            entry("Flask", &["src/flask/__init__.py", "src/flask/app.py"]).as_deref(),
+            Some("src/flask/__init__.py")
+        );
+        // PyYAML ships `yaml`: the largest top-level package wins
+        assert_eq!(
+            entry(
+                "PyYAML",
+                &[
+                    "_yaml/__init__.py",
+                    "yaml/__init__.py",
+                    "yaml/loader.py",
+                    "yaml/dumper.py",
+                    "yaml/nodes/__init__.py",
+                    "tests/__init__.py",
+                ]
+            )
+            .as_deref(),
+            Some("yaml/__init__.py")
+        );
+        // Subpackages and test packages are not top-level
+        assert_eq!(
+            entry("six", &["six.py", "tests/__init__.py", "tests/test_six.py"]).as_deref(),
+            Some("six.py")
+        );
+        assert_eq!(entry("empty", &["setup.py"]), None);
+    }
+
+    #[test]
+    fn test_conventional_entries() {
+        let crate_files = files(&["src/main.rs", "src/lib.rs"]);
+        assert_eq!(
+            entry_point(Registry::Crates, "serde", None, &crate_files).as_deref(),
+            Some("src/lib.rs")
+        );
+
+        let go_files = files(&[
+            "doc.go",
+            "yaml.go",
+            "decode.go",
+            "cmd/main.go",
+            "yaml_test.go",
+        ]);
+        assert_eq!(
+            entry_point(Registry::Go, "gopkg.in/yaml/v3", None, &go_files).as_deref(),
+            Some("yaml.go")
+        );
+        assert_eq!(
+            entry_point(Registry::Go, "gopkg.in/yaml.v3", None, &go_files).as_deref(),
+            Some("yaml.go")
+        );
+        assert_eq!(
+            entry_point(Registry::Go, "github.com/acme/go-thing", None, &go_files).as_deref(),
+            Some("doc.go")
+        );
+
+        let dart_files = files(&["lib/provider.dart", "lib/src/provider.dart"]);
+        assert_eq!(
+            entry_point(Registry::Pub, "provider", None, &dart_files).as_deref(),
+            Some("lib/provider.dart")
+        );
+        assert_eq!(entry_point(Registry::Maven, "g:a", None, &dart_files), None);
+    }
+}
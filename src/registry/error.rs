@@ -10,12 +10,25 @@ pub enum RegistryError {
     #[error("version not found: {package}@{version}")]
     VersionNotFound { package: String, version: String },
 
+    #[error("no published version found for {0}")]
+    NoPublishedVersion(String),
+
+    #[error("no sources available for {package}@{version}: {reason}")]
+    NoSourcesAvailable {
+        package: String,
+        version: String,
+        reason: String,
+    },
+
     #[error("invalid package: {0}")]
     InvalidPackage(String),
 
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    #[error("invalid HTTP client configuration: {0}")]
+    HttpConfig(String),
+
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
 
@@ -28,9 +41,15 @@ pub enum RegistryError {
     #[error("archive error: {0}")]
     Archive(String),
 
+    #[error("git error: {0}")]
+    Git(String),
+
     #[error("unsupported registry: {0}")]
     UnsupportedRegistry(String),
 
     #[error("rate limited")]
     RateLimited,
+
+    #[error("offline mode: network access is disabled (unset IDX_OFFLINE or drop --offline)")]
+    OfflineMode,
 }
@@ -1,14 +1,19 @@
 //! Go module proxy client.
 
-use std::io::{Cursor, Read};
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
 
 use reqwest::Client;
 use serde::Deserialize;
 use tracing::debug;
 use zip::ZipArchive;
 
-use super::client::{PackageFile, PackageInfo, RegistryClient};
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, default_http_client};
+use super::encoding::read_source;
 use super::error::RegistryError;
+use super::git::{self, Checkout, KNOWN_GIT_HOSTS};
 
 const GO_PROXY: &str = "https://proxy.golang.org";
 
@@ -19,15 +24,82 @@ pub struct GoClient {
 
 impl GoClient {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("index-registry/0.1.0")
-            .build()
-            .expect("failed to build http client");
+        Self::with_client(default_http_client())
+    }
 
+    /// Client sending requests through `client` (see [`super::http_client`]).
+    pub fn with_client(client: Client) -> Self {
         Self { client }
     }
 }
 
+/// Where the source for a git ref comes from.
+pub enum RefSource {
+    /// The proxy serves the ref under this version: a release when the ref
+    /// is a tag, otherwise a pseudo-version.
+    Proxy { version: String },
+    /// The proxy couldn't serve the ref, so it was cloned. `version` is a
+    /// pseudo-version built from the commit.
+    Git {
+        version: String,
+        files: Vec<PackageFile>,
+    },
+}
+
+impl RefSource {
+    /// Version the ref is indexed under.
+    pub fn version(&self) -> &str {
+        match self {
+            RefSource::Proxy { version } | RefSource::Git { version, .. } => version,
+        }
+    }
+}
+
+impl GoClient {
+    /// Resolve a branch, tag or commit of `module` to a version.
+    ///
+    /// Asks the proxy first, which answers with the canonical
+    /// pseudo-version for untagged commits. If the proxy can't serve the
+    /// module (private or unpublished forks), the repository is cloned at
+    /// `git_ref` and its indexable files are read directly.
+    pub async fn fetch_ref(&self, module: &str, git_ref: &str) -> Result<RefSource, RegistryError> {
+        if is_pseudo_version(git_ref) {
+            return Ok(RefSource::Proxy {
+                version: git_ref.to_string(),
+            });
+        }
+
+        // The proxy can't express refs containing a slash (e.g. feature/x)
+        if !git_ref.contains('/') {
+            let url = format!(
+                "{}/{}/@v/{}.info",
+                GO_PROXY,
+                escape_module(module),
+                escape_module(git_ref)
+            );
+            debug!(package = module, git_ref, url = %url, "resolving go module ref");
+
+            let response = self.client.get(&url).send().await?;
+            if !is_missing(response.status()) {
+                let info: GoVersionInfo = response.error_for_status()?.json().await?;
+                return Ok(RefSource::Proxy {
+                    version: info.version,
+                });
+            }
+        }
+
+        debug!(package = module, git_ref, "proxy can't serve ref, cloning");
+        let (url, subdir) = repo_for_module(module);
+        let checkout = Checkout::clone_ref(&url, git_ref).await?;
+        let files = read_checkout(checkout.path(), subdir.as_deref())?;
+
+        Ok(RefSource::Git {
+            version: pseudo_version(checkout.time, &checkout.commit),
+            files,
+        })
+    }
+}
+
 impl Default for GoClient {
     fn default() -> Self {
         Self::new()
@@ -59,6 +131,97 @@ fn normalize_version(version: &str) -> String {
     }
 }
 
+/// The proxy answers 404 or 410 for modules and versions it can't serve.
+fn is_missing(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE
+}
+
+/// Build the pseudo-version Go uses for a commit with no tagged ancestor,
+/// e.g. `v0.0.0-20240102150405-abcdef123456`.
+fn pseudo_version(commit_time: i64, commit: &str) -> String {
+    let time = chrono::DateTime::from_timestamp(commit_time, 0).unwrap_or_default();
+    let short: String = commit.chars().take(12).collect();
+    format!("v0.0.0-{}-{}", time.format("%Y%m%d%H%M%S"), short)
+}
+
+/// Check whether a version is a pseudo-version
+/// (`vX.Y.Z-[pre.0.]yyyymmddhhmmss-abcdefabcdef`, optionally `+incompatible`).
+fn is_pseudo_version(version: &str) -> bool {
+    let version = version.strip_suffix("+incompatible").unwrap_or(version);
+    let Some((rest, commit)) = version.rsplit_once('-') else {
+        return false;
+    };
+    let Some(time) = rest.rsplit(['-', '.']).next() else {
+        return false;
+    };
+
+    version.starts_with('v')
+        && commit.len() == 12
+        && commit.chars().all(|c| c.is_ascii_hexdigit())
+        && time.len() == 14
+        && time.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Git URL of the repository holding `module`, plus the module's
+/// subdirectory within it.
+///
+/// Only well-known hosts are split; other module paths are cloned as-is,
+/// which doesn't follow vanity import redirects.
+fn repo_for_module(module: &str) -> (String, Option<String>) {
+    let parts: Vec<&str> = module.split('/').collect();
+    if parts.len() > 3 && KNOWN_GIT_HOSTS.contains(&parts[0]) {
+        let repo = parts[..3].join("/");
+        let subdir = parts[3..].join("/");
+        return (format!("https://{}", repo), Some(subdir));
+    }
+    (format!("https://{}", module), None)
+}
+
+/// Read indexable files from a checkout, relative to the module's directory.
+///
+/// A trailing `/vN` major version may be a subdirectory or just a module
+/// path suffix on the repository root, so it falls back to the root when
+/// no such directory exists.
+fn read_checkout(root: &Path, subdir: Option<&str>) -> Result<Vec<PackageFile>, RegistryError> {
+    let module_root = match subdir {
+        Some(dir) if root.join(dir).is_dir() => root.join(dir),
+        Some(dir) if !is_major_version_suffix(dir) => {
+            return Err(RegistryError::InvalidPackage(format!(
+                "module directory {} not found in repository",
+                dir
+            )));
+        }
+        _ => root.to_path_buf(),
+    };
+
+    let mut files = Vec::new();
+    for path in git::list_files(&module_root)? {
+        if !is_indexable_file(&path) {
+            continue;
+        }
+
+        if let Some(content) = read_source(&path, File::open(module_root.join(&path))?) {
+            let is_test = is_test_file(&path);
+            files.push(PackageFile {
+                path,
+                content,
+                is_test,
+                is_vendored: false,
+            });
+        }
+    }
+
+    debug!(file_count = files.len(), "read source files from checkout");
+    Ok(files)
+}
+
+/// Check for a module path major version suffix such as `v2`.
+fn is_major_version_suffix(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
 // Go proxy response types
 #[derive(Debug, Deserialize)]
 struct GoVersionInfo {
@@ -78,21 +241,28 @@ impl RegistryClient for GoClient {
 
         let response = self.client.get(&full_url).send().await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if is_missing(response.status()) {
             return Err(RegistryError::PackageNotFound(name.to_string()));
         }
 
         let text = response.text().await?;
 
         // Version list is newline-separated
-        let versions: Vec<String> = text
+        let mut versions: Vec<String> = text
             .lines()
             .filter(|l| !l.is_empty())
             .map(|l| l.trim().to_string())
             .collect();
 
+        // Untagged modules have no list; @latest gives their pseudo-version
         if versions.is_empty() {
-            return Err(RegistryError::PackageNotFound(name.to_string()));
+            let latest_url = format!("{}/{}/@latest", GO_PROXY, escaped);
+            let response = self.client.get(&latest_url).send().await?;
+            if is_missing(response.status()) {
+                return Err(RegistryError::PackageNotFound(name.to_string()));
+            }
+            let info: GoVersionInfo = response.json().await?;
+            versions.push(info.version);
         }
 
         // Latest is typically last in list
@@ -121,7 +291,7 @@ impl RegistryClient for GoClient {
 
         let response = self.client.get(&url).send().await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if is_missing(response.status()) {
             return Err(RegistryError::VersionNotFound {
                 package: name.to_string(),
                 version: version.to_string(),
@@ -139,6 +309,7 @@ impl RegistryClient for GoClient {
             repository: Some(format!("https://{}", name)),
             license: None,
             tarball_url: zip_url,
+            entry_point: None,
         })
     }
 
@@ -160,7 +331,7 @@ impl RegistryClient for GoClient {
 
         let response = self.client.get(&url).send().await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if is_missing(response.status()) {
             return Err(RegistryError::VersionNotFound {
                 package: name.to_string(),
                 version: version.to_string(),
@@ -187,6 +358,9 @@ fn extract_module_zip(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         }
 
         let full_path = entry.name().to_string();
+        if !is_safe_entry(&full_path, entry.is_symlink()) {
+            continue;
+        }
 
         // Go module zips have module@version/ prefix, strip it
         let path = strip_module_prefix(&full_path);
@@ -195,9 +369,14 @@ fn extract_module_zip(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
             continue;
         }
 
-        let mut content = String::new();
-        if entry.read_to_string(&mut content).is_ok() {
-            files.push(PackageFile { path, content });
+        if let Some(content) = read_source(&path, &mut entry) {
+            let is_test = is_test_file(&path);
+            files.push(PackageFile {
+                path,
+                content,
+                is_test,
+                is_vendored: false,
+            });
         }
     }
 
@@ -224,12 +403,8 @@ fn strip_module_prefix(path: &str) -> String {
 fn is_indexable_file(path: &str) -> bool {
     let path_lower = path.to_lowercase();
 
-    // Include Go source files
-    if path_lower.ends_with(".go") {
-        // Skip test files
-        if path_lower.ends_with("_test.go") {
-            return false;
-        }
+    // Include source files in any language we parse
+    if super::is_source_file(&path_lower) {
         // Skip vendor directory
         if path_lower.starts_with("vendor/") || path_lower.contains("/vendor/") {
             return false;
@@ -245,6 +420,11 @@ fn is_indexable_file(path: &str) -> bool {
     false
 }
 
+/// Check if a file is a Go test (`*_test.go`).
+fn is_test_file(path: &str) -> bool {
+    path.to_lowercase().ends_with("_test.go")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -268,6 +448,75 @@ mod tests {
         assert_eq!(normalize_version("v1.9.1"), "v1.9.1");
     }
 
+    #[test]
+    fn test_is_pseudo_version() {
+        assert!(is_pseudo_version("v0.0.0-20240102150405-abcdef123456"));
+        assert!(is_pseudo_version("v1.9.2-0.20240102150405-abcdef123456"));
+        assert!(is_pseudo_version(
+            "v2.0.0-pre.0.20240102150405-abcdef123456"
+        ));
+        assert!(is_pseudo_version(
+            "v2.0.1-0.20240102150405-abcdef123456+incompatible"
+        ));
+
+        assert!(!is_pseudo_version("v1.9.1"));
+        assert!(!is_pseudo_version("v1.0.0-rc.1"));
+        assert!(!is_pseudo_version("main"));
+        assert!(!is_pseudo_version("abcdef123456"));
+    }
+
+    #[test]
+    fn test_pseudo_version() {
+        let version = pseudo_version(1704207845, "abcdef1234567890abcdef1234567890abcdef12");
+        assert_eq!(version, "v0.0.0-20240102150405-abcdef123456");
+        assert!(is_pseudo_version(&version));
+    }
+
+    #[test]
+    fn test_repo_for_module() {
+        assert_eq!(
+            repo_for_module("github.com/gin-gonic/gin"),
+            ("https://github.com/gin-gonic/gin".to_string(), None)
+        );
+        assert_eq!(
+            repo_for_module("github.com/go-redis/redis/v9"),
+            (
+                "https://github.com/go-redis/redis".to_string(),
+                Some("v9".to_string())
+            )
+        );
+        assert_eq!(
+            repo_for_module("gitlab.com/acme/tools/cmd/lint"),
+            (
+                "https://gitlab.com/acme/tools".to_string(),
+                Some("cmd/lint".to_string())
+            )
+        );
+        assert_eq!(
+            repo_for_module("go.acme.dev/internal/lib"),
+            ("https://go.acme.dev/internal/lib".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_read_checkout_filters_and_falls_back_to_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("vendor/x")).unwrap();
+        std::fs::write(dir.path().join("redis.go"), "package redis").unwrap();
+        std::fs::write(dir.path().join("redis_test.go"), "package redis").unwrap();
+        std::fs::write(dir.path().join("vendor/x/x.go"), "package x").unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module x").unwrap();
+
+        // v9 is a path suffix, not a directory, so the root is read
+        let files = read_checkout(dir.path(), Some("v9")).unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["redis.go", "redis_test.go"]);
+        assert!(!files[0].is_test);
+        assert!(files[1].is_test);
+
+        assert!(read_checkout(dir.path(), Some("cmd/lint")).is_err());
+    }
+
     #[test]
     fn test_strip_module_prefix() {
         assert_eq!(
@@ -284,11 +533,13 @@ mod tests {
     fn test_is_indexable_file() {
         assert!(is_indexable_file("main.go"));
         assert!(is_indexable_file("internal/handler.go"));
+        assert!(is_indexable_file("web/static/app.ts"));
         assert!(is_indexable_file("README.md"));
 
-        // Skip tests
-        assert!(!is_indexable_file("main_test.go"));
-        assert!(!is_indexable_file("handler_test.go"));
+        // Tests are returned, marked by is_test_file
+        assert!(is_indexable_file("main_test.go"));
+        assert!(is_test_file("handler_test.go"));
+        assert!(!is_test_file("handler.go"));
 
         // Skip vendor
         assert!(!is_indexable_file("vendor/github.com/pkg/errors/errors.go"));
@@ -321,4 +572,16 @@ mod tests {
         assert!(!files.is_empty());
         assert!(files.iter().any(|f| f.path.ends_with(".go")));
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch_ref_from_proxy() {
+        let client = GoClient::new();
+        let source = client
+            .fetch_ref("github.com/gin-gonic/gin", "master")
+            .await
+            .unwrap();
+        assert!(matches!(source, RefSource::Proxy { .. }));
+        assert!(is_pseudo_version(source.version()) || source.version().starts_with('v'));
+    }
 }
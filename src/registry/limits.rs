@@ -0,0 +1,198 @@
+//! Per-registry request limits.
+//!
+//! Registries tolerate different request rates, so each one gets its own
+//! lane: a semaphore bounding how many requests are in flight, and a backoff
+//! when the registry answers 429. A rate-limited request keeps its permit
+//! while it waits, which slows that registry's lane down without stalling
+//! requests to other registries.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+use super::error::RegistryError;
+use crate::types::Registry;
+
+/// Retries of a rate-limited request before giving up.
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Wait before the first retry of a rate-limited request; doubled for each
+/// further retry.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Requests to `registry` allowed in flight at once unless configured.
+///
+/// npm's registry is CDN-backed and copes with plenty of parallelism; Maven
+/// Central and crates.io ask crawlers to go easy.
+pub fn default_concurrency(registry: Registry) -> usize {
+    match registry {
+        Registry::Npm => 16,
+        Registry::Pypi => 8,
+        Registry::Crates => 4,
+        Registry::Go => 8,
+        Registry::Maven => 4,
+        Registry::Pub => 8,
+        Registry::Git => 4,
+    }
+}
+
+/// Concurrency limits shared by every client of an index, one lane per
+/// registry. Cloning shares the lanes.
+#[derive(Debug, Clone)]
+pub struct RegistryLimits {
+    lanes: Arc<HashMap<Registry, Arc<Semaphore>>>,
+    backoff: Duration,
+}
+
+impl RegistryLimits {
+    /// Limits using `concurrency` for the registries it lists and
+    /// [`default_concurrency`] for the rest. A limit of 0 is treated as 1.
+    pub fn new(concurrency: &BTreeMap<Registry, usize>) -> Self {
+        let lanes = [
+            Registry::Npm,
+            Registry::Pypi,
+            Registry::Crates,
+            Registry::Go,
+            Registry::Maven,
+            Registry::Pub,
+            Registry::Git,
+        ]
+        .into_iter()
+        .map(|registry| {
+            let permits = concurrency
+                .get(&registry)
+                .copied()
+                .unwrap_or_else(|| default_concurrency(registry))
+                .max(1);
+            (registry, Arc::new(Semaphore::new(permits)))
+        })
+        .collect();
+        Self {
+            lanes: Arc::new(lanes),
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+
+    /// Wait `backoff` before the first retry of a rate-limited request.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Run `request` in `registry`'s lane once a slot is free, retrying with
+    /// exponential backoff while the registry answers
+    /// [`RegistryError::RateLimited`].
+    pub async fn run<T, F, Fut>(&self, registry: Registry, request: F) -> Result<T, RegistryError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, RegistryError>>,
+    {
+        let lane = &self.lanes[&registry];
+        let _permit = lane
+            .acquire()
+            .await
+            .expect("registry lanes are never closed");
+
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(RegistryError::RateLimited) if attempt < MAX_RATE_LIMIT_RETRIES => {
+                    let wait = self.backoff * 2u32.pow(attempt);
+                    warn!(%registry, ?wait, "rate limited, backing off");
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RegistryLimits {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stand-in for a registry that records how many requests it is
+    /// serving at once, and answers 429 to the first `rate_limited` ones.
+    #[derive(Default)]
+    struct StubRegistry {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        requests: AtomicUsize,
+        rate_limited: usize,
+    }
+
+    impl StubRegistry {
+        async fn request(&self) -> Result<(), RegistryError> {
+            let count = self.requests.fetch_add(1, Ordering::SeqCst);
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            if count < self.rate_limited {
+                Err(RegistryError::RateLimited)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lanes_bound_concurrency_per_registry() {
+        let limits =
+            RegistryLimits::new(&BTreeMap::from([(Registry::Npm, 3), (Registry::Maven, 1)]))
+                .with_backoff(Duration::from_millis(1));
+        let npm = Arc::new(StubRegistry::default());
+        let maven = Arc::new(StubRegistry {
+            rate_limited: 2,
+            ..Default::default()
+        });
+
+        let mut tasks = Vec::new();
+        for _ in 0..12 {
+            for (registry, stub) in [(Registry::Npm, &npm), (Registry::Maven, &maven)] {
+                let limits = limits.clone();
+                let stub = Arc::clone(stub);
+                tasks.push(tokio::spawn(async move {
+                    limits.run(registry, || stub.request()).await
+                }));
+            }
+        }
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(npm.max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(maven.max_in_flight.load(Ordering::SeqCst), 1);
+        assert_eq!(npm.requests.load(Ordering::SeqCst), 12);
+        // The two 429s were retried in Maven's lane
+        assert_eq!(maven.requests.load(Ordering::SeqCst), 14);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+        let limits = RegistryLimits::default().with_backoff(Duration::from_millis(1));
+        let stub = StubRegistry {
+            rate_limited: usize::MAX,
+            ..Default::default()
+        };
+
+        let result = limits.run(Registry::Crates, || stub.request()).await;
+        assert!(matches!(result, Err(RegistryError::RateLimited)));
+        assert_eq!(
+            stub.requests.load(Ordering::SeqCst),
+            MAX_RATE_LIMIT_RETRIES as usize + 1
+        );
+    }
+}
@@ -1,14 +1,32 @@
 //! Maven Central registry client.
-
-use std::io::{Cursor, Read};
-
+//!
+//! Sources come from the `-sources.jar` published next to an artifact. Many
+//! older artifacts have none, so those fall back to the git repository
+//! named in the POM's `<scm>`, checked out at the release tag.
+//!
+//! Versions are listed with the Central search API, which is rate-limited
+//! and flaky under bursty load. When it throttles, fails or comes back
+//! empty, the artifact's `maven-metadata.xml` in the repository is read
+//! instead; only a throttled repository is retried, by the registry's
+//! [`super::RegistryLimits`] lane. Other repositories have no search API and
+//! always use the metadata.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::events::Event;
 use reqwest::Client;
 use serde::Deserialize;
 use tracing::debug;
 use zip::ZipArchive;
 
-use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo};
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo, default_http_client};
+use super::encoding::read_source;
 use super::error::RegistryError;
+use super::git::{self, Checkout, KNOWN_GIT_HOSTS};
 
 const MAVEN_SEARCH_API: &str = "https://search.maven.org/solrsearch/select";
 const MAVEN_REPO: &str = "https://repo1.maven.org/maven2";
@@ -16,16 +34,184 @@ const MAVEN_REPO: &str = "https://repo1.maven.org/maven2";
 /// Maven Central registry client.
 pub struct MavenClient {
     client: Client,
+    repo_url: String,
+    /// Search API listing the repository's versions, if it has one.
+    search_url: Option<String>,
 }
 
 impl MavenClient {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("index-registry/0.1.0")
-            .build()
-            .expect("failed to build http client");
+        Self::with_client(default_http_client())
+    }
+
+    /// Client for a Maven repository other than Maven Central. Versions are
+    /// listed from `maven-metadata.xml`.
+    pub fn with_repo_url(repo_url: String) -> Self {
+        Self {
+            client: default_http_client(),
+            repo_url,
+            search_url: None,
+        }
+    }
+
+    /// Client sending requests through `client` (see [`super::http_client`]).
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            repo_url: MAVEN_REPO.to_string(),
+            search_url: Some(MAVEN_SEARCH_API.to_string()),
+        }
+    }
+
+    /// URL of a file of `name`'s `version` artifact, e.g. `-sources.jar`.
+    fn artifact_url(
+        &self,
+        name: &str,
+        version: &str,
+        suffix: &str,
+    ) -> Result<String, RegistryError> {
+        let (group_id, artifact_id) = parse_coordinates(name)?;
+        Ok(format!(
+            "{}/{}/{}/{}/{}-{}{}",
+            self.repo_url,
+            group_to_path(group_id),
+            artifact_id,
+            version,
+            artifact_id,
+            version,
+            suffix
+        ))
+    }
+
+    /// Versions (newest first) and latest version from the search API, or
+    /// `None` when it is throttled, failing or returns nothing, so the
+    /// caller can fall back to `maven-metadata.xml`.
+    async fn search_versions(
+        &self,
+        search_url: &str,
+        name: &str,
+    ) -> Result<Option<(Vec<String>, Option<String>)>, RegistryError> {
+        let (group_id, artifact_id) = parse_coordinates(name)?;
+        let url = format!(
+            "{}?q=g:{}+AND+a:{}&core=gav&rows=100&wt=json",
+            search_url, group_id, artifact_id
+        );
+
+        debug!(package = name, url = %url, "searching maven versions");
+
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                debug!(package = name, error = %e, "maven search failed");
+                return Ok(None);
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            debug!(package = name, %status, "maven search unavailable");
+            return Ok(None);
+        }
+
+        let Ok(search) = response.json::<SearchResponse>().await else {
+            return Ok(None);
+        };
+        let docs = search.response.docs;
+        let versions: Vec<String> = docs.iter().map(|d| d.v.clone()).collect();
+        let latest = docs
+            .first()
+            .and_then(|d| d.latest_version.clone())
+            .or_else(|| versions.first().cloned());
+        Ok((!versions.is_empty()).then_some((versions, latest)))
+    }
+
+    /// Versions (newest first) and latest release from the artifact's
+    /// `maven-metadata.xml`.
+    async fn metadata_versions(
+        &self,
+        name: &str,
+    ) -> Result<(Vec<String>, Option<String>), RegistryError> {
+        let (group_id, artifact_id) = parse_coordinates(name)?;
+        let url = format!(
+            "{}/{}/{}/maven-metadata.xml",
+            self.repo_url,
+            group_to_path(group_id),
+            artifact_id
+        );
+        debug!(package = name, url = %url, "fetching maven metadata");
+
+        let response = self.client.get(&url).send().await?;
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => {
+                return Err(RegistryError::PackageNotFound(name.to_string()));
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(RegistryError::RateLimited),
+            _ => {}
+        }
+
+        let metadata = parse_metadata(&response.error_for_status()?.text().await?);
+        if metadata.versions.is_empty() {
+            return Err(RegistryError::PackageNotFound(name.to_string()));
+        }
+        let latest = metadata
+            .release
+            .or(metadata.latest)
+            .or_else(|| metadata.versions.last().cloned());
+        let mut versions = metadata.versions;
+        versions.reverse();
+        Ok((versions, latest))
+    }
+
+    /// Fetch and parse the artifact's POM, or `None` if it doesn't exist.
+    async fn fetch_pom(&self, name: &str, version: &str) -> Result<Option<Pom>, RegistryError> {
+        let url = self.artifact_url(name, version, ".pom")?;
+        debug!(package = name, version, url = %url, "fetching maven pom");
+
+        let response = self.client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let text = response.error_for_status()?.text().await?;
+        Ok(Some(parse_pom(&text)))
+    }
+
+    /// Read sources from the repository in the POM's `<scm>`, for artifacts
+    /// published without a sources JAR.
+    async fn download_scm_source(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Vec<PackageFile>, RegistryError> {
+        let (_, artifact_id) = parse_coordinates(name)?;
+        let no_sources = |reason: String| RegistryError::NoSourcesAvailable {
+            package: name.to_string(),
+            version: version.to_string(),
+            reason,
+        };
 
-        Self { client }
+        let Some(pom) = self.fetch_pom(name, version).await? else {
+            return Err(RegistryError::VersionNotFound {
+                package: name.to_string(),
+                version: version.to_string(),
+            });
+        };
+        let Some(url) = pom.scm_git_url() else {
+            return Err(no_sources(
+                "no sources JAR is published and the POM has no git <scm> to fall back to"
+                    .to_string(),
+            ));
+        };
+
+        let tags = tag_candidates(pom.scm_tag.as_deref(), artifact_id, version);
+        debug!(package = name, version, url = %url, ?tags, "no sources jar, using scm");
+
+        let checkout = Checkout::clone_first_ref(&url, &tags).await.map_err(|e| {
+            no_sources(format!(
+                "no sources JAR is published and checking out {} failed: {}",
+                url, e
+            ))
+        })?;
+        read_checkout(checkout.path(), artifact_id)
     }
 }
 
@@ -74,40 +260,14 @@ struct SearchDoc {
 
 impl RegistryClient for MavenClient {
     async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
-        let (group_id, artifact_id) = parse_coordinates(name)?;
-
-        let url = format!(
-            "{}?q=g:{}+AND+a:{}&core=gav&rows=100&wt=json",
-            MAVEN_SEARCH_API, group_id, artifact_id
-        );
-
-        debug!(package = name, url = %url, "fetching maven package");
-
-        let response = self.client.get(&url).send().await?;
-
-        if !response.status().is_success() {
-            return Err(RegistryError::PackageNotFound(name.to_string()));
-        }
-
-        let search_resp: SearchResponse = response.json().await?;
-
-        if search_resp.response.docs.is_empty() {
-            return Err(RegistryError::PackageNotFound(name.to_string()));
-        }
-
-        let versions: Vec<String> = search_resp
-            .response
-            .docs
-            .iter()
-            .map(|d| d.v.clone())
-            .collect();
-
-        let latest = search_resp
-            .response
-            .docs
-            .first()
-            .and_then(|d| d.latest_version.clone())
-            .or_else(|| versions.first().cloned());
+        let searched = match &self.search_url {
+            Some(search_url) => self.search_versions(search_url, name).await?,
+            None => None,
+        };
+        let (versions, latest) = match searched {
+            Some(found) => found,
+            None => self.metadata_versions(name).await?,
+        };
 
         Ok(PackageInfo {
             name: name.to_string(),
@@ -120,33 +280,40 @@ impl RegistryClient for MavenClient {
     }
 
     async fn get_version(&self, name: &str, version: &str) -> Result<VersionInfo, RegistryError> {
-        let (group_id, artifact_id) = parse_coordinates(name)?;
-        let group_path = group_to_path(group_id);
-
         // Check if the sources JAR exists
-        let sources_url = format!(
-            "{}/{}/{}/{}/{}-{}-sources.jar",
-            MAVEN_REPO, group_path, artifact_id, version, artifact_id, version
-        );
+        let sources_url = self.artifact_url(name, version, "-sources.jar")?;
 
         debug!(package = name, version = version, url = %sources_url, "checking maven version");
 
         let response = self.client.head(&sources_url).send().await?;
-
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(RegistryError::VersionNotFound {
-                package: name.to_string(),
-                version: version.to_string(),
-            });
-        }
+        let has_sources = response.status() != reqwest::StatusCode::NOT_FOUND;
+
+        // Versions without a sources JAR exist if they have a POM
+        let pom = match self.fetch_pom(name, version).await? {
+            Some(pom) => pom,
+            None if has_sources => Pom::default(),
+            None => {
+                return Err(RegistryError::VersionNotFound {
+                    package: name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+        };
+
+        let repository = pom.repository();
+        let tarball_url = match (has_sources, pom.scm_git_url()) {
+            (false, Some(url)) => url,
+            _ => sources_url,
+        };
 
         Ok(VersionInfo {
             name: name.to_string(),
             version: version.to_string(),
-            description: None,
-            repository: None,
-            license: None,
-            tarball_url: sources_url,
+            description: pom.description,
+            repository,
+            license: pom.license,
+            tarball_url,
+            entry_point: None,
         })
     }
 
@@ -155,13 +322,7 @@ impl RegistryClient for MavenClient {
         name: &str,
         version: &str,
     ) -> Result<Vec<PackageFile>, RegistryError> {
-        let (group_id, artifact_id) = parse_coordinates(name)?;
-        let group_path = group_to_path(group_id);
-
-        let sources_url = format!(
-            "{}/{}/{}/{}/{}-{}-sources.jar",
-            MAVEN_REPO, group_path, artifact_id, version, artifact_id, version
-        );
+        let sources_url = self.artifact_url(name, version, "-sources.jar")?;
 
         debug!(
             package = name,
@@ -173,11 +334,8 @@ impl RegistryClient for MavenClient {
         let response = self.client.get(&sources_url).send().await?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
-            // Try the main JAR if sources JAR doesn't exist (some packages don't have sources)
-            return Err(RegistryError::VersionNotFound {
-                package: name.to_string(),
-                version: version.to_string(),
-            });
+            // Older artifacts often have no sources JAR
+            return self.download_scm_source(name, version).await;
         }
 
         let bytes = response.bytes().await?;
@@ -200,14 +358,22 @@ fn extract_sources_jar(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         }
 
         let path = entry.name().to_string();
+        if !is_safe_entry(&path, entry.is_symlink()) {
+            continue;
+        }
 
         if !is_indexable_file(&path) {
             continue;
         }
 
-        let mut content = String::new();
-        if entry.read_to_string(&mut content).is_ok() {
-            files.push(PackageFile { path, content });
+        if let Some(content) = read_source(&path, &mut entry) {
+            let is_test = is_test_file(&path);
+            files.push(PackageFile {
+                path,
+                content,
+                is_test,
+                is_vendored: false,
+            });
         }
     }
 
@@ -215,24 +381,253 @@ fn extract_sources_jar(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
     Ok(files)
 }
 
-/// Check if a file should be indexed.
-fn is_indexable_file(path: &str) -> bool {
-    let path_lower = path.to_lowercase();
+/// Parts of a POM used for package metadata and the SCM fallback.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Pom {
+    description: Option<String>,
+    /// Name of the first listed license
+    license: Option<String>,
+    scm_connection: Option<String>,
+    scm_developer_connection: Option<String>,
+    scm_url: Option<String>,
+    scm_tag: Option<String>,
+}
 
-    // Include Java source files
-    if path_lower.ends_with(".java") {
-        // Skip test files
-        if path_lower.contains("/test/") || path_lower.contains("test.java") {
-            return false;
+impl Pom {
+    /// Git URL to clone for the `<scm>` repository.
+    ///
+    /// Prefers `scm:git:` connections; a browsable `<url>` is only used on
+    /// hosts whose repository URLs are known.
+    fn scm_git_url(&self) -> Option<String> {
+        let connection = [&self.scm_connection, &self.scm_developer_connection]
+            .into_iter()
+            .flatten()
+            .find_map(|c| c.strip_prefix("scm:git:"))
+            .filter(|url| !url.contains("${"))
+            .map(normalize_git_url);
+
+        connection.or_else(|| self.scm_url.as_deref().and_then(known_host_repo))
+    }
+
+    /// Browsable repository URL, falling back to the clone URL.
+    fn repository(&self) -> Option<String> {
+        self.scm_url
+            .clone()
+            .filter(|url| !url.contains("${"))
+            .or_else(|| self.scm_git_url())
+    }
+}
+
+/// Versions listed in a `maven-metadata.xml`.
+#[derive(Debug, Default, PartialEq)]
+struct MavenMetadata {
+    /// Oldest first, as published.
+    versions: Vec<String>,
+    latest: Option<String>,
+    release: Option<String>,
+}
+
+/// Parse the `<versioning>` of an artifact's `maven-metadata.xml`.
+fn parse_metadata(content: &str) -> MavenMetadata {
+    let mut metadata = MavenMetadata::default();
+    let mut reader = Reader::from_str(content);
+    let mut path: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+            }
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().unwrap_or_default();
+                let text = text.trim();
+                if text.is_empty() {
+                    buf.clear();
+                    continue;
+                }
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                match path.as_slice() {
+                    ["metadata", "versioning", "versions", "version"] => {
+                        metadata.versions.push(text.to_string());
+                    }
+                    ["metadata", "versioning", "latest"] => {
+                        metadata.latest = Some(text.to_string());
+                    }
+                    ["metadata", "versioning", "release"] => {
+                        metadata.release = Some(text.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
         }
-        return true;
+        buf.clear();
+    }
+
+    metadata
+}
+
+/// Parse the project description, license and `<scm>` of a POM.
+///
+/// Values inherited from a parent POM aren't resolved.
+fn parse_pom(content: &str) -> Pom {
+    let mut pom = Pom::default();
+    let mut reader = Reader::from_str(content);
+    let mut path: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+            }
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().unwrap_or_default();
+                // The first value wins, e.g. the first of several licenses
+                if let Some(field) = pom_field(&mut pom, &path)
+                    && field.is_none()
+                    && !text.trim().is_empty()
+                {
+                    *field = Some(text.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
     }
 
-    // Include Kotlin source files (some Maven packages are Kotlin)
-    if path_lower.ends_with(".kt") || path_lower.ends_with(".kts") {
-        if path_lower.contains("/test/") {
-            return false;
+    pom
+}
+
+/// The POM field stored at an element path, if it is one `parse_pom` reads.
+fn pom_field<'a>(pom: &'a mut Pom, path: &[String]) -> Option<&'a mut Option<String>> {
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    match path.as_slice() {
+        ["project", "description"] => Some(&mut pom.description),
+        ["project", "licenses", "license", "name"] => Some(&mut pom.license),
+        ["project", "scm", "connection"] => Some(&mut pom.scm_connection),
+        ["project", "scm", "developerConnection"] => Some(&mut pom.scm_developer_connection),
+        ["project", "scm", "url"] => Some(&mut pom.scm_url),
+        ["project", "scm", "tag"] => Some(&mut pom.scm_tag),
+        _ => None,
+    }
+}
+
+/// Rewrite `git://` and `git@host:` URLs on known hosts to HTTPS, which
+/// clones without credentials.
+fn normalize_git_url(url: &str) -> String {
+    let url = url.trim();
+    let rest = url
+        .strip_prefix("git://")
+        .or_else(|| url.strip_prefix("ssh://git@"))
+        .map(str::to_string)
+        .or_else(|| url.strip_prefix("git@").map(|r| r.replacen(':', "/", 1)));
+
+    match rest {
+        Some(rest)
+            if KNOWN_GIT_HOSTS
+                .iter()
+                .any(|h| rest.starts_with(&format!("{}/", h))) =>
+        {
+            format!("https://{}", rest)
         }
+        _ => url.to_string(),
+    }
+}
+
+/// `https://host/owner/repo` for a browsable URL on a known git host
+/// (`https://github.com/owner/repo/tree/main` -> `https://github.com/owner/repo`).
+fn known_host_repo(url: &str) -> Option<String> {
+    let rest = url
+        .trim()
+        .strip_prefix("https://")
+        .or_else(|| url.trim().strip_prefix("http://"))?;
+    let parts: Vec<&str> = rest.split('/').filter(|p| !p.is_empty()).collect();
+    match parts.as_slice() {
+        [host, owner, repo, ..] if KNOWN_GIT_HOSTS.contains(host) => Some(format!(
+            "https://{}/{}/{}",
+            host,
+            owner,
+            repo.trim_end_matches(".git")
+        )),
+        _ => None,
+    }
+}
+
+/// Tags a release of `version` is likely tagged with, the POM's own
+/// `<tag>` first.
+fn tag_candidates(scm_tag: Option<&str>, artifact_id: &str, version: &str) -> Vec<String> {
+    let mut tags: Vec<String> = scm_tag
+        .filter(|t| *t != "HEAD" && !t.contains("${"))
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    for tag in [
+        format!("v{}", version),
+        version.to_string(),
+        format!("{}-{}", artifact_id, version),
+        format!("release-{}", version),
+    ] {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Read indexable files from a checkout, from the artifact's module
+/// directory when the repository is a multi-module build.
+fn read_checkout(root: &Path, artifact_id: &str) -> Result<Vec<PackageFile>, RegistryError> {
+    let module_root = match root.join(artifact_id) {
+        dir if dir.is_dir() => dir,
+        _ => root.to_path_buf(),
+    };
+
+    let mut files = Vec::new();
+    for path in git::list_files(&module_root)? {
+        if !is_indexable_file(&path) {
+            continue;
+        }
+
+        if let Some(content) = read_source(&path, File::open(module_root.join(&path))?) {
+            let is_test = is_test_file(&path);
+            files.push(PackageFile {
+                path,
+                content,
+                is_test,
+                is_vendored: false,
+            });
+        }
+    }
+
+    debug!(
+        file_count = files.len(),
+        "read source files from scm checkout"
+    );
+    Ok(files)
+}
+
+/// Check if a file should be indexed.
+fn is_indexable_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+
+    // Include source files in any language we parse, and Kotlin (some Maven
+    // packages are Kotlin)
+    if super::is_source_file(&path_lower)
+        || path_lower.ends_with(".kt")
+        || path_lower.ends_with(".kts")
+    {
         return true;
     }
 
@@ -244,9 +639,21 @@ fn is_indexable_file(path: &str) -> bool {
     false
 }
 
+/// Check if a file belongs to the tests (`src/test/`, `*Test.java`,
+/// `*Spec.scala`).
+fn is_test_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    path_lower.contains("/test/")
+        || path_lower.ends_with("test.java")
+        || path_lower.ends_with("test.scala")
+        || path_lower.ends_with("spec.scala")
+        || path_lower.ends_with("suite.scala")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::serve;
 
     #[test]
     fn test_parse_coordinates() {
@@ -272,15 +679,251 @@ mod tests {
         assert!(is_indexable_file("src/main/java/com/example/App.java"));
         assert!(is_indexable_file("com/google/common/collect/Lists.java"));
         assert!(is_indexable_file("README.md"));
-
-        // Skip tests
-        assert!(!is_indexable_file("src/test/java/com/example/AppTest.java"));
+        assert!(is_indexable_file("org/apache/spark/rdd/RDD.scala"));
+        assert!(is_indexable_file("com/example/Client.kt"));
+
+        // Tests are returned, marked by is_test_file
+        assert!(is_indexable_file("src/test/java/com/example/AppTest.java"));
+        assert!(is_test_file("src/test/java/com/example/AppTest.java"));
+        assert!(is_test_file("com/example/AppTest.java"));
+        assert!(is_test_file("src/test/kotlin/AppSpec.kt"));
+        assert!(is_test_file("org/apache/spark/rdd/RDDSuite.scala"));
+        assert!(is_test_file("com/example/AppSpec.scala"));
+        assert!(!is_test_file("src/main/java/com/example/App.java"));
+        assert!(!is_test_file("org/apache/spark/rdd/RDD.scala"));
 
         // Skip non-source files
         assert!(!is_indexable_file("META-INF/MANIFEST.MF"));
         assert!(!is_indexable_file("pom.xml"));
     }
 
+    const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <parent><description>Parent</description></parent>
+  <artifactId>demo</artifactId>
+  <description>
+    A demo
+    library
+  </description>
+  <licenses>
+    <license><name>Apache-2.0</name></license>
+    <license><name>MIT</name></license>
+  </licenses>
+  <scm>
+    <connection>scm:git:git://github.com/example/demo.git</connection>
+    <url>https://github.com/example/demo/tree/main</url>
+    <tag>HEAD</tag>
+  </scm>
+</project>"#;
+
+    #[test]
+    fn test_parse_pom() {
+        let pom = parse_pom(POM);
+        assert_eq!(pom.description.as_deref(), Some("A demo library"));
+        assert_eq!(pom.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            pom.scm_git_url().as_deref(),
+            Some("https://github.com/example/demo.git")
+        );
+        assert_eq!(
+            pom.repository().as_deref(),
+            Some("https://github.com/example/demo/tree/main")
+        );
+        assert_eq!(pom.scm_tag.as_deref(), Some("HEAD"));
+    }
+
+    #[test]
+    fn test_scm_git_url() {
+        let scm = |connection: Option<&str>, url: Option<&str>| Pom {
+            scm_connection: connection.map(str::to_string),
+            scm_url: url.map(str::to_string),
+            ..Pom::default()
+        };
+
+        assert_eq!(
+            scm(Some("scm:git:git@github.com:example/demo.git"), None).scm_git_url(),
+            Some("https://github.com/example/demo.git".to_string())
+        );
+        assert_eq!(
+            scm(Some("scm:git:https://git.example.com/demo.git"), None).scm_git_url(),
+            Some("https://git.example.com/demo.git".to_string())
+        );
+        // Browsable URLs only on known hosts
+        assert_eq!(
+            scm(None, Some("https://gitlab.com/example/demo/-/tree/main")).scm_git_url(),
+            Some("https://gitlab.com/example/demo".to_string())
+        );
+        assert_eq!(
+            scm(None, Some("https://example.com/demo")).scm_git_url(),
+            None
+        );
+        // Other SCMs and unresolved properties are unusable
+        assert_eq!(
+            scm(Some("scm:svn:https://svn.example.com/demo"), None).scm_git_url(),
+            None
+        );
+        assert_eq!(
+            scm(Some("scm:git:${project.scm.url}"), None).scm_git_url(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tag_candidates() {
+        assert_eq!(
+            tag_candidates(Some("demo-parent-1.0"), "demo", "1.0"),
+            vec!["demo-parent-1.0", "v1.0", "1.0", "demo-1.0", "release-1.0"]
+        );
+        assert_eq!(tag_candidates(Some("HEAD"), "demo", "1.0")[0], "v1.0");
+    }
+
+    /// Serve `pom` as the POM of com.example:demo:1.0.0; everything else,
+    /// including the sources JAR, is a 404.
+    async fn stub_repo(pom: String) -> String {
+        let app = axum::Router::new().route(
+            "/com/example/demo/1.0.0/demo-1.0.0.pom",
+            axum::routing::get(move || async move { pom }),
+        );
+        serve(app).await
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[tokio::test]
+    async fn test_download_falls_back_to_scm() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let source = repo.path().join("src/main/java/com/example");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("Demo.java"), "public class Demo {}").unwrap();
+        git(repo.path(), &["init", "--quiet"]);
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "--quiet", "-m", "Release"]);
+        git(repo.path(), &["tag", "demo-1.0.0"]);
+
+        let pom = format!(
+            "<project><scm><connection>scm:git:file://{}</connection></scm></project>",
+            repo.path().display()
+        );
+        let client = MavenClient::with_repo_url(stub_repo(pom).await);
+
+        let files = client
+            .download_source("com.example:demo", "1.0.0")
+            .await
+            .unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/main/java/com/example/Demo.java"]);
+    }
+
+    #[tokio::test]
+    async fn test_download_without_sources_or_scm() {
+        let client = MavenClient::with_repo_url(stub_repo("<project/>".to_string()).await);
+
+        let err = client
+            .download_source("com.example:demo", "1.0.0")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::NoSourcesAvailable { .. }));
+
+        // No POM either: the version doesn't exist
+        let err = client
+            .download_source("com.example:demo", "2.0.0")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::VersionNotFound { .. }));
+    }
+
+    const METADATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>com.example</groupId>
+  <artifactId>demo</artifactId>
+  <versioning>
+    <latest>2.0.0-SNAPSHOT</latest>
+    <release>1.1.0</release>
+    <versions>
+      <version>1.0.0</version>
+      <version>1.1.0</version>
+      <version>2.0.0-SNAPSHOT</version>
+    </versions>
+    <lastUpdated>20240101000000</lastUpdated>
+  </versioning>
+</metadata>"#;
+
+    #[test]
+    fn test_parse_metadata() {
+        assert_eq!(
+            parse_metadata(METADATA),
+            MavenMetadata {
+                versions: vec![
+                    "1.0.0".to_string(),
+                    "1.1.0".to_string(),
+                    "2.0.0-SNAPSHOT".to_string()
+                ],
+                latest: Some("2.0.0-SNAPSHOT".to_string()),
+                release: Some("1.1.0".to_string()),
+            }
+        );
+        assert_eq!(parse_metadata("<metadata/>"), MavenMetadata::default());
+        assert_eq!(parse_metadata("not xml <"), MavenMetadata::default());
+    }
+
+    /// Repository serving [`METADATA`] for com.example:demo, behind a search
+    /// API that finds nothing and one that is throttled.
+    async fn stub_metadata_repo() -> String {
+        let app = axum::Router::new()
+            .route(
+                "/com/example/demo/maven-metadata.xml",
+                axum::routing::get(|| async { METADATA }),
+            )
+            .route(
+                "/search",
+                axum::routing::get(|| async {
+                    axum::Json(serde_json::json!({ "response": { "docs": [] } }))
+                }),
+            )
+            .route(
+                "/throttled",
+                axum::routing::get(|| async { axum::http::StatusCode::TOO_MANY_REQUESTS }),
+            );
+        serve(app).await
+    }
+
+    #[tokio::test]
+    async fn test_get_package_falls_back_to_metadata() {
+        let url = stub_metadata_repo().await;
+        let client = MavenClient {
+            search_url: Some(format!("{}/search", url)),
+            ..MavenClient::with_repo_url(url.clone())
+        };
+
+        let pkg = client.get_package("com.example:demo").await.unwrap();
+        assert_eq!(pkg.versions, vec!["2.0.0-SNAPSHOT", "1.1.0", "1.0.0"]);
+        assert_eq!(pkg.latest_version.as_deref(), Some("1.1.0"));
+
+        // A throttled search falls back at once instead of backing off
+        let client = MavenClient {
+            search_url: Some(format!("{}/throttled", url)),
+            ..MavenClient::with_repo_url(url.clone())
+        };
+        let pkg = client.get_package("com.example:demo").await.unwrap();
+        assert_eq!(pkg.latest_version.as_deref(), Some("1.1.0"));
+
+        // Without a search API the metadata is read directly
+        let client = MavenClient::with_repo_url(url);
+        let pkg = client.get_package("com.example:demo").await.unwrap();
+        assert_eq!(pkg.latest_version.as_deref(), Some("1.1.0"));
+        let err = client.get_package("com.example:missing").await.unwrap_err();
+        assert!(matches!(err, RegistryError::PackageNotFound(_)));
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_get_package_guava() {
@@ -1,4 +1,4 @@
-//! Registry clients for fetching packages from npm, PyPI, crates.io, etc.
+//! Registry clients for fetching packages from npm, PyPI, crates.io, pub.dev, etc.
 //!
 //! This crate provides async clients for downloading package source code
 //! from various package registries.
@@ -12,90 +12,295 @@
 //! let client = RegistryClients::new(Registry::Npm);
 //! let files: Vec<PackageFile> = client.download_source("lodash", "4.17.21").await?;
 //! ```
+//!
+//! Attach a [`RegistryCache`] with [`RegistryClients::with_cache`] to reuse
+//! responses across runs instead of refetching them. Build clients with
+//! [`RegistryClients::with_http_client`] and [`http_client`] to send requests
+//! through a proxy or trust a custom CA. In offline mode
+//! ([`RegistryClients::with_offline`]) only cached responses are served.
+//! Share one [`RegistryLimits`] between clients
+//! ([`RegistryClients::with_limits`]) to bound concurrent requests to each
+//! registry and back off when one rate-limits.
 
 #![allow(dead_code)]
 
+mod archive;
+mod cache;
 mod client;
 mod crates_io;
+mod encoding;
+mod entry;
 mod error;
+mod git;
 mod go;
+mod limits;
 mod maven;
 mod npm;
+mod r#pub;
 mod pypi;
+mod suggest;
 
-pub use client::{PackageFile, PackageInfo, RegistryClient, VersionInfo};
+pub use cache::{CacheKind, DEFAULT_CACHE_MAX_MB, DEFAULT_CACHE_TTL_HOURS, RegistryCache};
+pub use client::{
+    HttpOptions, LATEST_VERSION, PackageFile, PackageInfo, RegistryClient, VersionInfo,
+    http_client, resolve_version,
+};
 pub use crates_io::CratesIoClient;
+pub use entry::entry_point;
 pub use error::RegistryError;
-pub use go::GoClient;
+pub use git::GitClient;
+pub use go::{GoClient, RefSource};
+pub use limits::RegistryLimits;
 pub use maven::MavenClient;
 pub use npm::NpmClient;
+pub use r#pub::PubClient;
 pub use pypi::PypiClient;
+pub use suggest::rank_suggestions;
+
+use std::future::Future;
 
+use crate::indexer::Language;
 use crate::types::Registry;
+use tracing::debug;
 
 /// Unified registry client that dispatches to the appropriate implementation.
-pub enum RegistryClients {
+///
+/// Consults an optional [`RegistryCache`] before hitting the network.
+pub struct RegistryClients {
+    registry: Registry,
+    client: Client,
+    cache: Option<RegistryCache>,
+    limits: Option<RegistryLimits>,
+    offline: bool,
+}
+
+enum Client {
     Npm(NpmClient),
     Pypi(PypiClient),
     Crates(CratesIoClient),
     Maven(MavenClient),
     Go(GoClient),
+    Pub(PubClient),
+    Git(GitClient),
 }
 
 impl RegistryClients {
     /// Create a new client for the given registry.
     pub fn new(registry: Registry) -> Self {
-        match registry {
-            Registry::Npm => Self::Npm(NpmClient::new()),
-            Registry::Pypi => Self::Pypi(PypiClient::new()),
-            Registry::Crates => Self::Crates(CratesIoClient::new()),
-            Registry::Maven => Self::Maven(MavenClient::new()),
-            Registry::Go => Self::Go(GoClient::new()),
+        Self::with_http_client(registry, client::default_http_client())
+    }
+
+    /// Create a client for the given registry sending requests through
+    /// `http` (see [`http_client`]).
+    pub fn with_http_client(registry: Registry, http: reqwest::Client) -> Self {
+        let client = match registry {
+            Registry::Npm => Client::Npm(NpmClient::with_client(http)),
+            Registry::Pypi => Client::Pypi(PypiClient::with_client(http)),
+            Registry::Crates => Client::Crates(CratesIoClient::with_client(http)),
+            Registry::Maven => Client::Maven(MavenClient::with_client(http)),
+            Registry::Go => Client::Go(GoClient::with_client(http)),
+            Registry::Pub => Client::Pub(PubClient::with_client(http)),
+            Registry::Git => Client::Git(GitClient::new()),
+        };
+        Self {
+            registry,
+            client,
+            cache: None,
+            limits: None,
+            offline: false,
+        }
+    }
+
+    /// Serve responses from `cache` when possible, filling it on misses.
+    pub fn with_cache(mut self, cache: RegistryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Send network requests through `limits`' lane for this registry.
+    /// Cache hits don't take a slot.
+    pub fn with_limits(mut self, limits: RegistryLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Never touch the network: cache misses fail with
+    /// [`RegistryError::OfflineMode`] instead of sending a request.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Run a network request, in this registry's lane when limits are set.
+    async fn request<T, F, Fut>(&self, request: F) -> Result<T, RegistryError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, RegistryError>>,
+    {
+        if self.offline {
+            return Err(RegistryError::OfflineMode);
+        }
+        match &self.limits {
+            Some(limits) => limits.run(self.registry, request).await,
+            None => request().await,
         }
     }
 
     /// Get package metadata.
     pub async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
-        match self {
-            Self::Npm(c) => c.get_package(name).await,
-            Self::Pypi(c) => c.get_package(name).await,
-            Self::Crates(c) => c.get_package(name).await,
-            Self::Maven(c) => c.get_package(name).await,
-            Self::Go(c) => c.get_package(name).await,
+        let fetch = self.request(|| async move {
+            match &self.client {
+                Client::Npm(c) => c.get_package(name).await,
+                Client::Pypi(c) => c.get_package(name).await,
+                Client::Crates(c) => c.get_package(name).await,
+                Client::Maven(c) => c.get_package(name).await,
+                Client::Go(c) => c.get_package(name).await,
+                Client::Pub(c) => c.get_package(name).await,
+                Client::Git(c) => c.get_package(name).await,
+            }
+        });
+
+        match &self.cache {
+            Some(cache) => {
+                cache
+                    .fetch(CacheKind::Package, self.registry.as_str(), name, "", fetch)
+                    .await
+            }
+            None => fetch.await,
         }
     }
 
+    /// Package names the registry's search returns for `text`, most relevant
+    /// first, for suggesting what a misspelled name meant.
+    ///
+    /// Best effort: empty for registries without a search API (only npm has
+    /// one) or when the search fails.
+    pub async fn search_names(&self, text: &str) -> Vec<String> {
+        if self.offline {
+            return Vec::new();
+        }
+        let result = match &self.client {
+            Client::Npm(c) => c.search_names(text).await,
+            _ => return Vec::new(),
+        };
+        result.unwrap_or_else(|e| {
+            debug!(registry = %self.registry, error = %e, "package search failed");
+            Vec::new()
+        })
+    }
+
+    /// Resolve a requested version, using the latest release when it is
+    /// omitted or `"latest"`.
+    pub async fn resolve_version(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<String, RegistryError> {
+        resolve_version(self, name, version).await
+    }
+
     /// Get version metadata.
     pub async fn get_version(
         &self,
         name: &str,
         version: &str,
     ) -> Result<VersionInfo, RegistryError> {
-        match self {
-            Self::Npm(c) => c.get_version(name, version).await,
-            Self::Pypi(c) => c.get_version(name, version).await,
-            Self::Crates(c) => c.get_version(name, version).await,
-            Self::Maven(c) => c.get_version(name, version).await,
-            Self::Go(c) => c.get_version(name, version).await,
+        let fetch = self.request(|| async move {
+            match &self.client {
+                Client::Npm(c) => c.get_version(name, version).await,
+                Client::Pypi(c) => c.get_version(name, version).await,
+                Client::Crates(c) => c.get_version(name, version).await,
+                Client::Maven(c) => c.get_version(name, version).await,
+                Client::Go(c) => c.get_version(name, version).await,
+                Client::Pub(c) => c.get_version(name, version).await,
+                Client::Git(c) => c.get_version(name, version).await,
+            }
+        });
+
+        match &self.cache {
+            Some(cache) => {
+                cache
+                    .fetch(
+                        CacheKind::Version,
+                        self.registry.as_str(),
+                        name,
+                        version,
+                        fetch,
+                    )
+                    .await
+            }
+            None => fetch.await,
         }
     }
 
     /// Download and extract package source files.
+    ///
+    /// Only cached when the cache has source caching enabled.
     pub async fn download_source(
         &self,
         name: &str,
         version: &str,
     ) -> Result<Vec<PackageFile>, RegistryError> {
-        match self {
-            Self::Npm(c) => c.download_source(name, version).await,
-            Self::Pypi(c) => c.download_source(name, version).await,
-            Self::Crates(c) => c.download_source(name, version).await,
-            Self::Maven(c) => c.download_source(name, version).await,
-            Self::Go(c) => c.download_source(name, version).await,
+        let fetch = self.request(|| async move {
+            match &self.client {
+                Client::Npm(c) => c.download_source(name, version).await,
+                Client::Pypi(c) => c.download_source(name, version).await,
+                Client::Crates(c) => c.download_source(name, version).await,
+                Client::Maven(c) => c.download_source(name, version).await,
+                Client::Go(c) => c.download_source(name, version).await,
+                Client::Pub(c) => c.download_source(name, version).await,
+                Client::Git(c) => c.download_source(name, version).await,
+            }
+        });
+
+        match &self.cache {
+            Some(cache) if cache.caches_sources() => {
+                cache
+                    .fetch(
+                        CacheKind::Source,
+                        self.registry.as_str(),
+                        name,
+                        version,
+                        fetch,
+                    )
+                    .await
+            }
+            _ => fetch.await,
         }
     }
 }
 
+impl RegistryClient for RegistryClients {
+    async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
+        RegistryClients::get_package(self, name).await
+    }
+
+    async fn get_version(&self, name: &str, version: &str) -> Result<VersionInfo, RegistryError> {
+        RegistryClients::get_version(self, name, version).await
+    }
+
+    async fn download_source(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Vec<PackageFile>, RegistryError> {
+        RegistryClients::download_source(self, name, version).await
+    }
+}
+
+/// Check if a file is source code in any language idx parses.
+///
+/// Packages are not limited to their registry's language: npm packages ship
+/// Rust via napi-rs, Python sdists ship pyo3 extensions. Minified and bundled
+/// files are not source.
+fn is_source_file(path_lower: &str) -> bool {
+    if path_lower.contains(".min.") || path_lower.contains(".bundle.") {
+        return false;
+    }
+
+    Language::from_path(path_lower).is_some_and(|lang| !lang.is_documentation())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,5 +312,40 @@ mod tests {
         let _crates = RegistryClients::new(Registry::Crates);
         let _maven = RegistryClients::new(Registry::Maven);
         let _go = RegistryClients::new(Registry::Go);
+        let _pub = RegistryClients::new(Registry::Pub);
+        let _git = RegistryClients::new(Registry::Git);
+    }
+
+    #[tokio::test]
+    async fn test_offline_serves_only_cached_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = RegistryCache::new(dir.path());
+        let info = VersionInfo {
+            name: "left-pad".to_string(),
+            version: "1.3.0".to_string(),
+            description: None,
+            repository: None,
+            license: None,
+            tarball_url: "https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz".to_string(),
+            entry_point: None,
+        };
+        cache
+            .fetch(CacheKind::Version, "npm", "left-pad", "1.3.0", async {
+                Ok(info.clone())
+            })
+            .await
+            .unwrap();
+
+        let client = RegistryClients::new(Registry::Npm)
+            .with_cache(cache)
+            .with_offline(true);
+        let cached = client.get_version("left-pad", "1.3.0").await.unwrap();
+        assert_eq!(cached.tarball_url, info.tarball_url);
+
+        let err = client.download_source("left-pad", "1.3.0").await;
+        assert!(matches!(err, Err(RegistryError::OfflineMode)));
+        let err = client.get_package("left-pad").await;
+        assert!(matches!(err, Err(RegistryError::OfflineMode)));
+        assert!(client.search_names("left pad").await.is_empty());
     }
 }
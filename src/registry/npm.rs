@@ -1,7 +1,6 @@
 //! npm registry client.
 
 use std::collections::HashMap;
-use std::io::Read;
 
 use flate2::read::GzDecoder;
 use reqwest::Client;
@@ -9,7 +8,9 @@ use serde::Deserialize;
 use tar::Archive;
 use tracing::debug;
 
-use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo};
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo, default_http_client};
+use super::encoding::read_source;
 use super::error::RegistryError;
 
 const NPM_REGISTRY: &str = "https://registry.npmjs.org";
@@ -22,18 +23,44 @@ pub struct NpmClient {
 
 impl NpmClient {
     pub fn new() -> Self {
+        Self::with_client(default_http_client())
+    }
+
+    /// Client sending requests through `client` (see [`super::http_client`]).
+    pub fn with_client(client: Client) -> Self {
         Self {
-            client: Client::new(),
+            client,
             registry_url: NPM_REGISTRY.to_string(),
         }
     }
 
     pub fn with_registry_url(registry_url: String) -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
             registry_url,
         }
     }
+
+    /// Names of packages matching `text` in the registry's search, most
+    /// relevant first.
+    pub async fn search_names(&self, text: &str) -> Result<Vec<String>, RegistryError> {
+        let url = format!("{}/-/v1/search", self.registry_url);
+        debug!(text, url = %url, "searching npm packages");
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("text", text), ("size", "20")])
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(RegistryError::RateLimited);
+        }
+
+        let search: NpmSearchResponse = response.error_for_status()?.json().await?;
+        Ok(search.objects.into_iter().map(|o| o.package.name).collect())
+    }
 }
 
 impl Default for NpmClient {
@@ -88,6 +115,58 @@ struct NpmVersionInfo {
     license: Option<LicenseField>,
     repository: Option<Repository>,
     dist: NpmDist,
+    main: Option<String>,
+    module: Option<String>,
+    exports: Option<serde_json::Value>,
+}
+
+impl NpmVersionInfo {
+    /// The module `import "<name>"` resolves to: the root of `exports`, else
+    /// `module` (the ES build), else `main`.
+    fn entry_point(&self) -> Option<String> {
+        self.exports
+            .as_ref()
+            .and_then(root_export)
+            .or_else(|| self.module.clone())
+            .or_else(|| self.main.clone())
+            .map(|path| path.trim_start_matches("./").to_string())
+            .filter(|path| !path.is_empty())
+    }
+}
+
+/// Conditions tried, in order, when an export maps to several files. `types`
+/// is left out: it points at declarations, not the code.
+const EXPORT_CONDITIONS: &[&str] = &["import", "module", "default", "require", "node"];
+
+/// Target of the package root (`"."`) in an `exports` field, which is either
+/// a map of subpaths or the root's target itself.
+fn root_export(exports: &serde_json::Value) -> Option<String> {
+    match exports {
+        serde_json::Value::Object(map) if map.keys().any(|key| key.starts_with('.')) => {
+            map.get(".").and_then(export_target)
+        }
+        target => export_target(target),
+    }
+}
+
+/// File an export target resolves to, following conditions and fallback
+/// arrays.
+fn export_target(target: &serde_json::Value) -> Option<String> {
+    match target {
+        serde_json::Value::String(path) => Some(path.clone()),
+        serde_json::Value::Array(targets) => targets.iter().find_map(export_target),
+        serde_json::Value::Object(conditions) => EXPORT_CONDITIONS
+            .iter()
+            .filter_map(|condition| conditions.get(*condition))
+            .find_map(export_target)
+            .or_else(|| {
+                conditions
+                    .iter()
+                    .filter(|(condition, _)| condition.as_str() != "types")
+                    .find_map(|(_, target)| export_target(target))
+            }),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +174,21 @@ struct NpmDist {
     tarball: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct NpmSearchResponse {
+    objects: Vec<NpmSearchObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmSearchObject {
+    package: NpmSearchPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmSearchPackage {
+    name: String,
+}
+
 impl RegistryClient for NpmClient {
     async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
         let url = format!("{}/{}", self.registry_url, name);
@@ -142,6 +236,7 @@ impl RegistryClient for NpmClient {
         }
 
         let npm_ver: NpmVersionInfo = response.json().await?;
+        let entry_point = npm_ver.entry_point();
 
         Ok(VersionInfo {
             name: npm_ver.name,
@@ -150,6 +245,7 @@ impl RegistryClient for NpmClient {
             repository: npm_ver.repository.and_then(|r| r.url),
             license: npm_ver.license.map(|l| l.as_str().to_string()),
             tarball_url: npm_ver.dist.tarball,
+            entry_point,
         })
     }
 
@@ -187,7 +283,14 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         let path_str = path.to_string_lossy();
 
         // Skip directories
-        if entry.header().entry_type().is_dir() {
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            continue;
+        }
+        if !is_safe_entry(
+            &path_str,
+            entry_type.is_symlink() || entry_type.is_hard_link(),
+        ) {
             continue;
         }
 
@@ -203,11 +306,12 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         }
 
         // Read content
-        let mut content = String::new();
-        if entry.read_to_string(&mut content).is_ok() {
+        if let Some(content) = read_source(&clean_path, &mut entry) {
             files.push(PackageFile {
+                is_test: is_test_file(&clean_path),
                 path: clean_path,
                 content,
+                is_vendored: false,
             });
         }
     }
@@ -232,17 +336,8 @@ fn is_indexable_file(path: &str) -> bool {
         return true;
     }
 
-    // Source file extensions
-    let source_extensions = [
-        ".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs", ".py", ".pyi", ".rs", ".go",
-        ".java",
-    ];
-
-    // Must have a source extension
-    if !source_extensions
-        .iter()
-        .any(|ext| path_lower.ends_with(ext))
-    {
+    // Must be source in a language we parse (native addons bring Rust, ...)
+    if !super::is_source_file(&path_lower) {
         return false;
     }
 
@@ -254,17 +349,13 @@ fn is_indexable_file(path: &str) -> bool {
         return false;
     }
 
-    // Skip common non-source directories (but NOT examples/docs - we want those!)
+    // Skip common non-source directories (but NOT examples/docs/tests - we want those!)
     let skip_dirs = [
         "node_modules/",
         "dist/",
         "build/",
         "__pycache__/",
         ".git/",
-        "test/",
-        "tests/",
-        "__tests__/",
-        "spec/",
         "benchmark/",
         "benchmarks/",
     ];
@@ -276,6 +367,18 @@ fn is_indexable_file(path: &str) -> bool {
     true
 }
 
+/// Check if a file belongs to the package's tests (`__tests__/`, `*.test.ts`, ...).
+fn is_test_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    let file_name = path_lower.rsplit('/').next().unwrap_or(&path_lower);
+
+    let test_dirs = ["test/", "tests/", "__tests__/", "spec/"];
+
+    test_dirs.iter().any(|dir| path_lower.contains(dir))
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,7 +407,20 @@ mod tests {
         assert!(!is_indexable_file("src/index.min.js"));
         assert!(!is_indexable_file("node_modules/lodash/index.js"));
         assert!(!is_indexable_file("node_modules/foo/README.md"));
-        assert!(!is_indexable_file("test/index.test.ts"));
+
+        // Tests are returned, marked by is_test_file
+        assert!(is_indexable_file("test/index.test.ts"));
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("test/index.js"));
+        assert!(is_test_file("src/__tests__/parse.ts"));
+        assert!(is_test_file("src/parse.test.ts"));
+        assert!(is_test_file("lib/client.spec.js"));
+
+        assert!(!is_test_file("src/index.ts"));
+        assert!(!is_test_file("examples/basic.ts"));
     }
 
     #[test]
@@ -316,6 +432,96 @@ mod tests {
         assert_eq!(complex.as_str(), "Apache-2.0");
     }
 
+    #[test]
+    fn test_entry_point_from_exports() {
+        let entry_point = |fields: &str| {
+            let manifest = format!(
+                r#"{{"name": "pkg", "version": "1.0.0", "dist": {{"tarball": "t"}}{}}}"#,
+                fields
+            );
+            serde_json::from_str::<NpmVersionInfo>(&manifest)
+                .unwrap()
+                .entry_point()
+        };
+
+        assert_eq!(
+            entry_point(r#", "main": "lib/index.js""#).as_deref(),
+            Some("lib/index.js")
+        );
+        assert_eq!(
+            entry_point(r#", "main": "index.js", "module": "./esm/index.js""#).as_deref(),
+            Some("esm/index.js")
+        );
+        // exports wins over main, as a string, a subpath map or conditions
+        assert_eq!(
+            entry_point(r#", "main": "index.js", "exports": "./dist/index.mjs""#).as_deref(),
+            Some("dist/index.mjs")
+        );
+        assert_eq!(
+            entry_point(
+                r#", "exports": {"./utils": "./utils.js", ".": {"types": "./index.d.ts", "require": "./index.cjs", "import": "./index.mjs"}}"#
+            )
+            .as_deref(),
+            Some("index.mjs")
+        );
+        assert_eq!(
+            entry_point(
+                r#", "exports": {"types": "./index.d.ts", "node": {"default": "./node.js"}}"#
+            )
+            .as_deref(),
+            Some("node.js")
+        );
+        assert_eq!(
+            entry_point(r#", "exports": {".": [{"import": "./index.mjs"}, "./index.js"]}"#)
+                .as_deref(),
+            Some("index.mjs")
+        );
+        // Only subpaths exported: no root module
+        assert_eq!(
+            entry_point(r#", "main": "index.js", "exports": {"./a": "./a.js"}"#).as_deref(),
+            Some("index.js")
+        );
+        assert_eq!(entry_point(""), None);
+    }
+
+    fn tarball(entries: &[(&str, tar::EntryType, &str)]) -> Vec<u8> {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, entry_type, content) in entries {
+            let mut header = tar::Header::new_old();
+            // Written raw, since set_path refuses `..` components
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_size(content.len() as u64);
+            if entry_type.is_symlink() {
+                header.set_link_name("/etc/passwd").unwrap();
+            }
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_tarball_skips_entries_outside_package() {
+        let data = tarball(&[
+            ("../../etc/passwd", tar::EntryType::Regular, "root:x:0:0\n"),
+            ("package/../../evil.js", tar::EntryType::Regular, "evil()\n"),
+            ("package/lib/passwd.js", tar::EntryType::Symlink, ""),
+            (
+                "package/index.js",
+                tar::EntryType::Regular,
+                "module.exports = 1;\n",
+            ),
+        ]);
+
+        let files = extract_tarball(&data).unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["index.js"]);
+    }
+
     // Integration tests would hit the actual npm registry
     // Run with: cargo test --package index-registry -- --ignored
     #[tokio::test]
@@ -0,0 +1,286 @@
+//! pub.dev (Dart/Flutter) registry client.
+
+use flate2::read::GzDecoder;
+use reqwest::Client;
+use serde::Deserialize;
+use tar::Archive;
+use tracing::debug;
+
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo, default_http_client};
+use super::encoding::read_source;
+use super::error::RegistryError;
+
+const PUB_API: &str = "https://pub.dev/api";
+
+/// pub.dev registry client.
+pub struct PubClient {
+    client: Client,
+    api_url: String,
+}
+
+impl PubClient {
+    pub fn new() -> Self {
+        Self::with_client(default_http_client())
+    }
+
+    /// Client sending requests through `client` (see [`super::http_client`]).
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            api_url: PUB_API.to_string(),
+        }
+    }
+}
+
+impl Default for PubClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// pub.dev API response types
+#[derive(Debug, Deserialize)]
+struct PubPackageResponse {
+    name: String,
+    latest: PubVersionInfo,
+    versions: Vec<PubVersionInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PubVersionInfo {
+    version: String,
+    pubspec: Pubspec,
+    archive_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pubspec {
+    description: Option<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+}
+
+impl PubClient {
+    async fn fetch_version(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<PubVersionInfo, RegistryError> {
+        let url = format!("{}/packages/{}/versions/{}", self.api_url, name, version);
+        debug!(package = name, version = version, url = %url, "fetching pub version");
+
+        let response = self.client.get(&url).send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RegistryError::VersionNotFound {
+                package: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+impl RegistryClient for PubClient {
+    async fn get_package(&self, name: &str) -> Result<PackageInfo, RegistryError> {
+        let url = format!("{}/packages/{}", self.api_url, name);
+        debug!(package = name, url = %url, "fetching pub package");
+
+        let response = self.client.get(&url).send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RegistryError::PackageNotFound(name.to_string()));
+        }
+
+        let pkg: PubPackageResponse = response.json().await?;
+
+        let versions: Vec<String> = pkg.versions.iter().map(|v| v.version.clone()).collect();
+        let pubspec = pkg.latest.pubspec;
+
+        Ok(PackageInfo {
+            name: pkg.name,
+            description: pubspec.description,
+            repository: pubspec.repository.or(pubspec.homepage),
+            license: None, // Not in pubspec
+            versions,
+            latest_version: Some(pkg.latest.version),
+        })
+    }
+
+    async fn get_version(&self, name: &str, version: &str) -> Result<VersionInfo, RegistryError> {
+        let ver = self.fetch_version(name, version).await?;
+
+        Ok(VersionInfo {
+            name: name.to_string(),
+            version: ver.version,
+            description: ver.pubspec.description,
+            repository: ver.pubspec.repository.or(ver.pubspec.homepage),
+            license: None,
+            tarball_url: ver.archive_url,
+            entry_point: None,
+        })
+    }
+
+    async fn download_source(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Vec<PackageFile>, RegistryError> {
+        let ver = self.fetch_version(name, version).await?;
+
+        debug!(
+            package = name,
+            version = version,
+            archive = %ver.archive_url,
+            "downloading pub archive"
+        );
+
+        let response = self.client.get(&ver.archive_url).send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RegistryError::VersionNotFound {
+                package: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+
+        let bytes = response.bytes().await?;
+        extract_archive(&bytes)
+    }
+}
+
+/// Extract source files from a gzipped pub archive.
+fn extract_archive(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
+    let decoder = GzDecoder::new(data);
+    let mut archive = Archive::new(decoder);
+
+    let mut files = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?;
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            continue;
+        }
+
+        // pub archives have no top-level directory, just a leading "./" at most
+        let path_str = path.to_string_lossy();
+        if !is_safe_entry(
+            &path_str,
+            entry_type.is_symlink() || entry_type.is_hard_link(),
+        ) {
+            continue;
+        }
+        let clean_path = path_str.trim_start_matches("./").to_string();
+
+        if !is_indexable_file(&clean_path) {
+            continue;
+        }
+
+        if let Some(content) = read_source(&clean_path, &mut entry) {
+            files.push(PackageFile {
+                is_test: is_test_file(&clean_path),
+                path: clean_path,
+                content,
+                is_vendored: false,
+            });
+        }
+    }
+
+    debug!(
+        file_count = files.len(),
+        "extracted source files from archive"
+    );
+    Ok(files)
+}
+
+/// Check if a file should be indexed (Dart source, examples, or documentation).
+fn is_indexable_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+
+    // Include markdown documentation files
+    if path_lower.ends_with(".md") || path_lower.ends_with(".markdown") {
+        return true;
+    }
+
+    // Must be source in a language we parse
+    if !super::is_source_file(&path_lower) {
+        return false;
+    }
+
+    // Skip generated code
+    if path_lower.ends_with(".g.dart") || path_lower.ends_with(".freezed.dart") {
+        return false;
+    }
+
+    // Skip tooling (but NOT example/ or test/ - we want those!)
+    let skip_dirs = ["tool/", ".dart_tool/"];
+
+    if skip_dirs.iter().any(|d| path_lower.starts_with(d)) {
+        return false;
+    }
+
+    true
+}
+
+/// Check if a file belongs to the package's tests (`test/`, `*_test.dart`).
+fn is_test_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    path_lower.starts_with("test/") || path_lower.ends_with("_test.dart")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_indexable_file() {
+        // Dart source files
+        assert!(is_indexable_file("lib/provider.dart"));
+        assert!(is_indexable_file("lib/src/change_notifier_provider.dart"));
+        assert!(is_indexable_file("rust/src/api.rs"));
+
+        // Examples should be included!
+        assert!(is_indexable_file("example/lib/main.dart"));
+
+        // Markdown documentation
+        assert!(is_indexable_file("README.md"));
+        assert!(is_indexable_file("CHANGELOG.md"));
+
+        // Tests are returned, marked by is_test_file
+        assert!(is_indexable_file("test/provider_test.dart"));
+        assert!(is_test_file("test/provider_test.dart"));
+        assert!(is_test_file("lib/src/provider_test.dart"));
+        assert!(!is_test_file("lib/provider.dart"));
+
+        // Tooling and generated code skipped
+        assert!(!is_indexable_file("tool/build.dart"));
+        assert!(!is_indexable_file("lib/src/model.g.dart"));
+        assert!(!is_indexable_file("lib/src/model.freezed.dart"));
+
+        // Non-source files skipped
+        assert!(!is_indexable_file("pubspec.yaml"));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_get_package_provider() {
+        let client = PubClient::new();
+        let pkg = client.get_package("provider").await.unwrap();
+        assert_eq!(pkg.name, "provider");
+        assert!(!pkg.versions.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_download_provider() {
+        let client = PubClient::new();
+        let files = client.download_source("provider", "6.1.2").await.unwrap();
+        assert!(!files.is_empty());
+        assert!(files.iter().any(|f| f.path.ends_with(".dart")));
+    }
+}
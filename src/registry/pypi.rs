@@ -1,6 +1,6 @@
 //! PyPI registry client.
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek};
 
 use flate2::read::GzDecoder;
 use reqwest::Client;
@@ -9,7 +9,9 @@ use tar::Archive;
 use tracing::debug;
 use zip::ZipArchive;
 
-use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo};
+use super::archive::is_safe_entry;
+use super::client::{PackageFile, PackageInfo, RegistryClient, VersionInfo, default_http_client};
+use super::encoding::read_source;
 use super::error::RegistryError;
 
 const PYPI_API: &str = "https://pypi.org/pypi";
@@ -22,15 +24,20 @@ pub struct PypiClient {
 
 impl PypiClient {
     pub fn new() -> Self {
+        Self::with_client(default_http_client())
+    }
+
+    /// Client sending requests through `client` (see [`super::http_client`]).
+    pub fn with_client(client: Client) -> Self {
         Self {
-            client: Client::new(),
+            client,
             api_url: PYPI_API.to_string(),
         }
     }
 
     pub fn with_api_url(api_url: String) -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
             api_url,
         }
     }
@@ -132,6 +139,7 @@ impl RegistryClient for PypiClient {
             repository: pypi_ver.info.home_page,
             license: pypi_ver.info.license,
             tarball_url,
+            entry_point: None,
         })
     }
 
@@ -174,7 +182,14 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         let path = entry.path()?;
         let path_str = path.to_string_lossy().to_string();
 
-        if entry.header().entry_type().is_dir() {
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            continue;
+        }
+        if !is_safe_entry(
+            &path_str,
+            entry_type.is_symlink() || entry_type.is_hard_link(),
+        ) {
             continue;
         }
 
@@ -185,9 +200,10 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
             continue;
         }
 
-        let mut content = String::new();
-        if entry.read_to_string(&mut content).is_ok() {
+        if let Some(content) = read_source(&clean_path, &mut entry) {
             files.push(PackageFile {
+                is_test: is_test_file(&clean_path),
+                is_vendored: is_vendored_file(&clean_path),
                 path: clean_path,
                 content,
             });
@@ -205,6 +221,7 @@ fn extract_tarball(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
 fn extract_zip(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor).map_err(|e| RegistryError::Archive(e.to_string()))?;
+    let top_level = read_top_level(&mut archive);
 
     let mut files = Vec::new();
 
@@ -218,14 +235,26 @@ fn extract_zip(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
         }
 
         let path = file.name().to_string();
+        if !is_safe_entry(&path, file.is_symlink()) {
+            continue;
+        }
 
         if !is_indexable_file(&path) {
             continue;
         }
 
-        let mut content = String::new();
-        if file.read_to_string(&mut content).is_ok() {
-            files.push(PackageFile { path, content });
+        if let Some(content) = read_source(&path, &mut file) {
+            let is_test = is_test_file(&path);
+            let is_vendored = is_vendored_file(&path)
+                || top_level
+                    .as_deref()
+                    .is_some_and(|names| !in_top_level(&path, names));
+            files.push(PackageFile {
+                path,
+                content,
+                is_test,
+                is_vendored,
+            });
         }
     }
 
@@ -233,6 +262,50 @@ fn extract_zip(data: &[u8]) -> Result<Vec<PackageFile>, RegistryError> {
     Ok(files)
 }
 
+/// Top-level import names a wheel installs, from `*.dist-info/top_level.txt`.
+///
+/// `None` for sdist zips and for wheels whose build backend doesn't write the
+/// file; only the vendored-directory check applies to those.
+fn read_top_level<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<Vec<String>> {
+    let name = archive
+        .file_names()
+        .find(|name| {
+            name.strip_suffix("/top_level.txt")
+                .is_some_and(|dir| dir.ends_with(".dist-info") && !dir.contains('/'))
+        })?
+        .to_string();
+
+    let mut content = String::new();
+    archive
+        .by_name(&name)
+        .ok()?
+        .read_to_string(&mut content)
+        .ok()?;
+
+    let names: Vec<String> = content
+        .lines()
+        .map(|line| line.trim().replace('\\', "/"))
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!names.is_empty()).then_some(names)
+}
+
+/// Check if a wheel path belongs to one of its top-level packages or modules.
+fn in_top_level(path: &str, top_level: &[String]) -> bool {
+    top_level.iter().any(|name| {
+        path.strip_prefix(name.as_str())
+            .is_some_and(|rest| rest.starts_with('/') || rest == ".py" || rest == ".pyi")
+    })
+}
+
+/// Check if a file sits in a vendored dependency tree (`_vendor/`, `vendored/`,
+/// `vendor/`), like the copies of `requests` and `urllib3` inside pip.
+fn is_vendored_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    let mut dirs = path_lower.split('/').rev().skip(1);
+    dirs.any(|dir| matches!(dir, "_vendor" | "vendor" | "vendored" | "_vendored"))
+}
+
 /// Strip the first path component (e.g., "requests-2.28.0/src/..." -> "src/...")
 fn strip_first_component(path: &str) -> String {
     path.split_once('/')
@@ -252,30 +325,38 @@ fn is_indexable_file(path: &str) -> bool {
         return true;
     }
 
-    // Must be .py or .pyi for Python source
-    if !path_lower.ends_with(".py") && !path_lower.ends_with(".pyi") {
+    // Must be source in a language we parse (extensions bring Rust, ...)
+    if !super::is_source_file(&path_lower) {
         return false;
     }
 
-    // Skip test files (but NOT examples - we want those!)
-    let skip_patterns = [
-        "test_",
-        "_test.py",
-        "tests/",
-        "test/",
-        "__pycache__/",
-        "conftest.py",
-    ];
-
-    if skip_patterns.iter().any(|p| path_lower.contains(p)) {
+    // Skip bytecode caches (but NOT examples or tests - we want those!)
+    if path_lower.contains("__pycache__/") {
         return false;
     }
 
     true
 }
 
+/// Check if a file belongs to the package's tests (`tests/`, `test_*.py`, ...).
+fn is_test_file(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    let file_name = path_lower.rsplit('/').next().unwrap_or(&path_lower);
+
+    path_lower.contains("tests/")
+        || path_lower.contains("test/")
+        || file_name.starts_with("test_")
+        || file_name.ends_with("_test.py")
+        || file_name == "conftest.py"
+}
+
 #[cfg(test)]
 mod tests {
+    use std::io::Write;
+
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
     use super::*;
 
     #[test]
@@ -286,6 +367,10 @@ mod tests {
         assert!(is_indexable_file("typing_extensions.pyi"));
         assert!(is_indexable_file("setup.py"));
 
+        // Sources of native extensions too
+        assert!(is_indexable_file("src/lib.rs"));
+        assert!(!is_indexable_file("pydantic_core/_pydantic_core.so"));
+
         // Examples should be included!
         assert!(is_indexable_file("examples/basic.py"));
         assert!(is_indexable_file("example/advanced.py"));
@@ -296,9 +381,122 @@ mod tests {
         assert!(is_indexable_file("docs/api.rst"));
         assert!(is_indexable_file("CHANGELOG.rst"));
 
-        // Tests still skipped
-        assert!(!is_indexable_file("tests/test_api.py"));
-        assert!(!is_indexable_file("test_requests.py"));
+        // Tests are returned, marked by is_test_file
+        assert!(is_indexable_file("tests/test_api.py"));
+        assert!(!is_indexable_file("requests/__pycache__/api.py"));
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("tests/test_api.py"));
+        assert!(is_test_file("test_requests.py"));
+        assert!(is_test_file("requests/api_test.py"));
+        assert!(is_test_file("conftest.py"));
+
+        assert!(!is_test_file("requests/api.py"));
+        assert!(!is_test_file("requests/latest_version.py"));
+        assert!(!is_test_file("examples/basic.py"));
+    }
+
+    #[test]
+    fn test_is_vendored_file() {
+        assert!(is_vendored_file("pip/_vendor/requests/api.py"));
+        assert!(is_vendored_file("src/pkg/vendored/six.py"));
+        assert!(is_vendored_file("pkg/vendor/lib.py"));
+        assert!(is_vendored_file("vendor/lib.py"));
+
+        assert!(!is_vendored_file("pkg/vendor.py"));
+        assert!(!is_vendored_file("pkg/vendors/api.py"));
+        assert!(!is_vendored_file("requests/api.py"));
+    }
+
+    fn wheel(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_extract_wheel_marks_vendored() {
+        let data = wheel(&[
+            ("pip/__init__.py", "__version__ = '24.0'\n"),
+            ("pip/_internal/cli.py", "def main(): pass\n"),
+            ("pip/_vendor/requests/api.py", "def get(url): pass\n"),
+            ("pip/_vendor/README.rst", "Vendored copies\n"),
+            ("_bundled_six.py", "PY3 = True\n"),
+            ("pip-24.0.dist-info/top_level.txt", "pip\n"),
+            ("pip-24.0.dist-info/licenses/LICENSE.md", "MIT\n"),
+        ]);
+
+        let files = extract_zip(&data).unwrap();
+        let kept: Vec<_> = files
+            .iter()
+            .filter(|f| !f.is_vendored)
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(kept, vec!["pip/__init__.py", "pip/_internal/cli.py"]);
+
+        let vendored: Vec<_> = files
+            .iter()
+            .filter(|f| f.is_vendored)
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(
+            vendored,
+            vec![
+                "pip/_vendor/requests/api.py",
+                "pip/_vendor/README.rst",
+                "_bundled_six.py",
+                "pip-24.0.dist-info/licenses/LICENSE.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_zip_skips_entries_outside_package() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        writer.start_file("../../evil.py", options).unwrap();
+        writer.write_all(b"import os\n").unwrap();
+        writer
+            .add_symlink("pkg/link.py", "/etc/passwd", options)
+            .unwrap();
+        writer.start_file("pkg/__init__.py", options).unwrap();
+        writer.write_all(b"VERSION = 1\n").unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let files = extract_zip(&data).unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["pkg/__init__.py"]);
+    }
+
+    #[test]
+    fn test_extract_wheel_single_module_top_level() {
+        let data = wheel(&[
+            ("typing_extensions.py", "class Protocol: pass\n"),
+            (
+                "typing_extensions-4.9.0.dist-info/top_level.txt",
+                "typing_extensions\n",
+            ),
+        ]);
+        let files = extract_zip(&data).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(!files[0].is_vendored);
+
+        // Without top_level.txt only vendored directories are marked
+        let data = wheel(&[
+            ("pkg/__init__.py", "\n"),
+            ("helpers/util.py", "\n"),
+            ("pkg/_vendor/attr.py", "\n"),
+        ]);
+        let files = extract_zip(&data).unwrap();
+        let vendored: Vec<_> = files.iter().map(|f| f.is_vendored).collect();
+        assert_eq!(vendored, vec![false, false, true]);
     }
 
     #[test]
@@ -0,0 +1,125 @@
+//! "Did you mean" suggestions for misspelled package names.
+
+/// Most suggestions offered for one name.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Candidates likely to be what a misspelled `name` meant, closest first.
+///
+/// Names are compared case-insensitively by edit distance, where swapping
+/// two adjacent characters counts as one edit (`loadsh` is one edit from
+/// `lodash`). Candidates more than a third of the name's length away are
+/// dropped. Ties keep the candidates' order, so pass registry search results
+/// first to prefer popular packages.
+pub fn rank_suggestions(name: &str, candidates: &[String]) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut ranked: Vec<(usize, &String)> = Vec::new();
+    for candidate in candidates {
+        let lower = candidate.to_lowercase();
+        if lower == name || ranked.iter().any(|(_, seen)| seen.to_lowercase() == lower) {
+            continue;
+        }
+        let distance = edit_distance(&name, &lower);
+        if distance <= max_distance {
+            ranked.push((distance, candidate));
+        }
+    }
+
+    ranked.sort_by_key(|(distance, _)| *distance);
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Edits (insertions, deletions, substitutions and adjacent swaps) turning
+/// `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // rows[i][j] = distance between a[..i] and b[..j]
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("lodash", "lodash"), 0);
+        assert_eq!(edit_distance("loadsh", "lodash"), 1);
+        assert_eq!(edit_distance("axois", "axios"), 1);
+        assert_eq!(edit_distance("reqests", "requests"), 1);
+        assert_eq!(edit_distance("serde", "serde_json"), 5);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_rank_suggestions_closest_first() {
+        let candidates = names(&["lodash-es", "ladash", "lodash", "underscore"]);
+
+        assert_eq!(
+            rank_suggestions("loadsh", &candidates),
+            vec!["lodash", "ladash"]
+        );
+    }
+
+    #[test]
+    fn test_rank_suggestions_keeps_order_on_ties() {
+        let candidates = names(&["expresso", "expressa", "express"]);
+
+        assert_eq!(
+            rank_suggestions("expres", &candidates),
+            vec!["express", "expresso", "expressa"]
+        );
+    }
+
+    #[test]
+    fn test_rank_suggestions_limits_and_dedupes() {
+        let candidates = names(&[
+            "Requests", "requests", "request", "requets2", "requires", "quests",
+        ]);
+
+        assert_eq!(
+            rank_suggestions("requets", &candidates),
+            vec!["Requests", "request", "requets2"]
+        );
+    }
+
+    #[test]
+    fn test_rank_suggestions_skips_exact_and_distant_names() {
+        let candidates = names(&["requests", "httpx", "urllib3"]);
+
+        assert!(rank_suggestions("requests", &candidates).is_empty());
+        assert!(rank_suggestions("flask", &candidates).is_empty());
+    }
+}
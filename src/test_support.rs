@@ -0,0 +1,63 @@
+//! Stub servers shared by tests.
+
+use std::sync::{Arc, Mutex};
+
+/// Serve `app` on a free local port. Returns the base URL.
+pub async fn serve(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// A local `/v1/embeddings` API (see [`stub_embeddings`]).
+pub struct StubEmbeddings {
+    /// Base URL, for `openai_base_url`
+    pub url: String,
+    sent: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubEmbeddings {
+    /// Texts asked to be embedded so far, in the order received.
+    pub fn sent(&self) -> Vec<String> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// How many texts were asked to be embedded so far.
+    pub fn embedded(&self) -> usize {
+        self.sent.lock().unwrap().len()
+    }
+}
+
+/// Serve `/v1/embeddings` locally, answering every input with `vector` and
+/// recording the texts sent.
+pub async fn stub_embeddings(vector: Vec<f32>) -> StubEmbeddings {
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&sent);
+    let app = axum::Router::new().route(
+        "/v1/embeddings",
+        axum::routing::post(
+            move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                // Indexing sends a batch, a search query a single string
+                let inputs: Vec<String> = match &body["input"] {
+                    serde_json::Value::Array(inputs) => inputs
+                        .iter()
+                        .map(|input| input.as_str().unwrap_or_default().to_string())
+                        .collect(),
+                    input => vec![input.as_str().unwrap_or_default().to_string()],
+                };
+                let data: Vec<_> = inputs
+                    .iter()
+                    .map(|_| serde_json::json!({ "embedding": vector }))
+                    .collect();
+                recorded.lock().unwrap().extend(inputs);
+                axum::Json(serde_json::json!({ "data": data }))
+            },
+        ),
+    );
+
+    StubEmbeddings {
+        url: serve(app).await,
+        sent,
+    }
+}
@@ -47,10 +47,23 @@ impl std::str::FromStr for SearchScope {
 /// - Pypi → Python
 /// - Crates → Rust
 /// - Go → Go
-/// - Maven → Java/Kotlin
+/// - Maven → Java/Kotlin/Scala
+/// - Pub → Dart
 ///
 /// Users can filter by registry at query time - this is NOT a tenant-level setting.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Registry {
     Npm,
@@ -58,6 +71,9 @@ pub enum Registry {
     Crates,
     Go,
     Maven,
+    Pub,
+    /// Git repositories indexed directly, named `host/owner/repo`
+    Git,
 }
 
 impl Registry {
@@ -68,6 +84,8 @@ impl Registry {
             Registry::Crates => "crates",
             Registry::Go => "go",
             Registry::Maven => "maven",
+            Registry::Pub => "pub",
+            Registry::Git => "git",
         }
     }
 
@@ -112,6 +130,8 @@ impl std::str::FromStr for Registry {
             "crates" => Ok(Registry::Crates),
             "go" => Ok(Registry::Go),
             "maven" => Ok(Registry::Maven),
+            "pub" => Ok(Registry::Pub),
+            "git" => Ok(Registry::Git),
             _ => Err(format!("unknown registry: {}", s)),
         }
     }
@@ -128,6 +148,8 @@ pub enum Language {
     Go,
     Java,
     Kotlin,
+    Scala,
+    Dart,
     #[default]
     Unknown,
 }
@@ -207,6 +229,8 @@ mod tests {
         assert_eq!("crates".parse::<Registry>().unwrap(), Registry::Crates);
         assert_eq!("go".parse::<Registry>().unwrap(), Registry::Go);
         assert_eq!("maven".parse::<Registry>().unwrap(), Registry::Maven);
+        assert_eq!("pub".parse::<Registry>().unwrap(), Registry::Pub);
+        assert_eq!("git".parse::<Registry>().unwrap(), Registry::Git);
     }
 
     #[test]
@@ -230,6 +254,8 @@ mod tests {
             Registry::Crates,
             Registry::Go,
             Registry::Maven,
+            Registry::Pub,
+            Registry::Git,
         ] {
             let s = registry.as_str();
             let parsed: Registry = s.parse().unwrap();
@@ -251,6 +277,10 @@ mod tests {
             Registry::Crates.public_package_namespace("serde"),
             "public/crates/serde"
         );
+        assert_eq!(
+            Registry::Pub.public_package_namespace("provider"),
+            "public/pub/provider"
+        );
     }
 
     #[test]